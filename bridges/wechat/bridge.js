@@ -8,6 +8,10 @@
 const { WechatyBuilder } = require('wechaty');
 const QRCode = require('qrcode');
 const { Protocol } = require('./protocol');
+const { version: BRIDGE_VERSION } = require('./package.json');
+
+/** Features this bridge supports, announced in the hello handshake */
+const CAPABILITIES = ['qrcode', 'contacts'];
 
 class WeChatBridge {
   constructor(config) {
@@ -15,9 +19,11 @@ class WeChatBridge {
     this.protocol = new Protocol();
     this.bot = null;
     this._heartbeatInterval = null;
+    this.negotiatedVersion = null;
   }
 
   async start() {
+    this.protocol.sendHello(CAPABILITIES, BRIDGE_VERSION);
     this.protocol.sendStatus('starting');
 
     try {
@@ -135,17 +141,34 @@ class WeChatBridge {
   }
 
   _setupCommandHandlers() {
+    // Handshake acknowledgement: the backend accepted our protocol version
+    this.protocol.onCommand('hello_ack', (cmd) => {
+      this.negotiatedVersion = cmd.protocol_version;
+    });
+
     // Handle send_message command from Rust
+    // Tracked sends (with ref_id) are acknowledged via message_status;
+    // untracked ones report failures as errors
     this.protocol.onCommand('send_message', async (cmd) => {
+      const fail = (reason) => {
+        if (cmd.ref_id) {
+          this.protocol.sendMessageStatus(cmd.ref_id, 'failed', reason);
+        } else {
+          this.protocol.sendError(reason);
+        }
+      };
       try {
         const contact = await this.bot.Contact.find({ id: cmd.to_id });
         if (contact) {
           await contact.say(cmd.content);
+          if (cmd.ref_id) {
+            this.protocol.sendMessageStatus(cmd.ref_id, 'sent');
+          }
         } else {
-          this.protocol.sendError(`Contact not found: ${cmd.to_id}`);
+          fail(`Contact not found: ${cmd.to_id}`);
         }
       } catch (error) {
-        this.protocol.sendError(`Failed to send message: ${error.message}`);
+        fail(`Failed to send message: ${error.message}`);
       }
     });
 
@@ -5,6 +5,9 @@
 
 const readline = require('readline');
 
+/** NDJSON protocol version implemented by this bridge */
+const PROTOCOL_VERSION = 1;
+
 class Protocol {
   constructor() {
     this._handlers = new Map();
@@ -53,6 +56,16 @@ class Protocol {
 
   // Convenience methods for sending specific event types
 
+  /** Announce protocol version and capabilities; must be the first event sent */
+  sendHello(capabilities, bridgeVersion) {
+    this.send({
+      type: 'hello',
+      protocol_version: PROTOCOL_VERSION,
+      capabilities,
+      bridge_version: bridgeVersion || null,
+    });
+  }
+
   sendStatus(status) {
     this.send({ type: 'status', status });
   }
@@ -96,6 +109,11 @@ class Protocol {
     this.send({ type: 'pong', ts });
   }
 
+  /** Delivery acknowledgement for a send_message that carried a ref_id */
+  sendMessageStatus(refId, status, error) {
+    this.send({ type: 'message_status', ref_id: refId, status, error: error || null });
+  }
+
   /** Stop listening and close the readline interface */
   close() {
     if (this._rl) {
@@ -105,4 +123,4 @@ class Protocol {
   }
 }
 
-module.exports = { Protocol };
+module.exports = { Protocol, PROTOCOL_VERSION };
@@ -0,0 +1,16 @@
+-- Agent context carry-over: reuse an agent's ACP session (or a summary of it)
+-- across orchestration runs in the same workspace
+ALTER TABLE agents ADD COLUMN carry_over_context INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS agent_contexts (
+    agent_id TEXT NOT NULL,
+    workspace_id TEXT NOT NULL DEFAULT '',
+    acp_session_id TEXT DEFAULT NULL,
+    summary TEXT NOT NULL DEFAULT '',
+    run_count INTEGER NOT NULL DEFAULT 0,
+    last_task_run_id TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (agent_id, workspace_id),
+    FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+);
@@ -0,0 +1,31 @@
+-- Pipelines: ordered lists of orchestration stages where each stage's summary
+-- feeds the next stage's prompt
+CREATE TABLE IF NOT EXISTS pipelines (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    stages_json TEXT NOT NULL DEFAULT '[]',
+    workspace_id TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_pipelines_workspace ON pipelines(workspace_id);
+
+-- Pipeline runs: one execution of a pipeline, tracking the task run of each stage
+CREATE TABLE IF NOT EXISTS pipeline_runs (
+    id TEXT PRIMARY KEY,
+    pipeline_id TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK(status IN ('pending', 'running', 'awaiting_approval', 'completed', 'failed', 'cancelled')),
+    current_stage INTEGER NOT NULL DEFAULT 0,
+    initial_input TEXT NOT NULL DEFAULT '',
+    stage_task_run_ids_json TEXT NOT NULL DEFAULT '[]',
+    error_message TEXT DEFAULT NULL,
+    workspace_id TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (pipeline_id) REFERENCES pipelines(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_pipeline_runs_pipeline ON pipeline_runs(pipeline_id);
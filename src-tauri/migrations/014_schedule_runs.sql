@@ -0,0 +1,20 @@
+-- Schedule runs: one row per fired (or skipped) occurrence of a scheduled task
+CREATE TABLE IF NOT EXISTS schedule_runs (
+    id TEXT PRIMARY KEY,
+    -- The scheduled task run that owns the schedule
+    schedule_task_run_id TEXT NOT NULL,
+    -- The task run that executed this occurrence (NULL when skipped)
+    task_run_id TEXT DEFAULT NULL,
+    -- The next_run_at value this occurrence was due at
+    scheduled_for TEXT DEFAULT NULL,
+    fired_at TEXT NOT NULL DEFAULT (datetime('now')),
+    finished_at TEXT DEFAULT NULL,
+    outcome TEXT NOT NULL DEFAULT 'running'
+        CHECK(outcome IN ('running', 'completed', 'failed', 'cancelled', 'skipped')),
+    skipped_reason TEXT DEFAULT NULL,
+    error_message TEXT DEFAULT NULL,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (schedule_task_run_id) REFERENCES task_runs(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_schedule_runs_schedule ON schedule_runs(schedule_task_run_id, fired_at);
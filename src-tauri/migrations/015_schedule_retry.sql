@@ -0,0 +1,7 @@
+-- Migration 015: Retry and failure notification policy for scheduled tasks
+
+-- JSON: {"max_retries":2,"backoff_secs":60,"notify_on_failure":[{"type":"desktop"}]}
+ALTER TABLE task_runs ADD COLUMN failure_policy TEXT DEFAULT NULL;
+
+-- 1 for the scheduled firing, 2+ for retries of the same occurrence
+ALTER TABLE schedule_runs ADD COLUMN attempt INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,2 @@
+-- Migration 016: Per-assignment working directory (relative to the workspace root)
+ALTER TABLE task_assignments ADD COLUMN working_directory TEXT DEFAULT NULL;
@@ -0,0 +1,17 @@
+-- Orchestration templates: validated plans frozen from successful runs so
+-- future runs can skip planning
+CREATE TABLE IF NOT EXISTS orchestration_templates (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    source_task_run_id TEXT DEFAULT NULL,
+    prompt_template TEXT NOT NULL DEFAULT '',
+    analysis TEXT NOT NULL DEFAULT '',
+    assignments_json TEXT NOT NULL DEFAULT '[]',
+    variables_json TEXT NOT NULL DEFAULT '[]',
+    workspace_id TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_orchestration_templates_workspace ON orchestration_templates(workspace_id);
@@ -0,0 +1,14 @@
+-- Assignment events: buffered streaming output, thoughts and tool calls of
+-- each agent prompt, kept so runs can be inspected step by step afterwards
+CREATE TABLE IF NOT EXISTS assignment_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_run_id TEXT NOT NULL REFERENCES task_runs(id) ON DELETE CASCADE,
+    agent_id TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK(kind IN ('text', 'thought', 'tool_call')),
+    content TEXT NOT NULL DEFAULT '',
+    tool_call_id TEXT DEFAULT NULL,
+    tool_status TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f', 'now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_assignment_events_agent ON assignment_events(task_run_id, agent_id, created_at);
@@ -0,0 +1,3 @@
+-- Per-chat-tool environment profile (proxy, data dir, locale, extra variables)
+-- applied when spawning the bridge process
+ALTER TABLE chat_tools ADD COLUMN env_json TEXT NOT NULL DEFAULT '{}';
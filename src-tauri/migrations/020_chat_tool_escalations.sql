@@ -0,0 +1,21 @@
+-- Escalation of chat tool messages to a human: the owner contact that receives
+-- them, the Control Hub confidence below which a reply is escalated, and a log
+-- of escalated messages
+ALTER TABLE chat_tools ADD COLUMN owner_contact_id TEXT DEFAULT NULL;
+ALTER TABLE chat_tools ADD COLUMN escalation_threshold REAL DEFAULT NULL;
+
+CREATE TABLE IF NOT EXISTS chat_tool_escalations (
+    id TEXT PRIMARY KEY,
+    chat_tool_id TEXT NOT NULL,
+    external_sender_id TEXT DEFAULT NULL,
+    external_sender_name TEXT DEFAULT NULL,
+    original_content TEXT NOT NULL DEFAULT '',
+    hub_reply TEXT NOT NULL DEFAULT '',
+    reason TEXT NOT NULL DEFAULT '',
+    confidence REAL DEFAULT NULL,
+    owner_contact_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (chat_tool_id) REFERENCES chat_tools(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_chat_tool_escalations_tool ON chat_tool_escalations(chat_tool_id, created_at);
@@ -0,0 +1,13 @@
+-- Sync bookkeeping: the vector clock and content hash of every replicated
+-- record (agents, workspaces, templates, schedules), plus tombstones for
+-- records deleted locally
+CREATE TABLE IF NOT EXISTS sync_records (
+    entity TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    clock_json TEXT NOT NULL DEFAULT '{}',
+    content_hash TEXT NOT NULL DEFAULT '',
+    deleted INTEGER NOT NULL DEFAULT 0,
+    modified_at TEXT NOT NULL DEFAULT '',
+    modified_by TEXT NOT NULL DEFAULT '',
+    PRIMARY KEY (entity, entity_id)
+);
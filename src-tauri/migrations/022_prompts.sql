@@ -0,0 +1,22 @@
+-- Prompt library: reusable prompt snippets. Every content change adds a
+-- version; the prompt row points at its current version.
+CREATE TABLE IF NOT EXISTS prompts (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    tags_json TEXT NOT NULL DEFAULT '[]',
+    current_version INTEGER NOT NULL DEFAULT 1,
+    workspace_id TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS prompt_versions (
+    prompt_id TEXT NOT NULL REFERENCES prompts(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (prompt_id, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_prompts_workspace ON prompts(workspace_id);
@@ -0,0 +1,12 @@
+-- Opt-in cache of agent responses for identical assignment inputs
+CREATE TABLE IF NOT EXISTS response_cache (
+    agent_id TEXT NOT NULL,
+    model TEXT NOT NULL,
+    input_hash TEXT NOT NULL,
+    output_text TEXT NOT NULL,
+    source_assignment_id TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (agent_id, model, input_hash)
+);
+
+ALTER TABLE task_assignments ADD COLUMN cached INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,13 @@
+-- Files produced by task runs, stored under the run's output directory
+CREATE TABLE IF NOT EXISTS task_artifacts (
+    id TEXT PRIMARY KEY,
+    task_run_id TEXT NOT NULL REFERENCES task_runs(id) ON DELETE CASCADE,
+    assignment_id TEXT DEFAULT NULL,
+    agent_id TEXT DEFAULT NULL,
+    kind TEXT NOT NULL,
+    path TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_artifacts_run ON task_artifacts(task_run_id);
@@ -0,0 +1,16 @@
+-- Long-term memory: summaries of past task runs and chat conversations with
+-- their embeddings, retrieved into planning prompts
+CREATE TABLE IF NOT EXISTS memories (
+    id TEXT PRIMARY KEY,
+    workspace_id TEXT DEFAULT NULL,
+    source_kind TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    title TEXT NOT NULL DEFAULT '',
+    content TEXT NOT NULL,
+    embedding BLOB NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (source_kind, source_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_memories_workspace ON memories(workspace_id);
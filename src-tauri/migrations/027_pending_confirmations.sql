@@ -0,0 +1,7 @@
+-- When a run waiting for confirmation times out; kept across restarts so a
+-- resumed run does not get a fresh timeout
+CREATE TABLE IF NOT EXISTS pending_confirmations (
+    task_run_id TEXT PRIMARY KEY REFERENCES task_runs(id) ON DELETE CASCADE,
+    deadline_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
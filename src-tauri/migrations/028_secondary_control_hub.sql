@@ -0,0 +1,5 @@
+-- Fallback Control Hub per workspace, used when the primary fails
+ALTER TABLE agents ADD COLUMN is_secondary_hub INTEGER NOT NULL DEFAULT 0;
+
+-- The hub that actually planned and summarized the run
+ALTER TABLE task_runs ADD COLUMN served_by_hub_agent_id TEXT DEFAULT NULL;
@@ -0,0 +1,11 @@
+-- High-level events per workspace, shown as the activity feed
+CREATE TABLE IF NOT EXISTS activity_log (
+    id TEXT PRIMARY KEY,
+    workspace_id TEXT,
+    kind TEXT NOT NULL,
+    subject_id TEXT,
+    summary TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_log_workspace ON activity_log(workspace_id, created_at);
@@ -0,0 +1,3 @@
+-- Why the spam filter held back an incoming message; filtered messages are
+-- never forwarded to the Control Hub
+ALTER TABLE chat_tool_messages ADD COLUMN filter_reason TEXT;
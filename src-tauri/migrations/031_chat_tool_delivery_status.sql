@@ -0,0 +1,5 @@
+-- Delivery tracking for outgoing chat tool messages, driven by the bridge's
+-- message_status acknowledgements. NULL for incoming and untracked messages.
+ALTER TABLE chat_tool_messages ADD COLUMN delivery_status TEXT;
+ALTER TABLE chat_tool_messages ADD COLUMN delivery_error TEXT;
+ALTER TABLE chat_tool_messages ADD COLUMN delivery_attempts INTEGER NOT NULL DEFAULT 0;
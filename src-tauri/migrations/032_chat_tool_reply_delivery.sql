@@ -0,0 +1,5 @@
+-- How a chat tool delivers Control Hub replies: a typing indicator while the
+-- reply is generated (on bridges that support it) and an optional size above
+-- which long replies are split into several messages (NULL sends one message)
+ALTER TABLE chat_tools ADD COLUMN typing_indicator INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE chat_tools ADD COLUMN reply_chunk_chars INTEGER;
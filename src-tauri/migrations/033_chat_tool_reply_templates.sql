@@ -0,0 +1,3 @@
+-- Canned replies per chat tool (greeting, office hours, busy, hub unavailable);
+-- '{}' uses the defaults
+ALTER TABLE chat_tools ADD COLUMN reply_templates_json TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,8 @@
+-- Daily auto-reply quotas per chat tool contact (NULL = unlimited) and the
+-- counters they are checked against; counters belong to usage_date (local
+-- date) and restart on the next day
+ALTER TABLE chat_tool_contacts ADD COLUMN max_replies_per_day INTEGER DEFAULT NULL;
+ALTER TABLE chat_tool_contacts ADD COLUMN max_tokens_per_day INTEGER DEFAULT NULL;
+ALTER TABLE chat_tool_contacts ADD COLUMN replies_today INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE chat_tool_contacts ADD COLUMN tokens_today INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE chat_tool_contacts ADD COLUMN usage_date TEXT DEFAULT NULL;
@@ -0,0 +1,3 @@
+-- Prompt an agent runs right after it is spawned for a run (e.g. reading
+-- AGENTS.md); its output is prepended to the agent's first assignment
+ALTER TABLE agents ADD COLUMN warmup_prompt TEXT DEFAULT NULL;
@@ -0,0 +1,3 @@
+-- Agents managed by a workspace's agents.yaml: the manifest entry's name.
+-- NULL for agents created in the app
+ALTER TABLE agents ADD COLUMN manifest_name TEXT DEFAULT NULL;
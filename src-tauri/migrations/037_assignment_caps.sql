@@ -0,0 +1,3 @@
+-- Per-assignment caps on output tokens and cost (USD); NULL for no cap
+ALTER TABLE task_assignments ADD COLUMN max_tokens_out INTEGER DEFAULT NULL;
+ALTER TABLE task_assignments ADD COLUMN max_cost REAL DEFAULT NULL;
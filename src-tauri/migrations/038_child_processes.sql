@@ -0,0 +1,13 @@
+-- Agent and bridge processes the app spawned, so ones left behind by a crash
+-- can be found and stopped. started_at is the OS start time of the process;
+-- together with the pid it tells a survivor from a process that reused its pid
+CREATE TABLE IF NOT EXISTS child_processes (
+    pid INTEGER NOT NULL,
+    started_at TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    owner_id TEXT NOT NULL,
+    command TEXT NOT NULL,
+    app_pid INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (pid, started_at)
+);
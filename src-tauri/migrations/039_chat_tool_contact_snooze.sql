@@ -0,0 +1,4 @@
+-- Auto-replies to a contact are suspended until snoozed_until (UTC,
+-- datetime('now') format), e.g. while a human answers them; NULL or a past
+-- time means auto-replies are on
+ALTER TABLE chat_tool_contacts ADD COLUMN snoozed_until TEXT DEFAULT NULL;
@@ -0,0 +1,3 @@
+-- A human operator took over the conversation with a contact at
+-- takeover_started_at (UTC); auto-replies stay off until it ends
+ALTER TABLE chat_tool_contacts ADD COLUMN takeover_started_at TEXT DEFAULT NULL;
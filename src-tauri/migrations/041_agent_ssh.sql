@@ -0,0 +1,5 @@
+-- Run the agent's ACP command on another machine over SSH instead of
+-- locally; ssh_user and ssh_key_path fall back to the SSH config
+ALTER TABLE agents ADD COLUMN ssh_host TEXT DEFAULT NULL;
+ALTER TABLE agents ADD COLUMN ssh_user TEXT DEFAULT NULL;
+ALTER TABLE agents ADD COLUMN ssh_key_path TEXT DEFAULT NULL;
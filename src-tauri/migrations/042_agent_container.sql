@@ -0,0 +1,4 @@
+-- Run the agent's ACP command in a Docker container of this image; the
+-- workspace is mounted read_only or read_write
+ALTER TABLE agents ADD COLUMN container_image TEXT DEFAULT NULL;
+ALTER TABLE agents ADD COLUMN container_workspace_access TEXT NOT NULL DEFAULT 'read_only';
@@ -0,0 +1,11 @@
+-- Labels on task runs, and an archive flag that hides a run from the
+-- default task history listing
+CREATE TABLE IF NOT EXISTS task_run_labels (
+    task_run_id TEXT NOT NULL REFERENCES task_runs(id) ON DELETE CASCADE,
+    label TEXT NOT NULL,
+    PRIMARY KEY (task_run_id, label)
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_run_labels_label ON task_run_labels(label);
+
+ALTER TABLE task_runs ADD COLUMN archived_at TEXT DEFAULT NULL;
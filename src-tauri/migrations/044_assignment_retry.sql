@@ -0,0 +1,2 @@
+-- Assignment a retry re-executed; the retry is a new assignment of the same run
+ALTER TABLE task_assignments ADD COLUMN retry_of_assignment_id TEXT;
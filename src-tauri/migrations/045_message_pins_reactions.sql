@@ -0,0 +1,9 @@
+-- Pinned chat messages and reactions on them
+ALTER TABLE messages ADD COLUMN pinned_at TEXT DEFAULT NULL;
+
+CREATE TABLE IF NOT EXISTS message_reactions (
+    message_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    reaction TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (message_id, reaction)
+);
@@ -0,0 +1,9 @@
+-- Skill discovery results per workspace, kept across restarts.
+-- workspace_key is the workspace id, or '' for runs without a workspace.
+CREATE TABLE IF NOT EXISTS skill_cache (
+    workspace_key TEXT PRIMARY KEY,
+    working_directory TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    result_json TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,6 @@
+-- Rolling summary of a chat session, used to seed a new ACP session when the
+-- agent no longer has the old one
+ALTER TABLE sessions ADD COLUMN summary TEXT DEFAULT NULL;
+-- Messages of the session the summary covers, oldest first
+ALTER TABLE sessions ADD COLUMN summary_message_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE sessions ADD COLUMN summary_updated_at TEXT DEFAULT NULL;
@@ -0,0 +1,4 @@
+-- Set when an agent was auto-disabled by an error that is likely to pass
+-- (timeout, lost connection, overloaded provider), so a run finding every
+-- agent disabled can re-enable it
+ALTER TABLE agents ADD COLUMN disabled_transient INTEGER NOT NULL DEFAULT 0;
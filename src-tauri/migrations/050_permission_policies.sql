@@ -0,0 +1,11 @@
+-- Permission decisions remembered for an agent: requests with the same key
+-- (tool kind, and the program for commands) are answered without asking
+CREATE TABLE IF NOT EXISTS permission_policies (
+    id TEXT PRIMARY KEY,
+    agent_id TEXT NOT NULL,
+    request_key TEXT NOT NULL,
+    allow INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (agent_id, request_key),
+    FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+);
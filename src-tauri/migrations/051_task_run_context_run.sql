@@ -0,0 +1,2 @@
+-- Earlier run whose summary and outputs were attached to a run's planning prompt
+ALTER TABLE task_runs ADD COLUMN context_run_id TEXT REFERENCES task_runs(id) ON DELETE SET NULL;
@@ -0,0 +1,3 @@
+-- Groups a contact was put in, e.g. by an imported customer list.
+-- JSON array of group names; NULL when the contact is in none.
+ALTER TABLE chat_tool_contacts ADD COLUMN groups_json TEXT DEFAULT NULL;
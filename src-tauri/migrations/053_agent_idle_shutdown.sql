@@ -0,0 +1,3 @@
+-- Minutes without prompts after which the agent's chat process is stopped;
+-- NULL follows the app-wide agent_idle_shutdown_minutes, 0 keeps it running
+ALTER TABLE agents ADD COLUMN idle_shutdown_minutes INTEGER DEFAULT NULL;
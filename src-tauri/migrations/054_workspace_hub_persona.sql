@@ -0,0 +1,3 @@
+-- Preamble put before the Control Hub's planning, feedback and summary
+-- prompts of the workspace's runs; NULL for none
+ALTER TABLE workspaces ADD COLUMN hub_persona TEXT DEFAULT NULL;
@@ -0,0 +1,2 @@
+-- Mask secrets in the agent outputs of the workspace's runs and chats
+ALTER TABLE workspaces ADD COLUMN redact_outputs INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,2 @@
+-- Run at most one orchestration at a time in the workspace
+ALTER TABLE workspaces ADD COLUMN exclusive_runs INTEGER NOT NULL DEFAULT 0;
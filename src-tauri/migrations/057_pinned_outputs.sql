@@ -0,0 +1,13 @@
+-- Assignment outputs and A2A results the user pinned to a run's clipboard
+CREATE TABLE IF NOT EXISTS pinned_outputs (
+    id TEXT PRIMARY KEY,
+    task_run_id TEXT NOT NULL REFERENCES task_runs(id) ON DELETE CASCADE,
+    source TEXT NOT NULL,
+    assignment_id TEXT DEFAULT NULL,
+    agent_id TEXT DEFAULT NULL,
+    label TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_pinned_outputs_run ON pinned_outputs(task_run_id);
@@ -0,0 +1,2 @@
+-- Agent settings an assignment ran with instead of the agent's own (JSON)
+ALTER TABLE task_assignments ADD COLUMN overrides_json TEXT;
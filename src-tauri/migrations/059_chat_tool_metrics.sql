@@ -0,0 +1,17 @@
+-- How each batch of a chat tool's inbound messages was handled: messages
+-- waiting when it was picked up, Control Hub latency and the time from
+-- receipt to reply, for responsiveness metrics
+CREATE TABLE IF NOT EXISTS chat_tool_metrics (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    chat_tool_id TEXT NOT NULL,
+    batch_size INTEGER NOT NULL,
+    queue_depth INTEGER NOT NULL,
+    hub_latency_ms INTEGER DEFAULT NULL,
+    response_ms_total INTEGER DEFAULT NULL,
+    response_ms_max INTEGER DEFAULT NULL,
+    outcome TEXT NOT NULL,
+    recorded_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (chat_tool_id) REFERENCES chat_tools(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_chat_tool_metrics_tool ON chat_tool_metrics(chat_tool_id, recorded_at);
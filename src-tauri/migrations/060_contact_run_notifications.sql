@@ -0,0 +1,2 @@
+-- Run status DMs a contact subscribed to, as JSON; NULL means none
+ALTER TABLE chat_tool_contacts ADD COLUMN run_notifications_json TEXT DEFAULT NULL;
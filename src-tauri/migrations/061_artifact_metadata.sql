@@ -0,0 +1,2 @@
+-- Preview metadata of an artifact (MIME type, image dimensions), as JSON
+ALTER TABLE task_artifacts ADD COLUMN metadata_json TEXT DEFAULT NULL;
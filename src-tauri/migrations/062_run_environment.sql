@@ -0,0 +1,3 @@
+-- What a task run ran with (app, agent and registry versions, models,
+-- settings), as JSON; NULL for runs started before snapshots were kept
+ALTER TABLE task_runs ADD COLUMN environment_json TEXT DEFAULT NULL;
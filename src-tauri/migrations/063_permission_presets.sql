@@ -0,0 +1,4 @@
+-- Permission preset ("strict", "balanced" or "yolo") answering an agent's
+-- requests in task runs, and the preset chosen when a run was started
+ALTER TABLE agents ADD COLUMN permission_preset TEXT DEFAULT NULL;
+ALTER TABLE task_runs ADD COLUMN permission_preset TEXT DEFAULT NULL;
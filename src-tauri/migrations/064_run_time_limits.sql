@@ -0,0 +1,4 @@
+-- Wall-clock limit a task run was started with, in minutes (NULL follows
+-- the max_run_minutes setting), and whether the run was wound down at it
+ALTER TABLE task_runs ADD COLUMN max_duration_minutes INTEGER DEFAULT NULL;
+ALTER TABLE task_runs ADD COLUMN timed_out INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Why a task run or an assignment was cancelled: user_cancelled,
+-- too_expensive or wrong_direction (NULL for runs cancelled before reasons)
+ALTER TABLE task_runs ADD COLUMN cancel_reason TEXT DEFAULT NULL;
+ALTER TABLE task_assignments ADD COLUMN cancel_reason TEXT DEFAULT NULL;
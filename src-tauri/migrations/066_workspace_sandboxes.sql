@@ -0,0 +1,4 @@
+-- Sandbox workspaces mirror another workspace on a copy or git worktree of
+-- its working directory; sandbox_of is the workspace they were cloned from
+ALTER TABLE workspaces ADD COLUMN sandbox_of TEXT DEFAULT NULL;
+ALTER TABLE workspaces ADD COLUMN sandbox_mode TEXT DEFAULT NULL;
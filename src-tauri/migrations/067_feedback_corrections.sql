@@ -0,0 +1,3 @@
+-- Corrective actions the control hub's feedback has used in a run, so a
+-- resumed run keeps counting against the same limit
+ALTER TABLE task_runs ADD COLUMN feedback_corrections INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- The assignment each timeline event was recorded for (NULL for prompts
+-- outside an assignment and for events recorded before this column)
+ALTER TABLE assignment_events ADD COLUMN assignment_id TEXT DEFAULT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_assignment_events_assignment ON assignment_events(assignment_id);
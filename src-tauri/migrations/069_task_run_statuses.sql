@@ -0,0 +1,79 @@
+-- Run statuses and the hub reference the status CHECK and foreign keys did
+-- not allow:
+-- - 'needs_review': runs whose confirmation is dismissed are left for review.
+-- - 'awaiting_plan_approval': runs whose plan waits for the user's approval.
+-- - 'waiting_for_workspace': queued runs held back by their workspace's run
+--   lock.
+-- - Runs planned without a Control Hub record the built-in planner
+--   ('builtin-planner') as their hub, which has no agents row, so
+--   control_hub_agent_id no longer references agents. Like
+--   served_by_hub_agent_id it names the hub the run was started with.
+-- SQLite cannot alter a CHECK or a foreign key, so the table is rebuilt.
+-- Foreign keys are off meanwhile so dropping the old table does not cascade
+-- into the tables that reference it.
+PRAGMA foreign_keys=OFF;
+
+CREATE TABLE task_runs_new (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL DEFAULT '',
+    user_prompt TEXT NOT NULL,
+    control_hub_agent_id TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK(status IN ('pending','waiting_for_workspace','analyzing','running','awaiting_confirmation','awaiting_plan_approval','completed','failed','cancelled','needs_review')),
+    task_plan_json TEXT,
+    result_summary TEXT,
+    total_tokens_in INTEGER NOT NULL DEFAULT 0,
+    total_tokens_out INTEGER NOT NULL DEFAULT 0,
+    total_duration_ms INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    total_cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+    total_cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+    rating INTEGER DEFAULT NULL,
+    schedule_type TEXT NOT NULL DEFAULT 'none'
+        CHECK(schedule_type IN ('none', 'once', 'recurring')),
+    scheduled_time TEXT,
+    recurrence_pattern TEXT,
+    next_run_at TEXT,
+    is_paused INTEGER NOT NULL DEFAULT 0,
+    workspace_id TEXT DEFAULT NULL,
+    failure_policy TEXT DEFAULT NULL,
+    served_by_hub_agent_id TEXT DEFAULT NULL,
+    archived_at TEXT DEFAULT NULL,
+    context_run_id TEXT REFERENCES task_runs(id) ON DELETE SET NULL,
+    environment_json TEXT DEFAULT NULL,
+    permission_preset TEXT DEFAULT NULL,
+    max_duration_minutes INTEGER DEFAULT NULL,
+    timed_out INTEGER NOT NULL DEFAULT 0,
+    cancel_reason TEXT DEFAULT NULL,
+    feedback_corrections INTEGER NOT NULL DEFAULT 0
+);
+INSERT INTO task_runs_new (
+    id, title, user_prompt, control_hub_agent_id, status, task_plan_json,
+    result_summary, total_tokens_in, total_tokens_out, total_duration_ms,
+    created_at, updated_at, total_cache_creation_tokens,
+    total_cache_read_tokens, rating, schedule_type, scheduled_time,
+    recurrence_pattern, next_run_at, is_paused, workspace_id, failure_policy,
+    served_by_hub_agent_id, archived_at, context_run_id, environment_json,
+    permission_preset, max_duration_minutes, timed_out, cancel_reason,
+    feedback_corrections
+)
+SELECT
+    id, title, user_prompt, control_hub_agent_id, status, task_plan_json,
+    result_summary, total_tokens_in, total_tokens_out, total_duration_ms,
+    created_at, updated_at, total_cache_creation_tokens,
+    total_cache_read_tokens, rating, schedule_type, scheduled_time,
+    recurrence_pattern, next_run_at, is_paused, workspace_id, failure_policy,
+    served_by_hub_agent_id, archived_at, context_run_id, environment_json,
+    permission_preset, max_duration_minutes, timed_out, cancel_reason,
+    feedback_corrections
+FROM task_runs;
+DROP TABLE task_runs;
+ALTER TABLE task_runs_new RENAME TO task_runs;
+
+CREATE INDEX IF NOT EXISTS idx_task_runs_rating ON task_runs(rating);
+CREATE INDEX IF NOT EXISTS idx_task_runs_scheduled ON task_runs(next_run_at)
+    WHERE schedule_type != 'none' AND is_paused = 0;
+CREATE INDEX IF NOT EXISTS idx_task_runs_workspace ON task_runs(workspace_id);
+
+PRAGMA foreign_keys=ON;
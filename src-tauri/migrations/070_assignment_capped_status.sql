@@ -0,0 +1,54 @@
+-- Assignments stopped at their token or cost cap end as 'capped', which the
+-- status CHECK did not allow.
+-- SQLite cannot alter a CHECK, so the table is rebuilt. Foreign keys are off
+-- meanwhile so dropping the old table does not cascade.
+PRAGMA foreign_keys=OFF;
+
+CREATE TABLE task_assignments_new (
+    id TEXT PRIMARY KEY,
+    task_run_id TEXT NOT NULL REFERENCES task_runs(id) ON DELETE CASCADE,
+    agent_id TEXT NOT NULL REFERENCES agents(id),
+    agent_name TEXT NOT NULL DEFAULT '',
+    sequence_order INTEGER NOT NULL DEFAULT 0,
+    input_text TEXT NOT NULL DEFAULT '',
+    output_text TEXT,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK(status IN ('pending','running','completed','failed','skipped','cancelled','capped')),
+    model_used TEXT,
+    tokens_in INTEGER NOT NULL DEFAULT 0,
+    tokens_out INTEGER NOT NULL DEFAULT 0,
+    started_at TEXT,
+    completed_at TEXT,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+    working_directory TEXT DEFAULT NULL,
+    cached INTEGER NOT NULL DEFAULT 0,
+    max_tokens_out INTEGER DEFAULT NULL,
+    max_cost REAL DEFAULT NULL,
+    retry_of_assignment_id TEXT,
+    overrides_json TEXT,
+    cancel_reason TEXT DEFAULT NULL
+);
+INSERT INTO task_assignments_new (
+    id, task_run_id, agent_id, agent_name, sequence_order, input_text,
+    output_text, status, model_used, tokens_in, tokens_out, started_at,
+    completed_at, duration_ms, error_message, created_at, cache_creation_tokens,
+    cache_read_tokens, working_directory, cached, max_tokens_out, max_cost,
+    retry_of_assignment_id, overrides_json, cancel_reason
+)
+SELECT
+    id, task_run_id, agent_id, agent_name, sequence_order, input_text,
+    output_text, status, model_used, tokens_in, tokens_out, started_at,
+    completed_at, duration_ms, error_message, created_at, cache_creation_tokens,
+    cache_read_tokens, working_directory, cached, max_tokens_out, max_cost,
+    retry_of_assignment_id, overrides_json, cancel_reason
+FROM task_assignments;
+DROP TABLE task_assignments;
+ALTER TABLE task_assignments_new RENAME TO task_assignments;
+
+CREATE INDEX IF NOT EXISTS idx_task_assignments_run ON task_assignments(task_run_id, sequence_order);
+
+PRAGMA foreign_keys=ON;
@@ -0,0 +1,35 @@
+-- Occurrences whose run is left for review end as 'needs_review', which the
+-- outcome CHECK did not allow.
+-- SQLite cannot alter a CHECK, so the table is rebuilt. No table references
+-- schedule_runs, so dropping it cascades nowhere.
+CREATE TABLE schedule_runs_new (
+    id TEXT PRIMARY KEY,
+    -- The scheduled task run that owns the schedule
+    schedule_task_run_id TEXT NOT NULL,
+    -- The task run that executed this occurrence (NULL when skipped)
+    task_run_id TEXT DEFAULT NULL,
+    -- The next_run_at value this occurrence was due at
+    scheduled_for TEXT DEFAULT NULL,
+    fired_at TEXT NOT NULL DEFAULT (datetime('now')),
+    finished_at TEXT DEFAULT NULL,
+    outcome TEXT NOT NULL DEFAULT 'running'
+        CHECK(outcome IN ('running', 'completed', 'failed', 'cancelled', 'skipped', 'needs_review')),
+    skipped_reason TEXT DEFAULT NULL,
+    error_message TEXT DEFAULT NULL,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    -- 1 for the scheduled firing, 2+ for retries of the same occurrence
+    attempt INTEGER NOT NULL DEFAULT 1,
+    FOREIGN KEY (schedule_task_run_id) REFERENCES task_runs(id) ON DELETE CASCADE
+);
+INSERT INTO schedule_runs_new (
+    id, schedule_task_run_id, task_run_id, scheduled_for, fired_at, finished_at,
+    outcome, skipped_reason, error_message, duration_ms, attempt
+)
+SELECT
+    id, schedule_task_run_id, task_run_id, scheduled_for, fired_at, finished_at,
+    outcome, skipped_reason, error_message, duration_ms, attempt
+FROM schedule_runs;
+DROP TABLE schedule_runs;
+ALTER TABLE schedule_runs_new RENAME TO schedule_runs;
+
+CREATE INDEX IF NOT EXISTS idx_schedule_runs_schedule ON schedule_runs(schedule_task_run_id, fired_at);
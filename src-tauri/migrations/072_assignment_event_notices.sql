@@ -0,0 +1,25 @@
+-- Timeline notes about a prompt, such as a tool its session goes without,
+-- are 'notice' events, which the kind CHECK did not allow.
+-- SQLite cannot alter a CHECK, so the table is rebuilt. No table references
+-- assignment_events, so dropping it cascades nowhere.
+CREATE TABLE assignment_events_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_run_id TEXT NOT NULL REFERENCES task_runs(id) ON DELETE CASCADE,
+    agent_id TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK(kind IN ('text', 'thought', 'tool_call', 'notice')),
+    content TEXT NOT NULL DEFAULT '',
+    tool_call_id TEXT DEFAULT NULL,
+    tool_status TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    assignment_id TEXT DEFAULT NULL
+);
+INSERT INTO assignment_events_new (
+    id, task_run_id, agent_id, kind, content, tool_call_id, tool_status, created_at, assignment_id
+)
+SELECT id, task_run_id, agent_id, kind, content, tool_call_id, tool_status, created_at, assignment_id
+FROM assignment_events;
+DROP TABLE assignment_events;
+ALTER TABLE assignment_events_new RENAME TO assignment_events;
+
+CREATE INDEX IF NOT EXISTS idx_assignment_events_agent ON assignment_events(task_run_id, agent_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_assignment_events_assignment ON assignment_events(assignment_id);
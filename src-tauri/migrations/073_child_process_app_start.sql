@@ -0,0 +1,4 @@
+-- OS start time of the app instance that spawned a process; with app_pid it
+-- tells a running instance from a later process that reused its pid. NULL
+-- for processes recorded before this column.
+ALTER TABLE child_processes ADD COLUMN app_started_at TEXT DEFAULT NULL;
@@ -0,0 +1,261 @@
+//! Logging in agents whose CLI is signed out.
+//!
+//! An agent CLI that is not logged in fails `initialize`, `session/new` or
+//! its first prompt with an authentication error. The agent type's login
+//! command then runs in a managed pseudo-terminal (see `agent_terminal`):
+//! the URL and device code it prints are raised as `AGENT_AUTH_REQUIRED`,
+//! its prompts, e.g. for a pasted code, are answered like any agent
+//! terminal's, and once it exits successfully the caller retries the failed
+//! operation on a fresh agent process. Login commands come from
+//! `agent_login_commands` in the config, over the built-in ones.
+
+use std::time::{Duration, Instant};
+
+use crate::acp::{agent_terminal, discovery, provisioner};
+use crate::config;
+use crate::error::{AppError, AppResult};
+use crate::events;
+use crate::models::agent::AgentConfig;
+use crate::state::AppState;
+
+/// Login commands of known agent types.
+const BUILTIN_LOGIN_COMMANDS: &[(&str, &str)] = &[("codex-acp", "codex login --device-auth")];
+
+/// How long the user has to finish logging in.
+const LOGIN_TIMEOUT_SECS: u64 = 600;
+
+/// Phrases of the errors agents fail with when they are not logged in.
+const AUTH_ERROR_MARKERS: &[&str] = &[
+    "authentication required",
+    "auth_required",
+    "not authenticated",
+    "unauthenticated",
+    "not logged in",
+    "please log in",
+    "please login",
+    "run /login",
+    "login required",
+    "unauthorized",
+    "invalid api key",
+    "invalid_api_key",
+    "missing api key",
+];
+
+/// Whether the error says the agent has to log in first.
+pub fn is_auth_error(error: &AppError) -> bool {
+    let message = match error {
+        AppError::Acp(message) | AppError::AgentFailed { message, .. } => message.to_lowercase(),
+        _ => return false,
+    };
+    AUTH_ERROR_MARKERS.iter().any(|m| message.contains(m))
+}
+
+/// What a login command asks of the user: to visit a URL, to enter a code.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct LoginHint {
+    url: Option<String>,
+    code: Option<String>,
+}
+
+/// A device code such as `WDJB-MJHT`: two groups of 4 or 5 capitals and
+/// digits.
+fn is_device_code(word: &str) -> bool {
+    let group = |g: &str| (4..=5).contains(&g.len()) && g.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+    match word.split_once('-') {
+        Some((a, b)) => group(a) && group(b) && word.chars().any(|c| c.is_ascii_uppercase()),
+        None => false,
+    }
+}
+
+/// The first URL and device code in the login command's output.
+fn login_hint(output: &str) -> LoginHint {
+    let text = agent_terminal::strip_ansi(output);
+    let mut hint = LoginHint::default();
+    for word in text.split_whitespace() {
+        if let Some(start) = word.find("https://").or_else(|| word.find("http://")) {
+            if hint.url.is_none() {
+                hint.url = Some(word[start..].trim_end_matches(['.', ',', ')', ']', '"', '\'']).to_string());
+            }
+            continue;
+        }
+        let word = word.trim_matches(|c: char| !c.is_ascii_alphanumeric());
+        if hint.code.is_none() && is_device_code(word) {
+            hint.code = Some(word.to_string());
+        }
+    }
+    hint
+}
+
+fn login_command(state: &AppState, agent_type: &str) -> Option<Vec<String>> {
+    let line = config::current(state).agent_login_commands.get(agent_type).cloned().or_else(|| {
+        BUILTIN_LOGIN_COMMANDS.iter().find(|(t, _)| *t == agent_type).map(|(_, c)| c.to_string())
+    })?;
+    let words: Vec<String> = line.split_whitespace().map(String::from).collect();
+    (!words.is_empty()).then_some(words)
+}
+
+async fn agent_type(agent: &AgentConfig) -> AppResult<String> {
+    let command = agent
+        .acp_command
+        .clone()
+        .ok_or_else(|| AppError::AgentNotConfigured { agent_id: agent.id.clone() })?;
+    let args: Vec<String> = agent.acp_args_json.as_deref().and_then(|j| serde_json::from_str(j).ok()).unwrap_or_default();
+    Ok(provisioner::resolve_agent_command(&command, &args).await?.agent_type)
+}
+
+/// Log the agent in after it failed with an authentication error at
+/// `failed_at`. Returns false when its agent type has no login command, and
+/// true once it is logged in, also when another login finished meanwhile.
+/// The caller restarts the agent before retrying.
+pub async fn login(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    agent: &AgentConfig,
+    task_run_id: Option<&str>,
+    failed_at: Instant,
+) -> AppResult<bool> {
+    let agent_type = agent_type(agent).await?;
+    let Some(command) = login_command(state, &agent_type) else {
+        log::info!("Agent {} is not logged in and agent type {} has no login command", agent.id, agent_type);
+        return Ok(false);
+    };
+    let mut logins = state.agent_logins.lock().await;
+    if logins.get(&agent_type).is_some_and(|at| *at > failed_at) {
+        log::info!("Agent type {} logged in meanwhile, retrying agent {}", agent_type, agent.id);
+        return Ok(true);
+    }
+
+    let result = run_login(app, state, agent, &agent_type, task_run_id, &command).await;
+    events::emit(
+        app,
+        &events::AGENT_AUTH_COMPLETED,
+        serde_json::json!({
+            "agentId": agent.id,
+            "agentName": agent.name,
+            "taskRunId": task_run_id,
+            "success": result.is_ok(),
+            "error": result.as_ref().err().map(|e| e.to_string()),
+        }),
+    );
+    result?;
+    logins.insert(agent_type, Instant::now());
+    Ok(true)
+}
+
+async fn run_login(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    agent: &AgentConfig,
+    agent_type: &str,
+    task_run_id: Option<&str>,
+    command: &[String],
+) -> AppResult<()> {
+    log::info!("Logging in agent {}: {}", agent.id, command.join(" "));
+    let mut cmd = tokio::process::Command::new(&command[0]);
+    cmd.args(&command[1..])
+        .env("PATH", discovery::get_enriched_path())
+        .envs(discovery::get_agent_env_for_command(agent_type).await)
+        .kill_on_drop(true);
+    if let Some(home) = dirs::home_dir() {
+        cmd.current_dir(home);
+    }
+    let pty = agent_terminal::prepare_interactive(&agent.id, &mut cmd)
+        .ok_or_else(|| AppError::Internal("No terminal is available to log the agent in".into()))?;
+    // Subscribed before the terminal starts so none of its output is missed
+    let mut rx = state.terminal_events.subscribe();
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| AppError::Internal(format!("Failed to run login command {}: {}", command[0], e)))?;
+    // The command holds copies of the terminal; the agent's exit must close it
+    drop(cmd);
+    let terminal = agent_terminal::start(state, &agent.id, pty)
+        .ok_or_else(|| AppError::Internal("Failed to attach to the login terminal".into()))?;
+
+    let emit_hint = |hint: &LoginHint| {
+        events::emit(
+            app,
+            &events::AGENT_AUTH_REQUIRED,
+            serde_json::json!({
+                "agentId": agent.id,
+                "agentName": agent.name,
+                "taskRunId": task_run_id,
+                "terminalId": terminal.id,
+                "url": hint.url,
+                "code": hint.code,
+            }),
+        )
+    };
+    let mut hint = LoginHint::default();
+    emit_hint(&hint);
+
+    let mut output = String::new();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(LOGIN_TIMEOUT_SECS);
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => break status?,
+            event = rx.recv() => {
+                let (topic, payload) = match event {
+                    Ok(event) => event,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break child.wait().await?,
+                };
+                if topic.name != events::AGENT_TERMINAL_OUTPUT.name || payload["terminalId"] != terminal.id.as_str() {
+                    continue;
+                }
+                agent_terminal::push_tail(&mut output, payload["data"].as_str().unwrap_or_default());
+                // The URL or code may have scrolled out of the tail since
+                let found = login_hint(&output);
+                let next = LoginHint { url: found.url.or(hint.url.clone()), code: found.code.or(hint.code.clone()) };
+                if next != hint {
+                    hint = next;
+                    emit_hint(&hint);
+                }
+            }
+            // The login command is killed when dropped
+            _ = tokio::time::sleep_until(deadline) => {
+                return Err(AppError::Timeout(format!(
+                    "Timeout ({}s) waiting for agent {} to log in",
+                    LOGIN_TIMEOUT_SECS, agent.name
+                )));
+            }
+        }
+    };
+    drop(terminal);
+
+    if !status.success() {
+        return Err(AppError::Internal(format!("Login command {} exited with {}", command[0], status)));
+    }
+    log::info!("Agent {} logged in", agent.id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_device_code_and_url_in_login_output() {
+        let output = "Welcome to Codex\r\n1. Open this link in your browser:\r\n   \u{1b}[94mhttps://auth.openai.com/codex/device\u{1b}[0m\r\n\
+                      2. Enter this one-time code (expires in 15 minutes):\r\n   \u{1b}[1mWDJB-MJHT5\u{1b}[0m\r\n";
+        let hint = login_hint(output);
+        assert_eq!(hint.url.as_deref(), Some("https://auth.openai.com/codex/device"));
+        assert_eq!(hint.code.as_deref(), Some("WDJB-MJHT5"));
+
+        let hint = login_hint("Visit (https://github.com/login/device). UTF-8 codes like X-RAY or 2024-01 are not it");
+        assert_eq!(hint.url.as_deref(), Some("https://github.com/login/device"));
+        assert_eq!(hint.code, None);
+    }
+
+    #[test]
+    fn recognizes_errors_of_agents_that_are_not_logged_in() {
+        assert!(is_auth_error(&AppError::Acp("session/new failed: Authentication required".into())));
+        assert!(is_auth_error(&AppError::AgentFailed {
+            agent_id: "a".into(),
+            task_run_id: None,
+            code: -32603,
+            message: "Invalid API key · Please run /login".into(),
+        }));
+        assert!(!is_auth_error(&AppError::Acp("session/new failed: author field missing".into())));
+        assert!(!is_auth_error(&AppError::Timeout("not logged in".into())));
+    }
+}
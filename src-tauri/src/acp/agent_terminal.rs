@@ -0,0 +1,372 @@
+//! Terminal pass-through for agents that ask for input.
+//!
+//! Some agent CLIs now and then prompt on their terminal, e.g. in a login
+//! flow or to accept an SSH host key. Their stdin and stdout carry ACP, so
+//! each agent process gets a pseudo-terminal as its controlling terminal
+//! instead: what the CLI writes to `/dev/tty` is forwarded to the frontend,
+//! and output ending in a prompt marks the terminal as waiting. The user's
+//! reply is written back to the terminal and the CLI carries on with ACP;
+//! requests to the agent do not time out meanwhile. Unix only, and only
+//! while `agent_terminal_attach` is on.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{AppError, AppResult};
+use crate::events::{self, Topic};
+use crate::state::AppState;
+
+/// Most characters of recent output kept to find the prompt in.
+const TAIL_CHARS: usize = 1024;
+
+/// Terminal events raised off the async runtime, forwarded to the frontend.
+pub type TerminalEvents = tokio::sync::broadcast::Sender<(&'static Topic, Value)>;
+
+/// Terminals of running agents by id.
+pub type TerminalRegistry = Mutex<HashMap<String, Weak<AgentTerminal>>>;
+
+/// The controlling terminal of an agent process.
+pub struct AgentTerminal {
+    pub id: String,
+    pub agent_id: String,
+    writer: Mutex<std::fs::File>,
+    /// The prompt the agent waits at for input
+    prompt: Mutex<Option<String>>,
+    events: TerminalEvents,
+    /// Held so reading the terminal does not fail before the agent opens it
+    #[cfg(unix)]
+    _slave: std::os::fd::OwnedFd,
+}
+
+impl std::fmt::Debug for AgentTerminal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AgentTerminal").field("id", &self.id).field("agent_id", &self.agent_id).finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentTerminalInfo {
+    pub terminal_id: String,
+    pub agent_id: String,
+    pub prompt: Option<String>,
+}
+
+impl AgentTerminal {
+    pub fn is_waiting(&self) -> bool {
+        self.prompt.lock().map(|p| p.is_some()).unwrap_or(false)
+    }
+
+    pub fn info(&self) -> AgentTerminalInfo {
+        AgentTerminalInfo {
+            terminal_id: self.id.clone(),
+            agent_id: self.agent_id.clone(),
+            prompt: self.prompt.lock().ok().and_then(|p| p.clone()),
+        }
+    }
+
+    /// Type `data` into the terminal, as is; a reply ends with "\n".
+    pub fn write_input(&self, data: &str) -> AppResult<()> {
+        use std::io::Write;
+        let mut writer = self.writer.lock().map_err(|e| AppError::Internal(e.to_string()))?;
+        writer.write_all(data.as_bytes())?;
+        writer.flush()?;
+        drop(writer);
+        if let Ok(mut prompt) = self.prompt.lock() {
+            *prompt = None;
+        }
+        let _ = self.events.send((
+            &events::AGENT_TERMINAL_RESUMED,
+            serde_json::json!({ "terminalId": self.id, "agentId": self.agent_id }),
+        ));
+        Ok(())
+    }
+}
+
+/// Drop escape sequences such as colours and cursor moves.
+pub(crate) fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            out.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'[') {
+            chars.next();
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() || c == '~' {
+                    break;
+                }
+            }
+        } else {
+            chars.next();
+        }
+    }
+    out
+}
+
+/// The prompt the output ends at, if the agent looks to be waiting for
+/// input: a last line without a newline that ends like a question or a
+/// field, or asks for a key press.
+pub(crate) fn pending_prompt(tail: &str) -> Option<String> {
+    let text = strip_ansi(tail);
+    if text.ends_with('\n') {
+        return None;
+    }
+    let line = text.rsplit(['\n', '\r']).find(|l| !l.trim().is_empty())?.trim();
+    let asks_key = line.to_lowercase().contains("press enter") || line.to_lowercase().contains("press any key");
+    (asks_key || line.ends_with([':', '?', '>', ')', ']'])).then(|| line.to_string())
+}
+
+/// Keep the last `TAIL_CHARS` characters.
+pub(crate) fn push_tail(tail: &mut String, text: &str) {
+    tail.push_str(text);
+    let excess = tail.chars().count().saturating_sub(TAIL_CHARS);
+    if excess > 0 {
+        let cut = tail.char_indices().nth(excess).map_or(tail.len(), |(i, _)| i);
+        tail.drain(..cut);
+    }
+}
+
+/// A pseudo-terminal set up as the controlling terminal of a command about
+/// to be spawned.
+#[cfg(unix)]
+pub struct PreparedTerminal {
+    master: std::os::fd::OwnedFd,
+    slave: std::os::fd::OwnedFd,
+}
+
+#[cfg(not(unix))]
+pub struct PreparedTerminal;
+
+#[cfg(unix)]
+fn open_pty() -> std::io::Result<PreparedTerminal> {
+    use std::os::fd::FromRawFd;
+
+    let (mut master, mut slave) = (-1, -1);
+    let mut size = libc::winsize { ws_row: 24, ws_col: 80, ws_xpixel: 0, ws_ypixel: 0 };
+    // The window size is `*const` on Linux and `*mut` on macOS
+    let size_ptr = std::ptr::addr_of_mut!(size) as _;
+    // SAFETY: openpty only writes the two new descriptors, which are owned below
+    let rc = unsafe { libc::openpty(&mut master, &mut slave, std::ptr::null_mut(), std::ptr::null_mut(), size_ptr) };
+    if rc != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // SAFETY: both descriptors were just opened and nothing else owns them
+    let pty = unsafe {
+        PreparedTerminal {
+            master: std::os::fd::OwnedFd::from_raw_fd(master),
+            slave: std::os::fd::OwnedFd::from_raw_fd(slave),
+        }
+    };
+    // Other processes the app spawns must not inherit the terminal
+    for fd in [master, slave] {
+        // SAFETY: fcntl on a descriptor owned by `pty`
+        if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(pty)
+}
+
+/// Give `cmd` a new session with a pseudo-terminal as its controlling
+/// terminal. Returns `None`, and leaves `cmd` alone, when that fails.
+#[cfg(unix)]
+pub fn prepare(agent_id: &str, cmd: &mut tokio::process::Command) -> Option<PreparedTerminal> {
+    use std::os::fd::AsRawFd;
+
+    let pty = match open_pty() {
+        Ok(pty) => pty,
+        Err(e) => {
+            log::warn!("Failed to open a terminal for agent {}: {}", agent_id, e);
+            return None;
+        }
+    };
+    let slave = pty.slave.as_raw_fd();
+    // SAFETY: only async-signal-safe calls run between fork and exec
+    unsafe {
+        cmd.pre_exec(move || {
+            if libc::setsid() == -1 || libc::ioctl(slave, libc::TIOCSCTTY as _, 0) == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    Some(pty)
+}
+
+#[cfg(not(unix))]
+pub fn prepare(_agent_id: &str, _cmd: &mut tokio::process::Command) -> Option<PreparedTerminal> {
+    None
+}
+
+/// Like `prepare`, with the terminal as the command's stdin, stdout and
+/// stderr too, for commands that only talk to the user.
+#[cfg(unix)]
+pub fn prepare_interactive(agent_id: &str, cmd: &mut tokio::process::Command) -> Option<PreparedTerminal> {
+    let pty = prepare(agent_id, cmd)?;
+    let stdio = || pty.slave.try_clone().map(std::process::Stdio::from);
+    match (stdio(), stdio(), stdio()) {
+        (Ok(stdin), Ok(stdout), Ok(stderr)) => {
+            cmd.stdin(stdin).stdout(stdout).stderr(stderr);
+            Some(pty)
+        }
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            log::warn!("Failed to open a terminal for agent {}: {}", agent_id, e);
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn prepare_interactive(_agent_id: &str, _cmd: &mut tokio::process::Command) -> Option<PreparedTerminal> {
+    None
+}
+
+/// Register the terminal of a spawned agent and forward what the agent
+/// writes to it until the agent is gone.
+#[cfg(unix)]
+pub fn start(state: &AppState, agent_id: &str, pty: PreparedTerminal) -> Option<Arc<AgentTerminal>> {
+    let mut reader = match pty.master.try_clone() {
+        Ok(fd) => std::fs::File::from(fd),
+        Err(e) => {
+            log::warn!("Failed to attach to the terminal of agent {}: {}", agent_id, e);
+            return None;
+        }
+    };
+    let writer = std::fs::File::from(pty.master);
+    let terminal = Arc::new(AgentTerminal {
+        id: uuid::Uuid::new_v4().to_string(),
+        agent_id: agent_id.to_string(),
+        writer: Mutex::new(writer),
+        prompt: Mutex::new(None),
+        events: state.terminal_events.clone(),
+        _slave: pty.slave,
+    });
+    if let Ok(mut terminals) = state.agent_terminals.lock() {
+        terminals.retain(|_, t| t.strong_count() > 0);
+        terminals.insert(terminal.id.clone(), Arc::downgrade(&terminal));
+    }
+
+    let weak = Arc::downgrade(&terminal);
+    let (id, aid, events) = (terminal.id.clone(), agent_id.to_string(), state.terminal_events.clone());
+    let registry = state.agent_terminals.clone();
+    let spawned = std::thread::Builder::new().name(format!("agent-terminal-{}", agent_id)).spawn(move || {
+        use std::io::Read;
+        let mut buf = [0u8; 4096];
+        let mut tail = String::new();
+        loop {
+            // Fails once the agent and its terminal are gone
+            let n = match reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            let text = String::from_utf8_lossy(&buf[..n]).to_string();
+            push_tail(&mut tail, &text);
+            let _ = events.send((
+                &events::AGENT_TERMINAL_OUTPUT,
+                serde_json::json!({ "terminalId": id, "agentId": aid, "data": text }),
+            ));
+            let Some(terminal) = weak.upgrade() else { break };
+            let prompt = pending_prompt(&tail);
+            let was_waiting = match terminal.prompt.lock() {
+                Ok(mut current) => std::mem::replace(&mut *current, prompt.clone()).is_some(),
+                Err(_) => break,
+            };
+            if let (Some(prompt), false) = (prompt, was_waiting) {
+                log::info!("Agent {} waits for terminal input: {}", aid, prompt);
+                let _ = events.send((
+                    &events::AGENT_TERMINAL_WAITING,
+                    serde_json::json!({ "terminalId": id, "agentId": aid, "prompt": prompt }),
+                ));
+            }
+        }
+        if let Ok(mut terminals) = registry.lock() {
+            terminals.remove(&id);
+        }
+        let _ = events.send((&events::AGENT_TERMINAL_CLOSED, serde_json::json!({ "terminalId": id, "agentId": aid })));
+    });
+    if let Err(e) = spawned {
+        log::warn!("Failed to read the terminal of agent {}: {}", agent_id, e);
+    }
+    Some(terminal)
+}
+
+#[cfg(not(unix))]
+pub fn start(_state: &AppState, _agent_id: &str, _pty: PreparedTerminal) -> Option<Arc<AgentTerminal>> {
+    None
+}
+
+/// A running agent's terminal by id.
+pub fn get(state: &AppState, terminal_id: &str) -> AppResult<Arc<AgentTerminal>> {
+    let terminals = state.agent_terminals.lock().map_err(|e| AppError::Internal(e.to_string()))?;
+    terminals
+        .get(terminal_id)
+        .and_then(Weak::upgrade)
+        .ok_or_else(|| AppError::NotFound(format!("Agent terminal {terminal_id}")))
+}
+
+/// Terminals of running agents, waiting ones first.
+pub fn list(state: &AppState) -> Vec<AgentTerminalInfo> {
+    let Ok(terminals) = state.agent_terminals.lock() else {
+        return Vec::new();
+    };
+    let mut infos: Vec<AgentTerminalInfo> = terminals.values().filter_map(Weak::upgrade).map(|t| t.info()).collect();
+    infos.sort_by_key(|i| i.prompt.is_none());
+    infos
+}
+
+/// Whether the agent behind `process_key` waits for terminal input; requests
+/// to it should not time out meanwhile.
+pub async fn awaiting_input(state: &AppState, process_key: &str) -> bool {
+    let processes = state.agent_processes.lock().await;
+    processes
+        .get(process_key)
+        .and_then(|p| p.terminal.as_ref())
+        .is_some_and(|t| t.is_waiting())
+}
+
+/// Emit terminal events raised by the reader threads.
+pub fn forward_events(app: tauri::AppHandle, state: &AppState) {
+    let mut rx = state.terminal_events.subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok((topic, payload)) => events::emit(&app, topic, payload),
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                    log::warn!("Dropped {} agent terminal events", n);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_prompt_an_agent_waits_at() {
+        assert_eq!(
+            pending_prompt("Opening browser...\r\n\u{1b}[1mPaste the code:\u{1b}[0m ").as_deref(),
+            Some("Paste the code:")
+        );
+        assert_eq!(
+            pending_prompt("Are you sure you want to continue connecting (yes/no/[fingerprint])? ").as_deref(),
+            Some("Are you sure you want to continue connecting (yes/no/[fingerprint])?")
+        );
+        assert_eq!(pending_prompt("Press Enter to open the browser").as_deref(), Some("Press Enter to open the browser"));
+        assert_eq!(pending_prompt("Password:\r\n"), None);
+        assert_eq!(pending_prompt("\r⠋ Loading"), None);
+
+        let mut tail = String::new();
+        push_tail(&mut tail, &"é".repeat(TAIL_CHARS));
+        push_tail(&mut tail, "Token: ");
+        assert_eq!(tail.chars().count(), TAIL_CHARS);
+        assert!(tail.ends_with("Token: "));
+    }
+}
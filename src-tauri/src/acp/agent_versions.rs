@@ -0,0 +1,267 @@
+//! Explicit versions of registry agent CLIs.
+//!
+//! Agents otherwise follow the registry and are only upgraded when a service
+//! rejects their version (see `upgrade`). Here the user sees which version of
+//! a registry agent is installed against the latest one and installs a
+//! specific version. A chosen NPX version is pinned in
+//! `~/.iaagenthub/version_pins.json`; the provisioner then runs that version
+//! instead of the registry's or a CLI found on PATH, until the user goes back
+//! to the latest. Running agent processes keep their version until they are
+//! restarted.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::acp::discovery::{self, Distribution, RegistryEntry};
+use crate::acp::{builtin, provisioner};
+use crate::error::{AppError, AppResult};
+use crate::models::agent::AgentVersions;
+
+/// Published versions listed per agent, newest first.
+const MAX_LISTED_VERSIONS: usize = 50;
+
+/// Path of the pins: `~/.iaagenthub/version_pins.json`, registry ID -> version.
+fn pins_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".iaagenthub")
+        .join("version_pins.json")
+}
+
+fn load_pins() -> HashMap<String, String> {
+    std::fs::read_to_string(pins_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_pin(agent_type: &str, version: Option<&str>) -> AppResult<()> {
+    let mut pins = load_pins();
+    match version {
+        Some(version) => pins.insert(agent_type.to_string(), version.to_string()),
+        None => pins.remove(agent_type),
+    };
+    let path = pins_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&pins)?)?;
+    Ok(())
+}
+
+/// The version the user pinned a registry agent to.
+pub fn pinned_version(agent_type: &str) -> Option<String> {
+    load_pins().remove(agent_type)
+}
+
+/// Follow the registry again, e.g. after the agent was uninstalled.
+pub fn unpin(agent_type: &str) {
+    if pinned_version(agent_type).is_some() {
+        if let Err(e) = save_pin(agent_type, None) {
+            log::warn!("Failed to unpin {}: {}", agent_type, e);
+        }
+    }
+}
+
+/// Drop the pin of the registry agent run by `command`, after its service
+/// rejected the pinned version and it was upgraded.
+pub async fn unpin_command(command: &str) {
+    let Some(registry) = discovery::cached_registry().await else { return };
+    let Some(entry) = discovery::find_entry_by_command(&registry, command) else { return };
+    if let Some(version) = pinned_version(&entry.id) {
+        log::warn!("Unpinning {} from {}: the version was rejected and upgraded", entry.id, version);
+        unpin(&entry.id);
+    }
+}
+
+/// Name and version of an npm specifier: `@scope/name@1.2.3` ->
+/// (`@scope/name`, `Some("1.2.3")`).
+pub fn split_package(specifier: &str) -> (&str, Option<&str>) {
+    let start = usize::from(specifier.starts_with('@'));
+    match specifier[start..].find('@') {
+        Some(pos) => (&specifier[..start + pos], Some(&specifier[start + pos + 1..])),
+        None => (specifier, None),
+    }
+}
+
+/// Plain semver-like versions only, so nothing else reaches npm's arguments.
+fn is_valid_version(version: &str) -> bool {
+    version.starts_with(|c: char| c.is_ascii_digit())
+        && version.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+}
+
+/// Versions from `npm view <package> versions --json`, which prints an
+/// array oldest first, or a bare string for a package with one version.
+fn parse_versions(json: &str) -> Vec<String> {
+    let versions = match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(serde_json::Value::Array(items)) => items.into_iter().filter_map(|v| v.as_str().map(str::to_string)).collect(),
+        Ok(serde_json::Value::String(version)) => vec![version],
+        _ => Vec::new(),
+    };
+    versions.into_iter().rev().take(MAX_LISTED_VERSIONS).collect()
+}
+
+fn npm() -> AppResult<(String, String)> {
+    let enriched_path = discovery::get_enriched_path();
+    let npm_path = provisioner::resolve_in_path("npm", &enriched_path)
+        .ok_or_else(|| AppError::Internal("npm not found on PATH".into()))?;
+    Ok((npm_path, enriched_path))
+}
+
+async fn published_versions(package: &str) -> AppResult<Vec<String>> {
+    let (npm_path, enriched_path) = npm()?;
+    let output = tokio::process::Command::new(&npm_path)
+        .args(["view", package, "versions", "--json"])
+        .env("PATH", &enriched_path)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to run npm view: {e}")))?;
+    if !output.status.success() {
+        return Err(AppError::Internal(format!(
+            "npm view {} failed: {}",
+            package,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(parse_versions(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Install exactly `package@version` in the agent's adapters directory.
+async fn install_npm(agent_type: &str, package: &str, version: &str) -> AppResult<()> {
+    let adapter_dir = discovery::get_adapters_dir().join(agent_type);
+    tokio::fs::create_dir_all(&adapter_dir).await?;
+    let package_json = serde_json::json!({
+        "name": format!("{}-local", agent_type),
+        "private": true,
+        "dependencies": { package: version },
+    });
+    tokio::fs::write(adapter_dir.join("package.json"), serde_json::to_string_pretty(&package_json)?).await?;
+    // The lock file would keep the version installed before
+    let _ = tokio::fs::remove_file(adapter_dir.join("package-lock.json")).await;
+
+    let (npm_path, enriched_path) = npm()?;
+    log::info!("Installing {}@{} in {:?}", package, version, adapter_dir);
+    let output = tokio::process::Command::new(&npm_path)
+        .arg("install")
+        .current_dir(&adapter_dir)
+        .env("PATH", &enriched_path)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .map_err(|e| AppError::Internal(format!("npm install spawn error: {e}")))?;
+    if !output.status.success() {
+        return Err(AppError::Internal(format!(
+            "npm install {}@{} failed: {}",
+            package,
+            version,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+async fn registry_entry(agent_type: &str) -> AppResult<RegistryEntry> {
+    discovery::get_registry_entry(agent_type)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("Registry agent '{}' not found", agent_type)))
+}
+
+fn latest_version(entry: &RegistryEntry) -> String {
+    match &entry.distribution {
+        Distribution::Npx(npx) => split_package(&npx.package).1.unwrap_or(&entry.version).to_string(),
+        Distribution::Binary(_) => entry.version.clone(),
+    }
+}
+
+/// Installed, latest, pinned and published versions of a registry agent.
+pub async fn list(agent_type: &str) -> AppResult<AgentVersions> {
+    let entry = registry_entry(agent_type).await?;
+    let (package, available) = match &entry.distribution {
+        Distribution::Npx(npx) => {
+            let package = split_package(&npx.package).0;
+            let available = published_versions(package).await.unwrap_or_else(|e| {
+                log::warn!("Failed to list versions of {}: {}", package, e);
+                Vec::new()
+            });
+            (Some(package.to_string()), available)
+        }
+        Distribution::Binary(_) => (None, Vec::new()),
+    };
+    Ok(AgentVersions {
+        agent_type: entry.id.clone(),
+        name: entry.name.clone(),
+        package,
+        installed: discovery::installed_adapter_version(&entry),
+        latest: latest_version(&entry),
+        pinned: pinned_version(&entry.id),
+        available,
+    })
+}
+
+/// Install `version` of a registry agent and pin it, or with None install
+/// the registry's version and follow the registry again.
+pub async fn set(agent_type: &str, version: Option<&str>) -> AppResult<AgentVersions> {
+    let entry = registry_entry(agent_type).await?;
+    if builtin::is_builtin_agent(&entry.id) {
+        return Err(AppError::InvalidRequest(format!(
+            "{} is built in and runs the version bundled with the app",
+            entry.name
+        )));
+    }
+    match &entry.distribution {
+        Distribution::Npx(npx) => {
+            let package = split_package(&npx.package).0;
+            let target = match version {
+                Some(version) => {
+                    if !is_valid_version(version) || !published_versions(package).await?.iter().any(|v| v == version) {
+                        return Err(AppError::InvalidRequest(format!("{} has no version '{}'", package, version)));
+                    }
+                    version.to_string()
+                }
+                None => latest_version(&entry),
+            };
+            install_npm(&entry.id, package, &target).await?;
+            save_pin(&entry.id, version)?;
+        }
+        Distribution::Binary(platforms) => {
+            if version.is_some_and(|v| v != entry.version) {
+                return Err(AppError::InvalidRequest(format!(
+                    "{} is distributed as a binary; only the registry's version {} can be installed",
+                    entry.name, entry.version
+                )));
+            }
+            let target = platforms.get(discovery::get_current_platform()).ok_or_else(|| {
+                AppError::InvalidRequest(format!("{} has no binary for this platform", entry.name))
+            })?;
+            provisioner::download_and_extract_binary(target, &entry.id)
+                .await
+                .map_err(|e| AppError::Internal(format!("Download failed: {e}")))?;
+        }
+    }
+    discovery::mark_installed(&entry.id);
+    list(agent_type).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_package_specifiers() {
+        assert_eq!(split_package("@google/gemini-cli@0.27.3"), ("@google/gemini-cli", Some("0.27.3")));
+        assert_eq!(split_package("@google/gemini-cli"), ("@google/gemini-cli", None));
+        assert_eq!(split_package("some-tool@1.0.0"), ("some-tool", Some("1.0.0")));
+        assert_eq!(split_package("some-tool"), ("some-tool", None));
+    }
+
+    #[test]
+    fn parses_npm_versions_newest_first() {
+        assert_eq!(parse_versions("[\"0.1.0\", \"0.2.0-beta.1\", \"0.2.0\"]"), ["0.2.0", "0.2.0-beta.1", "0.1.0"]);
+        assert_eq!(parse_versions("\"1.0.0\""), ["1.0.0"]);
+        assert!(parse_versions("npm ERR! 404").is_empty());
+
+        assert!(is_valid_version("2.1.39") && is_valid_version("0.2.0-beta.1+build.5"));
+        assert!(!is_valid_version("latest") && !is_valid_version("1.0.0 --registry=x") && !is_valid_version(""));
+    }
+}
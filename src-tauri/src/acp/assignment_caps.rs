@@ -0,0 +1,181 @@
+//! Output token and cost caps of an assignment.
+//!
+//! The planner or the user can give an assignment `max_tokens_out` and
+//! `max_cost` (USD). Agents report usage only when a prompt finishes, so
+//! while one works its output tokens are estimated from the streamed text and
+//! thoughts, and its cost from those plus the prompt and tool output priced
+//! with the model's rates. Near a cap the prompt is cancelled and the agent
+//! is asked to wrap up; if the wrap-up reaches the cap it is cancelled too.
+//! The assignment then ends as `capped` with the text produced so far.
+
+use crate::chat_tool::quota::estimate_tokens;
+use crate::models::pricing::{ModelPricing, PricingTable, TokenUsage};
+use crate::models::task_run::PlannedAssignment;
+use crate::state::AppState;
+
+pub const CAPPED_STATUS: &str = "capped";
+
+/// Share of a cap at which the agent is asked to wrap up.
+const WRAP_UP_RATIO: f64 = 0.9;
+
+pub const WRAP_UP_PROMPT: &str = "You are about to reach the token or cost budget for this task. \
+     Stop exploring and wrap up now: briefly state what you completed, what remains, and your final result.";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AssignmentCaps {
+    pub max_tokens_out: Option<i64>,
+    pub max_cost: Option<f64>,
+}
+
+impl AssignmentCaps {
+    pub fn of(planned: &PlannedAssignment) -> Self {
+        Self {
+            max_tokens_out: planned.max_tokens_out,
+            max_cost: planned.max_cost,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max_tokens_out.is_none() && self.max_cost.is_none()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_tokens_out.is_some_and(|max| max < 1) {
+            return Err("max_tokens_out must be at least 1".into());
+        }
+        if self.max_cost.is_some_and(|max| max.is_nan() || max <= 0.0) {
+            return Err("max_cost must be greater than 0".into());
+        }
+        Ok(())
+    }
+}
+
+/// Start enforcing caps on the agent's assignment in a run. Empty caps are
+/// kept too, so the user can set caps while it runs.
+pub async fn begin(state: &AppState, task_run_id: &str, agent_id: &str, caps: AssignmentCaps) {
+    let mut all = state.assignment_caps.lock().await;
+    all.insert((task_run_id.to_string(), agent_id.to_string()), caps);
+}
+
+pub async fn end(state: &AppState, task_run_id: &str, agent_id: &str) {
+    let mut all = state.assignment_caps.lock().await;
+    all.remove(&(task_run_id.to_string(), agent_id.to_string()));
+}
+
+/// Caps in force for the agent's running assignment.
+pub async fn current(state: &AppState, task_run_id: &str, agent_id: &str) -> Option<AssignmentCaps> {
+    let all = state.assignment_caps.lock().await;
+    all.get(&(task_run_id.to_string(), agent_id.to_string())).copied()
+}
+
+/// Change the caps of a running assignment. Returns false when none is running.
+pub async fn update(state: &AppState, task_run_id: &str, agent_id: &str, caps: AssignmentCaps) -> bool {
+    let mut all = state.assignment_caps.lock().await;
+    let key = (task_run_id.to_string(), agent_id.to_string());
+    if !all.contains_key(&key) {
+        return false;
+    }
+    all.insert(key, caps);
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CapCheck {
+    Within,
+    /// Past `WRAP_UP_RATIO` of a cap
+    WrapUp,
+    Reached,
+}
+
+/// Estimated usage of one prompt.
+#[derive(Debug)]
+pub struct CapMonitor {
+    pricing: Option<ModelPricing>,
+    tokens_in: i64,
+    tokens_out: i64,
+}
+
+impl CapMonitor {
+    pub fn new(pricing: Option<ModelPricing>, prompt: &str) -> Self {
+        Self {
+            pricing,
+            tokens_in: estimate_tokens(prompt),
+            tokens_out: 0,
+        }
+    }
+
+    /// Monitor for an agent's prompt when it runs an assignment.
+    pub async fn start(state: &AppState, task_run_id: &str, agent_id: &str, model: &str, prompt: &str) -> Option<Self> {
+        current(state, task_run_id, agent_id).await?;
+        let pricing = PricingTable::load(state).lookup(model).cloned();
+        Some(Self::new(pricing, prompt))
+    }
+
+    /// Text or thoughts the agent produced.
+    pub fn record_output(&mut self, text: &str) {
+        self.tokens_out += estimate_tokens(text);
+    }
+
+    /// Text the agent reads back, such as tool output.
+    pub fn record_input(&mut self, text: &str) {
+        self.tokens_in += estimate_tokens(text);
+    }
+
+    pub fn tokens_out(&self) -> i64 {
+        self.tokens_out
+    }
+
+    /// Estimated cost in USD, or `None` when the model has no known pricing.
+    pub fn cost(&self) -> Option<f64> {
+        let usage = TokenUsage {
+            tokens_in: self.tokens_in,
+            tokens_out: self.tokens_out,
+            ..TokenUsage::default()
+        };
+        self.pricing.as_ref().map(|pricing| pricing.cost(&usage))
+    }
+
+    pub fn check(&self, caps: &AssignmentCaps) -> CapCheck {
+        let token_share = caps.max_tokens_out.map(|max| self.tokens_out as f64 / max as f64);
+        let cost_share = caps.max_cost.zip(self.cost()).map(|(max, cost)| cost / max);
+        let share = token_share.into_iter().chain(cost_share).fold(0.0, f64::max);
+        if share >= 1.0 {
+            CapCheck::Reached
+        } else if share >= WRAP_UP_RATIO {
+            CapCheck::WrapUp
+        } else {
+            CapCheck::Within
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_estimated_usage_against_caps() {
+        let pricing = PricingTable::builtin().lookup("claude-sonnet-4").cloned();
+        let mut monitor = CapMonitor::new(pricing, "");
+        let caps = AssignmentCaps { max_tokens_out: Some(100), max_cost: None };
+        assert!(caps.validate().is_ok());
+        assert!(AssignmentCaps { max_cost: Some(0.0), ..caps }.validate().is_err());
+
+        monitor.record_output(&"word".repeat(89));
+        assert_eq!(monitor.check(&caps), CapCheck::Within);
+        monitor.record_output("word");
+        assert_eq!(monitor.check(&caps), CapCheck::WrapUp);
+        monitor.record_output(&"word".repeat(10));
+        assert_eq!(monitor.check(&caps), CapCheck::Reached);
+
+        // 100k input tokens at $3/MTok is $0.30
+        let mut monitor = CapMonitor::new(PricingTable::builtin().lookup("sonnet").cloned(), "");
+        monitor.record_input(&"word".repeat(100_000));
+        let caps = AssignmentCaps { max_tokens_out: None, max_cost: Some(0.32) };
+        assert_eq!(monitor.check(&caps), CapCheck::WrapUp);
+
+        // Unpriced models are only held to the token cap
+        let monitor = CapMonitor::new(None, &"word".repeat(1_000_000));
+        assert_eq!(monitor.check(&caps), CapCheck::Within);
+    }
+}
@@ -0,0 +1,127 @@
+//! Agent settings overridden for one assignment.
+//!
+//! The planner or the user can give an assignment its own model,
+//! temperature, system prompt and permission profile. They are in force
+//! while the assignment runs and never change the stored agent. The model is
+//! switched with ACP's `session/set_model` and switched back before the
+//! agent's next prompt without it. ACP has no temperature parameter, so it
+//! goes in the prompt's `_meta` for agents that read it. The system prompt
+//! comes before the assignment's prompt, and the permission profile answers
+//! permission requests before remembered decisions or the user are asked.
+
+use crate::models::agent::AgentConfig;
+use crate::models::task_run::{AssignmentOverrides, PermissionProfile, PlannedAssignment};
+use crate::state::AppState;
+
+/// The planned assignment's overrides; invalid ones are ignored.
+pub fn checked(planned: &PlannedAssignment) -> AssignmentOverrides {
+    let overrides = planned.overrides.clone().unwrap_or_default();
+    match overrides.validate() {
+        Ok(()) => overrides,
+        Err(e) => {
+            log::warn!("Ignoring overrides of agent {}'s assignment: {}", planned.agent_id, e);
+            AssignmentOverrides::default()
+        }
+    }
+}
+
+/// Apply overrides to the agent's assignment in a run until `end`.
+pub async fn begin(state: &AppState, task_run_id: &str, agent_id: &str, overrides: AssignmentOverrides) {
+    if overrides.is_empty() {
+        return;
+    }
+    let mut all = state.assignment_overrides.lock().await;
+    all.insert((task_run_id.to_string(), agent_id.to_string()), overrides);
+}
+
+pub async fn end(state: &AppState, task_run_id: &str, agent_id: &str) {
+    let mut all = state.assignment_overrides.lock().await;
+    all.remove(&(task_run_id.to_string(), agent_id.to_string()));
+}
+
+/// Overrides in force for the agent's running assignment.
+pub async fn current(state: &AppState, task_run_id: &str, agent_id: &str) -> Option<AssignmentOverrides> {
+    let all = state.assignment_overrides.lock().await;
+    all.get(&(task_run_id.to_string(), agent_id.to_string())).cloned()
+}
+
+/// The agent as the assignment sees it.
+pub fn apply(agent: &AgentConfig, overrides: &AssignmentOverrides) -> AgentConfig {
+    let mut agent = agent.clone();
+    if let Some(model) = &overrides.model {
+        agent.model = model.clone();
+    }
+    if let Some(temperature) = overrides.temperature {
+        agent.temperature = temperature;
+    }
+    if let Some(system_prompt) = &overrides.system_prompt {
+        agent.system_prompt = system_prompt.clone();
+    }
+    agent
+}
+
+/// Preamble carrying the system prompt override, if any.
+pub fn instructions(overrides: &AssignmentOverrides) -> Option<String> {
+    let system_prompt = overrides.system_prompt.as_deref()?.trim();
+    Some(format!(
+        "<assignment_instructions>\nFor this assignment, follow these instructions instead of your usual role instructions:\n\n{}\n</assignment_instructions>",
+        system_prompt
+    ))
+}
+
+/// `_meta` of the assignment's prompts, if any.
+pub fn prompt_meta(overrides: &AssignmentOverrides) -> Option<serde_json::Value> {
+    let temperature = overrides.temperature?;
+    Some(serde_json::json!({ "temperature": temperature }))
+}
+
+/// The profile's answer to a permission request, or `None` to ask as usual.
+pub fn permission_decision(profile: PermissionProfile, tool_call: Option<&serde_json::Value>) -> Option<bool> {
+    match profile {
+        PermissionProfile::Ask => None,
+        PermissionProfile::AutoApprove => Some(true),
+        PermissionProfile::ReadOnly => {
+            let kind = tool_call.and_then(|t| t.get("kind")).and_then(|k| k.as_str());
+            Some(matches!(kind, Some("read" | "search" | "think" | "fetch")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn read_only_profile_rejects_edits_and_commands() {
+        let read = json!({ "kind": "read", "title": "Read src/lib.rs" });
+        let edit = json!({ "kind": "edit", "title": "Edit src/lib.rs" });
+        let git = json!({ "kind": "execute", "rawInput": { "command": "git status" } });
+        assert_eq!(permission_decision(PermissionProfile::ReadOnly, Some(&read)), Some(true));
+        assert_eq!(permission_decision(PermissionProfile::ReadOnly, Some(&edit)), Some(false));
+        assert_eq!(permission_decision(PermissionProfile::ReadOnly, Some(&git)), Some(false));
+        assert_eq!(permission_decision(PermissionProfile::ReadOnly, None), Some(false));
+        assert_eq!(permission_decision(PermissionProfile::AutoApprove, Some(&git)), Some(true));
+        assert_eq!(permission_decision(PermissionProfile::Ask, Some(&read)), None);
+    }
+
+    #[test]
+    fn overrides_parse_from_plan_json_and_validate() {
+        let overrides: AssignmentOverrides = serde_json::from_value(json!({
+            "model": "claude-haiku",
+            "temperature": 0.2,
+            "permission_profile": "read_only",
+        }))
+        .unwrap();
+        assert_eq!(overrides.permission_profile, Some(PermissionProfile::ReadOnly));
+        assert!(overrides.validate().is_ok());
+        assert_eq!(prompt_meta(&overrides), Some(json!({ "temperature": 0.2 })));
+        assert_eq!(instructions(&overrides), None);
+
+        let hot = AssignmentOverrides { temperature: Some(3.0), ..Default::default() };
+        assert!(hot.validate().is_err());
+        let blank = AssignmentOverrides { system_prompt: Some("  ".into()), ..Default::default() };
+        assert!(blank.validate().is_err());
+        assert!(AssignmentOverrides::default().is_empty());
+    }
+}
@@ -53,13 +53,16 @@ const ADAPTER_LIB_JS: &str = include_str!("../../resources/claude-code-acp/lib.j
 ///
 /// Failures are logged as warnings and do **not** block application startup.
 pub async fn ensure_builtin_deployed() {
-    let adapter_dir = get_builtin_adapter_dir();
-
-    if let Err(e) = deploy_builtin(&adapter_dir).await {
+    if let Err(e) = deploy().await {
         log::warn!("Built-in agent deployment failed (non-fatal): {}", e);
     }
 }
 
+/// Deploy the built-in agent, returning why it failed if it did.
+pub async fn deploy() -> Result<(), String> {
+    deploy_builtin(&get_builtin_adapter_dir()).await
+}
+
 /// Return a `DiscoveredAgent` for the built-in agent.
 ///
 /// `available` is `true` when `node_modules/.bin/claude-code-acp` exists on
@@ -1,6 +1,7 @@
 use serde_json::json;
 
 use crate::acp::manager::AgentProcess;
+use crate::acp::prompt_content::{self, PromptCapabilities};
 use crate::acp::transport;
 use crate::error::{AppError, AppResult};
 
@@ -70,10 +71,11 @@ pub async fn initialize_agent(process: &mut AgentProcess) -> AppResult<serde_jso
                         attempt + 1
                     );
                 }
+                process.prompt_capabilities = PromptCapabilities::from_initialize(&response);
                 return Ok(response);
             }
             Err(e) => {
-                let is_timeout = matches!(&e, AppError::Transport(msg) if msg.contains("Timeout"));
+                let is_timeout = matches!(&e, AppError::Timeout(_));
                 if is_timeout && attempt < INIT_MAX_RETRIES {
                     log::warn!(
                         "Agent initialization timed out (attempt {}/{}): {}",
@@ -532,16 +534,55 @@ pub async fn send_prompt(
     acp_session_id: &str,
     text: &str,
     request_id: i64,
+) -> AppResult<()> {
+    send_prompt_with_meta(process, acp_session_id, text, request_id, None).await
+}
+
+/// Send a prompt with `_meta` for agents that read extra settings from it.
+pub async fn send_prompt_with_meta(
+    process: &mut AgentProcess,
+    acp_session_id: &str,
+    text: &str,
+    request_id: i64,
+    meta: Option<&serde_json::Value>,
+) -> AppResult<()> {
+    send_prompt_content(process, acp_session_id, vec![prompt_content::text(text)], request_id, meta).await
+}
+
+/// Send a prompt of ACP content blocks (text, images, resources).
+pub async fn send_prompt_content(
+    process: &mut AgentProcess,
+    acp_session_id: &str,
+    content: Vec<serde_json::Value>,
+    request_id: i64,
+    meta: Option<&serde_json::Value>,
+) -> AppResult<()> {
+    let mut params = json!({
+        "sessionId": acp_session_id,
+        "prompt": content
+    });
+    if let Some(meta) = meta {
+        params["_meta"] = meta.clone();
+    }
+    let req = transport::build_request(request_id, "session/prompt", Some(params));
+
+    transport::send_message(process, &req).await
+}
+
+/// Switch the model of a session. The agent answers with a response to
+/// `request_id`, an error if it cannot switch.
+pub async fn set_model(
+    process: &mut AgentProcess,
+    acp_session_id: &str,
+    model_id: &str,
+    request_id: i64,
 ) -> AppResult<()> {
     let req = transport::build_request(
         request_id,
-        "session/prompt",
+        "session/set_model",
         Some(json!({
             "sessionId": acp_session_id,
-            "prompt": [{
-                "type": "text",
-                "text": text
-            }]
+            "modelId": model_id
         })),
     );
 
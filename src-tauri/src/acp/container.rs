@@ -0,0 +1,238 @@
+//! Docker container runtime for agents.
+//!
+//! An agent with `container_image` set runs its configured ACP command in a
+//! fresh container of that image instead of directly on this machine. The
+//! workspace is bind-mounted at its own path so the `cwd` sent with
+//! `session/new` is valid inside the container; it is read-only unless the
+//! agent's `container_workspace_access` is `read_write`. The scratch
+//! directory of an isolated run is always writable. Images are pulled on
+//! first use. A container is removed together with the process running it,
+//! so orchestration cleanup by process key removes a run's containers, and
+//! containers an earlier app instance left behind are removed on startup.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+
+use crate::acp::discovery;
+use crate::error::{AppError, AppResult};
+use crate::models::agent::AgentConfig;
+
+pub const READ_ONLY: &str = "read_only";
+pub const READ_WRITE: &str = "read_write";
+
+/// Label holding the pid of the app instance that started a container.
+const APP_PID_LABEL: &str = "agent-hub.app-pid";
+const AGENT_LABEL: &str = "agent-hub.agent-id";
+
+/// How an agent's container is run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerSpec {
+    pub image: String,
+    pub workspace_writable: bool,
+}
+
+impl ContainerSpec {
+    /// The agent's container settings, if it runs in one.
+    pub fn of(agent: &AgentConfig) -> Option<Self> {
+        let image = agent.container_image.as_deref().map(str::trim).filter(|i| !i.is_empty())?;
+        Some(Self {
+            image: image.to_string(),
+            workspace_writable: agent.container_workspace_access == READ_WRITE,
+        })
+    }
+}
+
+/// What a container mounts and runs.
+pub struct RunOptions<'a> {
+    pub name: &'a str,
+    pub agent_id: &'a str,
+    pub app_pid: u32,
+    pub workspace: &'a str,
+    pub scratch: Option<&'a str>,
+    /// `uid:gid` to run as, so files written to the workspace keep its owner
+    pub user: Option<String>,
+}
+
+/// A container name unique to one spawn of the agent.
+pub fn container_name(agent_id: &str) -> String {
+    let agent: String = agent_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .take(12)
+        .collect();
+    format!("agent-hub-{}-{}", agent, &uuid::Uuid::new_v4().simple().to_string()[..8])
+}
+
+/// Arguments for `docker` that run `command` in a new container with stdin
+/// attached. Environment variables are passed by name only; docker copies
+/// their values from its own environment, which keeps them off the command line.
+pub fn run_args(
+    spec: &ContainerSpec,
+    options: &RunOptions,
+    command: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+) -> Vec<String> {
+    let mut docker = vec![
+        "run".to_string(),
+        "--rm".into(),
+        "-i".into(),
+        "--init".into(),
+        "--name".into(),
+        options.name.into(),
+        "--label".into(),
+        format!("{}={}", APP_PID_LABEL, options.app_pid),
+        "--label".into(),
+        format!("{}={}", AGENT_LABEL, options.agent_id),
+    ];
+    let access = if spec.workspace_writable { "rw" } else { "ro" };
+    docker.extend(["-v".into(), format!("{}:{}:{}", options.workspace, options.workspace, access)]);
+    docker.extend(["-w".into(), options.workspace.into()]);
+    if let Some(scratch) = options.scratch {
+        docker.extend(["-v".into(), format!("{}:{}:rw", scratch, scratch)]);
+    }
+    if let Some(user) = &options.user {
+        docker.extend(["--user".into(), user.clone()]);
+    }
+    let mut keys: Vec<_> = env.keys().collect();
+    keys.sort();
+    for key in keys {
+        docker.extend(["-e".into(), key.clone()]);
+    }
+    docker.push(spec.image.clone());
+    docker.push(command.into());
+    docker.extend(args.iter().cloned());
+    docker
+}
+
+/// Owner of a directory as `uid:gid`.
+pub fn owner(path: &str) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(format!("{}:{}", metadata.uid(), metadata.gid()))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+fn docker() -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("docker");
+    cmd.env("PATH", discovery::get_enriched_path()).stdin(Stdio::null());
+    cmd
+}
+
+/// Pull the image unless it is already present.
+pub async fn ensure_image(image: &str) -> AppResult<()> {
+    let present = docker()
+        .args(["image", "inspect", image])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| AppError::Acp(format!("Failed to run docker: {e}")))?
+        .success();
+    if present {
+        return Ok(());
+    }
+    log::info!("Pulling container image {}", image);
+    let output = docker()
+        .args(["pull", image])
+        .output()
+        .await
+        .map_err(|e| AppError::Acp(format!("Failed to run docker: {e}")))?;
+    if !output.status.success() {
+        return Err(AppError::Acp(format!(
+            "Failed to pull container image {}: {}",
+            image,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    log::info!("Pulled container image {}", image);
+    Ok(())
+}
+
+/// Remove a container, stopping it first if it still runs.
+pub async fn remove(name: &str) {
+    match docker().args(["rm", "-f", name]).output().await {
+        Ok(output) if output.status.success() => log::info!("Removed agent container {}", name),
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            // --rm already removed it when the agent exited
+            if !stderr.contains("No such container") {
+                log::warn!("Failed to remove agent container {}: {}", name, stderr.trim());
+            }
+        }
+        Err(e) => log::warn!("Failed to run docker to remove container {}: {}", name, e),
+    }
+}
+
+/// Remove containers started by app instances that are no longer running.
+/// Returns how many were removed; without docker there is nothing to do.
+pub fn remove_orphans(is_running: impl Fn(u32) -> bool) -> usize {
+    let format = format!("{{{{.ID}}}} {{{{.Label \"{}\"}}}}", APP_PID_LABEL);
+    let filter = format!("label={}", APP_PID_LABEL);
+    let Ok(output) = std::process::Command::new("docker")
+        .env("PATH", discovery::get_enriched_path())
+        .args(["ps", "-a", "--filter", &filter, "--format", &format])
+        .stdin(Stdio::null())
+        .output()
+    else {
+        return 0;
+    };
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let mut removed = 0;
+    for (id, pid) in listing.lines().filter_map(|l| l.split_once(' ')) {
+        let Ok(pid) = pid.trim().parse::<u32>() else { continue };
+        if pid == std::process::id() || is_running(pid) {
+            continue;
+        }
+        let status = std::process::Command::new("docker")
+            .env("PATH", discovery::get_enriched_path())
+            .args(["rm", "-f", id])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        if status.is_ok_and(|s| s.success()) {
+            log::info!("[Reaper] Removed container {} left behind by app instance {}", id, pid);
+            removed += 1;
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_the_command_with_the_workspace_mounted() {
+        let spec = ContainerSpec { image: "node:20".into(), workspace_writable: false };
+        let options = RunOptions {
+            name: "agent-hub-a1-0000",
+            agent_id: "a1",
+            app_pid: 42,
+            workspace: "/work/repo",
+            scratch: Some("/out/run/scratch"),
+            user: Some("1000:1000".into()),
+        };
+        let env = HashMap::from([("API_KEY".to_string(), "secret".to_string())]);
+        let args = run_args(&spec, &options, "npx", &["agent-acp".into()], &env).join(" ");
+        assert_eq!(
+            args,
+            "run --rm -i --init --name agent-hub-a1-0000 --label agent-hub.app-pid=42 --label agent-hub.agent-id=a1 \
+             -v /work/repo:/work/repo:ro -w /work/repo -v /out/run/scratch:/out/run/scratch:rw --user 1000:1000 \
+             -e API_KEY node:20 npx agent-acp"
+        );
+        assert!(!args.contains("secret"));
+
+        let writable = ContainerSpec { workspace_writable: true, ..spec };
+        assert!(run_args(&writable, &options, "agent", &[], &HashMap::new()).contains(&"/work/repo:/work/repo:rw".to_string()));
+        assert!(container_name("a1/../x").starts_with("agent-hub-a1x-"));
+    }
+}
@@ -0,0 +1,135 @@
+//! Parts of dependency outputs passed to later assignments.
+//!
+//! An assignment's prompt carries the output of each assignment it depends
+//! on. The planner can name the parts a downstream assignment needs in
+//! `dependency_sections`, by the dependency's agent ID: keys or dotted key
+//! paths (`findings.0.title`) of a JSON output, read from the whole output
+//! or its first fenced `json` block, or headings of a Markdown output. Only
+//! those parts are passed on, which keeps prompts small in long chains.
+//! When none of them is found the full output is passed, so a wrong guess
+//! costs tokens rather than context.
+
+use serde_json::Value;
+
+use crate::models::task_run::PlannedAssignment;
+
+/// The parts of `dep_id`'s output the assignment asked for; empty for all.
+pub fn sections<'a>(planned: &'a PlannedAssignment, dep_id: &str) -> &'a [String] {
+    planned.dependency_sections.get(dep_id).map_or(&[], Vec::as_slice)
+}
+
+/// The output of a dependency as it goes in a downstream prompt.
+pub fn input_part(dep_name: &str, output: &str, sections: &[String]) -> String {
+    if !sections.is_empty() {
+        if let Some((found, selected)) = select(output, sections) {
+            log::info!(
+                "Passing {} of {} chars of {}'s output ({})",
+                selected.len(), output.len(), dep_name, found.join(", ")
+            );
+            return format!("\n--- Output from {dep_name} (only: {}) ---\n{selected}", found.join(", "));
+        }
+        log::info!("None of [{}] found in {}'s output; passing all of it", sections.join(", "), dep_name);
+    }
+    format!("\n--- Output from {dep_name} ---\n{output}")
+}
+
+/// The sections found in `output` and their text, or None if none is.
+fn select<'a>(output: &str, sections: &'a [String]) -> Option<(Vec<&'a str>, String)> {
+    let mut found = Vec::new();
+    let selected = if let Some(value) = json_value(output) {
+        let mut picked = serde_json::Map::new();
+        for section in sections {
+            if let Some(part) = json_path(&value, section.trim()) {
+                found.push(section.as_str());
+                picked.insert(section.trim().to_string(), part.clone());
+            }
+        }
+        serde_json::to_string_pretty(&Value::Object(picked)).unwrap_or_default()
+    } else {
+        let mut parts = Vec::new();
+        for section in sections {
+            if let Some(part) = markdown_section(output, section) {
+                found.push(section.as_str());
+                parts.push(part.trim_end());
+            }
+        }
+        parts.join("\n\n")
+    };
+    (!found.is_empty()).then_some((found, selected))
+}
+
+/// The output as JSON: all of it, or its first fenced `json` block.
+fn json_value(output: &str) -> Option<Value> {
+    let parsed = |text: &str| serde_json::from_str::<Value>(text.trim()).ok().filter(|v| v.is_object() || v.is_array());
+    parsed(output).or_else(|| {
+        let start = output.find("```json")? + "```json".len();
+        let end = output[start..].find("```")? + start;
+        parsed(&output[start..end])
+    })
+}
+
+/// The value at a dotted path; numeric segments index arrays.
+fn json_path<'v>(value: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.').filter(|s| !s.is_empty()).try_fold(value, |value, segment| match value {
+        Value::Object(map) => map.get(segment),
+        Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+/// Level and title of a Markdown heading line.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let title = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then(|| (level, title.trim().trim_end_matches('#').trim()))
+}
+
+/// The section under the heading titled `title`, with its sub-sections, up
+/// to the next heading of the same or a higher level.
+fn markdown_section<'o>(output: &'o str, title: &str) -> Option<&'o str> {
+    let title = title.trim().trim_start_matches('#').trim();
+    let mut offset = 0;
+    let mut start: Option<(usize, usize)> = None;
+    for line in output.split_inclusive('\n') {
+        if let Some((level, text)) = heading(line.trim_end()) {
+            match start {
+                Some((begin, open)) if level <= open => return Some(&output[begin..offset]),
+                None if text.eq_ignore_ascii_case(title) => start = Some((offset, level)),
+                _ => {}
+            }
+        }
+        offset += line.len();
+    }
+    start.map(|(begin, _)| &output[begin..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_keys_of_json_outputs() {
+        let output = "Here are the results:\n```json\n{\"summary\": \"3 issues\", \"findings\": [{\"title\": \"XSS\"}], \"raw\": \"...\"}\n```";
+        let sections = ["summary".to_string(), "findings.0.title".into(), "missing".into()];
+        let (found, selected) = select(output, &sections).unwrap();
+        assert_eq!(found, ["summary", "findings.0.title"]);
+        assert_eq!(
+            serde_json::from_str::<Value>(&selected).unwrap(),
+            serde_json::json!({ "summary": "3 issues", "findings.0.title": "XSS" })
+        );
+        assert!(select("{\"a\": 1}", &["b".into()]).is_none());
+    }
+
+    #[test]
+    fn selects_markdown_sections_and_falls_back_to_the_full_output() {
+        let output = "# Report\nIntro\n## API\nGET /users\n### Errors\n404\n## Notes\nLong notes\n";
+        assert_eq!(markdown_section(output, "api"), Some("## API\nGET /users\n### Errors\n404\n"));
+        assert_eq!(markdown_section(output, "## Notes"), Some("## Notes\nLong notes\n"));
+        assert_eq!(markdown_section(output, "Missing"), None);
+
+        let part = input_part("Researcher", output, &["Errors".into(), "Notes".into()]);
+        assert_eq!(part, "\n--- Output from Researcher (only: Errors, Notes) ---\n### Errors\n404\n\n## Notes\nLong notes");
+        let full = input_part("Researcher", output, &["Missing".into()]);
+        assert_eq!(full, format!("\n--- Output from Researcher ---\n{output}"));
+    }
+}
@@ -0,0 +1,192 @@
+//! Line-based unified diff, used to preview agent file writes.
+
+/// Lines of unchanged context around each hunk.
+const CONTEXT_LINES: usize = 3;
+
+/// Above this many LCS table cells the changed region is shown as a full
+/// replacement instead of a minimal diff.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DiffOp {
+    /// Line `a` of the old text equals line `b` of the new text
+    Equal(usize, usize),
+    /// Line of the old text removed
+    Delete(usize),
+    /// Line of the new text added
+    Insert(usize),
+}
+
+/// Produce a unified diff of `old` → `new` for `path`.
+/// Returns an empty string when the texts have the same lines.
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&a, &b);
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Group changes whose separating context would overlap into one hunk
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &i in &changes {
+        match groups.last_mut() {
+            Some((_, last)) if i - *last <= 2 * CONTEXT_LINES + 1 => *last = i,
+            _ => groups.push((i, i)),
+        }
+    }
+
+    // Old/new line position before each op
+    let mut a_pos = Vec::with_capacity(ops.len() + 1);
+    let mut b_pos = Vec::with_capacity(ops.len() + 1);
+    let (mut ai, mut bi) = (0usize, 0usize);
+    for op in &ops {
+        a_pos.push(ai);
+        b_pos.push(bi);
+        match op {
+            DiffOp::Equal(..) => {
+                ai += 1;
+                bi += 1;
+            }
+            DiffOp::Delete(_) => ai += 1,
+            DiffOp::Insert(_) => bi += 1,
+        }
+    }
+    a_pos.push(ai);
+    b_pos.push(bi);
+
+    let mut out = format!("--- a/{path}\n+++ b/{path}\n");
+    for (first, last) in groups {
+        let start = first.saturating_sub(CONTEXT_LINES);
+        let end = (last + CONTEXT_LINES + 1).min(ops.len());
+        let old_len = a_pos[end] - a_pos[start];
+        let new_len = b_pos[end] - b_pos[start];
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(a_pos[start], old_len),
+            hunk_range(b_pos[start], new_len)
+        ));
+        for op in &ops[start..end] {
+            match *op {
+                DiffOp::Equal(i, _) => out.push_str(&format!(" {}\n", a[i])),
+                DiffOp::Delete(i) => out.push_str(&format!("-{}\n", a[i])),
+                DiffOp::Insert(j) => out.push_str(&format!("+{}\n", b[j])),
+            }
+        }
+    }
+    out
+}
+
+/// Format a hunk range; an empty range points at the line before it.
+fn hunk_range(start: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{},0", start)
+    } else {
+        format!("{},{}", start + 1, len)
+    }
+}
+
+/// Compute a line edit script from the longest common subsequence.
+fn diff_lines(a: &[&str], b: &[&str]) -> Vec<DiffOp> {
+    // Strip the common prefix and suffix so the LCS table only covers the changed region
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+
+    let mut ops: Vec<DiffOp> = (0..prefix).map(|i| DiffOp::Equal(i, i)).collect();
+
+    let (n, m) = (a_mid.len(), b_mid.len());
+    if (n + 1).saturating_mul(m + 1) > MAX_LCS_CELLS {
+        ops.extend((0..n).map(|i| DiffOp::Delete(prefix + i)));
+        ops.extend((0..m).map(|j| DiffOp::Insert(prefix + j)));
+    } else {
+        // lcs[i][j] = LCS length of a_mid[i..] and b_mid[j..]
+        let width = m + 1;
+        let mut lcs = vec![0u32; (n + 1) * width];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i * width + j] = if a_mid[i] == b_mid[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if a_mid[i] == b_mid[j] {
+                ops.push(DiffOp::Equal(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                ops.push(DiffOp::Delete(prefix + i));
+                i += 1;
+            } else {
+                ops.push(DiffOp::Insert(prefix + j));
+                j += 1;
+            }
+        }
+        ops.extend((i..n).map(|i| DiffOp::Delete(prefix + i)));
+        ops.extend((j..m).map(|j| DiffOp::Insert(prefix + j)));
+    }
+
+    let a_suffix_start = a.len() - suffix;
+    let b_suffix_start = b.len() - suffix;
+    ops.extend((0..suffix).map(|k| DiffOp::Equal(a_suffix_start + k, b_suffix_start + k)));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_text_has_no_diff() {
+        assert_eq!(unified_diff("a.txt", "one\ntwo\n", "one\ntwo\n"), "");
+    }
+
+    #[test]
+    fn test_single_line_change() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n";
+        let diff = unified_diff("n.txt", old, new);
+        assert_eq!(
+            diff,
+            "--- a/n.txt\n+++ b/n.txt\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+        );
+    }
+
+    #[test]
+    fn test_new_file() {
+        let diff = unified_diff("new.rs", "", "fn main() {}\n");
+        assert_eq!(diff, "--- a/new.rs\n+++ b/new.rs\n@@ -0,0 +1,1 @@\n+fn main() {}\n");
+    }
+
+    #[test]
+    fn test_distant_changes_make_separate_hunks() {
+        let old: String = (1..=20).map(|i| format!("{i}\n")).collect();
+        let new: String = (1..=20)
+            .map(|i| match i {
+                2 => "two\n".to_string(),
+                19 => "nineteen\n".to_string(),
+                _ => format!("{i}\n"),
+            })
+            .collect();
+        let diff = unified_diff("f", &old, &new);
+        assert_eq!(diff.matches("@@ -").count(), 2);
+    }
+}
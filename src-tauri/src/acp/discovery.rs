@@ -300,30 +300,53 @@ pub async fn get_registry_entry(id: &str) -> Option<RegistryEntry> {
 /// For npx agents, this matches the binary name in the package (e.g. "gemini" matches package containing "gemini-cli").
 /// For binary agents, this matches the cmd field basename.
 pub async fn get_registry_entry_by_command(command: &str) -> Option<RegistryEntry> {
+    let registry = fetch_registry().await.ok()?;
+    find_entry_by_command(&registry, command).cloned()
+}
+
+fn entry_matches_command(entry: &RegistryEntry, basename: &str) -> bool {
+    match &entry.distribution {
+        Distribution::Npx(npx) => {
+            // Match against the binary name extracted from the package specifier
+            let pkg_basename = extract_npx_binary_name(&npx.package);
+            pkg_basename == basename || entry.id == basename
+        }
+        Distribution::Binary(platforms) => {
+            // Match against cmd basename from any platform
+            entry.id == basename
+                || platforms.values().any(|t| {
+                    let cmd_base = t.cmd.trim_start_matches("./");
+                    let cmd_base = cmd_base.strip_suffix(".exe").unwrap_or(cmd_base);
+                    cmd_base == basename
+                })
+        }
+    }
+}
+
+/// The registry as last loaded, from memory or the local cache; never
+/// fetches from the CDN.
+pub async fn cached_registry() -> Option<RegistryFile> {
+    if let Some(cached) = registry_mutex().lock().await.as_ref() {
+        return Some(cached.clone());
+    }
+    load_local_cache().await.ok()
+}
+
+/// Version of a registry agent's adapter installed locally, for NPX agents.
+pub fn installed_adapter_version(entry: &RegistryEntry) -> Option<String> {
+    match &entry.distribution {
+        Distribution::Npx(npx) => read_local_adapter_version(&entry.id, &npx.package),
+        Distribution::Binary(_) => None,
+    }
+}
+
+/// The entry of `registry` for an agent command.
+pub fn find_entry_by_command<'a>(registry: &'a RegistryFile, command: &str) -> Option<&'a RegistryEntry> {
     let basename = std::path::Path::new(command)
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or(command);
-
-    let registry = fetch_registry().await.ok()?;
-    registry.agents.into_iter().find(|entry| {
-        match &entry.distribution {
-            Distribution::Npx(npx) => {
-                // Match against the binary name extracted from the package specifier
-                let pkg_basename = extract_npx_binary_name(&npx.package);
-                pkg_basename == basename || entry.id == basename
-            }
-            Distribution::Binary(platforms) => {
-                // Match against cmd basename from any platform
-                entry.id == basename
-                    || platforms.values().any(|t| {
-                        let cmd_base = t.cmd.trim_start_matches("./");
-                        let cmd_base = cmd_base.strip_suffix(".exe").unwrap_or(cmd_base);
-                        cmd_base == basename
-                    })
-            }
-        }
-    })
+    registry.agents.iter().find(|entry| entry_matches_command(entry, basename))
 }
 
 /// Get environment variables from a registry entry for the current platform.
@@ -541,7 +564,7 @@ pub(crate) fn get_enriched_path() -> String {
 }
 
 /// Try to resolve the full path of a command using an enriched PATH.
-fn resolve_command(cmd: &str) -> Option<String> {
+pub(crate) fn resolve_command(cmd: &str) -> Option<String> {
     resolve_command_with_path(cmd, &get_enriched_path())
 }
 
@@ -0,0 +1,138 @@
+//! Detection of a run started twice in quick succession.
+//!
+//! A double click or a chat tool redelivering a message asks for the same run
+//! twice. A start whose workspace and normalized prompt match a run started
+//! less than `duplicate_run_window_secs` ago that is still queued or running
+//! gets that run back, flagged as a duplicate, instead of a second one. A
+//! start claims its prompt before its task run is created, so two starts
+//! arriving together cannot both get through.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::config;
+use crate::db::task_run_repo;
+use crate::error::{AppError, AppResult};
+use crate::models::task_run::StartedTaskRun;
+use crate::state::AppState;
+
+struct RecentStart {
+    task_run_id: String,
+    at: Instant,
+    /// Handed to the intake queue; until then the run counts as active
+    queued: bool,
+}
+
+#[derive(Default)]
+pub struct RecentStarts {
+    starts: HashMap<String, RecentStart>,
+}
+
+/// Case and whitespace differences do not make a prompt a different one.
+pub fn normalize_prompt(prompt: &str) -> String {
+    prompt.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn key(workspace_id: Option<&str>, prompt: &str) -> String {
+    format!("{}\n{}", workspace_id.unwrap_or_default(), normalize_prompt(prompt))
+}
+
+impl RecentStarts {
+    /// Claim the key for `task_run_id`, or return the live run that holds it.
+    fn claim(
+        &mut self,
+        key: String,
+        task_run_id: &str,
+        now: Instant,
+        window: Duration,
+        is_active: impl Fn(&str) -> bool,
+    ) -> Option<String> {
+        self.starts.retain(|_, s| now.duration_since(s.at) < window);
+        if let Some(start) = self.starts.get(&key) {
+            if !start.queued || is_active(&start.task_run_id) {
+                return Some(start.task_run_id.clone());
+            }
+        }
+        let start = RecentStart { task_run_id: task_run_id.to_string(), at: now, queued: false };
+        self.starts.insert(key, start);
+        None
+    }
+
+    /// Mark the claim as queued, or drop it when the run was not created.
+    fn settle(&mut self, key: &str, task_run_id: &str, created: bool) {
+        if !self.starts.get(key).is_some_and(|s| s.task_run_id == task_run_id) {
+            return;
+        }
+        if created {
+            if let Some(start) = self.starts.get_mut(key) {
+                start.queued = true;
+            }
+        } else {
+            self.starts.remove(key);
+        }
+    }
+}
+
+/// Claim a prompt for a run about to be created. Returns the id of the run
+/// the start duplicates, if any; otherwise the caller must `settle` the claim.
+pub async fn claim(state: &AppState, workspace_id: Option<&str>, prompt: &str, task_run_id: &str) -> Option<String> {
+    let window = config::current(state).duplicate_run_window_secs;
+    if window == 0 {
+        return None;
+    }
+    let mut starts = state.recent_run_starts.lock().await;
+    let active = state.active_task_runs.lock().await;
+    starts.claim(key(workspace_id, prompt), task_run_id, Instant::now(), Duration::from_secs(window), |id| {
+        active.contains_key(id)
+    })
+}
+
+/// Record whether the claimed run was created and queued.
+pub async fn settle(state: &AppState, workspace_id: Option<&str>, prompt: &str, task_run_id: &str, created: bool) {
+    let mut starts = state.recent_run_starts.lock().await;
+    starts.settle(&key(workspace_id, prompt), task_run_id, created);
+}
+
+/// The run a duplicate start gets back.
+pub async fn existing(state: &AppState, task_run_id: String) -> AppResult<StartedTaskRun> {
+    log::info!("Start request duplicates task run {}, returning it", task_run_id);
+    let state = state.clone();
+    let task_run = tokio::task::spawn_blocking(move || task_run_repo::get_task_run(&state, &task_run_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .map_err(|e| match e {
+            // The first start is still creating its run
+            AppError::NotFound(_) => AppError::InvalidRequest("The same run is already being started".into()),
+            e => e,
+        })?;
+    Ok(StartedTaskRun { task_run, duplicate: true })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_the_live_run_for_a_repeated_prompt() {
+        let mut starts = RecentStarts::default();
+        let window = Duration::from_secs(10);
+        let t0 = Instant::now();
+        let k = || key(Some("ws"), "  Fix the   BUG ");
+        assert_eq!(k(), key(Some("ws"), "fix the bug"));
+
+        assert_eq!(starts.claim(k(), "r1", t0, window, |_| false), None);
+        // Not queued yet: a concurrent start is a duplicate
+        assert_eq!(starts.claim(k(), "r2", t0, window, |_| false).as_deref(), Some("r1"));
+        starts.settle(&k(), "r1", true);
+        assert_eq!(starts.claim(k(), "r2", t0, window, |id| id == "r1").as_deref(), Some("r1"));
+        assert_eq!(starts.claim(key(None, "fix the bug"), "r3", t0, window, |_| true), None);
+
+        // Ended, or outside the window: a new run
+        assert_eq!(starts.claim(k(), "r4", t0, window, |_| false), None);
+        starts.settle(&k(), "r4", true);
+        assert_eq!(starts.claim(k(), "r5", t0 + window, window, |_| true), None);
+
+        starts.settle(&k(), "r5", false);
+        assert!(!starts.starts.contains_key(&k()));
+    }
+}
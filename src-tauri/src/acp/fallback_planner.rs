@@ -0,0 +1,346 @@
+//! Deterministic planner used when a workspace has no Control Hub.
+//!
+//! The prompt is split into steps (list items, or clauses joined by "then"
+//! or ";"), every enabled agent is scored against each step by how many of
+//! the step's keywords appear in its skills, name and description, and each
+//! step goes to the best-scoring agent. Steps for the same agent are merged,
+//! so a trivial request becomes a single assignment. No LLM is involved: the
+//! same prompt and agents always give the same plan, which keeps basic
+//! orchestration working offline.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::acp::orchestrator::resolve_agent_skills;
+use crate::db::agent_repo;
+use crate::error::{AppError, AppResult};
+use crate::i18n::{self, Locale, Msg};
+use crate::models::agent::{AgentConfig, AgentSkill};
+use crate::models::task_run::{PlannedAssignment, TaskPlan};
+use crate::state::AppState;
+
+/// Recorded as the run's `control_hub_agent_id` when this planner serves it.
+pub const BUILTIN_PLANNER_ID: &str = "builtin-planner";
+const MAX_ASSIGNMENTS: usize = 4;
+/// Score of a keyword found in a skill (id, name or task keywords) vs. elsewhere
+const SKILL_WEIGHT: u32 = 3;
+const TEXT_WEIGHT: u32 = 1;
+
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "that", "this", "from", "into", "onto", "then", "than", "are", "was", "were",
+    "will", "would", "should", "could", "can", "please", "make", "sure", "about", "also", "all", "any", "our",
+    "your", "you", "its", "use", "using", "some", "need", "needs", "want", "have", "has", "had", "not", "but",
+    "what", "when", "where", "which", "who", "how", "each", "every", "more", "most", "out", "over", "under",
+];
+
+pub fn is_builtin(hub_id: &str) -> bool {
+    hub_id == BUILTIN_PLANNER_ID
+}
+
+/// Hub to record for a new run: the workspace's Control Hub, or this planner
+/// when none is configured. Blocking.
+pub fn hub_id_for(state: &AppState, workspace_id: Option<&str>) -> AppResult<String> {
+    Ok(agent_repo::get_control_hub(state, workspace_id)?
+        .map(|hub| hub.id)
+        .unwrap_or_else(|| BUILTIN_PLANNER_ID.to_string()))
+}
+
+/// Strip common suffixes so "tests", "testing" and "tested" match "test".
+fn stem(word: &str) -> String {
+    for suffix in ["ing", "ed", "es", "s"] {
+        if let Some(base) = word.strip_suffix(suffix) {
+            if base.chars().count() >= 3 {
+                return base.to_string();
+            }
+        }
+    }
+    word.to_string()
+}
+
+/// Distinct lowercase, stemmed words of at least three letters, minus stopwords.
+fn keywords(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3 && !STOPWORDS.contains(w))
+        .map(stem)
+        .filter(|w| seen.insert(w.clone()))
+        .collect()
+}
+
+fn strip_list_marker(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if let Some(rest) = line.strip_prefix(['-', '*', '•']) {
+        return Some(rest.trim());
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(rest) = rest.strip_prefix(['.', ')']) {
+            return Some(rest.trim());
+        }
+    }
+    None
+}
+
+/// The prompt's steps: its list items when it has two or more (text before
+/// the list joins the first), else its "then"/";"-separated clauses.
+fn split_steps(prompt: &str) -> Vec<String> {
+    let mut intro: Vec<&str> = Vec::new();
+    let mut items: Vec<String> = Vec::new();
+    for line in prompt.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        match strip_list_marker(line) {
+            Some(item) if !item.is_empty() => items.push(item.to_string()),
+            _ if items.is_empty() => intro.push(line),
+            _ => {
+                if let Some(last) = items.last_mut() {
+                    last.push(' ');
+                    last.push_str(line);
+                }
+            }
+        }
+    }
+    if items.len() >= 2 {
+        if !intro.is_empty() {
+            items[0] = format!("{} {}", intro.join(" "), items[0]);
+        }
+        return items;
+    }
+
+    let mut steps = vec![prompt.trim().replace('\n', " ")];
+    for separator in [";", ", and then ", ", then ", " and then ", " then "] {
+        steps = steps
+            .iter()
+            .flat_map(|step| {
+                let lower = step.to_lowercase();
+                if lower.len() != step.len() {
+                    // Case folding moved byte offsets; keep the step whole
+                    return vec![step.clone()];
+                }
+                let mut parts = Vec::new();
+                let mut start = 0;
+                for (pos, _) in lower.match_indices(separator) {
+                    parts.push(step[start..pos].to_string());
+                    start = pos + separator.len();
+                }
+                parts.push(step[start..].to_string());
+                parts
+            })
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+    }
+    steps
+}
+
+/// What an agent is good at, as keywords.
+struct Profile {
+    skill_words: HashSet<String>,
+    text_words: HashSet<String>,
+    /// Skill id -> its keywords
+    skills: Vec<(String, HashSet<String>)>,
+}
+
+impl Profile {
+    fn new(name: &str, description: &str, agent_skills: &[AgentSkill]) -> Self {
+        let skills: Vec<(String, HashSet<String>)> = agent_skills
+            .iter()
+            .map(|skill| {
+                let text = format!("{} {} {}", skill.id.replace(['_', '-'], " "), skill.name, skill.task_keywords.join(" "));
+                (skill.id.clone(), keywords(&text).into_iter().collect())
+            })
+            .collect();
+        let skill_words = skills.iter().flat_map(|(_, words)| words.iter().cloned()).collect();
+        let text = format!("{} {} {}", name, description, agent_skills.iter().map(|s| s.description.as_str()).collect::<Vec<_>>().join(" "));
+        let text_words = keywords(&text).into_iter().collect();
+        Self {
+            skill_words,
+            text_words,
+            skills,
+        }
+    }
+
+    /// Score for a step, the keywords that matched and the matched skill ids.
+    fn score(&self, step_words: &[String]) -> Score {
+        let mut score = 0;
+        let mut matched = Vec::new();
+        for word in step_words {
+            if self.skill_words.contains(word) {
+                score += SKILL_WEIGHT;
+                matched.push(word.clone());
+            } else if self.text_words.contains(word) {
+                score += TEXT_WEIGHT;
+                matched.push(word.clone());
+            }
+        }
+        let skill_ids = self
+            .skills
+            .iter()
+            .filter(|(_, words)| step_words.iter().any(|w| words.contains(w)))
+            .map(|(id, _)| id.clone())
+            .collect();
+        (score, matched, skill_ids)
+    }
+}
+
+/// Score, matched keywords, matched skill ids.
+type Score = (u32, Vec<String>, Vec<String>);
+
+struct StepMatch {
+    agent_index: usize,
+    step: String,
+    matched: Vec<String>,
+    skill_ids: Vec<String>,
+}
+
+/// Assign each step to the best-scoring profile; ties go to the earlier one.
+/// Steps nobody matches stay with the previous step's agent (the best match
+/// for the whole prompt for a first step).
+fn match_steps(profiles: &[Profile], prompt: &str, steps: Vec<String>) -> Vec<StepMatch> {
+    let best = |words: &[String]| {
+        profiles
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, p.score(words)))
+            .fold(None, |best: Option<(usize, Score)>, (i, s)| match &best {
+                Some((_, b)) if b.0 >= s.0 => best,
+                _ => Some((i, s)),
+            })
+            .filter(|(_, s)| s.0 > 0)
+    };
+    let fallback = best(&keywords(prompt)).map(|(i, _)| i).unwrap_or(0);
+
+    let mut matches: Vec<StepMatch> = Vec::new();
+    for step in steps {
+        let (agent_index, matched, skill_ids) = match best(&keywords(&step)) {
+            Some((i, (_, matched, skill_ids))) => (i, matched, skill_ids),
+            None => (matches.last().map(|m| m.agent_index).unwrap_or(fallback), Vec::new(), Vec::new()),
+        };
+        matches.push(StepMatch { agent_index, step, matched, skill_ids });
+    }
+    matches
+}
+
+/// A plan for `user_prompt` over the enabled, non-hub `agents`.
+pub fn plan(user_prompt: &str, agents: &[AgentConfig]) -> AppResult<TaskPlan> {
+    let candidates: Vec<&AgentConfig> = agents.iter().filter(|a| a.is_enabled && !a.is_control_hub).collect();
+    if candidates.is_empty() {
+        return Err(AppError::InvalidRequest(
+            "No enabled agents to plan with. Enable an agent or set a Control Hub.".into(),
+        ));
+    }
+    let profiles: Vec<Profile> = candidates
+        .iter()
+        .map(|a| Profile::new(&a.name, &a.description, &resolve_agent_skills(a)))
+        .collect();
+
+    // Group steps per agent in order of first appearance; the orchestrator
+    // keys outputs by agent, so each agent gets one assignment
+    let mut groups: Vec<(usize, Vec<StepMatch>)> = Vec::new();
+    for m in match_steps(&profiles, user_prompt, split_steps(user_prompt)) {
+        let slot = match groups.iter().position(|(i, _)| *i == m.agent_index) {
+            Some(slot) => slot,
+            None if groups.len() < MAX_ASSIGNMENTS => {
+                groups.push((m.agent_index, Vec::new()));
+                groups.len() - 1
+            }
+            None => groups.len() - 1,
+        };
+        groups[slot].1.push(m);
+    }
+
+    let single = groups.len() == 1;
+    let mut assignments: Vec<PlannedAssignment> = Vec::new();
+    for (order, (agent_index, steps)) in groups.iter().enumerate() {
+        let agent = candidates[*agent_index];
+        let mut matched: Vec<String> = steps.iter().flat_map(|s| s.matched.iter().cloned()).collect();
+        matched.dedup();
+        let mut skill_ids: Vec<String> = steps.iter().flat_map(|s| s.skill_ids.iter().cloned()).collect();
+        skill_ids.dedup();
+        let task_description = if single {
+            user_prompt.trim().to_string()
+        } else {
+            steps.iter().map(|s| s.step.as_str()).collect::<Vec<_>>().join("\n")
+        };
+        let selection_reason = if matched.is_empty() {
+            "No skill keywords matched; assigned by the built-in planner as the closest agent".to_string()
+        } else {
+            format!("Matched keywords: {}", matched.join(", "))
+        };
+        assignments.push(PlannedAssignment {
+            agent_id: agent.id.clone(),
+            task_description,
+            sequence_order: order as i64,
+            depends_on: assignments.last().map(|a| vec![a.agent_id.clone()]).unwrap_or_default(),
+            matched_skills: skill_ids,
+            selection_reason,
+            working_directory: None,
+            output_file: None,
+            max_tokens_out: None,
+            max_cost: None,
+            confidence: None,
+            assumptions: Vec::new(),
+            overrides: None,
+            attachments: Vec::new(),
+            dependency_sections: Default::default(),
+        });
+    }
+
+    let names: Vec<&str> = groups.iter().map(|(i, _)| candidates[*i].name.as_str()).collect();
+    Ok(TaskPlan {
+        analysis: format!(
+            "Planned without a Control Hub by matching the request against agent skills: {}.",
+            names.join(" → ")
+        ),
+        assignments,
+        isolated: false,
+    })
+}
+
+/// Run summary without a hub: each agent's output under its name.
+pub fn summarize(outputs: &HashMap<String, String>, agents: &[AgentConfig], locale: Locale) -> String {
+    let mut sections: Vec<(String, &String)> = outputs
+        .iter()
+        .map(|(id, output)| {
+            let name = agents.iter().find(|a| a.id == *id).map(|a| a.name.clone()).unwrap_or_else(|| id.clone());
+            (name, output)
+        })
+        .collect();
+    sections.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut summary = format!("{}\n", i18n::text(locale, Msg::SummaryWithoutHub));
+    for (name, output) in sections {
+        summary.push_str(&format!("\n## {}\n\n{}\n", name, output.trim()));
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skill(id: &str, name: &str, keywords: &[&str]) -> AgentSkill {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "name": name, "skill_type": "skill", "description": "", "task_keywords": keywords,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn steps_go_to_the_best_matching_agent() {
+        assert_eq!(split_steps("Write the parser; then add tests").len(), 2);
+        assert_eq!(split_steps("Fix this:\n1. parser bug\n2. docs").len(), 2);
+        assert_eq!(split_steps("Summarize the thread").len(), 1);
+
+        let profiles = vec![
+            Profile::new("Coder", "Writes code", &[skill("code_review", "Code review", &["refactor"])]),
+            Profile::new("Writer", "Writes prose", &[skill("documentation", "Documentation", &["docs", "readme"])]),
+        ];
+        let steps = split_steps("Refactor the parser, then update the README docs");
+        let matches = match_steps(&profiles, "", steps);
+        assert_eq!(matches.iter().map(|m| m.agent_index).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(matches[1].skill_ids, vec!["documentation".to_string()]);
+
+        // Nothing matches: the step stays with the previous agent
+        let matches = match_steps(&profiles, "", vec!["update docs".into(), "ship it".into()]);
+        assert_eq!(matches[1].agent_index, 1);
+    }
+}
@@ -0,0 +1,178 @@
+//! File conflict detection for parallel agents.
+//!
+//! A write is attributed to the (task run, agent) whose prompt is in flight
+//! when it happens. A second in-flight writer of the same path is a conflict
+//! and is reported with `orchestration:file_conflict`. With the
+//! `file_conflict_mode` config set to `"serialize"`, client-side writes and
+//! write permission requests also wait until the earlier writer finishes.
+
+use std::path::Path;
+
+use tauri::Emitter;
+use tokio_util::sync::CancellationToken;
+
+use crate::config;
+use crate::state::AppState;
+
+/// How long a serialized write waits for the other writer before going ahead.
+const SERIALIZE_TIMEOUT_SECS: u64 = 600;
+
+/// An agent prompt in flight within a task run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileWriter {
+    pub task_run_id: String,
+    pub agent_id: String,
+}
+
+impl FileWriter {
+    pub fn new(task_run_id: &str, agent_id: &str) -> Self {
+        Self {
+            task_run_id: task_run_id.to_string(),
+            agent_id: agent_id.to_string(),
+        }
+    }
+}
+
+pub fn is_serialize_enabled(state: &AppState) -> bool {
+    config::current(state).file_conflict_mode == "serialize"
+}
+
+/// Paths a tool call writes to, resolved against `cwd`.
+///
+/// Uses the ACP `kind` when present (edit/delete/move), otherwise the tool
+/// name. Paths come from ACP `locations` and the common raw input keys.
+pub fn tool_call_write_paths(tool_call: &serde_json::Value, cwd: &str) -> Vec<String> {
+    let is_write = match tool_call.get("kind").and_then(|k| k.as_str()) {
+        Some(kind) => matches!(kind, "edit" | "delete" | "move"),
+        None => {
+            let name = tool_call
+                .get("name")
+                .or_else(|| tool_call.get("title"))
+                .and_then(|n| n.as_str())
+                .unwrap_or("")
+                .to_lowercase();
+            name.contains("write") || name.contains("edit")
+        }
+    };
+    if !is_write {
+        return Vec::new();
+    }
+
+    let mut paths: Vec<String> = Vec::new();
+    let location_paths = tool_call
+        .get("locations")
+        .and_then(|l| l.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|loc| loc.get("path").and_then(|p| p.as_str()));
+    let input_paths = ["file_path", "path", "notebook_path"]
+        .iter()
+        .filter_map(|key| tool_call.get("rawInput").and_then(|i| i.get(*key)).and_then(|p| p.as_str()));
+
+    for path in location_paths.chain(input_paths) {
+        let resolved = resolve_path(path, cwd);
+        if !paths.contains(&resolved) {
+            paths.push(resolved);
+        }
+    }
+    paths
+}
+
+fn resolve_path(path: &str, cwd: &str) -> String {
+    let p = Path::new(path);
+    if p.is_absolute() {
+        path.to_string()
+    } else {
+        Path::new(cwd).join(p).to_string_lossy().to_string()
+    }
+}
+
+/// Record that `writer` wrote `path`, reporting every other in-flight writer
+/// of the same path as a conflict.
+pub async fn record_write(app: &tauri::AppHandle, state: &AppState, writer: &FileWriter, path: &str) {
+    let others = {
+        let mut writers = state.file_writers.lock().await;
+        let entry = writers.entry(path.to_string()).or_default();
+        if entry.contains(writer) {
+            return;
+        }
+        let others = entry.clone();
+        entry.push(writer.clone());
+        others
+    };
+
+    for other in &others {
+        emit_conflict(app, writer, other, path, "warn");
+    }
+}
+
+/// In serialize mode, wait until no other in-flight agent has written `path`.
+/// Gives up after SERIALIZE_TIMEOUT_SECS or on cancellation, so two agents
+/// waiting on each other cannot deadlock the run.
+pub async fn wait_for_path(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    writer: &FileWriter,
+    path: &str,
+    cancel_token: Option<&CancellationToken>,
+) {
+    let started = std::time::Instant::now();
+    let mut reported = false;
+    loop {
+        let holder = {
+            let writers = state.file_writers.lock().await;
+            writers
+                .get(path)
+                .and_then(|w| w.iter().find(|other| *other != writer).cloned())
+        };
+        let Some(holder) = holder else { return };
+
+        if !reported {
+            emit_conflict(app, writer, &holder, path, "serialize");
+            reported = true;
+        }
+        if started.elapsed() >= std::time::Duration::from_secs(SERIALIZE_TIMEOUT_SECS)
+            || cancel_token.is_some_and(|t| t.is_cancelled())
+        {
+            log::warn!(
+                "Agent {} stopped waiting for {} to release {}",
+                writer.agent_id,
+                holder.agent_id,
+                path
+            );
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+}
+
+/// Forget every write made by `writer`, once its prompt has finished.
+pub async fn release_writer(state: &AppState, writer: &FileWriter) {
+    let mut writers = state.file_writers.lock().await;
+    writers.retain(|_, w| {
+        w.retain(|other| other != writer);
+        !w.is_empty()
+    });
+}
+
+fn emit_conflict(app: &tauri::AppHandle, writer: &FileWriter, other: &FileWriter, path: &str, mode: &str) {
+    log::warn!(
+        "File conflict on {}: agent {} (task run {}) and agent {} (task run {})",
+        path,
+        other.agent_id,
+        other.task_run_id,
+        writer.agent_id,
+        writer.task_run_id
+    );
+    let _ = app.emit(
+        "orchestration:file_conflict",
+        &serde_json::json!({
+            "taskRunId": writer.task_run_id,
+            "path": path,
+            "agentId": writer.agent_id,
+            "otherTaskRunId": other.task_run_id,
+            "otherAgentId": other.agent_id,
+            "mode": mode,
+        }),
+    );
+}
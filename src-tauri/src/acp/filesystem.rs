@@ -1,7 +1,17 @@
 use serde_json::json;
+use tauri::Emitter;
+use tokio_util::sync::CancellationToken;
 
+use crate::acp::diff;
 use crate::acp::transport::{self, JsonRpcResponse};
+use crate::db::migrations::get_output_dir;
+use crate::config;
 use crate::error::AppResult;
+use crate::models::task_run::FileWriteReview;
+use crate::state::{AppState, PendingFileWrite};
+
+/// How long a write waits for review before it is rejected.
+const WRITE_REVIEW_TIMEOUT_SECS: u64 = 1800;
 
 /// Check if a path is allowed given the trusted working directory.
 ///
@@ -150,3 +160,160 @@ pub async fn handle_write_text_file(
         }),
     }
 }
+
+/// Whether "review writes" mode is enabled.
+pub fn is_review_writes_enabled(state: &AppState) -> bool {
+    config::current(state).review_writes
+}
+
+/// Handle fs/write_text_file in review mode: diff the new content against the
+/// current file, emit it for approval, and only write once the user approves.
+/// Every reviewed diff is kept under the run's output directory.
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_reviewed_write_text_file(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run_id: &str,
+    agent_id: &str,
+    request_id: serde_json::Value,
+    params: &serde_json::Value,
+    trusted_dir: Option<&str>,
+    cancel_token: Option<&CancellationToken>,
+) -> AppResult<JsonRpcResponse> {
+    let path = params
+        .get("path")
+        .and_then(|p| p.as_str())
+        .unwrap_or("");
+    let content = params
+        .get("content")
+        .and_then(|c| c.as_str())
+        .unwrap_or("");
+
+    if !is_path_allowed(path, trusted_dir) {
+        return Ok(error_response(
+            request_id,
+            "Access denied: path is outside the trusted working directory".into(),
+        ));
+    }
+
+    let (current, is_new_file) = match tokio::fs::read_to_string(path).await {
+        Ok(current) => (current, false),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (String::new(), true),
+        Err(e) => return Ok(error_response(request_id, format!("Failed to read file: {e}"))),
+    };
+
+    let display_path = trusted_dir
+        .and_then(|dir| std::path::Path::new(path).strip_prefix(dir).ok())
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+    let diff = diff::unified_diff(&display_path, &current, content);
+
+    // Nothing to review: same lines as the current file
+    if diff.is_empty() && !is_new_file {
+        return apply_write(request_id, path, content).await;
+    }
+
+    let review = FileWriteReview {
+        write_id: uuid::Uuid::new_v4().to_string(),
+        task_run_id: task_run_id.to_string(),
+        agent_id: agent_id.to_string(),
+        path: path.to_string(),
+        diff,
+        is_new_file,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let (tx, rx) = tokio::sync::oneshot::channel::<bool>();
+    {
+        let mut pending = state.pending_file_writes.lock().await;
+        pending.insert(review.write_id.clone(), PendingFileWrite { review: review.clone(), tx });
+    }
+
+    let _ = app.emit("orchestration:file_write_review", &json!({
+        "taskRunId": task_run_id,
+        "agentId": agent_id,
+        "writeId": review.write_id,
+        "path": review.path,
+        "diff": review.diff,
+        "isNewFile": is_new_file,
+    }));
+
+    // Unanswered, timed-out, and cancelled reviews reject the write
+    let wait = tokio::time::timeout(std::time::Duration::from_secs(WRITE_REVIEW_TIMEOUT_SECS), rx);
+    let approved = match cancel_token {
+        Some(token) => tokio::select! {
+            result = wait => matches!(result, Ok(Ok(true))),
+            _ = token.cancelled() => false,
+        },
+        None => matches!(wait.await, Ok(Ok(true))),
+    };
+    {
+        let mut pending = state.pending_file_writes.lock().await;
+        pending.remove(&review.write_id);
+    }
+
+    if let Err(e) = save_diff_artifact(&review, approved) {
+        log::warn!("Failed to save diff artifact for {}: {}", review.path, e);
+    }
+    let _ = app.emit("orchestration:file_write_resolved", &json!({
+        "taskRunId": task_run_id,
+        "agentId": agent_id,
+        "writeId": review.write_id,
+        "approved": approved,
+    }));
+
+    if approved {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            let _ = tokio::fs::create_dir_all(parent).await;
+        }
+        apply_write(request_id, path, content).await
+    } else {
+        Ok(error_response(request_id, format!("Write to {} was rejected by the user", display_path)))
+    }
+}
+
+async fn apply_write(request_id: serde_json::Value, path: &str, content: &str) -> AppResult<JsonRpcResponse> {
+    match tokio::fs::write(path, content).await {
+        Ok(()) => Ok(JsonRpcResponse {
+            jsonrpc: "2.0".into(),
+            id: Some(request_id),
+            result: Some(json!({ "success": true })),
+            error: None,
+        }),
+        Err(e) => Ok(error_response(request_id, format!("Failed to write file: {e}"))),
+    }
+}
+
+fn error_response(request_id: serde_json::Value, message: String) -> JsonRpcResponse {
+    JsonRpcResponse {
+        jsonrpc: "2.0".into(),
+        id: Some(request_id),
+        result: None,
+        error: Some(transport::JsonRpcError {
+            code: -32000,
+            message,
+            data: None,
+        }),
+    }
+}
+
+/// Keep a reviewed diff in the run's artifacts: output/<task_run_id>/diffs/NNN-<file>.diff
+/// The header is read back by `run_changes`.
+fn save_diff_artifact(review: &FileWriteReview, approved: bool) -> std::io::Result<()> {
+    let dir = get_output_dir().join(&review.task_run_id).join("diffs");
+    std::fs::create_dir_all(&dir)?;
+
+    let seq = std::fs::read_dir(&dir)?.count() + 1;
+    let file_name = std::path::Path::new(&review.path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".into());
+    let header = format!(
+        "# path: {}\n# agent: {}\n# status: {}\n# new_file: {}\n\n",
+        review.path,
+        review.agent_id,
+        if approved { "approved" } else { "rejected" },
+        review.is_new_file
+    );
+    std::fs::write(dir.join(format!("{:03}-{}.diff", seq, file_name)), header + &review.diff)
+}
@@ -0,0 +1,130 @@
+//! Stopping agent processes nobody prompts any more.
+//!
+//! Orchestration processes end with their run, but an agent the user chats
+//! with or a chat tool's Control Hub keeps its process until the app exits.
+//! A periodic pass stops such a process once it has gone the agent's
+//! `idle_shutdown_minutes`, or the app-wide `agent_idle_shutdown_minutes`,
+//! without a request. Its ACP sessions end with it: the next chat message
+//! or chat tool reply spawns the agent again and starts a new session, which
+//! a chat seeds with the session's summary.
+
+use std::time::Duration;
+
+use crate::acp::{agent_terminal, manager, orchestrator, session_summary};
+use crate::config;
+use crate::db::agent_repo;
+use crate::error::AppError;
+use crate::events;
+use crate::state::AppState;
+use crate::telemetry;
+
+const CHECK_INTERVAL_SECS: u64 = 60;
+
+/// How long the agent's process may go unused; None keeps it running.
+/// `agent_minutes` overrides `default_minutes`, and 0 turns stopping off.
+fn idle_limit(agent_minutes: Option<i64>, default_minutes: u64) -> Option<Duration> {
+    let minutes = agent_minutes.map_or(default_minutes, |m| m.max(0) as u64);
+    (minutes > 0).then(|| Duration::from_secs(minutes * 60))
+}
+
+/// Idle limit of the agent; the app-wide one for agents that are gone.
+async fn agent_idle_limit(state: &AppState, agent_id: &str, default_minutes: u64) -> Option<Duration> {
+    let state_clone = state.clone();
+    let id = agent_id.to_string();
+    let agent_minutes = match telemetry::spawn_blocking(move || agent_repo::get_agent(&state_clone, &id)).await {
+        Ok(Ok(agent)) => agent.idle_shutdown_minutes,
+        Ok(Err(AppError::NotFound(_))) => None,
+        Ok(Err(e)) => {
+            log::warn!("[IdleAgents] Failed to load agent {}: {}", agent_id, e);
+            return None;
+        }
+        Err(e) => {
+            log::warn!("[IdleAgents] Spawn blocking failed for agent {}: {}", agent_id, e);
+            return None;
+        }
+    };
+    idle_limit(agent_minutes, default_minutes)
+}
+
+/// Stop the chat processes that went unused for longer than their limit.
+async fn stop_idle_agents(app: &tauri::AppHandle, state: &AppState) {
+    let default_minutes = config::current(state).agent_idle_shutdown_minutes;
+    let candidates: Vec<(String, Duration)> = {
+        let processes = state.agent_processes.lock().await;
+        processes
+            .iter()
+            .filter(|(key, _)| orchestrator::orch_task_run_id(key).is_none())
+            .map(|(key, process)| (key.clone(), process.last_used_at.elapsed()))
+            .collect()
+    };
+
+    for (agent_id, idle_for) in candidates {
+        let Some(limit) = agent_idle_limit(state, &agent_id, default_minutes).await else {
+            continue;
+        };
+        if idle_for < limit || agent_terminal::awaiting_input(state, &agent_id).await {
+            continue;
+        }
+        // An agent answering a chat prompt or a chat tool message is busy;
+        // holding the locks keeps a new one from starting while it stops
+        let chat_lock = session_summary::agent_lock(state, &agent_id).await;
+        let Ok(_chat_guard) = chat_lock.try_lock() else {
+            continue;
+        };
+        let hub_lock = state.chat_tool_hub_locks.lock().await.get(&agent_id).cloned();
+        let _hub_guard = match &hub_lock {
+            Some(lock) => match lock.try_lock() {
+                Ok(guard) => Some(guard),
+                Err(_) => continue,
+            },
+            None => None,
+        };
+        let still_idle = state
+            .agent_processes
+            .lock()
+            .await
+            .get(&agent_id)
+            .is_some_and(|p| p.last_used_at.elapsed() >= limit);
+        if !still_idle {
+            continue;
+        }
+
+        log::info!(
+            "[IdleAgents] Stopping agent {} after {} minute(s) without prompts",
+            agent_id,
+            idle_for.as_secs() / 60
+        );
+        manager::stop_agent_and_sessions(state, &agent_id).await;
+        events::emit(
+            app,
+            &events::ACP_AGENT_STOPPED,
+            serde_json::json!({ "agent_id": agent_id, "reason": "idle" }),
+        );
+    }
+}
+
+/// Stop idle agent processes until shutdown.
+pub async fn run(app: tauri::AppHandle, state: AppState) {
+    let mut interval = tokio::time::interval(Duration::from_secs(CHECK_INTERVAL_SECS));
+    interval.tick().await;
+    loop {
+        tokio::select! {
+            _ = state.shutdown_token.cancelled() => break,
+            _ = interval.tick() => stop_idle_agents(&app, &state).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agents_override_the_default_idle_limit() {
+        assert_eq!(idle_limit(None, 30), Some(Duration::from_secs(1800)));
+        assert_eq!(idle_limit(None, 0), None);
+        assert_eq!(idle_limit(Some(5), 30), Some(Duration::from_secs(300)));
+        assert_eq!(idle_limit(Some(0), 30), None);
+        assert_eq!(idle_limit(Some(90), 0), Some(Duration::from_secs(5400)));
+    }
+}
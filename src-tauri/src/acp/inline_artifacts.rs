@@ -0,0 +1,354 @@
+//! Images and files in agent outputs, kept as task artifacts.
+//!
+//! Agents sometimes answer with base64 `data:image/...` URIs or mention files
+//! they generated, such as a chart they saved. When an assignment completes,
+//! inline images are decoded into `output/<task_run_id>/images/` and replaced
+//! in the output by the file's path, so neither the stored output nor later
+//! prompts carry walls of base64. Referenced files of a previewable type that
+//! were written since the assignment started are registered where they are.
+//! Each artifact carries its MIME type and, for images, the dimensions, and
+//! is announced with `orchestration:agent_artifact` so the UI can show a
+//! preview.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use base64::Engine;
+use regex::{Captures, Regex};
+
+use crate::db::artifact_repo;
+use crate::db::migrations::get_output_dir;
+use crate::error::{AppError, AppResult};
+use crate::events;
+use crate::models::task_run::{ArtifactMetadata, TaskArtifact};
+use crate::state::AppState;
+
+pub const INLINE_IMAGE_ARTIFACT: &str = "inline_image";
+pub const GENERATED_FILE_ARTIFACT: &str = "generated_file";
+
+/// Shorter data URIs (icons, spacer pixels) stay in the text.
+const MIN_INLINE_IMAGE_CHARS: usize = 512;
+/// At most this many referenced files are registered per output.
+const MAX_FILE_REFERENCES: usize = 20;
+/// Bytes read from a referenced image to find its dimensions.
+const IMAGE_HEADER_BYTES: u64 = 256 * 1024;
+/// Largest image `preview` sends to the UI.
+const MAX_PREVIEW_BYTES: i64 = 10 * 1024 * 1024;
+
+/// File extensions worth previewing and their MIME types.
+const PREVIEWABLE: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("pdf", "application/pdf"),
+    ("csv", "text/csv"),
+    ("htm", "text/html"),
+    ("html", "text/html"),
+    ("xlsx", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+    ("docx", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+    ("pptx", "application/vnd.openxmlformats-officedocument.presentationml.presentation"),
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/wav"),
+    ("mp4", "video/mp4"),
+    ("zip", "application/zip"),
+];
+
+pub(crate) fn mime_for_path(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    PREVIEWABLE.iter().find(|(e, _)| *e == ext).map(|(_, mime)| *mime)
+}
+
+fn extension_for_mime(mime: &str) -> &'static str {
+    match mime {
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        _ => "png",
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct InlineImage {
+    mime_type: String,
+    bytes: Vec<u8>,
+    /// What replaced the data URI in the text
+    path: String,
+}
+
+/// Decode the data-URI images in `text`, replacing each with
+/// `path_of(index, extension)`. URIs that do not decode are left alone.
+fn take_inline_images(text: &str, path_of: impl Fn(usize, &str) -> String) -> (String, Vec<InlineImage>) {
+    let pattern = Regex::new(r"data:(image/(?:png|jpeg|jpg|gif|webp|svg\+xml));base64,([A-Za-z0-9+/]+={0,2})")
+        .expect("valid regex");
+    let mut images = Vec::new();
+    let replaced = pattern.replace_all(text, |caps: &Captures| {
+        let data = &caps[2];
+        if data.len() < MIN_INLINE_IMAGE_CHARS {
+            return caps[0].to_string();
+        }
+        match base64::engine::general_purpose::STANDARD.decode(data) {
+            Ok(bytes) => {
+                let mime_type = if &caps[1] == "image/jpg" { "image/jpeg" } else { &caps[1] };
+                let path = path_of(images.len(), extension_for_mime(mime_type));
+                images.push(InlineImage { mime_type: mime_type.to_string(), bytes, path: path.clone() });
+                path
+            }
+            Err(_) => caps[0].to_string(),
+        }
+    });
+    (replaced.into_owned(), images)
+}
+
+/// Paths of previewable files mentioned in `text`, in order and without
+/// duplicates. URLs other than `file://` are skipped.
+fn file_references(text: &str) -> Vec<String> {
+    let extensions: Vec<&str> = PREVIEWABLE.iter().map(|(ext, _)| *ext).collect();
+    let pattern = Regex::new(&format!(r"(?i)[^\s`'\x22()<>\[\]{{}}|*]+\.(?:{})\b", extensions.join("|")))
+        .expect("valid regex");
+    let mut references: Vec<String> = Vec::new();
+    for found in pattern.find_iter(text) {
+        let reference = found.as_str();
+        let reference = reference.strip_prefix("file://").unwrap_or(reference);
+        if reference.contains("://") || references.iter().any(|r| r == reference) {
+            continue;
+        }
+        references.push(reference.to_string());
+        if references.len() == MAX_FILE_REFERENCES {
+            break;
+        }
+    }
+    references
+}
+
+fn resolve(reference: &str, working_dir: Option<&Path>) -> Option<PathBuf> {
+    let path = match reference.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()?.join(rest),
+        None => PathBuf::from(reference),
+    };
+    if path.is_absolute() {
+        Some(path)
+    } else {
+        Some(working_dir?.join(path))
+    }
+}
+
+/// Width and height from a PNG, GIF or JPEG header.
+fn image_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |i: usize| Some(u16::from_be_bytes([*bytes.get(i)?, *bytes.get(i + 1)?]) as u32);
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let be32 = |i: usize| Some(u32::from_be_bytes(bytes.get(i..i + 4)?.try_into().ok()?));
+        return Some((be32(16)?, be32(20)?));
+    }
+    if bytes.starts_with(b"GIF8") {
+        let le16 = |i: usize| Some(u16::from_le_bytes([*bytes.get(i)?, *bytes.get(i + 1)?]) as u32);
+        return Some((le16(6)?, le16(8)?));
+    }
+    if bytes.starts_with(&[0xFF, 0xD8]) {
+        let mut i = 2;
+        while *bytes.get(i)? == 0xFF {
+            let marker = *bytes.get(i + 1)?;
+            // Start-of-frame segments hold the dimensions; C4, C8 and CC are not frames
+            if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                return Some((be16(i + 7)?, be16(i + 5)?));
+            }
+            i += 2 + be16(i + 2)? as usize;
+        }
+    }
+    None
+}
+
+fn metadata_for(mime_type: &str, header: &[u8]) -> ArtifactMetadata {
+    let size = if mime_type.starts_with("image/") { image_size(header) } else { None };
+    ArtifactMetadata {
+        mime_type: mime_type.to_string(),
+        width: size.map(|(w, _)| w),
+        height: size.map(|(_, h)| h),
+    }
+}
+
+async fn read_header(path: &Path) -> Vec<u8> {
+    use tokio::io::AsyncReadExt;
+    let mut header = Vec::new();
+    if let Ok(file) = tokio::fs::File::open(path).await {
+        let _ = file.take(IMAGE_HEADER_BYTES).read_to_end(&mut header).await;
+    }
+    header
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn register(
+    state: &AppState,
+    task_run_id: &str,
+    assignment_id: &str,
+    agent_id: &str,
+    kind: &'static str,
+    path: &Path,
+    size_bytes: i64,
+    metadata: ArtifactMetadata,
+) -> AppResult<Option<TaskArtifact>> {
+    let state = state.clone();
+    let (trid, aid, agid) = (task_run_id.to_string(), assignment_id.to_string(), agent_id.to_string());
+    let path = path.to_string_lossy().to_string();
+    tokio::task::spawn_blocking(move || {
+        // A file mentioned again by a later assignment is already known
+        if artifact_repo::find_artifact_by_path(&state, &trid, &path)?.is_some() {
+            return Ok(None);
+        }
+        artifact_repo::create_artifact(&state, &trid, Some(&aid), Some(&agid), kind, &path, size_bytes, Some(&metadata))
+            .map(Some)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+async fn write_images(images: &[InlineImage]) -> std::io::Result<()> {
+    for image in images {
+        let path = Path::new(&image.path);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, &image.bytes).await?;
+    }
+    Ok(())
+}
+
+/// Keep the images and generated files of a completed assignment's `text` as
+/// artifacts and return the text with inline images replaced by their paths.
+/// Problems are logged and leave the text as it was.
+#[allow(clippy::too_many_arguments)]
+pub async fn collect(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run_id: &str,
+    assignment_id: &str,
+    agent_id: &str,
+    working_dir: Option<&str>,
+    started: SystemTime,
+    text: &str,
+) -> String {
+    let dir = get_output_dir().join(task_run_id).join("images");
+    let prefix: String = assignment_id.chars().take(8).collect();
+    let (rewritten, images) = take_inline_images(text, |i, ext| {
+        dir.join(format!("{}-{}.{}", prefix, i + 1, ext)).to_string_lossy().to_string()
+    });
+    let references = file_references(&rewritten);
+    if images.is_empty() && references.is_empty() {
+        return text.to_string();
+    }
+    if let Err(e) = write_images(&images).await {
+        log::warn!("[InlineArtifacts] Failed to save images of assignment {}: {}", assignment_id, e);
+        return text.to_string();
+    }
+
+    let mut found: Vec<(&'static str, PathBuf, i64, ArtifactMetadata)> = images
+        .iter()
+        .map(|image| {
+            let metadata = metadata_for(&image.mime_type, &image.bytes);
+            (INLINE_IMAGE_ARTIFACT, PathBuf::from(&image.path), image.bytes.len() as i64, metadata)
+        })
+        .collect();
+    // Files older than the assignment were there before it and are not its output
+    let since = started.checked_sub(Duration::from_secs(1)).unwrap_or(started);
+    for reference in references {
+        let Some(path) = resolve(&reference, working_dir.map(Path::new)) else { continue };
+        let Some(mime_type) = mime_for_path(&path) else { continue };
+        if images.iter().any(|image| Path::new(&image.path) == path) {
+            continue;
+        }
+        let Ok(file) = tokio::fs::metadata(&path).await else { continue };
+        if !file.is_file() || file.modified().map_or(true, |modified| modified < since) {
+            continue;
+        }
+        let header = if mime_type.starts_with("image/") { read_header(&path).await } else { Vec::new() };
+        found.push((GENERATED_FILE_ARTIFACT, path, file.len() as i64, metadata_for(mime_type, &header)));
+    }
+
+    for (kind, path, size_bytes, metadata) in found {
+        match register(state, task_run_id, assignment_id, agent_id, kind, &path, size_bytes, metadata).await {
+            Ok(Some(artifact)) => events::emit(app, &events::AGENT_ARTIFACT, serde_json::json!({
+                "taskRunId": task_run_id,
+                "assignmentId": assignment_id,
+                "agentId": agent_id,
+                "artifact": artifact,
+            })),
+            Ok(None) => {}
+            Err(e) => log::warn!("[InlineArtifacts] Failed to register {}: {}", path.display(), e),
+        }
+    }
+    rewritten
+}
+
+/// An image artifact as a data URL the UI can show.
+pub async fn preview(state: &AppState, artifact_id: &str) -> AppResult<String> {
+    let state_clone = state.clone();
+    let id = artifact_id.to_string();
+    let artifact = tokio::task::spawn_blocking(move || artifact_repo::get_artifact(&state_clone, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    let mime_type = artifact
+        .metadata
+        .as_ref()
+        .map(|m| m.mime_type.as_str())
+        .filter(|mime| mime.starts_with("image/"))
+        .ok_or_else(|| AppError::InvalidRequest(format!("Artifact {} is not an image", artifact_id)))?;
+    if artifact.size_bytes > MAX_PREVIEW_BYTES {
+        return Err(AppError::InvalidRequest(format!(
+            "Image {} is too large to preview ({} bytes)",
+            artifact.path, artifact.size_bytes
+        )));
+    }
+    let bytes = tokio::fs::read(&artifact.path).await?;
+    Ok(format!("data:{};base64,{}", mime_type, base64::engine::general_purpose::STANDARD.encode(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32, padding: usize) -> Vec<u8> {
+        let mut bytes = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        bytes.extend(width.to_be_bytes());
+        bytes.extend(height.to_be_bytes());
+        bytes.extend(vec![0u8; padding]);
+        bytes
+    }
+
+    #[test]
+    fn inline_images_are_replaced_by_their_paths() {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(png(640, 480, 600));
+        let icon = "data:image/png;base64,iVBORw0KGgo=";
+        let text = format!("Chart:\n![chart](data:image/png;base64,{})\nIcon: {}", encoded, icon);
+
+        let (rewritten, images) = take_inline_images(&text, |i, ext| format!("/out/images/a-{}.{}", i + 1, ext));
+        assert_eq!(rewritten, format!("Chart:\n![chart](/out/images/a-1.png)\nIcon: {}", icon));
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].mime_type, "image/png");
+        assert_eq!(image_size(&images[0].bytes), Some((640, 480)));
+    }
+
+    #[test]
+    fn finds_file_references_but_not_urls() {
+        let text = "Saved `charts/q3.PNG` and report.pdf (see ./report.pdf), \
+                    from https://example.com/logo.png; source in main.rs; file:///tmp/a.csv.";
+        assert_eq!(file_references(text), ["charts/q3.PNG", "report.pdf", "./report.pdf", "/tmp/a.csv"]);
+        assert_eq!(resolve("out/a.png", Some(Path::new("/ws"))), Some(PathBuf::from("/ws/out/a.png")));
+        assert_eq!(resolve("out/a.png", None), None);
+        assert_eq!(mime_for_path(Path::new("/ws/Q3.JPEG")), Some("image/jpeg"));
+    }
+
+    #[test]
+    fn reads_image_dimensions_from_headers() {
+        let gif = b"GIF89a\x20\x03\x58\x02".to_vec();
+        assert_eq!(image_size(&gif), Some((800, 600)));
+        // SOI, an APP0 segment of 16 bytes, then SOF0 with height 300 and width 400
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10];
+        jpeg.extend([0u8; 14]);
+        jpeg.extend([0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01, 0x2C, 0x01, 0x90]);
+        assert_eq!(image_size(&jpeg), Some((400, 300)));
+        assert_eq!(image_size(b"not an image"), None);
+    }
+}
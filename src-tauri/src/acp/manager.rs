@@ -6,8 +6,17 @@ use tokio::process::{Child, ChildStdin, ChildStdout};
 use tokio::sync::mpsc;
 use tokio::sync::Mutex as AsyncMutex;
 
+use crate::acp::agent_terminal::{self, AgentTerminal};
+use crate::acp::container::{self, ContainerSpec};
 use crate::acp::discovery;
+use crate::acp::prompt_content::PromptCapabilities;
+use crate::acp::ssh::{self, SshTarget};
+use crate::acp::{orchestrator, run_sandbox};
+use crate::config;
+use crate::db::agent_repo;
 use crate::error::{AppError, AppResult};
+use crate::reaper;
+use crate::state::AppState;
 
 #[derive(Debug)]
 pub struct AgentProcess {
@@ -27,6 +36,14 @@ pub struct AgentProcess {
     pub status: AgentProcessStatus,
     /// Captured stderr lines for debugging
     pub stderr_lines: Arc<AsyncMutex<Vec<String>>>,
+    /// Docker container the agent runs in, removed when the process is stopped
+    pub container: Option<String>,
+    /// Controlling terminal the agent may prompt the user on
+    pub terminal: Option<Arc<AgentTerminal>>,
+    /// When the agent was last sent a request, for stopping idle agents
+    pub last_used_at: std::time::Instant,
+    /// Content the agent takes in prompts, known once it is initialized
+    pub prompt_capabilities: PromptCapabilities,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -48,7 +65,62 @@ impl std::fmt::Display for AgentProcessStatus {
     }
 }
 
+/// Where an agent's process runs.
+enum Runtime {
+    Local,
+    Ssh(SshTarget),
+    Container { spec: ContainerSpec, workspace: String },
+}
+
+/// What to start for an agent. Off this machine the agent's configured
+/// command and arguments are used, since the ones resolved locally mean
+/// nothing on a remote host or in a container.
+struct Launch {
+    runtime: Runtime,
+    command: String,
+    args: Vec<String>,
+}
+
+async fn launch_for(state: &AppState, agent_id: &str, command: &str, args: &[String]) -> Launch {
+    let state_clone = state.clone();
+    let id = agent_id.to_string();
+    let found = tokio::task::spawn_blocking(move || {
+        let agent = agent_repo::get_agent(&state_clone, &id).ok()?;
+        let runtime = if let Some(target) = SshTarget::of(&agent) {
+            if ContainerSpec::of(&agent).is_some() {
+                log::warn!("Agent {} has an SSH host and a container image; running it over SSH", agent.id);
+            }
+            Runtime::Ssh(target)
+        } else {
+            let spec = ContainerSpec::of(&agent)?;
+            let workspace = orchestrator::resolve_orchestrator_working_directory(&state_clone, agent.workspace_id.as_deref());
+            Runtime::Container { spec, workspace }
+        };
+        Some((runtime, agent))
+    })
+    .await
+    .ok()
+    .flatten();
+
+    match found {
+        Some((runtime, agent)) => match agent.acp_command {
+            Some(configured) => Launch {
+                runtime,
+                command: configured,
+                args: agent
+                    .acp_args_json
+                    .as_deref()
+                    .and_then(|j| serde_json::from_str(j).ok())
+                    .unwrap_or_default(),
+            },
+            None => Launch { runtime, command: command.to_string(), args: args.to_vec() },
+        },
+        None => Launch { runtime: Runtime::Local, command: command.to_string(), args: args.to_vec() },
+    }
+}
+
 pub async fn spawn_agent_process(
+    state: &AppState,
     agent_id: &str,
     command: &str,
     args: &[String],
@@ -61,13 +133,54 @@ pub async fn spawn_agent_process(
     let enriched_path = discovery::get_enriched_path();
     log::debug!("Enriched PATH for agent process: {}", enriched_path);
 
-    let mut cmd = tokio::process::Command::new(command);
-    cmd.args(args)
-        .env("PATH", &enriched_path)
-        .envs(extra_env)
-        .stdin(Stdio::piped())
+    let launch = launch_for(state, agent_id, command, args).await;
+    let mut container_name = None;
+    let mut cmd = match &launch.runtime {
+        Runtime::Local => {
+            let mut cmd = tokio::process::Command::new(command);
+            cmd.args(args)
+                .env("PATH", &enriched_path)
+                .envs(extra_env);
+            cmd
+        }
+        Runtime::Ssh(target) => {
+            log::info!("Running agent {} on {} over SSH: {} {:?}", agent_id, target.destination(), launch.command, launch.args);
+            let mut cmd = tokio::process::Command::new("ssh");
+            cmd.args(ssh::ssh_args(target, &launch.command, &launch.args, extra_env))
+                .env("PATH", &enriched_path);
+            cmd
+        }
+        Runtime::Container { spec, workspace } => {
+            container::ensure_image(&spec.image).await?;
+            let name = container::container_name(agent_id);
+            log::info!(
+                "Running agent {} in container {} of {}: {} {:?}",
+                agent_id, name, spec.image, launch.command, launch.args
+            );
+            let options = container::RunOptions {
+                name: &name,
+                agent_id,
+                app_pid: std::process::id(),
+                workspace,
+                scratch: extra_env.get(run_sandbox::SCRATCH_DIR_ENV).map(String::as_str),
+                user: container::owner(workspace),
+            };
+            let mut cmd = tokio::process::Command::new("docker");
+            cmd.args(container::run_args(spec, &options, &launch.command, &launch.args, extra_env))
+                .env("PATH", &enriched_path)
+                .envs(extra_env);
+            container_name = Some(name);
+            cmd
+        }
+    };
+    cmd.stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
+    let pty = if config::current(state).agent_terminal_attach {
+        agent_terminal::prepare(agent_id, &mut cmd)
+    } else {
+        None
+    };
 
     let mut child = cmd.spawn()
         .map_err(|e| {
@@ -76,6 +189,13 @@ pub async fn spawn_agent_process(
         })?;
 
     log::info!("Agent process spawned with PID: {:?}", child.id());
+    let recorded_command = match launch.runtime {
+        Runtime::Local => command,
+        Runtime::Ssh(_) => "ssh",
+        Runtime::Container { .. } => "docker",
+    };
+    reaper::record(state, "agent", agent_id, recorded_command, &child).await;
+    let terminal = pty.and_then(|pty| agent_terminal::start(state, agent_id, pty));
 
     let stdin = child
         .stdin
@@ -116,7 +236,7 @@ pub async fn spawn_agent_process(
     // Brief delay to let the process start, then check if it's still alive.
     // For npx-based agents, skip the early-exit check since npx takes time to download
     // the package on first run, but still sleep to allow pipes to connect.
-    let is_npx = crate::acp::provisioner::is_npx_command(command);
+    let is_npx = crate::acp::provisioner::is_npx_command(&launch.command);
     tokio::time::sleep(std::time::Duration::from_millis(200)).await;
 
     // Check if process exited immediately (skip for npx — it may still be downloading)
@@ -161,6 +281,10 @@ pub async fn spawn_agent_process(
         message_rx,
         status: AgentProcessStatus::Starting,
         stderr_lines,
+        container: container_name,
+        terminal,
+        last_used_at: std::time::Instant::now(),
+        prompt_capabilities: PromptCapabilities::default(),
     })
 }
 
@@ -206,11 +330,27 @@ fn spawn_reader_task(
 }
 
 pub async fn stop_agent_process(process: &mut AgentProcess) -> AppResult<()> {
-    process
-        .child
-        .kill()
-        .await
-        .map_err(|e| AppError::Acp(format!("Failed to kill agent process: {e}")))?;
+    let killed = process.child.kill().await;
+    // Killing the docker client leaves the container running
+    if let Some(name) = process.container.take() {
+        container::remove(&name).await;
+    }
+    killed.map_err(|e| AppError::Acp(format!("Failed to kill agent process: {e}")))?;
     process.status = AgentProcessStatus::Stopped;
     Ok(())
 }
+
+/// Stop the agent's process and drop its stdin handle and ACP sessions.
+pub async fn stop_agent_and_sessions(state: &AppState, agent_id: &str) {
+    let mut processes = state.agent_processes.lock().await;
+    if let Some(mut old_process) = processes.remove(agent_id) {
+        let _ = stop_agent_process(&mut old_process).await;
+    }
+    drop(processes);
+    let mut stdins = state.agent_stdins.lock().await;
+    stdins.remove(agent_id);
+    drop(stdins);
+    // Clean up temp ACP sessions for this agent
+    let mut acp_sessions = state.acp_sessions.lock().await;
+    acp_sessions.retain(|k, info| !(info.agent_id == agent_id || k == &format!("temp:{}", agent_id)));
+}
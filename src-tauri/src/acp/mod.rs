@@ -1,12 +1,44 @@
+pub mod agent_auth;
+pub mod agent_terminal;
+pub mod agent_versions;
+pub mod assignment_caps;
+pub mod assignment_overrides;
 pub mod builtin;
 pub mod client;
+pub mod container;
+pub mod dependency_outputs;
+pub mod diff;
+pub mod fallback_planner;
 pub mod discovery;
+pub mod duplicate_runs;
+pub mod file_conflicts;
+pub mod idle_agents;
+pub mod inline_artifacts;
 pub mod filesystem;
 pub mod manager;
 pub mod orchestrator;
+pub mod output_stream;
 pub mod permissions;
+pub mod pipeline;
+pub mod prompt_budget;
+pub mod prompt_content;
 pub mod provisioner;
+pub mod run_changes;
+pub mod run_context;
+pub mod run_deadline;
+pub mod run_queue;
+pub mod running_assignments;
+pub mod run_sandbox;
+pub mod session_summary;
+pub mod summary_stream;
+pub mod response_cache;
+pub mod skill_cache;
 pub mod skill_discovery;
+pub mod ssh;
+pub mod timeline;
+pub mod tool_payloads;
+pub mod templates;
 pub mod terminal;
 pub mod transport;
 pub mod upgrade;
+pub mod web_search;
@@ -1,20 +1,34 @@
 use std::collections::HashMap;
 use serde::Serialize;
-use tauri::Emitter;
 
-use crate::acp::{client, discovery, manager, provisioner, skill_discovery, upgrade};
-use crate::db::{agent_md, agent_repo, settings_repo, task_run_repo};
+use crate::acp::{agent_auth, agent_terminal, agent_versions, assignment_caps, assignment_overrides, client, dependency_outputs, discovery, duplicate_runs, fallback_planner, file_conflicts, filesystem, inline_artifacts, manager, output_stream, permissions, prompt_budget, prompt_content, provisioner, response_cache, run_changes, run_context, run_deadline, run_queue, run_sandbox, running_assignments, skill_cache, summary_stream, timeline, tool_payloads, upgrade, web_search};
+use crate::activity;
+use crate::chaos;
+use crate::chat_tool::run_notifications;
+use crate::config;
+use crate::db::{agent_context_repo, agent_md, agent_repo, permission_policy_repo, pinned_output_repo, task_run_repo};
 use crate::error::{AppError, AppResult};
+use crate::events;
+use crate::i18n::{self, Msg};
+use crate::knowledge;
+use crate::memory;
 use crate::models::agent::{AgentConfig, AgentSkill};
-use crate::models::task_run::{TaskPlan, TaskRun, PlannedAssignment};
+use crate::models::pricing::TokenUsage;
+use crate::models::task_run::{CancelReason, CreateTaskRunRequest, StartedTaskRun, TaskAssignment, TaskPlan, TaskRun, PlannedAssignment};
+use crate::prompts;
+use crate::redaction;
+use crate::run_environment;
 use crate::state::{AppState, ConfirmationAction};
+use crate::telemetry;
 use crate::db::migrations::{get_output_dir};
 use crate::acp::skill_discovery::SkillDiscoveryResult;
+use crate::acp::summary_stream::StoppedSummary;
 use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 /// Clean up all agent processes spawned for a specific task run.
 /// Uses the `orch:{task_run_id}:` prefix to find and kill all processes belonging to this task.
-async fn cleanup_task_processes(state: &AppState, task_run_id: &str) {
+pub(crate) async fn cleanup_task_processes(state: &AppState, task_run_id: &str) {
     let prefix = format!("orch:{}:", task_run_id);
 
     // Kill and remove all agent processes for this task run
@@ -42,6 +56,12 @@ async fn cleanup_task_processes(state: &AppState, task_run_id: &str) {
         stdins.retain(|k, _| !k.starts_with(&prefix));
     }
 
+    // Drop warm-up output no assignment used
+    {
+        let mut warmups = state.agent_warmups.lock().await;
+        warmups.retain(|k, _| !k.starts_with(&prefix));
+    }
+
     // Remove ACP sessions
     {
         let session_prefix = format!("orch_session:{}", prefix);
@@ -58,12 +78,114 @@ struct AgentPromptResult {
     cache_creation_tokens: i64,
     cache_read_tokens: i64,
     acp_session_id: String,
+    /// Stopped at the assignment's token or cost cap
+    capped: bool,
+}
+
+/// Create a task run for a user prompt and start orchestrating it in the background.
+pub async fn start_task_run(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    mut request: CreateTaskRunRequest,
+) -> AppResult<StartedTaskRun> {
+    if state.shutdown_token.is_cancelled() {
+        return Err(AppError::InvalidRequest("Application is shutting down".into()));
+    }
+
+    if let Some(prompt) = request.prompt.take() {
+        let state_clone = state.clone();
+        let text = std::mem::take(&mut request.user_prompt);
+        request.user_prompt = telemetry::spawn_blocking(move || prompts::compose(&state_clone, Some(&prompt), &text))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+    }
+    if request.user_prompt.trim().is_empty() {
+        return Err(AppError::InvalidRequest("Prompt is empty".into()));
+    }
+    if !request.pinned_output_ids.is_empty() {
+        let state_clone = state.clone();
+        let ids = std::mem::take(&mut request.pinned_output_ids);
+        let pinned = telemetry::spawn_blocking(move || pinned_output_repo::get_pinned_outputs(&state_clone, &ids))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+        request.user_prompt.push_str(&run_context::pinned_section(&pinned));
+    }
+
+    // Control hub for the workspace, or the built-in planner without one
+    let hub_id: String = {
+        let state_clone = state.clone();
+        let ws_id = request.workspace_id.clone();
+        telemetry::spawn_blocking(move || fallback_planner::hub_id_for(&state_clone, ws_id.as_deref()))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??
+    };
+
+    let task_run_id = uuid::Uuid::new_v4().to_string();
+    let workspace_id = request.workspace_id.clone();
+    if let Some(existing) = duplicate_runs::claim(state, workspace_id.as_deref(), &request.user_prompt, &task_run_id).await {
+        return duplicate_runs::existing(state, existing).await;
+    }
+    let title = if request.title.is_empty() {
+        request.user_prompt.chars().take(100).collect::<String>()
+    } else {
+        request.title.clone()
+    };
+
+    // Create task run record
+    let created = {
+        let state_clone = state.clone();
+        let trid = task_run_id.clone();
+        let t = title.clone();
+        let up = request.user_prompt.clone();
+        let ws_id = workspace_id.clone();
+        let context_run_id = request.attach_run_context.clone();
+        let permission_preset = request.permission_preset;
+        let max_duration_minutes = request.max_duration_minutes;
+        telemetry::spawn_blocking(move || {
+            if let Some(context_run_id) = &context_run_id {
+                task_run_repo::get_task_run(&state_clone, context_run_id)?;
+            }
+            let mut task_run =
+                task_run_repo::create_task_run(&state_clone, &trid, &t, &up, &hub_id, "pending", ws_id.as_deref())?;
+            if let Some(context_run_id) = context_run_id {
+                task_run_repo::set_context_run(&state_clone, &trid, &context_run_id)?;
+                task_run.context_run_id = Some(context_run_id);
+            }
+            if let Some(preset) = permission_preset {
+                task_run_repo::set_permission_preset(&state_clone, &trid, preset)?;
+                task_run.permission_preset = Some(preset);
+            }
+            if let Some(minutes) = max_duration_minutes {
+                task_run_repo::set_max_duration(&state_clone, &trid, minutes)?;
+                task_run.max_duration_minutes = Some(minutes as i64);
+            }
+            Ok(task_run)
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+        .and_then(|r| r)
+    };
+    let task_run: TaskRun = match created {
+        Ok(task_run) => task_run,
+        Err(e) => {
+            duplicate_runs::settle(state, workspace_id.as_deref(), &request.user_prompt, &task_run_id, false).await;
+            return Err(e);
+        }
+    };
+
+    // Orchestrate in the background once the intake queue starts it
+    let prompt = request.user_prompt.clone();
+    run_queue::enqueue(app, state, &task_run, request.priority, request.user_prompt, None, request.ignore_workspace_lock)
+        .await;
+    duplicate_runs::settle(state, workspace_id.as_deref(), &prompt, &task_run_id, true).await;
+
+    Ok(StartedTaskRun { task_run, duplicate: false })
 }
 
 /// Run a complete orchestration flow:
-/// 1. Validate control hub exists
+/// 1. Resolve the control hub (or the built-in planner when none is configured)
 /// 2. Create TaskRun record
-/// 3. Ask control hub to plan
+/// 3. Ask control hub to plan (skipped when a preset plan is given)
 /// 4. Execute assignments sequentially
 /// 5. Finalize and write summary
 pub async fn run_orchestration(
@@ -72,22 +194,28 @@ pub async fn run_orchestration(
     task_run_id: String,
     user_prompt: String,
     workspace_id: Option<String>,
+    preset_plan: Option<TaskPlan>,
 ) {
-    let result = run_orchestration_inner(&app, &state, &task_run_id, &user_prompt, workspace_id.as_deref()).await;
+    activity::record_run(&app, &state, &task_run_id, activity::RUN_STARTED).await;
+    let result = run_orchestration_inner(&app, &state, &task_run_id, &user_prompt, workspace_id.as_deref(), preset_plan).await;
 
     // Clean up all agent processes spawned for this task run (success, error, or cancel)
     cleanup_task_processes(&state, &task_run_id).await;
+    run_sandbox::finish(&state, &task_run_id).await;
 
     // Always clean up the active task run entry so new orchestrations can start
     {
         let mut tokens = state.active_task_runs.lock().await;
         tokens.remove(&task_run_id);
     }
+    run_queue::dispatch(&app, &state);
     // Clean up per-agent cancellation tokens for this task run
     {
         let mut agent_cancels = state.agent_cancellations.lock().await;
         agent_cancels.retain(|(trid, _), _| trid != &task_run_id);
     }
+    state.orch_permissions.lock().await.forget_run(&task_run_id);
+    run_deadline::end(&state, &task_run_id).await;
 
     if let Err(e) = &result {
         let error_msg = e.to_string();
@@ -95,26 +223,29 @@ pub async fn run_orchestration(
         let error_payload = serde_json::json!({
             "taskRunId": task_run_id,
             "error": error_msg,
+            "errorCode": e.code(),
         });
         log::info!("Emitting orchestration:error payload: {}", error_payload);
-        if let Err(emit_err) = app.emit("orchestration:error", error_payload) {
-            log::error!("Failed to emit orchestration:error event: {}", emit_err);
-        }
+        events::emit(&app, &events::RUN_ERROR, error_payload);
         // Update status to failed
         let state_clone = state.clone();
         let id_clone = task_run_id.clone();
-        let _ = tokio::task::spawn_blocking(move || {
+        let _ = telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_status(&state_clone, &id_clone, "failed")
         }).await;
     }
+    run_notifications::notify_finished(&app, &state, &task_run_id, &result);
+    activity::record_run(&app, &state, &task_run_id, activity::RUN_FINISHED).await;
 }
 
+#[tracing::instrument(name = "orchestration", skip(app, state, user_prompt, preset_plan), fields(preset = preset_plan.is_some()))]
 async fn run_orchestration_inner(
     app: &tauri::AppHandle,
     state: &AppState,
     task_run_id: &str,
     user_prompt: &str,
     workspace_id: Option<&str>,
+    preset_plan: Option<TaskPlan>,
 ) -> AppResult<()> {
     let start_time = std::time::Instant::now();
 
@@ -122,29 +253,30 @@ async fn run_orchestration_inner(
     if is_cancelled(state, task_run_id).await {
         return Ok(());
     }
+    let time_limit = run_deadline::begin(state, task_run_id, start_time).await;
 
-    // 1. Get the control hub agent (workspace-scoped)
-    let hub_agent: AgentConfig = {
+    // 1. Get the control hub agent (workspace-scoped); without one the
+    // built-in planner plans and no hub reviews or summarizes
+    let hub_agent: Option<AgentConfig> = {
         let state_clone = state.clone();
         let ws_id = workspace_id.map(|s| s.to_string());
-        tokio::task::spawn_blocking(move || agent_repo::get_control_hub(&state_clone, ws_id.as_deref()))
+        telemetry::spawn_blocking(move || agent_repo::get_control_hub(&state_clone, ws_id.as_deref()))
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??
-            .ok_or_else(|| AppError::Internal("No Control Hub agent configured for this workspace".into()))?
     };
 
     // 2. Update status to analyzing
     {
         let state_clone = state.clone();
         let id = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_status(&state_clone, &id, "analyzing")
         })
         .await
         .map_err(|e| AppError::Internal(e.to_string()))??;
     }
 
-    let _ = app.emit("orchestration:started", &serde_json::json!({
+    events::emit(app, &events::RUN_STARTED, serde_json::json!({
         "taskRunId": task_run_id,
         "status": "analyzing",
         "workspaceId": workspace_id,
@@ -153,45 +285,30 @@ async fn run_orchestration_inner(
     // 3. Discover workspace skills (cached)
     let cwd = resolve_orchestrator_working_directory(state, workspace_id);
     let discovery_result = {
-        let mut cache = state.discovered_skills.lock().await;
-        let needs_scan = match cache.as_ref() {
-            Some(cached) => !cached.scanned_directories.iter().any(|d| d.contains(&cwd)),
-            None => true,
-        };
-        if needs_scan {
-            let cwd_clone = cwd.clone();
-            let result = tokio::task::spawn_blocking(move || {
-                skill_discovery::discover_skills(&cwd_clone)
-            })
-            .await
-            .map_err(|e| AppError::Internal(e.to_string()))?;
-            log::info!(
-                "Skill discovery: found {} skills from {} directories",
-                result.skills.len(),
-                result.scanned_directories.len(),
-            );
-            *cache = Some(result.clone());
-            let _ = app.emit("orchestration:skills_discovered", &serde_json::json!({
+        let (result, scanned) = skill_cache::skills(state, workspace_id, &cwd, false).await?;
+        if scanned {
+            events::emit(app, &events::SKILLS_DISCOVERED, serde_json::json!({
                 "taskRunId": task_run_id,
                 "skillsCount": result.skills.len(),
             }));
-            Some(result)
-        } else {
-            cache.clone()
         }
+        Some(result)
     };
 
     // 4. Build agent catalog (scoped to the workspace if provided)
-    let all_agents: Vec<AgentConfig> = {
+    let mut all_agents: Vec<AgentConfig> = {
         let state_clone = state.clone();
         let ws_id = workspace_id.map(|s| s.to_string());
-        tokio::task::spawn_blocking(move || agent_repo::list_agents(&state_clone, ws_id.as_deref()))
+        telemetry::spawn_blocking(move || agent_repo::list_agents(&state_clone, ws_id.as_deref()))
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??
     };
+    let hub_only = recover_disabled_agents(app, state, task_run_id, workspace_id, &mut all_agents, hub_agent.as_ref()).await?;
+    let all_agents = all_agents;
 
     // Filter to only enabled agents for orchestration
     let enabled_agents: Vec<&AgentConfig> = all_agents.iter().filter(|a| a.is_enabled).collect();
+    run_environment::record(state, task_run_id, &enabled_agents).await;
 
     let catalog = build_agent_catalog_refs(&enabled_agents, discovery_result.as_ref());
 
@@ -204,69 +321,47 @@ async fn run_orchestration_inner(
         agent_md::read_agents_registry().unwrap_or_else(|_| catalog.clone())
     };
 
-    // 4. Ensure hub agent process is running and get a plan
-    let hub_process_key = orch_process_key(task_run_id, &hub_agent.id);
-    ensure_agent_running(app, state, &hub_agent, &hub_process_key).await?;
-
-    let plan_prompt = format!(
-        r#"You are the orchestrator control hub. Decompose the user request into subtasks and assign each to the best-matching agent.
-
-## Available Agents
-
-{catalog}
-
-## User Request
-
-{user_prompt}
-
-## Instructions
-
-1. Analyze the request and identify subtasks based ONLY on the information above.
-2. Match each subtask to the agent whose skills best fit.
-3. Respect each agent's constraints.
-4. If no agent has a matching skill, choose the most general-purpose agent.
-
-CRITICAL: You MUST respond with ONLY a valid JSON object. No explanations, no preamble, no markdown, no thinking — ONLY the JSON object below. Do NOT attempt to explore, research, or use tools. Make your plan based solely on the agent catalog and user request provided above.
-
-{{"analysis": "Brief reasoning about task decomposition and agent matching", "assignments": [{{"agent_id": "uuid-from-catalog", "task_description": "Detailed instruction for the agent", "sequence_order": 0, "depends_on": [], "matched_skills": ["skill_id"], "selection_reason": "Why this agent"}}]}}
-
-Rules:
-- Output ONLY the JSON object, nothing else
-- agent_id must come from the catalog above
-- matched_skills must reference skill IDs from the assigned agent
-- sequence_order: 0 for parallel, increment for sequential
-- depends_on: agent_ids whose output is needed first
-- Always return at least one assignment"#,
-        catalog = registry_content,
-    );
-
-    let plan_response = send_prompt_to_agent(app, state, &hub_agent.id, &plan_prompt, Some(task_run_id), None, workspace_id, &hub_process_key).await?;
-
-    if is_cancelled(state, task_run_id).await {
-        return Ok(());
+    // 4. Ensure hub agent process is running and get a plan, failing over
+    // to the secondary hub if the primary cannot plan
+    let mut hub_agent = hub_agent;
+    let mut hub_process_key = hub_agent.as_ref().map(|h| orch_process_key(task_run_id, &h.id)).unwrap_or_default();
+    let planned = match hub_agent.clone() {
+        Some(hub) if hub_only => hub_only_plan(&hub, user_prompt),
+        Some(hub) => match plan_with_hub(app, state, &hub, &hub_process_key, task_run_id, user_prompt, workspace_id, &cwd, &registry_content, preset_plan.as_ref()).await {
+            Ok(plan) => plan,
+            Err(e) => match fail_over_hub(app, state, task_run_id, workspace_id, &hub, &e).await {
+                Some(secondary) => {
+                    hub_process_key = orch_process_key(task_run_id, &secondary.id);
+                    let plan = plan_with_hub(app, state, &secondary, &hub_process_key, task_run_id, user_prompt, workspace_id, &cwd, &registry_content, preset_plan.as_ref()).await?;
+                    hub_agent = Some(secondary);
+                    plan
+                }
+                None => return Err(e),
+            },
+        },
+        None => match preset_plan.clone() {
+            Some(plan) => plan,
+            None => {
+                log::info!("No Control Hub for task {}, using the built-in planner", task_run_id);
+                fallback_planner::plan(user_prompt, &all_agents)?
+            }
+        },
+    };
+    if let Some(hub) = &hub_agent {
+        record_served_hub(state, task_run_id, &hub.id).await;
     }
 
-    // Parse the plan, with one retry on failure
-    let plan = match parse_task_plan(&plan_response.text) {
-        Ok(p) => p,
-        Err(first_err) => {
-            log::warn!("First plan parse failed, retrying with correction prompt: {}", first_err);
-
-            let retry_prompt = format!(
-                "Your previous response was not valid JSON. I need ONLY a raw JSON object, no text before or after it.\n\n\
-                 The expected format is:\n\
-                 {{\"analysis\": \"...\", \"assignments\": [{{\"agent_id\": \"...\", \"task_description\": \"...\", \"sequence_order\": 0, \"depends_on\": [], \"matched_skills\": [\"...\"], \"selection_reason\": \"...\"}}]}}\n\n\
-                 Respond with ONLY the JSON object. No markdown code fences, no explanation."
-            );
-
-            let retry_response = send_prompt_to_agent(app, state, &hub_agent.id, &retry_prompt, Some(task_run_id), None, workspace_id, &hub_process_key).await?;
-
-            parse_task_plan(&retry_response.text).map_err(|_| first_err)?
+    let mut plan = if preset_plan.is_some() || hub_only {
+        // Template runs come with a frozen plan and skip planning entirely
+        planned
+    } else {
+        if is_cancelled(state, task_run_id).await {
+            return Ok(());
         }
+        // Auto-correct matched_skills before validation
+        auto_correct_plan_skills(planned, &all_agents, discovery_result.as_ref())
     };
-
-    // Auto-correct matched_skills before validation
-    let plan = auto_correct_plan_skills(plan, &all_agents, discovery_result.as_ref());
+    normalize_plan_working_directories(state, workspace_id, &mut plan);
 
     // Validate skill matching (soft validation — warnings only)
     let validation = validate_plan_skill_matching(&plan, &all_agents, discovery_result.as_ref());
@@ -280,7 +375,7 @@ Rules:
             }
         }
     }
-    let _ = app.emit("orchestration:plan_validated", &serde_json::json!({
+    events::emit(app, &events::PLAN_VALIDATED, serde_json::json!({
         "taskRunId": task_run_id,
         "validation": &validation,
     }));
@@ -298,8 +393,9 @@ Rules:
     }
 
     // Filter out assignments to agents that are not in the workspace or are disabled
-    let plan = TaskPlan {
+    let mut plan = TaskPlan {
         analysis: plan.analysis,
+        isolated: plan.isolated,
         assignments: plan.assignments.into_iter().filter(|a| {
             match all_agents.iter().find(|ag| ag.id == a.agent_id) {
                 Some(ag) => ag.is_enabled,
@@ -326,23 +422,43 @@ Rules:
             .map_err(|e| AppError::Internal(format!("Failed to serialize plan: {e}")))?;
         let state_clone = state.clone();
         let id = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_plan(&state_clone, &id, &plan_json)
         })
         .await
         .map_err(|e| AppError::Internal(e.to_string()))??;
     }
 
-    let _ = app.emit("orchestration:plan_ready", &serde_json::json!({
+    // A preset plan was approved when it was saved
+    let confidence = plan.confidence();
+    let approval_threshold = config::current(state).plan_approval_confidence;
+    let requires_approval = hub_only || (preset_plan.is_none() && confidence.is_some_and(|c| c < approval_threshold));
+    events::emit(app, &events::PLAN_READY, serde_json::json!({
         "taskRunId": task_run_id,
         "plan": &plan,
+        "confidence": confidence,
+        "requiresApproval": requires_approval,
     }));
+    if requires_approval {
+        if !wait_for_plan_approval(app, state, task_run_id, confidence, approval_threshold).await? {
+            return Ok(());
+        }
+        // Overrides may have been edited while the plan waited
+        reload_plan_overrides(state, task_run_id, &mut plan).await;
+    }
+
+    if plan.isolated {
+        match run_sandbox::prepare(task_run_id) {
+            Ok(dir) => log::info!("Task {} runs isolated in {}", task_run_id, dir.display()),
+            Err(e) => log::warn!("Failed to create scratch directory for task {}: {}", task_run_id, e),
+        }
+    }
 
     // 5. Update status to running
     {
         let state_clone = state.clone();
         let id = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_status(&state_clone, &id, "running")
         })
         .await
@@ -355,21 +471,33 @@ Rules:
     let mut total_tokens_out: i64 = 0;
     let mut total_cache_creation_tokens: i64 = 0;
     let mut total_cache_read_tokens: i64 = 0;
-
-    // Group assignments by sequence_order
-    let mut sequence_groups: HashMap<i64, Vec<&PlannedAssignment>> = HashMap::new();
-    for assignment in &plan.assignments {
-        sequence_groups
-            .entry(assignment.sequence_order)
-            .or_default()
-            .push(assignment);
-    }
-
-    let mut sorted_orders: Vec<i64> = sequence_groups.keys().copied().collect();
-    sorted_orders.sort();
-
-    for order in &sorted_orders {
-        let group = &sequence_groups[order];
+    let mut feedback_corrections: usize = 0;
+    let mut replans: usize = 0;
+    // Past the time limit nothing more is dispatched
+    let mut timed_out = false;
+    let mut dispatched: usize = 0;
+
+    // Sequence groups run in order. A remainder plan revised after a failure
+    // replaces the groups that have not started yet.
+    let mut last_order: Option<i64> = None;
+    while let Some(order) = next_sequence_order(&plan, last_order) {
+        if last_order.is_some() {
+            // Give way to a waiting run of a higher priority
+            run_queue::yield_if_preempted(app, state, task_run_id).await;
+            if is_cancelled(state, task_run_id).await {
+                return Ok(());
+            }
+        }
+        if run_deadline::passed(state, task_run_id).await {
+            timed_out = true;
+            break;
+        }
+        last_order = Some(order);
+        let group: Vec<PlannedAssignment> = plan.assignments.iter()
+            .filter(|a| a.sequence_order == order)
+            .cloned()
+            .collect();
+        let mut group_failures: Vec<(String, String)> = Vec::new();
 
         // Build concurrency map: agent_id -> max_concurrency
         let agent_concurrency: HashMap<String, i64> = all_agents
@@ -378,9 +506,13 @@ Rules:
             .collect();
 
         // Split group into batches that respect max_concurrency per agent
-        let mut remaining: Vec<&PlannedAssignment> = group.iter().copied().collect();
+        let mut remaining: Vec<&PlannedAssignment> = group.iter().collect();
 
         while !remaining.is_empty() {
+            if run_deadline::passed(state, task_run_id).await {
+                timed_out = true;
+                break;
+            }
             let mut batch: Vec<&PlannedAssignment> = Vec::new();
             let mut batch_agent_count: HashMap<String, i64> = HashMap::new();
             let mut deferred: Vec<&PlannedAssignment> = Vec::new();
@@ -427,7 +559,7 @@ Rules:
                             .find(|a| a.id == *dep_id)
                             .map(|a| a.name.clone())
                             .unwrap_or_else(|| "Previous agent".into());
-                        input_parts.push(format!("\n--- Output from {dep_name} ---\n{output}"));
+                        input_parts.push(dependency_outputs::input_part(&dep_name, output, dependency_outputs::sections(planned, dep_id)));
                     }
                 }
 
@@ -437,6 +569,20 @@ Rules:
                     input_parts.push(peer_catalog);
                 }
 
+                let knowledge_context = {
+                    let state_clone = state.clone();
+                    let ws_id = workspace_id.map(|s| s.to_string());
+                    let description = planned.task_description.clone();
+                    telemetry::spawn_blocking(move || {
+                        knowledge::assignment_context(&state_clone, ws_id.as_deref(), &description)
+                    })
+                    .await
+                    .unwrap_or_default()
+                };
+                if !knowledge_context.is_empty() {
+                    input_parts.push(knowledge_context);
+                }
+
                 let input_text = input_parts.join("\n");
 
                 // Create assignment record
@@ -449,7 +595,7 @@ Rules:
                     let aname = agent_name.clone();
                     let seq = planned.sequence_order;
                     let inp = input_text.clone();
-                    tokio::task::spawn_blocking(move || {
+                    telemetry::spawn_blocking(move || {
                         task_run_repo::create_task_assignment(
                             &state_clone, &aid, &trid, &agid, &aname, seq, &inp,
                         )
@@ -458,11 +604,58 @@ Rules:
                     .map_err(|e| AppError::Internal(e.to_string()))??;
                 }
 
+                // Resolve and record the assignment's working directory
+                let working_dir = resolve_assignment_working_directory(
+                    state, workspace_id, planned.working_directory.as_deref(),
+                );
+                if let Some(subdir) = planned.working_directory.clone().filter(|_| working_dir.is_some()) {
+                    let state_clone = state.clone();
+                    let aid = assignment_id.clone();
+                    telemetry::spawn_blocking(move || {
+                        task_run_repo::set_assignment_working_directory(&state_clone, &aid, &subdir)
+                    })
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))??;
+                }
+
+                // Record the caps the agent works under; invalid caps are ignored
+                let caps = assignment_caps::AssignmentCaps::of(planned);
+                let caps = match caps.validate() {
+                    Ok(()) => caps,
+                    Err(e) => {
+                        log::warn!("Ignoring caps of agent {}'s assignment: {}", planned.agent_id, e);
+                        assignment_caps::AssignmentCaps::default()
+                    }
+                };
+                if !caps.is_empty() {
+                    let state_clone = state.clone();
+                    let aid = assignment_id.clone();
+                    telemetry::spawn_blocking(move || {
+                        task_run_repo::set_assignment_caps(&state_clone, &aid, caps.max_tokens_out, caps.max_cost)
+                    })
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))??;
+                }
+
+                // Record the agent settings the assignment overrides
+                let overrides = assignment_overrides::checked(planned);
+                if !overrides.is_empty() {
+                    let state_clone = state.clone();
+                    let aid = assignment_id.clone();
+                    let o = overrides.clone();
+                    telemetry::spawn_blocking(move || {
+                        task_run_repo::set_assignment_overrides(&state_clone, &aid, &o)
+                    })
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))??;
+                }
+                let agent_model = overrides.model.clone().unwrap_or(agent_model);
+
                 // Mark as running
                 {
                     let state_clone = state.clone();
                     let aid = assignment_id.clone();
-                    tokio::task::spawn_blocking(move || {
+                    telemetry::spawn_blocking(move || {
                         task_run_repo::update_task_assignment(
                             &state_clone, &aid, "running", None, None, 0, 0, 0, 0, 0, None,
                         )
@@ -478,7 +671,7 @@ Rules:
                     sessions.get(&orch_key).map(|s| s.acp_session_id.clone())
                 };
 
-                let _ = app.emit("orchestration:agent_started", &serde_json::json!({
+                events::emit(app, &events::AGENT_STARTED, serde_json::json!({
                     "taskRunId": task_run_id,
                     "assignmentId": assignment_id,
                     "agentId": planned.agent_id,
@@ -522,25 +715,46 @@ Rules:
 
                 let ws_id_clone = workspace_id.map(|s| s.to_string());
                 let all_agents_clone = all_agents.clone();
+                let output_path = output_stream::prepare(
+                    state, task_run_id, &assignment_id, planned.output_file.as_deref(),
+                ).await;
+                assignment_caps::begin(state, task_run_id, &planned.agent_id, caps).await;
+                assignment_overrides::begin(state, task_run_id, &planned.agent_id, overrides).await;
+                running_assignments::begin(state, task_run_id, &planned.agent_id, &assignment_id).await;
+                prompt_content::begin(state, task_run_id, &planned.agent_id, &planned.attachments).await;
                 join_set.spawn(async move {
                     let assign_start = std::time::Instant::now();
 
-                    let result = execute_with_a2a_routing(
+                    let result = execute_with_response_cache(
                         &app_clone,
                         &state_clone,
                         &agent_config,
                         &input_clone,
                         &task_run_id_clone,
+                        &assignment_id_clone,
                         agent_cancel_token.as_ref(),
                         ws_id_clone.as_deref(),
+                        working_dir.as_deref(),
                         &all_agents_clone,
                     ).await;
+                    if output_path.is_some() {
+                        output_stream::end(&state_clone, &assignment_id_clone).await;
+                    }
+                    assignment_caps::end(&state_clone, &task_run_id_clone, &agent_id_clone).await;
+                    assignment_overrides::end(&state_clone, &task_run_id_clone, &agent_id_clone).await;
+                    prompt_content::end(&state_clone, &task_run_id_clone, &agent_id_clone).await;
+                    running_assignments::end(&state_clone, &task_run_id_clone, &agent_id_clone).await;
 
                     let duration_ms = assign_start.elapsed().as_millis() as i64;
 
                     match result {
-                        Ok(prompt_result) => {
-                            // Update assignment as completed
+                        Ok((mut prompt_result, cached)) => {
+                            let status = if prompt_result.capped { assignment_caps::CAPPED_STATUS } else { "completed" };
+                            prompt_result.text = inline_artifacts::collect(
+                                &app_clone, &state_clone, &task_run_id_clone, &assignment_id_clone, &agent_id_clone,
+                                working_dir.as_deref(), std::time::SystemTime::now() - assign_start.elapsed(), &prompt_result.text,
+                            ).await;
+                            // Update assignment as completed, or capped
                             {
                                 let state_clone2 = state_clone.clone();
                                 let aid = assignment_id_clone.clone();
@@ -550,34 +764,45 @@ Rules:
                                 let to = prompt_result.tokens_out;
                                 let cct = prompt_result.cache_creation_tokens;
                                 let crt = prompt_result.cache_read_tokens;
-                                let _ = tokio::task::spawn_blocking(move || {
+                                let _ = telemetry::spawn_blocking(move || {
                                     task_run_repo::update_task_assignment(
-                                        &state_clone2, &aid, "completed", Some(&out), Some(&model),
+                                        &state_clone2, &aid, status, Some(&out), Some(&model),
                                         ti, to, cct, crt, duration_ms, None,
                                     )
                                 }).await;
                             }
 
-                            let _ = app_clone.emit("orchestration:agent_completed", &serde_json::json!({
+                            events::emit(&app_clone, &events::AGENT_COMPLETED, serde_json::json!({
                                 "taskRunId": task_run_id_clone,
                                 "assignmentId": assignment_id_clone,
                                 "agentId": agent_id_clone,
                                 "agentName": agent_name_clone,
                                 "durationMs": duration_ms,
-                                "status": "completed",
+                                "status": status,
                                 "tokensIn": prompt_result.tokens_in,
                                 "tokensOut": prompt_result.tokens_out,
                                 "cacheCreationTokens": prompt_result.cache_creation_tokens,
                                 "cacheReadTokens": prompt_result.cache_read_tokens,
                                 "acpSessionId": prompt_result.acp_session_id,
                                 "output": prompt_result.text.clone(),
+                                "cached": cached,
                             }));
 
+                            // Later prompts get a reference to the file instead of the full text
+                            if let Some(path) = output_path.as_deref() {
+                                match output_stream::finish(
+                                    &state_clone, &task_run_id_clone, &assignment_id_clone, &agent_id_clone, path, &prompt_result.text,
+                                ).await {
+                                    Ok(reference) => prompt_result.text = reference,
+                                    Err(e) => log::warn!("Failed to finish output file {}: {}", path.display(), e),
+                                }
+                            }
+
                             (agent_id_clone, Ok(prompt_result))
                         }
                         Err(e) => {
                             let err_msg = e.to_string();
-                            let is_cancelled = err_msg.contains("Agent cancelled");
+                            let is_cancelled = matches!(e, AppError::AgentCancelled { .. });
                             let status = if is_cancelled { "cancelled" } else { "failed" };
 
                             // Auto-disable agent on non-cancellation errors
@@ -585,15 +810,17 @@ Rules:
                                 let state_for_disable = state_clone.clone();
                                 let agent_id_for_disable = agent_id_clone.clone();
                                 let err_for_disable = err_msg.clone();
-                                let _ = tokio::task::spawn_blocking(move || {
+                                let transient = e.is_transient();
+                                let _ = telemetry::spawn_blocking(move || {
                                     agent_repo::disable_agent(
                                         &state_for_disable,
                                         &agent_id_for_disable,
                                         &err_for_disable,
+                                        transient,
                                     )
                                 }).await;
 
-                                let _ = app_clone.emit("orchestration:agent_auto_disabled", &serde_json::json!({
+                                events::emit(&app_clone, &events::AGENT_AUTO_DISABLED, serde_json::json!({
                                     "taskRunId": task_run_id_clone,
                                     "agentId": agent_id_clone,
                                     "agentName": agent_name_clone,
@@ -607,7 +834,7 @@ Rules:
                                 let aid = assignment_id_clone.clone();
                                 let em = err_msg.clone();
                                 let s = status.to_string();
-                                let _ = tokio::task::spawn_blocking(move || {
+                                let _ = telemetry::spawn_blocking(move || {
                                     task_run_repo::update_task_assignment(
                                         &state_clone2, &aid, &s, None, None,
                                         0, 0, 0, 0, duration_ms, Some(&em),
@@ -615,7 +842,7 @@ Rules:
                                 }).await;
                             }
 
-                            let _ = app_clone.emit("orchestration:agent_completed", &serde_json::json!({
+                            events::emit(&app_clone, &events::AGENT_COMPLETED, serde_json::json!({
                                 "taskRunId": task_run_id_clone,
                                 "assignmentId": assignment_id_clone,
                                 "agentId": agent_id_clone,
@@ -623,15 +850,17 @@ Rules:
                                 "durationMs": duration_ms,
                                 "status": status,
                                 "error": &err_msg,
+                                "errorCode": e.code(),
                             }));
 
                             log::warn!("Agent assignment failed for {}: {}", agent_name_clone, err_msg);
 
-                            (agent_id_clone, Err(err_msg))
+                            (agent_id_clone, Err((err_msg, is_cancelled)))
                         }
                     }
-                });
+                }.in_current_span());
             }
+            dispatched += batch.len();
 
             // Collect results from all parallel tasks
             while let Some(join_result) = join_set.join_next().await {
@@ -643,9 +872,12 @@ Rules:
                         total_cache_read_tokens += prompt_result.cache_read_tokens;
                         agent_outputs.insert(agent_id, prompt_result.text);
                     }
-                    Ok((agent_id, Err(err_msg))) => {
+                    Ok((agent_id, Err((err_msg, cancelled)))) => {
                         // Store error as output so downstream tasks can see it
-                        agent_outputs.insert(agent_id, format!("(Agent failed: {})", err_msg));
+                        agent_outputs.insert(agent_id.clone(), format!("(Agent failed: {})", err_msg));
+                        if !cancelled {
+                            group_failures.push((agent_id, err_msg));
+                        }
                     }
                     Err(e) => {
                         log::error!("Join error in parallel batch: {}", e);
@@ -655,331 +887,389 @@ Rules:
 
             remaining = deferred;
         }
+        // No review or revised plan once the time is up
+        if timed_out || run_deadline::passed(state, task_run_id).await {
+            timed_out = true;
+            break;
+        }
 
-        // After each sequence group, send feedback to control hub
-        if !agent_outputs.is_empty() {
-            let feedback = build_feedback_prompt(&agent_outputs, &all_agents);
-            let _ = app.emit("orchestration:feedback", &serde_json::json!({
-                "taskRunId": task_run_id,
-                "message": "Control Hub reviewing results...",
-            }));
+        // After each sequence group, let the control hub review and correct the results
+        if let Some(hub) = hub_agent.as_ref().filter(|_| !agent_outputs.is_empty()) {
+            let review = FeedbackReview {
+                app, state, task_run_id, workspace_id, hub_agent: hub, hub_process_key: &hub_process_key, all_agents: &all_agents,
+            };
+            let usage = review_with_hub_feedback(&review, order, &mut agent_outputs, &mut feedback_corrections).await;
+            total_tokens_in += usage.tokens_in;
+            total_tokens_out += usage.tokens_out;
+            total_cache_creation_tokens += usage.cache_creation_tokens;
+            total_cache_read_tokens += usage.cache_read_tokens;
+        }
 
-            // We don't need to act on the feedback for now, just log it
-            if let Ok(response) = send_prompt_to_agent(app, state, &hub_agent.id, &feedback, Some(task_run_id), None, workspace_id, &hub_process_key).await {
-                log::info!("Control Hub feedback: {}", response.text);
+        // Let the control hub revise the rest of the plan around failed assignments
+        let replan_hub = hub_agent.as_ref().filter(|_| {
+            !group_failures.is_empty() && replans < MAX_REPLANS && config::current(state).replan_on_failure
+        });
+        if let Some(hub) = replan_hub {
+            if is_cancelled(state, task_run_id).await {
+                return Ok(());
             }
+            replans += 1;
+            replan_after_failures(
+                app, state, task_run_id, workspace_id, hub, &hub_process_key, &all_agents,
+                &mut plan, order, &group_failures, &agent_outputs,
+            ).await;
         }
     }
 
-    // 7. Await user confirmation before summarizing
-    // Emit awaiting_confirmation event with all agent outputs
-    let _ = app.emit("orchestration:awaiting_confirmation", &serde_json::json!({
-        "taskRunId": task_run_id,
-        "agentOutputs": &agent_outputs.iter().map(|(id, out)| {
-            let name = all_agents.iter().find(|a| a.id == *id)
-                .map(|a| a.name.as_str()).unwrap_or("Unknown");
-            serde_json::json!({ "agentId": id, "agentName": name, "output": out })
-        }).collect::<Vec<_>>(),
-    }));
-
-    // Update status to awaiting_confirmation
-    {
-        let state_clone = state.clone();
-        let id = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || {
-            task_run_repo::update_task_run_status(&state_clone, &id, "awaiting_confirmation")
-        })
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))??;
+    // Group assignments by sequence_order, for regenerating them all
+    let mut sequence_groups: HashMap<i64, Vec<&PlannedAssignment>> = HashMap::new();
+    for assignment in &plan.assignments {
+        sequence_groups
+            .entry(assignment.sequence_order)
+            .or_default()
+            .push(assignment);
     }
 
-    // Confirmation + regeneration loop
-    loop {
-        if is_cancelled(state, task_run_id).await {
-            return Ok(());
-        }
+    let mut sorted_orders: Vec<i64> = sequence_groups.keys().copied().collect();
+    sorted_orders.sort();
 
-        // Create a oneshot channel and store it
-        let (tx, rx) = tokio::sync::oneshot::channel::<ConfirmationAction>();
+    // A timed-out run is summarized right away, without the user's confirmation
+    let not_started = plan.assignments.len().saturating_sub(dispatched);
+    if timed_out {
+        log::warn!(
+            "Run {} reached its time limit; {} planned assignment(s) did not start",
+            task_run_id, not_started
+        );
+        events::emit(app, &events::RUN_TIMED_OUT, serde_json::json!({
+            "taskRunId": task_run_id,
+            "limitMs": time_limit.map(|l| l.as_millis() as i64),
+            "notStarted": not_started,
+        }));
+    } else {
+        // 7. Await user confirmation before summarizing
+        // Emit awaiting_confirmation event with all agent outputs
+        events::emit(app, &events::AWAITING_CONFIRMATION, serde_json::json!({
+            "taskRunId": task_run_id,
+            "agentOutputs": &agent_outputs.iter().map(|(id, out)| {
+                let name = all_agents.iter().find(|a| a.id == *id)
+                    .map(|a| a.name.as_str()).unwrap_or("Unknown");
+                serde_json::json!({ "agentId": id, "agentName": name, "output": out })
+            }).collect::<Vec<_>>(),
+        }));
+
+        // Update status to awaiting_confirmation
         {
-            let mut confirmations = state.pending_confirmations.lock().await;
-            confirmations.insert(task_run_id.to_string(), tx);
-        }
-
-        // Wait for user action
-        let action = match tokio::time::timeout(
-            std::time::Duration::from_secs(3600), // 1 hour timeout
-            rx,
-        ).await {
-            Ok(Ok(action)) => action,
-            Ok(Err(_)) => ConfirmationAction::Confirm, // channel dropped
-            Err(_) => ConfirmationAction::Confirm,      // timeout
-        };
+            let state_clone = state.clone();
+            let id = task_run_id.to_string();
+            telemetry::spawn_blocking(move || {
+                task_run_repo::update_task_run_status(&state_clone, &id, "awaiting_confirmation")
+            })
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+        }
 
-        match action {
-            ConfirmationAction::Confirm => {
-                break; // Proceed to summary
+        // Confirmation + regeneration loop
+        loop {
+            if is_cancelled(state, task_run_id).await {
+                return Ok(());
             }
-            ConfirmationAction::RegenerateAgent(agent_id) => {
-                // Re-run a single agent
-                log::info!("Regenerating agent {} for task {}", agent_id, task_run_id);
 
-                let agent_config = all_agents.iter()
-                    .find(|a| a.id == agent_id)
-                    .ok_or_else(|| AppError::NotFound(format!("Agent {} not found", agent_id)))?
-                    .clone();
+            // Wait for user action
+            let Some(action) = wait_for_confirmation(app, state, task_run_id).await? else {
+                return Ok(());
+            };
 
-                let agent_name = agent_config.name.clone();
-                let agent_model = agent_config.model.clone();
+            match action {
+                ConfirmationAction::Confirm => {
+                    break; // Proceed to summary
+                }
+                ConfirmationAction::Dismiss => {
+                    return dismiss_for_review(app, state, task_run_id).await;
+                }
+                ConfirmationAction::RegenerateAgent(agent_id) => {
+                    // Re-run a single agent
+                    log::info!("Regenerating agent {} for task {}", agent_id, task_run_id);
 
-                // Find the original input for this agent from plan
-                let planned = plan.assignments.iter()
-                    .find(|a| a.agent_id == agent_id);
+                    let agent_config = all_agents.iter()
+                        .find(|a| a.id == agent_id)
+                        .ok_or_else(|| AppError::NotFound(format!("Agent {} not found", agent_id)))?
+                        .clone();
 
-                let input_text = if let Some(planned) = planned {
-                    let mut parts = vec![planned.task_description.clone()];
-                    for dep_id in &planned.depends_on {
-                        if let Some(output) = agent_outputs.get(dep_id) {
-                            let dep_name = all_agents.iter()
-                                .find(|a| a.id == *dep_id)
-                                .map(|a| a.name.clone())
-                                .unwrap_or_else(|| "Previous agent".into());
-                            parts.push(format!("\n--- Output from {dep_name} ---\n{output}"));
-                        }
-                    }
-                    parts.join("\n")
-                } else {
-                    "(Regenerated)".to_string()
-                };
+                    let agent_name = agent_config.name.clone();
+                    let agent_model = agent_config.model.clone();
 
-                // Emit agent_started for the regeneration
-                let regen_assignment_id = uuid::Uuid::new_v4().to_string();
-                let acp_sid = {
-                    let sessions = state.acp_sessions.lock().await;
-                    let orch_key = format!("orch_session:{}", orch_process_key(task_run_id, &agent_id));
-                    sessions.get(&orch_key).map(|s| s.acp_session_id.clone())
-                };
+                    // Find the original input for this agent from plan
+                    let planned = plan.assignments.iter()
+                        .find(|a| a.agent_id == agent_id);
 
-                let _ = app.emit("orchestration:agent_started", &serde_json::json!({
-                    "taskRunId": task_run_id,
-                    "assignmentId": regen_assignment_id,
-                    "agentId": agent_id,
-                    "agentName": agent_name,
-                    "model": agent_model,
-                    "sequenceOrder": 0,
-                    "acpSessionId": acp_sid,
-                    "isRegeneration": true,
-                }));
+                    let input_text = if let Some(planned) = planned {
+                        let mut parts = vec![planned.task_description.clone()];
+                        for dep_id in &planned.depends_on {
+                            if let Some(output) = agent_outputs.get(dep_id) {
+                                let dep_name = all_agents.iter()
+                                    .find(|a| a.id == *dep_id)
+                                    .map(|a| a.name.clone())
+                                    .unwrap_or_else(|| "Previous agent".into());
+                                parts.push(dependency_outputs::input_part(&dep_name, output, dependency_outputs::sections(planned, dep_id)));
+                            }
+                        }
+                        parts.join("\n")
+                    } else {
+                        "(Regenerated)".to_string()
+                    };
+
+                    // Emit agent_started for the regeneration
+                    let regen_assignment_id = uuid::Uuid::new_v4().to_string();
+                    let acp_sid = {
+                        let sessions = state.acp_sessions.lock().await;
+                        let orch_key = format!("orch_session:{}", orch_process_key(task_run_id, &agent_id));
+                        sessions.get(&orch_key).map(|s| s.acp_session_id.clone())
+                    };
+
+                    events::emit(app, &events::AGENT_STARTED, serde_json::json!({
+                        "taskRunId": task_run_id,
+                        "assignmentId": regen_assignment_id,
+                        "agentId": agent_id,
+                        "agentName": agent_name,
+                        "model": agent_model,
+                        "sequenceOrder": 0,
+                        "acpSessionId": acp_sid,
+                        "isRegeneration": true,
+                    }));
 
-                let assign_start = std::time::Instant::now();
-                let result = execute_agent_assignment_with_self_healing(
-                    app, state, &agent_config, &input_text, task_run_id, None, workspace_id,
-                ).await;
-                let duration_ms = assign_start.elapsed().as_millis() as i64;
+                    let working_dir = resolve_assignment_working_directory(
+                        state, workspace_id, planned.and_then(|p| p.working_directory.as_deref()),
+                    );
+                    let overrides = planned.map(assignment_overrides::checked).unwrap_or_default();
+                    assignment_overrides::begin(state, task_run_id, &agent_id, overrides).await;
+                    prompt_content::begin(state, task_run_id, &agent_id, planned.map_or(&[][..], |p| &p.attachments)).await;
+                    running_assignments::begin(state, task_run_id, &agent_id, &regen_assignment_id).await;
+                    let assign_start = std::time::Instant::now();
+                    let result = execute_agent_assignment_with_self_healing(
+                        app, state, &agent_config, &input_text, task_run_id, None, workspace_id, working_dir.as_deref(),
+                    ).await;
+                    assignment_overrides::end(state, task_run_id, &agent_id).await;
+                    prompt_content::end(state, task_run_id, &agent_id).await;
+                    running_assignments::end(state, task_run_id, &agent_id).await;
+                    let duration_ms = assign_start.elapsed().as_millis() as i64;
 
-                match result {
-                    Ok(prompt_result) => {
-                        total_tokens_in += prompt_result.tokens_in;
-                        total_tokens_out += prompt_result.tokens_out;
-                        total_cache_creation_tokens += prompt_result.cache_creation_tokens;
-                        total_cache_read_tokens += prompt_result.cache_read_tokens;
+                    match result {
+                        Ok(prompt_result) => {
+                            total_tokens_in += prompt_result.tokens_in;
+                            total_tokens_out += prompt_result.tokens_out;
+                            total_cache_creation_tokens += prompt_result.cache_creation_tokens;
+                            total_cache_read_tokens += prompt_result.cache_read_tokens;
 
-                        let _ = app.emit("orchestration:agent_completed", &serde_json::json!({
-                            "taskRunId": task_run_id,
-                            "assignmentId": regen_assignment_id,
-                            "agentId": agent_id,
-                            "agentName": agent_name,
-                            "durationMs": duration_ms,
-                            "status": "completed",
-                            "tokensIn": prompt_result.tokens_in,
-                            "tokensOut": prompt_result.tokens_out,
-                            "cacheCreationTokens": prompt_result.cache_creation_tokens,
-                            "cacheReadTokens": prompt_result.cache_read_tokens,
-                            "acpSessionId": prompt_result.acp_session_id,
-                            "output": prompt_result.text.clone(),
-                        }));
+                            events::emit(app, &events::AGENT_COMPLETED, serde_json::json!({
+                                "taskRunId": task_run_id,
+                                "assignmentId": regen_assignment_id,
+                                "agentId": agent_id,
+                                "agentName": agent_name,
+                                "durationMs": duration_ms,
+                                "status": "completed",
+                                "tokensIn": prompt_result.tokens_in,
+                                "tokensOut": prompt_result.tokens_out,
+                                "cacheCreationTokens": prompt_result.cache_creation_tokens,
+                                "cacheReadTokens": prompt_result.cache_read_tokens,
+                                "acpSessionId": prompt_result.acp_session_id,
+                                "output": prompt_result.text.clone(),
+                            }));
 
-                        agent_outputs.insert(agent_id.clone(), prompt_result.text);
-                    }
-                    Err(e) => {
-                        let err_msg = e.to_string();
+                            agent_outputs.insert(agent_id.clone(), prompt_result.text);
+                        }
+                        Err(e) => {
+                            let err_msg = e.to_string();
 
-                        // Auto-disable agent on regeneration failure
-                        {
-                            let state_for_disable = state.clone();
-                            let agent_id_for_disable = agent_id.clone();
-                            let err_for_disable = err_msg.clone();
-                            let _ = tokio::task::spawn_blocking(move || {
-                                agent_repo::disable_agent(
-                                    &state_for_disable,
-                                    &agent_id_for_disable,
-                                    &err_for_disable,
-                                )
-                            }).await;
+                            // Auto-disable agent on regeneration failure
+                            {
+                                let state_for_disable = state.clone();
+                                let agent_id_for_disable = agent_id.clone();
+                                let err_for_disable = err_msg.clone();
+                                let transient = e.is_transient();
+                                let _ = telemetry::spawn_blocking(move || {
+                                    agent_repo::disable_agent(
+                                        &state_for_disable,
+                                        &agent_id_for_disable,
+                                        &err_for_disable,
+                                        transient,
+                                    )
+                                }).await;
 
-                            let _ = app.emit("orchestration:agent_auto_disabled", &serde_json::json!({
+                                events::emit(app, &events::AGENT_AUTO_DISABLED, serde_json::json!({
+                                    "taskRunId": task_run_id,
+                                    "agentId": agent_id,
+                                    "agentName": agent_name,
+                                    "reason": &err_msg,
+                                }));
+                            }
+
+                            events::emit(app, &events::AGENT_COMPLETED, serde_json::json!({
                                 "taskRunId": task_run_id,
+                                "assignmentId": regen_assignment_id,
                                 "agentId": agent_id,
                                 "agentName": agent_name,
-                                "reason": &err_msg,
+                                "durationMs": duration_ms,
+                                "status": "failed",
+                                "error": &err_msg,
                             }));
+                            agent_outputs.insert(agent_id.clone(), format!("(Agent failed: {})", err_msg));
                         }
-
-                        let _ = app.emit("orchestration:agent_completed", &serde_json::json!({
-                            "taskRunId": task_run_id,
-                            "assignmentId": regen_assignment_id,
-                            "agentId": agent_id,
-                            "agentName": agent_name,
-                            "durationMs": duration_ms,
-                            "status": "failed",
-                            "error": &err_msg,
-                        }));
-                        agent_outputs.insert(agent_id.clone(), format!("(Agent failed: {})", err_msg));
                     }
-                }
-
-                // Re-emit awaiting_confirmation so UI updates
-                let _ = app.emit("orchestration:awaiting_confirmation", &serde_json::json!({
-                    "taskRunId": task_run_id,
-                    "agentOutputs": &agent_outputs.iter().map(|(id, out)| {
-                        let name = all_agents.iter().find(|a| a.id == *id)
-                            .map(|a| a.name.as_str()).unwrap_or("Unknown");
-                        serde_json::json!({ "agentId": id, "agentName": name, "output": out })
-                    }).collect::<Vec<_>>(),
-                }));
-            }
-            ConfirmationAction::RegenerateAll => {
-                // Re-run all agents
-                log::info!("Regenerating all agents for task {}", task_run_id);
 
-                // Clear existing outputs
-                agent_outputs.clear();
-
-                // Re-execute all assignments following the same sequence order
-                for order in &sorted_orders {
-                    let group = &sequence_groups[order];
-
-                    for planned in group {
-                        if is_cancelled(state, task_run_id).await {
-                            return Ok(());
-                        }
+                    // Re-emit awaiting_confirmation so UI updates
+                    events::emit(app, &events::AWAITING_CONFIRMATION, serde_json::json!({
+                        "taskRunId": task_run_id,
+                        "agentOutputs": &agent_outputs.iter().map(|(id, out)| {
+                            let name = all_agents.iter().find(|a| a.id == *id)
+                                .map(|a| a.name.as_str()).unwrap_or("Unknown");
+                            serde_json::json!({ "agentId": id, "agentName": name, "output": out })
+                        }).collect::<Vec<_>>(),
+                    }));
+                }
+                ConfirmationAction::RegenerateAll => {
+                    // Re-run all agents
+                    log::info!("Regenerating all agents for task {}", task_run_id);
 
-                        let agent_config = all_agents.iter()
-                            .find(|a| a.id == planned.agent_id)
-                            .ok_or_else(|| AppError::NotFound(format!("Agent {} not found", planned.agent_id)))?
-                            .clone();
+                    // Clear existing outputs
+                    agent_outputs.clear();
 
-                        let agent_name = agent_config.name.clone();
-                        let agent_model = agent_config.model.clone();
+                    // Re-execute all assignments following the same sequence order
+                    for order in &sorted_orders {
+                        let group = &sequence_groups[order];
 
-                        let mut input_parts = vec![planned.task_description.clone()];
-                        for dep_id in &planned.depends_on {
-                            if let Some(output) = agent_outputs.get(dep_id) {
-                                let dep_name = all_agents.iter()
-                                    .find(|a| a.id == *dep_id)
-                                    .map(|a| a.name.clone())
-                                    .unwrap_or_else(|| "Previous agent".into());
-                                input_parts.push(format!("\n--- Output from {dep_name} ---\n{output}"));
+                        for planned in group {
+                            if is_cancelled(state, task_run_id).await {
+                                return Ok(());
                             }
-                        }
-                        let input_text = input_parts.join("\n");
-
-                        let regen_assignment_id = uuid::Uuid::new_v4().to_string();
-                        let acp_sid = {
-                            let sessions = state.acp_sessions.lock().await;
-                            let orch_key = format!("orch_session:{}", orch_process_key(task_run_id, &planned.agent_id));
-                            sessions.get(&orch_key).map(|s| s.acp_session_id.clone())
-                        };
-
-                        let _ = app.emit("orchestration:agent_started", &serde_json::json!({
-                            "taskRunId": task_run_id,
-                            "assignmentId": regen_assignment_id,
-                            "agentId": planned.agent_id,
-                            "agentName": agent_name,
-                            "model": agent_model,
-                            "sequenceOrder": planned.sequence_order,
-                            "acpSessionId": acp_sid,
-                            "isRegeneration": true,
-                        }));
-
-                        let assign_start = std::time::Instant::now();
-                        let result = execute_agent_assignment_with_self_healing(
-                            app, state, &agent_config, &input_text, task_run_id, None, workspace_id,
-                        ).await;
-                        let duration_ms = assign_start.elapsed().as_millis() as i64;
 
-                        match result {
-                            Ok(prompt_result) => {
-                                total_tokens_in += prompt_result.tokens_in;
-                                total_tokens_out += prompt_result.tokens_out;
-                                total_cache_creation_tokens += prompt_result.cache_creation_tokens;
-                                total_cache_read_tokens += prompt_result.cache_read_tokens;
+                            let agent_config = all_agents.iter()
+                                .find(|a| a.id == planned.agent_id)
+                                .ok_or_else(|| AppError::NotFound(format!("Agent {} not found", planned.agent_id)))?
+                                .clone();
+
+                            let agent_name = agent_config.name.clone();
+                            let agent_model = agent_config.model.clone();
+
+                            let mut input_parts = vec![planned.task_description.clone()];
+                            for dep_id in &planned.depends_on {
+                                if let Some(output) = agent_outputs.get(dep_id) {
+                                    let dep_name = all_agents.iter()
+                                        .find(|a| a.id == *dep_id)
+                                        .map(|a| a.name.clone())
+                                        .unwrap_or_else(|| "Previous agent".into());
+                                    input_parts.push(dependency_outputs::input_part(&dep_name, output, dependency_outputs::sections(planned, dep_id)));
+                                }
+                            }
+                            let input_text = input_parts.join("\n");
 
-                                let _ = app.emit("orchestration:agent_completed", &serde_json::json!({
-                                    "taskRunId": task_run_id,
-                                    "assignmentId": regen_assignment_id,
-                                    "agentId": planned.agent_id,
-                                    "agentName": agent_name,
-                                    "durationMs": duration_ms,
-                                    "status": "completed",
-                                    "tokensIn": prompt_result.tokens_in,
-                                    "tokensOut": prompt_result.tokens_out,
-                                    "cacheCreationTokens": prompt_result.cache_creation_tokens,
-                                    "cacheReadTokens": prompt_result.cache_read_tokens,
-                                    "acpSessionId": prompt_result.acp_session_id,
-                                    "output": prompt_result.text.clone(),
-                                }));
+                            let regen_assignment_id = uuid::Uuid::new_v4().to_string();
+                            let acp_sid = {
+                                let sessions = state.acp_sessions.lock().await;
+                                let orch_key = format!("orch_session:{}", orch_process_key(task_run_id, &planned.agent_id));
+                                sessions.get(&orch_key).map(|s| s.acp_session_id.clone())
+                            };
 
-                                agent_outputs.insert(planned.agent_id.clone(), prompt_result.text);
-                            }
-                            Err(e) => {
-                                let err_msg = e.to_string();
+                            events::emit(app, &events::AGENT_STARTED, serde_json::json!({
+                                "taskRunId": task_run_id,
+                                "assignmentId": regen_assignment_id,
+                                "agentId": planned.agent_id,
+                                "agentName": agent_name,
+                                "model": agent_model,
+                                "sequenceOrder": planned.sequence_order,
+                                "acpSessionId": acp_sid,
+                                "isRegeneration": true,
+                            }));
 
-                                // Auto-disable agent on regenerate-all failure
-                                {
-                                    let state_for_disable = state.clone();
-                                    let agent_id_for_disable = planned.agent_id.clone();
-                                    let err_for_disable = err_msg.clone();
-                                    let _ = tokio::task::spawn_blocking(move || {
-                                        agent_repo::disable_agent(
-                                            &state_for_disable,
-                                            &agent_id_for_disable,
-                                            &err_for_disable,
-                                        )
-                                    }).await;
+                            let working_dir = resolve_assignment_working_directory(
+                                state, workspace_id, planned.working_directory.as_deref(),
+                            );
+                            assignment_overrides::begin(state, task_run_id, &planned.agent_id, assignment_overrides::checked(planned)).await;
+                            prompt_content::begin(state, task_run_id, &planned.agent_id, &planned.attachments).await;
+                            running_assignments::begin(state, task_run_id, &planned.agent_id, &regen_assignment_id).await;
+                            let assign_start = std::time::Instant::now();
+                            let result = execute_agent_assignment_with_self_healing(
+                                app, state, &agent_config, &input_text, task_run_id, None, workspace_id, working_dir.as_deref(),
+                            ).await;
+                            assignment_overrides::end(state, task_run_id, &planned.agent_id).await;
+                            prompt_content::end(state, task_run_id, &planned.agent_id).await;
+                            running_assignments::end(state, task_run_id, &planned.agent_id).await;
+                            let duration_ms = assign_start.elapsed().as_millis() as i64;
+
+                            match result {
+                                Ok(prompt_result) => {
+                                    total_tokens_in += prompt_result.tokens_in;
+                                    total_tokens_out += prompt_result.tokens_out;
+                                    total_cache_creation_tokens += prompt_result.cache_creation_tokens;
+                                    total_cache_read_tokens += prompt_result.cache_read_tokens;
+
+                                    events::emit(app, &events::AGENT_COMPLETED, serde_json::json!({
+                                        "taskRunId": task_run_id,
+                                        "assignmentId": regen_assignment_id,
+                                        "agentId": planned.agent_id,
+                                        "agentName": agent_name,
+                                        "durationMs": duration_ms,
+                                        "status": "completed",
+                                        "tokensIn": prompt_result.tokens_in,
+                                        "tokensOut": prompt_result.tokens_out,
+                                        "cacheCreationTokens": prompt_result.cache_creation_tokens,
+                                        "cacheReadTokens": prompt_result.cache_read_tokens,
+                                        "acpSessionId": prompt_result.acp_session_id,
+                                        "output": prompt_result.text.clone(),
+                                    }));
 
-                                    let _ = app.emit("orchestration:agent_auto_disabled", &serde_json::json!({
+                                    agent_outputs.insert(planned.agent_id.clone(), prompt_result.text);
+                                }
+                                Err(e) => {
+                                    let err_msg = e.to_string();
+
+                                    // Auto-disable agent on regenerate-all failure
+                                    {
+                                        let state_for_disable = state.clone();
+                                        let agent_id_for_disable = planned.agent_id.clone();
+                                        let err_for_disable = err_msg.clone();
+                                        let transient = e.is_transient();
+                                        let _ = telemetry::spawn_blocking(move || {
+                                            agent_repo::disable_agent(
+                                                &state_for_disable,
+                                                &agent_id_for_disable,
+                                                &err_for_disable,
+                                                transient,
+                                            )
+                                        }).await;
+
+                                        events::emit(app, &events::AGENT_AUTO_DISABLED, serde_json::json!({
+                                            "taskRunId": task_run_id,
+                                            "agentId": planned.agent_id,
+                                            "agentName": agent_name,
+                                            "reason": &err_msg,
+                                        }));
+                                    }
+
+                                    events::emit(app, &events::AGENT_COMPLETED, serde_json::json!({
                                         "taskRunId": task_run_id,
+                                        "assignmentId": regen_assignment_id,
                                         "agentId": planned.agent_id,
                                         "agentName": agent_name,
-                                        "reason": &err_msg,
+                                        "durationMs": duration_ms,
+                                        "status": "failed",
+                                        "error": &err_msg,
                                     }));
+                                    agent_outputs.insert(planned.agent_id.clone(), format!("(Agent failed: {})", err_msg));
                                 }
-
-                                let _ = app.emit("orchestration:agent_completed", &serde_json::json!({
-                                    "taskRunId": task_run_id,
-                                    "assignmentId": regen_assignment_id,
-                                    "agentId": planned.agent_id,
-                                    "agentName": agent_name,
-                                    "durationMs": duration_ms,
-                                    "status": "failed",
-                                    "error": &err_msg,
-                                }));
-                                agent_outputs.insert(planned.agent_id.clone(), format!("(Agent failed: {})", err_msg));
                             }
                         }
                     }
-                }
 
-                // Re-emit awaiting_confirmation
-                let _ = app.emit("orchestration:awaiting_confirmation", &serde_json::json!({
-                    "taskRunId": task_run_id,
-                    "agentOutputs": &agent_outputs.iter().map(|(id, out)| {
-                        let name = all_agents.iter().find(|a| a.id == *id)
-                            .map(|a| a.name.as_str()).unwrap_or("Unknown");
-                        serde_json::json!({ "agentId": id, "agentName": name, "output": out })
-                    }).collect::<Vec<_>>(),
-                }));
+                    // Re-emit awaiting_confirmation
+                    events::emit(app, &events::AWAITING_CONFIRMATION, serde_json::json!({
+                        "taskRunId": task_run_id,
+                        "agentOutputs": &agent_outputs.iter().map(|(id, out)| {
+                            let name = all_agents.iter().find(|a| a.id == *id)
+                                .map(|a| a.name.as_str()).unwrap_or("Unknown");
+                            serde_json::json!({ "agentId": id, "agentName": name, "output": out })
+                        }).collect::<Vec<_>>(),
+                    }));
+                }
             }
         }
     }
@@ -997,8 +1287,9 @@ Rules:
     }
 
     // 8. Finalize — ask control hub for a summary
-    let summary_prompt = format!(
-        "Summarize the results of the orchestration.\n\nOriginal request: {}\n\nAgent outputs:\n{}",
+    let mut summary_prompt = format!(
+        "{}Summarize the results of the orchestration.\n\nOriginal request: {}\n\nAgent outputs:\n{}",
+        hub_persona(state, workspace_id).await,
         user_prompt,
         agent_outputs
             .iter()
@@ -1012,11 +1303,30 @@ Rules:
             })
             .collect::<String>()
     );
+    if timed_out {
+        summary_prompt.push_str(&run_deadline::summary_note(not_started));
+    }
 
-    let summary = send_prompt_to_agent(app, state, &hub_agent.id, &summary_prompt, Some(task_run_id), None, workspace_id, &hub_process_key)
+    let hub_summary = match &hub_agent {
+        // Best effort: a timed-out run does not wait long for the hub
+        Some(hub) if timed_out => tokio::time::timeout(
+            run_deadline::SUMMARY_GRACE,
+            summarize_with_failover(app, state, task_run_id, workspace_id, hub, &hub_process_key, &summary_prompt),
+        )
         .await
-        .map(|r| r.text)
-        .unwrap_or_else(|_| "Summary not available".into());
+        .unwrap_or_else(|_| {
+            log::warn!("Control Hub did not summarize timed-out run {} in time", task_run_id);
+            None
+        }),
+        Some(hub) => summarize_with_failover(app, state, task_run_id, workspace_id, hub, &hub_process_key, &summary_prompt).await,
+        None => None,
+    };
+    let summary =
+        hub_summary.unwrap_or_else(|| fallback_planner::summarize(&agent_outputs, &all_agents, i18n::current(state)));
+    let summary = match time_limit.filter(|_| timed_out) {
+        Some(limit) => run_deadline::mark_summary(&summary, limit, not_started, i18n::current(state)),
+        None => summary,
+    };
 
     let total_duration_ms = start_time.elapsed().as_millis() as i64;
 
@@ -1025,7 +1335,7 @@ Rules:
         let state_clone = state.clone();
         let id = task_run_id.to_string();
         let sum = summary.clone();
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_summary(&state_clone, &id, &sum)
         })
         .await
@@ -1034,7 +1344,7 @@ Rules:
     {
         let state_clone = state.clone();
         let id = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_totals(
                 &state_clone, &id, total_tokens_in, total_tokens_out, total_cache_creation_tokens, total_cache_read_tokens, total_duration_ms,
             )
@@ -1045,18 +1355,27 @@ Rules:
     {
         let state_clone = state.clone();
         let id = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_status(&state_clone, &id, "completed")
         })
         .await
         .map_err(|e| AppError::Internal(e.to_string()))??;
     }
+    if timed_out {
+        let state_clone = state.clone();
+        let id = task_run_id.to_string();
+        telemetry::spawn_blocking(move || task_run_repo::set_timed_out(&state_clone, &id))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+    }
 
     // Write output summary file
     write_output_summary(state, task_run_id, user_prompt, &plan, &all_agents, &summary, total_duration_ms).await;
+    remember_task_run(state, task_run_id).await;
 
-    let _ = app.emit("orchestration:completed", &serde_json::json!({
+    events::emit(app, &events::RUN_COMPLETED, serde_json::json!({
         "taskRunId": task_run_id,
+        "timedOut": timed_out,
         "summary": summary,
         "totalDurationMs": total_duration_ms,
         "totalTokensIn": total_tokens_in,
@@ -1068,21 +1387,258 @@ Rules:
     Ok(())
 }
 
-fn build_agent_catalog_refs(agents: &[&AgentConfig], discovery: Option<&SkillDiscoveryResult>) -> String {
-    build_structured_agent_catalog(agents, discovery)
-}
+/// Ask the control hub for a task plan, with one retry when the response is not valid JSON.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip(app, state, hub_agent, user_prompt, registry_content), fields(hub_agent_id = %hub_agent.id))]
+async fn request_plan(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    hub_agent: &AgentConfig,
+    hub_process_key: &str,
+    task_run_id: &str,
+    user_prompt: &str,
+    workspace_id: Option<&str>,
+    cwd: &str,
+    registry_content: &str,
+) -> AppResult<TaskPlan> {
+    let subdirectories = list_workspace_subdirectories(cwd);
+    let workspace_layout = if subdirectories.is_empty() {
+        String::new()
+    } else {
+        format!("\n## Workspace Sub-directories\n\n{}\n", subdirectories.join("\n"))
+    };
 
-/// Build a structured agent catalog in XML format for the control hub prompt.
-/// XML is recommended by the Agent Skills spec for Claude model injection.
-fn build_structured_agent_catalog(agents: &[&AgentConfig], discovery: Option<&SkillDiscoveryResult>) -> String {
-    let mut xml = String::from("<available_agents>\n");
+    let memories = {
+        let state_clone = state.clone();
+        let ws_id = workspace_id.map(|s| s.to_string());
+        let prompt = user_prompt.to_string();
+        telemetry::spawn_blocking(move || memory::planning_context(&state_clone, ws_id.as_deref(), &prompt))
+            .await
+            .unwrap_or_default()
+    };
 
-    for a in agents {
-        let skills = if a.is_control_hub {
-            resolve_agent_skills(a)
-        } else {
-            resolve_agent_skills_with_discovery(a, discovery)
-        };
+    let knowledge_context = {
+        let state_clone = state.clone();
+        let ws_id = workspace_id.map(|s| s.to_string());
+        let prompt = user_prompt.to_string();
+        telemetry::spawn_blocking(move || knowledge::planning_context(&state_clone, ws_id.as_deref(), &prompt))
+            .await
+            .unwrap_or_default()
+    };
+
+    let run_context = {
+        let state_clone = state.clone();
+        let trid = task_run_id.to_string();
+        match telemetry::spawn_blocking(move || run_context::planning_context(&state_clone, &trid)).await {
+            Ok(Ok(context)) => context,
+            Ok(Err(e)) => {
+                log::warn!("Failed to load the earlier run attached to {}: {}", task_run_id, e);
+                String::new()
+            }
+            Err(e) => {
+                log::warn!("Spawn blocking failed: {}", e);
+                String::new()
+            }
+        }
+    };
+
+    let persona = hub_persona(state, workspace_id).await;
+    let plan_prompt = format!(
+        r#"{persona}You are the orchestrator control hub. Decompose the user request into subtasks and assign each to the best-matching agent.
+
+## Available Agents
+
+{catalog}
+
+## User Request
+
+{user_prompt}
+{run_context}{workspace_layout}{memories}{knowledge_context}
+## Instructions
+
+1. Analyze the request and identify subtasks based ONLY on the information above.
+2. Match each subtask to the agent whose skills best fit.
+3. Respect each agent's constraints.
+4. If no agent has a matching skill, choose the most general-purpose agent.
+
+CRITICAL: You MUST respond with ONLY a valid JSON object. No explanations, no preamble, no markdown, no thinking — ONLY the JSON object below. Do NOT attempt to explore, research, or use tools. Make your plan based solely on the agent catalog and user request provided above.
+
+{{"analysis": "Brief reasoning about task decomposition and agent matching", "isolated": false, "assignments": [{{"agent_id": "uuid-from-catalog", "task_description": "Detailed instruction for the agent", "sequence_order": 0, "depends_on": [], "matched_skills": ["skill_id"], "selection_reason": "Why this agent", "working_directory": null, "output_file": null, "max_tokens_out": null, "max_cost": null, "confidence": 0.9, "assumptions": [], "overrides": null, "attachments": [], "dependency_sections": {{}}}}]}}
+
+Rules:
+- Output ONLY the JSON object, nothing else
+- agent_id must come from the catalog above
+- matched_skills must reference skill IDs from the assigned agent
+- sequence_order: 0 for parallel, increment for sequential
+- depends_on: agent_ids whose output is needed first
+- dependency_sections: optional parts of a dependency's output the agent needs, by agent_id, e.g. {{"uuid-of-dependency": ["API", "summary"]}}: Markdown headings, or keys / dotted key paths when that agent answers in JSON. Only those parts are passed on; dependencies not listed are passed whole. Use it in long chains where the agent needs little of a long output
+- working_directory: optional sub-directory (relative to the workspace root) the agent should work in; null for the root
+- output_file: optional file name (e.g. "report.md") when the subtask's result is a document or code file; the agent's text is saved there and later agents get a reference to it. null otherwise
+- max_tokens_out / max_cost: optional caps on the agent's output tokens and cost in USD for open-ended subtasks; near a cap the agent is told to wrap up. null for no cap
+- isolated: true when agents will produce temporary or intermediate files (experiments, downloads, drafts) that should stay out of the workspace; they then get a scratch directory
+- confidence: 0 to 1, how sure you are that the subtask is what the user wants and that the agent can do it
+- assumptions: what you assumed that the request does not say; empty when nothing was assumed
+- overrides: optional agent settings for this subtask only, e.g. {{"model": "...", "temperature": 0.2, "system_prompt": "...", "permission_profile": "read_only"}}; permission_profile is "ask", "read_only" (edits and commands are rejected) or "auto_approve". null to use the agent's own settings
+- attachments: optional workspace files the agent needs to see, e.g. [{{"path": "docs/mockup.png", "description": "Target layout"}}]; paths are relative to the workspace root. Images are shown to agents that take them, other files are linked or embedded. Empty when none
+- Always return at least one assignment"#,
+        catalog = registry_content,
+        workspace_layout = workspace_layout,
+    );
+
+    let plan_response = send_prompt_to_agent(app, state, &hub_agent.id, &plan_prompt, Some(task_run_id), None, workspace_id, None, hub_process_key).await?;
+
+    // Parse the plan, with one retry on failure
+    let plan_text = chaos::corrupt_plan(state, &plan_response.text).unwrap_or(plan_response.text);
+    let plan = match parse_task_plan(&plan_text) {
+        Ok(p) => p,
+        Err(first_err) => {
+            log::warn!("First plan parse failed, retrying with correction prompt: {}", first_err);
+
+            let retry_prompt = format!(
+                "Your previous response was not valid JSON. I need ONLY a raw JSON object, no text before or after it.\n\n\
+                 The expected format is:\n\
+                 {{\"analysis\": \"...\", \"assignments\": [{{\"agent_id\": \"...\", \"task_description\": \"...\", \"sequence_order\": 0, \"depends_on\": [], \"matched_skills\": [\"...\"], \"selection_reason\": \"...\", \"working_directory\": null}}]}}\n\n\
+                 Respond with ONLY the JSON object. No markdown code fences, no explanation."
+            );
+
+            let retry_response = send_prompt_to_agent(app, state, &hub_agent.id, &retry_prompt, Some(task_run_id), None, workspace_id, None, hub_process_key).await?;
+
+            parse_task_plan(&retry_response.text).map_err(|_| first_err)?
+        }
+    };
+
+    Ok(plan)
+}
+
+/// Start the hub and get the run's plan from it, or take the preset plan.
+#[allow(clippy::too_many_arguments)]
+async fn plan_with_hub(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    hub_agent: &AgentConfig,
+    hub_process_key: &str,
+    task_run_id: &str,
+    user_prompt: &str,
+    workspace_id: Option<&str>,
+    cwd: &str,
+    registry_content: &str,
+    preset_plan: Option<&TaskPlan>,
+) -> AppResult<TaskPlan> {
+    ensure_agent_running(app, state, hub_agent, hub_process_key).await?;
+    match preset_plan {
+        Some(plan) => Ok(plan.clone()),
+        None => request_plan(app, state, hub_agent, hub_process_key, task_run_id, user_prompt, workspace_id, cwd, registry_content).await,
+    }
+}
+
+/// After the serving hub failed, switch the run to the workspace's secondary
+/// Control Hub. Returns `None` when there is none to switch to.
+async fn fail_over_hub(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run_id: &str,
+    workspace_id: Option<&str>,
+    failed_hub: &AgentConfig,
+    error: &AppError,
+) -> Option<AgentConfig> {
+    if is_cancelled(state, task_run_id).await {
+        return None;
+    }
+    let secondary = {
+        let state_clone = state.clone();
+        let ws_id = workspace_id.map(|s| s.to_string());
+        telemetry::spawn_blocking(move || agent_repo::get_secondary_hub(&state_clone, ws_id.as_deref()))
+            .await
+            .ok()?
+            .ok()??
+    };
+    if secondary.id == failed_hub.id || !secondary.is_enabled {
+        return None;
+    }
+
+    log::warn!(
+        "Control Hub {} failed for task {} ({}), failing over to {}",
+        failed_hub.name, task_run_id, error, secondary.name
+    );
+    stop_and_cleanup_agent(state, &orch_process_key(task_run_id, &failed_hub.id), &failed_hub.id).await;
+    record_served_hub(state, task_run_id, &secondary.id).await;
+    events::emit(app, &events::HUB_FAILOVER, serde_json::json!({
+        "taskRunId": task_run_id,
+        "fromAgentId": failed_hub.id,
+        "toAgentId": secondary.id,
+        "error": error.to_string(),
+    }));
+    Some(secondary)
+}
+
+async fn record_served_hub(state: &AppState, task_run_id: &str, hub_agent_id: &str) {
+    let state_clone = state.clone();
+    let id = task_run_id.to_string();
+    let hub_id = hub_agent_id.to_string();
+    let recorded = telemetry::spawn_blocking(move || task_run_repo::set_served_by_hub(&state_clone, &id, &hub_id)).await;
+    if let Ok(Err(e)) = recorded {
+        log::warn!("Failed to record serving hub for task {}: {}", task_run_id, e);
+    }
+}
+
+/// Ask the hub for the run summary, failing over to the secondary hub.
+async fn summarize_with_failover(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run_id: &str,
+    workspace_id: Option<&str>,
+    hub_agent: &AgentConfig,
+    hub_process_key: &str,
+    summary_prompt: &str,
+) -> Option<String> {
+    // The summary streams to the user, who may accept it early or cancel it
+    let ask = |hub: AgentConfig, process_key: String| async move {
+        ensure_agent_running(app, state, &hub, &process_key).await?;
+        let cancel = summary_stream::start(state, task_run_id, &hub.id).await;
+        let result =
+            send_prompt_to_agent(app, state, &hub.id, summary_prompt, Some(task_run_id), Some(&cancel), workspace_id, None, &process_key)
+                .await;
+        match (result, summary_stream::finish(state, task_run_id).await) {
+            (Ok(result), None | Some(StoppedSummary::Accepted(_))) => Ok(Some(result.text)),
+            (Err(e), None) => Err(e),
+            (result, Some(stopped)) => {
+                if result.is_err() {
+                    cancel_agent_turn(state, &process_key).await;
+                }
+                match stopped {
+                    StoppedSummary::Accepted(text) => Ok(Some(text)),
+                    StoppedSummary::Cancelled => {
+                        log::info!("Summary of task run {} cancelled, using the built-in summary", task_run_id);
+                        Ok(None)
+                    }
+                }
+            }
+        }
+    };
+    let error = match ask(hub_agent.clone(), hub_process_key.to_string()).await {
+        Ok(summary) => return summary,
+        Err(e) => e,
+    };
+    if let Some(secondary) = fail_over_hub(app, state, task_run_id, workspace_id, hub_agent, &error).await {
+        let process_key = orch_process_key(task_run_id, &secondary.id);
+        if let Ok(summary) = ask(secondary, process_key).await {
+            return summary;
+        }
+    }
+    Some("Summary not available".into())
+}
+
+fn build_agent_catalog_refs(agents: &[&AgentConfig], discovery: Option<&SkillDiscoveryResult>) -> String {
+    build_structured_agent_catalog(agents, discovery)
+}
+
+/// Build a structured agent catalog in XML format for the control hub prompt.
+/// XML is recommended by the Agent Skills spec for Claude model injection.
+pub(crate) fn build_structured_agent_catalog(agents: &[&AgentConfig], discovery: Option<&SkillDiscoveryResult>) -> String {
+    let mut xml = String::from("<available_agents>\n");
+
+    for a in agents {
+        let skills = catalog_skills(a, discovery);
 
         xml.push_str("  <agent>\n");
         xml.push_str(&format!("    <id>{}</id>\n", xml_escape(&a.id)));
@@ -1129,6 +1685,16 @@ fn build_structured_agent_catalog(agents: &[&AgentConfig], discovery: Option<&Sk
     xml
 }
 
+/// The skills an agent is listed with in the catalog. The control hub
+/// does not take on discovered workspace skills.
+pub(crate) fn catalog_skills(agent: &AgentConfig, discovery: Option<&SkillDiscoveryResult>) -> Vec<AgentSkill> {
+    if agent.is_control_hub {
+        resolve_agent_skills(agent)
+    } else {
+        resolve_agent_skills_with_discovery(agent, discovery)
+    }
+}
+
 /// Escape special XML characters in text content and attribute values.
 fn xml_escape(s: &str) -> String {
     s.replace('&', "&amp;")
@@ -1140,7 +1706,7 @@ fn xml_escape(s: &str) -> String {
 /// Resolve the effective skills for an agent.
 /// If `skills_json` is populated, use it directly.
 /// Otherwise, auto-convert `capabilities_json` entries into minimal AgentSkill structs.
-fn resolve_agent_skills(agent: &AgentConfig) -> Vec<AgentSkill> {
+pub(crate) fn resolve_agent_skills(agent: &AgentConfig) -> Vec<AgentSkill> {
     // Try parsing skills_json first
     if !agent.skills_json.is_empty() && agent.skills_json != "[]" {
         if let Ok(skills) = serde_json::from_str::<Vec<AgentSkill>>(&agent.skills_json) {
@@ -1238,6 +1804,7 @@ fn parse_a2a_call(text: &str) -> Option<A2aCall> {
 /// After each agent execution, checks the output for `<a2a_call>` markers.
 /// If found, executes the target agent and sends a follow-up prompt with the result.
 /// Loops until no more A2A calls or max iterations reached.
+#[allow(clippy::too_many_arguments)]
 async fn execute_with_a2a_routing(
     app: &tauri::AppHandle,
     state: &AppState,
@@ -1246,6 +1813,7 @@ async fn execute_with_a2a_routing(
     task_run_id: &str,
     cancel_token: Option<&CancellationToken>,
     workspace_id: Option<&str>,
+    working_dir: Option<&str>,
     all_agents: &[AgentConfig],
 ) -> AppResult<AgentPromptResult> {
     let mut current_input = initial_input.to_string();
@@ -1254,12 +1822,19 @@ async fn execute_with_a2a_routing(
 
     for iteration in 0..MAX_A2A_ITERATIONS {
         let result = execute_agent_assignment_with_self_healing(
-            app, state, agent, &current_input, task_run_id, cancel_token, workspace_id,
+            app, state, agent, &current_input, task_run_id, cancel_token, workspace_id, working_dir,
         )
         .await?;
 
         accumulated_text.push_str(&result.text);
 
+        // A capped agent is out of budget for follow-ups
+        if result.capped {
+            let mut final_result = result;
+            final_result.text = accumulated_text;
+            return Ok(final_result);
+        }
+
         // Check for A2A call in the output
         if let Some(a2a_call) = parse_a2a_call(&result.text) {
             // Validate target agent exists in workspace
@@ -1275,7 +1850,7 @@ async fn execute_with_a2a_routing(
             }
 
             // Emit A2A call event
-            let _ = app.emit("orchestration:a2a_call", &serde_json::json!({
+            events::emit(app, &events::A2A_CALL, serde_json::json!({
                 "taskRunId": task_run_id,
                 "callerAgentId": agent.id,
                 "targetAgentId": a2a_call.target_agent_id,
@@ -1293,93 +1868,903 @@ async fn execute_with_a2a_routing(
                 Some(task_run_id),
                 cancel_token,
                 workspace_id,
+                None,
                 &target_process_key,
             )
             .await;
 
-            let a2a_response = match target_result {
-                Ok(r) => r.text,
-                Err(e) => format!("(A2A call failed: {})", e),
-            };
+            let a2a_response = match target_result {
+                Ok(r) => r.text,
+                Err(e) => format!("(A2A call failed: {})", e),
+            };
+
+            // Emit A2A result event
+            events::emit(app, &events::A2A_RESULT, serde_json::json!({
+                "taskRunId": task_run_id,
+                "callerAgentId": agent.id,
+                "targetAgentId": a2a_call.target_agent_id,
+                "resultPreview": a2a_response.chars().take(200).collect::<String>(),
+                "iteration": iteration,
+            }));
+
+            // Build follow-up prompt for the calling agent
+            let target_name = target.map(|a| a.name.as_str()).unwrap_or("Unknown");
+            current_input = format!(
+                "## A2A Call Result\n\nAgent **{}** responded:\n\n{}\n\n---\n\nPlease continue your work with this result.",
+                target_name, a2a_response
+            );
+            total_result = Some(result);
+        } else {
+            // No A2A call — we're done
+            let mut final_result = result;
+            final_result.text = accumulated_text;
+            return Ok(final_result);
+        }
+    }
+
+    // Max iterations reached — return what we have
+    if let Some(mut r) = total_result {
+        r.text = accumulated_text;
+        Ok(r)
+    } else {
+        Err(AppError::Internal("A2A routing produced no result".into()))
+    }
+}
+
+/// Run an assignment through `execute_with_a2a_routing`, serving it from the
+/// response cache when enabled. Returns the result and whether it was cached.
+#[allow(clippy::too_many_arguments)]
+async fn execute_with_response_cache(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    agent: &AgentConfig,
+    input: &str,
+    task_run_id: &str,
+    assignment_id: &str,
+    cancel_token: Option<&CancellationToken>,
+    workspace_id: Option<&str>,
+    working_dir: Option<&str>,
+    all_agents: &[AgentConfig],
+) -> AppResult<(AgentPromptResult, bool)> {
+    // The cache does not tell outputs of overridden settings or attached files apart
+    if assignment_overrides::current(state, task_run_id, &agent.id).await.is_some()
+        || prompt_content::current(state, task_run_id, &agent.id).await.is_some()
+    {
+        let result = execute_with_a2a_routing(
+            app, state, agent, input, task_run_id, cancel_token, workspace_id, working_dir, all_agents,
+        )
+        .await?;
+        return Ok((result, false));
+    }
+    let cached = {
+        let state_clone = state.clone();
+        let agent_clone = agent.clone();
+        let input_clone = input.to_string();
+        let wd = working_dir.map(|s| s.to_string());
+        telemetry::spawn_blocking(move || {
+            response_cache::lookup(&state_clone, &agent_clone, &input_clone, wd.as_deref())
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+    };
+    if let Some(text) = cached {
+        log::info!("[ResponseCache] Serving cached response for agent {} in task {}", agent.id, task_run_id);
+        let state_clone = state.clone();
+        let aid = assignment_id.to_string();
+        telemetry::spawn_blocking(move || task_run_repo::mark_assignment_cached(&state_clone, &aid))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+        let result = AgentPromptResult {
+            text,
+            tokens_in: 0,
+            tokens_out: 0,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            acp_session_id: String::new(),
+            capped: false,
+        };
+        return Ok((result, true));
+    }
+
+    let result = execute_with_a2a_routing(
+        app, state, agent, input, task_run_id, cancel_token, workspace_id, working_dir, all_agents,
+    )
+    .await?;
+    // A capped output is cut short; the next run should not reuse it
+    if result.capped {
+        return Ok((result, false));
+    }
+
+    let state_clone = state.clone();
+    let agent_clone = agent.clone();
+    let input_clone = input.to_string();
+    let wd = working_dir.map(|s| s.to_string());
+    let output = result.text.clone();
+    let aid = assignment_id.to_string();
+    let _ = telemetry::spawn_blocking(move || {
+        response_cache::store(&state_clone, &agent_clone, &input_clone, wd.as_deref(), &output, &aid)
+    })
+    .await;
+
+    Ok((result, false))
+}
+
+/// Build a "Peer Agents" section for A2A discovery.
+/// Lists all enabled sibling agents in the workspace (excluding the current agent)
+/// so the executing agent can discover and delegate to them at runtime.
+fn build_peer_agent_section(all_agents: &[AgentConfig], current_agent_id: &str) -> String {
+    let peers: Vec<&AgentConfig> = all_agents
+        .iter()
+        .filter(|a| a.id != current_agent_id && a.is_enabled)
+        .collect();
+
+    if peers.is_empty() {
+        return String::new();
+    }
+
+    let mut section = String::from("\n\n---\n## Available Peer Agents\n");
+    section.push_str("You can delegate subtasks to these agents. To call a peer agent, ");
+    section.push_str("output an A2A call block at the end of your response:\n\n");
+    section.push_str("```\n<a2a_call agent_id=\"AGENT_UUID\">\nDetailed task description for the agent\n</a2a_call>\n```\n\n");
+    section.push_str("The orchestrator will execute the target agent and return the result in a follow-up prompt.\n\n");
+
+    for peer in &peers {
+        let caps = if peer.capabilities_json != "[]" {
+            &peer.capabilities_json
+        } else {
+            ""
+        };
+        section.push_str(&format!(
+            "- **{}** (`{}`): {} {}\n",
+            peer.name, peer.id, peer.description, caps
+        ));
+    }
+    section
+}
+
+/// Upper bound on corrective actions the control hub may apply during one run.
+const MAX_FEEDBACK_CORRECTIONS: usize = 3;
+
+fn build_feedback_prompt(outputs: &HashMap<String, String>, agents: &[AgentConfig], corrections_left: usize) -> String {
+    let mut parts = vec!["Here are the results from the agents so far:\n".to_string()];
+    for (id, output) in outputs {
+        let name = agents
+            .iter()
+            .find(|a| a.id == *id)
+            .map(|a| a.name.as_str())
+            .unwrap_or("Unknown");
+        parts.push(format!("--- {} ({}) ---\n{}\n", name, id, output));
+    }
+    if corrections_left == 0 {
+        parts.push("Are these results satisfactory? Reply with a brief assessment.".into());
+    } else {
+        parts.push(format!(
+            r#"Are these results satisfactory? Respond with ONLY a JSON object:
+
+{{"assessment": "Brief assessment", "actions": []}}
+
+If a result must be fixed, add up to {corrections_left} corrective actions:
+- {{"action": "rerun_agent", "agent_id": "...", "instructions": "What to change"}}
+- {{"action": "add_assignment", "agent_id": "...", "task_description": "...", "depends_on": [], "working_directory": null}}
+
+Leave "actions" empty when the results are good enough. agent_id must be one of the ids above or from the agent catalog."#
+        ));
+    }
+    parts.join("\n")
+}
+
+/// A correction the control hub requests after reviewing a sequence group
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum FeedbackAction {
+    /// Re-run an agent with extra instructions; its output is replaced
+    RerunAgent { agent_id: String, instructions: String },
+    /// Run an additional assignment; its output is appended to the agent's output
+    AddAssignment {
+        agent_id: String,
+        task_description: String,
+        #[serde(default)]
+        depends_on: Vec<String>,
+        #[serde(default)]
+        working_directory: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+struct HubFeedback {
+    #[serde(default)]
+    assessment: String,
+    #[serde(default)]
+    actions: Vec<FeedbackAction>,
+}
+
+/// Parse the hub's feedback response. A response that is not valid JSON is
+/// treated as a plain assessment with no actions.
+fn parse_hub_feedback(response: &str) -> HubFeedback {
+    let sanitized = sanitize_llm_json(&extract_json_from_response(response));
+    serde_json::from_str::<HubFeedback>(&sanitized).unwrap_or_else(|_| HubFeedback {
+        assessment: response.trim().to_string(),
+        actions: Vec::new(),
+    })
+}
+
+/// The run a control hub review belongs to.
+struct FeedbackReview<'a> {
+    app: &'a tauri::AppHandle,
+    state: &'a AppState,
+    task_run_id: &'a str,
+    workspace_id: Option<&'a str>,
+    hub_agent: &'a AgentConfig,
+    hub_process_key: &'a str,
+    all_agents: &'a [AgentConfig],
+}
+
+/// Send the results of a sequence group to the control hub and apply the
+/// corrective actions it asks for, up to MAX_FEEDBACK_CORRECTIONS per run.
+/// Returns the tokens the corrections used.
+async fn review_with_hub_feedback(
+    review: &FeedbackReview<'_>,
+    sequence_order: i64,
+    agent_outputs: &mut HashMap<String, String>,
+    corrections_used: &mut usize,
+) -> TokenUsage {
+    let FeedbackReview { app, state, task_run_id, workspace_id, hub_agent, hub_process_key, all_agents } = *review;
+    let mut usage = TokenUsage::default();
+    let corrections_left = MAX_FEEDBACK_CORRECTIONS.saturating_sub(*corrections_used);
+    let feedback = format!(
+        "{}{}",
+        hub_persona(state, workspace_id).await,
+        build_feedback_prompt(agent_outputs, all_agents, corrections_left)
+    );
+    events::emit(app, &events::FEEDBACK, serde_json::json!({
+        "taskRunId": task_run_id,
+        "message": "Control Hub reviewing results...",
+    }));
+
+    let response = match send_prompt_to_agent(app, state, &hub_agent.id, &feedback, Some(task_run_id), None, workspace_id, None, hub_process_key).await {
+        Ok(response) => response,
+        Err(e) => {
+            log::warn!("Control Hub feedback failed for {}: {}", task_run_id, e);
+            return usage;
+        }
+    };
+    let hub_feedback = parse_hub_feedback(&response.text);
+    log::info!("Control Hub feedback: {}", hub_feedback.assessment);
+    events::emit(app, &events::FEEDBACK, serde_json::json!({
+        "taskRunId": task_run_id,
+        "message": hub_feedback.assessment,
+        "actionCount": hub_feedback.actions.len(),
+    }));
+
+    for action in hub_feedback.actions.into_iter().take(corrections_left) {
+        if is_cancelled(state, task_run_id).await {
+            return usage;
+        }
+
+        let (agent_id, input, working_directory, replace_output) = match &action {
+            FeedbackAction::RerunAgent { agent_id, instructions } => {
+                let previous = agent_outputs.get(agent_id).cloned().unwrap_or_default();
+                let input = format!(
+                    "{}\n\n--- Your previous output ---\n{}",
+                    instructions, previous
+                );
+                (agent_id.clone(), input, None, true)
+            }
+            FeedbackAction::AddAssignment { agent_id, task_description, depends_on, working_directory } => {
+                let mut parts = vec![task_description.clone()];
+                for dep_id in depends_on {
+                    if let Some(output) = agent_outputs.get(dep_id) {
+                        let dep_name = all_agents.iter()
+                            .find(|a| a.id == *dep_id)
+                            .map(|a| a.name.clone())
+                            .unwrap_or_else(|| "Previous agent".into());
+                        parts.push(format!("\n--- Output from {dep_name} ---\n{output}"));
+                    }
+                }
+                (agent_id.clone(), parts.join("\n"), working_directory.clone(), false)
+            }
+        };
+
+        let Some(agent) = all_agents.iter().find(|a| a.id == agent_id && a.is_enabled && !a.is_control_hub) else {
+            log::warn!("Ignoring feedback action for unknown or disabled agent {}", agent_id);
+            continue;
+        };
+        *corrections_used += 1;
+        {
+            let state_clone = state.clone();
+            let trid = task_run_id.to_string();
+            let recorded = telemetry::spawn_blocking(move || task_run_repo::record_feedback_correction(&state_clone, &trid))
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))
+                .and_then(|r| r);
+            if let Err(e) = recorded {
+                log::warn!("Failed to record feedback correction for {}: {}", task_run_id, e);
+            }
+        }
+
+        events::emit(app, &events::FEEDBACK_ACTION, serde_json::json!({
+            "taskRunId": task_run_id,
+            "agentId": agent.id,
+            "agentName": agent.name,
+            "action": if replace_output { "rerun_agent" } else { "add_assignment" },
+            "correctionsUsed": *corrections_used,
+            "maxCorrections": MAX_FEEDBACK_CORRECTIONS,
+        }));
+
+        let working_dir = resolve_assignment_working_directory(state, workspace_id, working_directory.as_deref());
+        match run_feedback_assignment(app, state, task_run_id, workspace_id, agent, &input, sequence_order, working_dir.as_deref()).await {
+            Ok(prompt_result) => {
+                usage.tokens_in += prompt_result.tokens_in;
+                usage.tokens_out += prompt_result.tokens_out;
+                usage.cache_creation_tokens += prompt_result.cache_creation_tokens;
+                usage.cache_read_tokens += prompt_result.cache_read_tokens;
+                let output = match agent_outputs.get(&agent.id) {
+                    Some(previous) if !replace_output => format!("{}\n\n{}", previous, prompt_result.text),
+                    _ => prompt_result.text,
+                };
+                agent_outputs.insert(agent.id.clone(), output);
+            }
+            Err(err_msg) => {
+                if replace_output || !agent_outputs.contains_key(&agent.id) {
+                    agent_outputs.insert(agent.id.clone(), format!("(Agent failed: {})", err_msg));
+                }
+            }
+        }
+    }
+    usage
+}
+
+/// Run one assignment requested by hub feedback: record it, execute it with
+/// self-healing and emit the usual agent events.
+#[allow(clippy::too_many_arguments)]
+async fn run_feedback_assignment(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run_id: &str,
+    workspace_id: Option<&str>,
+    agent: &AgentConfig,
+    input: &str,
+    sequence_order: i64,
+    working_dir: Option<&str>,
+) -> Result<AgentPromptResult, String> {
+    let assignment_id = uuid::Uuid::new_v4().to_string();
+    {
+        let state_clone = state.clone();
+        let aid = assignment_id.clone();
+        let trid = task_run_id.to_string();
+        let agid = agent.id.clone();
+        let aname = agent.name.clone();
+        let inp = input.to_string();
+        telemetry::spawn_blocking(move || {
+            task_run_repo::create_task_assignment(&state_clone, &aid, &trid, &agid, &aname, sequence_order, &inp)?;
+            task_run_repo::update_task_assignment(&state_clone, &aid, "running", None, None, 0, 0, 0, 0, 0, None)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    }
+
+    events::emit(app, &events::AGENT_STARTED, serde_json::json!({
+        "taskRunId": task_run_id,
+        "assignmentId": assignment_id,
+        "agentId": agent.id,
+        "agentName": agent.name,
+        "model": agent.model,
+        "sequenceOrder": sequence_order,
+        "isCorrection": true,
+    }));
+
+    let agent_cancel_token = {
+        let task_tokens = state.active_task_runs.lock().await;
+        task_tokens.get(task_run_id).map(|t| t.child_token())
+    };
+    running_assignments::begin(state, task_run_id, &agent.id, &assignment_id).await;
+    let assign_start = std::time::Instant::now();
+    let result = execute_agent_assignment_with_self_healing(
+        app, state, agent, input, task_run_id, agent_cancel_token.as_ref(), workspace_id, working_dir,
+    ).await;
+    running_assignments::end(state, task_run_id, &agent.id).await;
+    let duration_ms = assign_start.elapsed().as_millis() as i64;
+
+    match result {
+        Ok(mut prompt_result) => {
+            prompt_result.text = inline_artifacts::collect(
+                app, state, task_run_id, &assignment_id, &agent.id,
+                working_dir, std::time::SystemTime::now() - assign_start.elapsed(), &prompt_result.text,
+            ).await;
+            let state_clone = state.clone();
+            let aid = assignment_id.clone();
+            let out = prompt_result.text.clone();
+            let model = agent.model.clone();
+            let (ti, to, cct, crt) = (
+                prompt_result.tokens_in,
+                prompt_result.tokens_out,
+                prompt_result.cache_creation_tokens,
+                prompt_result.cache_read_tokens,
+            );
+            let _ = telemetry::spawn_blocking(move || {
+                task_run_repo::update_task_assignment(
+                    &state_clone, &aid, "completed", Some(&out), Some(&model),
+                    ti, to, cct, crt, duration_ms, None,
+                )
+            }).await;
+
+            events::emit(app, &events::AGENT_COMPLETED, serde_json::json!({
+                "taskRunId": task_run_id,
+                "assignmentId": assignment_id,
+                "agentId": agent.id,
+                "agentName": agent.name,
+                "durationMs": duration_ms,
+                "status": "completed",
+                "tokensIn": prompt_result.tokens_in,
+                "tokensOut": prompt_result.tokens_out,
+                "cacheCreationTokens": prompt_result.cache_creation_tokens,
+                "cacheReadTokens": prompt_result.cache_read_tokens,
+                "acpSessionId": prompt_result.acp_session_id,
+                "output": prompt_result.text.clone(),
+            }));
+            Ok(prompt_result)
+        }
+        Err(e) => {
+            let err_msg = e.to_string();
+            let status = if matches!(e, AppError::AgentCancelled { .. }) { "cancelled" } else { "failed" };
+
+            let state_clone = state.clone();
+            let aid = assignment_id.clone();
+            let em = err_msg.clone();
+            let _ = telemetry::spawn_blocking(move || {
+                task_run_repo::update_task_assignment(
+                    &state_clone, &aid, status, None, None, 0, 0, 0, 0, duration_ms, Some(&em),
+                )
+            }).await;
+
+            events::emit(app, &events::AGENT_COMPLETED, serde_json::json!({
+                "taskRunId": task_run_id,
+                "assignmentId": assignment_id,
+                "agentId": agent.id,
+                "agentName": agent.name,
+                "durationMs": duration_ms,
+                "status": status,
+                "error": &err_msg,
+                "errorCode": e.code(),
+            }));
+            log::warn!("Feedback assignment failed for {}: {}", agent.name, err_msg);
+            Err(err_msg)
+        }
+    }
+}
+
+/// Most remainder plans the control hub may produce in one run.
+const MAX_REPLANS: usize = 2;
+/// Characters of each finished output shown to the hub when it re-plans.
+const REPLAN_OUTPUT_PREVIEW_CHARS: usize = 1_500;
+
+/// The lowest sequence order of the plan after `after`, if any is left.
+fn next_sequence_order(plan: &TaskPlan, after: Option<i64>) -> Option<i64> {
+    plan.assignments
+        .iter()
+        .map(|a| a.sequence_order)
+        .filter(|order| after.map_or(true, |after| *order > after))
+        .min()
+}
+
+/// The control hub's revision of the assignments that have not started yet
+#[derive(Debug, Clone, serde::Deserialize)]
+struct RemainderPlan {
+    #[serde(default)]
+    analysis: String,
+    assignments: Vec<PlannedAssignment>,
+}
+
+fn build_replan_prompt(
+    plan: &TaskPlan,
+    after_order: i64,
+    failures: &[(String, String)],
+    outputs: &HashMap<String, String>,
+    agents: &[&AgentConfig],
+) -> String {
+    let name_of = |id: &str| agents.iter().find(|a| a.id == id).map(|a| a.name.clone()).unwrap_or_else(|| "Unknown".into());
+    let mut parts = vec![format!("These assignments of sequence group {after_order} failed:\n")];
+    for (agent_id, error) in failures {
+        let task = plan.assignments
+            .iter()
+            .find(|a| a.agent_id == *agent_id && a.sequence_order == after_order)
+            .map(|a| a.task_description.as_str())
+            .unwrap_or("");
+        parts.push(format!("--- {} ({}) ---\nTask: {}\nError: {}\n", name_of(agent_id), agent_id, task, error));
+    }
+
+    let finished: Vec<_> = outputs.iter().filter(|(id, _)| !failures.iter().any(|(f, _)| f == *id)).collect();
+    if !finished.is_empty() {
+        parts.push("Results of the agents that succeeded so far:\n".into());
+        for (agent_id, output) in finished {
+            let preview: String = output.chars().take(REPLAN_OUTPUT_PREVIEW_CHARS).collect();
+            let elided = if preview.len() < output.len() { "\n[...]" } else { "" };
+            parts.push(format!("--- {} ({}) ---\n{}{}\n", name_of(agent_id), agent_id, preview, elided));
+        }
+    }
+
+    let remaining: Vec<&PlannedAssignment> = plan.assignments.iter().filter(|a| a.sequence_order > after_order).collect();
+    parts.push(format!(
+        "Assignments that have not started yet:\n{}\n",
+        serde_json::to_string_pretty(&remaining).unwrap_or_else(|_| "[]".into())
+    ));
+    parts.push(format!("Agents still available:\n{}", build_agent_catalog_refs(agents, None)));
+    parts.push(format!(
+        r#"Revise the rest of the plan so the failed work still gets done: re-assign a failed task to another agent, split it into smaller tasks, or drop it when the remaining work does not need it. Adjust the assignments that have not started yet, since they would otherwise receive the failure messages as input.
+
+Respond with ONLY a JSON object that replaces all assignments that have not started yet:
+
+{{"analysis": "Brief reasoning", "assignments": [{{"agent_id": "uuid-from-catalog", "task_description": "...", "sequence_order": {next}, "depends_on": [], "matched_skills": [], "selection_reason": "...", "working_directory": null}}]}}
+
+sequence_order must be {next} or greater. depends_on may name agents whose results are shown above."#,
+        next = after_order + 1
+    ));
+    parts.join("\n")
+}
+
+/// Replace the assignments after `after_order` with a revised remainder.
+/// Assignments to agents `usable` rejects are dropped, and the remainder is
+/// shifted to start after `after_order` if the hub numbered it too low.
+fn merge_remainder_plan(
+    plan: &TaskPlan,
+    after_order: i64,
+    remainder: Vec<PlannedAssignment>,
+    usable: impl Fn(&str) -> bool,
+) -> TaskPlan {
+    let mut remainder: Vec<PlannedAssignment> = remainder
+        .into_iter()
+        .filter(|a| {
+            let keep = usable(&a.agent_id);
+            if !keep {
+                log::warn!("Dropping revised assignment to unknown or unavailable agent '{}'", a.agent_id);
+            }
+            keep
+        })
+        .collect();
+    if let Some(lowest) = remainder.iter().map(|a| a.sequence_order).min().filter(|lowest| *lowest <= after_order) {
+        let shift = after_order + 1 - lowest;
+        for assignment in &mut remainder {
+            assignment.sequence_order += shift;
+        }
+    }
+    let mut assignments: Vec<PlannedAssignment> = plan.assignments
+        .iter()
+        .filter(|a| a.sequence_order <= after_order)
+        .cloned()
+        .collect();
+    assignments.extend(remainder);
+    TaskPlan { analysis: plan.analysis.clone(), assignments, isolated: plan.isolated }
+}
+
+/// Send the failures of a sequence group to the control hub and merge the
+/// remainder plan it answers with. The plan is kept as it is when the hub
+/// fails or its answer does not parse.
+#[allow(clippy::too_many_arguments)]
+async fn replan_after_failures(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run_id: &str,
+    workspace_id: Option<&str>,
+    hub_agent: &AgentConfig,
+    hub_process_key: &str,
+    all_agents: &[AgentConfig],
+    plan: &mut TaskPlan,
+    after_order: i64,
+    failures: &[(String, String)],
+    agent_outputs: &HashMap<String, String>,
+) {
+    // Failed agents were disabled when they failed
+    let available: Vec<&AgentConfig> = all_agents
+        .iter()
+        .filter(|a| a.is_enabled && !a.is_control_hub && !failures.iter().any(|(f, _)| *f == a.id))
+        .collect();
+    let prompt = format!(
+        "{}{}",
+        hub_persona(state, workspace_id).await,
+        build_replan_prompt(plan, after_order, failures, agent_outputs, &available)
+    );
+    events::emit(app, &events::FEEDBACK, serde_json::json!({
+        "taskRunId": task_run_id,
+        "message": "Control Hub revising the plan after a failure...",
+    }));
+
+    let response = match send_prompt_to_agent(app, state, &hub_agent.id, &prompt, Some(task_run_id), None, workspace_id, None, hub_process_key).await {
+        Ok(response) => response,
+        Err(e) => {
+            log::warn!("Control Hub re-planning failed for {}: {}", task_run_id, e);
+            return;
+        }
+    };
+    let sanitized = sanitize_llm_json(&extract_json_from_response(&response.text));
+    let remainder = match serde_json::from_str::<RemainderPlan>(&sanitized) {
+        Ok(remainder) => remainder,
+        Err(e) => {
+            log::warn!("Ignoring unparseable remainder plan for {}: {}", task_run_id, e);
+            return;
+        }
+    };
+
+    let mut revised = merge_remainder_plan(plan, after_order, remainder.assignments, |id| available.iter().any(|a| a.id == id));
+    normalize_plan_working_directories(state, workspace_id, &mut revised);
+    log::info!(
+        "Control Hub revised the plan of {} after sequence group {}: {}",
+        task_run_id, after_order, remainder.analysis
+    );
+    *plan = revised;
+
+    match serde_json::to_string(&*plan) {
+        Ok(plan_json) => {
+            let state_clone = state.clone();
+            let id = task_run_id.to_string();
+            let stored = telemetry::spawn_blocking(move || task_run_repo::update_task_run_plan(&state_clone, &id, &plan_json)).await;
+            if let Ok(Err(e)) = stored {
+                log::warn!("Failed to store the revised plan of {}: {}", task_run_id, e);
+            }
+        }
+        Err(e) => log::warn!("Failed to serialize the revised plan of {}: {}", task_run_id, e),
+    }
+    events::emit(app, &events::PLAN_REVISED, serde_json::json!({
+        "taskRunId": task_run_id,
+        "plan": &*plan,
+        "analysis": remainder.analysis,
+        "afterSequenceOrder": after_order,
+    }));
+}
 
-            // Emit A2A result event
-            let _ = app.emit("orchestration:a2a_result", &serde_json::json!({
-                "taskRunId": task_run_id,
-                "callerAgentId": agent.id,
-                "targetAgentId": a2a_call.target_agent_id,
-                "resultPreview": a2a_response.chars().take(200).collect::<String>(),
-                "iteration": iteration,
-            }));
+/// Input of a retried assignment. A planned assignment is rebuilt from its
+/// task description and the latest finished outputs of the agents it depends
+/// on, so retries of those are picked up; any other assignment, such as one
+/// added by hub feedback, gets its recorded input again.
+async fn retry_input(
+    state: &AppState,
+    original: &TaskAssignment,
+    plan: Option<&TaskPlan>,
+    assignments: &[TaskAssignment],
+    all_agents: &[AgentConfig],
+    workspace_id: Option<&str>,
+) -> String {
+    let planned = plan.and_then(|plan| {
+        plan.assignments
+            .iter()
+            .find(|p| p.agent_id == original.agent_id && p.sequence_order == original.sequence_order)
+    });
+    let Some(planned) = planned else {
+        return original.input_text.clone();
+    };
 
-            // Build follow-up prompt for the calling agent
-            let target_name = target.map(|a| a.name.as_str()).unwrap_or("Unknown");
-            current_input = format!(
-                "## A2A Call Result\n\nAgent **{}** responded:\n\n{}\n\n---\n\nPlease continue your work with this result.",
-                target_name, a2a_response
-            );
-            total_result = Some(result);
-        } else {
-            // No A2A call — we're done
-            let mut final_result = result;
-            final_result.text = accumulated_text;
-            return Ok(final_result);
+    let mut input_parts = vec![planned.task_description.clone()];
+    for dep_id in &planned.depends_on {
+        let latest = assignments
+            .iter()
+            .filter(|a| a.agent_id == *dep_id && matches!(a.status.as_str(), "completed" | "capped"))
+            .max_by(|a, b| (&a.completed_at, &a.created_at).cmp(&(&b.completed_at, &b.created_at)));
+        if let Some(dep) = latest {
+            let output = dep.output_text.as_deref().unwrap_or_default();
+            input_parts.push(dependency_outputs::input_part(&dep.agent_name, output, dependency_outputs::sections(planned, dep_id)));
         }
     }
 
-    // Max iterations reached — return what we have
-    if let Some(mut r) = total_result {
-        r.text = accumulated_text;
-        Ok(r)
-    } else {
-        Err(AppError::Internal("A2A routing produced no result".into()))
+    let peer_catalog = build_peer_agent_section(all_agents, &planned.agent_id);
+    if !peer_catalog.is_empty() {
+        input_parts.push(peer_catalog);
+    }
+    let knowledge_context = {
+        let state_clone = state.clone();
+        let ws_id = workspace_id.map(|s| s.to_string());
+        let description = planned.task_description.clone();
+        telemetry::spawn_blocking(move || knowledge::assignment_context(&state_clone, ws_id.as_deref(), &description))
+            .await
+            .unwrap_or_default()
+    };
+    if !knowledge_context.is_empty() {
+        input_parts.push(knowledge_context);
     }
+    input_parts.join("\n")
 }
 
-/// Build a "Peer Agents" section for A2A discovery.
-/// Lists all enabled sibling agents in the workspace (excluding the current agent)
-/// so the executing agent can discover and delegate to them at runtime.
-fn build_peer_agent_section(all_agents: &[AgentConfig], current_agent_id: &str) -> String {
-    let peers: Vec<&AgentConfig> = all_agents
-        .iter()
-        .filter(|a| a.id != current_agent_id && a.is_enabled)
-        .collect();
-
-    if peers.is_empty() {
-        return String::new();
+/// Re-execute a historical assignment as a new assignment of the same run,
+/// for when one agent's output turns out bad after the run has ended. The
+/// retry is recorded and returned as running, then executed in the
+/// background; its tokens are added to the run's totals. The run's status
+/// and summary are left as they are.
+pub async fn retry_assignment(app: &tauri::AppHandle, state: &AppState, assignment_id: &str) -> AppResult<TaskAssignment> {
+    let (original, task_run, assignments) = {
+        let state_clone = state.clone();
+        let id = assignment_id.to_string();
+        telemetry::spawn_blocking(move || {
+            let original = task_run_repo::get_assignment(&state_clone, &id)?;
+            let task_run = task_run_repo::get_task_run(&state_clone, &original.task_run_id)?;
+            let assignments = task_run_repo::list_assignments_for_run(&state_clone, &original.task_run_id)?;
+            Ok::<_, AppError>((original, task_run, assignments))
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??
+    };
+    let task_run_id = task_run.id.clone();
+    if state.active_task_runs.lock().await.contains_key(&task_run_id) {
+        return Err(AppError::InvalidRequest(format!(
+            "Task run {} is still in progress; its assignments can be retried once it has ended",
+            task_run_id
+        )));
     }
 
-    let mut section = String::from("\n\n---\n## Available Peer Agents\n");
-    section.push_str("You can delegate subtasks to these agents. To call a peer agent, ");
-    section.push_str("output an A2A call block at the end of your response:\n\n");
-    section.push_str("```\n<a2a_call agent_id=\"AGENT_UUID\">\nDetailed task description for the agent\n</a2a_call>\n```\n\n");
-    section.push_str("The orchestrator will execute the target agent and return the result in a follow-up prompt.\n\n");
+    let workspace_id = task_run.workspace_id.clone();
+    let (agent, all_agents) = {
+        let state_clone = state.clone();
+        let agent_id = original.agent_id.clone();
+        let ws_id = workspace_id.clone();
+        telemetry::spawn_blocking(move || {
+            let agent = agent_repo::get_agent(&state_clone, &agent_id)?;
+            let all_agents = agent_repo::list_agents(&state_clone, ws_id.as_deref())?;
+            Ok::<_, AppError>((agent, all_agents))
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??
+    };
+    let plan: Option<TaskPlan> = task_run.task_plan_json.as_deref().and_then(|json| serde_json::from_str(json).ok());
+    let input = retry_input(state, &original, plan.as_ref(), &assignments, &all_agents, workspace_id.as_deref()).await;
 
-    for peer in &peers {
-        let caps = if peer.capabilities_json != "[]" {
-            &peer.capabilities_json
-        } else {
-            ""
-        };
-        section.push_str(&format!(
-            "- **{}** (`{}`): {} {}\n",
-            peer.name, peer.id, peer.description, caps
-        ));
+    // Claim the run so cancellation, the concurrency limit and the reaper see
+    // the retry; a run that started meanwhile keeps its own token
+    let agent_cancel_token = {
+        let mut tokens = state.active_task_runs.lock().await;
+        if tokens.contains_key(&task_run_id) {
+            return Err(AppError::InvalidRequest(format!("Task run {} is already in progress", task_run_id)));
+        }
+        let token = CancellationToken::new();
+        let agent_token = token.child_token();
+        tokens.insert(task_run_id.clone(), token);
+        agent_token
+    };
+    {
+        let mut agent_cancels = state.agent_cancellations.lock().await;
+        agent_cancels.insert((task_run_id.clone(), agent.id.clone()), agent_cancel_token.clone());
     }
-    section
+
+    let retry_id = uuid::Uuid::new_v4().to_string();
+    let caps = assignment_caps::AssignmentCaps { max_tokens_out: original.max_tokens_out, max_cost: original.max_cost };
+    let created = {
+        let state_clone = state.clone();
+        let original = original.clone();
+        let retry_id = retry_id.clone();
+        let inp = input.clone();
+        telemetry::spawn_blocking(move || {
+            task_run_repo::create_task_assignment(
+                &state_clone, &retry_id, &original.task_run_id, &original.agent_id, &original.agent_name,
+                original.sequence_order, &inp,
+            )?;
+            task_run_repo::set_assignment_retry_of(&state_clone, &retry_id, &original.id)?;
+            if let Some(subdir) = &original.working_directory {
+                task_run_repo::set_assignment_working_directory(&state_clone, &retry_id, subdir)?;
+            }
+            if !caps.is_empty() {
+                task_run_repo::set_assignment_caps(&state_clone, &retry_id, caps.max_tokens_out, caps.max_cost)?;
+            }
+            if !original.overrides.is_empty() {
+                task_run_repo::set_assignment_overrides(&state_clone, &retry_id, &original.overrides)?;
+            }
+            task_run_repo::update_task_assignment(&state_clone, &retry_id, "running", None, None, 0, 0, 0, 0, 0, None)?;
+            task_run_repo::get_assignment(&state_clone, &retry_id)
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+        .and_then(|r| r)
+    };
+    let created = match created {
+        Ok(created) => created,
+        Err(e) => {
+            finish_retry(app, state, &task_run_id).await;
+            return Err(e);
+        }
+    };
+    log::info!("Retrying assignment {} of task run {} as {}", original.id, task_run_id, retry_id);
+    let model_used = original.overrides.model.clone().unwrap_or_else(|| agent.model.clone());
+
+    events::emit(app, &events::AGENT_STARTED, serde_json::json!({
+        "taskRunId": task_run_id,
+        "assignmentId": retry_id,
+        "agentId": agent.id,
+        "agentName": agent.name,
+        "model": model_used,
+        "sequenceOrder": original.sequence_order,
+        "retryOfAssignmentId": original.id,
+    }));
+
+    let (app, state) = (app.clone(), state.clone());
+    let working_dir = resolve_assignment_working_directory(&state, workspace_id.as_deref(), original.working_directory.as_deref());
+    tokio::spawn(async move {
+        assignment_caps::begin(&state, &task_run_id, &agent.id, caps).await;
+        assignment_overrides::begin(&state, &task_run_id, &agent.id, original.overrides.clone()).await;
+        running_assignments::begin(&state, &task_run_id, &agent.id, &retry_id).await;
+        let assign_start = std::time::Instant::now();
+        let result = execute_agent_assignment_with_self_healing(
+            &app, &state, &agent, &input, &task_run_id, Some(&agent_cancel_token), workspace_id.as_deref(), working_dir.as_deref(),
+        ).await;
+        assignment_caps::end(&state, &task_run_id, &agent.id).await;
+        assignment_overrides::end(&state, &task_run_id, &agent.id).await;
+        running_assignments::end(&state, &task_run_id, &agent.id).await;
+        let duration_ms = assign_start.elapsed().as_millis() as i64;
+
+        match result {
+            Ok(mut prompt_result) => {
+                let status = if prompt_result.capped { assignment_caps::CAPPED_STATUS } else { "completed" };
+                prompt_result.text = inline_artifacts::collect(
+                    &app, &state, &task_run_id, &retry_id, &agent.id,
+                    working_dir.as_deref(), std::time::SystemTime::now() - assign_start.elapsed(), &prompt_result.text,
+                ).await;
+                let state_clone = state.clone();
+                let (aid, trid) = (retry_id.clone(), task_run_id.clone());
+                let out = prompt_result.text.clone();
+                let model = model_used.clone();
+                let (ti, to, cct, crt) = (
+                    prompt_result.tokens_in,
+                    prompt_result.tokens_out,
+                    prompt_result.cache_creation_tokens,
+                    prompt_result.cache_read_tokens,
+                );
+                let recorded = telemetry::spawn_blocking(move || {
+                    task_run_repo::update_task_assignment(
+                        &state_clone, &aid, status, Some(&out), Some(&model), ti, to, cct, crt, duration_ms, None,
+                    )?;
+                    task_run_repo::add_task_run_tokens(&state_clone, &trid, ti, to, cct, crt)
+                }).await;
+                if let Ok(Err(e)) = recorded {
+                    log::warn!("Failed to record retried assignment {}: {}", retry_id, e);
+                }
+
+                events::emit(&app, &events::AGENT_COMPLETED, serde_json::json!({
+                    "taskRunId": task_run_id,
+                    "assignmentId": retry_id,
+                    "agentId": agent.id,
+                    "agentName": agent.name,
+                    "durationMs": duration_ms,
+                    "status": status,
+                    "tokensIn": prompt_result.tokens_in,
+                    "tokensOut": prompt_result.tokens_out,
+                    "cacheCreationTokens": prompt_result.cache_creation_tokens,
+                    "cacheReadTokens": prompt_result.cache_read_tokens,
+                    "acpSessionId": prompt_result.acp_session_id,
+                    "output": prompt_result.text,
+                }));
+            }
+            Err(e) => {
+                let err_msg = e.to_string();
+                let status = if matches!(e, AppError::AgentCancelled { .. }) { "cancelled" } else { "failed" };
+                let state_clone = state.clone();
+                let aid = retry_id.clone();
+                let em = err_msg.clone();
+                let _ = telemetry::spawn_blocking(move || {
+                    task_run_repo::update_task_assignment(
+                        &state_clone, &aid, status, None, None, 0, 0, 0, 0, duration_ms, Some(&em),
+                    )
+                }).await;
+
+                events::emit(&app, &events::AGENT_COMPLETED, serde_json::json!({
+                    "taskRunId": task_run_id,
+                    "assignmentId": retry_id,
+                    "agentId": agent.id,
+                    "agentName": agent.name,
+                    "durationMs": duration_ms,
+                    "status": status,
+                    "error": &err_msg,
+                    "errorCode": e.code(),
+                }));
+                log::warn!("Retried assignment {} failed for {}: {}", retry_id, agent.name, err_msg);
+            }
+        }
+        finish_retry(&app, &state, &task_run_id).await;
+    });
+
+    Ok(created)
 }
 
-fn build_feedback_prompt(outputs: &HashMap<String, String>, agents: &[AgentConfig]) -> String {
-    let mut parts = vec!["Here are the results from the agents so far:\n".to_string()];
-    for (id, output) in outputs {
-        let name = agents
-            .iter()
-            .find(|a| a.id == *id)
-            .map(|a| a.name.as_str())
-            .unwrap_or("Unknown");
-        parts.push(format!("--- {} ---\n{}\n", name, output));
-    }
-    parts.push("Are these results satisfactory? Reply with a brief assessment.".into());
-    parts.join("\n")
+/// Release a run claimed for an assignment retry and stop its agent.
+async fn finish_retry(app: &tauri::AppHandle, state: &AppState, task_run_id: &str) {
+    cleanup_task_processes(state, task_run_id).await;
+    state.active_task_runs.lock().await.remove(task_run_id);
+    state.agent_cancellations.lock().await.retain(|(trid, _), _| trid != task_run_id);
+    run_queue::dispatch(app, state);
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -1795,6 +3180,54 @@ fn orch_process_key(task_run_id: &str, agent_id: &str) -> String {
     format!("orch:{}:{}", task_run_id, agent_id)
 }
 
+/// Task run of an orchestration process key, the inverse of `orch_process_key`.
+pub(crate) fn orch_task_run_id(process_key: &str) -> Option<&str> {
+    process_key.strip_prefix("orch:")?.split(':').next()
+}
+
+/// Longest an agent's warm-up prompt may take before the run goes on without it.
+const WARMUP_TIMEOUT_SECS: u64 = 300;
+
+/// Run the agent's warm-up prompt on a freshly spawned process and keep its
+/// output for the first assignment. Failures only cost the warm-up.
+async fn run_warmup(app: &tauri::AppHandle, state: &AppState, agent: &AgentConfig, process_key: &str) {
+    let Some(prompt) = agent.warmup_prompt.as_deref().map(str::trim).filter(|p| !p.is_empty()) else {
+        return;
+    };
+    log::info!("Warming up agent {} (key={})", agent.id, process_key);
+    let warmup = send_prompt_and_collect(
+        app, state, &agent.id, prompt, None, None, agent.workspace_id.as_deref(), None, process_key,
+    );
+    match tokio::time::timeout(std::time::Duration::from_secs(WARMUP_TIMEOUT_SECS), Box::pin(warmup)).await {
+        Ok(Ok(result)) if !result.text.trim().is_empty() => {
+            let mut warmups = state.agent_warmups.lock().await;
+            warmups.insert(process_key.to_string(), result.text.trim().to_string());
+        }
+        Ok(Ok(_)) => log::info!("Warm-up of agent {} produced no output", agent.id),
+        Ok(Err(e)) => log::warn!("Warm-up of agent {} failed: {}", agent.id, e),
+        Err(_) => {
+            log::warn!("Warm-up of agent {} timed out after {}s", agent.id, WARMUP_TIMEOUT_SECS);
+            // Stop the turn so its late output does not leak into the first assignment
+            cancel_agent_turn(state, process_key).await;
+        }
+    }
+}
+
+/// Cancel the prompt an orchestration process is working on, if any.
+async fn cancel_agent_turn(state: &AppState, process_key: &str) {
+    let session_key = format!("orch_session:{}", process_key);
+    let acp_session_id = {
+        let sessions = state.acp_sessions.lock().await;
+        sessions.get(&session_key).map(|s| s.acp_session_id.clone())
+    };
+    if let Some(acp_session_id) = acp_session_id {
+        let mut processes = state.agent_processes.lock().await;
+        if let Some(process) = processes.get_mut(process_key) {
+            let _ = client::cancel_prompt(process, &acp_session_id).await;
+        }
+    }
+}
+
 async fn ensure_agent_running(
     app: &tauri::AppHandle,
     state: &AppState,
@@ -1811,7 +3244,7 @@ async fn ensure_agent_running(
     }
 
     let acp_command = agent.acp_command.clone().ok_or_else(|| {
-        AppError::Internal(format!("Agent {} has no ACP command configured", agent.id))
+        AppError::AgentNotConfigured { agent_id: agent.id.clone() }
     })?;
 
     let args: Vec<String> = agent
@@ -1829,9 +3262,13 @@ async fn ensure_agent_running(
     );
 
     // Build extra environment variables
-    let extra_env = discovery::get_agent_env_for_command(&resolved.agent_type).await;
+    let mut extra_env = discovery::get_agent_env_for_command(&resolved.agent_type).await;
+    if let Some(scratch) = orch_task_run_id(process_key).and_then(run_sandbox::active) {
+        extra_env.insert(run_sandbox::SCRATCH_DIR_ENV.to_string(), scratch.to_string_lossy().to_string());
+    }
 
     let process = manager::spawn_agent_process(
+        state,
         &agent.id,
         &resolved.command,
         &resolved.args,
@@ -1853,6 +3290,8 @@ async fn ensure_agent_running(
     {
         use crate::acp::transport;
 
+        // In "review writes" mode, file I/O goes through the client so writes can be reviewed
+        let review_writes = filesystem::is_review_writes_enabled(state);
         let init_req = transport::build_request(
             1,
             "initialize",
@@ -1864,8 +3303,8 @@ async fn ensure_agent_running(
                 },
                 "clientCapabilities": {
                     "fs": {
-                        "readTextFile": false,
-                        "writeTextFile": false
+                        "readTextFile": review_writes,
+                        "writeTextFile": review_writes
                     },
                     "terminal": false
                 }
@@ -1881,13 +3320,13 @@ async fn ensure_agent_running(
         }
 
         // Wait for initialize response using non-blocking try_recv
-        let init_deadline = std::time::Instant::now() + std::time::Duration::from_secs(120);
+        let mut init_deadline = std::time::Instant::now() + std::time::Duration::from_secs(120);
         let init_response = loop {
             let recv_result = {
                 let mut processes = state.agent_processes.lock().await;
                 match processes.get_mut(process_key) {
                     Some(process) => process.message_rx.try_recv(),
-                    None => return Err(AppError::Internal(format!("Agent {} disappeared during init", agent.id))),
+                    None => return Err(AppError::AgentProcessLost { agent_id: agent.id.clone(), process_key: process_key.to_string() }),
                 }
             };
             match recv_result {
@@ -1901,7 +3340,12 @@ async fn ensure_agent_running(
                 }
                 Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
                     if std::time::Instant::now() >= init_deadline {
-                        return Err(AppError::Transport(
+                        // A login prompt on the agent's terminal holds up initialization
+                        if agent_terminal::awaiting_input(state, process_key).await {
+                            init_deadline = std::time::Instant::now() + std::time::Duration::from_secs(120);
+                            continue;
+                        }
+                        return Err(AppError::Timeout(
                             "Timeout (120s) waiting for agent initialization".into()
                         ));
                     }
@@ -1920,15 +3364,20 @@ async fn ensure_agent_running(
             let msg = error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error");
             return Err(AppError::Acp(format!("Initialize failed: {}", msg)));
         }
+        if let Some(process) = state.agent_processes.lock().await.get_mut(process_key) {
+            process.prompt_capabilities = prompt_content::PromptCapabilities::from_initialize(&init_response);
+        }
 
         log::info!("Agent {} initialized successfully", agent.id);
     }
 
-    let _ = app.emit("acp:agent_started", &serde_json::json!({
+    events::emit(app, &events::ACP_AGENT_STARTED, serde_json::json!({
         "agent_id": agent.id,
         "status": "Running"
     }));
 
+    run_warmup(app, state, agent, process_key).await;
+
     Ok(())
 }
 
@@ -1952,12 +3401,13 @@ async fn create_session_nonblocking(
     log::info!("create_session_nonblocking: Starting for agent {} (key={})", agent_id, process_key);
 
     // Send session/new request (brief lock)
+    let mcp_servers = web_search::session_mcp_servers(state, agent_id).await;
     let req = transport::build_request(
         2,
         "session/new",
         Some(serde_json::json!({
             "cwd": cwd,
-            "mcpServers": []
+            "mcpServers": mcp_servers
         })),
     );
     {
@@ -1965,85 +3415,321 @@ async fn create_session_nonblocking(
         if let Some(process) = processes.get_mut(process_key) {
             transport::send_message(process, &req).await?;
         } else {
-            return Err(AppError::Internal(format!("Agent {} not found (key={})", agent_id, process_key)));
+            return Err(AppError::AgentProcessLost { agent_id: agent_id.to_string(), process_key: process_key.to_string() });
         }
     }
     // Lock released — other agents can now access their processes
 
-    // Wait for session/new response using non-blocking try_recv
-    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(90);
-    let response = loop {
-        let recv_result = {
-            let mut processes = state.agent_processes.lock().await;
-            match processes.get_mut(process_key) {
-                Some(process) => process.message_rx.try_recv(),
-                None => return Err(AppError::Internal(format!("Agent {} disappeared", agent_id))),
-            }
-        };
+    // Wait for session/new response using non-blocking try_recv
+    let response = wait_for_response_nonblocking(state, process_key, agent_id, 2, "session/new").await?;
+
+    // Parse the response
+    if let Some(error) = response.get("error") {
+        let msg = error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error");
+        return Err(AppError::Acp(format!("session/new failed: {}", msg)));
+    }
+
+    let result = response.get("result").ok_or_else(|| {
+        let resp_str = serde_json::to_string(&response).unwrap_or_default();
+        AppError::Acp(format!("No result in session/new response: {}", resp_str))
+    })?;
+
+    let session_id = result
+        .get("sessionId")
+        .and_then(|s| s.as_str())
+        .ok_or_else(|| {
+            let resp_str = serde_json::to_string(&response).unwrap_or_default();
+            AppError::Acp(format!("No sessionId in session/new response: {}", resp_str))
+        })?
+        .to_string();
+
+    log::info!("create_session_nonblocking: Session created: {}", session_id);
+    Ok(session_id)
+}
+
+/// Wait for the JSON-RPC response with the given id using non-blocking
+/// try_recv, skipping notifications and unrelated responses.
+async fn wait_for_response_nonblocking(
+    state: &AppState,
+    process_key: &str,
+    agent_id: &str,
+    response_id: i64,
+    method: &str,
+) -> AppResult<serde_json::Value> {
+    let mut deadline = std::time::Instant::now() + std::time::Duration::from_secs(90);
+    loop {
+        let recv_result = {
+            let mut processes = state.agent_processes.lock().await;
+            match processes.get_mut(process_key) {
+                Some(process) => process.message_rx.try_recv(),
+                None => return Err(AppError::AgentProcessLost { agent_id: agent_id.to_string(), process_key: process_key.to_string() }),
+            }
+        };
+
+        match recv_result {
+            Ok(msg) => {
+                // Check if this is the response we're waiting for
+                if let Some(msg_id) = msg.get("id") {
+                    if msg_id == &serde_json::json!(response_id) {
+                        return Ok(msg);
+                    }
+                    log::debug!(
+                        "wait_for_response_nonblocking: skipping response with id={}, waiting for id={}",
+                        msg_id, response_id
+                    );
+                } else {
+                    let notification = msg.get("method").and_then(|m| m.as_str()).unwrap_or("unknown");
+                    log::debug!(
+                        "wait_for_response_nonblocking: skipping notification '{}' while waiting for {}",
+                        notification, method
+                    );
+                }
+            }
+            Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
+                if std::time::Instant::now() >= deadline {
+                    if agent_terminal::awaiting_input(state, process_key).await {
+                        deadline = std::time::Instant::now() + std::time::Duration::from_secs(90);
+                        continue;
+                    }
+                    return Err(AppError::Timeout(format!(
+                        "Timeout (90s) waiting for {} response", method
+                    )));
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
+                return Err(AppError::Transport(format!(
+                    "Agent message channel disconnected while waiting for {}", method
+                )));
+            }
+        }
+    }
+}
+
+/// Resume a previous ACP session on a freshly spawned agent via session/load.
+async fn load_session_nonblocking(
+    state: &AppState,
+    process_key: &str,
+    agent_id: &str,
+    acp_session_id: &str,
+    cwd: &str,
+) -> AppResult<()> {
+    use crate::acp::transport;
+
+    let mcp_servers = web_search::session_mcp_servers(state, agent_id).await;
+    let req = transport::build_request(
+        3,
+        "session/load",
+        Some(serde_json::json!({
+            "sessionId": acp_session_id,
+            "cwd": cwd,
+            "mcpServers": mcp_servers
+        })),
+    );
+    {
+        let mut processes = state.agent_processes.lock().await;
+        if let Some(process) = processes.get_mut(process_key) {
+            transport::send_message(process, &req).await?;
+        } else {
+            return Err(AppError::AgentProcessLost { agent_id: agent_id.to_string(), process_key: process_key.to_string() });
+        }
+    }
+
+    let response = wait_for_response_nonblocking(state, process_key, agent_id, 3, "session/load").await?;
+    if let Some(error) = response.get("error") {
+        let msg = error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error");
+        return Err(AppError::Acp(format!("session/load failed: {}", msg)));
+    }
+    Ok(())
+}
+
+/// Open the orchestration session for an agent with context carry-over enabled.
+/// Tries to resume the session stored from a previous run; if that fails, a new
+/// session is created and the stored summary is returned as a prompt preamble.
+async fn open_carried_over_session(
+    state: &AppState,
+    process_key: &str,
+    agent_id: &str,
+    workspace_id: Option<&str>,
+    cwd: &str,
+) -> AppResult<(String, Option<String>)> {
+    let context = {
+        let state_clone = state.clone();
+        let aid = agent_id.to_string();
+        let ws = workspace_id.map(|s| s.to_string());
+        telemetry::spawn_blocking(move || {
+            agent_context_repo::get_agent_context(&state_clone, &aid, ws.as_deref())
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??
+    };
+
+    let Some(context) = context else {
+        let acp_id = create_session_nonblocking(state, process_key, agent_id, cwd).await?;
+        return Ok((acp_id, None));
+    };
 
-        match recv_result {
-            Ok(msg) => {
-                // Check if this is the session/new response (id=2)
-                if let Some(msg_id) = msg.get("id") {
-                    if msg_id == &serde_json::json!(2) {
-                        break msg;
-                    }
-                    log::debug!(
-                        "create_session_nonblocking: skipping response with id={}, waiting for id=2",
-                        msg_id
-                    );
-                } else {
-                    let method = msg.get("method").and_then(|m| m.as_str()).unwrap_or("unknown");
-                    log::debug!(
-                        "create_session_nonblocking: skipping notification '{}' while waiting for session/new",
-                        method
-                    );
-                }
-            }
-            Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
-                if std::time::Instant::now() >= deadline {
-                    return Err(AppError::Transport(
-                        "Timeout (90s) waiting for session/new response".into()
-                    ));
-                }
-                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    if let Some(previous_id) = context.acp_session_id.as_deref() {
+        match load_session_nonblocking(state, process_key, agent_id, previous_id, cwd).await {
+            Ok(()) => {
+                log::info!("Agent {} resumed carried-over session {}", agent_id, previous_id);
+                return Ok((previous_id.to_string(), None));
             }
-            Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
-                return Err(AppError::Transport(
-                    "Agent message channel disconnected during session creation".into()
-                ));
+            Err(e) => {
+                log::info!(
+                    "Agent {} could not resume session {} ({}), falling back to summary",
+                    agent_id, previous_id, e
+                );
             }
         }
+    }
+
+    let acp_id = create_session_nonblocking(state, process_key, agent_id, cwd).await?;
+    let preamble = if context.summary.trim().is_empty() {
+        None
+    } else {
+        Some(format!(
+            "<previous_context>\nYou have worked in this workspace before. Summary of your previous tasks:\n\n{}\n</previous_context>",
+            context.summary.trim()
+        ))
     };
+    Ok((acp_id, preamble))
+}
 
-    // Parse the response
-    if let Some(error) = response.get("error") {
-        let msg = error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error");
-        return Err(AppError::Acp(format!("session/new failed: {}", msg)));
+/// Send a prompt to an agent and collect the complete text response.
+/// Files the agent writes meanwhile count as in flight for conflict
+/// detection until the prompt finishes.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip(app, state, prompt, cancel_token), fields(prompt_chars = prompt.len()))]
+async fn send_prompt_to_agent(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    agent_id: &str,
+    prompt: &str,
+    task_run_id: Option<&str>,
+    cancel_token: Option<&CancellationToken>,
+    workspace_id: Option<&str>,
+    working_dir: Option<&str>,
+    process_key: &str,
+) -> AppResult<AgentPromptResult> {
+    let result = send_prompt_and_collect(
+        app, state, agent_id, prompt, task_run_id, cancel_token, workspace_id, working_dir, process_key,
+    )
+    .await;
+    if let Some(trid) = task_run_id {
+        file_conflicts::release_writer(state, &file_conflicts::FileWriter::new(trid, agent_id)).await;
     }
+    result
+}
 
-    let result = response.get("result").ok_or_else(|| {
-        let resp_str = serde_json::to_string(&response).unwrap_or_default();
-        AppError::Acp(format!("No result in session/new response: {}", resp_str))
-    })?;
+/// The decision remembered for similar permission requests, from the run
+/// or else the agent's policies, with the scope it came from.
+async fn remembered_permission(
+    state: &AppState,
+    task_run_id: &str,
+    agent_id: &str,
+    request_key: &str,
+) -> Option<(bool, &'static str)> {
+    if let Some(allow) = state.orch_permissions.lock().await.remembered(task_run_id, request_key) {
+        return Some((allow, "run"));
+    }
+    let state_clone = state.clone();
+    let (aid, key) = (agent_id.to_string(), request_key.to_string());
+    match telemetry::spawn_blocking(move || permission_policy_repo::get_decision(&state_clone, &aid, &key)).await {
+        Ok(Ok(decision)) => decision.map(|allow| (allow, "agent")),
+        Ok(Err(e)) => {
+            log::warn!("Failed to read the permission policies of agent {}: {}", agent_id, e);
+            None
+        }
+        Err(e) => {
+            log::warn!("Spawn blocking failed: {}", e);
+            None
+        }
+    }
+}
 
-    let session_id = result
-        .get("sessionId")
-        .and_then(|s| s.as_str())
-        .ok_or_else(|| {
-            let resp_str = serde_json::to_string(&response).unwrap_or_default();
-            AppError::Acp(format!("No sessionId in session/new response: {}", resp_str))
-        })?
-        .to_string();
+/// The decision of the permission preset the run was started with, or else
+/// the agent's, with the scope it came from.
+async fn preset_permission(
+    state: &AppState,
+    task_run_id: &str,
+    agent_id: &str,
+    tool_call: Option<&serde_json::Value>,
+) -> Option<(bool, &'static str)> {
+    let state_clone = state.clone();
+    let (trid, aid) = (task_run_id.to_string(), agent_id.to_string());
+    let presets = telemetry::spawn_blocking(move || {
+        let run_preset = task_run_repo::get_task_run(&state_clone, &trid)?.permission_preset;
+        let agent_preset = agent_repo::get_agent(&state_clone, &aid)?.permission_preset;
+        Ok::<_, AppError>((run_preset, agent_preset))
+    })
+    .await;
+    let (run_preset, agent_preset) = match presets {
+        Ok(Ok(presets)) => presets,
+        Ok(Err(e)) => {
+            log::warn!("Failed to read the permission presets of run {}: {}", task_run_id, e);
+            return None;
+        }
+        Err(e) => {
+            log::warn!("Spawn blocking failed: {}", e);
+            return None;
+        }
+    };
+    match (run_preset, agent_preset) {
+        (Some(preset), _) => preset.decision(tool_call).map(|allow| (allow, "run_preset")),
+        (None, Some(preset)) => preset.decision(tool_call).map(|allow| (allow, "agent_preset")),
+        (None, None) => None,
+    }
+}
 
-    log::info!("create_session_nonblocking: Session created: {}", session_id);
-    Ok(session_id)
+/// Switch an orchestration session to `model`, or back to `agent_model` when
+/// an earlier assignment switched it and this one does not. Returns false
+/// when the agent could not switch to `model`.
+async fn select_session_model(
+    state: &AppState,
+    process_key: &str,
+    agent_id: &str,
+    session_key: &str,
+    acp_session_id: &str,
+    model: Option<&str>,
+    agent_model: &str,
+) -> bool {
+    let current = state.acp_sessions.lock().await.get(session_key).and_then(|s| s.model_override.clone());
+    if current.as_deref() == model {
+        return true;
+    }
+    let target = model.unwrap_or(agent_model);
+    let request_id = chrono::Utc::now().timestamp_millis();
+    let sent = {
+        let mut processes = state.agent_processes.lock().await;
+        match processes.get_mut(process_key) {
+            Some(process) => client::set_model(process, acp_session_id, target, request_id).await,
+            None => Err(AppError::AgentProcessLost { agent_id: agent_id.to_string(), process_key: process_key.to_string() }),
+        }
+    };
+    let result = match sent {
+        Ok(()) => wait_for_response_nonblocking(state, process_key, agent_id, request_id, "session/set_model").await,
+        Err(e) => Err(e),
+    };
+    let error = match result {
+        Ok(response) => response.get("error").map(|e| e.to_string()),
+        Err(e) => Some(e.to_string()),
+    };
+    if let Some(error) = error {
+        log::warn!("Agent {} could not switch its session to model {}: {}", agent_id, target, error);
+        return false;
+    }
+    log::info!("Switched session of agent {} to model {}", agent_id, target);
+    if let Some(session) = state.acp_sessions.lock().await.get_mut(session_key) {
+        session.model_override = model.map(|m| m.to_string());
+    }
+    true
 }
 
-/// Send a prompt to an agent and collect the complete text response.
-/// This creates a session if needed and waits for the full result.
+/// Send a prompt and wait for the full result, creating a session if needed.
 /// Also forwards tool_call, thought events and extracts token usage.
-async fn send_prompt_to_agent(
+#[allow(clippy::too_many_arguments)]
+async fn send_prompt_and_collect(
     app: &tauri::AppHandle,
     state: &AppState,
     agent_id: &str,
@@ -2051,32 +3737,61 @@ async fn send_prompt_to_agent(
     task_run_id: Option<&str>,
     cancel_token: Option<&CancellationToken>,
     workspace_id: Option<&str>,
+    working_dir: Option<&str>,
     process_key: &str,
 ) -> AppResult<AgentPromptResult> {
     // Ensure agent is running
     let agent: AgentConfig = {
         let state_clone = state.clone();
         let aid = agent_id.to_string();
-        tokio::task::spawn_blocking(move || agent_repo::get_agent(&state_clone, &aid))
+        telemetry::spawn_blocking(move || agent_repo::get_agent(&state_clone, &aid))
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??
     };
     ensure_agent_running(app, state, &agent, process_key).await?;
-
-    // Check if we have an orchestration ACP session for this process key
-    let orch_session_key = format!("orch_session:{}", process_key);
+    // The assignment may run with settings of its own
+    let overrides = match task_run_id {
+        Some(trid) => assignment_overrides::current(state, trid, agent_id).await.unwrap_or_default(),
+        None => Default::default(),
+    };
+    let stored_model = agent.model.clone();
+    let mut agent = assignment_overrides::apply(&agent, &overrides);
+
+    // Check if we have an orchestration ACP session for this process key.
+    // Assignments in a sub-directory get their own session rooted there.
+    let orch_session_key = match working_dir {
+        Some(dir) => format!("orch_session:{}@{}", process_key, dir),
+        None => format!("orch_session:{}", process_key),
+    };
     let acp_session_id = {
         let sessions = state.acp_sessions.lock().await;
         sessions.get(&orch_session_key).map(|s| s.acp_session_id.clone())
     };
+    let session_cwd = match working_dir {
+        Some(dir) => dir.to_string(),
+        None => resolve_orchestrator_working_directory(state, workspace_id),
+    };
+    let writer = task_run_id.map(|trid| file_conflicts::FileWriter::new(trid, agent_id));
+    let serialize_writes = writer.is_some() && file_conflicts::is_serialize_enabled(state);
 
+    // Agents with context carry-over reuse their session (or its summary) across runs
+    let carry_over = agent.carry_over_context && task_run_id.is_some();
+    let mut context_preamble: Option<String> = None;
+
+    let new_session = acp_session_id.is_none();
     let acp_session_id = if let Some(id) = acp_session_id {
         id
     } else {
         // Create a new ACP session using non-blocking pattern to avoid holding
         // the agent_processes lock during the entire session creation handshake.
-        let cwd = resolve_orchestrator_working_directory(state, workspace_id);
-        let acp_id = create_session_nonblocking(state, process_key, agent_id, &cwd).await?;
+        let acp_id = if carry_over {
+            let (acp_id, preamble) =
+                open_carried_over_session(state, process_key, agent_id, workspace_id, &session_cwd).await?;
+            context_preamble = preamble;
+            acp_id
+        } else {
+            create_session_nonblocking(state, process_key, agent_id, &session_cwd).await?
+        };
 
         let mut sessions = state.acp_sessions.lock().await;
         sessions.insert(
@@ -2091,14 +3806,120 @@ async fn send_prompt_to_agent(
         acp_id
     };
 
+    // The first assignment after a warm-up starts from its output
+    if task_run_id.is_some() {
+        let warmup_output = state.agent_warmups.lock().await.remove(process_key);
+        if let Some(output) = warmup_output {
+            let warmup = format!(
+                "<warmup>\nBefore this task you ran your warm-up prompt. Its output:\n\n{}\n</warmup>",
+                output
+            );
+            context_preamble = Some(match context_preamble {
+                Some(preamble) => format!("{}\n\n{}", preamble, warmup),
+                None => warmup,
+            });
+        }
+    }
+
+    // Isolated runs keep scratch files out of the workspace
+    if let Some(scratch) = task_run_id.and_then(run_sandbox::active) {
+        let note = run_sandbox::instructions(&scratch);
+        context_preamble = Some(match context_preamble {
+            Some(preamble) => format!("{}\n\n{}", preamble, note),
+            None => note,
+        });
+    }
+
+    // An assignment's system prompt override comes first
+    if let Some(instructions) = assignment_overrides::instructions(&overrides) {
+        context_preamble = Some(match context_preamble {
+            Some(preamble) => format!("{}\n\n{}", instructions, preamble),
+            None => instructions,
+        });
+    }
+
+    // Switch the session to the assignment's model, or back to the agent's own
+    if task_run_id.is_some()
+        && !select_session_model(state, process_key, agent_id, &orch_session_key, &acp_session_id, overrides.model.as_deref(), &stored_model).await
+    {
+        agent.model = stored_model.clone();
+    }
+    let prompt_meta = assignment_overrides::prompt_meta(&overrides);
+
+    // Prompts too large for the model's context go in parts, elided if need be
+    let full_prompt = match &context_preamble {
+        Some(preamble) => format!("{}\n\n{}", preamble, prompt),
+        None => prompt.to_string(),
+    };
+    let delivery = prompt_budget::plan(&full_prompt, prompt_budget::load_context_window(state, &agent.model));
+    if delivery.is_split() || delivery.omitted_tokens > 0 {
+        log::warn!(
+            "Prompt for agent {} (~{} tokens) exceeds the context budget of {}: sending {} parts, {} tokens left out",
+            agent_id, delivery.estimated_tokens, agent.model, delivery.parts.len(), delivery.omitted_tokens,
+        );
+        events::emit(app, &events::PROMPT_SPLIT, serde_json::json!({
+            "taskRunId": task_run_id.unwrap_or(""),
+            "agentId": agent_id,
+            "model": agent.model,
+            "estimatedTokens": delivery.estimated_tokens,
+            "parts": delivery.parts.len(),
+            "omittedTokens": delivery.omitted_tokens,
+        }));
+    }
+    let mut parts = delivery.parts;
+    let final_part = parts.pop().unwrap_or_default();
+    for part in &parts {
+        if cancel_token.is_some_and(|token| token.is_cancelled()) {
+            return Err(AppError::AgentCancelled {
+                agent_id: agent_id.to_string(),
+                task_run_id: task_run_id.map(|s| s.to_string()),
+            });
+        }
+        let part_request_id = chrono::Utc::now().timestamp_millis();
+        {
+            let mut processes = state.agent_processes.lock().await;
+            let Some(process) = processes.get_mut(process_key) else {
+                return Err(AppError::AgentProcessLost { agent_id: agent_id.to_string(), process_key: process_key.to_string() });
+            };
+            client::send_prompt_with_meta(process, &acp_session_id, part, part_request_id, prompt_meta.as_ref()).await?;
+        }
+        let response = wait_for_response_nonblocking(state, process_key, agent_id, part_request_id, "session/prompt").await?;
+        if let Some(error) = response.get("error") {
+            return Err(AppError::AgentFailed {
+                agent_id: agent_id.to_string(),
+                task_run_id: task_run_id.map(|s| s.to_string()),
+                code: error.get("code").and_then(|v| v.as_i64()).unwrap_or(0),
+                message: error.get("message").and_then(|v| v.as_str()).unwrap_or("Unknown agent error").to_string(),
+            });
+        }
+    }
+
+    // Files attached to the assignment follow the prompt's text
+    let mut content = vec![prompt_content::text(&final_part)];
+    let attachments = match task_run_id {
+        Some(trid) => prompt_content::current(state, trid, agent_id).await,
+        None => None,
+    };
+    if let Some(attachments) = attachments {
+        let capabilities = {
+            let processes = state.agent_processes.lock().await;
+            processes.get(process_key).map(|p| p.prompt_capabilities).unwrap_or_default()
+        };
+        let root = std::path::PathBuf::from(resolve_orchestrator_working_directory(state, workspace_id));
+        let blocks = telemetry::spawn_blocking(move || prompt_content::blocks(&root, &attachments, capabilities))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        content.extend(blocks);
+    }
+
     // Send prompt
-    let request_id = chrono::Utc::now().timestamp_millis();
+    let mut request_id = chrono::Utc::now().timestamp_millis();
     {
         let mut processes = state.agent_processes.lock().await;
         if let Some(process) = processes.get_mut(process_key) {
-            client::send_prompt(process, &acp_session_id, prompt, request_id).await?;
+            client::send_prompt_content(process, &acp_session_id, content, request_id, prompt_meta.as_ref()).await?;
         } else {
-            return Err(AppError::Internal(format!("Agent {} process not found when sending prompt (key={})", agent_id, process_key)));
+            return Err(AppError::AgentProcessLost { agent_id: agent_id.to_string(), process_key: process_key.to_string() });
         }
     }
 
@@ -2108,17 +3929,100 @@ async fn send_prompt_to_agent(
     let mut tokens_out: i64 = 0;
     let mut cache_creation_tokens: i64 = 0;
     let mut cache_read_tokens: i64 = 0;
-    let mut jsonrpc_error: Option<String> = None;
+    let mut jsonrpc_error: Option<AppError> = None;
 
     // Stall detection state
     let mut last_text_chunk_at = std::time::Instant::now();
     let mut continue_nudges_sent: usize = 0;
 
+    // Streamed output is kept for step-by-step inspection of the run
+    let assignment_id = match task_run_id {
+        Some(trid) => running_assignments::current(state, trid, agent_id).await,
+        None => None,
+    };
+    let mut recorder =
+        task_run_id.map(|trid| timeline::TimelineRecorder::new(state, trid, assignment_id.as_deref(), agent_id));
+    // A new session that should have had the web search tool says why it has not
+    if let Some(recorder) = recorder.as_mut().filter(|_| new_session) {
+        if let Some(reason) = web_search::unavailable(&config::current(state).web_search, &agent) {
+            recorder.push_notice(reason).await;
+        }
+    }
+    // Workspaces that redact outputs get them masked before anything else sees them
+    let mut redactor = {
+        let state_clone = state.clone();
+        let ws = workspace_id.map(|s| s.to_string());
+        telemetry::spawn_blocking(move || redaction::for_workspace(&state_clone, ws.as_deref()))
+            .await
+            .ok()
+            .flatten()
+            .map(redaction::StreamRedactor::new)
+    };
+    // Assignments with an output file get their text streamed into it
+    let mut output_writer = match assignment_id.as_deref() {
+        Some(aid) => output_stream::OutputWriter::open(state, aid).await,
+        None => None,
+    };
+    // Assignments with a token or cost cap are wrapped up near it and stopped at it
+    let mut cap_monitor = match task_run_id {
+        Some(trid) => assignment_caps::CapMonitor::start(state, trid, agent_id, &agent.model, prompt).await,
+        None => None,
+    };
+    let mut wrap_up_sent = false;
+    let mut capped = false;
+    // Past the run's time limit the agent is asked to wrap up, then stopped
+    let deadline = match task_run_id {
+        Some(trid) => run_deadline::deadline(state, trid).await,
+        None => None,
+    };
+    let mut deadline_wrap_up_sent = false;
+
     loop {
         // Check per-agent cancellation
         if let Some(token) = cancel_token {
             if token.is_cancelled() {
-                return Err(AppError::Internal("Agent cancelled".into()));
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.flush().await;
+                }
+                return Err(AppError::AgentCancelled {
+                    agent_id: agent_id.to_string(),
+                    task_run_id: task_run_id.map(|s| s.to_string()),
+                });
+            }
+        }
+
+        if let Some(deadline) = deadline {
+            let check = run_deadline::check(deadline, std::time::Instant::now());
+            let wrap_up = check == run_deadline::DeadlineCheck::WrapUp && !deadline_wrap_up_sent;
+            let stop = check == run_deadline::DeadlineCheck::Stop;
+            if wrap_up || stop {
+                log::info!(
+                    "Agent {} reached the run's time limit, {}",
+                    agent_id,
+                    if stop { "stopping" } else { "asking it to wrap up" },
+                );
+                let wrap_up_request_id = chrono::Utc::now().timestamp_millis();
+                {
+                    let mut procs = state.agent_processes.lock().await;
+                    if let Some(process) = procs.get_mut(process_key) {
+                        if let Err(e) = client::cancel_prompt(process, &acp_session_id).await {
+                            log::warn!("Failed to cancel timed-out prompt of agent {}: {}", agent_id, e);
+                        }
+                        if wrap_up
+                            && client::send_prompt(process, &acp_session_id, run_deadline::WRAP_UP_PROMPT, wrap_up_request_id)
+                                .await
+                                .is_ok()
+                        {
+                            request_id = wrap_up_request_id;
+                        }
+                    }
+                }
+                capped = true;
+                if stop {
+                    break;
+                }
+                deadline_wrap_up_sent = true;
+                last_text_chunk_at = std::time::Instant::now();
             }
         }
 
@@ -2128,13 +4032,20 @@ async fn send_prompt_to_agent(
             let mut processes = state.agent_processes.lock().await;
             match processes.get_mut(process_key) {
                 Some(process) => process.message_rx.try_recv(),
-                None => return Err(AppError::Internal(format!("Agent {} process disappeared (key={})", agent_id, process_key))),
+                None => return Err(AppError::AgentProcessLost { agent_id: agent_id.to_string(), process_key: process_key.to_string() }),
             }
         };
         // HashMap lock is released here
 
         let msg = match recv_result {
-            Ok(msg) => Some(msg),
+            Ok(msg) => {
+                chaos::delay_message(state, agent_id).await;
+                let is_update = msg.get("method").and_then(|m| m.as_str()) == Some("session/update");
+                if is_update && chaos::drop_message(state, agent_id) {
+                    continue;
+                }
+                Some(msg)
+            }
             Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
                 // No message yet — check for stall, then yield
                 if last_text_chunk_at.elapsed() >= std::time::Duration::from_secs(STALL_TIMEOUT_SECS) {
@@ -2160,7 +4071,7 @@ async fn send_prompt_to_agent(
                         if nudge_sent {
                             continue_nudges_sent += 1;
                             last_text_chunk_at = std::time::Instant::now();
-                            let _ = app.emit("orchestration:agent_nudged", &serde_json::json!({
+                            events::emit(app, &events::AGENT_NUDGED, serde_json::json!({
                                 "taskRunId": task_run_id.unwrap_or(""),
                                 "agentId": agent_id,
                                 "nudgeCount": continue_nudges_sent,
@@ -2210,12 +4121,19 @@ async fn send_prompt_to_agent(
                                 {
                                     collected_text.push_str(text);
                                     last_text_chunk_at = std::time::Instant::now();
-
-                                    let _ = app.emit("orchestration:agent_chunk", &serde_json::json!({
-                                        "taskRunId": task_run_id.unwrap_or(""),
-                                        "agentId": agent_id,
-                                        "text": text,
-                                    }));
+                                    if let Some(monitor) = cap_monitor.as_mut() {
+                                        monitor.record_output(text);
+                                    }
+                                    let visible = match redactor.as_mut() {
+                                        Some(redactor) => redactor.push(text),
+                                        None => text.to_string(),
+                                    };
+                                    if !visible.is_empty() {
+                                        forward_agent_text(
+                                            app, state, task_run_id, agent_id, &visible, recorder.as_mut(), output_writer.as_mut(),
+                                        )
+                                        .await;
+                                    }
                                 }
                             }
                             "tool_call" | "tool_call_update" => {
@@ -2238,23 +4156,47 @@ async fn send_prompt_to_agent(
                                     .and_then(|u| u.get("status"))
                                     .and_then(|v| v.as_str())
                                     .unwrap_or(update_type);
-                                let raw_input = update
-                                    .and_then(|u| u.get("rawInput"))
-                                    .cloned();
-                                let raw_output = update
-                                    .and_then(|u| u.get("rawOutput"))
-                                    .cloned();
-
-                                let _ = app.emit("orchestration:agent_tool_call", &serde_json::json!({
+                                // Oversized payloads go to artifact files, referenced by id
+                                let raw_input = tool_payloads::inline(
+                                    state, task_run_id, agent_id, tool_call_id, "input",
+                                    update.and_then(|u| u.get("rawInput")),
+                                ).await;
+                                let raw_output = tool_payloads::inline(
+                                    state, task_run_id, agent_id, tool_call_id, "output",
+                                    update.and_then(|u| u.get("rawOutput")),
+                                ).await;
+
+                                events::emit(app, &events::AGENT_TOOL_CALL, serde_json::json!({
                                     "taskRunId": task_run_id.unwrap_or(""),
                                     "agentId": agent_id,
                                     "toolCallId": tool_call_id,
                                     "name": tool_name,
                                     "title": tool_title,
                                     "status": tool_status,
-                                    "rawInput": raw_input,
-                                    "rawOutput": raw_output,
+                                    "rawInput": raw_input.value,
+                                    "rawOutput": raw_output.value,
+                                    "rawInputArtifactId": raw_input.artifact_id,
+                                    "rawOutputArtifactId": raw_output.artifact_id,
                                 }));
+
+                                if let (Some(recorder), Some(update)) = (recorder.as_mut(), update) {
+                                    recorder.push_tool_call(update).await;
+                                }
+                                // Finished tool output is read back by the model
+                                if let (Some(monitor), Some(output), "completed") =
+                                    (cap_monitor.as_mut(), update.and_then(|u| u.get("rawOutput")), tool_status)
+                                {
+                                    monitor.record_input(&output.to_string());
+                                }
+
+                                // Pending calls may still wait on permission; count the write once it runs
+                                if let (Some(writer), Some(update)) = (&writer, update) {
+                                    if tool_status != "pending" {
+                                        for path in file_conflicts::tool_call_write_paths(update, &session_cwd) {
+                                            file_conflicts::record_write(app, state, writer, &path).await;
+                                        }
+                                    }
+                                }
                             }
                             "agent_thought_chunk" => {
                                 // Forward agent thought events
@@ -2265,7 +4207,13 @@ async fn send_prompt_to_agent(
                                     .and_then(|c| c.get("text"))
                                     .and_then(|t| t.as_str())
                                 {
-                                    let _ = app.emit("orchestration:agent_thought", &serde_json::json!({
+                                    if let Some(monitor) = cap_monitor.as_mut() {
+                                        monitor.record_output(text);
+                                    }
+                                    if let Some(recorder) = recorder.as_mut() {
+                                        recorder.push_chunk("thought", text).await;
+                                    }
+                                    events::emit(app, &events::AGENT_THOUGHT, serde_json::json!({
                                         "taskRunId": task_run_id.unwrap_or(""),
                                         "agentId": agent_id,
                                         "text": text,
@@ -2299,37 +4247,91 @@ async fn send_prompt_to_agent(
                             .cloned()
                             .unwrap_or_else(|| serde_json::json!([]));
 
-                        if let Some(trid) = task_run_id {
-                            log::info!(
-                                "Emitting orchestration:orch_permission for agent {} (task_run={}, request_id={})",
-                                agent_id, trid, perm_request_id
-                            );
-                            // Emit orchestration-specific permission event
-                            let _ = app.emit("orchestration:orch_permission", &serde_json::json!({
-                                "taskRunId": trid,
-                                "agentId": agent_id,
-                                "requestId": perm_request_id,
-                                "sessionId": session_id_val,
-                                "toolCall": tool_call_info,
-                                "options": options,
-                            }));
-
-                            // Wait for user response via oneshot channel
-                            let (tx, rx) = tokio::sync::oneshot::channel::<String>();
-                            {
-                                let perm_key = (trid.to_string(), perm_request_id.clone());
-                                let mut perms = state.pending_orch_permissions.lock().await;
-                                perms.insert(perm_key, tx);
+                        if let (Some(writer), Some(tool_call)) = (&writer, &tool_call_info) {
+                            if serialize_writes {
+                                for path in file_conflicts::tool_call_write_paths(tool_call, &session_cwd) {
+                                    file_conflicts::wait_for_path(app, state, writer, &path, cancel_token).await;
+                                }
                             }
+                        }
+
+                        if let Some(trid) = task_run_id {
+                            let request_key = permissions::request_key(tool_call_info.as_ref());
+                            let profile_decision = overrides
+                                .permission_profile
+                                .and_then(|profile| assignment_overrides::permission_decision(profile, tool_call_info.as_ref()));
+                            let remembered = match profile_decision {
+                                Some(allow) => Some((allow, "assignment")),
+                                None => match remembered_permission(state, trid, agent_id, &request_key).await {
+                                    Some(decision) => Some(decision),
+                                    None => preset_permission(state, trid, agent_id, tool_call_info.as_ref()).await,
+                                },
+                            };
+                            let option_id = if let Some((allow, scope)) = remembered {
+                                log::info!(
+                                    "Answering permission request {} of agent {} from the {} decision for '{}'",
+                                    perm_request_id, agent_id, scope, request_key
+                                );
+                                events::emit(app, &events::PERMISSION_REMEMBERED, serde_json::json!({
+                                    "taskRunId": trid,
+                                    "agentId": agent_id,
+                                    "requestKey": request_key,
+                                    "allowed": allow,
+                                    "scope": scope,
+                                }));
+                                permissions::pick_option(&options, allow)
+                            } else {
+                                // Wait for the user's answer; a request similar to
+                                // one already waiting joins its prompt
+                                let (tx, rx) = tokio::sync::oneshot::channel::<String>();
+                                let waiter = permissions::PermissionWaiter { options: options.clone(), tx };
+                                let batched = state.orch_permissions.lock().await.add(
+                                    trid,
+                                    &perm_request_id,
+                                    agent_id,
+                                    &request_key,
+                                    waiter,
+                                );
+                                match batched {
+                                    Some((batch_request_id, count)) => {
+                                        log::info!(
+                                            "Permission request {} of agent {} joins prompt {} ({} requests)",
+                                            perm_request_id, agent_id, batch_request_id, count
+                                        );
+                                        events::emit(app, &events::ORCH_PERMISSION_BATCHED, serde_json::json!({
+                                            "taskRunId": trid,
+                                            "agentId": agent_id,
+                                            "requestId": batch_request_id,
+                                            "count": count,
+                                            "toolCall": tool_call_info,
+                                        }));
+                                    }
+                                    None => {
+                                        log::info!(
+                                            "Emitting orchestration:orch_permission for agent {} (task_run={}, request_id={})",
+                                            agent_id, trid, perm_request_id
+                                        );
+                                        events::emit(app, &events::ORCH_PERMISSION, serde_json::json!({
+                                            "taskRunId": trid,
+                                            "agentId": agent_id,
+                                            "requestId": perm_request_id,
+                                            "sessionId": session_id_val,
+                                            "toolCall": tool_call_info,
+                                            "options": options,
+                                            "requestKey": request_key,
+                                        }));
+                                    }
+                                }
 
-                            // Wait with timeout
-                            let option_id = match tokio::time::timeout(
-                                std::time::Duration::from_secs(600),
-                                rx,
-                            ).await {
-                                Ok(Ok(id)) => id,
-                                Ok(Err(_)) => "allow".to_string(), // channel dropped, default allow
-                                Err(_) => "allow".to_string(),     // timeout, default allow
+                                // Wait with timeout
+                                match tokio::time::timeout(
+                                    std::time::Duration::from_secs(600),
+                                    rx,
+                                ).await {
+                                    Ok(Ok(id)) => id,
+                                    Ok(Err(_)) => "allow".to_string(), // channel dropped, default allow
+                                    Err(_) => "allow".to_string(),     // timeout, default allow
+                                }
                             };
 
                             // Send permission response back to agent via stdin
@@ -2359,8 +4361,41 @@ async fn send_prompt_to_agent(
                             }
                         } else {
                             // Non-orchestration context: forward as before
-                            let _ = app.emit("acp:permission_request", &msg);
+                            events::emit(app, &events::ACP_PERMISSION_REQUEST, msg.clone());
+                        }
+                    }
+                    "fs/read_text_file" | "fs/write_text_file" => {
+                        let rpc_id = msg.get("id").cloned().unwrap_or(serde_json::Value::Null);
+                        let params = msg.get("params").cloned().unwrap_or_default();
+                        let trusted_dir = resolve_orchestrator_working_directory(state, workspace_id);
+
+                        if let (Some(writer), "fs/write_text_file") = (&writer, method) {
+                            if let Some(path) = params.get("path").and_then(|p| p.as_str()) {
+                                if serialize_writes {
+                                    file_conflicts::wait_for_path(app, state, writer, path, cancel_token).await;
+                                }
+                                file_conflicts::record_write(app, state, writer, path).await;
+                            }
                         }
+
+                        let response = if method == "fs/read_text_file" {
+                            filesystem::handle_read_text_file(rpc_id, &params, Some(&trusted_dir)).await?
+                        } else {
+                            filesystem::handle_reviewed_write_text_file(
+                                app,
+                                state,
+                                task_run_id.unwrap_or(""),
+                                agent_id,
+                                rpc_id,
+                                &params,
+                                Some(&trusted_dir),
+                                cancel_token,
+                            )
+                            .await?
+                        };
+                        send_response_to_agent(state, process_key, &response).await;
+                        // Time spent waiting for review is not a stall
+                        last_text_chunk_at = std::time::Instant::now();
                     }
                     "" => {
                         // JSON-RPC response — check if this is for the original prompt or a nudge
@@ -2399,7 +4434,12 @@ async fn send_prompt_to_agent(
                             let err_code = error.get("code")
                                 .and_then(|v| v.as_i64())
                                 .unwrap_or(0);
-                            jsonrpc_error = Some(format!("Agent error (code {}): {}", err_code, err_msg));
+                            jsonrpc_error = Some(AppError::AgentFailed {
+                                agent_id: agent_id.to_string(),
+                                task_run_id: task_run_id.map(|s| s.to_string()),
+                                code: err_code,
+                                message: err_msg.to_string(),
+                            });
                         }
 
                         if is_original_response {
@@ -2415,24 +4455,102 @@ async fn send_prompt_to_agent(
             }
             None => break,
         }
+
+        // Near a cap the prompt is cancelled for a wrap-up; at the cap the wrap-up is cancelled too
+        if let (Some(monitor), Some(trid)) = (cap_monitor.as_ref(), task_run_id) {
+            let check = match assignment_caps::current(state, trid, agent_id).await {
+                Some(caps) => monitor.check(&caps),
+                None => assignment_caps::CapCheck::Within,
+            };
+            let wrap_up = check != assignment_caps::CapCheck::Within && !wrap_up_sent;
+            let stop = check == assignment_caps::CapCheck::Reached && wrap_up_sent;
+            if wrap_up || stop {
+                log::info!(
+                    "Agent {} {} its cap (~{} tokens out, ~${:.4}), {}",
+                    agent_id,
+                    if stop { "reached" } else { "is nearing" },
+                    monitor.tokens_out(),
+                    monitor.cost().unwrap_or(0.0),
+                    if stop { "stopping" } else { "asking it to wrap up" },
+                );
+                let wrap_up_request_id = chrono::Utc::now().timestamp_millis();
+                {
+                    let mut procs = state.agent_processes.lock().await;
+                    if let Some(process) = procs.get_mut(process_key) {
+                        if let Err(e) = client::cancel_prompt(process, &acp_session_id).await {
+                            log::warn!("Failed to cancel capped prompt of agent {}: {}", agent_id, e);
+                        }
+                        // Without a wrap-up, the cancelled prompt's response ends the loop
+                        if wrap_up
+                            && client::send_prompt(process, &acp_session_id, assignment_caps::WRAP_UP_PROMPT, wrap_up_request_id)
+                                .await
+                                .is_ok()
+                        {
+                            request_id = wrap_up_request_id;
+                        }
+                    }
+                }
+                capped = true;
+                events::emit(app, &events::AGENT_CAPPED, serde_json::json!({
+                    "taskRunId": trid,
+                    "agentId": agent_id,
+                    "tokensOut": monitor.tokens_out(),
+                    "cost": monitor.cost(),
+                    "wrappingUp": wrap_up,
+                }));
+                if stop {
+                    break;
+                }
+                wrap_up_sent = true;
+                last_text_chunk_at = std::time::Instant::now();
+            }
+        }
+    }
+
+    if let Some(rest) = redactor.as_mut().map(|r| r.finish()).filter(|rest| !rest.is_empty()) {
+        forward_agent_text(app, state, task_run_id, agent_id, &rest, recorder.as_mut(), output_writer.as_mut()).await;
+    }
+    if let Some(recorder) = recorder.as_mut() {
+        recorder.flush().await;
     }
 
     // Return error if the agent returned a JSON-RPC error
     if let Some(err) = jsonrpc_error {
         if collected_text.is_empty() {
-            if upgrade::detect_upgrade_error(&err).is_some() {
-                return Err(AppError::VersionUpgradeRequired(err));
+            if upgrade::detect_upgrade_error(&err.to_string()).is_some() {
+                return Err(AppError::VersionUpgradeRequired(err.to_string()));
             }
-            return Err(AppError::Internal(err));
+            return Err(err);
         }
         // If we got both text and an error, log the error but return the text
         log::warn!("Agent returned error alongside text: {}", err);
     }
 
     if collected_text.is_empty() {
-        return Err(AppError::Internal(
-            "Agent returned no response. Check that the agent is running and configured correctly.".into()
-        ));
+        return Err(AppError::AgentNoResponse {
+            agent_id: agent_id.to_string(),
+            task_run_id: task_run_id.map(|s| s.to_string()),
+        });
+    }
+    if let Some(redactor) = redactor.as_ref() {
+        collected_text = redactor.redactor().redact(&collected_text);
+    }
+
+    if carry_over {
+        let state_clone = state.clone();
+        let aid = agent_id.to_string();
+        let ws = workspace_id.map(|s| s.to_string());
+        let sid = acp_session_id.clone();
+        let trid = task_run_id.unwrap_or("").to_string();
+        let p = prompt.to_string();
+        let text = collected_text.clone();
+        let saved = telemetry::spawn_blocking(move || {
+            agent_context_repo::save_agent_context(&state_clone, &aid, ws.as_deref(), &sid, &trid, &p, &text)
+        })
+        .await;
+        if let Ok(Err(e)) = saved {
+            log::warn!("Failed to save carried-over context for agent {}: {}", agent_id, e);
+        }
     }
 
     Ok(AgentPromptResult {
@@ -2442,9 +4560,52 @@ async fn send_prompt_to_agent(
         cache_creation_tokens,
         cache_read_tokens,
         acp_session_id,
+        capped,
     })
 }
 
+/// Pass text an agent streamed on to the run's timeline, the assignment's
+/// output file, the frontend and the run's summary stream.
+async fn forward_agent_text(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run_id: Option<&str>,
+    agent_id: &str,
+    text: &str,
+    recorder: Option<&mut timeline::TimelineRecorder>,
+    output_writer: Option<&mut output_stream::OutputWriter>,
+) {
+    if let Some(recorder) = recorder {
+        recorder.push_chunk("text", text).await;
+    }
+    if let Some(writer) = output_writer {
+        writer.write(text).await;
+    }
+    events::emit(app, &events::AGENT_CHUNK, serde_json::json!({
+        "taskRunId": task_run_id.unwrap_or(""),
+        "agentId": agent_id,
+        "text": text,
+    }));
+    if let Some(trid) = task_run_id {
+        summary_stream::push_chunk(app, state, trid, agent_id, text).await;
+    }
+}
+
+/// Write a JSON-RPC response to an agent's stdin.
+async fn send_response_to_agent(state: &AppState, process_key: &str, response: &crate::acp::transport::JsonRpcResponse) {
+    use tokio::io::AsyncWriteExt;
+
+    let stdins = state.agent_stdins.lock().await;
+    if let Some(stdin) = stdins.get(process_key) {
+        let json_str = serde_json::to_string(response).unwrap_or_default();
+        let mut stdin_writer = stdin.lock().await;
+        let _ = stdin_writer.write_all(json_str.as_bytes()).await;
+        let _ = stdin_writer.write_all(b"\n").await;
+        let _ = stdin_writer.flush().await;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn execute_agent_assignment(
     app: &tauri::AppHandle,
     state: &AppState,
@@ -2453,10 +4614,12 @@ async fn execute_agent_assignment(
     task_run_id: &str,
     cancel_token: Option<&CancellationToken>,
     workspace_id: Option<&str>,
+    working_dir: Option<&str>,
 ) -> AppResult<AgentPromptResult> {
     let process_key = orch_process_key(task_run_id, &agent.id);
     ensure_agent_running(app, state, agent, &process_key).await?;
-    send_prompt_to_agent(app, state, &agent.id, input, Some(task_run_id), cancel_token, workspace_id, &process_key).await
+    chaos::maybe_kill_agent(state, &process_key).await;
+    send_prompt_to_agent(app, state, &agent.id, input, Some(task_run_id), cancel_token, workspace_id, working_dir, &process_key).await
 }
 
 /// Stop an agent process and clean up all associated state (sessions, stdin handles).
@@ -2477,11 +4640,18 @@ async fn stop_and_cleanup_agent(state: &AppState, process_key: &str, agent_id: &
         stdins.remove(process_key);
     }
 
-    // Remove all ACP sessions belonging to this process key
+    // A respawned agent warms up again
+    {
+        let mut warmups = state.agent_warmups.lock().await;
+        warmups.remove(process_key);
+    }
+
+    // Remove all ACP sessions belonging to this process key, including sub-directory sessions
     {
         let mut sessions = state.acp_sessions.lock().await;
         let session_key = format!("orch_session:{}", process_key);
-        sessions.remove(&session_key);
+        let subdir_prefix = format!("{}@", session_key);
+        sessions.retain(|k, _| *k != session_key && !k.starts_with(&subdir_prefix));
     }
 }
 
@@ -2496,6 +4666,10 @@ const MAX_UPGRADE_RETRIES: usize = 1;
 /// 3. Optionally updates the local adapter
 /// 4. Kills the old agent process and clears sessions
 /// 5. Retries the assignment (agent will be re-spawned by `ensure_agent_running`)
+///
+/// An agent that fails because it is not logged in is logged in with its
+/// login command and the assignment retried once the same way.
+#[allow(clippy::too_many_arguments)]
 async fn execute_agent_assignment_with_self_healing(
     app: &tauri::AppHandle,
     state: &AppState,
@@ -2504,11 +4678,14 @@ async fn execute_agent_assignment_with_self_healing(
     task_run_id: &str,
     cancel_token: Option<&CancellationToken>,
     workspace_id: Option<&str>,
+    working_dir: Option<&str>,
 ) -> AppResult<AgentPromptResult> {
     let mut retries = 0;
+    let mut logged_in = false;
 
     loop {
-        let result = execute_agent_assignment(app, state, agent, input, task_run_id, cancel_token, workspace_id).await;
+        let started_at = std::time::Instant::now();
+        let result = execute_agent_assignment(app, state, agent, input, task_run_id, cancel_token, workspace_id, working_dir).await;
 
         match result {
             Ok(prompt_result) => return Ok(prompt_result),
@@ -2543,7 +4720,7 @@ async fn execute_agent_assignment_with_self_healing(
                 );
 
                 // Emit upgrading event
-                let _ = app.emit("orchestration:agent_upgrading", &serde_json::json!({
+                events::emit(app, &events::AGENT_UPGRADING, serde_json::json!({
                     "taskRunId": task_run_id,
                     "agentId": agent.id,
                     "agentName": agent.name,
@@ -2553,7 +4730,7 @@ async fn execute_agent_assignment_with_self_healing(
                 // Run npm upgrade
                 if let Err(e) = upgrade::run_npm_upgrade(&upgrade_info).await {
                     log::error!("npm upgrade failed for {}: {}", upgrade_info.package, e);
-                    let _ = app.emit("orchestration:agent_upgrade_failed", &serde_json::json!({
+                    events::emit(app, &events::AGENT_UPGRADE_FAILED, serde_json::json!({
                         "taskRunId": task_run_id,
                         "agentId": agent.id,
                         "agentName": agent.name,
@@ -2562,6 +4739,11 @@ async fn execute_agent_assignment_with_self_healing(
                     return Err(e);
                 }
 
+                // The service rejected the version, so a pin would bring it back
+                if let Some(command) = agent.acp_command.as_deref() {
+                    agent_versions::unpin_command(command).await;
+                }
+
                 // Update local adapter (non-fatal)
                 if let Err(e) = upgrade::update_local_adapter(&upgrade_info.agent_type).await {
                     log::warn!("Local adapter update failed (non-fatal): {}", e);
@@ -2577,7 +4759,7 @@ async fn execute_agent_assignment_with_self_healing(
                 stop_and_cleanup_agent(state, &process_key, &agent.id).await;
 
                 // Emit upgraded event
-                let _ = app.emit("orchestration:agent_upgraded", &serde_json::json!({
+                events::emit(app, &events::AGENT_UPGRADED, serde_json::json!({
                     "taskRunId": task_run_id,
                     "agentId": agent.id,
                     "agentName": agent.name,
@@ -2588,6 +4770,17 @@ async fn execute_agent_assignment_with_self_healing(
                 // which re-spawns the agent with the upgraded binary
                 continue;
             }
+            Err(e) if !logged_in && agent_auth::is_auth_error(&e) => {
+                log::info!("Agent {} is not logged in: {}", agent.id, e);
+                if !agent_auth::login(app, state, agent, Some(task_run_id), started_at).await? {
+                    return Err(e);
+                }
+                logged_in = true;
+                // The agent reads its credentials when it starts
+                let process_key = orch_process_key(task_run_id, &agent.id);
+                stop_and_cleanup_agent(state, &process_key, &agent.id).await;
+                continue;
+            }
             Err(other) => return Err(other),
         }
     }
@@ -2602,6 +4795,266 @@ async fn is_cancelled(state: &AppState, task_run_id: &str) -> bool {
     }
 }
 
+/// Wait for the user's confirmation action. The deadline is persisted so a
+/// resumed run keeps its original timeout; when it passes, the configured
+/// timeout action applies. Returns `None` if the run is cancelled meanwhile.
+///
+/// However the wait ends, the pending confirmation is removed again. So is
+/// the stored deadline, unless the app is shutting down and the run is to
+/// resume with it on the next launch.
+async fn wait_for_confirmation(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run_id: &str,
+) -> AppResult<Option<ConfirmationAction>> {
+    let (tx, rx) = tokio::sync::oneshot::channel::<ConfirmationAction>();
+    {
+        let mut confirmations = state.pending_confirmations.lock().await;
+        confirmations.insert(task_run_id.to_string(), tx);
+    }
+    let result = await_confirmation(app, state, task_run_id, rx).await;
+    state.pending_confirmations.lock().await.remove(task_run_id);
+    if !state.shutdown_token.is_cancelled() {
+        clear_confirmation_deadline(state, task_run_id).await;
+    }
+    result
+}
+
+async fn await_confirmation(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run_id: &str,
+    mut rx: tokio::sync::oneshot::Receiver<ConfirmationAction>,
+) -> AppResult<Option<ConfirmationAction>> {
+    let cancel_token = {
+        let tokens = state.active_task_runs.lock().await;
+        tokens.get(task_run_id).cloned().unwrap_or_default()
+    };
+
+    let settings = config::current(state);
+    let deadline = {
+        let state_clone = state.clone();
+        let id = task_run_id.to_string();
+        let timeout_minutes = settings.confirmation_timeout_minutes as i64;
+        telemetry::spawn_blocking(move || -> AppResult<chrono::DateTime<chrono::Utc>> {
+            let stored = task_run_repo::get_confirmation_deadline(&state_clone, &id)?
+                .and_then(|d| chrono::DateTime::parse_from_rfc3339(&d).ok())
+                .map(|d| d.with_timezone(&chrono::Utc));
+            if let Some(deadline) = stored {
+                return Ok(deadline);
+            }
+            let deadline = chrono::Utc::now() + chrono::Duration::minutes(timeout_minutes);
+            task_run_repo::set_confirmation_deadline(&state_clone, &id, &deadline.to_rfc3339())?;
+            Ok(deadline)
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??
+    };
+    let remaining = (deadline - chrono::Utc::now()).to_std().unwrap_or_default();
+
+    let timed_out = tokio::select! {
+        _ = cancel_token.cancelled() => return Ok(None),
+        result = tokio::time::timeout(remaining, &mut rx) => match result {
+            Ok(Ok(action)) => Some(action),
+            Ok(Err(_)) => Some(ConfirmationAction::Confirm), // channel dropped
+            Err(_) => None,
+        },
+    };
+    let action = match timed_out {
+        Some(action) => action,
+        None => match settings.confirmation_timeout_action.as_str() {
+            "fail" => {
+                return Err(AppError::Internal(format!(
+                    "Confirmation timed out after {} minutes",
+                    settings.confirmation_timeout_minutes
+                )));
+            }
+            "pause" => {
+                log::info!("Confirmation for task {} timed out, pausing until the user responds", task_run_id);
+                cleanup_task_processes(state, task_run_id).await;
+                events::emit(app, &events::CONFIRMATION_PAUSED, serde_json::json!({
+                    "taskRunId": task_run_id,
+                }));
+                tokio::select! {
+                    _ = cancel_token.cancelled() => return Ok(None),
+                    result = &mut rx => result.unwrap_or(ConfirmationAction::Confirm),
+                }
+            }
+            _ => ConfirmationAction::Confirm,
+        },
+    };
+    Ok(Some(action))
+}
+
+/// When every agent of the workspace besides the hub is disabled, e.g.
+/// auto-disabled during a provider outage, re-enable the ones disabled by
+/// transient errors. Failing that, returns true when the hub should run the
+/// whole task itself, which the user is asked to approve.
+async fn recover_disabled_agents(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run_id: &str,
+    workspace_id: Option<&str>,
+    all_agents: &mut [AgentConfig],
+    hub_agent: Option<&AgentConfig>,
+) -> AppResult<bool> {
+    let has_workers = all_agents.iter().any(|a| !a.is_control_hub);
+    if !has_workers || all_agents.iter().any(|a| !a.is_control_hub && a.is_enabled) {
+        return Ok(false);
+    }
+
+    let transient: Vec<String> = all_agents
+        .iter()
+        .filter(|a| !a.is_control_hub && a.disabled_transient)
+        .map(|a| a.id.clone())
+        .collect();
+    if !transient.is_empty() {
+        let state_clone = state.clone();
+        let ids = transient.clone();
+        telemetry::spawn_blocking(move || agent_repo::set_agents_enabled(&state_clone, &ids, true))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+        for agent in all_agents.iter_mut().filter(|a| transient.contains(&a.id)) {
+            agent.is_enabled = true;
+            agent.disabled_reason = None;
+            agent.disabled_transient = false;
+        }
+        log::info!("All agents disabled for task {}, re-enabled {} disabled by transient errors", task_run_id, transient.len());
+        events::emit(app, &events::AGENTS_RECOVERY, serde_json::json!({
+            "taskRunId": task_run_id,
+            "workspaceId": workspace_id,
+            "action": "reenabled_agents",
+            "agentIds": &transient,
+            "message": format!("All agents were disabled; re-enabled {} that failed with transient errors", transient.len()),
+        }));
+        return Ok(false);
+    }
+
+    let Some(hub) = hub_agent.filter(|h| h.is_enabled) else {
+        return Ok(false);
+    };
+    log::info!("All agents disabled for task {}, offering to run it on the Control Hub", task_run_id);
+    events::emit(app, &events::AGENTS_RECOVERY, serde_json::json!({
+        "taskRunId": task_run_id,
+        "workspaceId": workspace_id,
+        "action": "run_on_hub",
+        "agentIds": [&hub.id],
+        "message": "All agents are disabled; approve the plan to run the whole task on the Control Hub",
+    }));
+    Ok(true)
+}
+
+/// Single-assignment plan giving the whole task to the hub.
+fn hub_only_plan(hub: &AgentConfig, user_prompt: &str) -> TaskPlan {
+    TaskPlan {
+        analysis: "All workspace agents are disabled, so the Control Hub runs the whole task.".into(),
+        assignments: vec![PlannedAssignment {
+            agent_id: hub.id.clone(),
+            task_description: user_prompt.to_string(),
+            sequence_order: 0,
+            depends_on: Vec::new(),
+            matched_skills: Vec::new(),
+            selection_reason: "Fallback: every other agent is disabled".into(),
+            working_directory: None,
+            output_file: None,
+            max_tokens_out: None,
+            max_cost: None,
+            confidence: None,
+            assumptions: vec!["The disabled agents stay disabled for this run".into()],
+            overrides: None,
+            attachments: Vec::new(),
+            dependency_sections: Default::default(),
+        }],
+        isolated: false,
+    }
+}
+
+/// Hold a run whose plan the hub is not confident in until the user approves
+/// it. Returns false when the plan was rejected, which cancels the run, or
+/// the run was cancelled meanwhile.
+async fn wait_for_plan_approval(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run_id: &str,
+    confidence: Option<f64>,
+    threshold: f64,
+) -> AppResult<bool> {
+    let (tx, rx) = tokio::sync::oneshot::channel::<bool>();
+    state.pending_plan_approvals.lock().await.insert(task_run_id.to_string(), tx);
+    let cancel_token = {
+        let tokens = state.active_task_runs.lock().await;
+        tokens.get(task_run_id).cloned().unwrap_or_default()
+    };
+    {
+        let state_clone = state.clone();
+        let id = task_run_id.to_string();
+        telemetry::spawn_blocking(move || task_run_repo::update_task_run_status(&state_clone, &id, "awaiting_plan_approval"))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+    }
+    log::info!(
+        "Plan of task {} has confidence {:?} below {}, waiting for approval",
+        task_run_id, confidence, threshold
+    );
+    events::emit(app, &events::AWAITING_PLAN_APPROVAL, serde_json::json!({
+        "taskRunId": task_run_id,
+        "confidence": confidence,
+        "threshold": threshold,
+    }));
+
+    let approved = tokio::select! {
+        _ = cancel_token.cancelled() => None,
+        result = rx => result.ok(),
+    };
+    state.pending_plan_approvals.lock().await.remove(task_run_id);
+    match approved {
+        Some(true) => {
+            log::info!("Plan of task {} approved", task_run_id);
+            Ok(true)
+        }
+        Some(false) => {
+            log::info!("Plan of task {} rejected, cancelling the run", task_run_id);
+            let state_clone = state.clone();
+            let ids = [task_run_id.to_string()];
+            telemetry::spawn_blocking(move || task_run_repo::cancel_task_runs(&state_clone, &ids, CancelReason::WrongDirection))
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))??;
+            Ok(false)
+        }
+        // Cancelled, or shutting down: a resumed run asks again
+        None => Ok(false),
+    }
+}
+
+async fn clear_confirmation_deadline(state: &AppState, task_run_id: &str) {
+    let state_clone = state.clone();
+    let id = task_run_id.to_string();
+    let cleared = telemetry::spawn_blocking(move || task_run_repo::clear_confirmation_deadline(&state_clone, &id)).await;
+    if let Ok(Err(e)) = cleared {
+        log::warn!("Failed to clear confirmation deadline for task {}: {}", task_run_id, e);
+    }
+}
+
+/// Leave a run's results unsummarized and mark it for review.
+async fn dismiss_for_review(app: &tauri::AppHandle, state: &AppState, task_run_id: &str) -> AppResult<()> {
+    log::info!("Confirmation dismissed for task {}, marking for review", task_run_id);
+    {
+        let mut confirmations = state.pending_confirmations.lock().await;
+        confirmations.remove(task_run_id);
+    }
+    {
+        let state_clone = state.clone();
+        let id = task_run_id.to_string();
+        telemetry::spawn_blocking(move || task_run_repo::update_task_run_status(&state_clone, &id, "needs_review"))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+    }
+    events::emit(app, &events::NEEDS_REVIEW, serde_json::json!({
+        "taskRunId": task_run_id,
+    }));
+    Ok(())
+}
+
 async fn write_output_summary(
     state: &AppState,
     task_run_id: &str,
@@ -2621,7 +5074,7 @@ async fn write_output_summary(
     let assignments = {
         let state_clone = state.clone();
         let trid = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::list_assignments_for_run(&state_clone, &trid)
         })
         .await
@@ -2634,13 +5087,21 @@ async fn write_output_summary(
     let total_in: i64 = assignments.iter().map(|a| a.tokens_in).sum();
     let total_out: i64 = assignments.iter().map(|a| a.tokens_out).sum();
 
+    let locale = i18n::current(state);
+    let label = |msg| i18n::text(locale, msg);
     let mut md = format!(
-        "# Task: {}\n**Duration**: {}\n**Total Tokens**: {} in / {} out\n\n## Plan\n{}\n\n## Agent Executions\n| # | Agent | Model | Tokens In | Tokens Out | Duration | Status |\n|---|-------|-------|-----------|------------|----------|--------|\n",
+        "# {}: {}\n**{}**: {}\n**{}**: {} in / {} out\n\n## {}\n{}\n\n## {}\n{}\n|---|-------|-------|-----------|------------|----------|--------|\n",
+        label(Msg::SummaryTitle),
         user_prompt.lines().next().unwrap_or("Orchestration"),
+        label(Msg::SummaryDuration),
         duration_str,
+        label(Msg::SummaryTokens),
         total_in,
         total_out,
+        label(Msg::SummaryPlan),
         plan.analysis,
+        label(Msg::SummaryExecutions),
+        label(Msg::SummaryTableHeader),
     );
 
     for (i, assignment) in assignments.iter().enumerate() {
@@ -2656,7 +5117,12 @@ async fn write_output_summary(
         ));
     }
 
-    md.push_str(&format!("\n## Result\n{}\n", summary));
+    match run_changes::collect(task_run_id) {
+        Ok(changes) => md.push_str(&run_changes::render_markdown(&changes, locale, &output_dir.join("diffs"))),
+        Err(e) => log::warn!("Failed to read code changes of {}: {}", task_run_id, e),
+    }
+
+    md.push_str(&format!("\n## {}\n{}\n", label(Msg::SummaryResult), summary));
 
     let summary_path = output_dir.join("summary.md");
     if let Err(e) = std::fs::write(&summary_path, &md) {
@@ -2666,7 +5132,18 @@ async fn write_output_summary(
     }
 }
 
-fn format_duration(ms: i64) -> String {
+/// Add a completed run to the workspace's long-term memory.
+async fn remember_task_run(state: &AppState, task_run_id: &str) {
+    let state_clone = state.clone();
+    let trid = task_run_id.to_string();
+    match telemetry::spawn_blocking(move || memory::remember_task_run(&state_clone, &trid)).await {
+        Ok(Err(e)) => log::warn!("Failed to remember task run {}: {}", task_run_id, e),
+        Err(e) => log::warn!("Failed to remember task run {}: {}", task_run_id, e),
+        Ok(Ok(())) => {}
+    }
+}
+
+pub(crate) fn format_duration(ms: i64) -> String {
     if ms < 1000 {
         format!("{}ms", ms)
     } else if ms < 60_000 {
@@ -2678,10 +5155,34 @@ fn format_duration(ms: i64) -> String {
     }
 }
 
+/// Preamble with the workspace's hub persona for the Control Hub's planning,
+/// feedback and summary prompts; empty when the workspace has none.
+async fn hub_persona(state: &AppState, workspace_id: Option<&str>) -> String {
+    let Some(ws_id) = workspace_id.map(|s| s.to_string()) else {
+        return String::new();
+    };
+    let state_clone = state.clone();
+    let persona = match telemetry::spawn_blocking(move || crate::db::workspace_repo::get_workspace(&state_clone, &ws_id)).await {
+        Ok(Ok(ws)) => ws.hub_persona,
+        Ok(Err(e)) => {
+            log::warn!("Failed to load the hub persona of workspace {:?}: {}", workspace_id, e);
+            None
+        }
+        Err(e) => {
+            log::warn!("Spawn blocking failed: {}", e);
+            None
+        }
+    };
+    match persona.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(persona) => format!("## Workspace Persona\n\nIn this workspace, act as follows:\n{}\n\n---\n\n", persona),
+        None => String::new(),
+    }
+}
+
 /// Resolve the effective working directory for orchestration.
 /// When workspace_id is provided, uses the workspace's working_directory.
 /// Falls back to the user-configured setting, then current_dir().
-fn resolve_orchestrator_working_directory(state: &AppState, workspace_id: Option<&str>) -> String {
+pub(crate) fn resolve_orchestrator_working_directory(state: &AppState, workspace_id: Option<&str>) -> String {
     if let Some(ws_id) = workspace_id {
         if let Ok(ws) = crate::db::workspace_repo::get_workspace(state, ws_id) {
             if !ws.working_directory.is_empty() {
@@ -2689,14 +5190,88 @@ fn resolve_orchestrator_working_directory(state: &AppState, workspace_id: Option
             }
         }
     }
-    if let Ok(Some(setting)) = settings_repo::get_setting(state, "working_directory") {
-        if !setting.value.is_empty() {
-            return setting.value;
+    crate::commands::settings_commands::resolve_working_directory(state)
+}
+
+/// Resolve an assignment's sub-directory against the workspace root.
+/// Returns the absolute path, or None when no sub-directory is set or it fails
+/// validation (in which case the agent works in the workspace root).
+fn resolve_assignment_working_directory(
+    state: &AppState,
+    workspace_id: Option<&str>,
+    subdir: Option<&str>,
+) -> Option<String> {
+    let subdir = subdir?;
+    let root = resolve_orchestrator_working_directory(state, workspace_id);
+    match validate_working_subdirectory(std::path::Path::new(&root), subdir) {
+        Ok(dir) => Some(dir.to_string_lossy().to_string()),
+        Err(reason) => {
+            log::warn!("Ignoring working directory '{}' (root {}): {}", subdir, root, reason);
+            None
+        }
+    }
+}
+
+/// Check that `subdir` is a relative path naming an existing directory inside `root`.
+/// Symlinks are resolved, so a link pointing outside the root is rejected.
+fn validate_working_subdirectory(root: &std::path::Path, subdir: &str) -> Result<std::path::PathBuf, String> {
+    use std::path::Component;
+
+    let rel = std::path::Path::new(subdir);
+    if rel.is_absolute() || !rel.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err("must be a relative path without '..'".into());
+    }
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("workspace root is not accessible: {e}"))?;
+    let dir = root
+        .join(rel)
+        .canonicalize()
+        .map_err(|_| "directory does not exist".to_string())?;
+    if !dir.starts_with(&root) {
+        return Err("resolves outside the workspace root".into());
+    }
+    if !dir.is_dir() {
+        return Err("not a directory".into());
+    }
+    Ok(dir)
+}
+
+/// Normalize the planner's working directories, dropping any that fail validation.
+fn normalize_plan_working_directories(state: &AppState, workspace_id: Option<&str>, plan: &mut TaskPlan) {
+    let root = resolve_orchestrator_working_directory(state, workspace_id);
+    for assignment in &mut plan.assignments {
+        let Some(subdir) = assignment.working_directory.take() else {
+            continue;
+        };
+        let subdir = subdir.trim().trim_end_matches('/').to_string();
+        if subdir.is_empty() || subdir == "." {
+            continue;
+        }
+        match validate_working_subdirectory(std::path::Path::new(&root), &subdir) {
+            Ok(_) => assignment.working_directory = Some(subdir),
+            Err(reason) => log::warn!(
+                "Dropping working directory '{}' for agent {}: {}",
+                subdir, assignment.agent_id, reason
+            ),
         }
     }
-    std::env::current_dir()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_else(|_| ".".into())
+}
+
+/// List the top-level sub-directories of the workspace root for the planner.
+fn list_workspace_subdirectories(root: &str) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| !name.starts_with('.') && name != "node_modules" && name != "target")
+        .collect();
+    dirs.sort();
+    dirs.truncate(50);
+    dirs
 }
 
 // ---------------------------------------------------------------------------
@@ -2705,7 +5280,7 @@ fn resolve_orchestrator_working_directory(state: &AppState, workspace_id: Option
 
 /// Resume a single orchestration task run from its persisted state.
 ///
-/// - `pending` / `analyzing`: Restart from scratch via `run_orchestration`
+/// - `pending` / `waiting_for_workspace` / `analyzing`: Restart from scratch via `run_orchestration`
 /// - `running`: Load plan + completed assignment outputs, skip completed, re-run the rest
 /// - `awaiting_confirmation`: Load completed outputs, re-enter confirmation flow
 pub async fn resume_orchestration(
@@ -2722,7 +5297,7 @@ pub async fn resume_orchestration(
     );
 
     let result = match status.as_str() {
-        "pending" | "analyzing" => {
+        "pending" | "waiting_for_workspace" | "analyzing" => {
             // No usable plan — restart from scratch
             let user_prompt = task_run.user_prompt.clone();
             let workspace_id = task_run.workspace_id.clone();
@@ -2732,6 +5307,7 @@ pub async fn resume_orchestration(
                 &task_run_id,
                 &user_prompt,
                 workspace_id.as_deref(),
+                None,
             )
             .await
         }
@@ -2741,6 +5317,9 @@ pub async fn resume_orchestration(
         "awaiting_confirmation" => {
             resume_from_confirmation(&app, &state, &task_run).await
         }
+        "awaiting_plan_approval" => {
+            resume_from_plan_approval(&app, &state, &task_run).await
+        }
         _ => {
             log::warn!("Unexpected status '{}' for resume, skipping task {}", status, task_run_id);
             Ok(())
@@ -2749,33 +5328,39 @@ pub async fn resume_orchestration(
 
     // Clean up (same as run_orchestration)
     cleanup_task_processes(&state, &task_run_id).await;
+    run_sandbox::finish(&state, &task_run_id).await;
 
     {
         let mut tokens = state.active_task_runs.lock().await;
         tokens.remove(&task_run_id);
     }
+    run_queue::dispatch(&app, &state);
     {
         let mut agent_cancels = state.agent_cancellations.lock().await;
         agent_cancels.retain(|(trid, _), _| trid != &task_run_id);
     }
+    state.orch_permissions.lock().await.forget_run(&task_run_id);
 
     if let Err(e) = &result {
         let error_msg = e.to_string();
         log::error!("Resumed orchestration failed for {}: {}", task_run_id, error_msg);
-        let _ = app.emit("orchestration:error", serde_json::json!({
+        events::emit(&app, &events::RUN_ERROR, serde_json::json!({
             "taskRunId": task_run_id,
             "error": error_msg,
+            "errorCode": e.code(),
         }));
         let state_clone = state.clone();
         let id_clone = task_run_id.clone();
-        let _ = tokio::task::spawn_blocking(move || {
+        let _ = telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_status(&state_clone, &id_clone, "failed")
         }).await;
     }
+    run_notifications::notify_finished(&app, &state, &task_run_id, &result);
 }
 
 /// Resume an orchestration task that was previously in `running` state.
 /// Loads the saved plan, skips completed assignments, and re-executes the rest.
+#[tracing::instrument(name = "resumed_orchestration", skip_all, fields(task_run_id = %task_run.id))]
 async fn resume_orchestration_running(
     app: &tauri::AppHandle,
     state: &AppState,
@@ -2795,19 +5380,13 @@ async fn resume_orchestration_running(
     })?;
 
     // 2. Validate hub agent still exists
-    let hub_agent: AgentConfig = {
-        let state_clone = state.clone();
-        let hub_id = task_run.control_hub_agent_id.clone();
-        tokio::task::spawn_blocking(move || agent_repo::get_agent(&state_clone, &hub_id))
-            .await
-            .map_err(|e| AppError::Internal(e.to_string()))??
-    };
+    let hub_agent = load_serving_hub(state, task_run).await?;
 
     // 3. Load all agents (scoped to workspace)
     let all_agents: Vec<AgentConfig> = {
         let state_clone = state.clone();
         let ws_id: Option<String> = workspace_id.map(|s| s.to_string());
-        tokio::task::spawn_blocking(move || agent_repo::list_agents(&state_clone, ws_id.as_deref()))
+        telemetry::spawn_blocking(move || agent_repo::list_agents(&state_clone, ws_id.as_deref()))
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??
     };
@@ -2816,7 +5395,7 @@ async fn resume_orchestration_running(
     let db_assignments: Vec<crate::models::task_run::TaskAssignment> = {
         let state_clone = state.clone();
         let trid = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || task_run_repo::list_assignments_for_run(&state_clone, &trid))
+        telemetry::spawn_blocking(move || task_run_repo::list_assignments_for_run(&state_clone, &trid))
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??
     };
@@ -2827,12 +5406,20 @@ async fn resume_orchestration_running(
     let mut total_tokens_out: i64 = 0;
     let mut total_cache_creation_tokens: i64 = 0;
     let mut total_cache_read_tokens: i64 = 0;
+    // Corrections used before the run stopped still count against the limit
+    let mut feedback_corrections: usize = {
+        let state_clone = state.clone();
+        let trid = task_run_id.to_string();
+        telemetry::spawn_blocking(move || task_run_repo::get_feedback_corrections(&state_clone, &trid))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??
+    };
 
     // Track which (agent_id, sequence_order) pairs are already completed
     let mut completed_keys: std::collections::HashSet<(String, i64)> = std::collections::HashSet::new();
 
     for assignment in &db_assignments {
-        if assignment.status == "completed" {
+        if matches!(assignment.status.as_str(), "completed" | assignment_caps::CAPPED_STATUS) {
             if let Some(ref output) = assignment.output_text {
                 agent_outputs.insert(assignment.agent_id.clone(), output.clone());
             }
@@ -2855,14 +5442,14 @@ async fn resume_orchestration_running(
     {
         let state_clone = state.clone();
         let id = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_status(&state_clone, &id, "running")
         })
         .await
         .map_err(|e| AppError::Internal(e.to_string()))??;
     }
 
-    let _ = app.emit("orchestration:started", &serde_json::json!({
+    events::emit(app, &events::RUN_STARTED, serde_json::json!({
         "taskRunId": task_run_id,
         "status": "running",
         "resumed": true,
@@ -2898,7 +5485,7 @@ async fn resume_orchestration_running(
     let mut sorted_orders: Vec<i64> = sequence_groups.keys().copied().collect();
     sorted_orders.sort();
 
-    let hub_process_key = orch_process_key(task_run_id, &hub_agent.id);
+    let hub_process_key = hub_agent.as_ref().map(|h| orch_process_key(task_run_id, &h.id)).unwrap_or_default();
 
     for order in &sorted_orders {
         let group = &sequence_groups[order];
@@ -2967,7 +5554,7 @@ async fn resume_orchestration_running(
                             .find(|a| a.id == *dep_id)
                             .map(|a| a.name.clone())
                             .unwrap_or_else(|| "Previous agent".into());
-                        input_parts.push(format!("\n--- Output from {dep_name} ---\n{output}"));
+                        input_parts.push(dependency_outputs::input_part(&dep_name, output, dependency_outputs::sections(planned, dep_id)));
                     }
                 }
 
@@ -2976,6 +5563,20 @@ async fn resume_orchestration_running(
                     input_parts.push(peer_catalog);
                 }
 
+                let knowledge_context = {
+                    let state_clone = state.clone();
+                    let ws_id = workspace_id.map(|s| s.to_string());
+                    let description = planned.task_description.clone();
+                    telemetry::spawn_blocking(move || {
+                        knowledge::assignment_context(&state_clone, ws_id.as_deref(), &description)
+                    })
+                    .await
+                    .unwrap_or_default()
+                };
+                if !knowledge_context.is_empty() {
+                    input_parts.push(knowledge_context);
+                }
+
                 let input_text = input_parts.join("\n");
 
                 // Create assignment record
@@ -2988,7 +5589,7 @@ async fn resume_orchestration_running(
                     let aname = agent_name.clone();
                     let seq = planned.sequence_order;
                     let inp = input_text.clone();
-                    tokio::task::spawn_blocking(move || {
+                    telemetry::spawn_blocking(move || {
                         task_run_repo::create_task_assignment(
                             &state_clone, &aid, &trid, &agid, &aname, seq, &inp,
                         )
@@ -2997,11 +5598,58 @@ async fn resume_orchestration_running(
                     .map_err(|e| AppError::Internal(e.to_string()))??;
                 }
 
+                // Resolve and record the assignment's working directory
+                let working_dir = resolve_assignment_working_directory(
+                    state, workspace_id, planned.working_directory.as_deref(),
+                );
+                if let Some(subdir) = planned.working_directory.clone().filter(|_| working_dir.is_some()) {
+                    let state_clone = state.clone();
+                    let aid = assignment_id.clone();
+                    telemetry::spawn_blocking(move || {
+                        task_run_repo::set_assignment_working_directory(&state_clone, &aid, &subdir)
+                    })
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))??;
+                }
+
+                // Record the caps the agent works under; invalid caps are ignored
+                let caps = assignment_caps::AssignmentCaps::of(planned);
+                let caps = match caps.validate() {
+                    Ok(()) => caps,
+                    Err(e) => {
+                        log::warn!("Ignoring caps of agent {}'s assignment: {}", planned.agent_id, e);
+                        assignment_caps::AssignmentCaps::default()
+                    }
+                };
+                if !caps.is_empty() {
+                    let state_clone = state.clone();
+                    let aid = assignment_id.clone();
+                    telemetry::spawn_blocking(move || {
+                        task_run_repo::set_assignment_caps(&state_clone, &aid, caps.max_tokens_out, caps.max_cost)
+                    })
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))??;
+                }
+
+                // Record the agent settings the assignment overrides
+                let overrides = assignment_overrides::checked(planned);
+                if !overrides.is_empty() {
+                    let state_clone = state.clone();
+                    let aid = assignment_id.clone();
+                    let o = overrides.clone();
+                    telemetry::spawn_blocking(move || {
+                        task_run_repo::set_assignment_overrides(&state_clone, &aid, &o)
+                    })
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))??;
+                }
+                let agent_model = overrides.model.clone().unwrap_or(agent_model);
+
                 // Mark as running
                 {
                     let state_clone = state.clone();
                     let aid = assignment_id.clone();
-                    tokio::task::spawn_blocking(move || {
+                    telemetry::spawn_blocking(move || {
                         task_run_repo::update_task_assignment(
                             &state_clone, &aid, "running", None, None, 0, 0, 0, 0, 0, None,
                         )
@@ -3010,7 +5658,7 @@ async fn resume_orchestration_running(
                     .map_err(|e| AppError::Internal(e.to_string()))??;
                 }
 
-                let _ = app.emit("orchestration:agent_started", &serde_json::json!({
+                events::emit(app, &events::AGENT_STARTED, serde_json::json!({
                     "taskRunId": task_run_id,
                     "assignmentId": assignment_id,
                     "agentId": planned.agent_id,
@@ -3049,24 +5697,45 @@ async fn resume_orchestration_running(
 
                 let ws_id_clone: Option<String> = workspace_id.map(|s| s.to_string());
                 let all_agents_clone = all_agents.clone();
+                let output_path = output_stream::prepare(
+                    state, task_run_id, &assignment_id, planned.output_file.as_deref(),
+                ).await;
+                assignment_caps::begin(state, task_run_id, &planned.agent_id, caps).await;
+                assignment_overrides::begin(state, task_run_id, &planned.agent_id, overrides).await;
+                running_assignments::begin(state, task_run_id, &planned.agent_id, &assignment_id).await;
+                prompt_content::begin(state, task_run_id, &planned.agent_id, &planned.attachments).await;
                 join_set.spawn(async move {
                     let assign_start = std::time::Instant::now();
 
-                    let result = execute_with_a2a_routing(
+                    let result = execute_with_response_cache(
                         &app_clone,
                         &state_clone,
                         &agent_config,
                         &input_clone,
                         &task_run_id_clone,
+                        &assignment_id_clone,
                         agent_cancel_token.as_ref(),
                         ws_id_clone.as_deref(),
+                        working_dir.as_deref(),
                         &all_agents_clone,
                     ).await;
+                    if output_path.is_some() {
+                        output_stream::end(&state_clone, &assignment_id_clone).await;
+                    }
+                    assignment_caps::end(&state_clone, &task_run_id_clone, &agent_id_clone).await;
+                    assignment_overrides::end(&state_clone, &task_run_id_clone, &agent_id_clone).await;
+                    prompt_content::end(&state_clone, &task_run_id_clone, &agent_id_clone).await;
+                    running_assignments::end(&state_clone, &task_run_id_clone, &agent_id_clone).await;
 
                     let duration_ms = assign_start.elapsed().as_millis() as i64;
 
                     match result {
-                        Ok(prompt_result) => {
+                        Ok((mut prompt_result, cached)) => {
+                            let status = if prompt_result.capped { assignment_caps::CAPPED_STATUS } else { "completed" };
+                            prompt_result.text = inline_artifacts::collect(
+                                &app_clone, &state_clone, &task_run_id_clone, &assignment_id_clone, &agent_id_clone,
+                                working_dir.as_deref(), std::time::SystemTime::now() - assign_start.elapsed(), &prompt_result.text,
+                            ).await;
                             {
                                 let state_clone2 = state_clone.clone();
                                 let aid = assignment_id_clone.clone();
@@ -3076,49 +5745,62 @@ async fn resume_orchestration_running(
                                 let to = prompt_result.tokens_out;
                                 let cct = prompt_result.cache_creation_tokens;
                                 let crt = prompt_result.cache_read_tokens;
-                                let _ = tokio::task::spawn_blocking(move || {
+                                let _ = telemetry::spawn_blocking(move || {
                                     task_run_repo::update_task_assignment(
-                                        &state_clone2, &aid, "completed", Some(&out), Some(&model),
+                                        &state_clone2, &aid, status, Some(&out), Some(&model),
                                         ti, to, cct, crt, duration_ms, None,
                                     )
                                 }).await;
                             }
 
-                            let _ = app_clone.emit("orchestration:agent_completed", &serde_json::json!({
+                            events::emit(&app_clone, &events::AGENT_COMPLETED, serde_json::json!({
                                 "taskRunId": task_run_id_clone,
                                 "assignmentId": assignment_id_clone,
                                 "agentId": agent_id_clone,
                                 "agentName": agent_name_clone,
                                 "durationMs": duration_ms,
-                                "status": "completed",
+                                "status": status,
                                 "tokensIn": prompt_result.tokens_in,
                                 "tokensOut": prompt_result.tokens_out,
                                 "cacheCreationTokens": prompt_result.cache_creation_tokens,
                                 "cacheReadTokens": prompt_result.cache_read_tokens,
                                 "acpSessionId": prompt_result.acp_session_id,
                                 "output": prompt_result.text.clone(),
+                                "cached": cached,
                             }));
 
+                            // Later prompts get a reference to the file instead of the full text
+                            if let Some(path) = output_path.as_deref() {
+                                match output_stream::finish(
+                                    &state_clone, &task_run_id_clone, &assignment_id_clone, &agent_id_clone, path, &prompt_result.text,
+                                ).await {
+                                    Ok(reference) => prompt_result.text = reference,
+                                    Err(e) => log::warn!("Failed to finish output file {}: {}", path.display(), e),
+                                }
+                            }
+
                             (agent_id_clone, Ok(prompt_result))
                         }
                         Err(e) => {
                             let err_msg = e.to_string();
-                            let is_cancelled = err_msg.contains("Agent cancelled");
+                            let is_cancelled = matches!(e, AppError::AgentCancelled { .. });
                             let status = if is_cancelled { "cancelled" } else { "failed" };
 
                             if !is_cancelled {
                                 let state_for_disable = state_clone.clone();
                                 let agent_id_for_disable = agent_id_clone.clone();
                                 let err_for_disable = err_msg.clone();
-                                let _ = tokio::task::spawn_blocking(move || {
+                                let transient = e.is_transient();
+                                let _ = telemetry::spawn_blocking(move || {
                                     agent_repo::disable_agent(
                                         &state_for_disable,
                                         &agent_id_for_disable,
                                         &err_for_disable,
+                                        transient,
                                     )
                                 }).await;
 
-                                let _ = app_clone.emit("orchestration:agent_auto_disabled", &serde_json::json!({
+                                events::emit(&app_clone, &events::AGENT_AUTO_DISABLED, serde_json::json!({
                                     "taskRunId": task_run_id_clone,
                                     "agentId": agent_id_clone,
                                     "agentName": agent_name_clone,
@@ -3131,7 +5813,7 @@ async fn resume_orchestration_running(
                                 let aid = assignment_id_clone.clone();
                                 let em = err_msg.clone();
                                 let s = status.to_string();
-                                let _ = tokio::task::spawn_blocking(move || {
+                                let _ = telemetry::spawn_blocking(move || {
                                     task_run_repo::update_task_assignment(
                                         &state_clone2, &aid, &s, None, None,
                                         0, 0, 0, 0, duration_ms, Some(&em),
@@ -3139,7 +5821,7 @@ async fn resume_orchestration_running(
                                 }).await;
                             }
 
-                            let _ = app_clone.emit("orchestration:agent_completed", &serde_json::json!({
+                            events::emit(&app_clone, &events::AGENT_COMPLETED, serde_json::json!({
                                 "taskRunId": task_run_id_clone,
                                 "assignmentId": assignment_id_clone,
                                 "agentId": agent_id_clone,
@@ -3147,12 +5829,13 @@ async fn resume_orchestration_running(
                                 "durationMs": duration_ms,
                                 "status": status,
                                 "error": &err_msg,
+                                "errorCode": e.code(),
                             }));
 
                             (agent_id_clone, Err(err_msg))
                         }
                     }
-                });
+                }.in_current_span());
             }
 
             // Collect results
@@ -3178,21 +5861,70 @@ async fn resume_orchestration_running(
         }
 
         // Feedback to hub after each sequence group
-        if !agent_outputs.is_empty() {
-            ensure_agent_running(app, state, &hub_agent, &hub_process_key).await?;
-            let feedback = build_feedback_prompt(&agent_outputs, &all_agents);
-            let _ = app.emit("orchestration:feedback", &serde_json::json!({
-                "taskRunId": task_run_id,
-                "message": "Control Hub reviewing results...",
-            }));
-            if let Ok(response) = send_prompt_to_agent(app, state, &hub_agent.id, &feedback, Some(task_run_id), None, workspace_id, &hub_process_key).await {
-                log::info!("Control Hub feedback (resume): {}", response.text);
-            }
+        if let Some(hub) = hub_agent.as_ref().filter(|_| !agent_outputs.is_empty()) {
+            ensure_agent_running(app, state, hub, &hub_process_key).await?;
+            let review = FeedbackReview {
+                app, state, task_run_id, workspace_id, hub_agent: hub, hub_process_key: &hub_process_key, all_agents: &all_agents,
+            };
+            let usage = review_with_hub_feedback(&review, *order, &mut agent_outputs, &mut feedback_corrections).await;
+            total_tokens_in += usage.tokens_in;
+            total_tokens_out += usage.tokens_out;
+            total_cache_creation_tokens += usage.cache_creation_tokens;
+            total_cache_read_tokens += usage.cache_read_tokens;
         }
     }
 
     // 8. Enter confirmation flow (same as normal orchestration)
-    run_confirmation_and_summary(app, state, task_run_id, user_prompt, workspace_id, &hub_agent, &hub_process_key, &plan, &all_agents, &mut agent_outputs, &mut total_tokens_in, &mut total_tokens_out, &mut total_cache_creation_tokens, &mut total_cache_read_tokens, start_time).await
+    run_confirmation_and_summary(app, state, task_run_id, user_prompt, workspace_id, hub_agent.as_ref(), &hub_process_key, &plan, &all_agents, &mut agent_outputs, &mut total_tokens_in, &mut total_tokens_out, &mut total_cache_creation_tokens, &mut total_cache_read_tokens, start_time).await
+}
+
+/// The hub serving a run being resumed; `None` when the built-in planner serves it.
+/// Resume a run whose plan was waiting for approval: ask again, then run it.
+/// Take the assignment overrides of the stored plan, where they are edited
+/// while the plan awaits approval.
+async fn reload_plan_overrides(state: &AppState, task_run_id: &str, plan: &mut TaskPlan) {
+    let state_clone = state.clone();
+    let id = task_run_id.to_string();
+    let stored = telemetry::spawn_blocking(move || task_run_repo::get_task_run(&state_clone, &id)).await;
+    let stored: Option<TaskPlan> = match stored {
+        Ok(Ok(task_run)) => task_run.task_plan_json.as_deref().and_then(|json| serde_json::from_str(json).ok()),
+        Ok(Err(e)) => {
+            log::warn!("Failed to reload the plan of task {}: {}", task_run_id, e);
+            None
+        }
+        Err(e) => {
+            log::warn!("Spawn blocking failed: {}", e);
+            None
+        }
+    };
+    let Some(stored) = stored.filter(|s| s.assignments.len() == plan.assignments.len()) else {
+        return;
+    };
+    for (assignment, stored) in plan.assignments.iter_mut().zip(stored.assignments) {
+        assignment.overrides = stored.overrides;
+    }
+}
+
+async fn resume_from_plan_approval(app: &tauri::AppHandle, state: &AppState, task_run: &TaskRun) -> AppResult<()> {
+    let plan: Option<TaskPlan> = task_run.task_plan_json.as_deref().and_then(|json| serde_json::from_str(json).ok());
+    let confidence = plan.as_ref().and_then(TaskPlan::confidence);
+    let threshold = config::current(state).plan_approval_confidence;
+    if !wait_for_plan_approval(app, state, &task_run.id, confidence, threshold).await? {
+        return Ok(());
+    }
+    resume_orchestration_running(app, state, task_run).await
+}
+
+async fn load_serving_hub(state: &AppState, task_run: &TaskRun) -> AppResult<Option<AgentConfig>> {
+    let hub_id = task_run.serving_hub_id().to_string();
+    if fallback_planner::is_builtin(&hub_id) {
+        return Ok(None);
+    }
+    let state_clone = state.clone();
+    telemetry::spawn_blocking(move || agent_repo::get_agent(&state_clone, &hub_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .map(Some)
 }
 
 /// Resume an orchestration task that was previously in `awaiting_confirmation` state.
@@ -3216,19 +5948,13 @@ async fn resume_from_confirmation(
     })?;
 
     // 2. Validate hub agent
-    let hub_agent: AgentConfig = {
-        let state_clone = state.clone();
-        let hub_id = task_run.control_hub_agent_id.clone();
-        tokio::task::spawn_blocking(move || agent_repo::get_agent(&state_clone, &hub_id))
-            .await
-            .map_err(|e| AppError::Internal(e.to_string()))??
-    };
+    let hub_agent = load_serving_hub(state, task_run).await?;
 
     // 3. Load all agents
     let all_agents: Vec<AgentConfig> = {
         let state_clone = state.clone();
         let ws_id: Option<String> = workspace_id.map(|s| s.to_string());
-        tokio::task::spawn_blocking(move || agent_repo::list_agents(&state_clone, ws_id.as_deref()))
+        telemetry::spawn_blocking(move || agent_repo::list_agents(&state_clone, ws_id.as_deref()))
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??
     };
@@ -3237,7 +5963,7 @@ async fn resume_from_confirmation(
     let db_assignments = {
         let state_clone = state.clone();
         let trid = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || task_run_repo::list_assignments_for_run(&state_clone, &trid))
+        telemetry::spawn_blocking(move || task_run_repo::list_assignments_for_run(&state_clone, &trid))
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??
     };
@@ -3249,7 +5975,7 @@ async fn resume_from_confirmation(
     let mut total_cache_read_tokens: i64 = 0;
 
     for assignment in &db_assignments {
-        if assignment.status == "completed" {
+        if matches!(assignment.status.as_str(), "completed" | assignment_caps::CAPPED_STATUS) {
             if let Some(ref output) = assignment.output_text {
                 agent_outputs.insert(assignment.agent_id.clone(), output.clone());
             }
@@ -3266,10 +5992,10 @@ async fn resume_from_confirmation(
         agent_outputs.len(),
     );
 
-    let hub_process_key = orch_process_key(task_run_id, &hub_agent.id);
+    let hub_process_key = hub_agent.as_ref().map(|h| orch_process_key(task_run_id, &h.id)).unwrap_or_default();
 
     // 5. Emit awaiting_confirmation and enter confirmation loop
-    run_confirmation_and_summary(app, state, task_run_id, user_prompt, workspace_id, &hub_agent, &hub_process_key, &plan, &all_agents, &mut agent_outputs, &mut total_tokens_in, &mut total_tokens_out, &mut total_cache_creation_tokens, &mut total_cache_read_tokens, start_time).await
+    run_confirmation_and_summary(app, state, task_run_id, user_prompt, workspace_id, hub_agent.as_ref(), &hub_process_key, &plan, &all_agents, &mut agent_outputs, &mut total_tokens_in, &mut total_tokens_out, &mut total_cache_creation_tokens, &mut total_cache_read_tokens, start_time).await
 }
 
 /// Shared confirmation + summary logic used by both normal orchestration and resume paths.
@@ -3280,7 +6006,7 @@ async fn run_confirmation_and_summary(
     task_run_id: &str,
     user_prompt: &str,
     workspace_id: Option<&str>,
-    hub_agent: &AgentConfig,
+    hub_agent: Option<&AgentConfig>,
     hub_process_key: &str,
     plan: &TaskPlan,
     all_agents: &[AgentConfig],
@@ -3292,7 +6018,7 @@ async fn run_confirmation_and_summary(
     start_time: std::time::Instant,
 ) -> AppResult<()> {
     // Emit awaiting_confirmation
-    let _ = app.emit("orchestration:awaiting_confirmation", &serde_json::json!({
+    events::emit(app, &events::AWAITING_CONFIRMATION, serde_json::json!({
         "taskRunId": task_run_id,
         "agentOutputs": &agent_outputs.iter().map(|(id, out)| {
             let name = all_agents.iter().find(|a| a.id == *id)
@@ -3305,7 +6031,7 @@ async fn run_confirmation_and_summary(
     {
         let state_clone = state.clone();
         let id = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_status(&state_clone, &id, "awaiting_confirmation")
         })
         .await
@@ -3318,25 +6044,17 @@ async fn run_confirmation_and_summary(
             return Ok(());
         }
 
-        let (tx, rx) = tokio::sync::oneshot::channel::<ConfirmationAction>();
-        {
-            let mut confirmations = state.pending_confirmations.lock().await;
-            confirmations.insert(task_run_id.to_string(), tx);
-        }
-
-        let action = match tokio::time::timeout(
-            std::time::Duration::from_secs(3600),
-            rx,
-        ).await {
-            Ok(Ok(action)) => action,
-            Ok(Err(_)) => ConfirmationAction::Confirm,
-            Err(_) => ConfirmationAction::Confirm,
+        let Some(action) = wait_for_confirmation(app, state, task_run_id).await? else {
+            return Ok(());
         };
 
         match action {
             ConfirmationAction::Confirm => {
                 break;
             }
+            ConfirmationAction::Dismiss => {
+                return dismiss_for_review(app, state, task_run_id).await;
+            }
             ConfirmationAction::RegenerateAgent(agent_id) => {
                 log::info!("Regenerating agent {} for task {}", agent_id, task_run_id);
 
@@ -3358,7 +6076,7 @@ async fn run_confirmation_and_summary(
                                 .find(|a| a.id == *dep_id)
                                 .map(|a| a.name.clone())
                                 .unwrap_or_else(|| "Previous agent".into());
-                            parts.push(format!("\n--- Output from {dep_name} ---\n{output}"));
+                            parts.push(dependency_outputs::input_part(&dep_name, output, dependency_outputs::sections(planned, dep_id)));
                         }
                     }
                     parts.join("\n")
@@ -3367,7 +6085,7 @@ async fn run_confirmation_and_summary(
                 };
 
                 let regen_assignment_id = uuid::Uuid::new_v4().to_string();
-                let _ = app.emit("orchestration:agent_started", &serde_json::json!({
+                events::emit(app, &events::AGENT_STARTED, serde_json::json!({
                     "taskRunId": task_run_id,
                     "assignmentId": regen_assignment_id,
                     "agentId": agent_id,
@@ -3377,10 +6095,20 @@ async fn run_confirmation_and_summary(
                     "isRegeneration": true,
                 }));
 
+                let working_dir = resolve_assignment_working_directory(
+                    state, workspace_id, planned.and_then(|p| p.working_directory.as_deref()),
+                );
+                let overrides = planned.map(assignment_overrides::checked).unwrap_or_default();
+                assignment_overrides::begin(state, task_run_id, &agent_id, overrides).await;
+                prompt_content::begin(state, task_run_id, &agent_id, planned.map_or(&[][..], |p| &p.attachments)).await;
+                running_assignments::begin(state, task_run_id, &agent_id, &regen_assignment_id).await;
                 let assign_start = std::time::Instant::now();
                 let result = execute_agent_assignment_with_self_healing(
-                    app, state, &agent_config, &input_text, task_run_id, None, workspace_id,
+                    app, state, &agent_config, &input_text, task_run_id, None, workspace_id, working_dir.as_deref(),
                 ).await;
+                assignment_overrides::end(state, task_run_id, &agent_id).await;
+                prompt_content::end(state, task_run_id, &agent_id).await;
+                running_assignments::end(state, task_run_id, &agent_id).await;
                 let duration_ms = assign_start.elapsed().as_millis() as i64;
 
                 match result {
@@ -3390,7 +6118,7 @@ async fn run_confirmation_and_summary(
                         *total_cache_creation_tokens += prompt_result.cache_creation_tokens;
                         *total_cache_read_tokens += prompt_result.cache_read_tokens;
 
-                        let _ = app.emit("orchestration:agent_completed", &serde_json::json!({
+                        events::emit(app, &events::AGENT_COMPLETED, serde_json::json!({
                             "taskRunId": task_run_id,
                             "assignmentId": regen_assignment_id,
                             "agentId": agent_id,
@@ -3414,15 +6142,17 @@ async fn run_confirmation_and_summary(
                             let state_for_disable = state.clone();
                             let agent_id_for_disable = agent_id.clone();
                             let err_for_disable = err_msg.clone();
-                            let _ = tokio::task::spawn_blocking(move || {
+                            let transient = e.is_transient();
+                            let _ = telemetry::spawn_blocking(move || {
                                 agent_repo::disable_agent(
                                     &state_for_disable,
                                     &agent_id_for_disable,
                                     &err_for_disable,
+                                    transient,
                                 )
                             }).await;
 
-                            let _ = app.emit("orchestration:agent_auto_disabled", &serde_json::json!({
+                            events::emit(app, &events::AGENT_AUTO_DISABLED, serde_json::json!({
                                 "taskRunId": task_run_id,
                                 "agentId": agent_id,
                                 "agentName": agent_name,
@@ -3430,7 +6160,7 @@ async fn run_confirmation_and_summary(
                             }));
                         }
 
-                        let _ = app.emit("orchestration:agent_completed", &serde_json::json!({
+                        events::emit(app, &events::AGENT_COMPLETED, serde_json::json!({
                             "taskRunId": task_run_id,
                             "assignmentId": regen_assignment_id,
                             "agentId": agent_id,
@@ -3444,7 +6174,7 @@ async fn run_confirmation_and_summary(
                 }
 
                 // Re-emit awaiting_confirmation
-                let _ = app.emit("orchestration:awaiting_confirmation", &serde_json::json!({
+                events::emit(app, &events::AWAITING_CONFIRMATION, serde_json::json!({
                     "taskRunId": task_run_id,
                     "agentOutputs": &agent_outputs.iter().map(|(id, out)| {
                         let name = all_agents.iter().find(|a| a.id == *id)
@@ -3489,13 +6219,13 @@ async fn run_confirmation_and_summary(
                                     .find(|a| a.id == *dep_id)
                                     .map(|a| a.name.clone())
                                     .unwrap_or_else(|| "Previous agent".into());
-                                input_parts.push(format!("\n--- Output from {dep_name} ---\n{output}"));
+                                input_parts.push(dependency_outputs::input_part(&dep_name, output, dependency_outputs::sections(planned, dep_id)));
                             }
                         }
                         let input_text = input_parts.join("\n");
 
                         let regen_assignment_id = uuid::Uuid::new_v4().to_string();
-                        let _ = app.emit("orchestration:agent_started", &serde_json::json!({
+                        events::emit(app, &events::AGENT_STARTED, serde_json::json!({
                             "taskRunId": task_run_id,
                             "assignmentId": regen_assignment_id,
                             "agentId": planned.agent_id,
@@ -3505,10 +6235,19 @@ async fn run_confirmation_and_summary(
                             "isRegeneration": true,
                         }));
 
+                        let working_dir = resolve_assignment_working_directory(
+                            state, workspace_id, planned.working_directory.as_deref(),
+                        );
+                        assignment_overrides::begin(state, task_run_id, &planned.agent_id, assignment_overrides::checked(planned)).await;
+                        prompt_content::begin(state, task_run_id, &planned.agent_id, &planned.attachments).await;
+                        running_assignments::begin(state, task_run_id, &planned.agent_id, &regen_assignment_id).await;
                         let assign_start = std::time::Instant::now();
                         let result = execute_agent_assignment_with_self_healing(
-                            app, state, &agent_config, &input_text, task_run_id, None, workspace_id,
+                            app, state, &agent_config, &input_text, task_run_id, None, workspace_id, working_dir.as_deref(),
                         ).await;
+                        assignment_overrides::end(state, task_run_id, &planned.agent_id).await;
+                        prompt_content::end(state, task_run_id, &planned.agent_id).await;
+                        running_assignments::end(state, task_run_id, &planned.agent_id).await;
                         let duration_ms = assign_start.elapsed().as_millis() as i64;
 
                         match result {
@@ -3518,7 +6257,7 @@ async fn run_confirmation_and_summary(
                                 *total_cache_creation_tokens += prompt_result.cache_creation_tokens;
                                 *total_cache_read_tokens += prompt_result.cache_read_tokens;
 
-                                let _ = app.emit("orchestration:agent_completed", &serde_json::json!({
+                                events::emit(app, &events::AGENT_COMPLETED, serde_json::json!({
                                     "taskRunId": task_run_id,
                                     "assignmentId": regen_assignment_id,
                                     "agentId": planned.agent_id,
@@ -3542,15 +6281,17 @@ async fn run_confirmation_and_summary(
                                     let state_for_disable = state.clone();
                                     let agent_id_for_disable = planned.agent_id.clone();
                                     let err_for_disable = err_msg.clone();
-                                    let _ = tokio::task::spawn_blocking(move || {
+                                    let transient = e.is_transient();
+                                    let _ = telemetry::spawn_blocking(move || {
                                         agent_repo::disable_agent(
                                             &state_for_disable,
                                             &agent_id_for_disable,
                                             &err_for_disable,
+                                            transient,
                                         )
                                     }).await;
 
-                                    let _ = app.emit("orchestration:agent_auto_disabled", &serde_json::json!({
+                                    events::emit(app, &events::AGENT_AUTO_DISABLED, serde_json::json!({
                                         "taskRunId": task_run_id,
                                         "agentId": planned.agent_id,
                                         "agentName": agent_name,
@@ -3558,7 +6299,7 @@ async fn run_confirmation_and_summary(
                                     }));
                                 }
 
-                                let _ = app.emit("orchestration:agent_completed", &serde_json::json!({
+                                events::emit(app, &events::AGENT_COMPLETED, serde_json::json!({
                                     "taskRunId": task_run_id,
                                     "assignmentId": regen_assignment_id,
                                     "agentId": planned.agent_id,
@@ -3574,7 +6315,7 @@ async fn run_confirmation_and_summary(
                 }
 
                 // Re-emit awaiting_confirmation
-                let _ = app.emit("orchestration:awaiting_confirmation", &serde_json::json!({
+                events::emit(app, &events::AWAITING_CONFIRMATION, serde_json::json!({
                     "taskRunId": task_run_id,
                     "agentOutputs": &agent_outputs.iter().map(|(id, out)| {
                         let name = all_agents.iter().find(|a| a.id == *id)
@@ -3597,10 +6338,10 @@ async fn run_confirmation_and_summary(
     }
 
     // Generate summary
-    ensure_agent_running(app, state, hub_agent, hub_process_key).await?;
 
     let summary_prompt = format!(
-        "Summarize the results of the orchestration.\n\nOriginal request: {}\n\nAgent outputs:\n{}",
+        "{}Summarize the results of the orchestration.\n\nOriginal request: {}\n\nAgent outputs:\n{}",
+        hub_persona(state, workspace_id).await,
         user_prompt,
         agent_outputs
             .iter()
@@ -3615,10 +6356,11 @@ async fn run_confirmation_and_summary(
             .collect::<String>()
     );
 
-    let summary = send_prompt_to_agent(app, state, &hub_agent.id, &summary_prompt, Some(task_run_id), None, workspace_id, hub_process_key)
-        .await
-        .map(|r| r.text)
-        .unwrap_or_else(|_| "Summary not available".into());
+    let hub_summary = match hub_agent {
+        Some(hub) => summarize_with_failover(app, state, task_run_id, workspace_id, hub, hub_process_key, &summary_prompt).await,
+        None => None,
+    };
+    let summary = hub_summary.unwrap_or_else(|| fallback_planner::summarize(agent_outputs, all_agents, i18n::current(state)));
 
     let total_duration_ms = start_time.elapsed().as_millis() as i64;
 
@@ -3627,7 +6369,7 @@ async fn run_confirmation_and_summary(
         let state_clone = state.clone();
         let id = task_run_id.to_string();
         let sum = summary.clone();
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_summary(&state_clone, &id, &sum)
         })
         .await
@@ -3640,7 +6382,7 @@ async fn run_confirmation_and_summary(
         let to = *total_tokens_out;
         let cc = *total_cache_creation_tokens;
         let cr = *total_cache_read_tokens;
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_totals(&state_clone, &id, ti, to, cc, cr, total_duration_ms)
         })
         .await
@@ -3649,7 +6391,7 @@ async fn run_confirmation_and_summary(
     {
         let state_clone = state.clone();
         let id = task_run_id.to_string();
-        tokio::task::spawn_blocking(move || {
+        telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_status(&state_clone, &id, "completed")
         })
         .await
@@ -3657,8 +6399,9 @@ async fn run_confirmation_and_summary(
     }
 
     write_output_summary(state, task_run_id, user_prompt, plan, all_agents, &summary, total_duration_ms).await;
+    remember_task_run(state, task_run_id).await;
 
-    let _ = app.emit("orchestration:completed", &serde_json::json!({
+    events::emit(app, &events::RUN_COMPLETED, serde_json::json!({
         "taskRunId": task_run_id,
         "summary": summary,
         "totalDurationMs": total_duration_ms,
@@ -3671,84 +6414,102 @@ async fn run_confirmation_and_summary(
     Ok(())
 }
 
-/// Resume all incomplete orchestration tasks found in the database.
-/// Called once during app startup.
+/// Resume a run a previous session left unfinished, in the background.
+/// Runs whose control hub no longer exists are marked as failed instead.
 ///
-/// Each task is spawned independently since every task run uses its own agent
+/// Each run is spawned independently since every task run uses its own agent
 /// processes (keyed by `orch:{task_run_id}:{agent_id}`), so there is no
 /// resource contention even within the same workspace.
-pub async fn resume_incomplete_tasks(app: tauri::AppHandle, state: AppState) {
-    let incomplete_tasks = {
+pub async fn resume_task_run(app: &tauri::AppHandle, state: &AppState, task_run: TaskRun) {
+    let task_run_id = task_run.id.clone();
+    let status = task_run.status.clone();
+
+    // Validate that the control hub agent still exists
+    let hub_exists = fallback_planner::is_builtin(task_run.serving_hub_id()) || {
         let state_clone = state.clone();
-        match tokio::task::spawn_blocking(move || task_run_repo::list_incomplete_task_runs(&state_clone)).await {
-            Ok(Ok(tasks)) => tasks,
-            Ok(Err(e)) => {
-                log::error!("Failed to query incomplete task runs on startup: {}", e);
-                return;
-            }
-            Err(e) => {
-                log::error!("Spawn blocking failed for incomplete task query: {}", e);
-                return;
-            }
-        }
+        let hub_id = task_run.serving_hub_id().to_string();
+        matches!(
+            telemetry::spawn_blocking(move || agent_repo::get_agent(&state_clone, &hub_id)).await,
+            Ok(Ok(_))
+        )
     };
 
-    if incomplete_tasks.is_empty() {
-        log::info!("No incomplete orchestration tasks to resume on startup");
+    if !hub_exists {
+        log::warn!(
+            "Control hub agent '{}' no longer exists for task {} — marking as failed",
+            task_run.serving_hub_id(), task_run_id
+        );
+        let state_clone = state.clone();
+        let id = task_run_id.clone();
+        let _ = telemetry::spawn_blocking(move || {
+            task_run_repo::update_task_run_status(&state_clone, &id, "failed")
+        }).await;
         return;
     }
 
-    log::info!(
-        "Found {} incomplete orchestration task(s) to resume on startup",
-        incomplete_tasks.len()
-    );
+    // Create a cancellation token and register it
+    let cancel_token = CancellationToken::new();
+    {
+        let mut tokens = state.active_task_runs.lock().await;
+        tokens.insert(task_run_id.clone(), cancel_token);
+    }
 
-    for task_run in incomplete_tasks {
-        let task_run_id = task_run.id.clone();
-        let status = task_run.status.clone();
+    // Emit resuming event to frontend
+    events::emit(app, &events::RUN_RESUMING, serde_json::json!({
+        "taskRunId": task_run_id,
+        "status": status,
+    }));
 
-        // Validate that the control hub agent still exists
-        let hub_exists = {
-            let state_clone = state.clone();
-            let hub_id = task_run.control_hub_agent_id.clone();
-            matches!(
-                tokio::task::spawn_blocking(move || agent_repo::get_agent(&state_clone, &hub_id)).await,
-                Ok(Ok(_))
-            )
-        };
+    let app_clone = app.clone();
+    let state_clone = state.clone();
+    tauri::async_runtime::spawn(async move {
+        resume_orchestration(app_clone, state_clone, task_run).await;
+    });
+}
 
-        if !hub_exists {
-            log::warn!(
-                "Control hub agent '{}' no longer exists for task {} — marking as failed",
-                task_run.control_hub_agent_id, task_run_id
-            );
-            let state_clone = state.clone();
-            let id = task_run_id.clone();
-            let _ = tokio::task::spawn_blocking(move || {
-                task_run_repo::update_task_run_status(&state_clone, &id, "failed")
-            }).await;
-            continue;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hub_feedback_with_actions() {
+        let response = r#"Reviewed.
+```json
+{"assessment": "The tests are missing", "actions": [
+  {"action": "rerun_agent", "agent_id": "a1", "instructions": "Add tests"},
+  {"action": "add_assignment", "agent_id": "a2", "task_description": "Review the tests", "depends_on": ["a1"]},
+]}
+```"#;
+        let feedback = parse_hub_feedback(response);
+        assert_eq!(feedback.assessment, "The tests are missing");
+        assert_eq!(feedback.actions.len(), 2);
+        assert!(matches!(
+            &feedback.actions[0],
+            FeedbackAction::RerunAgent { agent_id, instructions } if agent_id == "a1" && instructions == "Add tests"
+        ));
+        assert!(matches!(
+            &feedback.actions[1],
+            FeedbackAction::AddAssignment { agent_id, depends_on, working_directory: None, .. }
+                if agent_id == "a2" && depends_on == &["a1".to_string()]
+        ));
+    }
 
-        // Create a cancellation token and register it
-        let cancel_token = CancellationToken::new();
-        {
-            let mut tokens = state.active_task_runs.lock().await;
-            tokens.insert(task_run_id.clone(), cancel_token);
-        }
+    #[test]
+    fn test_parse_hub_feedback_without_actions() {
+        let feedback = parse_hub_feedback(r#"{"assessment": "Looks good"}"#);
+        assert_eq!(feedback.assessment, "Looks good");
+        assert!(feedback.actions.is_empty());
+    }
 
-        // Emit resuming event to frontend
-        let _ = app.emit("orchestration:resuming", &serde_json::json!({
-            "taskRunId": task_run_id,
-            "status": status,
-        }));
+    #[test]
+    fn test_parse_hub_feedback_plain_text() {
+        let feedback = parse_hub_feedback("  All results are satisfactory.\n");
+        assert_eq!(feedback.assessment, "All results are satisfactory.");
+        assert!(feedback.actions.is_empty());
 
-        // Spawn each task independently — no contention since each task run
-        // uses its own agent processes via orch:{task_run_id}:{agent_id} keys
-        let app_clone = app.clone();
-        let state_clone = state.clone();
-        tauri::async_runtime::spawn(async move {
-            resume_orchestration(app_clone, state_clone, task_run).await;
-        });
+        // An unknown action makes the whole response a plain assessment
+        let feedback = parse_hub_feedback(r#"{"assessment": "x", "actions": [{"action": "delete_everything"}]}"#);
+        assert!(feedback.actions.is_empty());
+        assert!(feedback.assessment.contains("delete_everything"));
     }
 }
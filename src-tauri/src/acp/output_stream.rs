@@ -0,0 +1,160 @@
+//! Streaming of assignment text into files under the run's output directory.
+//!
+//! An assignment with an `output_file` has its agent's text chunks appended
+//! to `output/<task_run_id>/<output_file>` as they arrive. When it completes,
+//! the file is registered as a task artifact and later prompts get a short
+//! reference to it instead of the full text.
+
+use std::path::{Component, Path, PathBuf};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::db::artifact_repo;
+use crate::db::migrations::get_output_dir;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+pub const OUTPUT_FILE_ARTIFACT: &str = "output_file";
+
+/// Characters of the output kept in the reference given to later prompts.
+const REFERENCE_PREVIEW_CHARS: usize = 500;
+
+/// Resolve an assignment's `output_file` inside the run's output directory.
+/// Absolute paths and `..` components are rejected.
+pub fn resolve_path(task_run_id: &str, output_file: &str) -> AppResult<PathBuf> {
+    let relative = Path::new(output_file.trim());
+    let is_plain = relative.components().all(|c| matches!(c, Component::Normal(_)));
+    if output_file.trim().is_empty() || !is_plain {
+        return Err(AppError::InvalidRequest(format!(
+            "Output file '{}' must be a relative path inside the run's output directory",
+            output_file
+        )));
+    }
+    Ok(get_output_dir().join(task_run_id).join(relative))
+}
+
+/// Start routing an assignment's text into `path`, replacing any earlier content.
+pub async fn begin(state: &AppState, assignment_id: &str, path: &Path) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, b"").await?;
+    let mut streams = state.output_streams.lock().await;
+    streams.insert(assignment_id.to_string(), path.to_path_buf());
+    Ok(())
+}
+
+/// `begin` for an assignment's optional `output_file`. Problems are logged
+/// and the assignment then runs without a file.
+pub async fn prepare(
+    state: &AppState,
+    task_run_id: &str,
+    assignment_id: &str,
+    output_file: Option<&str>,
+) -> Option<PathBuf> {
+    let path = match resolve_path(task_run_id, output_file?) {
+        Ok(path) => path,
+        Err(e) => {
+            log::warn!("[OutputStream] Ignoring output file for assignment {}: {}", assignment_id, e);
+            return None;
+        }
+    };
+    match begin(state, assignment_id, &path).await {
+        Ok(()) => Some(path),
+        Err(e) => {
+            log::warn!("[OutputStream] Failed to create {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+pub async fn end(state: &AppState, assignment_id: &str) {
+    let mut streams = state.output_streams.lock().await;
+    streams.remove(assignment_id);
+}
+
+/// Appends streamed chunks to an assignment's output file.
+pub struct OutputWriter {
+    file: tokio::fs::File,
+    path: PathBuf,
+}
+
+impl OutputWriter {
+    /// Writer for the assignment, if it streams to a file.
+    pub async fn open(state: &AppState, assignment_id: &str) -> Option<Self> {
+        let path = {
+            let streams = state.output_streams.lock().await;
+            streams.get(assignment_id).cloned()?
+        };
+        match tokio::fs::OpenOptions::new().append(true).create(true).open(&path).await {
+            Ok(file) => Some(Self { file, path }),
+            Err(e) => {
+                log::warn!("[OutputStream] Failed to open {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub async fn write(&mut self, text: &str) {
+        if let Err(e) = self.file.write_all(text.as_bytes()).await {
+            log::warn!("[OutputStream] Failed to write {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Write the final text to the file, register it as an artifact and return
+/// the reference that replaces the text in later prompts.
+pub async fn finish(
+    state: &AppState,
+    task_run_id: &str,
+    assignment_id: &str,
+    agent_id: &str,
+    path: &Path,
+    text: &str,
+) -> AppResult<String> {
+    // The streamed chunks already hold the text; writing it again also covers
+    // outputs that were never streamed, such as cached responses.
+    tokio::fs::write(path, text).await?;
+    let size_bytes = text.len() as i64;
+
+    let state_clone = state.clone();
+    let trid = task_run_id.to_string();
+    let aid = assignment_id.to_string();
+    let agid = agent_id.to_string();
+    let path_str = path.to_string_lossy().to_string();
+    tokio::task::spawn_blocking(move || {
+        artifact_repo::create_artifact(
+            &state_clone, &trid, Some(&aid), Some(&agid), OUTPUT_FILE_ARTIFACT, &path_str, size_bytes, None,
+        )
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    let preview: String = text.chars().take(REFERENCE_PREVIEW_CHARS).collect();
+    let ellipsis = if text.chars().count() > REFERENCE_PREVIEW_CHARS { "…" } else { "" };
+    Ok(format!(
+        "[Full output written to {} ({} bytes). Read that file for the complete content.]\n\nPreview:\n{}{}",
+        path.display(),
+        size_bytes,
+        preview,
+        ellipsis
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_relative_paths_inside_the_run_directory() {
+        let path = resolve_path("run-1", " reports/summary.md ").unwrap();
+        assert_eq!(path, get_output_dir().join("run-1").join("reports/summary.md"));
+    }
+
+    #[test]
+    fn rejects_paths_that_leave_the_run_directory() {
+        for output_file in ["", "  ", "../escape.md", "reports/../../escape.md", "./summary.md", "/etc/passwd"] {
+            assert!(resolve_path("run-1", output_file).is_err(), "{:?} should be rejected", output_file);
+        }
+    }
+}
@@ -1,9 +1,12 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tauri::{AppHandle, Emitter};
 
 use crate::acp::transport;
 use crate::error::AppResult;
+use crate::state::OrchPermissionKey;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermissionRequest {
@@ -40,3 +43,308 @@ pub fn build_permission_response(
         error: None,
     }
 }
+
+/// A run's agent waiting for the answer to one of its permission requests.
+pub struct PermissionWaiter {
+    /// Options of the agent's own request
+    pub options: serde_json::Value,
+    pub tx: tokio::sync::oneshot::Sender<String>,
+}
+
+/// Similar requests of a run answered with one prompt.
+pub struct PendingPermission {
+    /// Agent whose request opened the prompt
+    pub agent_id: String,
+    pub request_key: String,
+    pub waiters: Vec<PermissionWaiter>,
+}
+
+/// Permission requests of task runs waiting for the user, and the decisions
+/// the user asked to remember for the rest of a run.
+#[derive(Default)]
+pub struct OrchPermissions {
+    pending: HashMap<OrchPermissionKey, PendingPermission>,
+    /// (task_run_id, request_key) -> allowed
+    remembered: HashMap<(String, String), bool>,
+}
+
+impl OrchPermissions {
+    /// Add a request. A run's request similar to one already waiting joins
+    /// its prompt; returns that prompt's request id and the number of
+    /// requests it now answers. Otherwise opens a prompt and returns `None`.
+    pub fn add(
+        &mut self,
+        task_run_id: &str,
+        request_id: &str,
+        agent_id: &str,
+        request_key: &str,
+        waiter: PermissionWaiter,
+    ) -> Option<(String, usize)> {
+        let open = self
+            .pending
+            .iter_mut()
+            .find(|((trid, _), p)| trid == task_run_id && p.request_key == request_key);
+        if let Some(((_, open_request_id), pending)) = open {
+            pending.waiters.push(waiter);
+            return Some((open_request_id.clone(), pending.waiters.len()));
+        }
+        let pending = PendingPermission {
+            agent_id: agent_id.to_string(),
+            request_key: request_key.to_string(),
+            waiters: vec![waiter],
+        };
+        self.pending.insert((task_run_id.to_string(), request_id.to_string()), pending);
+        None
+    }
+
+    /// Take the prompt the user answered.
+    pub fn take(&mut self, task_run_id: &str, request_id: &str) -> Option<PendingPermission> {
+        self.pending.remove(&(task_run_id.to_string(), request_id.to_string()))
+    }
+
+    pub fn remember(&mut self, task_run_id: &str, request_key: &str, allow: bool) {
+        self.remembered.insert((task_run_id.to_string(), request_key.to_string()), allow);
+    }
+
+    pub fn remembered(&self, task_run_id: &str, request_key: &str) -> Option<bool> {
+        self.remembered.get(&(task_run_id.to_string(), request_key.to_string())).copied()
+    }
+
+    /// Drop a finished run's prompts and remembered decisions.
+    pub fn forget_run(&mut self, task_run_id: &str) {
+        self.pending.retain(|(trid, _), _| trid != task_run_id);
+        self.remembered.retain(|(trid, _), _| trid != task_run_id);
+    }
+}
+
+/// What makes permission requests similar: the ACP tool kind ("read",
+/// "edit", ...), and for commands the program run.
+pub fn request_key(tool_call: Option<&serde_json::Value>) -> String {
+    let kind = tool_call.and_then(|t| t.get("kind")).and_then(|k| k.as_str()).unwrap_or("other");
+    if kind != "execute" {
+        return kind.to_string();
+    }
+    let command = tool_call.and_then(|t| t.get("rawInput")).and_then(|i| i.get("command"));
+    let program = match command {
+        Some(serde_json::Value::String(command)) => command.split_whitespace().next(),
+        Some(serde_json::Value::Array(args)) => args.first().and_then(|a| a.as_str()),
+        _ => None,
+    };
+    match program.and_then(|p| p.rsplit('/').next()).filter(|p| !p.is_empty()) {
+        Some(program) => format!("execute:{program}"),
+        None => kind.to_string(),
+    }
+}
+
+fn option_kind<'a>(options: &'a serde_json::Value, option_id: &str) -> Option<&'a str> {
+    options
+        .as_array()?
+        .iter()
+        .find(|o| o.get("optionId").and_then(|id| id.as_str()) == Some(option_id))?
+        .get("kind")?
+        .as_str()
+}
+
+/// Whether the chosen option grants the request.
+pub fn is_allow(options: &serde_json::Value, option_id: &str) -> bool {
+    option_kind(options, option_id).unwrap_or(option_id).starts_with("allow")
+}
+
+/// The option of a request that allows or rejects it once, falling back to
+/// any allowing or rejecting option, then to the plain "allow"/"reject" ids.
+pub fn pick_option(options: &serde_json::Value, allow: bool) -> String {
+    let prefix = if allow { "allow" } else { "reject" };
+    let candidates: Vec<(&str, &str)> = options
+        .as_array()
+        .map(|options| {
+            options
+                .iter()
+                .filter_map(|o| Some((o.get("optionId")?.as_str()?, o.get("kind")?.as_str()?)))
+                .filter(|(_, kind)| kind.starts_with(prefix))
+                .collect()
+        })
+        .unwrap_or_default();
+    candidates
+        .iter()
+        .find(|(_, kind)| kind.ends_with("_once"))
+        .or(candidates.first())
+        .map_or_else(|| prefix.to_string(), |(id, _)| id.to_string())
+}
+
+/// What a permission preset does with one kind of tool call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresetRule {
+    Allow,
+    Deny,
+    /// Left to remembered decisions and the user
+    Prompt,
+}
+
+/// Named permission presets shipped with the app, chosen per agent or per
+/// run. A preset answers requests by ACP tool kind; decisions the user
+/// remembered for the run or the agent take precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionPreset {
+    /// Reads and searches only; deletes and commands are rejected
+    Strict,
+    /// Reads and edits in the workspace; commands and deletes ask
+    Balanced,
+    /// Everything is allowed
+    Yolo,
+}
+
+/// ACP tool kinds; requests without a known kind count as "other".
+pub const TOOL_KINDS: [&str; 9] = ["read", "search", "think", "fetch", "edit", "move", "delete", "execute", "other"];
+
+impl PermissionPreset {
+    pub const ALL: [PermissionPreset; 3] = [PermissionPreset::Strict, PermissionPreset::Balanced, PermissionPreset::Yolo];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PermissionPreset::Strict => "strict",
+            PermissionPreset::Balanced => "balanced",
+            PermissionPreset::Yolo => "yolo",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == name)
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            PermissionPreset::Strict => "Reads and searches only; fetches, edits and moves ask, deletes and commands are rejected",
+            PermissionPreset::Balanced => "Reads, fetches and edits are allowed; commands, deletes and other tools ask",
+            PermissionPreset::Yolo => "Every request is allowed",
+        }
+    }
+
+    /// The rule for a tool kind.
+    pub fn rule(self, kind: &str) -> PresetRule {
+        use PresetRule::{Allow, Deny, Prompt};
+        match (self, kind) {
+            (PermissionPreset::Yolo, _) => Allow,
+            (_, "read" | "search" | "think") => Allow,
+            (PermissionPreset::Strict, "delete" | "execute") => Deny,
+            (PermissionPreset::Strict, _) => Prompt,
+            (PermissionPreset::Balanced, "fetch" | "edit" | "move") => Allow,
+            (PermissionPreset::Balanced, _) => Prompt,
+        }
+    }
+
+    /// Whether the preset allows a request, or None when it asks.
+    pub fn decision(self, tool_call: Option<&serde_json::Value>) -> Option<bool> {
+        let kind = tool_call.and_then(|t| t.get("kind")).and_then(|k| k.as_str()).unwrap_or("other");
+        let kind = if TOOL_KINDS.contains(&kind) { kind } else { "other" };
+        match self.rule(kind) {
+            PresetRule::Allow => Some(true),
+            PresetRule::Deny => Some(false),
+            PresetRule::Prompt => None,
+        }
+    }
+}
+
+/// A preset with its rule for every tool kind, as shown when choosing one.
+#[derive(Debug, Clone, Serialize)]
+pub struct PresetInfo {
+    pub preset: PermissionPreset,
+    pub description: &'static str,
+    pub rules: Vec<(&'static str, PresetRule)>,
+}
+
+pub fn presets() -> Vec<PresetInfo> {
+    PermissionPreset::ALL
+        .into_iter()
+        .map(|preset| PresetInfo {
+            preset,
+            description: preset.description(),
+            rules: TOOL_KINDS.into_iter().map(|kind| (kind, preset.rule(kind))).collect(),
+        })
+        .collect()
+}
+
+/// The option answering a waiter's request: the chosen one when the request
+/// offers it, else its own option with the same effect.
+pub fn option_for(options: &serde_json::Value, chosen: &str, allow: bool) -> String {
+    if option_kind(options, chosen).is_some() {
+        chosen.to_string()
+    } else {
+        pick_option(options, allow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn waiter(options: serde_json::Value) -> (PermissionWaiter, tokio::sync::oneshot::Receiver<String>) {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        (PermissionWaiter { options, tx }, rx)
+    }
+
+    #[test]
+    fn batches_similar_requests_and_remembers_decisions() {
+        let read = json!({ "kind": "read", "title": "Read src/lib.rs" });
+        let git = json!({ "kind": "execute", "rawInput": { "command": "/usr/bin/git status" } });
+        assert_eq!(request_key(Some(&read)), "read");
+        assert_eq!(request_key(Some(&git)), "execute:git");
+        assert_eq!(request_key(None), "other");
+
+        let mut perms = OrchPermissions::default();
+        assert_eq!(perms.add("t1", "1", "a1", "read", waiter(json!([])).0), None);
+        assert_eq!(perms.add("t1", "2", "a2", "read", waiter(json!([])).0), Some(("1".into(), 2)));
+        assert_eq!(perms.add("t2", "3", "a1", "read", waiter(json!([])).0), None);
+        assert_eq!(perms.add("t1", "4", "a1", "execute:git", waiter(json!([])).0), None);
+        let pending = perms.take("t1", "1").unwrap();
+        assert_eq!((pending.agent_id.as_str(), pending.waiters.len()), ("a1", 2));
+
+        perms.remember("t1", "read", true);
+        assert_eq!(perms.remembered("t1", "read"), Some(true));
+        assert_eq!(perms.remembered("t2", "read"), None);
+        perms.forget_run("t1");
+        assert_eq!(perms.remembered("t1", "read"), None);
+        assert!(perms.take("t1", "4").is_none());
+    }
+
+    #[test]
+    fn translates_a_decision_into_each_requests_options() {
+        let options = json!([
+            { "optionId": "yes-always", "kind": "allow_always" },
+            { "optionId": "yes", "kind": "allow_once" },
+            { "optionId": "no", "kind": "reject_once" },
+        ]);
+        assert!(is_allow(&options, "yes-always"));
+        assert!(!is_allow(&options, "no"));
+        assert!(is_allow(&json!([]), "allow"));
+        assert_eq!(pick_option(&options, true), "yes");
+        assert_eq!(pick_option(&options, false), "no");
+        assert_eq!(pick_option(&json!([]), false), "reject");
+        assert_eq!(option_for(&options, "yes-always", true), "yes-always");
+        assert_eq!(option_for(&options, "allow_always", true), "yes");
+    }
+
+    #[test]
+    fn presets_answer_requests_by_tool_kind() {
+        let read = json!({ "kind": "read" });
+        let edit = json!({ "kind": "edit" });
+        let git = json!({ "kind": "execute", "rawInput": { "command": "git push" } });
+        let odd = json!({ "kind": "launch_rockets" });
+
+        assert_eq!(PermissionPreset::Strict.decision(Some(&read)), Some(true));
+        assert_eq!(PermissionPreset::Strict.decision(Some(&edit)), None);
+        assert_eq!(PermissionPreset::Strict.decision(Some(&git)), Some(false));
+        assert_eq!(PermissionPreset::Balanced.decision(Some(&edit)), Some(true));
+        assert_eq!(PermissionPreset::Balanced.decision(Some(&git)), None);
+        assert_eq!(PermissionPreset::Balanced.decision(Some(&odd)), None);
+        assert_eq!(PermissionPreset::Balanced.decision(None), None);
+        assert_eq!(PermissionPreset::Yolo.decision(Some(&git)), Some(true));
+
+        for preset in PermissionPreset::ALL {
+            assert_eq!(PermissionPreset::parse(preset.as_str()), Some(preset));
+        }
+        assert_eq!(PermissionPreset::parse("lenient"), None);
+        assert!(presets().iter().all(|p| p.rules.len() == TOOL_KINDS.len()));
+    }
+}
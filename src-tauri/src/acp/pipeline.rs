@@ -0,0 +1,313 @@
+//! Pipeline execution: runs a sequence of orchestrations where each stage's
+//! summary feeds the next stage's prompt, with optional approval gates.
+//! Stages go through the intake queue like any other run, so they count
+//! against the concurrency limit and wait for an exclusive workspace.
+
+use tauri::Emitter;
+use tokio_util::sync::CancellationToken;
+
+use crate::acp::{fallback_planner, run_queue};
+use crate::db::{pipeline_repo, task_run_repo};
+use crate::error::{AppError, AppResult};
+use crate::models::pipeline::{Pipeline, PipelineStage, PREVIOUS_SUMMARY_PLACEHOLDER};
+use crate::models::task_run::RunPriority;
+use crate::state::AppState;
+
+/// How a pipeline run ended.
+enum PipelineOutcome {
+    Completed,
+    Cancelled,
+}
+
+/// Run all stages of a pipeline. Status updates and events are emitted along
+/// the way; the caller only needs to register the cancellation token.
+pub async fn run_pipeline(
+    app: tauri::AppHandle,
+    state: AppState,
+    pipeline_run_id: String,
+    pipeline: Pipeline,
+    initial_input: String,
+    workspace_id: Option<String>,
+    cancel_token: CancellationToken,
+) {
+    let total_stages = pipeline.stages().len();
+    let _ = app.emit(
+        "pipeline:started",
+        serde_json::json!({
+            "pipelineRunId": pipeline_run_id,
+            "pipelineId": pipeline.id,
+            "totalStages": total_stages,
+        }),
+    );
+
+    let result = run_pipeline_inner(
+        &app,
+        &state,
+        &pipeline_run_id,
+        &pipeline,
+        &initial_input,
+        workspace_id.as_deref(),
+        &cancel_token,
+    )
+    .await;
+
+    {
+        let mut runs = state.active_pipeline_runs.lock().await;
+        runs.remove(&pipeline_run_id);
+    }
+    {
+        let mut approvals = state.pending_pipeline_approvals.lock().await;
+        approvals.remove(&pipeline_run_id);
+    }
+
+    let (status, event, error) = match result {
+        Ok(PipelineOutcome::Completed) => ("completed", "pipeline:completed", None),
+        Ok(PipelineOutcome::Cancelled) => ("cancelled", "pipeline:cancelled", None),
+        Err(e) => {
+            log::error!("[Pipeline:{}] Failed: {}", pipeline_run_id, e);
+            ("failed", "pipeline:failed", Some(e.to_string()))
+        }
+    };
+
+    let state_clone = state.clone();
+    let id = pipeline_run_id.clone();
+    let err = error.clone();
+    let _ = tokio::task::spawn_blocking(move || {
+        pipeline_repo::update_pipeline_run_status(&state_clone, &id, status, err.as_deref())
+    })
+    .await;
+
+    let _ = app.emit(
+        event,
+        serde_json::json!({
+            "pipelineRunId": pipeline_run_id,
+            "pipelineId": pipeline.id,
+            "totalStages": total_stages,
+            "error": error,
+        }),
+    );
+}
+
+async fn run_pipeline_inner(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    pipeline_run_id: &str,
+    pipeline: &Pipeline,
+    initial_input: &str,
+    workspace_id: Option<&str>,
+    cancel_token: &CancellationToken,
+) -> AppResult<PipelineOutcome> {
+    let stages = pipeline.stages();
+    let total_stages = stages.len();
+    let mut previous_summary = initial_input.to_string();
+    let mut stage_task_run_ids: Vec<String> = Vec::new();
+
+    for (index, stage) in stages.iter().enumerate() {
+        if cancel_token.is_cancelled() {
+            return Ok(PipelineOutcome::Cancelled);
+        }
+
+        // 1. Approval gate
+        if stage.requires_approval {
+            set_run_status(state, pipeline_run_id, "awaiting_approval").await?;
+            let _ = app.emit(
+                "pipeline:awaiting_approval",
+                serde_json::json!({
+                    "pipelineRunId": pipeline_run_id,
+                    "pipelineId": pipeline.id,
+                    "stageIndex": index,
+                    "stageName": stage.name,
+                    "totalStages": total_stages,
+                    "previousSummary": previous_summary,
+                }),
+            );
+
+            let (tx, rx) = tokio::sync::oneshot::channel::<bool>();
+            {
+                let mut approvals = state.pending_pipeline_approvals.lock().await;
+                approvals.insert(pipeline_run_id.to_string(), tx);
+            }
+
+            let approved = tokio::select! {
+                result = rx => result.unwrap_or(false),
+                _ = cancel_token.cancelled() => false,
+            };
+            if !approved {
+                log::info!("[Pipeline:{}] Stage {} not approved, stopping", pipeline_run_id, index);
+                return Ok(PipelineOutcome::Cancelled);
+            }
+        }
+        set_run_status(state, pipeline_run_id, "running").await?;
+
+        // 2. Create the task run for this stage
+        let hub_id = {
+            let state_clone = state.clone();
+            let ws_id = workspace_id.map(|s| s.to_string());
+            tokio::task::spawn_blocking(move || fallback_planner::hub_id_for(&state_clone, ws_id.as_deref()))
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))??
+        };
+
+        let prompt = build_stage_prompt(stage, &previous_summary, index == 0);
+        let task_run_id = uuid::Uuid::new_v4().to_string();
+        let task_run = {
+            let state_clone = state.clone();
+            let trid = task_run_id.clone();
+            let title = format!("{} · {}", pipeline.name, stage.name);
+            let p = prompt.clone();
+            let hub_id = hub_id.clone();
+            let ws_id = workspace_id.map(|s| s.to_string());
+            tokio::task::spawn_blocking(move || {
+                task_run_repo::create_task_run(&state_clone, &trid, &title, &p, &hub_id, "pending", ws_id.as_deref())
+            })
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??
+        };
+
+        stage_task_run_ids.push(task_run_id.clone());
+        {
+            let state_clone = state.clone();
+            let id = pipeline_run_id.to_string();
+            let ids_json = serde_json::to_string(&stage_task_run_ids)?;
+            let stage_index = index as i64;
+            tokio::task::spawn_blocking(move || {
+                pipeline_repo::record_pipeline_stage(&state_clone, &id, stage_index, &ids_json)
+            })
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+        }
+
+        let _ = app.emit(
+            "pipeline:stage_started",
+            serde_json::json!({
+                "pipelineRunId": pipeline_run_id,
+                "pipelineId": pipeline.id,
+                "stageIndex": index,
+                "stageName": stage.name,
+                "totalStages": total_stages,
+                "taskRunId": task_run_id,
+            }),
+        );
+
+        // 3. Queue the orchestration and wait for it to end. Its token is a
+        // child of the pipeline's, so cancelling the pipeline cancels it too
+        let finished =
+            run_queue::enqueue_and_wait(app, state, &task_run, RunPriority::Interactive, prompt, cancel_token.child_token());
+        tokio::pin!(finished);
+        tokio::select! {
+            _ = &mut finished => {}
+            _ = cancel_token.cancelled() => {
+                // A stage still waiting never starts; a started one winds down
+                if run_queue::remove(app, state, &task_run_id).await {
+                    state.active_task_runs.lock().await.remove(&task_run_id);
+                }
+                finished.await;
+            }
+        }
+
+        // 4. Inspect the outcome and carry the summary forward
+        let task_run = {
+            let state_clone = state.clone();
+            let trid = task_run_id.clone();
+            tokio::task::spawn_blocking(move || task_run_repo::get_task_run(&state_clone, &trid))
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))??
+        };
+
+        match stage_result(stage, &task_run.status, task_run.result_summary, cancel_token.is_cancelled())? {
+            Some(summary) => previous_summary = summary,
+            None => return Ok(PipelineOutcome::Cancelled),
+        }
+
+        let _ = app.emit(
+            "pipeline:stage_completed",
+            serde_json::json!({
+                "pipelineRunId": pipeline_run_id,
+                "pipelineId": pipeline.id,
+                "stageIndex": index,
+                "stageName": stage.name,
+                "totalStages": total_stages,
+                "taskRunId": task_run_id,
+            }),
+        );
+    }
+
+    Ok(PipelineOutcome::Completed)
+}
+
+async fn set_run_status(state: &AppState, pipeline_run_id: &str, status: &str) -> AppResult<()> {
+    let state_clone = state.clone();
+    let id = pipeline_run_id.to_string();
+    let s = status.to_string();
+    tokio::task::spawn_blocking(move || pipeline_repo::update_pipeline_run_status(&state_clone, &id, &s, None))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// The summary a stage hands to the next one, None when the pipeline was
+/// cancelled, or an error when the stage's run did not complete.
+fn stage_result(stage: &PipelineStage, status: &str, summary: Option<String>, cancelled: bool) -> AppResult<Option<String>> {
+    if cancelled || status == "cancelled" {
+        return Ok(None);
+    }
+    if status != "completed" {
+        return Err(AppError::Internal(format!("Stage '{}' ended with status '{}'", stage.name, status)));
+    }
+    Ok(Some(summary.unwrap_or_default()))
+}
+
+/// Build a stage prompt, substituting or appending the previous stage's summary.
+/// For the first stage, the "previous summary" is the pipeline's initial input.
+fn build_stage_prompt(stage: &PipelineStage, previous_summary: &str, is_first: bool) -> String {
+    if stage.prompt_template.contains(PREVIOUS_SUMMARY_PLACEHOLDER) {
+        return stage.prompt_template.replace(PREVIOUS_SUMMARY_PLACEHOLDER, previous_summary);
+    }
+    if previous_summary.trim().is_empty() {
+        return stage.prompt_template.clone();
+    }
+    let heading = if is_first { "Input" } else { "Result of the previous stage" };
+    format!("{}\n\n## {}\n\n{}", stage.prompt_template, heading, previous_summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(name: &str, prompt_template: &str) -> PipelineStage {
+        PipelineStage { name: name.into(), prompt_template: prompt_template.into(), requires_approval: false }
+    }
+
+    #[test]
+    fn each_stage_gets_the_previous_summary() {
+        let stages = [stage("Research", "Research the topic"), stage("Write", "Write it up:\n{{previous_summary}}")];
+        let mut previous_summary = "rust async".to_string();
+        let mut prompts = Vec::new();
+        for (index, stage) in stages.iter().enumerate() {
+            prompts.push(build_stage_prompt(stage, &previous_summary, index == 0));
+            let summary = format!("{} done", stage.name);
+            previous_summary = stage_result(stage, "completed", Some(summary), false).unwrap().unwrap();
+        }
+        assert_eq!(prompts[0], "Research the topic\n\n## Input\n\nrust async");
+        assert_eq!(prompts[1], "Write it up:\nResearch done");
+        assert_eq!(previous_summary, "Write done");
+    }
+
+    #[test]
+    fn first_stage_without_input_uses_its_template() {
+        assert_eq!(build_stage_prompt(&stage("Plan", "Plan it"), "  ", true), "Plan it");
+        assert_eq!(
+            build_stage_prompt(&stage("Review", "Review it"), "Looks fine", false),
+            "Review it\n\n## Result of the previous stage\n\nLooks fine"
+        );
+    }
+
+    #[test]
+    fn stage_that_did_not_complete_stops_the_pipeline() {
+        let review = stage("Review", "Review it");
+        assert!(stage_result(&review, "failed", None, false).unwrap_err().to_string().contains("'failed'"));
+        assert!(stage_result(&review, "needs_review", None, false).is_err());
+        assert_eq!(stage_result(&review, "cancelled", None, false).unwrap(), None);
+        assert_eq!(stage_result(&review, "completed", Some("ok".into()), true).unwrap(), None);
+        assert_eq!(stage_result(&review, "completed", None, false).unwrap(), Some(String::new()));
+    }
+}
@@ -0,0 +1,269 @@
+//! Prompt budgets against a model's context window.
+//!
+//! Agents truncate input that does not fit their model's context without
+//! saying so. Before a prompt is sent its size is estimated and compared
+//! with the model's window (a built-in table, extended by the
+//! `model_context_windows` config):
+//!
+//! - up to `MESSAGE_SHARE` of the window it is sent as is;
+//! - up to `TOTAL_SHARE` it is delivered over several turns, the agent
+//!   acknowledging each part until the last one asks it to act;
+//! - beyond that the middle of the input is replaced by a notice saying how
+//!   much was left out, keeping the instruction at the start and the most
+//!   recent material at the end, and the rest is delivered as above.
+
+use std::collections::HashMap;
+
+use crate::chat_tool::quota::estimate_tokens;
+use crate::config;
+use crate::models::pricing::normalize_model;
+use crate::state::AppState;
+
+/// Largest share of the window a single message may take.
+const MESSAGE_SHARE: f64 = 0.5;
+/// Largest share of the window a whole delivery may take; the rest is left
+/// for the agent's own instructions, tool output and answer.
+const TOTAL_SHARE: f64 = 0.8;
+/// Share of the kept input taken from the start when input is elided.
+const HEAD_SHARE: f64 = 0.7;
+/// Tokens reserved for the framing text of each part.
+const PART_FRAMING_TOKENS: i64 = 100;
+
+/// Context windows in tokens, keyed like the pricing table.
+const BUILTIN_CONTEXT_WINDOWS: &[(&str, i64)] = &[
+    ("claude-opus-4-5", 200_000),
+    ("claude-opus-4", 200_000),
+    ("claude-sonnet-4", 200_000),
+    ("claude-haiku-4-5", 200_000),
+    ("claude-3-7-sonnet", 200_000),
+    ("claude-3-5-sonnet", 200_000),
+    ("claude-3-5-haiku", 200_000),
+    ("opus", 200_000),
+    ("sonnet", 200_000),
+    ("haiku", 200_000),
+    ("gpt-5", 272_000),
+    ("gpt-5-mini", 272_000),
+    ("gpt-5-nano", 272_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4.1-mini", 1_047_576),
+    ("gpt-4.1-nano", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4o-mini", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("o3", 200_000),
+    ("o4-mini", 200_000),
+    ("gemini-2.5-pro", 1_048_576),
+    ("gemini-2.5-flash", 1_048_576),
+    ("gemini-2.0-flash", 1_048_576),
+];
+
+/// Context window of a model: an override or built-in entry for its
+/// normalized id, else the longest entry it extends. Models tagged "[1m]"
+/// run with a million-token window.
+pub fn context_window(model: &str, overrides: &HashMap<String, i64>) -> Option<i64> {
+    let id = normalize_model(model);
+    if id.is_empty() {
+        return None;
+    }
+    // Overrides come last so they win over built-in entries
+    let entries: Vec<(String, i64)> = BUILTIN_CONTEXT_WINDOWS
+        .iter()
+        .map(|&(m, w)| (m.to_string(), w))
+        .chain(overrides.iter().map(|(m, w)| (normalize_model(m), *w)))
+        .collect();
+    if let Some((_, window)) = entries.iter().rfind(|(m, _)| *m == id) {
+        return Some(*window);
+    }
+    if model.to_lowercase().contains("[1m]") {
+        return Some(1_000_000);
+    }
+    entries
+        .iter()
+        .filter(|(m, _)| id.strip_prefix(m.as_str()).is_some_and(|rest| rest.starts_with('-') || rest.starts_with('.')))
+        .max_by_key(|(m, _)| m.len())
+        .map(|(_, w)| *w)
+}
+
+/// Context window of a model with the configured overrides applied.
+pub fn load_context_window(state: &AppState, model: &str) -> Option<i64> {
+    context_window(model, &config::current(state).model_context_windows)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptDelivery {
+    /// Messages to send in order; all but the last are acknowledged only
+    pub parts: Vec<String>,
+    pub estimated_tokens: i64,
+    /// Tokens of input left out because it did not fit the window
+    pub omitted_tokens: i64,
+}
+
+impl PromptDelivery {
+    pub fn is_split(&self) -> bool {
+        self.parts.len() > 1
+    }
+}
+
+/// How to deliver `text` to a model with the given context window.
+pub fn plan(text: &str, context_window: Option<i64>) -> PromptDelivery {
+    let estimated_tokens = estimate_tokens(text);
+    let single = |text: &str| PromptDelivery {
+        parts: vec![text.to_string()],
+        estimated_tokens,
+        omitted_tokens: 0,
+    };
+    let Some(window) = context_window.filter(|w| *w > 0) else {
+        return single(text);
+    };
+    let message_budget = (window as f64 * MESSAGE_SHARE) as i64;
+    if estimated_tokens <= message_budget {
+        return single(text);
+    }
+
+    let total_budget = (window as f64 * TOTAL_SHARE) as i64;
+    let (text, omitted_tokens) = if estimated_tokens > total_budget {
+        elide(text, total_budget)
+    } else {
+        (text.to_string(), 0)
+    };
+
+    let chunks = chunk(&text, (message_budget - PART_FRAMING_TOKENS).max(1));
+    let count = chunks.len();
+    let parts = chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| frame_part(i + 1, count, &chunk))
+        .collect();
+    PromptDelivery { parts, estimated_tokens, omitted_tokens }
+}
+
+fn frame_part(number: usize, count: usize, chunk: &str) -> String {
+    if number < count {
+        format!(
+            "[Part {number} of {count}] This input is too long for one message and arrives in {count} parts. \
+             Do not act on it yet: reply only with \"OK\" until you receive the final part.\n\n{chunk}"
+        )
+    } else {
+        format!(
+            "[Part {number} of {count}] This is the final part. You now have the full input; \
+             carry out the task it describes.\n\n{chunk}"
+        )
+    }
+}
+
+/// Tokens of one character, in quarters: `estimate_tokens` counts ASCII
+/// characters as a quarter token and others as one.
+fn quarter_tokens(c: char) -> i64 {
+    if c.is_ascii() {
+        1
+    } else {
+        4
+    }
+}
+
+/// Byte length of the longest prefix of `text` within `budget` tokens,
+/// ending at a line break when one falls in its second half.
+fn prefix_len(text: &str, budget: i64) -> usize {
+    let mut used = 0;
+    let mut end = text.len();
+    for (i, c) in text.char_indices() {
+        used += quarter_tokens(c);
+        if used > budget * 4 {
+            end = i;
+            break;
+        }
+    }
+    if end < text.len() {
+        if let Some(newline) = text[..end].rfind('\n').filter(|n| *n >= end / 2) {
+            return newline + 1;
+        }
+    }
+    end
+}
+
+/// Byte offset where the longest suffix of `text` within `budget` tokens
+/// starts, at a line start when one falls in its first half.
+fn suffix_start(text: &str, budget: i64) -> usize {
+    let mut used = 0;
+    let mut start = 0;
+    for (i, c) in text.char_indices().rev() {
+        used += quarter_tokens(c);
+        if used > budget * 4 {
+            start = i + c.len_utf8();
+            break;
+        }
+    }
+    if start > 0 {
+        let suffix_len = text.len() - start;
+        if let Some(newline) = text[start..].find('\n').filter(|n| *n <= suffix_len / 2) {
+            return start + newline + 1;
+        }
+    }
+    start
+}
+
+/// Keep the start and end of `text` within `budget` tokens, replacing the
+/// middle with a notice. Returns the text and the tokens left out.
+fn elide(text: &str, budget: i64) -> (String, i64) {
+    let head_end = prefix_len(text, (budget as f64 * HEAD_SHARE) as i64);
+    let tail_start = suffix_start(text, budget - estimate_tokens(&text[..head_end])).max(head_end);
+    let omitted = &text[head_end..tail_start];
+    let omitted_tokens = estimate_tokens(omitted);
+    let notice = format!(
+        "\n[... {} lines (about {} tokens) of this input were left out because it exceeds the model's context window ...]\n",
+        omitted.lines().count(),
+        omitted_tokens
+    );
+    (format!("{}{}{}", &text[..head_end], notice, &text[tail_start..]), omitted_tokens)
+}
+
+/// Split `text` into pieces of at most `budget` tokens, at line breaks where possible.
+fn chunk(text: &str, budget: i64) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let len = prefix_len(rest, budget).max(rest.chars().next().map_or(1, char::len_utf8));
+        chunks.push(rest[..len].to_string());
+        rest = &rest[len..];
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_context_windows() {
+        let overrides = HashMap::from([("my-model".to_string(), 32_000)]);
+        assert_eq!(context_window("claude-sonnet-4-5-20250929", &overrides), Some(200_000));
+        assert_eq!(context_window("claude-sonnet-4-5[1m]", &overrides), Some(1_000_000));
+        assert_eq!(context_window("openai/gpt-4o-mini", &overrides), Some(128_000));
+        assert_eq!(context_window("my-model-v2", &overrides), Some(32_000));
+        assert_eq!(context_window("unknown", &overrides), None);
+    }
+
+    #[test]
+    fn splits_and_elides_oversized_prompts() {
+        let line = format!("{}\n", "word ".repeat(20)); // 25 tokens
+        let text = line.repeat(100); // 2500 tokens
+
+        assert_eq!(plan(&text, Some(10_000)).parts, vec![text.clone()]);
+        assert_eq!(plan(&text, None).parts.len(), 1);
+
+        // 1500-token messages, 2400-token total: elided, then split in two
+        let delivery = plan(&text, Some(3_000));
+        assert!(delivery.omitted_tokens > 0);
+        assert_eq!(delivery.parts.len(), 2);
+        assert!(delivery.parts[0].starts_with("[Part 1 of 2]"));
+        assert!(delivery.parts[1].contains("left out because it exceeds"));
+        assert!(delivery.parts.iter().all(|p| estimate_tokens(p) <= 1_500));
+
+        // Fits the total but not one message
+        let delivery = plan(&text, Some(4_000));
+        assert_eq!(delivery.omitted_tokens, 0);
+        assert_eq!(delivery.parts.len(), 2);
+        let rejoined: String = delivery.parts.iter().map(|p| p.split_once("\n\n").unwrap().1).collect();
+        assert_eq!(rejoined, text);
+    }
+}
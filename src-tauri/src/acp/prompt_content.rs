@@ -0,0 +1,208 @@
+//! Prompt content beyond plain text.
+//!
+//! The planner can attach workspace files to an assignment. They are sent
+//! after the prompt's text as ACP content blocks: images as `image` blocks
+//! when the agent's `promptCapabilities` take images, small text files as
+//! embedded `resource` blocks when they take embedded context, and anything
+//! else as a `resource_link`, which every agent must accept. Paths are
+//! relative to the workspace root; ones outside it, missing or not files are
+//! left out.
+
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use serde_json::{json, Value};
+
+use crate::acp::inline_artifacts;
+use crate::models::task_run::PromptAttachment;
+use crate::state::AppState;
+
+/// At most this many files are attached to a prompt.
+pub const MAX_ATTACHMENTS: usize = 10;
+/// Larger images are linked instead of sent.
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+/// Larger text files are linked instead of embedded.
+const MAX_EMBEDDED_TEXT_BYTES: u64 = 64 * 1024;
+
+/// Image types agents take in `image` blocks.
+const IMAGE_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// Files attached to an assignment's prompts.
+pub type Attachments = Vec<PromptAttachment>;
+
+/// Content an agent accepts besides text and resource links, from its
+/// `initialize` response.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PromptCapabilities {
+    pub image: bool,
+    pub embedded_context: bool,
+}
+
+impl PromptCapabilities {
+    pub fn from_initialize(response: &Value) -> Self {
+        let capabilities = &response["result"]["agentCapabilities"]["promptCapabilities"];
+        Self {
+            image: capabilities["image"].as_bool().unwrap_or(false),
+            embedded_context: capabilities["embeddedContext"].as_bool().unwrap_or(false),
+        }
+    }
+}
+
+/// Send `attachments` with the agent's prompts in a run until `end`.
+pub async fn begin(state: &AppState, task_run_id: &str, agent_id: &str, attachments: &[PromptAttachment]) {
+    if attachments.is_empty() {
+        return;
+    }
+    if attachments.len() > MAX_ATTACHMENTS {
+        log::warn!(
+            "Agent {}'s assignment has {} attachments; only the first {} are sent",
+            agent_id, attachments.len(), MAX_ATTACHMENTS
+        );
+    }
+    let attachments = attachments.iter().take(MAX_ATTACHMENTS).cloned().collect();
+    let mut all = state.assignment_attachments.lock().await;
+    all.insert((task_run_id.to_string(), agent_id.to_string()), attachments);
+}
+
+pub async fn end(state: &AppState, task_run_id: &str, agent_id: &str) {
+    let mut all = state.assignment_attachments.lock().await;
+    all.remove(&(task_run_id.to_string(), agent_id.to_string()));
+}
+
+/// Files attached to the agent's running assignment.
+pub async fn current(state: &AppState, task_run_id: &str, agent_id: &str) -> Option<Attachments> {
+    let all = state.assignment_attachments.lock().await;
+    all.get(&(task_run_id.to_string(), agent_id.to_string())).cloned()
+}
+
+pub fn text(text: &str) -> Value {
+    json!({ "type": "text", "text": text })
+}
+
+/// The file `path` names inside `root`. Symlinks are resolved, so a link
+/// pointing outside the root is rejected.
+fn resolve(root: &Path, path: &str) -> Result<PathBuf, String> {
+    use std::path::Component;
+
+    let rel = Path::new(path.trim());
+    if rel.is_absolute() || !rel.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err("must be a relative path without '..'".into());
+    }
+    let root = root.canonicalize().map_err(|e| format!("workspace root is not accessible: {e}"))?;
+    let file = root.join(rel).canonicalize().map_err(|_| "file does not exist".to_string())?;
+    if !file.starts_with(&root) {
+        return Err("resolves outside the workspace root".into());
+    }
+    if !file.is_file() {
+        return Err("not a file".into());
+    }
+    Ok(file)
+}
+
+/// `file://` URI of an absolute path.
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from(if path.starts_with('/') { "file://" } else { "file:///" });
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~:".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+fn block(file: &Path, attachment: &PromptAttachment, capabilities: PromptCapabilities) -> std::io::Result<Value> {
+    let size = std::fs::metadata(file)?.len();
+    let mime_type = inline_artifacts::mime_for_path(file);
+    let uri = file_uri(file);
+
+    if let Some(mime_type) = mime_type.filter(|m| capabilities.image && IMAGE_TYPES.contains(m) && size <= MAX_IMAGE_BYTES) {
+        let data = base64::engine::general_purpose::STANDARD.encode(std::fs::read(file)?);
+        return Ok(json!({ "type": "image", "mimeType": mime_type, "data": data, "uri": uri }));
+    }
+    if capabilities.embedded_context && mime_type.map_or(true, |m| m.starts_with("text/")) && size <= MAX_EMBEDDED_TEXT_BYTES {
+        if let Ok(text) = String::from_utf8(std::fs::read(file)?) {
+            return Ok(json!({
+                "type": "resource",
+                "resource": { "uri": uri, "mimeType": mime_type.unwrap_or("text/plain"), "text": text },
+            }));
+        }
+    }
+    let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut link = json!({ "type": "resource_link", "uri": uri, "name": name, "size": size });
+    if let Some(mime_type) = mime_type {
+        link["mimeType"] = json!(mime_type);
+    }
+    if let Some(description) = attachment.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        link["description"] = json!(description);
+    }
+    Ok(link)
+}
+
+/// Content blocks of the attachments found in `root`; the others are logged
+/// and left out.
+pub fn blocks(root: &Path, attachments: &[PromptAttachment], capabilities: PromptCapabilities) -> Vec<Value> {
+    attachments
+        .iter()
+        .filter_map(|attachment| {
+            let built = resolve(root, &attachment.path)
+                .and_then(|file| block(&file, attachment, capabilities).map_err(|e| e.to_string()));
+            match built {
+                Ok(block) => Some(block),
+                Err(reason) => {
+                    log::warn!("Not attaching '{}' (root {}): {}", attachment.path, root.display(), reason);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attach(path: &str) -> PromptAttachment {
+        PromptAttachment { path: path.to_string(), description: None }
+    }
+
+    #[test]
+    fn builds_blocks_by_file_type_and_agent_capabilities() {
+        let root = std::env::temp_dir().join(format!("prompt-content-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/chart.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        std::fs::write(root.join("docs/spec notes.md"), "# Spec\n").unwrap();
+        std::fs::write(root.join("report.pdf"), b"%PDF-1.7").unwrap();
+        let attachments =
+            [attach("docs/chart.png"), attach("docs/spec notes.md"), attach("report.pdf"), attach("../etc/passwd"), attach("missing.txt")];
+
+        let all = PromptCapabilities { image: true, embedded_context: true };
+        let blocks = blocks(&root, &attachments, all);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0]["type"], "image");
+        assert_eq!(blocks[0]["mimeType"], "image/png");
+        assert_eq!(blocks[0]["data"], "iVBORw==");
+        assert_eq!(blocks[1]["type"], "resource");
+        assert_eq!(blocks[1]["resource"]["text"], "# Spec\n");
+        assert!(blocks[1]["resource"]["uri"].as_str().unwrap().ends_with("/docs/spec%20notes.md"));
+        assert_eq!(blocks[2]["type"], "resource_link");
+        assert_eq!(blocks[2]["mimeType"], "application/pdf");
+        assert_eq!(blocks[2]["name"], "report.pdf");
+
+        let baseline = super::blocks(&root, &attachments, PromptCapabilities::default());
+        assert!(baseline.iter().all(|b| b["type"] == "resource_link"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn reads_prompt_capabilities_from_initialize() {
+        let response = json!({ "result": { "agentCapabilities": { "promptCapabilities": { "image": true, "audio": false } } } });
+        assert_eq!(
+            PromptCapabilities::from_initialize(&response),
+            PromptCapabilities { image: true, embedded_context: false }
+        );
+        assert_eq!(PromptCapabilities::from_initialize(&json!({ "result": {} })), PromptCapabilities::default());
+    }
+}
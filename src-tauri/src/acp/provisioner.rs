@@ -1,7 +1,7 @@
 use std::path::Path;
 
 use crate::acp::discovery::{self, BinaryTarget, Distribution};
-use crate::acp::builtin;
+use crate::acp::{agent_versions, builtin};
 use crate::error::AppResult;
 
 /// The resolved command after provisioning.
@@ -18,6 +18,7 @@ pub struct ResolvedCommand {
 /// Resolve the actual command + args for a given registry command name.
 ///
 /// Resolution priority:
+/// 0. A version the user pinned, installed in `~/.iaagenthub/adapters/<agent_id>/`
 /// 1. Check PATH (enriched) — use directly
 /// 2. Check `~/.iaagenthub/adapters/<agent_id>/` — use previously cached binary
 /// 3. For binary distribution: download + extract → use cached binary
@@ -68,9 +69,19 @@ pub async fn resolve_agent_command(
         }
     }
 
-    // 1. Check PATH
+    // Priority 0b: a pinned version wins over whatever is on PATH
+    let pinned = entry.as_ref().and_then(|e| agent_versions::pinned_version(&e.id));
+    if let (Some(entry), Some(version)) = (&entry, &pinned) {
+        if let Distribution::Npx(npx) = &entry.distribution {
+            if let Some(resolved) = local_npx_adapter(entry, &npx.package, &npx.args, basename, args, Some(version)) {
+                return Ok(resolved);
+            }
+        }
+    }
+
+    // 1. Check PATH, unless a pinned version has yet to be fetched
     let enriched_path = discovery::get_enriched_path();
-    if let Some(resolved) = resolve_in_path(basename, &enriched_path) {
+    if let Some(resolved) = resolve_in_path(basename, &enriched_path).filter(|_| pinned.is_none()) {
         log::info!("Provisioner: found {} on PATH at {}", basename, resolved);
         return Ok(ResolvedCommand {
             command: resolved,
@@ -130,59 +141,25 @@ pub async fn resolve_agent_command(
                 }
             }
             Distribution::Npx(npx) => {
-                // 3b. Check for locally-installed npm adapter in adapters dir
-                let local_bin = discovery::get_adapters_dir()
-                    .join(&entry.id)
-                    .join("node_modules")
-                    .join(".bin")
-                    .join(basename);
-                if local_bin.exists() {
-                    // Check if the local adapter version matches the registry version
-                    let registry_version = extract_package_version(&npx.package);
-                    let local_version = read_local_adapter_version(&entry.id, &npx.package);
-                    let is_stale = match (&registry_version, &local_version) {
-                        (Some(reg_ver), Some(loc_ver)) if reg_ver != loc_ver => {
-                            log::warn!(
-                                "Provisioner: local adapter {} is stale (local={}, registry={}), skipping cache",
-                                entry.id, loc_ver, reg_ver
-                            );
-                            true
-                        }
-                        (Some(_), None) => {
-                            log::warn!(
-                                "Provisioner: cannot determine local adapter version for {}, skipping cache",
-                                entry.id,
-                            );
-                            true
-                        }
-                        _ => false,
-                    };
-
-                    if !is_stale {
-                        log::info!(
-                            "Provisioner: using locally-installed npm adapter for {} at {:?} (version: {})",
-                            entry.id,
-                            local_bin,
-                            local_version.as_deref().unwrap_or("unknown"),
-                        );
-                        let mut final_args = npx.args.clone();
-                        final_args.extend(args.iter().cloned());
-                        return Ok(ResolvedCommand {
-                            command: local_bin.to_string_lossy().to_string(),
-                            args: final_args,
-                            agent_type: entry.id.clone(),
-                        });
-                    }
+                // 3b. Check for locally-installed npm adapter in adapters dir,
+                // of the pinned version or else the registry's
+                let wanted = pinned.clone().or_else(|| extract_package_version(&npx.package));
+                if let Some(resolved) = local_npx_adapter(entry, &npx.package, &npx.args, basename, args, wanted.as_deref()) {
+                    return Ok(resolved);
                 }
 
                 // 4. NPX fallback
                 if let Some(npx_path) = resolve_in_path("npx", &enriched_path) {
+                    let package = match &pinned {
+                        Some(version) => format!("{}@{}", agent_versions::split_package(&npx.package).0, version),
+                        None => npx.package.clone(),
+                    };
                     log::info!(
                         "Provisioner: using npx for {} (package: {})",
                         basename,
-                        npx.package
+                        package
                     );
-                    let mut npx_args = vec!["-y".to_string(), npx.package.clone()];
+                    let mut npx_args = vec!["-y".to_string(), package];
                     npx_args.extend(args.iter().cloned());
                     return Ok(ResolvedCommand {
                         command: npx_path,
@@ -206,6 +183,57 @@ pub async fn resolve_agent_command(
     })
 }
 
+/// The npm adapter installed in the adapters dir, unless its version is not
+/// `wanted`.
+fn local_npx_adapter(
+    entry: &discovery::RegistryEntry,
+    package: &str,
+    npx_args: &[String],
+    basename: &str,
+    args: &[String],
+    wanted: Option<&str>,
+) -> Option<ResolvedCommand> {
+    let local_bin = discovery::get_adapters_dir()
+        .join(&entry.id)
+        .join("node_modules")
+        .join(".bin")
+        .join(basename);
+    if !local_bin.exists() {
+        return None;
+    }
+    let local_version = read_local_adapter_version(&entry.id, package);
+    match (wanted, &local_version) {
+        (Some(wanted), Some(local)) if wanted != local => {
+            log::warn!(
+                "Provisioner: local adapter {} is stale (local={}, wanted={}), skipping cache",
+                entry.id, local, wanted
+            );
+            return None;
+        }
+        (Some(_), None) => {
+            log::warn!(
+                "Provisioner: cannot determine local adapter version for {}, skipping cache",
+                entry.id,
+            );
+            return None;
+        }
+        _ => {}
+    }
+    log::info!(
+        "Provisioner: using locally-installed npm adapter for {} at {:?} (version: {})",
+        entry.id,
+        local_bin,
+        local_version.as_deref().unwrap_or("unknown"),
+    );
+    let mut final_args = npx_args.to_vec();
+    final_args.extend(args.iter().cloned());
+    Some(ResolvedCommand {
+        command: local_bin.to_string_lossy().to_string(),
+        args: final_args,
+        agent_type: entry.id.clone(),
+    })
+}
+
 /// Get the args from a registry entry distribution for the current platform.
 fn get_distribution_args(entry: &discovery::RegistryEntry) -> Vec<String> {
     match &entry.distribution {
@@ -229,7 +257,7 @@ pub fn is_npx_command(command: &str) -> bool {
     basename == "npx" || basename == "pnpx"
 }
 
-fn resolve_in_path(cmd: &str, path_env: &str) -> Option<String> {
+pub(crate) fn resolve_in_path(cmd: &str, path_env: &str) -> Option<String> {
     #[cfg(target_os = "windows")]
     let lookup = "where.exe";
     #[cfg(not(target_os = "windows"))]
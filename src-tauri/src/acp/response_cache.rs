@@ -0,0 +1,121 @@
+//! Opt-in cache of agent responses for identical assignments.
+//!
+//! Enabled by the `response_cache_ttl_minutes` config (0, the default, disables
+//! it). An assignment whose agent, model, system prompt, skills, working
+//! directory and input match an entry younger than the TTL gets the cached output instead of
+//! prompting the agent. A cached assignment does not repeat the agent's side
+//! effects, such as file edits, so this suits read-only work like reports.
+
+use crate::config;
+use crate::db::response_cache_repo;
+use crate::models::agent::AgentConfig;
+use crate::state::AppState;
+
+/// Cache lifetime in seconds, or `None` when the cache is off.
+pub fn ttl_secs(state: &AppState) -> Option<i64> {
+    let minutes = config::current(state).response_cache_ttl_minutes;
+    (minutes > 0).then(|| minutes * 60)
+}
+
+/// Only surrounding whitespace is ignored; inside the input it can be
+/// meaningful (code, indentation, tables).
+fn normalize(input: &str) -> &str {
+    input.trim()
+}
+
+/// Everything besides the model that shapes the agent's answer is part of the key.
+fn input_hash(agent: &AgentConfig, input: &str, working_dir: Option<&str>) -> String {
+    let keyed = format!(
+        "{}\0{}\0{}\0{}",
+        agent.system_prompt,
+        agent.skills_json,
+        working_dir.unwrap_or_default(),
+        normalize(input)
+    );
+    ring::digest::digest(&ring::digest::SHA256, keyed.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Cached output for this assignment input, if the cache is on and holds one. Blocking.
+pub fn lookup(state: &AppState, agent: &AgentConfig, input: &str, working_dir: Option<&str>) -> Option<String> {
+    let ttl = ttl_secs(state)?;
+    let hash = input_hash(agent, input, working_dir);
+    match response_cache_repo::get_response(state, &agent.id, &agent.model, &hash, ttl) {
+        Ok(output) => output.filter(|o| !o.trim().is_empty()),
+        Err(e) => {
+            log::warn!("[ResponseCache] Lookup failed for agent {}: {}", agent.id, e);
+            None
+        }
+    }
+}
+
+/// Remember an agent's output when the cache is on. Empty outputs are not
+/// stored. Blocking.
+pub fn store(
+    state: &AppState,
+    agent: &AgentConfig,
+    input: &str,
+    working_dir: Option<&str>,
+    output: &str,
+    assignment_id: &str,
+) {
+    if output.trim().is_empty() {
+        return;
+    }
+    let Some(ttl) = ttl_secs(state) else {
+        return;
+    };
+    let hash = input_hash(agent, input, working_dir);
+    let result = response_cache_repo::prune(state, ttl).and_then(|_| {
+        response_cache_repo::save_response(state, &agent.id, &agent.model, &hash, output, assignment_id)
+    });
+    if let Err(e) = result {
+        log::warn!("[ResponseCache] Failed to store response for agent {}: {}", agent.id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent() -> AgentConfig {
+        serde_json::from_value(serde_json::json!({
+            "id": "a1", "name": "Writer", "icon": "", "description": "", "status": "idle",
+            "execution_mode": "acp", "model": "gemini-2.5-pro", "temperature": 0.7, "max_tokens": 4096,
+            "system_prompt": "Be brief.", "capabilities_json": "[]", "skills_json": "[]", "is_control_hub": false,
+            "is_secondary_hub": false, "md_file_path": null, "max_concurrency": 1, "available_models_json": null,
+            "is_enabled": true, "disabled_reason": null, "created_at": "", "updated_at": "",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn only_surrounding_whitespace_shares_a_key() {
+        let agent = agent();
+        assert_eq!(
+            input_hash(&agent, "  Summarize the report\n", None),
+            input_hash(&agent, "Summarize the report", None)
+        );
+        assert_ne!(
+            input_hash(&agent, "fn a() {\n    b()\n}", None),
+            input_hash(&agent, "fn a() { b() }", None)
+        );
+        assert_ne!(
+            input_hash(&agent, "Summarize the report", None),
+            input_hash(&agent, "Summarize the report", Some("docs"))
+        );
+    }
+
+    #[test]
+    fn system_prompt_and_skills_are_part_of_the_key() {
+        let agent = agent();
+        let base = input_hash(&agent, "Summarize", None);
+        let prompted = AgentConfig { system_prompt: "Be thorough.".into(), ..agent.clone() };
+        let skilled = AgentConfig { skills_json: r#"["pdf"]"#.into(), ..agent.clone() };
+        assert_ne!(base, input_hash(&prompted, "Summarize", None));
+        assert_ne!(base, input_hash(&skilled, "Summarize", None));
+    }
+}
@@ -0,0 +1,182 @@
+//! Code changes of a task run, read back from the diffs kept for reviewed
+//! writes (`output/<task_run_id>/diffs/`). Used for the "Code Changes"
+//! section of the run's summary.md and by `get_run_changes`.
+
+use std::path::Path;
+
+use crate::db::migrations::get_output_dir;
+use crate::i18n::{self, Locale, Msg};
+use crate::models::task_run::{FileChange, RunChanges};
+
+/// Diff lines embedded per file in summary.md; the full diff stays in the
+/// run's diffs directory.
+const MAX_EMBEDDED_DIFF_LINES: usize = 400;
+
+/// One saved diff file.
+#[derive(Debug, PartialEq)]
+struct SavedDiff {
+    path: String,
+    agent_id: String,
+    approved: bool,
+    is_new_file: bool,
+    diff: String,
+}
+
+fn parse_saved_diff(text: &str) -> Option<SavedDiff> {
+    let (header, diff) = text.split_once("\n\n").unwrap_or((text, ""));
+    let field = |name: &str| {
+        header
+            .lines()
+            .find_map(|line| line.strip_prefix("# ")?.strip_prefix(name)?.strip_prefix(": "))
+            .map(|v| v.trim().to_string())
+    };
+    // Prefer the workspace-relative path the diff itself shows
+    let path = diff
+        .lines()
+        .find_map(|line| line.strip_prefix("+++ b/"))
+        .map(|p| p.to_string())
+        .or_else(|| field("path"))?;
+    Some(SavedDiff {
+        path,
+        agent_id: field("agent").unwrap_or_default(),
+        approved: field("status").as_deref() == Some("approved"),
+        is_new_file: field("new_file").as_deref() == Some("true"),
+        diff: diff.to_string(),
+    })
+}
+
+/// Added and removed lines of a single-file unified diff.
+fn diff_stats(diff: &str) -> (usize, usize) {
+    // Everything before the first hunk is the ---/+++ header
+    let hunks = diff.lines().skip_while(|line| !line.starts_with("@@"));
+    hunks.fold((0, 0), |(additions, deletions), line| match line.as_bytes().first() {
+        Some(b'+') => (additions + 1, deletions),
+        Some(b'-') => (additions, deletions + 1),
+        _ => (additions, deletions),
+    })
+}
+
+fn aggregate(diffs: Vec<SavedDiff>) -> RunChanges {
+    let mut files: Vec<FileChange> = Vec::new();
+    for saved in diffs {
+        let index = match files.iter().position(|f| f.path == saved.path) {
+            Some(i) => i,
+            None => {
+                files.push(FileChange {
+                    path: saved.path.clone(),
+                    agent_ids: Vec::new(),
+                    is_new_file: saved.is_new_file && saved.approved,
+                    additions: 0,
+                    deletions: 0,
+                    diff: String::new(),
+                    rejected_writes: 0,
+                });
+                files.len() - 1
+            }
+        };
+        let file = &mut files[index];
+        if !saved.approved {
+            file.rejected_writes += 1;
+            continue;
+        }
+        let (additions, deletions) = diff_stats(&saved.diff);
+        file.additions += additions;
+        file.deletions += deletions;
+        file.diff.push_str(&saved.diff);
+        if !file.agent_ids.contains(&saved.agent_id) {
+            file.agent_ids.push(saved.agent_id);
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    RunChanges {
+        additions: files.iter().map(|f| f.additions).sum(),
+        deletions: files.iter().map(|f| f.deletions).sum(),
+        files,
+    }
+}
+
+/// Changes recorded for a run; empty when it made no reviewed writes.
+pub fn collect(task_run_id: &str) -> std::io::Result<RunChanges> {
+    let dir = get_output_dir().join(task_run_id).join("diffs");
+    if !dir.is_dir() {
+        return Ok(RunChanges::default());
+    }
+    // Files are numbered in write order
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("diff"))
+        .collect();
+    paths.sort();
+    let diffs = paths
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .filter_map(|text| parse_saved_diff(&text))
+        .collect();
+    Ok(aggregate(diffs))
+}
+
+/// "Code Changes" section for summary.md: a stats table and each file's
+/// diff collapsed under a `<details>` block. Empty when nothing changed.
+pub fn render_markdown(changes: &RunChanges, locale: Locale, diffs_dir: &Path) -> String {
+    if changes.files.is_empty() {
+        return String::new();
+    }
+    let mut md = format!(
+        "\n## {}\n{}\n|------|---|---|\n",
+        i18n::text(locale, Msg::SummaryCodeChanges),
+        i18n::text(locale, Msg::SummaryChangesTableHeader),
+    );
+    for file in &changes.files {
+        md.push_str(&format!("| `{}` | +{} | -{} |\n", file.path, file.additions, file.deletions));
+    }
+    md.push_str(&format!("| | **+{}** | **-{}** |\n", changes.additions, changes.deletions));
+
+    for file in changes.files.iter().filter(|f| !f.diff.is_empty()) {
+        let lines: Vec<&str> = file.diff.lines().collect();
+        let mut body = lines[..lines.len().min(MAX_EMBEDDED_DIFF_LINES)].join("\n");
+        if lines.len() > MAX_EMBEDDED_DIFF_LINES {
+            let omitted = (lines.len() - MAX_EMBEDDED_DIFF_LINES).to_string();
+            let dir = diffs_dir.to_string_lossy();
+            body.push_str(&format!(
+                "\n# {}",
+                i18n::tr(locale, Msg::SummaryDiffTruncated, &[("count", &omitted), ("dir", &dir)])
+            ));
+        }
+        md.push_str(&format!(
+            "\n<details>\n<summary><code>{}</code> (+{} -{})</summary>\n\n```diff\n{}\n```\n\n</details>\n",
+            file.path, file.additions, file.deletions, body
+        ));
+    }
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates_saved_diffs_per_file() {
+        let first = "# path: /ws/src/a.rs\n# agent: coder\n# status: approved\n# new_file: false\n\n\
+                     --- a/src/a.rs\n+++ b/src/a.rs\n@@ -1,2 +1,2 @@\n-old\n+new\n same\n";
+        let rejected = "# path: /ws/src/a.rs\n# agent: coder\n# status: rejected\n\n\
+                        --- a/src/a.rs\n+++ b/src/a.rs\n@@ -1 +1 @@\n-x\n+y\n";
+        let second = "# path: /ws/src/b.rs\n# agent: tester\n# status: approved\n# new_file: true\n\n\
+                      --- a/src/b.rs\n+++ b/src/b.rs\n@@ -0,0 +1,2 @@\n+one\n+two\n";
+
+        let saved: Vec<SavedDiff> = [second, first, rejected].iter().filter_map(|t| parse_saved_diff(t)).collect();
+        assert_eq!(saved[1].path, "src/a.rs");
+        assert!(saved[0].is_new_file);
+
+        let changes = aggregate(saved);
+        assert_eq!(changes.files.len(), 2);
+        assert_eq!(changes.files[0].path, "src/a.rs");
+        assert_eq!((changes.files[0].additions, changes.files[0].deletions), (1, 1));
+        assert_eq!(changes.files[0].rejected_writes, 1);
+        assert!(changes.files[1].is_new_file);
+        assert_eq!((changes.additions, changes.deletions), (3, 1));
+
+        let md = render_markdown(&changes, Locale::En, Path::new("/out/diffs"));
+        assert!(md.contains("| `src/b.rs` | +2 | -0 |"));
+        assert!(md.contains("```diff\n--- a/src/a.rs"));
+    }
+}
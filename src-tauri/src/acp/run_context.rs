@@ -0,0 +1,144 @@
+//! Context of an earlier run attached to a new one.
+//!
+//! A follow-up request ("now fix the issues found in that run") names the
+//! earlier run in `attach_run_context` when it starts. The new run's
+//! planning prompt then carries the earlier run's request, summary and the
+//! outputs of its finished assignments, so the user does not have to paste
+//! them in. Long outputs are cut to fit a budget.
+//!
+//! Outputs the user pinned to a run's clipboard can be appended to a new
+//! request's prompt as well, whole.
+
+use crate::db::task_run_repo;
+use crate::error::AppResult;
+use crate::models::task_run::{PinnedOutput, TaskRun};
+use crate::state::AppState;
+
+/// Most characters of one assignment's output included.
+const MAX_OUTPUT_CHARS: usize = 3000;
+/// Most characters of assignment outputs included in all.
+const MAX_OUTPUTS_CHARS: usize = 12_000;
+
+/// Planning prompt section for the earlier run attached to the run; empty
+/// when there is none. Blocking.
+pub fn planning_context(state: &AppState, task_run_id: &str) -> AppResult<String> {
+    let run = task_run_repo::get_task_run(state, task_run_id)?;
+    let Some(context_run_id) = run.context_run_id else {
+        return Ok(String::new());
+    };
+    let context_run = task_run_repo::get_task_run(state, &context_run_id)?;
+    let assignments = task_run_repo::list_assignments_for_run(state, &context_run_id)?;
+    let outputs: Vec<(&str, &str)> = assignments
+        .iter()
+        .filter(|a| a.status == "completed" || a.status == "capped")
+        .filter_map(|a| Some((a.agent_name.as_str(), a.output_text.as_deref()?)))
+        .collect();
+    Ok(build_section(&context_run, &outputs))
+}
+
+fn build_section(run: &TaskRun, outputs: &[(&str, &str)]) -> String {
+    let mut section = format!(
+        "\n## Earlier Run\n\nThe user attached this earlier run \"{}\" as context; the request may refer to it.\n\n\
+         ### Its Request\n{}\n",
+        run.title,
+        run.user_prompt.trim()
+    );
+    match run.result_summary.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(summary) => section.push_str(&format!("\n### Its Outcome ({})\n{}\n", run.status, summary)),
+        None => section.push_str(&format!("\n### Its Outcome\nThe run is {} and has no summary.\n", run.status)),
+    }
+    section.push_str(&format_outputs(outputs));
+    section
+}
+
+/// The agents' outputs, each cut to `MAX_OUTPUT_CHARS`, until
+/// `MAX_OUTPUTS_CHARS` are used up.
+fn format_outputs(outputs: &[(&str, &str)]) -> String {
+    let mut budget = MAX_OUTPUTS_CHARS;
+    let mut omitted = 0;
+    let mut section = String::new();
+    for (agent_name, output) in outputs {
+        let output = output.trim();
+        if output.is_empty() {
+            continue;
+        }
+        let limit = MAX_OUTPUT_CHARS.min(budget);
+        if limit == 0 {
+            omitted += 1;
+            continue;
+        }
+        let count = output.chars().count();
+        let text = if count > limit {
+            let kept: String = output.chars().take(limit).collect();
+            format!("{}\n[... {} more characters]", kept, count - limit)
+        } else {
+            output.to_string()
+        };
+        budget -= count.min(limit);
+        section.push_str(&format!("\n#### {}\n{}\n", agent_name, text));
+    }
+    if section.is_empty() {
+        return section;
+    }
+    if omitted > 0 {
+        section.push_str(&format!("\n({} more output(s) omitted)\n", omitted));
+    }
+    format!("\n### Its Agents' Outputs\n{}", section)
+}
+
+/// Prompt or export section with the pinned outputs; empty when there are none.
+pub fn pinned_section(pinned: &[PinnedOutput]) -> String {
+    if pinned.is_empty() {
+        return String::new();
+    }
+    let mut section = String::from("\n\n## Pinned Outputs\n");
+    for output in pinned {
+        section.push_str(&format!("\n### {}\n{}\n", output.label, output.content.trim()));
+    }
+    section
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cuts_earlier_outputs_to_the_budget() {
+        let long = "x".repeat(MAX_OUTPUT_CHARS + 10);
+        let outputs = vec![
+            ("Reviewer", "Found 2 issues:\n1. unchecked unwrap in parser.rs\n2. missing test"),
+            ("Writer", "   "),
+            ("Coder", long.as_str()),
+            ("Coder", long.as_str()),
+            ("Coder", long.as_str()),
+            ("Coder", long.as_str()),
+            ("Tester", "all green"),
+        ];
+        let section = format_outputs(&outputs);
+        assert!(section.starts_with("\n### Its Agents' Outputs\n\n#### Reviewer\nFound 2 issues:"));
+        assert!(!section.contains("#### Writer"));
+        assert_eq!(section.matches("[... 10 more characters]").count(), 3);
+        assert_eq!(section.matches("#### Coder").count(), 4);
+        assert!(section.ends_with("(1 more output(s) omitted)\n"));
+        assert_eq!(format_outputs(&[("Writer", "")]), "");
+    }
+
+    #[test]
+    fn formats_pinned_outputs() {
+        let pin = |label: &str, content: &str| PinnedOutput {
+            id: String::new(),
+            task_run_id: "run".into(),
+            source: "assignment".into(),
+            assignment_id: None,
+            agent_id: None,
+            label: label.into(),
+            content: content.into(),
+            created_at: String::new(),
+        };
+        assert_eq!(pinned_section(&[]), "");
+        assert_eq!(
+            pinned_section(&[pin("Coder", "fn main() {}\n"), pin("Reviewer via A2A", "LGTM")]),
+            "\n\n## Pinned Outputs\n\n### Coder\nfn main() {}\n\n### Reviewer via A2A\nLGTM\n"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Wall-clock limits of task runs.
+//!
+//! A run is limited by the `max_duration_minutes` it was started with, or
+//! else the `max_run_minutes` setting. Past the deadline no new assignment
+//! starts, running agents are asked to wrap up and stopped if they have not
+//! within `WRAP_UP_GRACE`, and the run skips the user's confirmation: the
+//! hub summarizes what was finished, the summary is marked as timed out and
+//! the report is written as for any completed run.
+
+use std::time::{Duration, Instant};
+
+use crate::acp::orchestrator::format_duration;
+use crate::config::AppConfig;
+use crate::db::task_run_repo;
+use crate::i18n::{self, Locale, Msg};
+use crate::state::AppState;
+use crate::telemetry;
+
+pub const WRAP_UP_PROMPT: &str = "The time for this task is up. Stop working now: briefly state what you completed, \
+     what remains, and your result so far.";
+
+/// Time running agents get to wrap up after the deadline.
+pub const WRAP_UP_GRACE: Duration = Duration::from_secs(120);
+
+/// Time the hub gets to summarize a timed-out run before the agents'
+/// outputs are summarized without it.
+pub const SUMMARY_GRACE: Duration = Duration::from_secs(120);
+
+/// Limit of a run: its own when set, else the setting; 0 sets none.
+fn limit(config: &AppConfig, run_minutes: Option<i64>) -> Option<Duration> {
+    let minutes = run_minutes.map_or(config.max_run_minutes, |m| m.max(0) as u64);
+    Some(minutes).filter(|m| *m > 0).map(|m| Duration::from_secs(m * 60))
+}
+
+/// Start the clock of a run that started at `started`. Returns its limit.
+pub async fn begin(state: &AppState, task_run_id: &str, started: Instant) -> Option<Duration> {
+    let state_clone = state.clone();
+    let trid = task_run_id.to_string();
+    let run_minutes = match telemetry::spawn_blocking(move || task_run_repo::get_task_run(&state_clone, &trid)).await {
+        Ok(Ok(task_run)) => task_run.max_duration_minutes,
+        Ok(Err(e)) => {
+            log::warn!("Failed to read the time limit of run {}: {}", task_run_id, e);
+            None
+        }
+        Err(e) => {
+            log::warn!("Spawn blocking failed: {}", e);
+            None
+        }
+    };
+    let limit = limit(&crate::config::current(state), run_minutes)?;
+    log::info!("Run {} is limited to {}", task_run_id, format_duration(limit.as_millis() as i64));
+    state.run_deadlines.lock().await.insert(task_run_id.to_string(), started + limit);
+    Some(limit)
+}
+
+pub async fn end(state: &AppState, task_run_id: &str) {
+    state.run_deadlines.lock().await.remove(task_run_id);
+}
+
+/// When the run reaches its limit, if it has one.
+pub async fn deadline(state: &AppState, task_run_id: &str) -> Option<Instant> {
+    state.run_deadlines.lock().await.get(task_run_id).copied()
+}
+
+pub async fn passed(state: &AppState, task_run_id: &str) -> bool {
+    deadline(state, task_run_id).await.is_some_and(|d| Instant::now() >= d)
+}
+
+/// What an agent working on an assignment should do at `now`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeadlineCheck {
+    Within,
+    WrapUp,
+    /// The wrap-up grace is over too
+    Stop,
+}
+
+pub fn check(deadline: Instant, now: Instant) -> DeadlineCheck {
+    if now >= deadline + WRAP_UP_GRACE {
+        DeadlineCheck::Stop
+    } else if now >= deadline {
+        DeadlineCheck::WrapUp
+    } else {
+        DeadlineCheck::Within
+    }
+}
+
+/// Extra instructions for the hub summarizing a timed-out run.
+pub fn summary_note(not_started: usize) -> String {
+    format!(
+        "\n\nThe run reached its time limit and was wound down; {} planned assignment(s) did not start \
+         and some outputs may be unfinished. Summarize what was achieved and list what remains.",
+        not_started
+    )
+}
+
+/// Mark a summary as timed out.
+pub fn mark_summary(summary: &str, limit: Duration, not_started: usize, locale: Locale) -> String {
+    let note = i18n::tr(
+        locale,
+        Msg::SummaryTimedOut,
+        &[
+            ("limit", &format_duration(limit.as_millis() as i64)),
+            ("not_started", &not_started.to_string()),
+        ],
+    );
+    format!("> {}\n\n{}", note, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_limit_overrides_the_setting() {
+        let config = AppConfig { max_run_minutes: 30, ..AppConfig::default() };
+        assert_eq!(limit(&config, None), Some(Duration::from_secs(30 * 60)));
+        assert_eq!(limit(&config, Some(5)), Some(Duration::from_secs(5 * 60)));
+        assert_eq!(limit(&config, Some(0)), None);
+        assert_eq!(limit(&AppConfig::default(), None), None);
+    }
+
+    #[test]
+    fn agents_wrap_up_at_the_deadline_and_stop_after_the_grace() {
+        let deadline = Instant::now();
+        assert_eq!(check(deadline, deadline - Duration::from_secs(1)), DeadlineCheck::Within);
+        assert_eq!(check(deadline, deadline), DeadlineCheck::WrapUp);
+        assert_eq!(check(deadline, deadline + WRAP_UP_GRACE), DeadlineCheck::Stop);
+
+        let summary = mark_summary("Done: parser.", Duration::from_secs(1800), 2, Locale::En);
+        assert!(summary.starts_with("> Timed out after "));
+        assert!(summary.contains("2 planned assignment(s) did not start"));
+        assert!(summary.ends_with("\n\nDone: parser."));
+    }
+}
@@ -0,0 +1,615 @@
+//! Intake queue for task runs.
+//!
+//! Interactive, scheduled and chat-tool-triggered runs are enqueued instead
+//! of started directly. At most `max_concurrent_runs` task runs are active
+//! at once (0: no limit); the rest wait, higher priority first and in
+//! arrival order within a priority, unless the user reorders them. A queued
+//! run already has its `pending` task run record and cancellation token, so
+//! cancelling it works as for a running one. Shutdown empties the queue and
+//! the waiting runs start from scratch on the next launch.
+//!
+//! In a workspace with `exclusive_runs` on, a run holds the workspace while
+//! it is active: later runs of the workspace wait with the status
+//! `waiting_for_workspace`, even when a slot is free, unless they were
+//! started with `ignore_workspace_lock`.
+//!
+//! With `preempt_runs` on, a run waiting for a slot that a run of a lower
+//! priority holds gets it at that run's next sequence group: the lower one
+//! pauses there until a slot is free again. Paused runs go on before waiting
+//! runs of the same or a lower priority, in the order they were paused.
+
+use tauri::Emitter;
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+use crate::acp::orchestrator;
+use crate::config;
+use crate::db::{task_run_repo, workspace_repo};
+use crate::error::AppError;
+use crate::events;
+use crate::models::task_run::{QueuedRun, RunPriority, TaskPlan, TaskRun};
+use crate::state::AppState;
+use crate::telemetry;
+
+pub const QUEUE_CHANGED_EVENT: &str = "orchestration:queue_changed";
+
+struct Entry {
+    run: QueuedRun,
+    prompt: String,
+    preset_plan: Option<TaskPlan>,
+    /// Told when the run has ended, or was dropped without running
+    done: Option<oneshot::Sender<()>>,
+}
+
+/// A started run paused for a run of a higher priority.
+struct Paused {
+    run: QueuedRun,
+    /// Told when it may go on
+    resume: oneshot::Sender<()>,
+}
+
+enum Next {
+    Start(Entry),
+    Resume(Paused),
+}
+
+#[derive(Default)]
+pub struct RunQueue {
+    waiting: Vec<Entry>,
+    /// Runs the queue started that have not ended yet, paused ones included
+    started: Vec<QueuedRun>,
+    paused: Vec<Paused>,
+    /// Run asked to pause at its next sequence group
+    preempting: Option<String>,
+}
+
+impl RunQueue {
+    /// Index a run of this priority is inserted at: after every waiting run
+    /// of the same or a higher priority.
+    fn insert_position(&self, priority: RunPriority) -> usize {
+        self.waiting
+            .iter()
+            .rposition(|e| e.run.priority >= priority)
+            .map_or(0, |i| i + 1)
+    }
+
+    fn push(&mut self, entry: Entry) {
+        let position = self.insert_position(entry.run.priority);
+        self.waiting.insert(position, entry);
+    }
+
+    fn contains(&self, task_run_id: &str) -> bool {
+        self.waiting.iter().any(|e| e.run.task_run_id == task_run_id)
+    }
+
+    /// Whether the run holds no slot: it waits or is paused.
+    fn idle(&self, task_run_id: &str) -> bool {
+        self.contains(task_run_id) || self.paused.iter().any(|p| p.run.task_run_id == task_run_id)
+    }
+
+    fn remove(&mut self, task_run_id: &str) -> Option<Entry> {
+        let index = self.waiting.iter().position(|e| e.run.task_run_id == task_run_id)?;
+        Some(self.waiting.remove(index))
+    }
+
+    /// Move a waiting run to `position`, clamped to the end of the queue.
+    fn move_to(&mut self, task_run_id: &str, position: usize) -> bool {
+        let Some(entry) = self.remove(task_run_id) else {
+            return false;
+        };
+        let position = position.min(self.waiting.len());
+        self.waiting.insert(position, entry);
+        true
+    }
+
+    /// Let a waiting run start whether or not its workspace is held. It
+    /// then doesn't hold the workspace either.
+    fn ignore_lock(&mut self, task_run_id: &str) -> bool {
+        let Some(entry) = self.waiting.iter_mut().find(|e| e.run.task_run_id == task_run_id) else {
+            return false;
+        };
+        entry.run.ignore_workspace_lock = true;
+        entry.run.exclusive = false;
+        true
+    }
+
+    /// Whether another active run of its workspace keeps `run` waiting.
+    fn blocked(&self, run: &QueuedRun) -> bool {
+        if run.ignore_workspace_lock || run.workspace_id.is_none() {
+            return false;
+        }
+        self.started
+            .iter()
+            .filter(|r| r.workspace_id == run.workspace_id)
+            .any(|r| r.exclusive || run.exclusive)
+    }
+
+    /// Take the first waiting run that is not blocked, as started.
+    fn take_next(&mut self) -> Option<Entry> {
+        let index = self.waiting.iter().position(|e| !self.blocked(&e.run))?;
+        let entry = self.waiting.remove(index);
+        self.started.push(entry.run.clone());
+        Some(entry)
+    }
+
+    /// The paused run to resume or the waiting run to start next: a paused
+    /// run, if it has at least the priority of the first waiting one.
+    fn next(&mut self) -> Option<Next> {
+        let resumable = (0..self.paused.len()).rev().max_by_key(|&i| self.paused[i].run.priority);
+        let startable = self.waiting.iter().find(|e| !self.blocked(&e.run)).map(|e| e.run.priority);
+        match resumable {
+            Some(i) if startable.map_or(true, |p| self.paused[i].run.priority >= p) => {
+                Some(Next::Resume(self.paused.remove(i)))
+            }
+            _ => self.take_next().map(Next::Start),
+        }
+    }
+
+    /// The active run to pause for the first waiting run that could start:
+    /// the last started of the lowest priority below it. None while another
+    /// run is yet to pause.
+    fn preempt_candidate(&self) -> Option<String> {
+        if self.preempting.is_some() {
+            return None;
+        }
+        let waiting = self.waiting.iter().find(|e| !self.blocked(&e.run))?.run.priority;
+        self.started
+            .iter()
+            .rev()
+            .filter(|r| r.priority < waiting && !self.idle(&r.task_run_id))
+            .min_by_key(|r| r.priority)
+            .map(|r| r.task_run_id.clone())
+    }
+
+    /// Change the priority of a waiting or started run.
+    fn set_priority(&mut self, task_run_id: &str, priority: RunPriority) -> bool {
+        if let Some(mut entry) = self.remove(task_run_id) {
+            entry.run.priority = priority;
+            self.push(entry);
+            return true;
+        }
+        let Some(run) = self.started.iter_mut().find(|r| r.task_run_id == task_run_id) else {
+            return false;
+        };
+        run.priority = priority;
+        if let Some(paused) = self.paused.iter_mut().find(|p| p.run.task_run_id == task_run_id) {
+            paused.run.priority = priority;
+        }
+        true
+    }
+
+    /// A started run has ended.
+    fn finish(&mut self, task_run_id: &str) -> Option<QueuedRun> {
+        if self.preempting.as_deref() == Some(task_run_id) {
+            self.preempting = None;
+        }
+        self.paused.retain(|p| p.run.task_run_id != task_run_id);
+        let index = self.started.iter().position(|r| r.task_run_id == task_run_id)?;
+        Some(self.started.remove(index))
+    }
+
+    /// Update which waiting runs wait for their workspace. Returns the runs
+    /// that started waiting for it and the ones that stopped.
+    fn mark_waiting(&mut self) -> (Vec<String>, Vec<String>) {
+        let blocked: Vec<bool> = self.waiting.iter().map(|e| self.blocked(&e.run)).collect();
+        let (mut waiting, mut freed) = (Vec::new(), Vec::new());
+        for (entry, blocked) in self.waiting.iter_mut().zip(blocked) {
+            if entry.run.waiting_for_workspace != blocked {
+                entry.run.waiting_for_workspace = blocked;
+                let ids = if blocked { &mut waiting } else { &mut freed };
+                ids.push(entry.run.task_run_id.clone());
+            }
+        }
+        (waiting, freed)
+    }
+
+    pub fn list(&self) -> Vec<QueuedRun> {
+        self.waiting.iter().map(|e| e.run.clone()).collect()
+    }
+}
+
+async fn emit_changed(app: &tauri::AppHandle, state: &AppState) {
+    let runs = state.run_queue.lock().await.list();
+    let _ = app.emit(QUEUE_CHANGED_EVENT, runs);
+}
+
+/// Queue a created task run to orchestrate in the background.
+pub async fn enqueue(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run: &TaskRun,
+    priority: RunPriority,
+    prompt: String,
+    preset_plan: Option<TaskPlan>,
+    ignore_workspace_lock: bool,
+) {
+    let run = queued_run(task_run, priority, ignore_workspace_lock);
+    push(app, state, run, prompt, preset_plan, None, CancellationToken::new()).await;
+}
+
+/// Queue a created task run and wait until it has ended or was dropped.
+/// `cancel_token` becomes the run's cancellation token, so a caller can
+/// hand in a child of its own.
+pub async fn enqueue_and_wait(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    task_run: &TaskRun,
+    priority: RunPriority,
+    prompt: String,
+    cancel_token: CancellationToken,
+) {
+    let (done, finished) = oneshot::channel();
+    push(app, state, queued_run(task_run, priority, false), prompt, None, Some(done), cancel_token).await;
+    let _ = finished.await;
+}
+
+fn queued_run(task_run: &TaskRun, priority: RunPriority, ignore_workspace_lock: bool) -> QueuedRun {
+    QueuedRun {
+        task_run_id: task_run.id.clone(),
+        title: task_run.title.clone(),
+        workspace_id: task_run.workspace_id.clone(),
+        priority,
+        queued_at: chrono::Utc::now().to_rfc3339(),
+        exclusive: false,
+        ignore_workspace_lock,
+        waiting_for_workspace: false,
+    }
+}
+
+/// Whether the workspace runs one orchestration at a time.
+async fn exclusive_workspace(state: &AppState, workspace_id: Option<&str>) -> bool {
+    let Some(ws_id) = workspace_id.map(str::to_string) else {
+        return false;
+    };
+    let state = state.clone();
+    let workspace = telemetry::spawn_blocking(move || workspace_repo::get_workspace(&state, &ws_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+        .and_then(|r| r);
+    match workspace {
+        Ok(workspace) => workspace.exclusive_runs,
+        Err(e) => {
+            log::warn!("Failed to load workspace for its run lock: {}", e);
+            false
+        }
+    }
+}
+
+async fn push(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    mut run: QueuedRun,
+    prompt: String,
+    preset_plan: Option<TaskPlan>,
+    done: Option<oneshot::Sender<()>>,
+    cancel_token: CancellationToken,
+) {
+    {
+        let mut tokens = state.active_task_runs.lock().await;
+        tokens.insert(run.task_run_id.clone(), cancel_token);
+    }
+    run.exclusive = !run.ignore_workspace_lock && exclusive_workspace(state, run.workspace_id.as_deref()).await;
+    state.run_queue.lock().await.push(Entry { run, prompt, preset_plan, done });
+    emit_changed(app, state).await;
+    dispatch(app, state);
+}
+
+/// Start waiting runs while fewer than the configured limit are active.
+/// Runs started outside the queue, such as resumed ones, count too, though
+/// only runs the queue started hold their workspace.
+pub fn dispatch(app: &tauri::AppHandle, state: &AppState) {
+    let (app, state) = (app.clone(), state.clone());
+    tokio::spawn(async move {
+        let settings = config::current(&state);
+        let limit = settings.max_concurrent_runs;
+        let mut queue = state.run_queue.lock().await;
+        let (mut entries, mut resumed) = (Vec::new(), Vec::new());
+        {
+            let tokens = state.active_task_runs.lock().await;
+            let mut active = tokens.keys().filter(|id| !queue.idle(id)).count();
+            while limit == 0 || active < limit {
+                match queue.next() {
+                    Some(Next::Start(entry)) => entries.push(entry),
+                    Some(Next::Resume(paused)) => resumed.push(paused),
+                    None => break,
+                }
+                active += 1;
+            }
+            if limit > 0 && active >= limit && settings.preempt_runs {
+                if let Some(id) = queue.preempt_candidate() {
+                    log::info!("Asking task run {} to pause for a run of a higher priority", id);
+                    queue.preempting = Some(id);
+                }
+            }
+        }
+
+        // Record the status while the queue is held, so a run started by a
+        // later dispatch can't have it overwritten
+        let (waiting, freed) = queue.mark_waiting();
+        let marked = !waiting.is_empty() || !freed.is_empty();
+        // Only statuses that were stored are announced
+        let mut updated: Vec<(String, &str)> = Vec::new();
+        if marked {
+            let state_clone = state.clone();
+            let (w, f) = (waiting.clone(), freed.clone());
+            let stored = telemetry::spawn_blocking(move || {
+                (
+                    task_run_repo::update_task_run_statuses(&state_clone, &w, "waiting_for_workspace"),
+                    task_run_repo::update_task_run_statuses(&state_clone, &f, "pending"),
+                )
+            })
+            .await;
+            match stored {
+                Ok((waiting_stored, freed_stored)) => {
+                    for (ids, status, stored) in
+                        [(waiting, "waiting_for_workspace", waiting_stored), (freed, "pending", freed_stored)]
+                    {
+                        match stored {
+                            Ok(()) => updated.extend(ids.into_iter().map(|id| (id, status))),
+                            Err(e) => log::warn!("Failed to mark queued runs {}: {}", status, e),
+                        }
+                    }
+                }
+                Err(e) => log::warn!("Failed to update the status of queued runs: {}", e),
+            }
+        }
+        drop(queue);
+
+        for (id, status) in updated {
+            events::emit(&app, &events::TASK_RUN_UPDATED, serde_json::json!({ "taskRunId": id, "status": status }));
+        }
+        for paused in resumed {
+            let _ = paused.resume.send(());
+        }
+        let started = !entries.is_empty();
+        for entry in entries {
+            tokio::spawn(run(app.clone(), state.clone(), entry));
+        }
+        if started || marked {
+            emit_changed(&app, &state).await;
+        }
+    });
+}
+
+fn emit_lock(app: &tauri::AppHandle, topic: &'static events::Topic, run: &QueuedRun) {
+    events::emit(app, topic, serde_json::json!({
+        "workspaceId": run.workspace_id,
+        "taskRunId": run.task_run_id,
+    }));
+}
+
+async fn run(app: tauri::AppHandle, state: AppState, entry: Entry) {
+    let Entry { run, prompt, preset_plan, done } = entry;
+    let cancelled = {
+        let mut tokens = state.active_task_runs.lock().await;
+        match tokens.get(&run.task_run_id) {
+            Some(token) if !token.is_cancelled() => false,
+            Some(_) => {
+                tokens.remove(&run.task_run_id);
+                true
+            }
+            None => true,
+        }
+    };
+    if cancelled {
+        log::info!("Queued task run {} was cancelled before it started", run.task_run_id);
+    } else {
+        if run.exclusive {
+            emit_lock(&app, &events::WORKSPACE_LOCKED, &run);
+        }
+        orchestrator::run_orchestration(
+            app.clone(),
+            state.clone(),
+            run.task_run_id.clone(),
+            prompt,
+            run.workspace_id.clone(),
+            preset_plan,
+        )
+        .await;
+    }
+    state.run_queue.lock().await.finish(&run.task_run_id);
+    if run.exclusive && !cancelled {
+        emit_lock(&app, &events::WORKSPACE_UNLOCKED, &run);
+    }
+    if let Some(done) = done {
+        let _ = done.send(());
+    }
+    dispatch(&app, &state);
+}
+
+/// Take a run out of the queue without starting it. Returns false when it
+/// was not waiting. Its cancellation token is left to the caller.
+pub async fn remove(app: &tauri::AppHandle, state: &AppState, task_run_id: &str) -> bool {
+    let removed = state.run_queue.lock().await.remove(task_run_id).is_some();
+    if removed {
+        emit_changed(app, state).await;
+    }
+    removed
+}
+
+/// Move a waiting run to `position` (0 starts next). Returns false when it
+/// was not waiting.
+pub async fn move_to(app: &tauri::AppHandle, state: &AppState, task_run_id: &str, position: usize) -> bool {
+    let moved = state.run_queue.lock().await.move_to(task_run_id, position);
+    if moved {
+        emit_changed(app, state).await;
+    }
+    moved
+}
+
+/// Called by a started run at each sequence group: when it was asked to
+/// make room for a run of a higher priority, pause until it may go on or is
+/// cancelled.
+pub async fn yield_if_preempted(app: &tauri::AppHandle, state: &AppState, task_run_id: &str) {
+    let (resume, resumed) = oneshot::channel();
+    let preempted_by = {
+        let mut queue = state.run_queue.lock().await;
+        if queue.preempting.as_deref() != Some(task_run_id) {
+            return;
+        }
+        queue.preempting = None;
+        let Some(run) = queue.started.iter().find(|r| r.task_run_id == task_run_id).cloned() else {
+            return;
+        };
+        let by = queue.waiting.iter().find(|e| !queue.blocked(&e.run)).map(|e| e.run.task_run_id.clone());
+        queue.paused.push(Paused { run, resume });
+        by
+    };
+    log::info!("Task run {} paused for a run of a higher priority", task_run_id);
+    events::emit(app, &events::RUN_PREEMPTED, serde_json::json!({
+        "taskRunId": task_run_id,
+        "byTaskRunId": preempted_by,
+    }));
+    dispatch(app, state);
+
+    let cancel = state.active_task_runs.lock().await.get(task_run_id).cloned().unwrap_or_default();
+    tokio::select! {
+        _ = cancel.cancelled() => {
+            state.run_queue.lock().await.paused.retain(|p| p.run.task_run_id != task_run_id);
+        }
+        _ = resumed => {
+            log::info!("Task run {} goes on after being preempted", task_run_id);
+            events::emit(app, &events::RUN_PREEMPTION_ENDED, serde_json::json!({ "taskRunId": task_run_id }));
+        }
+    }
+}
+
+/// Change the priority of a waiting or started run. Returns false when the
+/// queue has no such run.
+pub async fn set_priority(app: &tauri::AppHandle, state: &AppState, task_run_id: &str, priority: RunPriority) -> bool {
+    let found = state.run_queue.lock().await.set_priority(task_run_id, priority);
+    if found {
+        emit_changed(app, state).await;
+        dispatch(app, state);
+    }
+    found
+}
+
+/// Start a waiting run as soon as a slot is free, even while its workspace
+/// is held. Returns false when it was not waiting.
+pub async fn ignore_lock(app: &tauri::AppHandle, state: &AppState, task_run_id: &str) -> bool {
+    let found = state.run_queue.lock().await.ignore_lock(task_run_id);
+    if found {
+        dispatch(app, state);
+    }
+    found
+}
+
+/// Empty the queue on shutdown. The runs keep their `pending` or
+/// `waiting_for_workspace` status.
+pub async fn clear(state: &AppState) -> usize {
+    let waiting = std::mem::take(&mut state.run_queue.lock().await.waiting);
+    let mut tokens = state.active_task_runs.lock().await;
+    for entry in &waiting {
+        tokens.remove(&entry.run.task_run_id);
+    }
+    waiting.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, priority: RunPriority) -> Entry {
+        let run = QueuedRun {
+            task_run_id: id.into(),
+            title: id.into(),
+            workspace_id: None,
+            priority,
+            queued_at: String::new(),
+            exclusive: false,
+            ignore_workspace_lock: false,
+            waiting_for_workspace: false,
+        };
+        Entry { run, prompt: String::new(), preset_plan: None, done: None }
+    }
+
+    fn in_workspace(id: &str, workspace_id: &str, exclusive: bool) -> Entry {
+        let mut entry = entry(id, RunPriority::Interactive);
+        entry.run.workspace_id = Some(workspace_id.into());
+        entry.run.exclusive = exclusive;
+        entry
+    }
+
+    fn ids(queue: &RunQueue) -> Vec<String> {
+        queue.list().into_iter().map(|r| r.task_run_id).collect()
+    }
+
+    #[test]
+    fn orders_by_priority_then_arrival_and_reorders() {
+        let mut queue = RunQueue::default();
+        queue.push(entry("chat", RunPriority::ChatTool));
+        queue.push(entry("sched-1", RunPriority::Scheduled));
+        queue.push(entry("ui-1", RunPriority::Interactive));
+        queue.push(entry("sched-2", RunPriority::Scheduled));
+        queue.push(entry("ui-2", RunPriority::Interactive));
+        assert_eq!(ids(&queue), ["ui-1", "ui-2", "sched-1", "sched-2", "chat"]);
+
+        assert!(queue.move_to("chat", 0));
+        assert!(queue.move_to("ui-1", 99));
+        assert_eq!(ids(&queue), ["chat", "ui-2", "sched-1", "sched-2", "ui-1"]);
+        assert!(!queue.move_to("missing", 0));
+
+        assert!(queue.remove("sched-1").is_some());
+        assert!(!queue.contains("sched-1"));
+        assert_eq!(queue.waiting.len(), 4);
+    }
+
+    #[test]
+    fn exclusive_runs_hold_their_workspace() {
+        let mut queue = RunQueue::default();
+        queue.push(in_workspace("a-1", "a", true));
+        queue.push(in_workspace("a-2", "a", true));
+        queue.push(in_workspace("b-1", "b", false));
+        let mut forced = in_workspace("a-3", "a", false);
+        forced.run.ignore_workspace_lock = true;
+        queue.push(forced);
+
+        assert_eq!(queue.take_next().map(|e| e.run.task_run_id).as_deref(), Some("a-1"));
+        assert_eq!(queue.take_next().map(|e| e.run.task_run_id).as_deref(), Some("b-1"));
+        assert_eq!(queue.take_next().map(|e| e.run.task_run_id).as_deref(), Some("a-3"));
+        assert!(queue.take_next().is_none());
+        assert_eq!(queue.mark_waiting(), (vec!["a-2".to_string()], Vec::new()));
+        assert_eq!(queue.mark_waiting(), (Vec::new(), Vec::new()));
+
+        assert!(queue.finish("a-1").is_some());
+        assert!(queue.finish("a-3").is_some());
+        assert_eq!(queue.mark_waiting(), (Vec::new(), vec!["a-2".to_string()]));
+        assert_eq!(queue.take_next().map(|e| e.run.task_run_id).as_deref(), Some("a-2"));
+
+        queue.push(in_workspace("a-4", "a", true));
+        assert!(queue.take_next().is_none());
+        assert!(queue.ignore_lock("a-4"));
+        assert_eq!(queue.take_next().map(|e| e.run.task_run_id).as_deref(), Some("a-4"));
+        assert!(!queue.ignore_lock("a-4"));
+    }
+
+    #[test]
+    fn preempts_the_last_started_lowest_priority_run_and_resumes_fairly() {
+        let mut queue = RunQueue::default();
+        for (id, priority) in [("chat", RunPriority::ChatTool), ("sched", RunPriority::Scheduled), ("chat-2", RunPriority::ChatTool)] {
+            queue.push(entry(id, priority));
+            assert!(queue.take_next().is_some());
+        }
+        assert_eq!(queue.preempt_candidate(), None);
+
+        queue.push(entry("urgent", RunPriority::Urgent));
+        assert_eq!(queue.preempt_candidate().as_deref(), Some("chat-2"));
+        queue.preempting = Some("chat-2".into());
+        assert_eq!(queue.preempt_candidate(), None);
+
+        let (resume, _) = oneshot::channel();
+        let run = queue.started[2].clone();
+        queue.paused.push(Paused { run, resume });
+        queue.preempting = None;
+        assert!(queue.idle("chat-2"));
+        queue.push(entry("chat-3", RunPriority::ChatTool));
+        assert!(matches!(queue.next(), Some(Next::Start(e)) if e.run.task_run_id == "urgent"));
+        assert!(matches!(queue.next(), Some(Next::Resume(p)) if p.run.task_run_id == "chat-2"));
+        assert!(matches!(queue.next(), Some(Next::Start(e)) if e.run.task_run_id == "chat-3"));
+
+        assert!(queue.set_priority("sched", RunPriority::Urgent));
+        assert!(!queue.set_priority("missing", RunPriority::Urgent));
+        assert!(queue.finish("chat").is_some());
+    }
+}
@@ -0,0 +1,91 @@
+//! Per-run scratch directories.
+//!
+//! A plan with `isolated` set gets `output/<task_run_id>/scratch`. Its agents
+//! are started with `AGENT_HUB_SCRATCH_DIR` pointing there and every prompt
+//! of the run tells them to keep temporary and intermediate files in it, so
+//! the workspace only receives the files the task is meant to change. The
+//! directory existing is what marks a run as isolated, so resumed runs keep
+//! their sandbox. When the run ends it is deleted unless the
+//! `keep_run_scratch` setting is on.
+
+use std::path::PathBuf;
+
+use crate::config;
+use crate::db::migrations::get_output_dir;
+use crate::db::task_run_repo;
+use crate::error::AppResult;
+use crate::state::AppState;
+use crate::telemetry;
+
+/// Environment variable agent processes of an isolated run find the directory in.
+pub const SCRATCH_DIR_ENV: &str = "AGENT_HUB_SCRATCH_DIR";
+
+/// Run statuses after which the scratch directory is no longer needed.
+const FINISHED_STATUSES: &[&str] = &["completed", "failed", "cancelled", "needs_review"];
+
+pub fn scratch_dir(task_run_id: &str) -> PathBuf {
+    get_output_dir().join(task_run_id).join("scratch")
+}
+
+/// Create the run's scratch directory.
+pub fn prepare(task_run_id: &str) -> AppResult<PathBuf> {
+    let dir = scratch_dir(task_run_id);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// The run's scratch directory if the run is isolated.
+pub fn active(task_run_id: &str) -> Option<PathBuf> {
+    Some(scratch_dir(task_run_id)).filter(|dir| dir.is_dir())
+}
+
+/// Note telling an agent where its scratch files go.
+pub fn instructions(dir: &std::path::Path) -> String {
+    format!(
+        "<scratch_directory>\nThis task runs in isolation. Write temporary files, intermediate results, \
+         downloads and build experiments to {} (also in the {} environment variable), not to the workspace. \
+         Only change workspace files the task asks you to change.\n</scratch_directory>",
+        dir.display(),
+        SCRATCH_DIR_ENV
+    )
+}
+
+/// Delete the scratch directory of a run that has finished, unless the
+/// settings keep it. Runs that may still resume keep theirs.
+pub async fn finish(state: &AppState, task_run_id: &str) {
+    let Some(dir) = active(task_run_id) else {
+        return;
+    };
+    let state_clone = state.clone();
+    let trid = task_run_id.to_string();
+    let status = match telemetry::spawn_blocking(move || task_run_repo::get_task_run(&state_clone, &trid)).await {
+        Ok(Ok(task_run)) => task_run.status,
+        _ => return,
+    };
+    if !FINISHED_STATUSES.contains(&status.as_str()) {
+        return;
+    }
+    if config::current(state).keep_run_scratch {
+        log::info!("Keeping scratch directory of task {} at {}", task_run_id, dir.display());
+        return;
+    }
+    match tokio::fs::remove_dir_all(&dir).await {
+        Ok(()) => log::info!("Removed scratch directory of task {}", task_run_id),
+        Err(e) => log::warn!("Failed to remove scratch directory {}: {}", dir.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scratch_directory_lives_in_the_run_output() {
+        let dir = scratch_dir("run-1");
+        assert!(dir.ends_with("output/run-1/scratch"));
+        let note = instructions(&dir);
+        assert!(note.contains(&dir.display().to_string()));
+        assert!(note.contains(SCRATCH_DIR_ENV));
+        assert_eq!(active(&format!("missing-{}", uuid::Uuid::new_v4())), None);
+    }
+}
@@ -0,0 +1,25 @@
+//! The assignment each agent of a run is working on.
+//!
+//! An agent has one process per run and works on one prompt at a time, so
+//! records made while a prompt streams, such as timeline events, can name
+//! the assignment the prompt belongs to. Prompts outside an assignment, such
+//! as the control hub's planning and reviews, have none.
+
+use crate::state::AppState;
+
+/// Attribute the agent's prompts in a run to `assignment_id` until `end`.
+pub async fn begin(state: &AppState, task_run_id: &str, agent_id: &str, assignment_id: &str) {
+    let mut running = state.running_assignments.lock().await;
+    running.insert((task_run_id.to_string(), agent_id.to_string()), assignment_id.to_string());
+}
+
+pub async fn end(state: &AppState, task_run_id: &str, agent_id: &str) {
+    let mut running = state.running_assignments.lock().await;
+    running.remove(&(task_run_id.to_string(), agent_id.to_string()));
+}
+
+/// The assignment the agent is working on in the run.
+pub async fn current(state: &AppState, task_run_id: &str, agent_id: &str) -> Option<String> {
+    let running = state.running_assignments.lock().await;
+    running.get(&(task_run_id.to_string(), agent_id.to_string())).cloned()
+}
@@ -0,0 +1,240 @@
+//! Rolling summaries of chat sessions.
+//!
+//! The agent is asked to fold the messages since the last summary into it,
+//! in a throwaway ACP session so the conversation itself is left alone. The
+//! summary is stored on the session row. When an agent no longer has a
+//! session (it restarted, or `session/load` failed) the new ACP session
+//! starts from the summary instead of from nothing. Sessions are summarized
+//! on request and after every `session_summary_every_messages` messages.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+use crate::acp::{client, orchestrator};
+use crate::config;
+use crate::db::{message_repo, session_repo};
+use crate::error::{AppError, AppResult};
+use crate::models::message::ChatMessage;
+use crate::models::session::Session;
+use crate::state::AppState;
+
+/// Most characters of new messages sent to be summarized; the newest are
+/// kept when they do not all fit.
+const MAX_TRANSCRIPT_CHARS: usize = 24_000;
+const SUMMARY_TIMEOUT_SECS: u64 = 120;
+
+/// The lock a chat prompt or summary holds on the agent's message channel.
+pub async fn agent_lock(state: &AppState, agent_id: &str) -> Arc<Mutex<()>> {
+    let mut locks = state.chat_agent_locks.lock().await;
+    locks.entry(agent_id.to_string()).or_default().clone()
+}
+
+fn build_summary_prompt(previous: Option<&str>, messages: &[ChatMessage]) -> String {
+    let mut budget = MAX_TRANSCRIPT_CHARS;
+    let mut lines = Vec::new();
+    for message in messages.iter().rev() {
+        let text = message.text();
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let line = format!("{}: {}", message.role, text);
+        let len = line.chars().count();
+        if len > budget {
+            break;
+        }
+        budget -= len;
+        lines.push(line);
+    }
+    let omitted = messages.iter().filter(|m| !m.text().trim().is_empty()).count() - lines.len();
+    lines.reverse();
+
+    let mut prompt = String::from(
+        "Summarize the conversation below so it can be continued without its history. Keep the goals, \
+         decisions, facts, file names and open questions; drop small talk. Reply with the summary only, \
+         in at most 400 words.\n\n",
+    );
+    if let Some(previous) = previous {
+        prompt.push_str(&format!("Summary of the conversation before these messages:\n{}\n\n", previous));
+    }
+    if omitted > 0 {
+        prompt.push_str(&format!("({} earlier message(s) omitted)\n", omitted));
+    }
+    prompt.push_str(&format!("Messages:\n{}", lines.join("\n\n")));
+    prompt
+}
+
+/// Preamble recalling a session's summary, for a new ACP session.
+pub fn resume_context(session: &Session) -> Option<String> {
+    let summary = session.summary.as_deref()?.trim();
+    if summary.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "Summary of this conversation so far, from before the agent session was restarted:\n\n{}\n\n---",
+        summary
+    ))
+}
+
+/// Read the reply to `request_id` off the agent's message channel.
+async fn collect_reply(state: &AppState, agent_id: &str, request_id: i64) -> AppResult<String> {
+    let deadline = Instant::now() + Duration::from_secs(SUMMARY_TIMEOUT_SECS);
+    let mut reply = String::new();
+    loop {
+        let received = {
+            let mut processes = state.agent_processes.lock().await;
+            match processes.get_mut(agent_id) {
+                Some(process) => process.message_rx.try_recv(),
+                None => return Err(AppError::AgentNotRunning(agent_id.to_string())),
+            }
+        };
+        let msg = match received {
+            Ok(msg) => msg,
+            Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
+                if Instant::now() >= deadline {
+                    return Err(AppError::Timeout(format!(
+                        "Timeout ({}s) waiting for the session summary",
+                        SUMMARY_TIMEOUT_SECS
+                    )));
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                continue;
+            }
+            Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
+                return Err(AppError::AgentNotRunning(agent_id.to_string()));
+            }
+        };
+        if msg.get("method").and_then(|m| m.as_str()) == Some("session/update") {
+            let update = msg.get("params").and_then(|p| p.get("update"));
+            if update.and_then(|u| u.get("sessionUpdate")).and_then(|s| s.as_str()) == Some("agent_message_chunk") {
+                if let Some(text) = update.and_then(|u| u.get("content")).and_then(|c| c.get("text")).and_then(|t| t.as_str()) {
+                    reply.push_str(text);
+                }
+            }
+            continue;
+        }
+        if msg.get("id").and_then(|id| id.as_i64()) != Some(request_id) {
+            continue;
+        }
+        if let Some(error) = msg.get("error") {
+            return Err(AppError::Internal(format!("The agent could not summarize the session: {}", error)));
+        }
+        return Ok(reply);
+    }
+}
+
+/// Ask the session's agent to fold the messages since the last summary into
+/// it. Returns the session unchanged when there are no new messages.
+pub async fn summarize_session(state: &AppState, session_id: &str) -> AppResult<Session> {
+    let state_clone = state.clone();
+    let sid = session_id.to_string();
+    let (session, messages, cwd) = tokio::task::spawn_blocking(move || {
+        let session = session_repo::get_session(&state_clone, &sid)?;
+        let messages = message_repo::get_messages(&state_clone, &sid)?;
+        let cwd = orchestrator::resolve_orchestrator_working_directory(&state_clone, session.workspace_id.as_deref());
+        Ok::<_, AppError>((session, messages, cwd))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    let covered = (session.summary_message_count.max(0) as usize).min(messages.len());
+    if covered == messages.len() {
+        return Ok(session);
+    }
+    let prompt = build_summary_prompt(session.summary.as_deref(), &messages[covered..]);
+
+    let agent_id = session.agent_id.clone();
+    let lock = agent_lock(state, &agent_id).await;
+    let _guard = lock.lock().await;
+    let request_id = chrono::Utc::now().timestamp_millis();
+    let acp_session_id = {
+        let mut processes = state.agent_processes.lock().await;
+        let process = processes
+            .get_mut(&agent_id)
+            .ok_or_else(|| AppError::AgentNotRunning(agent_id.clone()))?;
+        let (acp_session_id, _models) = client::create_session(process, &cwd).await?;
+        client::send_prompt(process, &acp_session_id, &prompt, request_id).await?;
+        acp_session_id
+    };
+    let reply = collect_reply(state, &agent_id, request_id).await;
+    if let Some(process) = state.agent_processes.lock().await.get_mut(&agent_id) {
+        if let Err(e) = client::end_session(process, &acp_session_id).await {
+            log::warn!("Failed to end summary session {}: {}", acp_session_id, e);
+        }
+    }
+    let summary = reply?.trim().to_string();
+    if summary.is_empty() {
+        return Err(AppError::Internal("The agent returned an empty summary".into()));
+    }
+
+    log::info!("Summarized {} message(s) of session {}", messages.len() - covered, session_id);
+    let state_clone = state.clone();
+    let sid = session_id.to_string();
+    let count = messages.len() as i64;
+    tokio::task::spawn_blocking(move || session_repo::update_session_summary(&state_clone, &sid, &summary, count))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Summarize the session when enough messages have come in since the last
+/// summary. Failures are logged; the next reply tries again.
+pub async fn summarize_if_due(state: &AppState, session_id: &str) {
+    let every = config::current(state).session_summary_every_messages;
+    if every == 0 {
+        return;
+    }
+    let state_clone = state.clone();
+    let sid = session_id.to_string();
+    let due = tokio::task::spawn_blocking(move || {
+        let session = session_repo::get_session(&state_clone, &sid)?;
+        let count = message_repo::get_messages(&state_clone, &sid)?.len() as i64;
+        Ok::<_, AppError>(count - session.summary_message_count >= every as i64)
+    })
+    .await;
+    match due {
+        Ok(Ok(true)) => {
+            if let Err(e) = summarize_session(state, session_id).await {
+                log::warn!("Failed to summarize session {}: {}", session_id, e);
+            }
+        }
+        Ok(Ok(false)) => {}
+        Ok(Err(e)) => log::warn!("Failed to check the summary of session {}: {}", session_id, e),
+        Err(e) => log::warn!("Spawn blocking failed: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, text: &str) -> ChatMessage {
+        ChatMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: "s".into(),
+            role: role.into(),
+            content_json: serde_json::json!([{ "type": "text", "text": text }]).to_string(),
+            tool_calls_json: None,
+            created_at: String::new(),
+            pinned_at: None,
+            reactions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn builds_a_rolling_summary_prompt_from_the_newest_messages() {
+        let long = "x".repeat(MAX_TRANSCRIPT_CHARS);
+        let messages = vec![
+            message("User", &long),
+            message("User", "Rename the config loader"),
+            message("Agent", "Renamed it to load_config in src/config.rs"),
+            message("Agent", "  "),
+        ];
+        let prompt = build_summary_prompt(Some("The user is refactoring config handling."), &messages);
+        assert!(prompt.contains("before these messages:\nThe user is refactoring config handling."));
+        assert!(prompt.contains("(1 earlier message(s) omitted)"));
+        assert!(prompt.ends_with("User: Rename the config loader\n\nAgent: Renamed it to load_config in src/config.rs"));
+        assert!(!prompt.contains(&long));
+    }
+}
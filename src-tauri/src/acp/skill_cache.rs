@@ -0,0 +1,111 @@
+//! Per-workspace cache of skill discovery scans.
+//!
+//! Each workspace, and runs without one, keeps the scan of its working
+//! directory in memory and in the `skill_cache` table, so a restart does not
+//! repeat it. A cached scan is used while the working directory is the same
+//! and the skills fingerprint, the modification times of the skills
+//! directories and their SKILL.md files, has not changed. Otherwise the
+//! directories are scanned again.
+
+use std::collections::HashMap;
+
+use crate::acp::skill_discovery::{self, SkillDiscoveryResult};
+use crate::db::skill_cache_repo::{self, StoredScan};
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use crate::telemetry;
+
+#[derive(Default)]
+pub struct SkillCache {
+    /// Keyed by workspace id, '' for runs without a workspace
+    scans: HashMap<String, StoredScan>,
+}
+
+fn is_fresh(scan: &StoredScan, cwd: &str, fingerprint: &str) -> bool {
+    scan.working_directory == cwd && scan.fingerprint == fingerprint
+}
+
+/// The skills available to a workspace's runs. Scans when no fresh scan is
+/// cached or `force` is set; the flag returned tells whether it did.
+pub async fn skills(
+    state: &AppState,
+    workspace_id: Option<&str>,
+    cwd: &str,
+    force: bool,
+) -> AppResult<(SkillDiscoveryResult, bool)> {
+    let key = workspace_id.unwrap_or_default().to_string();
+    // Held throughout, so concurrent runs wait for one scan instead of each scanning
+    let mut cache = state.discovered_skills.lock().await;
+
+    let cwd_clone = cwd.to_string();
+    let fingerprint = telemetry::spawn_blocking(move || skill_discovery::fingerprint(&cwd_clone))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    if !force {
+        if let Some(scan) = cache.scans.get(&key).filter(|s| is_fresh(s, cwd, &fingerprint)) {
+            return Ok((scan.result.clone(), false));
+        }
+        let (state_clone, key_clone) = (state.clone(), key.clone());
+        let stored = telemetry::spawn_blocking(move || skill_cache_repo::get_scan(&state_clone, &key_clone))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to load cached skills for '{}': {}", key, e);
+                None
+            });
+        if let Some(scan) = stored.filter(|s| is_fresh(s, cwd, &fingerprint)) {
+            let result = scan.result.clone();
+            cache.scans.insert(key, scan);
+            return Ok((result, false));
+        }
+    }
+
+    let cwd_clone = cwd.to_string();
+    let result = telemetry::spawn_blocking(move || skill_discovery::discover_skills(&cwd_clone))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let scan = StoredScan { working_directory: cwd.to_string(), fingerprint, result: result.clone() };
+    let (state_clone, key_clone) = (state.clone(), key.clone());
+    let saved = telemetry::spawn_blocking(move || skill_cache_repo::save_scan(&state_clone, &key_clone, &scan).map(|_| scan))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    match saved {
+        Ok(scan) => {
+            cache.scans.insert(key, scan);
+        }
+        Err(e) => log::warn!("Failed to store skills scanned for '{}': {}", key, e),
+    }
+    Ok((result, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_changes_when_a_skill_is_added_or_edited() {
+        let cwd = std::env::temp_dir().join(format!("skill-cache-{}", uuid::Uuid::new_v4()));
+        let skill = cwd.join("skills").join("review");
+        std::fs::create_dir_all(&skill).unwrap();
+        let cwd_str = cwd.to_string_lossy().to_string();
+
+        let empty = skill_discovery::fingerprint(&cwd_str);
+        assert_eq!(skill_discovery::fingerprint(&cwd_str), empty);
+        std::fs::write(skill.join("SKILL.md"), "---\nname: review\n---\n").unwrap();
+        let written = skill_discovery::fingerprint(&cwd_str);
+        assert_ne!(written, empty);
+
+        let scan = StoredScan {
+            working_directory: cwd_str.clone(),
+            fingerprint: written.clone(),
+            result: skill_discovery::discover_skills(&cwd_str),
+        };
+        assert!(is_fresh(&scan, &cwd_str, &written));
+        assert!(!is_fresh(&scan, "/elsewhere", &written));
+
+        std::fs::create_dir_all(cwd.join("skills").join("deploy")).unwrap();
+        assert!(!is_fresh(&scan, &cwd_str, &skill_discovery::fingerprint(&cwd_str)));
+        let _ = std::fs::remove_dir_all(&cwd);
+    }
+}
@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -55,33 +55,19 @@ pub fn discover_skills(cwd: &str) -> SkillDiscoveryResult {
     let mut scanned_directories: Vec<String> = Vec::new();
     let mut seen_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
 
-    // 1. Working directory: {cwd}/skills/
-    let project_skills_dir = Path::new(cwd).join("skills");
-    if project_skills_dir.is_dir() {
-        scanned_directories.push(project_skills_dir.to_string_lossy().to_string());
-        let entries = scan_skills_directory(&project_skills_dir, "project");
-        for entry in entries {
+    for (skills_dir, location) in skills_roots(cwd) {
+        if !skills_dir.is_dir() {
+            continue;
+        }
+        scanned_directories.push(skills_dir.to_string_lossy().to_string());
+        for entry in scan_skills_directory(&skills_dir, location) {
+            // Project skills take priority (dedup by ID)
             if seen_ids.insert(entry.skill.id.clone()) {
                 skills.push(entry);
             }
         }
     }
 
-    // 2. Config directory: ~/.iaagenthub/skills/
-    if let Some(home) = dirs::home_dir() {
-        let global_skills_dir = home.join(".iaagenthub").join("skills");
-        if global_skills_dir.is_dir() {
-            scanned_directories.push(global_skills_dir.to_string_lossy().to_string());
-            let entries = scan_skills_directory(&global_skills_dir, "user");
-            for entry in entries {
-                // Project skills take priority (dedup by ID)
-                if seen_ids.insert(entry.skill.id.clone()) {
-                    skills.push(entry);
-                }
-            }
-        }
-    }
-
     log::info!(
         "Skill discovery: found {} skills from {} directories",
         skills.len(),
@@ -95,6 +81,47 @@ pub fn discover_skills(cwd: &str) -> SkillDiscoveryResult {
     }
 }
 
+/// The `skills/` directories a scan of `cwd` reads, project ones first.
+fn skills_roots(cwd: &str) -> Vec<(PathBuf, &'static str)> {
+    let mut roots = vec![(Path::new(cwd).join("skills"), "project")];
+    if let Some(home) = dirs::home_dir() {
+        roots.push((home.join(".iaagenthub").join("skills"), "user"));
+    }
+    roots
+}
+
+/// Fingerprint of what a scan of `cwd` reads: the modification times of the
+/// `skills/` directories, of every skill directory and of its SKILL.md.
+/// Adding, removing or editing a skill changes it, so a cached scan with
+/// the same fingerprint is still accurate.
+pub fn fingerprint(cwd: &str) -> String {
+    fn stamp(stamps: &mut String, path: &Path) {
+        let modified = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or_else(|| "-".to_string(), |d| d.as_nanos().to_string());
+        stamps.push_str(&format!("{}\0{}\n", path.display(), modified));
+    }
+
+    let mut stamps = String::new();
+    for (skills_dir, _) in skills_roots(cwd) {
+        stamp(&mut stamps, &skills_dir);
+        let Ok(read_dir) = std::fs::read_dir(&skills_dir) else { continue };
+        let mut dirs: Vec<PathBuf> = read_dir.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.is_dir()).collect();
+        dirs.sort();
+        for dir in dirs {
+            stamp(&mut stamps, &dir);
+            stamp(&mut stamps, &dir.join("SKILL.md"));
+        }
+    }
+    ring::digest::digest(&ring::digest::SHA256, stamps.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 /// Scan a `skills/` directory. Each immediate subdirectory is a skill.
 fn scan_skills_directory(skills_dir: &Path, location: &str) -> Vec<SkillDirEntry> {
     let mut entries = Vec::new();
@@ -0,0 +1,109 @@
+//! Remote agent execution over SSH.
+//!
+//! An agent with `ssh_host` set has its ACP command run on that host: the
+//! app spawns `ssh` and the JSON-RPC stream flows over the connection's
+//! stdin and stdout exactly as it does for a local process. The command and
+//! arguments are the agent's configured ones, since paths the provisioner
+//! resolves locally mean nothing on the remote machine. The agent's extra
+//! environment is passed through `env` because sshd only accepts the
+//! variables its `AcceptEnv` allows.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::models::agent::AgentConfig;
+
+/// Where an agent's ACP command runs remotely.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SshTarget {
+    pub host: String,
+    pub user: Option<String>,
+    pub key_path: Option<String>,
+}
+
+impl SshTarget {
+    /// The agent's remote target, if it has one.
+    pub fn of(agent: &AgentConfig) -> Option<Self> {
+        let host = agent.ssh_host.as_deref().map(str::trim).filter(|h| !h.is_empty())?;
+        Some(Self {
+            host: host.to_string(),
+            user: agent.ssh_user.clone().filter(|u| !u.trim().is_empty()),
+            key_path: agent.ssh_key_path.clone().filter(|k| !k.trim().is_empty()),
+        })
+    }
+
+    /// `user@host`, or the bare host when the SSH config picks the user.
+    pub fn destination(&self) -> String {
+        match &self.user {
+            Some(user) => format!("{}@{}", user, self.host),
+            None => self.host.clone(),
+        }
+    }
+}
+
+/// Quote a word for a POSIX shell.
+pub fn shell_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Arguments for `ssh` that run `command` on the target with stdio forwarded.
+/// Batch mode makes a connection that needs a password or a host key
+/// confirmation fail instead of waiting for input nobody can give.
+pub fn ssh_args(target: &SshTarget, command: &str, args: &[String], env: &HashMap<String, String>) -> Vec<String> {
+    let mut ssh = vec![
+        "-T".to_string(),
+        "-o".into(),
+        "BatchMode=yes".into(),
+        "-o".into(),
+        "ServerAliveInterval=30".into(),
+    ];
+    if let Some(key) = &target.key_path {
+        ssh.extend(["-i".into(), expand_home(key).to_string_lossy().to_string()]);
+    }
+    ssh.extend([target.destination(), "--".into()]);
+
+    let mut vars: Vec<_> = env.iter().collect();
+    vars.sort();
+    let mut remote: Vec<String> = Vec::new();
+    if !vars.is_empty() {
+        remote.push("env".into());
+        remote.extend(vars.into_iter().map(|(k, v)| shell_quote(&format!("{}={}", k, v))));
+    }
+    remote.push(shell_quote(command));
+    remote.extend(args.iter().map(|a| shell_quote(a)));
+    ssh.push(remote.join(" "));
+    ssh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_a_quoted_remote_command() {
+        let target = SshTarget { host: "build-box".into(), user: Some("ci".into()), key_path: Some("/keys/id".into()) };
+        let env = HashMap::from([("API_KEY".to_string(), "a b'c".to_string())]);
+        let args = ssh_args(&target, "npx", &["@zed-industries/claude-code-acp".into(), "--flag=x y".into()], &env);
+        assert_eq!(&args[..7], ["-T", "-o", "BatchMode=yes", "-o", "ServerAliveInterval=30", "-i", "/keys/id"]);
+        assert_eq!(args[7], "ci@build-box");
+        assert_eq!(args[8], "--");
+        assert_eq!(args[9], r"env 'API_KEY=a b'\''c' npx @zed-industries/claude-code-acp '--flag=x y'");
+
+        let bare = SshTarget { host: "box".into(), user: None, key_path: None };
+        assert_eq!(ssh_args(&bare, "agent", &[], &HashMap::new()), ["-T", "-o", "BatchMode=yes", "-o", "ServerAliveInterval=30", "box", "--", "agent"]);
+        assert_eq!(shell_quote(""), "''");
+    }
+}
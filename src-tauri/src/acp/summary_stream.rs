@@ -0,0 +1,116 @@
+//! Streaming of a run's final summary.
+//!
+//! The hub's summary of a run can take a while. While it is written its
+//! text streams as `SUMMARY_CHUNK` events, and the user may stop it early:
+//! accepting keeps the text so far as the summary, cancelling drops it for
+//! the built-in summary of the agents' outputs. Either way the run completes.
+
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{AppError, AppResult};
+use crate::events;
+use crate::state::AppState;
+
+/// A summary being written by the hub agent `agent_id`.
+#[derive(Debug)]
+pub struct SummaryStream {
+    agent_id: String,
+    text: String,
+    cancel: CancellationToken,
+    /// Set once the user accepted (true) or cancelled (false) the summary
+    accepted: Option<bool>,
+}
+
+/// What the user made of a summary they stopped.
+#[derive(Debug, PartialEq)]
+pub enum StoppedSummary {
+    /// Keep this text as the summary
+    Accepted(String),
+    /// Use the built-in summary instead
+    Cancelled,
+}
+
+impl SummaryStream {
+    fn stopped(self) -> Option<StoppedSummary> {
+        let accepted = self.accepted?;
+        let text = self.text.trim();
+        Some(if accepted && !text.is_empty() {
+            StoppedSummary::Accepted(text.to_string())
+        } else {
+            StoppedSummary::Cancelled
+        })
+    }
+}
+
+/// Start streaming the run's summary written by `agent_id`. The returned
+/// token is cancelled when the user stops the summary.
+pub async fn start(state: &AppState, task_run_id: &str, agent_id: &str) -> CancellationToken {
+    let cancel = CancellationToken::new();
+    let stream = SummaryStream {
+        agent_id: agent_id.to_string(),
+        text: String::new(),
+        cancel: cancel.clone(),
+        accepted: None,
+    };
+    state.summary_streams.lock().await.insert(task_run_id.to_string(), stream);
+    cancel
+}
+
+/// Forward a chunk of the agent's reply if it is writing the run's summary.
+pub async fn push_chunk(app: &tauri::AppHandle, state: &AppState, task_run_id: &str, agent_id: &str, text: &str) {
+    let mut streams = state.summary_streams.lock().await;
+    let Some(stream) = streams.get_mut(task_run_id) else {
+        return;
+    };
+    if stream.agent_id != agent_id || stream.accepted.is_some() {
+        return;
+    }
+    stream.text.push_str(text);
+    drop(streams);
+    events::emit(app, &events::SUMMARY_CHUNK, serde_json::json!({
+        "taskRunId": task_run_id,
+        "agentId": agent_id,
+        "text": text,
+    }));
+}
+
+/// End the run's summary stream; None unless the user stopped it.
+pub async fn finish(state: &AppState, task_run_id: &str) -> Option<StoppedSummary> {
+    state.summary_streams.lock().await.remove(task_run_id)?.stopped()
+}
+
+/// Accept the summary written so far, or cancel it.
+pub async fn stop(state: &AppState, task_run_id: &str, accept: bool) -> AppResult<()> {
+    let mut streams = state.summary_streams.lock().await;
+    let stream = streams
+        .get_mut(task_run_id)
+        .ok_or_else(|| AppError::NotFound(format!("No summary being written for task run {}", task_run_id)))?;
+    stream.accepted.get_or_insert(accept);
+    stream.cancel.cancel();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(text: &str, accepted: Option<bool>) -> SummaryStream {
+        SummaryStream {
+            agent_id: "hub".into(),
+            text: text.into(),
+            cancel: CancellationToken::new(),
+            accepted,
+        }
+    }
+
+    #[test]
+    fn stopped_summaries_keep_the_text_so_far_when_accepted() {
+        assert_eq!(stream("## Results\nAll done ", None).stopped(), None);
+        assert_eq!(
+            stream("## Results\nAll done ", Some(true)).stopped(),
+            Some(StoppedSummary::Accepted("## Results\nAll done".into()))
+        );
+        assert_eq!(stream("## Results", Some(false)).stopped(), Some(StoppedSummary::Cancelled));
+        assert_eq!(stream(" \n", Some(true)).stopped(), Some(StoppedSummary::Cancelled));
+    }
+}
@@ -0,0 +1,390 @@
+//! Orchestration templates: freeze the validated plan of a successful run so
+//! later runs can reuse it without asking the control hub to plan again.
+
+use std::collections::HashMap;
+
+use crate::acp::{duplicate_runs, fallback_planner, orchestrator, run_queue};
+use crate::db::{agent_repo, task_run_repo, template_repo};
+use crate::error::{AppError, AppResult};
+use crate::models::agent::AgentConfig;
+use crate::models::task_run::{PlannedAssignment, StartedTaskRun, TaskPlan, TaskRun};
+use crate::models::template::{
+    AgentPin, OrchestrationTemplate, PromoteRunOptions, RunTemplateRequest, TemplateAssignment,
+    TemplateVariable,
+};
+use crate::state::AppState;
+
+/// Build a template from a completed run's stored plan.
+///
+/// Variables are the values passed in `options.variables` plus quoted text,
+/// paths and URLs found in the user prompt; each occurrence in the prompt and
+/// task descriptions becomes a `{{name}}` placeholder.
+pub fn build_template(task_run: &TaskRun, options: PromoteRunOptions) -> AppResult<OrchestrationTemplate> {
+    if task_run.status != "completed" {
+        return Err(AppError::InvalidRequest(format!(
+            "Only completed runs can become templates (task run {} is {})",
+            task_run.id, task_run.status
+        )));
+    }
+    let plan_json = task_run.task_plan_json.as_deref().ok_or_else(|| {
+        AppError::InvalidRequest(format!("Task run {} has no stored plan", task_run.id))
+    })?;
+    let plan: TaskPlan = serde_json::from_str(plan_json)?;
+
+    let variables = extract_variables(&task_run.user_prompt, &options.variables);
+    let assignments: Vec<TemplateAssignment> = plan
+        .assignments
+        .iter()
+        .map(|a| TemplateAssignment {
+            agent: match a.matched_skills.first() {
+                Some(skill) if options.pin_by_tag => AgentPin::Tag { tag: skill.clone() },
+                _ => AgentPin::Id { agent_id: a.agent_id.clone() },
+            },
+            source_agent_id: a.agent_id.clone(),
+            task_description: templatize(&a.task_description, &variables),
+            sequence_order: a.sequence_order,
+            depends_on: a.depends_on.clone(),
+            matched_skills: a.matched_skills.clone(),
+            working_directory: a.working_directory.clone(),
+            output_file: a.output_file.clone(),
+            max_tokens_out: a.max_tokens_out,
+            max_cost: a.max_cost,
+            overrides: a.overrides.clone(),
+        })
+        .collect();
+
+    let name = options
+        .name
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| task_run.title.clone());
+
+    Ok(OrchestrationTemplate {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        description: options.description,
+        source_task_run_id: Some(task_run.id.clone()),
+        prompt_template: templatize(&task_run.user_prompt, &variables),
+        analysis: plan.analysis,
+        assignments_json: serde_json::to_string(&assignments)?,
+        variables_json: serde_json::to_string(&variables)?,
+        workspace_id: task_run.workspace_id.clone(),
+        created_at: String::new(),
+        updated_at: String::new(),
+    })
+}
+
+/// Resolve a template into the user prompt and plan of a new run.
+/// `agents` are the agents of the run's workspace.
+pub fn instantiate_template(
+    template: &OrchestrationTemplate,
+    agents: &[AgentConfig],
+    values: &HashMap<String, String>,
+) -> AppResult<(String, TaskPlan)> {
+    let variables = template.variables();
+    if let Some(unknown) = values.keys().find(|k| !variables.iter().any(|v| &v.name == *k)) {
+        return Err(AppError::InvalidRequest(format!(
+            "Template {} has no variable '{}'",
+            template.name, unknown
+        )));
+    }
+    let resolved_values: HashMap<&str, &str> = variables
+        .iter()
+        .map(|v| {
+            let value = values.get(&v.name).unwrap_or(&v.default_value);
+            (v.name.as_str(), value.as_str())
+        })
+        .collect();
+
+    let template_assignments = template.assignments();
+    let mut agent_map: HashMap<String, String> = HashMap::new();
+    for a in &template_assignments {
+        let agent_id = resolve_pin(&a.agent, &a.source_agent_id, agents)?;
+        agent_map.insert(a.source_agent_id.clone(), agent_id);
+    }
+
+    let assignments = template_assignments
+        .iter()
+        .map(|a| PlannedAssignment {
+            agent_id: agent_map[&a.source_agent_id].clone(),
+            task_description: fill(&a.task_description, &resolved_values),
+            sequence_order: a.sequence_order,
+            depends_on: a
+                .depends_on
+                .iter()
+                .map(|id| agent_map.get(id).cloned().unwrap_or_else(|| id.clone()))
+                .collect(),
+            matched_skills: a.matched_skills.clone(),
+            selection_reason: format!("From template '{}'", template.name),
+            working_directory: a.working_directory.clone(),
+            output_file: a.output_file.clone(),
+            max_tokens_out: a.max_tokens_out,
+            max_cost: a.max_cost,
+            confidence: None,
+            assumptions: Vec::new(),
+            overrides: a.overrides.clone(),
+            attachments: Vec::new(),
+            dependency_sections: Default::default(),
+        })
+        .collect();
+
+    Ok((
+        fill(&template.prompt_template, &resolved_values),
+        TaskPlan {
+            analysis: template.analysis.clone(),
+            assignments,
+            isolated: false,
+        },
+    ))
+}
+
+/// Create a task run from a template and start orchestrating it in the background.
+pub async fn start_template_run(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    request: RunTemplateRequest,
+) -> AppResult<StartedTaskRun> {
+    if state.shutdown_token.is_cancelled() {
+        return Err(AppError::InvalidRequest("Application is shutting down".into()));
+    }
+
+    let priority = request.priority;
+    let (prompt, plan, title, hub_id, workspace_id) = {
+        let state_clone = state.clone();
+        tokio::task::spawn_blocking(move || {
+            let template = template_repo::get_template(&state_clone, &request.template_id)?;
+            let workspace_id = request.workspace_id.or_else(|| template.workspace_id.clone());
+            let hub_id = fallback_planner::hub_id_for(&state_clone, workspace_id.as_deref())?;
+            let agents = agent_repo::list_agents(&state_clone, workspace_id.as_deref())?;
+            let (prompt, plan) = instantiate_template(&template, &agents, &request.variables)?;
+
+            let title = if request.title.is_empty() {
+                template.name.clone()
+            } else {
+                request.title
+            };
+            Ok::<_, AppError>((prompt, plan, title, hub_id, workspace_id))
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??
+    };
+
+    let task_run_id = uuid::Uuid::new_v4().to_string();
+    if let Some(existing) = duplicate_runs::claim(state, workspace_id.as_deref(), &prompt, &task_run_id).await {
+        return duplicate_runs::existing(state, existing).await;
+    }
+    let created = {
+        let state_clone = state.clone();
+        let trid = task_run_id.clone();
+        let p = prompt.clone();
+        let ws_id = workspace_id.clone();
+        tokio::task::spawn_blocking(move || {
+            task_run_repo::create_task_run(&state_clone, &trid, &title, &p, &hub_id, "pending", ws_id.as_deref())
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+        .and_then(|r| r)
+    };
+    let task_run = match created {
+        Ok(task_run) => task_run,
+        Err(e) => {
+            duplicate_runs::settle(state, workspace_id.as_deref(), &prompt, &task_run_id, false).await;
+            return Err(e);
+        }
+    };
+
+    run_queue::enqueue(app, state, &task_run, priority, prompt.clone(), Some(plan), false).await;
+    duplicate_runs::settle(state, workspace_id.as_deref(), &prompt, &task_run_id, true).await;
+
+    Ok(StartedTaskRun { task_run, duplicate: false })
+}
+
+/// Pick the agent for a pin. A tag pin prefers the source agent when it still matches.
+fn resolve_pin(pin: &AgentPin, source_agent_id: &str, agents: &[AgentConfig]) -> AppResult<String> {
+    match pin {
+        AgentPin::Id { agent_id } => agents
+            .iter()
+            .find(|a| &a.id == agent_id && a.is_enabled)
+            .map(|a| a.id.clone())
+            .ok_or_else(|| {
+                AppError::InvalidRequest(format!("Agent {} pinned by the template is not available", agent_id))
+            }),
+        AgentPin::Tag { tag } => {
+            let tag = tag.to_lowercase();
+            let candidates: Vec<&AgentConfig> = agents
+                .iter()
+                .filter(|a| a.is_enabled && !a.is_control_hub)
+                .filter(|a| {
+                    orchestrator::resolve_agent_skills(a)
+                        .iter()
+                        .any(|s| s.id.to_lowercase() == tag || s.name.to_lowercase() == tag)
+                })
+                .collect();
+            candidates
+                .iter()
+                .find(|a| a.id == source_agent_id)
+                .or_else(|| candidates.first())
+                .map(|a| a.id.clone())
+                .ok_or_else(|| AppError::InvalidRequest(format!("No enabled agent has the skill '{}'", tag)))
+        }
+    }
+}
+
+/// Collect template variables: explicit ones first, then quoted text, paths
+/// and URLs from the user prompt. Values shorter than two characters are ignored.
+fn extract_variables(user_prompt: &str, explicit: &HashMap<String, String>) -> Vec<TemplateVariable> {
+    let mut variables: Vec<TemplateVariable> = explicit
+        .iter()
+        .filter(|(_, value)| value.chars().count() >= 2)
+        .map(|(name, value)| TemplateVariable {
+            name: name.clone(),
+            default_value: value.clone(),
+        })
+        .collect();
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut next_index = 1;
+    for value in prompt_value_candidates(user_prompt) {
+        if value.chars().count() < 2 || variables.iter().any(|v| v.default_value == value) {
+            continue;
+        }
+        let name = loop {
+            let candidate = format!("var_{}", next_index);
+            next_index += 1;
+            if !variables.iter().any(|v| v.name == candidate) {
+                break candidate;
+            }
+        };
+        variables.push(TemplateVariable { name, default_value: value });
+    }
+    variables
+}
+
+fn prompt_value_candidates(text: &str) -> Vec<String> {
+    const QUOTES: [(char, char); 4] = [('"', '"'), ('`', '`'), ('“', '”'), ('「', '」')];
+
+    let mut values = Vec::new();
+    for (open, close) in QUOTES {
+        let mut rest = text;
+        while let Some(start) = rest.find(open) {
+            let after = &rest[start + open.len_utf8()..];
+            let Some(end) = after.find(close) else { break };
+            values.push(after[..end].trim().to_string());
+            rest = &after[end + close.len_utf8()..];
+        }
+    }
+
+    for token in text.split_whitespace() {
+        let token = token.trim_matches(|c: char| ",.;:()[]<>\"'`，。；：（）".contains(c));
+        if token.contains("://") || (token.contains('/') && token.len() >= 3) {
+            values.push(token.to_string());
+        }
+    }
+    values
+}
+
+/// Replace variable values with `{{name}}` placeholders, longest values first
+/// so a value contained in another does not split it. Values are swapped for
+/// NUL-delimited markers first so no value can match inside a placeholder.
+fn templatize(text: &str, variables: &[TemplateVariable]) -> String {
+    let mut ordered: Vec<(usize, &TemplateVariable)> = variables.iter().enumerate().collect();
+    ordered.sort_by_key(|(_, v)| std::cmp::Reverse(v.default_value.len()));
+    let marked = ordered.iter().fold(text.to_string(), |acc, (i, v)| {
+        acc.replace(&v.default_value, &format!("\0{}\0", i))
+    });
+    variables.iter().enumerate().fold(marked, |acc, (i, v)| {
+        acc.replace(&format!("\0{}\0", i), &format!("{{{{{}}}}}", v.name))
+    })
+}
+
+fn fill(text: &str, values: &HashMap<&str, &str>) -> String {
+    fill_placeholders(text, values).0
+}
+
+/// Substitute `{{name}}` placeholders; a placeholder can carry a default as
+/// `{{name|default text}}`. Placeholders with neither a value nor a default
+/// are left in place and returned by name, each once.
+pub fn fill_placeholders<'a>(text: &'a str, values: &HashMap<&str, &str>) -> (String, Vec<&'a str>) {
+    let mut out = String::with_capacity(text.len());
+    let mut missing: Vec<&str> = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + len + 2;
+        out.push_str(&rest[..start]);
+        let inner = &rest[start + 2..start + 2 + len];
+        let (name, default) = match inner.split_once('|') {
+            Some((name, default)) => (name.trim(), Some(default)),
+            None => (inner.trim(), None),
+        };
+        match values.get(name).copied().or(default) {
+            Some(value) => out.push_str(value),
+            None => {
+                out.push_str(&rest[start..end]);
+                if !missing.contains(&name) {
+                    missing.push(name);
+                }
+            }
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    (out, missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(name: &str, default_value: &str) -> TemplateVariable {
+        TemplateVariable { name: name.into(), default_value: default_value.into() }
+    }
+
+    fn pairs(variables: &[TemplateVariable]) -> Vec<(&str, &str)> {
+        variables.iter().map(|v| (v.name.as_str(), v.default_value.as_str())).collect()
+    }
+
+    #[test]
+    fn test_extract_explicit_then_prompt_values() {
+        let explicit = HashMap::from([("team".to_string(), "ops".to_string()), ("x".to_string(), "a".to_string())]);
+        let prompt = r#"Summarize "Q3 report" from /data/q3.csv, then post it to https://example.com/hook."#;
+        let variables = extract_variables(prompt, &explicit);
+        assert_eq!(
+            pairs(&variables),
+            [("team", "ops"), ("var_1", "Q3 report"), ("var_2", "/data/q3.csv"), ("var_3", "https://example.com/hook")]
+        );
+    }
+
+    #[test]
+    fn test_extract_skips_known_values_and_taken_names() {
+        let explicit = HashMap::from([("var_1".to_string(), "/src".to_string())]);
+        let variables = extract_variables(r#"Lint /src and "/docs" and `x`"#, &explicit);
+        assert_eq!(pairs(&variables), [("var_1", "/src"), ("var_2", "/docs")]);
+    }
+
+    #[test]
+    fn test_templatize_longest_value_first() {
+        let variables = [variable("dir", "src"), variable("file", "src/main.rs")];
+        let text = templatize("Edit src/main.rs, then check src", &variables);
+        assert_eq!(text, "Edit {{file}}, then check {{dir}}");
+
+        let values = HashMap::from([("dir", "lib"), ("file", "lib/mod.rs")]);
+        assert_eq!(fill(&text, &values), "Edit lib/mod.rs, then check lib");
+    }
+
+    #[test]
+    fn test_fill_leaves_unknown_placeholders() {
+        let values = HashMap::from([("name", "world")]);
+        assert_eq!(fill("Hello {{name}}, {{other}} {name}", &values), "Hello world, {{other}} {name}");
+    }
+
+    #[test]
+    fn test_fill_placeholders_uses_defaults_and_reports_missing() {
+        let values = HashMap::from([("repo", "hub")]);
+        let (text, missing) = fill_placeholders("{{ repo }} on {{branch|main}} by {{a}} and {{a}}", &values);
+        assert_eq!(text, "hub on main by {{a}} and {{a}}");
+        assert_eq!(missing, ["a"]);
+    }
+}
@@ -0,0 +1,96 @@
+//! Records an agent prompt's streamed output as timeline events.
+//!
+//! Text and thought chunks are buffered and written as one event per kind
+//! change, tool call, or `StreamBuffer` flush, so a long stream does not
+//! become one row per token. Events go through the DB write queue.
+
+use crate::db::assignment_event_repo::{self, EventSource};
+use crate::db::stream_buffer::StreamBuffer;
+use crate::state::AppState;
+
+/// Tool output longer than this is truncated in the recorded event.
+const MAX_TOOL_OUTPUT_CHARS: usize = 2000;
+
+pub struct TimelineRecorder {
+    state: AppState,
+    source: EventSource,
+    kind: &'static str,
+    buffer: StreamBuffer,
+}
+
+impl TimelineRecorder {
+    /// Recorder for an agent's prompt in a run, as part of `assignment_id`
+    /// when the prompt belongs to an assignment.
+    pub fn new(state: &AppState, task_run_id: &str, assignment_id: Option<&str>, agent_id: &str) -> Self {
+        Self {
+            state: state.clone(),
+            source: EventSource {
+                task_run_id: task_run_id.to_string(),
+                assignment_id: assignment_id.map(str::to_string),
+                agent_id: agent_id.to_string(),
+            },
+            kind: "text",
+            buffer: StreamBuffer::default(),
+        }
+    }
+
+    /// Append a streamed chunk; `kind` is "text" or "thought".
+    pub async fn push_chunk(&mut self, kind: &'static str, text: &str) {
+        if kind != self.kind {
+            self.flush().await;
+            self.kind = kind;
+        }
+        if self.buffer.push(text) {
+            self.flush().await;
+        }
+    }
+
+    /// Record a tool_call or tool_call_update from a session/update notification.
+    pub async fn push_tool_call(&mut self, update: &serde_json::Value) {
+        self.flush().await;
+
+        let raw_output = update.get("rawOutput").map(|o| {
+            let text = match o {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            if text.chars().count() > MAX_TOOL_OUTPUT_CHARS {
+                let truncated: String = text.chars().take(MAX_TOOL_OUTPUT_CHARS).collect();
+                format!("{}...(truncated)", truncated)
+            } else {
+                text
+            }
+        });
+        let content = serde_json::json!({
+            "name": update.get("name"),
+            "title": update.get("title"),
+            "kind": update.get("kind"),
+            "rawInput": update.get("rawInput"),
+            "rawOutput": raw_output,
+        })
+        .to_string();
+        let tool_call_id = update.get("toolCallId").and_then(|v| v.as_str()).map(|s| s.to_string());
+        let tool_status = update.get("status").and_then(|v| v.as_str()).map(|s| s.to_string());
+
+        self.insert("tool_call", content, tool_call_id, tool_status);
+    }
+
+    /// Record a note about the prompt, such as a tool it goes without.
+    pub async fn push_notice(&mut self, text: String) {
+        self.flush().await;
+        self.insert("notice", text, None, None);
+    }
+
+    /// Write any buffered text as an event.
+    pub async fn flush(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let content = self.buffer.take();
+        self.insert(self.kind, content, None, None);
+    }
+
+    fn insert(&self, kind: &'static str, content: String, tool_call_id: Option<String>, tool_status: Option<String>) {
+        assignment_event_repo::queue_event(&self.state, &self.source, kind, content, tool_call_id, tool_status);
+    }
+}
@@ -0,0 +1,130 @@
+//! Size cap for tool-call payloads forwarded to the frontend.
+//!
+//! An agent's `rawInput` / `rawOutput` can be megabytes (whole files, command
+//! output). Payloads larger than MAX_INLINE_BYTES are cut to a preview in the
+//! `orchestration:agent_tool_call` event; the full JSON is written to
+//! `output/<task_run_id>/tool_calls/` and registered as a task artifact whose
+//! id goes along in the event, so the UI can load it with `get_tool_payload`.
+
+use std::path::PathBuf;
+
+use crate::db::artifact_repo;
+use crate::db::migrations::get_output_dir;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+pub const TOOL_PAYLOAD_ARTIFACT: &str = "tool_payload";
+
+/// Serialized size above which a payload is replaced by a preview.
+const MAX_INLINE_BYTES: usize = 16 * 1024;
+const PREVIEW_CHARS: usize = 2000;
+
+/// A payload as sent in the event.
+#[derive(Debug, Default)]
+pub struct InlinePayload {
+    pub value: Option<serde_json::Value>,
+    /// Artifact holding the full payload when `value` is a preview
+    pub artifact_id: Option<String>,
+}
+
+/// The payload text kept in the event for an oversized payload.
+fn preview(value: &serde_json::Value, serialized: &str) -> String {
+    let text = value.as_str().unwrap_or(serialized);
+    let head: String = text.chars().take(PREVIEW_CHARS).collect();
+    format!("{}…(truncated, {} bytes)", head, serialized.len())
+}
+
+fn payload_path(task_run_id: &str, tool_call_id: &str, field: &str) -> PathBuf {
+    let name: String = tool_call_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let name = if name.is_empty() { uuid::Uuid::new_v4().to_string() } else { name };
+    get_output_dir()
+        .join(task_run_id)
+        .join("tool_calls")
+        .join(format!("{}-{}.json", name, field))
+}
+
+/// Write the full payload and register (or refresh) its artifact.
+async fn store(
+    state: &AppState,
+    task_run_id: &str,
+    agent_id: &str,
+    tool_call_id: &str,
+    field: &str,
+    serialized: String,
+) -> AppResult<String> {
+    let path = payload_path(task_run_id, tool_call_id, field);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let size_bytes = serialized.len() as i64;
+    tokio::fs::write(&path, serialized).await?;
+
+    let state_clone = state.clone();
+    let trid = task_run_id.to_string();
+    let agid = agent_id.to_string();
+    let path_str = path.to_string_lossy().to_string();
+    let artifact = tokio::task::spawn_blocking(move || {
+        // Updates for one tool call resend the same payload field
+        match artifact_repo::find_artifact_by_path(&state_clone, &trid, &path_str)? {
+            Some(existing) => {
+                artifact_repo::set_artifact_size(&state_clone, &existing.id, size_bytes)?;
+                Ok(existing)
+            }
+            None => artifact_repo::create_artifact(
+                &state_clone, &trid, None, Some(&agid), TOOL_PAYLOAD_ARTIFACT, &path_str, size_bytes, None,
+            ),
+        }
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    Ok(artifact.id)
+}
+
+/// Cap `value` for the event. Outside a task run there is nowhere to keep the
+/// full payload, so it is only truncated.
+pub async fn inline(
+    state: &AppState,
+    task_run_id: Option<&str>,
+    agent_id: &str,
+    tool_call_id: &str,
+    field: &str,
+    value: Option<&serde_json::Value>,
+) -> InlinePayload {
+    let Some(value) = value else {
+        return InlinePayload::default();
+    };
+    let serialized = value.to_string();
+    if serialized.len() <= MAX_INLINE_BYTES {
+        return InlinePayload { value: Some(value.clone()), artifact_id: None };
+    }
+
+    let preview = serde_json::Value::String(preview(value, &serialized));
+    let artifact_id = match task_run_id {
+        Some(trid) => match store(state, trid, agent_id, tool_call_id, field, serialized).await {
+            Ok(id) => Some(id),
+            Err(e) => {
+                log::warn!("[ToolPayloads] Failed to store {} of tool call {}: {}", field, tool_call_id, e);
+                None
+            }
+        },
+        None => None,
+    };
+    InlinePayload { value: Some(preview), artifact_id }
+}
+
+/// The full payload behind a `tool_payload` artifact.
+pub async fn load(state: &AppState, artifact_id: &str) -> AppResult<serde_json::Value> {
+    let state_clone = state.clone();
+    let id = artifact_id.to_string();
+    let artifact = tokio::task::spawn_blocking(move || artifact_repo::get_artifact(&state_clone, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    if artifact.kind != TOOL_PAYLOAD_ARTIFACT {
+        return Err(AppError::InvalidRequest(format!("Artifact {} is not a tool payload", artifact_id)));
+    }
+    let text = tokio::fs::read_to_string(&artifact.path).await?;
+    Ok(serde_json::from_str(&text)?)
+}
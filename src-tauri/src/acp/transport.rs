@@ -50,7 +50,9 @@ pub async fn send_message(process: &mut AgentProcess, msg: &JsonRpcRequest) -> A
         .flush()
         .await
         .map_err(|e| AppError::Transport(format!("Failed to flush agent stdin: {e}")))?;
+    drop(stdin);
 
+    process.last_used_at = std::time::Instant::now();
     Ok(())
 }
 
@@ -114,7 +116,7 @@ pub async fn receive_response_with_timeout(
                 ));
             }
             Err(_) => {
-                return Err(AppError::Transport(format!(
+                return Err(AppError::Timeout(format!(
                     "Timeout ({}s) waiting for response with id={}",
                     timeout.as_secs(),
                     expected_id
@@ -0,0 +1,471 @@
+//! Web search tool for agents, served over MCP.
+//!
+//! Agent CLIs without built-in browsing can still take on research: with
+//! `web_search.enabled` on, the orchestrator lists a local MCP server in the
+//! `mcpServers` of the sessions it opens for local agents. The server is the
+//! app binary itself started with `--mcp-web-search`, so it ships with every
+//! bundle, given the backend and its key in environment variables. It offers `web_search`, which queries Brave,
+//! Tavily or a SearXNG instance, and `fetch_url`, which returns the text of
+//! a page.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::config;
+use crate::db::agent_repo;
+use crate::error::{AppError, AppResult};
+use crate::models::agent::AgentConfig;
+use crate::state::AppState;
+use crate::telemetry;
+
+pub const SERVER_NAME: &str = "agent-hub-web-search";
+/// Argument of the app binary that serves the tool over stdio instead of
+/// opening the app.
+pub const SERVE_ARG: &str = "--mcp-web-search";
+const PROTOCOL_VERSION: &str = "2024-11-05";
+const BACKENDS: &[&str] = &["brave", "tavily", "searxng"];
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+/// Text of a fetched page returned at most, in characters.
+const MAX_PAGE_CHARS: usize = 20_000;
+
+const ENV_BACKEND: &str = "AGENT_HUB_SEARCH_BACKEND";
+const ENV_API_KEY: &str = "AGENT_HUB_SEARCH_API_KEY";
+const ENV_BASE_URL: &str = "AGENT_HUB_SEARCH_BASE_URL";
+const ENV_MAX_RESULTS: &str = "AGENT_HUB_SEARCH_MAX_RESULTS";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebSearchConfig {
+    pub enabled: bool,
+    /// "brave", "tavily" or "searxng"
+    pub backend: String,
+    /// API key of Brave or Tavily
+    pub api_key: Option<String>,
+    /// SearXNG instance, e.g. "http://localhost:8888"
+    pub base_url: Option<String>,
+    /// Results a search returns at most
+    pub max_results: usize,
+    /// Agents given the tool; empty gives it to every local agent
+    pub agent_ids: Vec<String>,
+}
+
+impl Default for WebSearchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: "brave".into(),
+            api_key: None,
+            base_url: None,
+            max_results: 5,
+            agent_ids: Vec::new(),
+        }
+    }
+}
+
+impl WebSearchConfig {
+    pub fn validate(&self) -> AppResult<()> {
+        let invalid = |message: String| Err(AppError::InvalidRequest(message));
+        if !BACKENDS.contains(&self.backend.as_str()) {
+            return invalid(format!("Unknown web search backend '{}'", self.backend));
+        }
+        if !(1..=20).contains(&self.max_results) {
+            return invalid("Web search max_results must be between 1 and 20".into());
+        }
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.backend == "searxng" {
+            let url = self.base_url.as_deref().unwrap_or_default();
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return invalid("Web search with SearXNG needs the http(s) base_url of the instance".into());
+            }
+        } else if self.api_key.as_deref().map_or(true, |k| k.trim().is_empty()) {
+            return invalid(format!("Web search with {} needs an api_key", self.backend));
+        }
+        server_command().map(|_| ())
+    }
+
+    /// The config the app passed to the server process.
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let defaults = Self::default();
+        Self {
+            enabled: true,
+            backend: var(ENV_BACKEND).unwrap_or(defaults.backend),
+            api_key: var(ENV_API_KEY),
+            base_url: var(ENV_BASE_URL),
+            max_results: var(ENV_MAX_RESULTS).and_then(|v| v.parse().ok()).unwrap_or(defaults.max_results),
+            agent_ids: Vec::new(),
+        }
+    }
+
+    fn env(&self) -> Vec<Value> {
+        let mut vars = vec![(ENV_BACKEND, self.backend.clone()), (ENV_MAX_RESULTS, self.max_results.to_string())];
+        if let Some(key) = &self.api_key {
+            vars.push((ENV_API_KEY, key.clone()));
+        }
+        if let Some(url) = &self.base_url {
+            vars.push((ENV_BASE_URL, url.clone()));
+        }
+        vars.into_iter().map(|(name, value)| json!({ "name": name, "value": value })).collect()
+    }
+}
+
+/// The app binary, which serves the tool when started with `SERVE_ARG`.
+fn server_command() -> AppResult<PathBuf> {
+    std::env::current_exe()
+        .map_err(|e| AppError::Internal(format!("Web search cannot start its server: app binary not found: {}", e)))
+}
+
+/// Whether sessions of `agent` are given the tool. Agents on an SSH host or
+/// in a container can't start the local server, so they are not.
+fn is_wanted(config: &WebSearchConfig, agent: &AgentConfig) -> bool {
+    config.enabled
+        && (config.agent_ids.is_empty() || config.agent_ids.contains(&agent.id))
+        && agent.ssh_host.is_none()
+        && agent.container_image.is_none()
+}
+
+/// The `mcpServers` of a session for `agent`.
+pub fn mcp_servers(config: &WebSearchConfig, agent: &AgentConfig) -> Value {
+    if !is_wanted(config, agent) {
+        return json!([]);
+    }
+    match server_command() {
+        Ok(command) => json!([{
+            "name": SERVER_NAME,
+            "command": command,
+            "args": [SERVE_ARG],
+            "env": config.env(),
+        }]),
+        Err(e) => {
+            log::warn!("{}", e);
+            json!([])
+        }
+    }
+}
+
+/// Why a session of `agent` goes without the tool it should have, if it
+/// does; recorded in the run's timeline.
+pub fn unavailable(config: &WebSearchConfig, agent: &AgentConfig) -> Option<String> {
+    if !is_wanted(config, agent) {
+        return None;
+    }
+    server_command().err().map(|e| e.to_string())
+}
+
+/// The `mcpServers` of a session for the agent `agent_id`.
+pub async fn session_mcp_servers(state: &AppState, agent_id: &str) -> Value {
+    let config = config::current(state).web_search;
+    if !config.enabled {
+        return json!([]);
+    }
+    let state_clone = state.clone();
+    let id = agent_id.to_string();
+    let agent = telemetry::spawn_blocking(move || agent_repo::get_agent(&state_clone, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+        .and_then(|r| r);
+    match agent {
+        Ok(agent) => mcp_servers(&config, &agent),
+        Err(e) => {
+            log::warn!("Failed to load agent {} for its MCP servers: {}", agent_id, e);
+            json!([])
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Server
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+struct SearchResult {
+    title: String,
+    url: String,
+    snippet: String,
+}
+
+fn tools() -> Value {
+    json!([
+        {
+            "name": "web_search",
+            "description": "Search the web. Returns the title, URL and a snippet of each result.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "What to search for" },
+                    "max_results": { "type": "integer", "minimum": 1, "maximum": 20 }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "fetch_url",
+            "description": "Fetch a web page and return its text.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "url": { "type": "string", "description": "http(s) URL of the page" }
+                },
+                "required": ["url"]
+            }
+        }
+    ])
+}
+
+/// Serve the tool over stdio with the config the app passed in the
+/// environment; what the app binary does when started with `SERVE_ARG`.
+pub fn serve_from_env() -> AppResult<()> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(serve_stdio(WebSearchConfig::from_env()))
+}
+
+/// Serve the tools over MCP on stdin and stdout until stdin closes.
+pub async fn serve_stdio(config: WebSearchConfig) -> AppResult<()> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("agent-hub/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| AppError::Transport(format!("HTTP client error: {e}")))?;
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await? {
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        // Notifications, e.g. notifications/initialized, get no response
+        let Some(id) = message.get("id").cloned() else {
+            continue;
+        };
+        let method = message.get("method").and_then(Value::as_str).unwrap_or_default();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let response = match handle(&client, &config, method, &params).await {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => {
+                json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+            }
+        };
+        let mut out = serde_json::to_vec(&response)?;
+        out.push(b'\n');
+        stdout.write_all(&out).await?;
+        stdout.flush().await?;
+    }
+    Ok(())
+}
+
+async fn handle(
+    client: &reqwest::Client,
+    config: &WebSearchConfig,
+    method: &str,
+    params: &Value,
+) -> Result<Value, (i64, String)> {
+    match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": params.get("protocolVersion").and_then(Value::as_str).unwrap_or(PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") },
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => {
+            let name = params.get("name").and_then(Value::as_str).unwrap_or_default();
+            let args = params.get("arguments").cloned().unwrap_or(Value::Null);
+            let text = match name {
+                "web_search" => web_search(client, config, &args).await,
+                "fetch_url" => fetch_url(client, &args).await,
+                _ => return Err((-32602, format!("Unknown tool '{}'", name))),
+            };
+            Ok(match text {
+                Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+                Err(e) => json!({ "content": [{ "type": "text", "text": e.to_string() }], "isError": true }),
+            })
+        }
+        _ => Err((-32601, format!("Method not found: {}", method))),
+    }
+}
+
+async fn get_text(request: reqwest::RequestBuilder) -> AppResult<(String, String)> {
+    let resp = request.send().await.map_err(|e| AppError::Transport(format!("Request error: {e}")))?;
+    if !resp.status().is_success() {
+        return Err(AppError::Transport(format!("HTTP {}", resp.status())));
+    }
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let body = resp.text().await.map_err(|e| AppError::Transport(format!("Response error: {e}")))?;
+    Ok((content_type, body))
+}
+
+async fn web_search(client: &reqwest::Client, config: &WebSearchConfig, args: &Value) -> AppResult<String> {
+    let query = args.get("query").and_then(Value::as_str).map(str::trim).unwrap_or_default();
+    if query.is_empty() {
+        return Err(AppError::InvalidRequest("Query is empty".into()));
+    }
+    let max = args
+        .get("max_results")
+        .and_then(Value::as_u64)
+        .map_or(config.max_results, |n| (n as usize).clamp(1, 20));
+    let key = config.api_key.as_deref().unwrap_or_default();
+
+    let request = match config.backend.as_str() {
+        "brave" => client
+            .get("https://api.search.brave.com/res/v1/web/search")
+            .query(&[("q", query), ("count", &max.to_string())])
+            .header("X-Subscription-Token", key)
+            .header(reqwest::header::ACCEPT, "application/json"),
+        "tavily" => client
+            .post("https://api.tavily.com/search")
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(json!({ "api_key": key, "query": query, "max_results": max }).to_string()),
+        _ => {
+            let base = config.base_url.as_deref().unwrap_or_default().trim_end_matches('/');
+            client.get(format!("{}/search", base)).query(&[("q", query), ("format", "json")])
+        }
+    };
+    let (_, body) = get_text(request).await?;
+    let results = parse_results(&config.backend, &serde_json::from_str(&body)?, max);
+    Ok(format_results(query, &results))
+}
+
+/// The results in a backend's response, at most `max`.
+fn parse_results(backend: &str, response: &Value, max: usize) -> Vec<SearchResult> {
+    let (list, snippet_field) = match backend {
+        "brave" => (response.pointer("/web/results"), "description"),
+        _ => (response.get("results"), "content"),
+    };
+    let field = |item: &Value, name: &str| item.get(name).and_then(Value::as_str).unwrap_or_default().trim().to_string();
+    list.and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|item| SearchResult {
+            title: field(item, "title"),
+            url: field(item, "url"),
+            snippet: html_to_text(&field(item, snippet_field)),
+        })
+        .filter(|r| !r.url.is_empty())
+        .take(max)
+        .collect()
+}
+
+fn format_results(query: &str, results: &[SearchResult]) -> String {
+    if results.is_empty() {
+        return format!("No results for \"{}\".", query);
+    }
+    results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| format!("{}. {}\n   {}\n   {}", i + 1, r.title, r.url, r.snippet))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+async fn fetch_url(client: &reqwest::Client, args: &Value) -> AppResult<String> {
+    let url = args.get("url").and_then(Value::as_str).map(str::trim).unwrap_or_default();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(AppError::InvalidRequest(format!("Not an http(s) URL: '{}'", url)));
+    }
+    let (content_type, body) = get_text(client.get(url)).await?;
+    let text = if content_type.contains("html") { html_to_text(&body) } else { body };
+    let mut truncated: String = text.chars().take(MAX_PAGE_CHARS).collect();
+    if truncated.len() < text.len() {
+        truncated.push_str("\n\n[truncated]");
+    }
+    Ok(truncated)
+}
+
+/// Readable text of an HTML page or fragment.
+fn html_to_text(html: &str) -> String {
+    let hidden = Regex::new(r"(?is)<(script|style|noscript|head)\b.*?</(script|style|noscript|head)>").expect("valid regex");
+    let blocks = Regex::new(r"(?i)<(br|/p|/div|/li|/h[1-6]|/tr)\b[^>]*>").expect("valid regex");
+    let tags = Regex::new(r"(?s)<[^>]*>").expect("valid regex");
+    let spaces = Regex::new(r"[ \t\r\f]+").expect("valid regex");
+    let blank_lines = Regex::new(r"\n\s*\n+").expect("valid regex");
+
+    let text = hidden.replace_all(html, " ");
+    let text = blocks.replace_all(&text, "\n");
+    let text = tags.replace_all(&text, "");
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    let text = spaces.replace_all(&text, " ");
+    let text = blank_lines.replace_all(&text, "\n\n");
+    text.lines().map(str::trim).collect::<Vec<_>>().join("\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_backend_results() {
+        let brave = json!({ "web": { "results": [
+            { "title": "Rust", "url": "https://rust-lang.org", "description": "A <strong>language</strong>" },
+            { "title": "No URL" },
+            { "title": "Docs", "url": "https://doc.rust-lang.org", "description": "" }
+        ] } });
+        let results = parse_results("brave", &brave, 5);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].snippet, "A language");
+        assert_eq!(parse_results("brave", &brave, 1).len(), 1);
+
+        let searxng = json!({ "results": [{ "title": "T", "url": "https://x.io", "content": "snippet" }] });
+        assert_eq!(
+            format_results("q", &parse_results("searxng", &searxng, 5)),
+            "1. T\n   https://x.io\n   snippet"
+        );
+        assert_eq!(format_results("q", &[]), "No results for \"q\".");
+    }
+
+    #[test]
+    fn extracts_page_text_and_validates_config() {
+        let html = "<html><head><title>x</title><style>p{}</style></head><body><h1>Title</h1>\
+                    <p>One &amp; two</p><script>alert(1)</script><p>Three<br>four</p></body></html>";
+        assert_eq!(html_to_text(html), "Title\nOne & two\nThree\nfour");
+
+        let mut config = WebSearchConfig { enabled: true, ..Default::default() };
+        assert!(config.validate().is_err());
+        config.api_key = Some("key".into());
+        assert!(config.validate().is_ok());
+        config.backend = "searxng".into();
+        assert!(config.validate().is_err());
+        config.base_url = Some("http://localhost:8888".into());
+        assert!(config.validate().is_ok());
+        config.backend = "bing".into();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn sessions_start_the_app_binary_as_server() {
+        let mut agent: AgentConfig = serde_json::from_value(json!({
+            "id": "agent-1", "name": "Agent", "icon": "", "description": "", "status": "idle",
+            "execution_mode": "acp", "model": "gemini-2.5-pro", "temperature": 0.7, "max_tokens": 4096,
+            "system_prompt": "", "capabilities_json": "[]", "skills_json": "[]", "is_control_hub": false,
+            "is_secondary_hub": false, "md_file_path": null, "max_concurrency": 1, "available_models_json": null,
+            "is_enabled": true, "disabled_reason": null, "created_at": "", "updated_at": "",
+        }))
+        .unwrap();
+        let config = WebSearchConfig { enabled: true, api_key: Some("key".into()), ..Default::default() };
+
+        let servers = mcp_servers(&config, &agent);
+        assert_eq!(servers[0]["command"], json!(std::env::current_exe().unwrap()));
+        assert_eq!(servers[0]["args"], json!([SERVE_ARG]));
+        assert_eq!(unavailable(&config, &agent), None);
+
+        agent.ssh_host = Some("build-box".into());
+        assert_eq!(mcp_servers(&config, &agent), json!([]));
+    }
+}
@@ -0,0 +1,67 @@
+//! Workspace activity feed
+//!
+//! High-level events (runs starting and finishing, agents added, schedules
+//! firing, chat tool logins) are appended to `activity_log` and emitted as
+//! `activity:recorded` so an open feed updates live. Recording failures are
+//! logged and never abort the caller.
+
+use tauri::{AppHandle, Emitter};
+
+use crate::db::{activity_repo, task_run_repo};
+use crate::error::AppError;
+use crate::state::AppState;
+use crate::telemetry;
+
+pub const RUN_STARTED: &str = "run_started";
+pub const RUN_FINISHED: &str = "run_finished";
+pub const AGENT_ADDED: &str = "agent_added";
+pub const SCHEDULE_FIRED: &str = "schedule_fired";
+pub const CHAT_TOOL_LOGIN: &str = "chat_tool_login";
+
+pub async fn record(
+    app: &AppHandle,
+    state: &AppState,
+    workspace_id: Option<&str>,
+    kind: &'static str,
+    subject_id: Option<&str>,
+    summary: String,
+) {
+    let state_clone = state.clone();
+    let ws_id = workspace_id.map(|s| s.to_string());
+    let sid = subject_id.map(|s| s.to_string());
+    let result = telemetry::spawn_blocking(move || {
+        activity_repo::record_activity(&state_clone, ws_id.as_deref(), kind, sid.as_deref(), &summary)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))
+    .and_then(|r| r);
+
+    match result {
+        Ok(entry) => {
+            let _ = app.emit("activity:recorded", &entry);
+        }
+        Err(e) => log::warn!("[Activity] Failed to record {}: {}", kind, e),
+    }
+}
+
+/// Record a run starting or finishing; the summary names the run and, once
+/// finished, its final status. Runs that stopped short of a final status
+/// (e.g. paused at confirmation) are not recorded as finished.
+pub async fn record_run(app: &AppHandle, state: &AppState, task_run_id: &str, kind: &'static str) {
+    let state_clone = state.clone();
+    let trid = task_run_id.to_string();
+    let task_run = match telemetry::spawn_blocking(move || task_run_repo::get_task_run(&state_clone, &trid)).await {
+        Ok(Ok(task_run)) => task_run,
+        _ => return,
+    };
+    let finished = matches!(task_run.status.as_str(), "completed" | "failed" | "cancelled" | "needs_review");
+    if kind == RUN_FINISHED && !finished {
+        return;
+    }
+    let summary = if kind == RUN_FINISHED {
+        format!("Run \"{}\" finished: {}", task_run.title, task_run.status)
+    } else {
+        format!("Run \"{}\" started", task_run.title)
+    };
+    record(app, state, task_run.workspace_id.as_deref(), kind, Some(task_run_id), summary).await;
+}
@@ -0,0 +1,246 @@
+//! `agent-hub` — command-line companion to the desktop app.
+//!
+//! Reads the app's SQLite database directly. `run` asks the running app to
+//! start the orchestration; when the app is closed the run is queued and
+//! starts the next time the app opens.
+
+use std::process::ExitCode;
+
+use app_lib::acp::fallback_planner;
+use app_lib::acp::permissions::PermissionPreset;
+use app_lib::acp::web_search;
+use app_lib::db::{agent_repo, migrations, task_run_repo, workspace_repo};
+use app_lib::error::{AppError, AppResult};
+use app_lib::ipc::{self, IpcMethod};
+use app_lib::models::task_run::{CreateTaskRunRequest, RunPriority, StartedTaskRun, TaskRun};
+use app_lib::state::AppState;
+
+const USAGE: &str = "\
+Usage:
+  agent-hub run \"<prompt>\" [--workspace <id|name>] [--title <title>] [--context <run id>]
+                [--permissions <strict|balanced|yolo>] [--max-minutes <n>]
+  agent-hub tasks list [--workspace <id|name>] [--limit <n>]
+  agent-hub agents list [--workspace <id|name>]
+  agent-hub mcp web-search   Serve the web search tool to an agent over MCP
+
+Options:
+  --context  Plan the run with an earlier run's summary and outputs
+  --permissions  Permission preset answering the agents' requests in the run
+  --max-minutes  Wind the run down after this many minutes; 0 sets no limit
+  --json     Print JSON instead of text";
+
+struct Args {
+    positional: Vec<String>,
+    workspace: Option<String>,
+    title: Option<String>,
+    context_run: Option<String>,
+    permission_preset: Option<PermissionPreset>,
+    max_minutes: Option<u64>,
+    limit: usize,
+    json: bool,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1).collect()) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let positional: Vec<&str> = args.positional.iter().map(|s| s.as_str()).collect();
+    let result = match positional.as_slice() {
+        ["run", prompt] => run(&args, prompt),
+        ["tasks", "list"] => list_tasks(&args),
+        ["agents", "list"] => list_agents(&args),
+        ["mcp", "web-search"] => web_search::serve_from_env(),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(raw: Vec<String>) -> Result<Args, String> {
+    let mut args = Args {
+        positional: Vec::new(),
+        workspace: None,
+        title: None,
+        context_run: None,
+        permission_preset: None,
+        max_minutes: None,
+        limit: 20,
+        json: false,
+    };
+    let mut iter = raw.into_iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| iter.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "--workspace" | "-w" => args.workspace = Some(value("--workspace")?),
+            "--title" => args.title = Some(value("--title")?),
+            "--context" => args.context_run = Some(value("--context")?),
+            "--permissions" => {
+                let name = value("--permissions")?;
+                args.permission_preset = Some(
+                    PermissionPreset::parse(&name).ok_or_else(|| format!("Unknown permission preset {}", name))?,
+                )
+            }
+            "--max-minutes" => {
+                args.max_minutes = Some(
+                    value("--max-minutes")?
+                        .parse()
+                        .map_err(|_| "--max-minutes must be a number".to_string())?,
+                )
+            }
+            "--limit" => {
+                args.limit = value("--limit")?
+                    .parse()
+                    .map_err(|_| "--limit must be a number".to_string())?
+            }
+            "--json" => args.json = true,
+            "-h" | "--help" => return Err("agent-hub — Agent Hub command line".into()),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+            _ => args.positional.push(arg),
+        }
+    }
+    Ok(args)
+}
+
+fn open_state() -> AppResult<AppState> {
+    Ok(AppState::new(migrations::init_db()?))
+}
+
+/// Accept a workspace id or (case-insensitive) name.
+fn resolve_workspace(state: &AppState, workspace: Option<&str>) -> AppResult<Option<String>> {
+    let Some(workspace) = workspace else {
+        return Ok(None);
+    };
+    workspace_repo::list_workspaces(state)?
+        .into_iter()
+        .find(|w| w.id == workspace || w.name.eq_ignore_ascii_case(workspace))
+        .map(|w| Some(w.id))
+        .ok_or_else(|| AppError::NotFound(format!("Workspace '{}' not found", workspace)))
+}
+
+fn run(args: &Args, prompt: &str) -> AppResult<()> {
+    let state = open_state()?;
+    let workspace_id = resolve_workspace(&state, args.workspace.as_deref())?;
+    let request = CreateTaskRunRequest {
+        user_prompt: prompt.to_string(),
+        title: args.title.clone().unwrap_or_default(),
+        workspace_id,
+        prompt: None,
+        priority: RunPriority::Interactive,
+        attach_run_context: args.context_run.clone(),
+        ignore_workspace_lock: false,
+        pinned_output_ids: Vec::new(),
+        permission_preset: args.permission_preset,
+        max_duration_minutes: args.max_minutes,
+    };
+
+    let (started, queued): (StartedTaskRun, bool) = match ipc::call(IpcMethod::Run(Box::new(request.clone())))? {
+        Some(value) => (serde_json::from_value(value)?, false),
+        None => (StartedTaskRun { task_run: queue_run(&state, request)?, duplicate: false }, true),
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&started)?);
+    } else if queued {
+        println!("Queued {} — it starts when Agent Hub is opened", started.task_run.id);
+    } else if started.duplicate {
+        println!("Already running {}", started.task_run.id);
+    } else {
+        println!("Started {}", started.task_run.id);
+    }
+    Ok(())
+}
+
+/// Create a pending task run; the app resumes pending runs on startup.
+fn queue_run(state: &AppState, request: CreateTaskRunRequest) -> AppResult<TaskRun> {
+    let hub_id = fallback_planner::hub_id_for(state, request.workspace_id.as_deref())?;
+    let title = if request.title.is_empty() {
+        request.user_prompt.chars().take(100).collect()
+    } else {
+        request.title
+    };
+    if let Some(context_run_id) = &request.attach_run_context {
+        task_run_repo::get_task_run(state, context_run_id)?;
+    }
+    let mut task_run = task_run_repo::create_task_run(
+        state,
+        &uuid::Uuid::new_v4().to_string(),
+        &title,
+        &request.user_prompt,
+        &hub_id,
+        "pending",
+        request.workspace_id.as_deref(),
+    )?;
+    if let Some(context_run_id) = request.attach_run_context {
+        task_run_repo::set_context_run(state, &task_run.id, &context_run_id)?;
+        task_run.context_run_id = Some(context_run_id);
+    }
+    if let Some(preset) = request.permission_preset {
+        task_run_repo::set_permission_preset(state, &task_run.id, preset)?;
+        task_run.permission_preset = Some(preset);
+    }
+    if let Some(minutes) = request.max_duration_minutes {
+        task_run_repo::set_max_duration(state, &task_run.id, minutes)?;
+        task_run.max_duration_minutes = Some(minutes as i64);
+    }
+    Ok(task_run)
+}
+
+fn list_tasks(args: &Args) -> AppResult<()> {
+    let state = open_state()?;
+    let workspace_id = resolve_workspace(&state, args.workspace.as_deref())?;
+    let runs: Vec<_> = task_run_repo::list_task_runs(&state, workspace_id.as_deref())?
+        .into_iter()
+        .take(args.limit)
+        .collect();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&runs)?);
+        return Ok(());
+    }
+    for run in &runs {
+        println!("{}  {:<22}  {}  {}", run.id, run.status, run.created_at, run.title);
+    }
+    if runs.is_empty() {
+        println!("No task runs");
+    }
+    Ok(())
+}
+
+fn list_agents(args: &Args) -> AppResult<()> {
+    let state = open_state()?;
+    let workspace_id = resolve_workspace(&state, args.workspace.as_deref())?;
+    let agents = agent_repo::list_agents(&state, workspace_id.as_deref())?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&agents)?);
+        return Ok(());
+    }
+    for agent in &agents {
+        let mut flags = Vec::new();
+        if agent.is_control_hub {
+            flags.push("control hub");
+        }
+        if !agent.is_enabled {
+            flags.push("disabled");
+        }
+        println!("{}  {:<24}  {:<20}  {}", agent.id, agent.name, agent.model, flags.join(", "));
+    }
+    if agents.is_empty() {
+        println!("No agents");
+    }
+    Ok(())
+}
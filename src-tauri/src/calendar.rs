@@ -0,0 +1,135 @@
+//! iCalendar (RFC 5545) export of scheduled orchestrations, so upcoming agent
+//! runs show up in calendar apps. Schedule times are stored in UTC and are
+//! exported as UTC date-times.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::models::task_run::{RecurrencePattern, TaskRun};
+
+/// Used when a schedule has never run and its duration is unknown.
+const DEFAULT_DURATION_MINUTES: i64 = 15;
+const MAX_DESCRIPTION_CHARS: usize = 500;
+
+/// Build a calendar with one event per active schedule. Paused schedules and
+/// schedules without a start time are left out.
+pub fn build_schedule_calendar(runs: &[TaskRun], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//InfiniAct//Agent Hub//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:Agent Hub schedules".to_string(),
+    ];
+    let dtstamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+
+    for run in runs.iter().filter(|r| r.schedule_type != "none" && !r.is_paused) {
+        let start = match run.schedule_type.as_str() {
+            "once" => run.scheduled_time.as_deref().or(run.next_run_at.as_deref()),
+            _ => run.next_run_at.as_deref(),
+        };
+        let Some(start) = start.and_then(parse_utc) else {
+            continue;
+        };
+        let duration_minutes = if run.total_duration_ms > 0 {
+            (run.total_duration_ms + 59_999) / 60_000
+        } else {
+            DEFAULT_DURATION_MINUTES
+        };
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@agent-hub", run.id));
+        lines.push(format!("DTSTAMP:{}", dtstamp));
+        lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%SZ")));
+        lines.push(format!("DURATION:PT{}M", duration_minutes));
+        lines.push(format!("SUMMARY:{}", escape_text(&run.title)));
+        let description: String = run.user_prompt.chars().take(MAX_DESCRIPTION_CHARS).collect();
+        lines.push(format!("DESCRIPTION:{}", escape_text(&description)));
+        if run.schedule_type == "recurring" {
+            if let Some(rule) = run
+                .recurrence_pattern_json
+                .as_deref()
+                .and_then(|json| serde_json::from_str::<RecurrencePattern>(json).ok())
+                .and_then(|pattern| recurrence_rule(&pattern))
+            {
+                lines.push(format!("RRULE:{}", rule));
+            }
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|l| fold_line(l)).collect::<Vec<_>>().join("\r\n") + "\r\n"
+}
+
+/// RRULE value for a recurrence pattern, mirroring how the scheduler computes runs.
+fn recurrence_rule(pattern: &RecurrencePattern) -> Option<String> {
+    const WEEKDAYS: [&str; 7] = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+
+    let interval = pattern.interval.max(1);
+    let day_of_month = pattern.day_of_month.unwrap_or(1).clamp(1, 28);
+    match pattern.frequency.as_str() {
+        "daily" => Some(format!("FREQ=DAILY;INTERVAL={}", interval)),
+        "weekly" => {
+            let days: Vec<&str> = pattern
+                .days_of_week
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|d| WEEKDAYS.get(*d as usize).copied())
+                .collect();
+            if days.is_empty() {
+                Some(format!("FREQ=WEEKLY;INTERVAL={}", interval))
+            } else {
+                Some(format!("FREQ=WEEKLY;INTERVAL={};BYDAY={}", interval, days.join(",")))
+            }
+        }
+        "monthly" => Some(format!("FREQ=MONTHLY;INTERVAL={};BYMONTHDAY={}", interval, day_of_month)),
+        "yearly" => Some(format!(
+            "FREQ=YEARLY;INTERVAL={};BYMONTH={};BYMONTHDAY={}",
+            interval,
+            pattern.month.unwrap_or(1).clamp(1, 12),
+            day_of_month
+        )),
+        _ => None,
+    }
+}
+
+/// Schedule times are ISO 8601 in UTC, with or without a trailing `Z`.
+fn parse_utc(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            let trimmed = value.trim_end_matches('Z');
+            NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%dT%H:%M:%S")
+                .or_else(|_| NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S"))
+                .or_else(|_| NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%dT%H:%M"))
+                .ok()
+                .map(|naive| naive.and_utc())
+        })
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line to 75 octets per line without splitting a UTF-8 character.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += len;
+    }
+    folded
+}
@@ -0,0 +1,200 @@
+//! Fault injection for resilience testing.
+//!
+//! Built only with the `chaos` Cargo feature; in other builds every hook is a
+//! no-op. With the feature on and `chaos.enabled` set, orchestration hooks
+//! randomly delay or drop agent messages, kill agent processes before a
+//! prompt, and corrupt the Control Hub's plan JSON, so the stall, retry and
+//! resume paths get exercised. Faults are drawn from a generator seeded by
+//! `chaos.seed`: the same seed and the same sequence of hook calls inject the
+//! same faults.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::state::AppState;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChaosConfig {
+    pub enabled: bool,
+    pub seed: u64,
+    /// Chance that an agent message is held back before it is handled
+    pub delay_probability: f64,
+    pub max_delay_ms: u64,
+    /// Chance that a `session/update` notification is discarded
+    pub drop_probability: f64,
+    /// Chance that the agent process is killed before a prompt is sent
+    pub kill_probability: f64,
+    /// Chance that the Control Hub's first plan response is truncated
+    pub malformed_plan_probability: f64,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seed: 0,
+            delay_probability: 0.0,
+            max_delay_ms: 5000,
+            drop_probability: 0.0,
+            kill_probability: 0.0,
+            malformed_plan_probability: 0.0,
+        }
+    }
+}
+
+impl ChaosConfig {
+    pub fn probabilities(&self) -> [(&'static str, f64); 4] {
+        [
+            ("delay_probability", self.delay_probability),
+            ("drop_probability", self.drop_probability),
+            ("kill_probability", self.kill_probability),
+            ("malformed_plan_probability", self.malformed_plan_probability),
+        ]
+    }
+}
+
+/// Seeded fault decisions (splitmix64).
+pub struct FaultInjector {
+    config: ChaosConfig,
+    state: u64,
+}
+
+impl FaultInjector {
+    pub fn new(config: ChaosConfig) -> Self {
+        let state = config.seed;
+        Self { config, state }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn roll(&mut self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+
+    pub fn delay(&mut self) -> Option<Duration> {
+        if !self.roll(self.config.delay_probability) {
+            return None;
+        }
+        let ms = self.next_u64() % self.config.max_delay_ms.max(1);
+        Some(Duration::from_millis(ms))
+    }
+
+    pub fn drop_message(&mut self) -> bool {
+        self.roll(self.config.drop_probability)
+    }
+
+    pub fn kill_process(&mut self) -> bool {
+        self.roll(self.config.kill_probability)
+    }
+
+    /// The plan text cut off part-way, so it no longer parses.
+    pub fn corrupt_plan(&mut self, text: &str) -> Option<String> {
+        if !self.roll(self.config.malformed_plan_probability) {
+            return None;
+        }
+        let chars = text.chars().count();
+        let keep = if chars > 1 { (self.next_u64() % (chars as u64 - 1)) as usize } else { 0 };
+        Some(text.chars().take(keep).collect())
+    }
+}
+
+static INJECTOR: Mutex<Option<FaultInjector>> = Mutex::new(None);
+
+/// Run `f` with the injector when fault injection is compiled in and enabled.
+/// The injector is reseeded whenever the config changes.
+fn with_injector<R>(state: &AppState, f: impl FnOnce(&mut FaultInjector) -> R) -> Option<R> {
+    if !cfg!(feature = "chaos") {
+        return None;
+    }
+    let config = config::current(state).chaos;
+    if !config.enabled {
+        return None;
+    }
+    let mut injector = INJECTOR.lock().ok()?;
+    if !injector.as_ref().is_some_and(|i| i.config == config) {
+        log::warn!("[Chaos] Fault injection active with seed {}", config.seed);
+        *injector = Some(FaultInjector::new(config));
+    }
+    injector.as_mut().map(f)
+}
+
+/// Hold back an incoming agent message.
+pub async fn delay_message(state: &AppState, agent_id: &str) {
+    if let Some(delay) = with_injector(state, |i| i.delay()).flatten() {
+        log::warn!("[Chaos] Delaying message from agent {} by {}ms", agent_id, delay.as_millis());
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Whether to discard an incoming `session/update` notification.
+pub fn drop_message(state: &AppState, agent_id: &str) -> bool {
+    let dropped = with_injector(state, |i| i.drop_message()).unwrap_or(false);
+    if dropped {
+        log::warn!("[Chaos] Dropping session update from agent {}", agent_id);
+    }
+    dropped
+}
+
+/// Kill the agent process as if it crashed.
+pub async fn maybe_kill_agent(state: &AppState, process_key: &str) {
+    if !with_injector(state, |i| i.kill_process()).unwrap_or(false) {
+        return;
+    }
+    let mut processes = state.agent_processes.lock().await;
+    if let Some(process) = processes.get_mut(process_key) {
+        log::warn!("[Chaos] Killing agent process {}", process_key);
+        let _ = process.child.start_kill();
+    }
+}
+
+/// A malformed replacement for the Control Hub's plan response, if one is injected.
+pub fn corrupt_plan(state: &AppState, text: &str) -> Option<String> {
+    let corrupted = with_injector(state, |i| i.corrupt_plan(text)).flatten();
+    if corrupted.is_some() {
+        log::warn!("[Chaos] Corrupting plan response");
+    }
+    corrupted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_injects_same_faults() {
+        let config = ChaosConfig {
+            enabled: true,
+            seed: 42,
+            delay_probability: 0.5,
+            drop_probability: 0.5,
+            malformed_plan_probability: 1.0,
+            ..ChaosConfig::default()
+        };
+        let decisions = |injector: &mut FaultInjector| {
+            (0..32).map(|_| (injector.delay(), injector.drop_message())).collect::<Vec<_>>()
+        };
+        let mut a = FaultInjector::new(config.clone());
+        let mut b = FaultInjector::new(config);
+        assert_eq!(decisions(&mut a), decisions(&mut b));
+        assert!(!a.kill_process());
+
+        let plan = r#"{"analysis": "x", "assignments": []}"#;
+        let corrupted = a.corrupt_plan(plan).unwrap();
+        assert!(corrupted.len() < plan.len());
+    }
+}
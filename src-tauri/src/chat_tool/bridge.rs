@@ -1,17 +1,26 @@
 use serde_json::json;
-use tauri::Emitter;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::ChildStdout;
 use tokio::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
 
 use crate::acp::{discovery, manager as acp_manager, provisioner, transport};
+use crate::activity;
+use crate::config;
 use crate::db::{agent_repo, chat_tool_repo, task_run_repo};
 use crate::error::{AppError, AppResult};
+use crate::events;
+use crate::i18n::{self, Msg};
 use crate::models::agent::AgentConfig;
-use crate::models::chat_tool::{BridgeCommand, BridgeEvent};
+use crate::models::chat_tool::{
+    BridgeCapabilities, BridgeCommand, BridgeEvent, ChatTool, ChatToolMessage, BRIDGE_PROTOCOL_VERSION,
+    MIN_BRIDGE_PROTOCOL_VERSION,
+};
 use crate::state::AppState;
+use crate::telemetry;
 
+use super::reply_templates::ReplyTemplates;
+use super::{chunking, delivery, escalation, keywords, metrics, quota, spam_filter};
 use super::manager::{self as chat_manager, check_process_alive, send_bridge_command};
 
 /// What the event loop should do after handling an event.
@@ -20,6 +29,8 @@ enum EventAction {
     Continue,
     /// A fatal error occurred — the bridge should be restarted.
     Restart { reason: String },
+    /// The bridge cannot be used (e.g. incompatible protocol) — stop it without restarting.
+    Stop { reason: String },
 }
 
 pub async fn run_bridge_event_loop(
@@ -40,6 +51,7 @@ pub async fn run_bridge_event_loop(
     log::info!("[Bridge:{}] Event loop started", chat_tool_id);
 
     let mut restart_reason: Option<String> = None;
+    let mut stop_reason: Option<String> = None;
 
     loop {
         tokio::select! {
@@ -69,13 +81,17 @@ pub async fn run_bridge_event_loop(
                                         restart_reason = Some(reason);
                                         break;
                                     }
+                                    Ok(EventAction::Stop { reason }) => {
+                                        stop_reason = Some(reason);
+                                        break;
+                                    }
                                     Err(e) => {
                                         log::error!("[Bridge:{}] Error handling event: {}", chat_tool_id, e);
                                     }
                                 }
                             }
                             Err(e) => {
-                                log::warn!("[Bridge:{}] Failed to parse NDJSON: '{}', error: {}", chat_tool_id, trimmed, e);
+                                report_unparsed_line(&app, &state, &chat_tool_id, trimmed, &e).await;
                             }
                         }
                     }
@@ -83,10 +99,10 @@ pub async fn run_bridge_event_loop(
                         log::info!("[Bridge:{}] stdout closed, ending event loop", chat_tool_id);
                         let state_clone = state.clone();
                         let id = chat_tool_id.clone();
-                        let _ = tokio::task::spawn_blocking(move || {
+                        let _ = telemetry::spawn_blocking(move || {
                             chat_tool_repo::update_chat_tool_status(&state_clone, &id, "stopped", Some("Bridge process exited"))
                         }).await;
-                        let _ = app.emit("chat_tool:status_changed", json!({
+                        events::emit(&app, &events::CHAT_TOOL_STATUS_CHANGED, json!({
                             "chatToolId": chat_tool_id,
                             "status": "stopped",
                             "message": "Bridge process exited"
@@ -137,17 +153,21 @@ pub async fn run_bridge_event_loop(
                             log::info!("[Bridge:{}] Pong received, bridge is alive", chat_tool_id);
                             last_event_time = Instant::now();
                         }
+                        Ok(WaitResult::Stop(reason)) => {
+                            stop_reason = Some(reason);
+                            break;
+                        }
                         Ok(WaitResult::StreamClosed) => {
                             log::info!("[Bridge:{}] stdout closed while waiting for pong", chat_tool_id);
                             let state_clone = state.clone();
                             let id = chat_tool_id.clone();
-                            let _ = tokio::task::spawn_blocking(move || {
+                            let _ = telemetry::spawn_blocking(move || {
                                 chat_tool_repo::update_chat_tool_status(
                                     &state_clone, &id, "stopped",
                                     Some("Bridge process exited"),
                                 )
                             }).await;
-                            let _ = app.emit("chat_tool:status_changed", json!({
+                            events::emit(&app, &events::CHAT_TOOL_STATUS_CHANGED, json!({
                                 "chatToolId": chat_tool_id,
                                 "status": "stopped",
                                 "message": "Bridge process exited"
@@ -169,13 +189,13 @@ pub async fn run_bridge_event_loop(
                                 log::warn!("[Bridge:{}] Process already exited", chat_tool_id);
                                 let state_clone = state.clone();
                                 let id = chat_tool_id.clone();
-                                let _ = tokio::task::spawn_blocking(move || {
+                                let _ = telemetry::spawn_blocking(move || {
                                     chat_tool_repo::update_chat_tool_status(
                                         &state_clone, &id, "stopped",
                                         Some("Bridge process exited unexpectedly"),
                                     )
                                 }).await;
-                                let _ = app.emit("chat_tool:status_changed", json!({
+                                events::emit(&app, &events::CHAT_TOOL_STATUS_CHANGED, json!({
                                     "chatToolId": chat_tool_id,
                                     "status": "stopped",
                                     "message": "Bridge process exited unexpectedly"
@@ -184,13 +204,13 @@ pub async fn run_bridge_event_loop(
                                 log::error!("[Bridge:{}] Bridge unresponsive, killing process", chat_tool_id);
                                 let state_clone = state.clone();
                                 let id = chat_tool_id.clone();
-                                let _ = tokio::task::spawn_blocking(move || {
+                                let _ = telemetry::spawn_blocking(move || {
                                     chat_tool_repo::update_chat_tool_status(
                                         &state_clone, &id, "error",
                                         Some("Bridge unresponsive"),
                                     )
                                 }).await;
-                                let _ = app.emit("chat_tool:status_changed", json!({
+                                events::emit(&app, &events::CHAT_TOOL_STATUS_CHANGED, json!({
                                     "chatToolId": chat_tool_id,
                                     "status": "error",
                                     "message": "Bridge unresponsive"
@@ -212,6 +232,16 @@ pub async fn run_bridge_event_loop(
 
     log::info!("[Bridge:{}] Event loop ended", chat_tool_id);
 
+    // Stop an unusable bridge without restarting it; status was already set by the handler
+    if let Some(reason) = stop_reason {
+        log::warn!("[Bridge:{}] Stopping bridge: {}", chat_tool_id, reason);
+        let mut processes = state.chat_tool_processes.lock().await;
+        if let Some(mut process) = processes.remove(&chat_tool_id) {
+            let _ = chat_manager::stop_bridge_process(&mut process).await;
+        }
+        return;
+    }
+
     // Auto-restart if needed
     if let Some(reason) = restart_reason {
         if cancel_token.is_cancelled() {
@@ -237,7 +267,7 @@ pub async fn run_bridge_event_loop(
             let state_clone = state.clone();
             let id = chat_tool_id.clone();
             let r = reason.clone();
-            let _ = tokio::task::spawn_blocking(move || {
+            let _ = telemetry::spawn_blocking(move || {
                 chat_tool_repo::update_chat_tool_status(
                     &state_clone, &id, "starting",
                     Some(&format!("Restarting: {}", r)),
@@ -245,7 +275,7 @@ pub async fn run_bridge_event_loop(
             })
             .await;
         }
-        let _ = app.emit("chat_tool:status_changed", json!({
+        events::emit(&app, &events::CHAT_TOOL_STATUS_CHANGED, json!({
             "chatToolId": chat_tool_id,
             "status": "starting",
             "message": format!("Restarting: {}", reason)
@@ -262,7 +292,7 @@ pub async fn run_bridge_event_loop(
         let chat_tool = {
             let state_clone = state.clone();
             let id = chat_tool_id.clone();
-            tokio::task::spawn_blocking(move || {
+            telemetry::spawn_blocking(move || {
                 chat_tool_repo::get_chat_tool(&state_clone, &id)
             })
             .await
@@ -277,9 +307,14 @@ pub async fn run_bridge_event_loop(
         };
 
         // 5. Spawn new bridge process
-        let spawn_result = chat_manager::spawn_bridge(
-            &chat_tool_id, &chat_tool.plugin_type, &chat_tool.config_json,
-        ).await;
+        let spawn_result = match chat_tool.env_profile() {
+            Ok(env_profile) => {
+                chat_manager::spawn_bridge(
+                    &state, &chat_tool_id, &chat_tool.plugin_type, &chat_tool.config_json, &env_profile,
+                ).await
+            }
+            Err(e) => Err(AppError::InvalidRequest(e)),
+        };
 
         match spawn_result {
             Ok((process, new_stdout)) => {
@@ -300,13 +335,13 @@ pub async fn run_bridge_event_loop(
                 let state_clone = state.clone();
                 let id = chat_tool_id.clone();
                 let err = e.to_string();
-                let _ = tokio::task::spawn_blocking(move || {
+                let _ = telemetry::spawn_blocking(move || {
                     chat_tool_repo::update_chat_tool_status(
                         &state_clone, &id, "error", Some(&err),
                     )
                 })
                 .await;
-                let _ = app.emit("chat_tool:status_changed", json!({
+                events::emit(&app, &events::CHAT_TOOL_STATUS_CHANGED, json!({
                     "chatToolId": chat_tool_id,
                     "status": "error",
                     "message": e.to_string()
@@ -318,6 +353,7 @@ pub async fn run_bridge_event_loop(
 
 enum WaitResult {
     Pong,
+    Stop(String),
     StreamClosed,
     Error,
 }
@@ -348,6 +384,9 @@ async fn wait_for_pong(
                                 // Treat restart-triggering errors as pong failure
                                 return WaitResult::Error;
                             }
+                            Ok(EventAction::Stop { reason }) => {
+                                return WaitResult::Stop(reason);
+                            }
                             Err(e) => {
                                 log::error!("[Bridge:{}] Error handling event during pong wait: {}", chat_tool_id, e);
                             }
@@ -355,7 +394,7 @@ async fn wait_for_pong(
                         }
                     }
                     Err(e) => {
-                        log::warn!("[Bridge:{}] Failed to parse NDJSON during pong wait: '{}', error: {}", chat_tool_id, trimmed, e);
+                        report_unparsed_line(app, state, chat_tool_id, trimmed, &e).await;
                     }
                 }
             }
@@ -365,6 +404,54 @@ async fn wait_for_pong(
     }
 }
 
+/// Surface an NDJSON line that could not be parsed as a `BridgeEvent`.
+///
+/// Non-JSON output (e.g. stray library logging) is only logged. A JSON object
+/// with a `type` we don't understand means the bridge speaks a different
+/// protocol, so the frontend is told about it instead of silently dropping it.
+async fn report_unparsed_line(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    chat_tool_id: &str,
+    line: &str,
+    error: &serde_json::Error,
+) {
+    let event_type = serde_json::from_str::<serde_json::Value>(line)
+        .ok()
+        .and_then(|v| v.get("type").and_then(|t| t.as_str()).map(String::from));
+
+    let Some(event_type) = event_type else {
+        log::warn!("[Bridge:{}] Failed to parse NDJSON: '{}', error: {}", chat_tool_id, line, error);
+        return;
+    };
+
+    let protocol_version = {
+        let processes = state.chat_tool_processes.lock().await;
+        processes
+            .get(chat_tool_id)
+            .map(|p| p.effective_capabilities().protocol_version)
+    };
+
+    log::warn!(
+        "[Bridge:{}] Incompatible bridge event '{}' (bridge protocol v{}, app v{}): {}",
+        chat_tool_id,
+        event_type,
+        protocol_version.unwrap_or(0),
+        BRIDGE_PROTOCOL_VERSION,
+        error
+    );
+
+    events::emit(app, &events::CHAT_TOOL_PROTOCOL_MISMATCH, json!({
+            "chatToolId": chat_tool_id,
+            "eventType": event_type,
+            "error": error.to_string(),
+            "bridgeProtocolVersion": protocol_version,
+            "appProtocolVersion": BRIDGE_PROTOCOL_VERSION
+        }),
+    );
+}
+
+#[tracing::instrument(skip(app, state, event))]
 async fn handle_bridge_event(
     app: &tauri::AppHandle,
     state: &AppState,
@@ -372,20 +459,83 @@ async fn handle_bridge_event(
     event: BridgeEvent,
 ) -> AppResult<EventAction> {
     match event {
+        BridgeEvent::Hello {
+            protocol_version,
+            capabilities,
+            bridge_version,
+        } => {
+            log::info!(
+                "[Bridge:{}] Hello: protocol v{}, bridge {}, capabilities {:?}",
+                chat_tool_id,
+                protocol_version,
+                bridge_version.as_deref().unwrap_or("unknown"),
+                capabilities
+            );
+
+            if !BridgeCapabilities::is_supported_version(protocol_version) {
+                let reason = format!(
+                    "Bridge protocol v{} is not supported (supported: v{}-v{})",
+                    protocol_version, MIN_BRIDGE_PROTOCOL_VERSION, BRIDGE_PROTOCOL_VERSION
+                );
+
+                let state_clone = state.clone();
+                let id = chat_tool_id.to_string();
+                let r = reason.clone();
+                let _ = telemetry::spawn_blocking(move || {
+                    chat_tool_repo::update_chat_tool_status(&state_clone, &id, "error", Some(&r))
+                })
+                .await;
+
+                events::emit(app, &events::CHAT_TOOL_INCOMPATIBLE, json!({
+                        "chatToolId": chat_tool_id,
+                        "protocolVersion": protocol_version,
+                        "minSupportedVersion": MIN_BRIDGE_PROTOCOL_VERSION,
+                        "maxSupportedVersion": BRIDGE_PROTOCOL_VERSION,
+                        "message": reason
+                    }),
+                );
+                events::emit(app, &events::CHAT_TOOL_STATUS_CHANGED, json!({
+                        "chatToolId": chat_tool_id,
+                        "status": "error",
+                        "message": reason
+                    }),
+                );
+
+                return Ok(EventAction::Stop { reason });
+            }
+
+            let negotiated = BridgeCapabilities::from_announced(protocol_version, &capabilities);
+            {
+                let mut processes = state.chat_tool_processes.lock().await;
+                if let Some(process) = processes.get_mut(chat_tool_id) {
+                    process.capabilities = Some(negotiated.clone());
+                    let ack = BridgeCommand::HelloAck { protocol_version };
+                    if let Err(e) = send_bridge_command(process, &ack).await {
+                        log::warn!("[Bridge:{}] Failed to acknowledge hello: {}", chat_tool_id, e);
+                    }
+                }
+            }
+
+            events::emit(app, &events::CHAT_TOOL_CAPABILITIES, json!({
+                    "chatToolId": chat_tool_id,
+                    "capabilities": negotiated,
+                    "bridgeVersion": bridge_version
+                }),
+            );
+        }
+
         BridgeEvent::Status { status } => {
             log::info!("[Bridge:{}] Status: {}", chat_tool_id, status);
             let state_clone = state.clone();
             let id = chat_tool_id.to_string();
             let s = status.clone();
-            tokio::task::spawn_blocking(move || {
+            telemetry::spawn_blocking(move || {
                 chat_tool_repo::update_chat_tool_status(&state_clone, &id, &s, None)
             })
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??;
 
-            let _ = app.emit(
-                "chat_tool:status_changed",
-                json!({
+            events::emit(app, &events::CHAT_TOOL_STATUS_CHANGED, json!({
                     "chatToolId": chat_tool_id,
                     "status": status,
                     "message": serde_json::Value::Null
@@ -403,7 +553,7 @@ async fn handle_bridge_event(
 
             let state_clone = state.clone();
             let id = chat_tool_id.to_string();
-            tokio::task::spawn_blocking(move || {
+            telemetry::spawn_blocking(move || {
                 chat_tool_repo::update_chat_tool_status(
                     &state_clone,
                     &id,
@@ -414,9 +564,7 @@ async fn handle_bridge_event(
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??;
 
-            let _ = app.emit(
-                "chat_tool:qr_code",
-                json!({
+            events::emit(app, &events::CHAT_TOOL_QR_CODE, json!({
                     "chatToolId": chat_tool_id,
                     "url": url,
                     "imageBase64": image_base64
@@ -440,7 +588,7 @@ async fn handle_bridge_event(
             let state_clone = state.clone();
             let id = chat_tool_id.to_string();
             let name = user_name.clone();
-            tokio::task::spawn_blocking(move || {
+            telemetry::spawn_blocking(move || {
                 chat_tool_repo::update_chat_tool_status(
                     &state_clone,
                     &id,
@@ -451,14 +599,26 @@ async fn handle_bridge_event(
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??;
 
-            let _ = app.emit(
-                "chat_tool:login",
-                json!({
+            events::emit(app, &events::CHAT_TOOL_LOGIN, json!({
                     "chatToolId": chat_tool_id,
                     "userId": user_id,
                     "userName": user_name
                 }),
             );
+
+            let state_clone = state.clone();
+            let id = chat_tool_id.to_string();
+            if let Ok(Ok(chat_tool)) = telemetry::spawn_blocking(move || chat_tool_repo::get_chat_tool(&state_clone, &id)).await {
+                activity::record(
+                    app,
+                    state,
+                    chat_tool.workspace_id.as_deref(),
+                    activity::CHAT_TOOL_LOGIN,
+                    Some(chat_tool_id),
+                    format!("Chat tool \"{}\" logged in as {}", chat_tool.name, user_name),
+                )
+                .await;
+            }
         }
 
         BridgeEvent::Logout => {
@@ -466,7 +626,7 @@ async fn handle_bridge_event(
 
             let state_clone = state.clone();
             let id = chat_tool_id.to_string();
-            tokio::task::spawn_blocking(move || {
+            telemetry::spawn_blocking(move || {
                 chat_tool_repo::update_chat_tool_status(
                     &state_clone,
                     &id,
@@ -477,9 +637,7 @@ async fn handle_bridge_event(
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??;
 
-            let _ = app.emit(
-                "chat_tool:logout",
-                json!({ "chatToolId": chat_tool_id }),
+            events::emit(app, &events::CHAT_TOOL_LOGOUT, json!({ "chatToolId": chat_tool_id }),
             );
         }
 
@@ -501,7 +659,7 @@ async fn handle_bridge_event(
             let state_clone = state.clone();
             let id = chat_tool_id.to_string();
             let sid = sender_id.clone();
-            let is_blocked = tokio::task::spawn_blocking(move || -> bool {
+            let is_blocked = telemetry::spawn_blocking(move || -> bool {
                 let db = match state_clone.db.lock() {
                     Ok(db) => db,
                     Err(_) => return false,
@@ -522,6 +680,8 @@ async fn handle_bridge_event(
                 return Ok(EventAction::Continue);
             }
 
+            let is_text = content_type == "text";
+
             // Save message to DB (is_processed defaults to false)
             let state_clone = state.clone();
             let id = chat_tool_id.to_string();
@@ -529,7 +689,7 @@ async fn handle_bridge_event(
             let sname = sender_name.clone();
             let c = content.clone();
             let ct = content_type;
-            let message = tokio::task::spawn_blocking(move || {
+            let message = telemetry::spawn_blocking(move || {
                 chat_tool_repo::save_chat_tool_message(
                     &state_clone, &id, "incoming",
                     Some(&sid), Some(&sname), &c, &ct,
@@ -541,23 +701,47 @@ async fn handle_bridge_event(
             // Increment received count
             let state_clone = state.clone();
             let id = chat_tool_id.to_string();
-            let _ = tokio::task::spawn_blocking(move || {
+            let _ = telemetry::spawn_blocking(move || {
                 chat_tool_repo::increment_message_count(&state_clone, &id, "incoming")
             })
             .await;
 
-            let _ = app.emit(
-                "chat_tool:message_received",
-                json!({
+            events::emit(app, &events::CHAT_TOOL_MESSAGE_RECEIVED, json!({
                     "chatToolId": chat_tool_id,
                     "message": message
                 }),
             );
 
+            // Spam filter: caught messages stay flagged and get no reply
+            let filter_config = config::current(state).chat_tool_spam_filter;
+            if filter_config.enabled {
+                let state_clone = state.clone();
+                let id = chat_tool_id.to_string();
+                let sid = sender_id.clone();
+                let history = telemetry::spawn_blocking(move || chat_tool_repo::sender_history(&state_clone, &id, &sid))
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))
+                    .and_then(|r| r)
+                    .unwrap_or_default();
+                if let Some(reason) = spam_filter::check(&filter_config, &content, &message.content_type, history) {
+                    flag_filtered_message(app, state, chat_tool_id, &message.id, &sender_id, &reason).await;
+                    return Ok(EventAction::Continue);
+                }
+            }
+
+            // Snoozed or taken over by a human: the message is only stored
+            if is_paused(state, chat_tool_id, &sender_id).await {
+                log::info!("[Bridge:{}] Auto-replies to {} are paused, leaving message", chat_tool_id, sender_id);
+                let state_clone = state.clone();
+                let mids = vec![message.id.clone()];
+                let _ = telemetry::spawn_blocking(move || chat_tool_repo::mark_messages_processed_batch(&state_clone, &mids, "")).await;
+                return Ok(EventAction::Continue);
+            }
+
             // Check auto-reply mode
             let state_clone = state.clone();
             let ct_id = chat_tool_id.to_string();
-            let chat_tool = tokio::task::spawn_blocking(move || {
+            let chat_tool = telemetry::spawn_blocking(move || {
                 chat_tool_repo::get_chat_tool(&state_clone, &ct_id)
             })
             .await
@@ -567,6 +751,35 @@ async fn handle_bridge_event(
                 return Ok(EventAction::Continue);
             }
 
+            // Keyword commands from the owner or allowed contacts are answered
+            // directly, without the Control Hub
+            let keywords = {
+                let state_clone = state.clone();
+                telemetry::spawn_blocking(move || keywords::load_keywords(&state_clone))
+                    .await
+                    .unwrap_or_default()
+            };
+            if let Some(command) = is_text.then(|| keywords::parse_command(&content, &keywords)).flatten() {
+                let state_clone = state.clone();
+                let id = chat_tool_id.to_string();
+                let sid = sender_id.clone();
+                let contact = telemetry::spawn_blocking(move || chat_tool_repo::find_contact(&state_clone, &id, &sid))
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))
+                    .and_then(|r| r)
+                    .unwrap_or_default();
+                if keywords::may_send_commands(&chat_tool, &sender_id, contact.as_ref(), &keywords) {
+                    log::info!("[Bridge:{}] Keyword command from {}: {:?}", chat_tool_id, sender_id, command);
+                    let reply = keywords::execute_command(app, state, &chat_tool, command, &keywords).await;
+                    send_keyword_reply(app, state, chat_tool_id, &message.id, &sender_id, &reply).await;
+                    return Ok(EventAction::Continue);
+                }
+                log::info!("[Bridge:{}] {} may not send keyword commands, handling as a message", chat_tool_id, sender_id);
+            }
+
+            let templates = chat_tool.reply_templates();
+            send_canned_replies(state, &chat_tool, &templates, &sender_id, &sender_name).await;
+
             // Check if this chat tool is already processing a message
             {
                 let processing = state.chat_tool_processing.lock().await;
@@ -577,12 +790,14 @@ async fn handle_bridge_event(
                     );
                     // Send busy reply (release processing lock first)
                     drop(processing);
+                    let busy = templates.busy_reply(i18n::current(state), &sender_name, &chat_tool.name);
                     let processes = state.chat_tool_processes.lock().await;
-                    if let Some(process) = processes.get(chat_tool_id) {
+                    if let (Some(process), Some(busy)) = (processes.get(chat_tool_id), busy) {
                         let cmd = BridgeCommand::SendMessage {
                             to_id: sender_id.clone(),
-                            content: "正在处理前面的消息，请稍候".to_string(),
+                            content: busy,
                             content_type: "text".into(),
+                            ref_id: None,
                         };
                         let _ = send_bridge_command(process, &cmd).await;
                     }
@@ -631,7 +846,7 @@ async fn handle_bridge_event(
 
             let state_clone = state.clone();
             let id = chat_tool_id.to_string();
-            let _ = tokio::task::spawn_blocking(move || {
+            let _ = telemetry::spawn_blocking(move || {
                 chat_tool_repo::upsert_contacts(&state_clone, &id, &contact_data)
             })
             .await;
@@ -652,14 +867,12 @@ async fn handle_bridge_event(
             let state_clone = state.clone();
             let id = chat_tool_id.to_string();
             let err = error.clone();
-            let _ = tokio::task::spawn_blocking(move || {
+            let _ = telemetry::spawn_blocking(move || {
                 chat_tool_repo::update_chat_tool_status(&state_clone, &id, "error", Some(&err))
             })
             .await;
 
-            let _ = app.emit(
-                "chat_tool:error",
-                json!({
+            events::emit(app, &events::CHAT_TOOL_ERROR, json!({
                     "chatToolId": chat_tool_id,
                     "error": error
                 }),
@@ -673,18 +886,22 @@ async fn handle_bridge_event(
         BridgeEvent::Heartbeat => {
             let state_clone = state.clone();
             let id = chat_tool_id.to_string();
-            let _ = tokio::task::spawn_blocking(move || {
+            let _ = telemetry::spawn_blocking(move || {
                 chat_tool_repo::update_last_active(&state_clone, &id)
             })
             .await;
         }
 
+        BridgeEvent::MessageStatus { ref_id, status, error } => {
+            delivery::handle_status(app, state, chat_tool_id, &ref_id, &status, error.as_deref()).await;
+        }
+
         BridgeEvent::Pong { ts } => {
             log::debug!("[Bridge:{}] Pong received (ts={})", chat_tool_id, ts);
             // last_event_time is updated at the line-read level; nothing else needed here.
             let state_clone = state.clone();
             let id = chat_tool_id.to_string();
-            let _ = tokio::task::spawn_blocking(move || {
+            let _ = telemetry::spawn_blocking(move || {
                 chat_tool_repo::update_last_active(&state_clone, &id)
             })
             .await;
@@ -694,6 +911,291 @@ async fn handle_bridge_event(
     Ok(EventAction::Continue)
 }
 
+/// Send a keyword command's reply and record it like an auto-reply.
+async fn send_keyword_reply(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    chat_tool_id: &str,
+    message_id: &str,
+    sender_id: &str,
+    reply: &str,
+) {
+    if let Err(e) = delivery::send_tracked(state, chat_tool_id, sender_id, reply, "text").await {
+        log::error!("[Bridge:{}] Failed to send command reply to {}: {}", chat_tool_id, sender_id, e);
+        return;
+    }
+
+    let state_clone = state.clone();
+    let id = chat_tool_id.to_string();
+    let mid = message_id.to_string();
+    let r = reply.to_string();
+    let _ = telemetry::spawn_blocking(move || {
+        chat_tool_repo::mark_message_processed(&state_clone, &mid, &r)?;
+        chat_tool_repo::increment_message_count(&state_clone, &id, "outgoing")
+    })
+    .await;
+
+    events::emit(app, &events::CHAT_TOOL_MESSAGE_PROCESSED, json!({
+            "chatToolId": chat_tool_id,
+            "messageId": message_id,
+            "agentResponse": reply
+        }),
+    );
+}
+
+/// How often the office-hours notice may go to the same sender.
+const NOTICE_INTERVAL_HOURS: i64 = 12;
+
+/// Greet a first-time sender and, outside office hours, send the office-hours
+/// notice (at most once per NOTICE_INTERVAL_HOURS per sender).
+async fn send_canned_replies(
+    state: &AppState,
+    chat_tool: &ChatTool,
+    templates: &ReplyTemplates,
+    sender_id: &str,
+    sender_name: &str,
+) {
+    if let Some(greeting) = templates.render(&templates.greeting, sender_name, &chat_tool.name) {
+        let state_clone = state.clone();
+        let id = chat_tool.id.clone();
+        let sid = sender_id.to_string();
+        let history = telemetry::spawn_blocking(move || chat_tool_repo::sender_history(&state_clone, &id, &sid)).await;
+        if matches!(history, Ok(Ok(h)) if h.total <= 1) {
+            send_canned_reply(state, &chat_tool.id, sender_id, &greeting).await;
+        }
+    }
+
+    let now = chrono::Local::now().naive_local();
+    if let Some(notice) = templates.closed_notice(now, sender_name, &chat_tool.name) {
+        let state_clone = state.clone();
+        let id = chat_tool.id.clone();
+        let sid = sender_id.to_string();
+        let n = notice.clone();
+        let recently = telemetry::spawn_blocking(move || {
+            chat_tool_repo::sent_recently(&state_clone, &id, &sid, &n, NOTICE_INTERVAL_HOURS)
+        })
+        .await;
+        if matches!(recently, Ok(Ok(false))) {
+            send_canned_reply(state, &chat_tool.id, sender_id, &notice).await;
+        }
+    }
+}
+
+/// Send a reply template's text and count it as sent.
+async fn send_canned_reply(state: &AppState, chat_tool_id: &str, to_id: &str, text: &str) {
+    if let Err(e) = delivery::send_tracked(state, chat_tool_id, to_id, text, "text").await {
+        log::warn!("[Bridge:{}] Failed to send canned reply to {}: {}", chat_tool_id, to_id, e);
+        return;
+    }
+    let state_clone = state.clone();
+    let id = chat_tool_id.to_string();
+    let _ = telemetry::spawn_blocking(move || chat_tool_repo::increment_message_count(&state_clone, &id, "outgoing")).await;
+}
+
+/// Show or clear a typing indicator for each sender, on bridges that support it.
+async fn set_typing(state: &AppState, chat_tool_id: &str, sender_ids: &[String], active: bool) {
+    let processes = state.chat_tool_processes.lock().await;
+    let Some(process) = processes.get(chat_tool_id) else {
+        return;
+    };
+    if !process.effective_capabilities().typing {
+        return;
+    }
+    for sid in sender_ids {
+        let cmd = BridgeCommand::Typing { to_id: sid.clone(), active };
+        if let Err(e) = send_bridge_command(process, &cmd).await {
+            log::debug!("[Bridge:{}] Failed to set typing indicator for {}: {}", chat_tool_id, sid, e);
+        }
+    }
+}
+
+/// Flag a message the spam filter caught so it is never forwarded.
+async fn flag_filtered_message(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    chat_tool_id: &str,
+    message_id: &str,
+    sender_id: &str,
+    reason: &str,
+) {
+    log::info!("[Bridge:{}] Filtered message from {}: {}", chat_tool_id, sender_id, reason);
+    let state_clone = state.clone();
+    let mid = message_id.to_string();
+    let r = reason.to_string();
+    if let Ok(Err(e)) = telemetry::spawn_blocking(move || chat_tool_repo::flag_message(&state_clone, &mid, &r)).await {
+        log::warn!("[Bridge:{}] Failed to flag message {}: {}", chat_tool_id, message_id, e);
+    }
+    events::emit(app, &events::CHAT_TOOL_MESSAGE_FILTERED, json!({
+            "chatToolId": chat_tool_id,
+            "messageId": message_id,
+            "senderId": sender_id,
+            "reason": reason
+        }),
+    );
+}
+
+/// Whether auto-replies to a sender are snoozed or a human has taken over.
+/// A failed lookup counts as not paused.
+async fn is_paused(state: &AppState, chat_tool_id: &str, sender_id: &str) -> bool {
+    let state_clone = state.clone();
+    let id = chat_tool_id.to_string();
+    let sid = sender_id.to_string();
+    let contact = telemetry::spawn_blocking(move || chat_tool_repo::find_contact(&state_clone, &id, &sid)).await;
+    matches!(contact, Ok(Ok(Some(contact))) if contact.auto_reply_paused())
+}
+
+/// Mark the messages of senders with paused auto-replies processed without
+/// a reply. Returns the messages that may be forwarded.
+async fn skip_paused(state: &AppState, chat_tool_id: &str, messages: Vec<ChatToolMessage>) -> Vec<ChatToolMessage> {
+    let mut paused: Vec<String> = Vec::new();
+    for sid in messages.iter().filter_map(|m| m.external_sender_id.as_ref()) {
+        if !paused.contains(sid) && is_paused(state, chat_tool_id, sid).await {
+            paused.push(sid.clone());
+        }
+    }
+    if paused.is_empty() {
+        return messages;
+    }
+
+    let (skipped, forwarded): (Vec<_>, Vec<_>) = messages
+        .into_iter()
+        .partition(|m| m.external_sender_id.as_ref().is_some_and(|sid| paused.contains(sid)));
+    log::info!("[Bridge:{}] Leaving {} message(s) from paused contacts", chat_tool_id, skipped.len());
+    let mids: Vec<String> = skipped.into_iter().map(|m| m.id).collect();
+    let state_clone = state.clone();
+    let _ = telemetry::spawn_blocking(move || chat_tool_repo::mark_messages_processed_batch(&state_clone, &mids, "")).await;
+    forwarded
+}
+
+/// Ask the Control Hub to classify each message when the LLM classifier is
+/// on. Returns the messages that may be forwarded; a failed classification
+/// lets the message through.
+async fn classify_batch(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    chat_tool_id: &str,
+    chat_tool_name: &str,
+    workspace_id: Option<&str>,
+    messages: Vec<ChatToolMessage>,
+) -> Vec<ChatToolMessage> {
+    let filter_config = config::current(state).chat_tool_spam_filter;
+    if !filter_config.enabled || !filter_config.llm_classifier {
+        return messages;
+    }
+    let mut passed = Vec::with_capacity(messages.len());
+    for msg in messages {
+        let sender = msg.external_sender_name.as_deref().unwrap_or("Unknown");
+        let prompt = spam_filter::classifier_prompt(sender, &msg.content);
+        match forward_to_control_hub(app, state, chat_tool_id, chat_tool_name, workspace_id, &prompt).await {
+            Ok(Some(reply)) if spam_filter::is_spam_verdict(&reply) => {
+                let sender_id = msg.external_sender_id.as_deref().unwrap_or("");
+                flag_filtered_message(app, state, chat_tool_id, &msg.id, sender_id, "Classified as spam by the Control Hub").await;
+            }
+            _ => passed.push(msg),
+        }
+    }
+    passed
+}
+
+/// Answer senders over their daily quota with the over-quota reply (at most
+/// once per NOTICE_INTERVAL_HOURS) and mark their messages processed.
+/// Returns the messages that may be forwarded; a failed lookup lets the
+/// message through.
+async fn enforce_quotas(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    chat_tool_id: &str,
+    chat_tool_name: &str,
+    templates: &ReplyTemplates,
+    messages: Vec<ChatToolMessage>,
+) -> Vec<ChatToolMessage> {
+    let mut sender_ids: Vec<String> = Vec::new();
+    for sid in messages.iter().filter_map(|m| m.external_sender_id.as_ref()) {
+        if !sender_ids.contains(sid) {
+            sender_ids.push(sid.clone());
+        }
+    }
+
+    let mut over_quota: Vec<String> = Vec::new();
+    for sid in sender_ids {
+        let state_clone = state.clone();
+        let id = chat_tool_id.to_string();
+        let s = sid.clone();
+        let contact = telemetry::spawn_blocking(move || chat_tool_repo::find_contact(&state_clone, &id, &s)).await;
+        let Ok(Ok(Some(contact))) = contact else {
+            continue;
+        };
+        let Some(reason) = quota::exceeded(&contact) else {
+            continue;
+        };
+        log::info!("[Bridge:{}] {} is over quota: {}", chat_tool_id, sid, reason);
+
+        let mids: Vec<String> = messages
+            .iter()
+            .filter(|m| m.external_sender_id.as_deref() == Some(sid.as_str()))
+            .map(|m| m.id.clone())
+            .collect();
+        let reply = templates.render(&templates.over_quota, &contact.name, chat_tool_name);
+        let state_clone = state.clone();
+        let r = reply.clone().unwrap_or_default();
+        let _ = telemetry::spawn_blocking(move || chat_tool_repo::mark_messages_processed_batch(&state_clone, &mids, &r)).await;
+
+        if let Some(reply) = reply {
+            let state_clone = state.clone();
+            let id = chat_tool_id.to_string();
+            let s = sid.clone();
+            let r = reply.clone();
+            let recently = telemetry::spawn_blocking(move || {
+                chat_tool_repo::sent_recently(&state_clone, &id, &s, &r, NOTICE_INTERVAL_HOURS)
+            })
+            .await;
+            if matches!(recently, Ok(Ok(false))) {
+                send_canned_reply(state, chat_tool_id, &sid, &reply).await;
+            }
+        }
+        events::emit(app, &events::CHAT_TOOL_QUOTA_EXCEEDED, json!({
+                "chatToolId": chat_tool_id,
+                "contactId": contact.id,
+                "senderId": sid,
+                "reason": reason
+            }),
+        );
+        over_quota.push(sid);
+    }
+
+    messages
+        .into_iter()
+        .filter(|m| !m.external_sender_id.as_ref().is_some_and(|sid| over_quota.contains(sid)))
+        .collect()
+}
+
+/// Count a Control Hub reply against the quotas of each sender in the batch.
+async fn record_quota_usage(
+    state: &AppState,
+    chat_tool_id: &str,
+    messages: &[ChatToolMessage],
+    sender_ids: &[String],
+    reply: &str,
+) {
+    let reply_tokens = quota::estimate_tokens(reply);
+    for sid in sender_ids {
+        let from_sender: Vec<&ChatToolMessage> =
+            messages.iter().filter(|m| m.external_sender_id.as_deref() == Some(sid.as_str())).collect();
+        let tokens = reply_tokens + from_sender.iter().map(|m| quota::estimate_tokens(&m.content)).sum::<i64>();
+        let name = from_sender.iter().find_map(|m| m.external_sender_name.clone()).unwrap_or_default();
+        let state_clone = state.clone();
+        let id = chat_tool_id.to_string();
+        let s = sid.clone();
+        if let Ok(Err(e)) = telemetry::spawn_blocking(move || {
+            chat_tool_repo::record_contact_usage(&state_clone, &id, &s, &name, tokens)
+        })
+        .await
+        {
+            log::warn!("[Bridge:{}] Failed to record quota usage of {}: {}", chat_tool_id, sid, e);
+        }
+    }
+}
+
 /// Process the queue of unprocessed messages for a chat tool.
 ///
 /// Loops until no more unprocessed messages remain:
@@ -714,7 +1216,7 @@ async fn process_message_queue(
         // 1. Fetch unprocessed messages
         let state_clone = state.clone();
         let ct_id = chat_tool_id.to_string();
-        let unprocessed = tokio::task::spawn_blocking(move || {
+        let unprocessed = telemetry::spawn_blocking(move || {
             chat_tool_repo::list_unprocessed_messages(&state_clone, &ct_id)
         })
         .await;
@@ -726,6 +1228,38 @@ async fn process_message_queue(
                 break;
             }
         };
+        let queue_depth = messages.len();
+
+        // Contacts paused while their messages waited get no reply
+        let messages = skip_paused(state, chat_tool_id, messages).await;
+        if messages.is_empty() {
+            continue;
+        }
+
+        // Messages the classifier flags drop out of the next fetch
+        let messages = classify_batch(app, state, chat_tool_id, chat_tool_name, workspace_id, messages).await;
+        if messages.is_empty() {
+            continue;
+        }
+
+        // Escalation and reply settings are re-read per batch so edits apply without a restart
+        let (owner_contact_id, escalation_threshold, typing_indicator, reply_chunk_chars, templates) = {
+            let state_clone = state.clone();
+            let ct_id = chat_tool_id.to_string();
+            match telemetry::spawn_blocking(move || chat_tool_repo::get_chat_tool(&state_clone, &ct_id)).await {
+                Ok(Ok(ct)) => {
+                    let templates = ct.reply_templates();
+                    (ct.owner_contact_id, ct.escalation_threshold, ct.typing_indicator, ct.reply_chunk_chars, templates)
+                }
+                _ => (None, None, false, None, ReplyTemplates::default()),
+            }
+        };
+
+        // Senders over their daily quota are answered without the Control Hub
+        let messages = enforce_quotas(app, state, chat_tool_id, chat_tool_name, &templates, messages).await;
+        if messages.is_empty() {
+            continue;
+        }
 
         log::info!(
             "[Bridge:{}] Processing batch of {} unprocessed messages",
@@ -753,9 +1287,17 @@ async fn process_message_queue(
             }
         }
 
-        let merged_prompt = prompt_parts.join("\n\n");
+        let mut merged_prompt = prompt_parts.join("\n\n");
+
+        if owner_contact_id.is_some() {
+            merged_prompt.push_str(escalation::ESCALATION_INSTRUCTIONS);
+        }
 
-        // 3. Send to Control Hub
+        // 3. Send to Control Hub, showing senders a typing indicator meanwhile
+        if typing_indicator {
+            set_typing(state, chat_tool_id, &sender_ids, true).await;
+        }
+        let hub_started = Instant::now();
         let agent_reply = forward_to_control_hub(
             app,
             state,
@@ -765,67 +1307,70 @@ async fn process_message_queue(
             &merged_prompt,
         )
         .await;
+        let hub_latency_ms = hub_started.elapsed().as_millis() as i64;
+        if typing_indicator {
+            set_typing(state, chat_tool_id, &sender_ids, false).await;
+        }
 
         match agent_reply {
-            Ok(Some(reply)) => {
+            Ok(Some(hub_reply)) => {
+                let verdict = escalation::evaluate_reply(&hub_reply, escalation_threshold);
+                if let (Some(owner), Some(reason)) = (owner_contact_id.as_deref(), verdict.escalate_reason.as_deref()) {
+                    let escalation = Escalation {
+                        chat_tool_name,
+                        owner_contact_id: owner,
+                        hub_reply: &hub_reply,
+                        reason,
+                        confidence: verdict.confidence,
+                    };
+                    escalate_batch(app, state, chat_tool_id, &messages, &escalation).await;
+                    metrics::record(state, chat_tool_id, "escalated", &messages, queue_depth, Some(hub_latency_ms), false);
+                    continue;
+                }
+                let reply = verdict.reply;
+
                 // 4. Mark batch as processed
                 let state_clone = state.clone();
                 let mids = message_ids.clone();
                 let r = reply.clone();
-                let _ = tokio::task::spawn_blocking(move || {
+                let _ = telemetry::spawn_blocking(move || {
                     chat_tool_repo::mark_messages_processed_batch(&state_clone, &mids, &r)
                 })
                 .await;
 
-                // 5. Send reply to each sender through bridge
+                // 5. Send reply to each sender through bridge; each send is
+                // stored as an outgoing message with its own delivery status.
+                // Long replies go out in chunks when the chat tool asks for it.
+                let chunks = chunking::split_reply(&reply, reply_chunk_chars.unwrap_or(0));
                 for sid in &sender_ids {
-                    let processes = state.chat_tool_processes.lock().await;
-                    if let Some(process) = processes.get(chat_tool_id) {
-                        let cmd = BridgeCommand::SendMessage {
-                            to_id: sid.clone(),
-                            content: reply.clone(),
-                            content_type: "text".into(),
-                        };
-                        if let Err(e) = send_bridge_command(process, &cmd).await {
+                    for (i, chunk) in chunks.iter().enumerate() {
+                        if i > 0 {
+                            tokio::time::sleep(Duration::from_millis(chunking::CHUNK_PAUSE_MS)).await;
+                        }
+                        if let Err(e) = delivery::send_tracked(state, chat_tool_id, sid, chunk, "text").await {
                             log::error!(
                                 "[Bridge:{}] Failed to send reply to {}: {}",
                                 chat_tool_id, sid, e
                             );
+                            break;
                         }
                     }
                 }
+                metrics::record(state, chat_tool_id, "replied", &messages, queue_depth, Some(hub_latency_ms), true);
 
                 // Increment sent count
                 let state_clone = state.clone();
                 let id = chat_tool_id.to_string();
-                let _ = tokio::task::spawn_blocking(move || {
+                let _ = telemetry::spawn_blocking(move || {
                     chat_tool_repo::increment_message_count(&state_clone, &id, "outgoing")
                 })
                 .await;
 
-                // Save outgoing message
-                let state_clone = state.clone();
-                let id = chat_tool_id.to_string();
-                let first_sender = sender_ids.first().cloned();
-                let r2 = reply.clone();
-                let _ = tokio::task::spawn_blocking(move || {
-                    chat_tool_repo::save_chat_tool_message(
-                        &state_clone,
-                        &id,
-                        "outgoing",
-                        first_sender.as_deref(),
-                        None,
-                        &r2,
-                        "text",
-                    )
-                })
-                .await;
+                record_quota_usage(state, chat_tool_id, &messages, &sender_ids, &reply).await;
 
                 // Emit processed events for each message in batch
                 for mid in &message_ids {
-                    let _ = app.emit(
-                        "chat_tool:message_processed",
-                        json!({
+                    events::emit(app, &events::CHAT_TOOL_MESSAGE_PROCESSED, json!({
                             "chatToolId": chat_tool_id,
                             "messageId": mid,
                             "agentResponse": reply
@@ -834,8 +1379,20 @@ async fn process_message_queue(
                 }
             }
             Ok(None) => {
-                // No Control Hub available — skip, messages stay unprocessed
+                // No Control Hub available — messages stay unprocessed; senders
+                // get the hub-unavailable reply if the chat tool has one
                 log::info!("[Bridge:{}] No Control Hub, skipping batch", chat_tool_id);
+                metrics::record(state, chat_tool_id, "no_hub", &messages, queue_depth, None, false);
+                for sid in &sender_ids {
+                    let sender_name = messages
+                        .iter()
+                        .find(|m| m.external_sender_id.as_deref() == Some(sid.as_str()))
+                        .and_then(|m| m.external_sender_name.as_deref())
+                        .unwrap_or("");
+                    if let Some(reply) = templates.render(&templates.hub_unavailable, sender_name, chat_tool_name) {
+                        send_canned_reply(state, chat_tool_id, sid, &reply).await;
+                    }
+                }
                 break;
             }
             Err(e) => {
@@ -843,12 +1400,13 @@ async fn process_message_queue(
                     "[Bridge:{}] Control Hub reply failed for batch: {}",
                     chat_tool_id, e
                 );
+                metrics::record(state, chat_tool_id, "failed", &messages, queue_depth, None, false);
                 // Mark all messages with error
                 for mid in &message_ids {
                     let state_clone = state.clone();
                     let mid_clone = mid.clone();
                     let err = e.to_string();
-                    let _ = tokio::task::spawn_blocking(move || {
+                    let _ = telemetry::spawn_blocking(move || {
                         chat_tool_repo::mark_message_error(&state_clone, &mid_clone, &err)
                     })
                     .await;
@@ -861,6 +1419,117 @@ async fn process_message_queue(
     }
 }
 
+/// A batch the Control Hub escalated, and the owner contact it goes to.
+struct Escalation<'a> {
+    chat_tool_name: &'a str,
+    owner_contact_id: &'a str,
+    hub_reply: &'a str,
+    reason: &'a str,
+    confidence: Option<f64>,
+}
+
+/// Forward a batch the Control Hub escalated to the owner contact, record it,
+/// and mark the messages processed without replying to the senders. Messages
+/// whose forward fails stay unprocessed, so a later batch picks them up again.
+async fn escalate_batch(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    chat_tool_id: &str,
+    messages: &[ChatToolMessage],
+    escalation: &Escalation<'_>,
+) {
+    let Escalation { chat_tool_name, owner_contact_id, hub_reply, reason, confidence } = *escalation;
+    log::info!(
+        "[Bridge:{}] Escalating {} message(s) to owner contact {}: {}",
+        chat_tool_id, messages.len(), owner_contact_id, reason
+    );
+
+    // One escalation per sender, with that sender's messages in order
+    let mut by_sender: Vec<(Option<String>, Option<String>, Vec<&ChatToolMessage>)> = Vec::new();
+    for msg in messages {
+        match by_sender.iter_mut().find(|(sid, _, _)| *sid == msg.external_sender_id) {
+            Some((_, _, sender_messages)) => sender_messages.push(msg),
+            None => by_sender.push((
+                msg.external_sender_id.clone(),
+                msg.external_sender_name.clone(),
+                vec![msg],
+            )),
+        }
+    }
+
+    let mut forwarded: Vec<String> = Vec::new();
+    for (sender_id, sender_name, sender_messages) in by_sender {
+        let original = sender_messages.iter().map(|m| m.content.as_str()).collect::<Vec<_>>().join("\n");
+        let forward = i18n::tr(
+            i18n::current(state),
+            Msg::EscalationForward,
+            &[
+                ("chat_tool", chat_tool_name),
+                ("sender", sender_name.as_deref().unwrap_or("Unknown")),
+                ("content", &original),
+                ("reason", reason),
+            ],
+        );
+        let sent = {
+            let processes = state.chat_tool_processes.lock().await;
+            match processes.get(chat_tool_id) {
+                Some(process) => {
+                    let cmd = BridgeCommand::SendMessage {
+                        to_id: owner_contact_id.to_string(),
+                        content: forward,
+                        content_type: "text".into(),
+                        ref_id: None,
+                    };
+                    send_bridge_command(process, &cmd).await.map_err(|e| e.to_string())
+                }
+                None => Err("bridge is not running".to_string()),
+            }
+        };
+        if let Err(e) = sent {
+            log::error!(
+                "[Bridge:{}] Failed to forward escalation to {}: {}",
+                chat_tool_id, owner_contact_id, e
+            );
+            continue;
+        }
+        forwarded.extend(sender_messages.iter().map(|m| m.id.clone()));
+
+        let state_clone = state.clone();
+        let id = chat_tool_id.to_string();
+        let hr = hub_reply.to_string();
+        let r = reason.to_string();
+        let owner = owner_contact_id.to_string();
+        let saved = telemetry::spawn_blocking(move || {
+            chat_tool_repo::save_escalation(
+                &state_clone, &id, sender_id.as_deref(), sender_name.as_deref(),
+                &original, &hr, &r, confidence, &owner,
+            )
+        })
+        .await;
+        match saved {
+            Ok(Ok(escalation)) => {
+                events::emit(app, &events::CHAT_TOOL_ESCALATED, json!({
+                        "chatToolId": chat_tool_id,
+                        "escalation": escalation
+                    }),
+                );
+            }
+            Ok(Err(e)) => log::error!("[Bridge:{}] Failed to record escalation: {}", chat_tool_id, e),
+            Err(e) => log::error!("[Bridge:{}] Failed to record escalation: {}", chat_tool_id, e),
+        }
+    }
+
+    if forwarded.is_empty() {
+        return;
+    }
+    let state_clone = state.clone();
+    let hr = hub_reply.to_string();
+    let _ = telemetry::spawn_blocking(move || {
+        chat_tool_repo::mark_messages_processed_batch(&state_clone, &forwarded, &hr)
+    })
+    .await;
+}
+
 /// Forward a message to the workspace's Control Hub agent and collect the full text response.
 ///
 /// Returns `Ok(None)` if no Control Hub is configured or it is not running — the caller
@@ -869,7 +1538,7 @@ async fn process_message_queue(
 /// Maintains a persistent ACP session per chat_tool_id so follow-up messages
 /// share context. If the session becomes invalid, a new one is created automatically.
 /// Reuses a single TaskRun per chat tool to track all message processing.
-async fn forward_to_control_hub(
+pub(crate) async fn forward_to_control_hub(
     app: &tauri::AppHandle,
     state: &AppState,
     chat_tool_id: &str,
@@ -882,7 +1551,7 @@ async fn forward_to_control_hub(
     // 1. Find the Control Hub agent for this workspace
     let state_clone = state.clone();
     let ws_id = workspace_id.map(|s| s.to_string());
-    let hub = tokio::task::spawn_blocking(move || {
+    let hub = telemetry::spawn_blocking(move || {
         agent_repo::get_control_hub(&state_clone, ws_id.as_deref())
     })
     .await
@@ -900,6 +1569,15 @@ async fn forward_to_control_hub(
     };
     let agent_id = hub.id.clone();
 
+    // Several chat tools may share this Control Hub; hold its lock until the
+    // response is collected so concurrent instances don't steal each other's replies.
+    // Taken before the process check so an idle shutdown cannot stop the hub in between.
+    let hub_lock = {
+        let mut locks = state.chat_tool_hub_locks.lock().await;
+        locks.entry(agent_id.clone()).or_default().clone()
+    };
+    let _hub_guard = hub_lock.lock().await;
+
     // 2. Ensure the Control Hub process is running (auto-start if needed)
     if let Err(e) = ensure_control_hub_running(app, state, &hub).await {
         log::warn!(
@@ -917,7 +1595,7 @@ async fn forward_to_control_hub(
         let state_clone = state.clone();
         let trid = task_run_id.clone();
         let pt = prompt_text.to_string();
-        let _ = tokio::task::spawn_blocking(move || {
+        let _ = telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_prompt(&state_clone, &trid, &pt)
         })
         .await;
@@ -927,7 +1605,7 @@ async fn forward_to_control_hub(
     {
         let state_clone = state.clone();
         let trid = task_run_id.clone();
-        let _ = tokio::task::spawn_blocking(move || {
+        let _ = telemetry::spawn_blocking(move || {
             task_run_repo::update_task_run_status(&state_clone, &trid, "running")
         })
         .await;
@@ -966,7 +1644,7 @@ async fn forward_to_control_hub(
             // Update task run as completed with summary
             let state_clone = state.clone();
             let trid = task_run_id.clone();
-            let _ = tokio::task::spawn_blocking(move || {
+            let _ = telemetry::spawn_blocking(move || {
                 task_run_repo::update_task_run_status(&state_clone, &trid, "completed")
             })
             .await;
@@ -974,12 +1652,12 @@ async fn forward_to_control_hub(
             let summary = text.clone();
             let state_clone = state.clone();
             let trid = task_run_id.clone();
-            let _ = tokio::task::spawn_blocking(move || {
+            let _ = telemetry::spawn_blocking(move || {
                 task_run_repo::update_task_run_summary(&state_clone, &trid, &summary)
             })
             .await;
 
-            let _ = app.emit("orchestration:task_run_updated", json!({
+            events::emit(app, &events::TASK_RUN_UPDATED, json!({
                 "taskRunId": task_run_id,
                 "status": "completed"
             }));
@@ -987,7 +1665,8 @@ async fn forward_to_control_hub(
         Err(e) => {
             // 7. If session error, clear old session and retry once
             let err_msg = e.to_string();
-            if err_msg.contains("session") || err_msg.contains("timed out") || err_msg.contains("channel closed") {
+            let stale_session = matches!(e, AppError::Acp(_) | AppError::Timeout(_) | AppError::Transport(_));
+            if stale_session {
                 log::warn!(
                     "[Bridge:{}] Session may be stale ({}), clearing and retrying",
                     chat_tool_id, err_msg
@@ -1026,7 +1705,7 @@ async fn forward_to_control_hub(
                     Ok(text) => {
                         let state_clone = state.clone();
                         let trid = task_run_id.clone();
-                        let _ = tokio::task::spawn_blocking(move || {
+                        let _ = telemetry::spawn_blocking(move || {
                             task_run_repo::update_task_run_status(&state_clone, &trid, "completed")
                         })
                         .await;
@@ -1034,12 +1713,12 @@ async fn forward_to_control_hub(
                         let summary = text.clone();
                         let state_clone = state.clone();
                         let trid = task_run_id.clone();
-                        let _ = tokio::task::spawn_blocking(move || {
+                        let _ = telemetry::spawn_blocking(move || {
                             task_run_repo::update_task_run_summary(&state_clone, &trid, &summary)
                         })
                         .await;
 
-                        let _ = app.emit("orchestration:task_run_updated", json!({
+                        events::emit(app, &events::TASK_RUN_UPDATED, json!({
                             "taskRunId": task_run_id,
                             "status": "completed"
                         }));
@@ -1047,12 +1726,12 @@ async fn forward_to_control_hub(
                     Err(_) => {
                         let state_clone = state.clone();
                         let trid = task_run_id.clone();
-                        let _ = tokio::task::spawn_blocking(move || {
+                        let _ = telemetry::spawn_blocking(move || {
                             task_run_repo::update_task_run_status(&state_clone, &trid, "failed")
                         })
                         .await;
 
-                        let _ = app.emit("orchestration:task_run_updated", json!({
+                        events::emit(app, &events::TASK_RUN_UPDATED, json!({
                             "taskRunId": task_run_id,
                             "status": "failed"
                         }));
@@ -1063,12 +1742,12 @@ async fn forward_to_control_hub(
                 // Non-retryable error — mark task run as failed
                 let state_clone = state.clone();
                 let trid = task_run_id.clone();
-                let _ = tokio::task::spawn_blocking(move || {
+                let _ = telemetry::spawn_blocking(move || {
                     task_run_repo::update_task_run_status(&state_clone, &trid, "failed")
                 })
                 .await;
 
-                let _ = app.emit("orchestration:task_run_updated", json!({
+                events::emit(app, &events::TASK_RUN_UPDATED, json!({
                     "taskRunId": task_run_id,
                     "status": "failed"
                 }));
@@ -1104,10 +1783,7 @@ async fn ensure_control_hub_running(
     );
 
     let acp_command = agent.acp_command.clone().ok_or_else(|| {
-        AppError::Internal(format!(
-            "Control Hub agent {} has no ACP command configured",
-            agent_id
-        ))
+        AppError::AgentNotConfigured { agent_id: agent_id.to_string() }
     })?;
 
     let args: Vec<String> = agent
@@ -1149,6 +1825,7 @@ async fn ensure_control_hub_running(
 
     // Spawn process
     let process = acp_manager::spawn_agent_process(
+        state,
         agent_id,
         &resolved.command,
         &resolved.args,
@@ -1203,10 +1880,10 @@ async fn ensure_control_hub_running(
             match processes.get_mut(agent_id) {
                 Some(process) => process.message_rx.try_recv(),
                 None => {
-                    return Err(AppError::Internal(format!(
-                        "Control Hub agent {} disappeared during init",
-                        agent_id
-                    )));
+                    return Err(AppError::AgentProcessLost {
+                        agent_id: agent_id.to_string(),
+                        process_key: agent_id.to_string(),
+                    });
                 }
             }
         };
@@ -1221,7 +1898,7 @@ async fn ensure_control_hub_running(
             }
             Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
                 if std::time::Instant::now() >= init_deadline {
-                    return Err(AppError::Transport(
+                    return Err(AppError::Timeout(
                         "Timeout (120s) waiting for Control Hub initialization".into(),
                     ));
                 }
@@ -1241,7 +1918,7 @@ async fn ensure_control_hub_running(
             .get("message")
             .and_then(|m| m.as_str())
             .unwrap_or("Unknown error");
-        return Err(AppError::Internal(format!(
+        return Err(AppError::Acp(format!(
             "Control Hub initialize failed: {}",
             msg
         )));
@@ -1253,9 +1930,7 @@ async fn ensure_control_hub_running(
     );
 
     // Emit event so frontend shows the agent as running
-    let _ = app.emit(
-        "acp:agent_started",
-        &json!({
+    events::emit(app, &events::ACP_AGENT_STARTED, json!({
             "agent_id": agent_id,
             "status": "Running"
         }),
@@ -1292,7 +1967,7 @@ async fn get_or_create_task_run(
     let ws = workspace_id.map(|s| s.to_string());
     let t = title.clone();
     let pt = prompt_preview.to_string();
-    let task_run = tokio::task::spawn_blocking(move || {
+    let task_run = telemetry::spawn_blocking(move || {
         task_run_repo::create_task_run(
             &state_clone, &nid, &t, &pt, &aid, "running", ws.as_deref(),
         )
@@ -1305,9 +1980,7 @@ async fn get_or_create_task_run(
         runs.insert(chat_tool_id.to_string(), new_id.clone());
     }
 
-    let _ = app.emit(
-        "orchestration:task_run_created",
-        json!({ "taskRun": task_run }),
+    events::emit(app, &events::TASK_RUN_CREATED, json!({ "taskRun": task_run }),
     );
 
     Ok(new_id)
@@ -1365,7 +2038,7 @@ async fn get_or_create_session(
     let deadline = std::time::Instant::now() + std::time::Duration::from_secs(90);
     let response = loop {
         if std::time::Instant::now() >= deadline {
-            return Err(AppError::Internal("Timeout waiting for session/new response".into()));
+            return Err(AppError::Timeout("Timeout (90s) waiting for session/new response".into()));
         }
 
         let recv_result = {
@@ -1390,7 +2063,7 @@ async fn get_or_create_session(
                 continue;
             }
             Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
-                return Err(AppError::Internal("Agent channel closed during session creation".into()));
+                return Err(AppError::Transport("Agent channel closed during session creation".into()));
             }
         }
     };
@@ -1398,16 +2071,16 @@ async fn get_or_create_session(
     // Check for error
     if let Some(error) = response.get("error") {
         let msg = error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error");
-        return Err(AppError::Internal(format!("session/new failed: {}", msg)));
+        return Err(AppError::Acp(format!("session/new failed: {}", msg)));
     }
 
     let result = response.get("result").ok_or_else(|| {
-        AppError::Internal("No result in session/new response".into())
+        AppError::Acp("No result in session/new response".into())
     })?;
     let session_id = result
         .get("sessionId")
         .and_then(|s| s.as_str())
-        .ok_or_else(|| AppError::Internal("No sessionId in session/new response".into()))?
+        .ok_or_else(|| AppError::Acp("No sessionId in session/new response".into()))?
         .to_string();
 
     log::info!("[Bridge:{}] Created ACP session: {}", chat_tool_id, session_id);
@@ -1447,7 +2120,7 @@ async fn collect_response(
     loop {
         if std::time::Instant::now() >= deadline {
             if collected_text.is_empty() {
-                return Err(AppError::Internal("Agent response timed out".into()));
+                return Err(AppError::Timeout("Timeout (120s) waiting for the agent's response".into()));
             }
             break;
         }
@@ -1526,7 +2199,7 @@ async fn collect_response(
             }
             Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
                 if collected_text.is_empty() {
-                    return Err(AppError::Internal(
+                    return Err(AppError::Transport(
                         "Agent message channel closed".into(),
                     ));
                 }
@@ -1536,9 +2209,10 @@ async fn collect_response(
     }
 
     if collected_text.is_empty() {
-        return Err(AppError::Internal(
-            "Agent returned empty response".into(),
-        ));
+        return Err(AppError::AgentNoResponse {
+            agent_id: agent_id.to_string(),
+            task_run_id: None,
+        });
     }
 
     log::info!(
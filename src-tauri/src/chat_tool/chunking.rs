@@ -0,0 +1,89 @@
+//! Splitting long Control Hub replies into several chat messages.
+//!
+//! Chat apps truncate or collapse very long messages, so a chat tool can set
+//! `reply_chunk_chars` to send a reply as a series of shorter messages. Breaks
+//! prefer paragraph boundaries, then line boundaries, then whitespace; a
+//! single word longer than the limit is cut.
+
+/// Pause between chunks so they arrive in order and read like a conversation.
+pub const CHUNK_PAUSE_MS: u64 = 800;
+
+/// Split `text` into pieces of at most `max_chars` characters. A limit of
+/// zero or less returns the text unchanged.
+pub fn split_reply(text: &str, max_chars: i64) -> Vec<String> {
+    let text = text.trim();
+    if max_chars <= 0 || text.chars().count() as i64 <= max_chars {
+        return vec![text.to_string()];
+    }
+    let max = max_chars as usize;
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        push_piece(&mut chunks, &mut current, paragraph, "\n\n", max);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Append `piece` to `current`, starting a new chunk when it would not fit
+/// and breaking the piece down when it exceeds `max` on its own.
+fn push_piece(chunks: &mut Vec<String>, current: &mut String, piece: &str, separator: &str, max: usize) {
+    let len = piece.chars().count();
+    if len > max {
+        match separator {
+            "\n\n" => {
+                for line in piece.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                    push_piece(chunks, current, line, "\n", max);
+                }
+            }
+            "\n" => {
+                for word in piece.split_whitespace() {
+                    push_piece(chunks, current, word, " ", max);
+                }
+            }
+            _ => {
+                let chars: Vec<char> = piece.chars().collect();
+                for part in chars.chunks(max) {
+                    if !current.is_empty() {
+                        chunks.push(std::mem::take(current));
+                    }
+                    current.extend(part);
+                }
+            }
+        }
+        return;
+    }
+
+    let needed = if current.is_empty() { len } else { current.chars().count() + separator.len() + len };
+    if needed > max {
+        chunks.push(std::mem::take(current));
+    }
+    if !current.is_empty() {
+        current.push_str(separator);
+    }
+    current.push_str(piece);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_the_largest_boundary_that_fits() {
+        assert_eq!(split_reply("short reply", 0), vec!["short reply"]);
+        assert_eq!(split_reply("short reply", 100), vec!["short reply"]);
+
+        let text = "First paragraph.\n\nSecond paragraph.\n\nThird.";
+        assert_eq!(split_reply(text, 36), vec!["First paragraph.\n\nSecond paragraph.", "Third."]);
+
+        assert_eq!(split_reply("one two three four", 9), vec!["one two", "three", "four"]);
+        assert_eq!(split_reply("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+
+        for chunk in split_reply("第一行内容\n第二行内容比较长一些\n\n第三段", 8) {
+            assert!(chunk.chars().count() <= 8, "{chunk:?}");
+        }
+    }
+}
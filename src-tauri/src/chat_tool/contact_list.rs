@@ -0,0 +1,287 @@
+//! Contact lists of a chat tool, so an existing customer list with its
+//! block list, groups and quotas can be loaded before the bridge syncs any
+//! contacts, and exported again.
+//!
+//! A CSV list starts with a header row. `external_id` is required; `name`,
+//! `contact_type`, `is_blocked` (or `blocked`), `groups` (separated by `;`),
+//! `max_replies_per_day` and `max_tokens_per_day` are optional and other
+//! columns are ignored. A JSON list is an array of objects with the same
+//! fields. Empty values keep what an existing contact already has.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat_tool::quota;
+use crate::chat_tool::transcript::csv_field;
+use crate::error::{AppError, AppResult};
+use crate::models::chat_tool::{ChatToolContact, ContactRecord};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContactListFormat {
+    Csv,
+    Json,
+}
+
+impl ContactListFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+
+    /// The format of a contact list file, by its extension.
+    pub fn of_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+const CSV_COLUMNS: [&str; 7] = [
+    "external_id",
+    "name",
+    "contact_type",
+    "is_blocked",
+    "groups",
+    "max_replies_per_day",
+    "max_tokens_per_day",
+];
+
+/// The contacts of a list and the problems of the rows that were skipped.
+/// Fails when the list as a whole cannot be read.
+pub fn parse(format: ContactListFormat, text: &str) -> AppResult<(Vec<ContactRecord>, Vec<String>)> {
+    let rows: Vec<(String, Result<ContactRecord, String>)> = match format {
+        ContactListFormat::Csv => parse_csv_list(text)?,
+        ContactListFormat::Json => serde_json::from_str::<Vec<ContactRecord>>(text)
+            .map_err(|e| AppError::InvalidRequest(format!("Invalid contact list: {}", e)))?
+            .into_iter()
+            .enumerate()
+            .map(|(i, record)| (format!("Contact {}", i + 1), Ok(record)))
+            .collect(),
+    };
+
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+    for (at, record) in rows {
+        match record.and_then(normalize) {
+            Ok(record) => records.push(record),
+            Err(e) => errors.push(format!("{}: {}", at, e)),
+        }
+    }
+    Ok((records, errors))
+}
+
+fn normalize(mut record: ContactRecord) -> Result<ContactRecord, String> {
+    record.external_id = record.external_id.trim().to_string();
+    if record.external_id.is_empty() {
+        return Err("missing external_id".into());
+    }
+    let non_empty = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    record.name = non_empty(record.name);
+    record.contact_type = non_empty(record.contact_type);
+    record.groups = record.groups.map(|groups| {
+        let mut unique: Vec<String> = Vec::new();
+        for group in groups.iter().map(|g| g.trim()).filter(|g| !g.is_empty()) {
+            if !unique.iter().any(|g| g == group) {
+                unique.push(group.to_string());
+            }
+        }
+        unique
+    });
+    quota::validate(record.max_replies_per_day, record.max_tokens_per_day)?;
+    Ok(record)
+}
+
+fn parse_csv_list(text: &str) -> AppResult<Vec<(String, Result<ContactRecord, String>)>> {
+    let mut rows = parse_csv(text).into_iter().enumerate();
+    let header: Vec<String> = rows
+        .next()
+        .map(|(_, header)| header.iter().map(|h| h.trim().to_ascii_lowercase()).collect())
+        .unwrap_or_default();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let Some(id_column) = column("external_id") else {
+        return Err(AppError::InvalidRequest("The contact list has no external_id column".into()));
+    };
+    let columns = CSV_COLUMNS.map(|name| {
+        if name == "is_blocked" {
+            column(name).or_else(|| column("blocked"))
+        } else {
+            column(name)
+        }
+    });
+
+    Ok(rows
+        .filter(|(_, row)| row.iter().any(|field| !field.trim().is_empty()))
+        // Row numbers as a spreadsheet shows them, the header being row 1
+        .map(|(i, row)| (format!("Row {}", i + 1), csv_record(&row, id_column, &columns)))
+        .collect())
+}
+
+/// The record of a CSV row; `columns` holds the index of each of
+/// `CSV_COLUMNS` in the row, if the list has it.
+fn csv_record(row: &[String], id_column: usize, columns: &[Option<usize>; 7]) -> Result<ContactRecord, String> {
+    let cell = |index: Option<usize>| {
+        index
+            .and_then(|i| row.get(i))
+            .map(|field| field.trim())
+            .filter(|field| !field.is_empty())
+    };
+    let number = |index: Option<usize>, name: &str| {
+        cell(index)
+            .map(|value| value.parse::<i64>().map_err(|_| format!("{} is not a number: {}", name, value)))
+            .transpose()
+    };
+    let is_blocked = match cell(columns[3]).map(str::to_ascii_lowercase).as_deref() {
+        None => None,
+        Some("true" | "yes" | "y" | "1") => Some(true),
+        Some("false" | "no" | "n" | "0") => Some(false),
+        Some(other) => return Err(format!("is_blocked is not true or false: {}", other)),
+    };
+    Ok(ContactRecord {
+        external_id: row.get(id_column).cloned().unwrap_or_default(),
+        name: cell(columns[1]).map(String::from),
+        contact_type: cell(columns[2]).map(String::from),
+        is_blocked,
+        groups: cell(columns[4]).map(|groups| groups.split(';').map(String::from).collect()),
+        max_replies_per_day: number(columns[5], "max_replies_per_day")?,
+        max_tokens_per_day: number(columns[6], "max_tokens_per_day")?,
+    })
+}
+
+/// Rows of a CSV text. Quoted fields may hold commas, doubled quotes and
+/// line breaks.
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+pub fn render(format: ContactListFormat, contacts: &[ChatToolContact]) -> AppResult<String> {
+    let records: Vec<ContactRecord> = contacts.iter().map(ContactRecord::from).collect();
+    Ok(match format {
+        ContactListFormat::Csv => {
+            let mut out = CSV_COLUMNS.join(",");
+            out.push('\n');
+            for record in &records {
+                let number = |n: Option<i64>| n.map(|n| n.to_string()).unwrap_or_default();
+                let fields = [
+                    record.external_id.clone(),
+                    record.name.clone().unwrap_or_default(),
+                    record.contact_type.clone().unwrap_or_default(),
+                    record.is_blocked.unwrap_or_default().to_string(),
+                    record.groups.as_deref().unwrap_or_default().join(";"),
+                    number(record.max_replies_per_day),
+                    number(record.max_tokens_per_day),
+                ];
+                out.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+                out.push('\n');
+            }
+            out
+        }
+        ContactListFormat::Json => serde_json::to_string_pretty(&records)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_csv_contact_lists() {
+        let text = "\u{feff}Name,External_ID,Blocked,Groups,Notes,max_replies_per_day\r\n\
+                    \"Lee, Ann\",wx_1,yes,VIP; wholesale;VIP,\"said \"\"hi\"\"\nthen left\",5\r\n\
+                    ,,,,\r\n\
+                    Bob,wx_2,,,,\n\
+                    Eve,,true,,,\n\
+                    Mal,wx_3,maybe,,,\n\
+                    Zed,wx_4,no,,,-1";
+        let (records, errors) = parse(ContactListFormat::Csv, text).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                ContactRecord {
+                    external_id: "wx_1".into(),
+                    name: Some("Lee, Ann".into()),
+                    is_blocked: Some(true),
+                    groups: Some(vec!["VIP".into(), "wholesale".into()]),
+                    max_replies_per_day: Some(5),
+                    ..Default::default()
+                },
+                ContactRecord { external_id: "wx_2".into(), name: Some("Bob".into()), ..Default::default() },
+            ]
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "Row 5: missing external_id",
+                "Row 6: is_blocked is not true or false: maybe",
+                "Row 7: Quotas cannot be negative",
+            ]
+        );
+        assert!(parse(ContactListFormat::Csv, "name,blocked\nAnn,yes").is_err());
+    }
+
+    #[test]
+    fn exported_lists_read_back() {
+        let contact = ChatToolContact {
+            id: "c1".into(),
+            chat_tool_id: "t1".into(),
+            external_id: "wx_1".into(),
+            name: "Lee, Ann".into(),
+            avatar_url: None,
+            contact_type: "personal".into(),
+            is_blocked: true,
+            max_replies_per_day: None,
+            max_tokens_per_day: Some(1000),
+            replies_today: 0,
+            tokens_today: 0,
+            snoozed_until: None,
+            takeover_started_at: None,
+            groups: vec!["VIP".into()],
+            run_notifications: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        for format in [ContactListFormat::Csv, ContactListFormat::Json] {
+            let text = render(format, std::slice::from_ref(&contact)).unwrap();
+            let (records, errors) = parse(format, &text).unwrap();
+            assert!(errors.is_empty());
+            assert_eq!(records, vec![ContactRecord::from(&contact)]);
+        }
+    }
+}
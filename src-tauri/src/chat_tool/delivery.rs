@@ -0,0 +1,247 @@
+//! Delivery tracking for outgoing chat tool messages.
+//!
+//! A tracked send stores the outgoing message first, as `pending`, and passes
+//! its id to the bridge as `ref_id`. The bridge acknowledges with
+//! `message_status` events (sent, delivered, read, failed) which update the
+//! stored status and are emitted as `chat_tool:message_status`. Failed sends
+//! are retried with exponential backoff until MAX_SEND_ATTEMPTS is reached.
+//! Bridges that never acknowledge leave their messages `pending`.
+
+use serde_json::json;
+use tauri::{AppHandle, Emitter};
+use tokio::time::Duration;
+
+use crate::db::chat_tool_repo;
+use crate::error::{AppError, AppResult};
+use crate::models::chat_tool::{BridgeCommand, ChatToolMessage};
+use crate::state::AppState;
+use crate::telemetry;
+
+use super::manager::send_bridge_command;
+
+pub const PENDING: &str = "pending";
+pub const SENT: &str = "sent";
+pub const DELIVERED: &str = "delivered";
+pub const READ: &str = "read";
+pub const FAILED: &str = "failed";
+
+/// Attempts per message, the first send included.
+pub const MAX_SEND_ATTEMPTS: i64 = 4;
+const RETRY_BASE_SECS: u64 = 5;
+
+fn rank(status: &str) -> u8 {
+    match status {
+        SENT => 1,
+        DELIVERED => 2,
+        READ => 3,
+        _ => 0,
+    }
+}
+
+/// Whether a message in `current` status may move to `next`. Acknowledgements
+/// can arrive out of order, so a status never moves backwards, and a message
+/// the recipient already has cannot fail.
+fn accepts(current: Option<&str>, next: &str) -> bool {
+    match next {
+        PENDING => current == Some(FAILED),
+        FAILED => !matches!(current, Some(DELIVERED | READ)),
+        SENT | DELIVERED | READ => rank(next) > current.map_or(0, rank),
+        _ => false,
+    }
+}
+
+/// Wait before the next attempt: 5s, 10s, 20s, ...
+fn retry_delay(failed_attempts: i64) -> Duration {
+    let exponent = failed_attempts.clamp(1, 8) as u32 - 1;
+    Duration::from_secs(RETRY_BASE_SECS * 2u64.pow(exponent))
+}
+
+fn send_command(message: &ChatToolMessage) -> BridgeCommand {
+    BridgeCommand::SendMessage {
+        to_id: message.external_sender_id.clone().unwrap_or_default(),
+        content: message.content.clone(),
+        content_type: message.content_type.clone(),
+        ref_id: Some(message.id.clone()),
+    }
+}
+
+/// Store an outgoing message and send it through the chat tool's bridge with
+/// delivery tracking. Fails if the chat tool is not running or the bridge
+/// cannot be written to; in the latter case the stored message is `failed`.
+pub async fn send_tracked(
+    state: &AppState,
+    chat_tool_id: &str,
+    to_id: &str,
+    content: &str,
+    content_type: &str,
+) -> AppResult<ChatToolMessage> {
+    let processes = state.chat_tool_processes.lock().await;
+    let process = processes.get(chat_tool_id).ok_or_else(|| {
+        AppError::InvalidRequest(format!("Chat tool {} is not running", chat_tool_id))
+    })?;
+
+    let state_clone = state.clone();
+    let id = chat_tool_id.to_string();
+    let to = to_id.to_string();
+    let c = content.to_string();
+    let ct = content_type.to_string();
+    let message = telemetry::spawn_blocking(move || {
+        let message = chat_tool_repo::save_chat_tool_message(&state_clone, &id, "outgoing", Some(&to), None, &c, &ct)?;
+        chat_tool_repo::set_delivery_status(&state_clone, &message.id, PENDING, None)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    if let Err(e) = send_bridge_command(process, &send_command(&message)).await {
+        let state_clone = state.clone();
+        let mid = message.id.clone();
+        let err = e.to_string();
+        let _ = telemetry::spawn_blocking(move || {
+            chat_tool_repo::set_delivery_status(&state_clone, &mid, FAILED, Some(&err))
+        })
+        .await;
+        return Err(e);
+    }
+    Ok(message)
+}
+
+/// Apply a status to a tracked message and emit it. Returns the updated
+/// message, or None when the status was ignored.
+async fn record(
+    app: &AppHandle,
+    state: &AppState,
+    chat_tool_id: &str,
+    message_id: &str,
+    status: &str,
+    error: Option<&str>,
+) -> Option<ChatToolMessage> {
+    let state_clone = state.clone();
+    let mid = message_id.to_string();
+    let current = match telemetry::spawn_blocking(move || chat_tool_repo::get_chat_tool_message(&state_clone, &mid)).await {
+        Ok(Ok(message)) if message.chat_tool_id == chat_tool_id => message,
+        _ => {
+            log::debug!("[Bridge:{}] Status for unknown message {}", chat_tool_id, message_id);
+            return None;
+        }
+    };
+    if !accepts(current.delivery_status.as_deref(), status) {
+        return None;
+    }
+
+    let state_clone = state.clone();
+    let mid = message_id.to_string();
+    let s = status.to_string();
+    let err = error.map(|e| e.to_string());
+    let updated = match telemetry::spawn_blocking(move || {
+        chat_tool_repo::set_delivery_status(&state_clone, &mid, &s, err.as_deref())
+    })
+    .await
+    {
+        Ok(Ok(message)) => message,
+        Ok(Err(e)) => {
+            log::warn!("[Bridge:{}] Failed to record status of {}: {}", chat_tool_id, message_id, e);
+            return None;
+        }
+        Err(e) => {
+            log::warn!("[Bridge:{}] Failed to record status of {}: {}", chat_tool_id, message_id, e);
+            return None;
+        }
+    };
+
+    let _ = app.emit(
+        "chat_tool:message_status",
+        json!({
+            "chatToolId": chat_tool_id,
+            "messageId": message_id,
+            "status": status,
+            "error": updated.delivery_error,
+            "attempts": updated.delivery_attempts
+        }),
+    );
+    Some(updated)
+}
+
+/// Handle a `message_status` acknowledgement from the bridge.
+pub async fn handle_status(
+    app: &AppHandle,
+    state: &AppState,
+    chat_tool_id: &str,
+    ref_id: &str,
+    status: &str,
+    error: Option<&str>,
+) {
+    if status == FAILED {
+        log::warn!(
+            "[Bridge:{}] Delivery of {} failed: {}",
+            chat_tool_id, ref_id, error.unwrap_or("no reason given")
+        );
+    }
+    if let Some(message) = record(app, state, chat_tool_id, ref_id, status, error).await {
+        if status == FAILED {
+            schedule_retry(app.clone(), state.clone(), chat_tool_id.to_string(), message);
+        }
+    }
+}
+
+/// Resend a failed message in the background, backing off after each
+/// failure, until it leaves `failed` or runs out of attempts.
+fn schedule_retry(app: AppHandle, state: AppState, chat_tool_id: String, message: ChatToolMessage) {
+    if message.delivery_attempts >= MAX_SEND_ATTEMPTS {
+        log::warn!(
+            "[Bridge:{}] Giving up on message {} after {} attempts",
+            chat_tool_id, message.id, message.delivery_attempts
+        );
+        return;
+    }
+    tokio::spawn(async move {
+        let mut failed_attempts = message.delivery_attempts;
+        loop {
+            tokio::time::sleep(retry_delay(failed_attempts)).await;
+
+            // A late acknowledgement may have settled the message meanwhile
+            let Some(message) = record(&app, &state, &chat_tool_id, &message.id, PENDING, None).await else {
+                return;
+            };
+            let sent = {
+                let processes = state.chat_tool_processes.lock().await;
+                match processes.get(&chat_tool_id) {
+                    Some(process) => send_bridge_command(process, &send_command(&message)).await,
+                    None => Err(AppError::InvalidRequest(format!("Chat tool {} is not running", chat_tool_id))),
+                }
+            };
+            let Err(e) = sent else {
+                log::info!("[Bridge:{}] Resent message {} (attempt {})", chat_tool_id, message.id, failed_attempts + 1);
+                return;
+            };
+            match record(&app, &state, &chat_tool_id, &message.id, FAILED, Some(&e.to_string())).await {
+                Some(updated) if updated.delivery_attempts < MAX_SEND_ATTEMPTS => {
+                    failed_attempts = updated.delivery_attempts;
+                }
+                _ => {
+                    log::warn!("[Bridge:{}] Giving up on message {}: {}", chat_tool_id, message.id, e);
+                    return;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_never_moves_backwards() {
+        assert!(accepts(Some(PENDING), SENT));
+        assert!(accepts(Some(SENT), READ));
+        assert!(!accepts(Some(DELIVERED), SENT));
+        assert!(accepts(Some(SENT), FAILED));
+        assert!(!accepts(Some(READ), FAILED));
+        assert!(accepts(Some(FAILED), PENDING));
+        assert!(!accepts(Some(SENT), PENDING));
+        assert!(!accepts(Some(PENDING), "bounced"));
+
+        assert_eq!(retry_delay(1), Duration::from_secs(5));
+        assert_eq!(retry_delay(3), Duration::from_secs(20));
+    }
+}
@@ -0,0 +1,107 @@
+//! Escalation of chat tool messages to a human. When a chat tool has an owner
+//! contact, the Control Hub is asked to mark replies it cannot stand behind;
+//! those messages are forwarded to the owner instead of being auto-replied.
+
+/// Appended to the Control Hub prompt when the chat tool has an owner contact.
+pub const ESCALATION_INSTRUCTIONS: &str = "\n\n---\n\
+If these messages need a human (you cannot answer reliably, or they ask for a decision \
+or commitment you should not make), reply only with <escalate>short reason</escalate>. \
+Otherwise end your reply with <confidence>N</confidence>, where N between 0 and 1 is how \
+confident you are that the reply is correct.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HubVerdict {
+    /// Reply text with the markers removed
+    pub reply: String,
+    /// Set when the reply must go to the owner contact instead of the sender
+    pub escalate_reason: Option<String>,
+    pub confidence: Option<f64>,
+}
+
+/// Read the escalation and confidence markers out of a Control Hub reply.
+/// A confidence below `threshold` escalates the reply as well.
+pub fn evaluate_reply(reply: &str, threshold: Option<f64>) -> HubVerdict {
+    let (without_confidence, confidence_text) = take_tag(reply, "confidence");
+    let confidence = confidence_text.and_then(|c| c.trim().parse::<f64>().ok());
+    let (cleaned, escalate_text) = take_tag(&without_confidence, "escalate");
+
+    let escalate_reason = match (escalate_text, confidence, threshold) {
+        (Some(reason), _, _) => Some(if reason.trim().is_empty() {
+            "Control Hub requested a human".to_string()
+        } else {
+            reason.trim().to_string()
+        }),
+        (None, Some(c), Some(t)) if c < t => Some(format!("Confidence {:.2} is below {:.2}", c, t)),
+        _ => None,
+    };
+
+    HubVerdict {
+        reply: cleaned.trim().to_string(),
+        escalate_reason,
+        confidence,
+    }
+}
+
+/// Remove the first `<tag>…</tag>` (or a bare `<tag>` / `<tag/>`) from `text`,
+/// returning the remaining text and the tag's content.
+fn take_tag(text: &str, tag: &str) -> (String, Option<String>) {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let self_closing = format!("<{}/>", tag);
+
+    if let Some(start) = text.find(&self_closing) {
+        let rest = format!("{}{}", &text[..start], &text[start + self_closing.len()..]);
+        return (rest, Some(String::new()));
+    }
+    let Some(start) = text.find(&open) else {
+        return (text.to_string(), None);
+    };
+    let after = &text[start + open.len()..];
+    match after.find(&close) {
+        Some(end) => (
+            format!("{}{}", &text[..start], &after[end + close.len()..]),
+            Some(after[..end].to_string()),
+        ),
+        None => (text[..start].to_string(), Some(after.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn low_confidence_escalates_and_is_stripped() {
+        let verdict = evaluate_reply("Probably Tuesday. <confidence>0.4</confidence>", Some(0.6));
+        assert_eq!(verdict.reply, "Probably Tuesday.");
+        assert_eq!(verdict.confidence, Some(0.4));
+        assert_eq!(verdict.escalate_reason.as_deref(), Some("Confidence 0.40 is below 0.60"));
+
+        let confident = evaluate_reply("Tuesday. <confidence>0.9</confidence>", Some(0.6));
+        assert_eq!(confident.escalate_reason, None);
+    }
+
+    #[test]
+    fn explicit_escalation_uses_reason_or_default() {
+        let verdict = evaluate_reply("<escalate> needs a refund decision </escalate>", None);
+        assert_eq!(verdict.reply, "");
+        assert_eq!(verdict.escalate_reason.as_deref(), Some("needs a refund decision"));
+
+        let empty = evaluate_reply("<escalate></escalate>", None);
+        assert_eq!(empty.escalate_reason.as_deref(), Some("Control Hub requested a human"));
+    }
+
+    #[test]
+    fn no_threshold_never_escalates_on_confidence() {
+        let verdict = evaluate_reply("Maybe. <confidence>0.1</confidence>", None);
+        assert_eq!(verdict.escalate_reason, None);
+        assert_eq!(verdict.confidence, Some(0.1));
+    }
+
+    #[test]
+    fn take_tag_handles_self_closing_unclosed_and_absent() {
+        assert_eq!(take_tag("a <escalate/> b", "escalate"), ("a  b".to_string(), Some(String::new())));
+        assert_eq!(take_tag("a <escalate>why", "escalate"), ("a ".to_string(), Some("why".to_string())));
+        assert_eq!(take_tag("plain", "escalate"), ("plain".to_string(), None));
+    }
+}
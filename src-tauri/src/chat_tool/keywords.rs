@@ -0,0 +1,353 @@
+//! Keyword commands chat tool contacts can send instead of a prompt, such as
+//! "/status". They are answered from app data before a message is handed to
+//! the Control Hub for auto-reply. Only the chat tool's owner contact and
+//! contacts in the allowed group may send them; from anyone else they are
+//! ordinary messages.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::acp::templates;
+use crate::config;
+use crate::db::{task_run_repo, template_repo};
+use crate::error::{AppError, AppResult};
+use crate::i18n::{self, Locale, Msg};
+use crate::models::chat_tool::{ChatTool, ChatToolContact};
+use crate::models::task_run::RunPriority;
+use crate::models::template::RunTemplateRequest;
+use crate::state::AppState;
+
+/// At most this many task runs are listed in a reply.
+const MAX_LISTED_RUNS: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandKeywords {
+    pub help: String,
+    pub status: String,
+    pub tasks: String,
+    pub run: String,
+    /// Contacts in this group may send commands besides the chat tool's
+    /// owner contact; empty leaves them to the owner
+    pub allowed_group: String,
+}
+
+impl Default for CommandKeywords {
+    fn default() -> Self {
+        Self {
+            help: "/help".into(),
+            status: "/status".into(),
+            tasks: "/tasks".into(),
+            run: "/run".into(),
+            allowed_group: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeywordCommand {
+    Help,
+    Status,
+    Tasks,
+    /// Start the named template; `key=value` arguments fill its variables.
+    Run {
+        template: String,
+        variables: HashMap<String, String>,
+    },
+}
+
+pub fn load_keywords(state: &AppState) -> CommandKeywords {
+    config::current(state).chat_tool_command_keywords
+}
+
+/// Whether the sender may use keyword commands: the chat tool's owner
+/// contact, or a contact in the allowed group.
+pub fn may_send_commands(
+    chat_tool: &ChatTool,
+    sender_id: &str,
+    contact: Option<&ChatToolContact>,
+    keywords: &CommandKeywords,
+) -> bool {
+    if chat_tool.owner_contact_id.as_deref() == Some(sender_id) {
+        return true;
+    }
+    !keywords.allowed_group.is_empty()
+        && contact.is_some_and(|c| c.groups.contains(&keywords.allowed_group))
+}
+
+/// Parse a message as a keyword command. The keyword must be the first word
+/// and is matched case-insensitively.
+pub fn parse_command(content: &str, keywords: &CommandKeywords) -> Option<KeywordCommand> {
+    let mut words = content.split_whitespace();
+    let first = words.next()?.to_lowercase();
+    let matches = |keyword: &str| !keyword.is_empty() && first == keyword.to_lowercase();
+
+    if matches(&keywords.help) {
+        Some(KeywordCommand::Help)
+    } else if matches(&keywords.status) {
+        Some(KeywordCommand::Status)
+    } else if matches(&keywords.tasks) {
+        Some(KeywordCommand::Tasks)
+    } else if matches(&keywords.run) {
+        let mut name_parts = Vec::new();
+        let mut variables = HashMap::new();
+        for word in words {
+            match word.split_once('=') {
+                Some((key, value)) if !key.is_empty() => {
+                    variables.insert(key.to_string(), value.to_string());
+                }
+                _ => name_parts.push(word),
+            }
+        }
+        Some(KeywordCommand::Run {
+            template: name_parts.join(" "),
+            variables,
+        })
+    } else {
+        None
+    }
+}
+
+/// Execute a command for a chat tool and return the reply text.
+pub async fn execute_command(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    chat_tool: &ChatTool,
+    command: KeywordCommand,
+    keywords: &CommandKeywords,
+) -> String {
+    let locale = i18n::current(state);
+    let result = match command {
+        KeywordCommand::Help => Ok(help_text(locale, keywords)),
+        KeywordCommand::Status => {
+            let state = state.clone();
+            let ws = chat_tool.workspace_id.clone();
+            tokio::task::spawn_blocking(move || status_text(&state, locale, ws.as_deref()))
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))
+                .and_then(|r| r)
+        }
+        KeywordCommand::Tasks => {
+            let state = state.clone();
+            let ws = chat_tool.workspace_id.clone();
+            tokio::task::spawn_blocking(move || tasks_text(&state, locale, ws.as_deref()))
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))
+                .and_then(|r| r)
+        }
+        KeywordCommand::Run { template, variables } => {
+            run_template(app, state, locale, chat_tool, &template, variables, keywords).await
+        }
+    };
+
+    result.unwrap_or_else(|e| {
+        log::warn!("[Bridge:{}] Keyword command failed: {}", chat_tool.id, e);
+        i18n::tr(locale, Msg::CommandFailed, &[("error", &e.to_string())])
+    })
+}
+
+fn help_text(locale: Locale, keywords: &CommandKeywords) -> String {
+    let mut lines = vec![i18n::text(locale, Msg::HelpHeader).to_string()];
+    for (keyword, msg) in [
+        (&keywords.status, Msg::HelpStatus),
+        (&keywords.tasks, Msg::HelpTasks),
+        (&keywords.run, Msg::HelpRun),
+    ] {
+        if !keyword.is_empty() {
+            lines.push(i18n::tr(locale, msg, &[("keyword", keyword)]));
+        }
+    }
+    lines.join("\n")
+}
+
+fn status_text(state: &AppState, locale: Locale, workspace_id: Option<&str>) -> AppResult<String> {
+    let runs = task_run_repo::list_task_runs(state, workspace_id)?;
+    let active = runs
+        .iter()
+        .filter(|r| matches!(r.status.as_str(), "pending" | "waiting_for_workspace" | "analyzing" | "running"))
+        .count();
+    let awaiting = runs.iter().filter(|r| r.status == "awaiting_confirmation").count();
+    let scheduled: Vec<_> = runs.iter().filter(|r| r.schedule_type != "none").collect();
+    let paused = scheduled.iter().filter(|r| r.is_paused).count();
+    let next_run = scheduled
+        .iter()
+        .filter(|r| !r.is_paused)
+        .filter_map(|r| r.next_run_at.as_deref().map(|at| (at, r.title.as_str())))
+        .min_by(|a, b| a.0.cmp(b.0));
+
+    let mut lines = vec![
+        i18n::tr(locale, Msg::StatusActive, &[("count", &active.to_string())]),
+        i18n::tr(locale, Msg::StatusAwaiting, &[("count", &awaiting.to_string())]),
+        i18n::tr(
+            locale,
+            Msg::StatusScheduled,
+            &[("count", &scheduled.len().to_string()), ("paused", &paused.to_string())],
+        ),
+    ];
+    if let Some((at, title)) = next_run {
+        lines.push(i18n::tr(locale, Msg::StatusNextRun, &[("at", at), ("title", title)]));
+    }
+    Ok(lines.join("\n"))
+}
+
+fn tasks_text(state: &AppState, locale: Locale, workspace_id: Option<&str>) -> AppResult<String> {
+    let runs = task_run_repo::list_task_runs(state, workspace_id)?;
+    let active: Vec<_> = runs
+        .iter()
+        .filter(|r| {
+            matches!(
+                r.status.as_str(),
+                "pending" | "waiting_for_workspace" | "analyzing" | "running" | "awaiting_confirmation"
+            )
+        })
+        .collect();
+    if active.is_empty() {
+        return Ok(i18n::text(locale, Msg::TasksNone).into());
+    }
+
+    let mut lines: Vec<String> = active
+        .iter()
+        .take(MAX_LISTED_RUNS)
+        .map(|r| format!("• {} [{}] {}", r.title, r.status, r.updated_at))
+        .collect();
+    if active.len() > MAX_LISTED_RUNS {
+        let more = (active.len() - MAX_LISTED_RUNS).to_string();
+        lines.push(i18n::tr(locale, Msg::TasksMore, &[("count", &more)]));
+    }
+    Ok(lines.join("\n"))
+}
+
+async fn run_template(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    locale: Locale,
+    chat_tool: &ChatTool,
+    name: &str,
+    variables: HashMap<String, String>,
+    keywords: &CommandKeywords,
+) -> AppResult<String> {
+    if name.is_empty() {
+        return Ok(i18n::tr(locale, Msg::RunUsage, &[("keyword", &keywords.run)]));
+    }
+
+    let state_clone = state.clone();
+    let ws = chat_tool.workspace_id.clone();
+    let candidates = tokio::task::spawn_blocking(move || template_repo::list_templates(&state_clone, ws.as_deref()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    let Some(template) = candidates.iter().find(|t| t.name.eq_ignore_ascii_case(name)) else {
+        return Ok(i18n::tr(locale, Msg::RunTemplateNotFound, &[("name", name)]));
+    };
+
+    let started = templates::start_template_run(
+        app,
+        state,
+        RunTemplateRequest {
+            template_id: template.id.clone(),
+            variables,
+            title: String::new(),
+            workspace_id: chat_tool.workspace_id.clone(),
+            priority: RunPriority::ChatTool,
+        },
+    )
+    .await?;
+    if !started.duplicate {
+        log::info!(
+            "[Bridge:{}] Started task run {} from template {}",
+            chat_tool.id, started.task_run.id, template.name
+        );
+    }
+    Ok(i18n::tr(locale, Msg::RunStarted, &[("title", &started.task_run.title)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keywords_case_insensitively() {
+        let keywords = CommandKeywords::default();
+        assert_eq!(parse_command("/HELP", &keywords), Some(KeywordCommand::Help));
+        assert_eq!(parse_command("  /status please", &keywords), Some(KeywordCommand::Status));
+        assert_eq!(parse_command("/tasks", &keywords), Some(KeywordCommand::Tasks));
+        assert_eq!(parse_command("what is /status", &keywords), None);
+        assert_eq!(parse_command("/statuses", &keywords), None);
+        assert_eq!(parse_command("", &keywords), None);
+
+        let custom = CommandKeywords { status: "状态".into(), tasks: String::new(), ..CommandKeywords::default() };
+        assert_eq!(parse_command("状态", &custom), Some(KeywordCommand::Status));
+        assert_eq!(parse_command("/status", &custom), None);
+        // An empty keyword never matches
+        assert_eq!(parse_command("/tasks", &custom), None);
+    }
+
+    #[test]
+    fn parses_run_template_and_variables() {
+        let keywords = CommandKeywords::default();
+        assert_eq!(
+            parse_command("/run Weekly report team=ops week=12 =x", &keywords),
+            Some(KeywordCommand::Run {
+                template: "Weekly report =x".into(),
+                variables: HashMap::from([("team".into(), "ops".into()), ("week".into(), "12".into())]),
+            })
+        );
+        assert_eq!(
+            parse_command("/run", &keywords),
+            Some(KeywordCommand::Run { template: String::new(), variables: HashMap::new() })
+        );
+    }
+
+    #[test]
+    fn only_owner_and_allowed_group_may_send_commands() {
+        let chat_tool = ChatTool {
+            id: "t1".into(),
+            name: "WeChat".into(),
+            plugin_type: "wechat".into(),
+            config_json: "{}".into(),
+            linked_agent_id: None,
+            status: "running".into(),
+            status_message: None,
+            auto_reply_mode: "all".into(),
+            workspace_id: None,
+            messages_received: 0,
+            messages_sent: 0,
+            last_active_at: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            env_json: "{}".into(),
+            owner_contact_id: Some("wx_owner".into()),
+            escalation_threshold: None,
+            typing_indicator: false,
+            reply_chunk_chars: None,
+            reply_templates_json: "{}".into(),
+        };
+        let contact = ChatToolContact {
+            id: "c1".into(),
+            chat_tool_id: "t1".into(),
+            external_id: "wx_1".into(),
+            name: "Ann".into(),
+            avatar_url: None,
+            contact_type: "personal".into(),
+            is_blocked: false,
+            max_replies_per_day: None,
+            max_tokens_per_day: None,
+            replies_today: 0,
+            tokens_today: 0,
+            snoozed_until: None,
+            takeover_started_at: None,
+            groups: vec!["ops".into()],
+            run_notifications: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let keywords = CommandKeywords::default();
+        assert!(may_send_commands(&chat_tool, "wx_owner", None, &keywords));
+        assert!(!may_send_commands(&chat_tool, "wx_1", Some(&contact), &keywords));
+        assert!(!may_send_commands(&chat_tool, "wx_2", None, &keywords));
+
+        let keywords = CommandKeywords { allowed_group: "ops".into(), ..CommandKeywords::default() };
+        assert!(may_send_commands(&chat_tool, "wx_1", Some(&contact), &keywords));
+        assert!(!may_send_commands(&chat_tool, "wx_2", None, &keywords));
+    }
+}
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
 
@@ -6,7 +7,9 @@ use tokio::process::{Child, ChildStdin, ChildStdout};
 use tokio::sync::Mutex as AsyncMutex;
 
 use crate::error::{AppError, AppResult};
-use crate::models::chat_tool::BridgeCommand;
+use crate::models::chat_tool::{BridgeCapabilities, BridgeCommand, ChatToolEnvProfile};
+use crate::reaper;
+use crate::state::AppState;
 
 #[derive(Debug)]
 pub struct ChatToolProcess {
@@ -14,51 +17,92 @@ pub struct ChatToolProcess {
     pub plugin_type: String,
     pub child: Child,
     pub stdin: Arc<AsyncMutex<BufWriter<ChildStdin>>>,
+    /// Capabilities negotiated via the `hello` handshake (None until received).
+    pub capabilities: Option<BridgeCapabilities>,
+    /// Directory holding the bridge's login state; also its working directory
+    pub data_dir: PathBuf,
+}
+
+impl ChatToolProcess {
+    /// Negotiated capabilities, falling back to the legacy feature set for
+    /// bridges that never sent a `hello` event.
+    pub fn effective_capabilities(&self) -> BridgeCapabilities {
+        self.capabilities
+            .clone()
+            .unwrap_or_else(BridgeCapabilities::legacy)
+    }
+}
+
+/// Directory holding a bridge's login state. Each chat tool gets its own by
+/// default so two bridges of the same plugin type never share a session.
+pub fn resolve_bridge_data_dir(chat_tool_id: &str, env: &ChatToolEnvProfile) -> PathBuf {
+    match &env.data_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => crate::db::migrations::get_base_dir().join("chat_tools").join(chat_tool_id),
+    }
+}
+
+fn bridge_error(chat_tool_id: &str, message: String) -> AppError {
+    AppError::Bridge { chat_tool_id: chat_tool_id.to_string(), message }
 }
 
 /// Spawn a bridge subprocess and return (process, stdout) for the event loop.
+/// The bridge runs inside its data directory with the chat tool's environment profile.
 pub async fn spawn_bridge(
+    state: &AppState,
     chat_tool_id: &str,
     plugin_type: &str,
     config_json: &str,
+    env: &ChatToolEnvProfile,
 ) -> AppResult<(ChatToolProcess, ChildStdout)> {
     let bridge_path = get_bridge_path(plugin_type)?;
+    let data_dir = resolve_bridge_data_dir(chat_tool_id, env);
+    std::fs::create_dir_all(&data_dir)?;
 
     log::info!(
-        "Spawning bridge process: plugin_type={}, path={}, chat_tool_id={}",
-        plugin_type, bridge_path, chat_tool_id
+        "Spawning bridge process: plugin_type={}, path={}, chat_tool_id={}, data_dir={}",
+        plugin_type, bridge_path, chat_tool_id, data_dir.display()
     );
 
     let enriched_path = crate::acp::discovery::get_enriched_path();
 
     let mut cmd = tokio::process::Command::new("node");
     cmd.arg(&bridge_path)
+        .current_dir(&data_dir)
+        .envs(&env.env)
         .env("CHAT_TOOL_CONFIG", config_json)
         .env("CHAT_TOOL_ID", chat_tool_id)
+        .env("CHAT_TOOL_DATA_DIR", &data_dir)
         .env("PATH", &enriched_path)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
+    if let Some(proxy) = &env.proxy {
+        for key in ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"] {
+            cmd.env(key, proxy);
+        }
+    }
+    if let Some(locale) = &env.locale {
+        cmd.env("LANG", locale).env("LC_ALL", locale);
+    }
 
     let mut child = cmd.spawn().map_err(|e| {
         log::error!("Failed to spawn bridge process: {}", e);
-        AppError::Internal(format!(
-            "Failed to spawn bridge process for '{}': {e}",
-            plugin_type
-        ))
+        bridge_error(chat_tool_id, format!("Failed to spawn bridge process for '{}': {e}", plugin_type))
     })?;
 
     log::info!("Bridge process spawned with PID: {:?}", child.id());
+    reaper::record(state, "bridge", chat_tool_id, &format!("node {}", bridge_path), &child).await;
 
     let stdin = child
         .stdin
         .take()
-        .ok_or_else(|| AppError::Internal("Failed to capture bridge stdin".into()))?;
+        .ok_or_else(|| bridge_error(chat_tool_id, "Failed to capture bridge stdin".into()))?;
 
     let stdout = child
         .stdout
         .take()
-        .ok_or_else(|| AppError::Internal("Failed to capture bridge stdout".into()))?;
+        .ok_or_else(|| bridge_error(chat_tool_id, "Failed to capture bridge stdout".into()))?;
 
     // Capture stderr into a shared buffer for diagnostics
     let stderr_lines = std::sync::Arc::new(AsyncMutex::new(Vec::<String>::new()));
@@ -96,7 +140,7 @@ pub async fn spawn_bridge(
                 exit_status, stderr_output
             );
             log::error!("{}", msg);
-            return Err(AppError::Internal(msg));
+            return Err(bridge_error(chat_tool_id, msg));
         }
         Ok(None) => {
             log::info!("Bridge process is running (PID: {:?})", child.id());
@@ -111,6 +155,8 @@ pub async fn spawn_bridge(
         plugin_type: plugin_type.to_string(),
         child,
         stdin: Arc::new(AsyncMutex::new(BufWriter::new(stdin))),
+        capabilities: None,
+        data_dir,
     };
 
     Ok((process, stdout))
@@ -129,15 +175,15 @@ pub async fn send_bridge_command(
     stdin
         .write_all(json.as_bytes())
         .await
-        .map_err(|e| AppError::Internal(format!("Failed to write to bridge stdin: {e}")))?;
+        .map_err(|e| bridge_error(&process.chat_tool_id, format!("Failed to write to bridge stdin: {e}")))?;
     stdin
         .write_all(b"\n")
         .await
-        .map_err(|e| AppError::Internal(format!("Failed to write newline to bridge stdin: {e}")))?;
+        .map_err(|e| bridge_error(&process.chat_tool_id, format!("Failed to write newline to bridge stdin: {e}")))?;
     stdin
         .flush()
         .await
-        .map_err(|e| AppError::Internal(format!("Failed to flush bridge stdin: {e}")))?;
+        .map_err(|e| bridge_error(&process.chat_tool_id, format!("Failed to flush bridge stdin: {e}")))?;
 
     Ok(())
 }
@@ -179,7 +225,7 @@ pub fn check_process_alive(process: &mut ChatToolProcess) -> bool {
     }
 }
 
-fn get_bridge_path(plugin_type: &str) -> AppResult<String> {
+pub(crate) fn get_bridge_path(plugin_type: &str) -> AppResult<String> {
     let exe_path = std::env::current_exe()
         .map_err(|e| AppError::Internal(format!("Failed to get exe path: {e}")))?;
 
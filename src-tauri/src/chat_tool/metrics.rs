@@ -0,0 +1,221 @@
+//! Responsiveness metrics of a chat tool.
+//!
+//! Every batch of inbound messages the bridge hands to the Control Hub is
+//! recorded with the number of messages waiting when it was picked up, how
+//! long the Control Hub took and, once replies are sent, how long each
+//! message waited since it was received. Metrics for a range sum the batches
+//! and incoming messages into totals and a time series, so operators can see
+//! whether replies keep up with inbound volume.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::db::chat_tool_repo;
+use crate::models::chat_tool::{ChatToolMessage, ChatToolMetricSample, ChatToolMetrics, ChatToolMetricsBucket, MetricsRange};
+use crate::state::AppState;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(text, TIME_FORMAT).ok().map(|t| t.and_utc())
+}
+
+/// Queue the sample of a handled batch. `replied` batches get the time from
+/// each message's receipt until now.
+pub fn record(
+    state: &AppState,
+    chat_tool_id: &str,
+    outcome: &str,
+    messages: &[ChatToolMessage],
+    queue_depth: usize,
+    hub_latency_ms: Option<i64>,
+    replied: bool,
+) {
+    let now = Utc::now();
+    let waits: Vec<i64> = if replied {
+        messages
+            .iter()
+            .filter_map(|m| parse_time(&m.created_at))
+            .map(|received| (now - received).num_milliseconds().max(0))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let sample = ChatToolMetricSample {
+        batch_size: messages.len() as i64,
+        queue_depth: queue_depth as i64,
+        hub_latency_ms,
+        response_ms_total: (!waits.is_empty()).then(|| waits.iter().sum()),
+        response_ms_max: waits.iter().copied().max(),
+        outcome: outcome.to_string(),
+        recorded_at: now.format(TIME_FORMAT).to_string(),
+    };
+    chat_tool_repo::queue_metric_sample(state, chat_tool_id.to_string(), sample);
+}
+
+/// Metrics of the range ending now. Blocking.
+pub fn load(state: &AppState, chat_tool_id: &str, range: MetricsRange) -> crate::error::AppResult<ChatToolMetrics> {
+    let now = Utc::now();
+    let since = (now - range.span()).format(TIME_FORMAT).to_string();
+    let samples = chat_tool_repo::list_metric_samples(state, chat_tool_id, &since)?;
+    let received = chat_tool_repo::list_incoming_times(state, chat_tool_id, &since)?;
+    let backlog = chat_tool_repo::count_unprocessed_messages(state, chat_tool_id)?;
+    Ok(summarize(chat_tool_id, range, now, &samples, &received, backlog))
+}
+
+/// The 95th percentile of `values`, which must be sorted.
+fn p95(values: &[i64]) -> Option<i64> {
+    let index = (values.len() as f64 * 0.95).ceil() as usize;
+    values.get(index.max(1) - 1).copied()
+}
+
+fn mean(total: i64, count: i64) -> Option<f64> {
+    (count > 0).then(|| total as f64 / count as f64)
+}
+
+#[derive(Default)]
+struct Totals {
+    received: i64,
+    replied: i64,
+    response_ms: i64,
+    hub_ms: i64,
+    hub_batches: i64,
+    max_queue_depth: i64,
+}
+
+impl Totals {
+    fn add(&mut self, sample: &ChatToolMetricSample) {
+        if let Some(total) = sample.response_ms_total {
+            self.replied += sample.batch_size;
+            self.response_ms += total;
+        }
+        if let Some(hub_ms) = sample.hub_latency_ms {
+            self.hub_ms += hub_ms;
+            self.hub_batches += 1;
+        }
+        self.max_queue_depth = self.max_queue_depth.max(sample.queue_depth);
+    }
+}
+
+/// Sum samples and receipt times of `range` ending at `now`.
+pub fn summarize(
+    chat_tool_id: &str,
+    range: MetricsRange,
+    now: DateTime<Utc>,
+    samples: &[ChatToolMetricSample],
+    received: &[String],
+    backlog: i64,
+) -> ChatToolMetrics {
+    let since = now - range.span();
+    let width = range.bucket();
+    let count = (range.span().num_seconds() / width.num_seconds()) as usize;
+    let bucket_of = |time: DateTime<Utc>| -> Option<usize> {
+        let index = (time - since).num_seconds().div_euclid(width.num_seconds());
+        usize::try_from(index).ok().map(|i| i.min(count - 1))
+    };
+
+    let mut totals = Totals::default();
+    let mut buckets: Vec<Totals> = (0..count).map(|_| Totals::default()).collect();
+    for i in received.iter().filter_map(|t| parse_time(t)).filter_map(bucket_of) {
+        totals.received += 1;
+        buckets[i].received += 1;
+    }
+    for sample in samples {
+        totals.add(sample);
+        if let Some(i) = parse_time(&sample.recorded_at).and_then(bucket_of) {
+            buckets[i].add(sample);
+        }
+    }
+
+    let mut response_max: Vec<i64> = samples.iter().filter_map(|s| s.response_ms_max).collect();
+    response_max.sort_unstable();
+    let mut hub_latency: Vec<i64> = samples.iter().filter_map(|s| s.hub_latency_ms).collect();
+    hub_latency.sort_unstable();
+
+    ChatToolMetrics {
+        chat_tool_id: chat_tool_id.to_string(),
+        range,
+        since: since.format(TIME_FORMAT).to_string(),
+        messages_received: totals.received,
+        messages_replied: totals.replied,
+        batches: samples.len() as i64,
+        escalated_batches: samples.iter().filter(|s| s.outcome == "escalated").count() as i64,
+        failed_batches: samples.iter().filter(|s| matches!(s.outcome.as_str(), "failed" | "no_hub")).count() as i64,
+        avg_response_ms: mean(totals.response_ms, totals.replied),
+        p95_response_ms: p95(&response_max),
+        max_response_ms: response_max.last().copied(),
+        avg_hub_latency_ms: mean(totals.hub_ms, totals.hub_batches),
+        p95_hub_latency_ms: p95(&hub_latency),
+        max_queue_depth: totals.max_queue_depth,
+        backlog,
+        buckets: buckets
+            .iter()
+            .enumerate()
+            .map(|(i, b)| ChatToolMetricsBucket {
+                start: (since + width * i as i32).format(TIME_FORMAT).to_string(),
+                received: b.received,
+                replied: b.replied,
+                avg_response_ms: mean(b.response_ms, b.replied),
+                avg_hub_latency_ms: mean(b.hub_ms, b.hub_batches),
+                max_queue_depth: b.max_queue_depth,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(recorded_at: &str, batch_size: i64, queue_depth: i64, hub: Option<i64>, response: Option<(i64, i64)>, outcome: &str) -> ChatToolMetricSample {
+        ChatToolMetricSample {
+            batch_size,
+            queue_depth,
+            hub_latency_ms: hub,
+            response_ms_total: response.map(|(total, _)| total),
+            response_ms_max: response.map(|(_, max)| max),
+            outcome: outcome.into(),
+            recorded_at: recorded_at.into(),
+        }
+    }
+
+    #[test]
+    fn sums_batches_into_totals_and_buckets() {
+        let now = parse_time("2026-03-01 12:00:00").unwrap();
+        let samples = vec![
+            sample("2026-03-01 11:02:00", 2, 3, Some(4000), Some((10_000, 6000)), "replied"),
+            sample("2026-03-01 11:03:00", 1, 1, Some(2000), None, "escalated"),
+            sample("2026-03-01 11:58:00", 3, 5, None, None, "failed"),
+        ];
+        let received: Vec<String> =
+            ["2026-03-01 11:01:00", "2026-03-01 11:01:30", "2026-03-01 11:02:10", "2026-03-01 11:57:00", "2026-03-01 10:00:00"]
+                .iter()
+                .map(|t| t.to_string())
+                .collect();
+
+        let metrics = summarize("ct", MetricsRange::Hour, now, &samples, &received, 3);
+        assert_eq!(metrics.since, "2026-03-01 11:00:00");
+        assert_eq!(metrics.messages_received, 4);
+        assert_eq!(metrics.messages_replied, 2);
+        assert_eq!((metrics.batches, metrics.escalated_batches, metrics.failed_batches), (3, 1, 1));
+        assert_eq!(metrics.avg_response_ms, Some(5000.0));
+        assert_eq!(metrics.max_response_ms, Some(6000));
+        assert_eq!(metrics.avg_hub_latency_ms, Some(3000.0));
+        assert_eq!(metrics.p95_hub_latency_ms, Some(4000));
+        assert_eq!((metrics.max_queue_depth, metrics.backlog), (5, 3));
+
+        assert_eq!(metrics.buckets.len(), 12);
+        assert_eq!(metrics.buckets[0].start, "2026-03-01 11:00:00");
+        assert_eq!((metrics.buckets[0].received, metrics.buckets[0].replied), (3, 2));
+        assert_eq!(metrics.buckets[0].max_queue_depth, 3);
+        assert_eq!((metrics.buckets[11].received, metrics.buckets[11].max_queue_depth), (1, 5));
+        assert_eq!(metrics.buckets[11].avg_response_ms, None);
+    }
+
+    #[test]
+    fn p95_picks_the_value_below_the_top_five_percent() {
+        let values: Vec<i64> = (1..=100).collect();
+        assert_eq!(p95(&values), Some(95));
+        assert_eq!(p95(&[7]), Some(7));
+        assert_eq!(p95(&[]), None);
+    }
+}
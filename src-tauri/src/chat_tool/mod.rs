@@ -1,2 +1,14 @@
 pub mod bridge;
+pub mod chunking;
+pub mod contact_list;
+pub mod delivery;
+pub mod escalation;
+pub mod keywords;
 pub mod manager;
+pub mod metrics;
+pub mod quota;
+pub mod reply_templates;
+pub mod run_notifications;
+pub mod spam_filter;
+pub mod takeover;
+pub mod transcript;
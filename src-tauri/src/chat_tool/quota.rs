@@ -0,0 +1,77 @@
+//! Daily auto-reply quotas per contact.
+//!
+//! A contact can be limited to a number of Control Hub replies and a number
+//! of tokens per day. Senders over quota are answered with the chat tool's
+//! over-quota template instead of being forwarded. The Control Hub does not
+//! report token usage for chat replies, so tokens are estimated from the
+//! sender's messages and the reply.
+
+use crate::models::chat_tool::ChatToolContact;
+
+/// Rough token count: about four characters per token for ASCII text and
+/// one token per character otherwise (CJK text is roughly one per character).
+pub fn estimate_tokens(text: &str) -> i64 {
+    let (ascii, other) = text
+        .chars()
+        .fold((0i64, 0i64), |(a, o), c| if c.is_ascii() { (a + 1, o) } else { (a, o + 1) });
+    (ascii + 3) / 4 + other
+}
+
+/// Why a contact may not get another auto-reply today, if it may not.
+pub fn exceeded(contact: &ChatToolContact) -> Option<String> {
+    if let Some(max) = contact.max_replies_per_day.filter(|max| contact.replies_today >= *max) {
+        return Some(format!("Daily reply quota of {} reached", max));
+    }
+    if let Some(max) = contact.max_tokens_per_day.filter(|max| contact.tokens_today >= *max) {
+        return Some(format!("Daily token quota of {} reached", max));
+    }
+    None
+}
+
+pub fn validate(max_replies_per_day: Option<i64>, max_tokens_per_day: Option<i64>) -> Result<(), String> {
+    if max_replies_per_day.is_some_and(|max| max < 0) || max_tokens_per_day.is_some_and(|max| max < 0) {
+        return Err("Quotas cannot be negative".into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotas_and_token_estimates() {
+        let mut contact = ChatToolContact {
+            id: "c1".into(),
+            chat_tool_id: "t1".into(),
+            external_id: "wx_1".into(),
+            name: "Ann".into(),
+            avatar_url: None,
+            contact_type: "personal".into(),
+            is_blocked: false,
+            max_replies_per_day: None,
+            max_tokens_per_day: Some(1000),
+            replies_today: 12,
+            tokens_today: 999,
+            snoozed_until: None,
+            takeover_started_at: None,
+            groups: Vec::new(),
+            run_notifications: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        assert_eq!(exceeded(&contact), None);
+        contact.max_replies_per_day = Some(12);
+        assert_eq!(exceeded(&contact).as_deref(), Some("Daily reply quota of 12 reached"));
+        contact.max_replies_per_day = Some(0);
+        contact.replies_today = 0;
+        assert!(exceeded(&contact).is_some());
+
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("hello world"), 3);
+        assert_eq!(estimate_tokens("你好 ok"), 3);
+
+        assert!(validate(Some(10), None).is_ok());
+        assert!(validate(None, Some(-1)).is_err());
+    }
+}
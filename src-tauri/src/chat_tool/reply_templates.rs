@@ -0,0 +1,177 @@
+//! Canned replies a chat tool sends without asking the Control Hub: a
+//! greeting for first-time senders, a notice outside office hours, the busy
+//! reply while an earlier batch is being answered, a fallback when no
+//! Control Hub is available, and a reply to senders over their daily quota. Templates use `{{name}}` placeholders filled with
+//! `sender_name`, `chat_tool_name` and `office_hours`; an empty template
+//! sends nothing. Without a busy template the busy reply comes from the
+//! locale's catalog.
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{self, Locale, Msg};
+use crate::prompts;
+
+pub const VARIABLES: [&str; 3] = ["sender_name", "chat_tool_name", "office_hours"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplyTemplates {
+    /// Sent before answering a sender's first message
+    pub greeting: String,
+    /// Sent while an earlier batch is still being answered; None uses the
+    /// localized default
+    pub busy: Option<String>,
+    /// Sent when no Control Hub can answer
+    pub hub_unavailable: String,
+    /// Sent instead of forwarding when a sender is over its daily quota
+    pub over_quota: String,
+    /// When set, senders writing outside these hours get its notice
+    pub office_hours: Option<OfficeHours>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OfficeHours {
+    /// Local time, "HH:MM"; an end before the start spans midnight
+    pub start: String,
+    pub end: String,
+    /// ISO weekdays, 1 = Monday … 7 = Sunday, on which the hours apply
+    #[serde(default = "default_weekdays")]
+    pub weekdays: Vec<u32>,
+    pub notice: String,
+}
+
+fn default_weekdays() -> Vec<u32> {
+    vec![1, 2, 3, 4, 5]
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|_| format!("Invalid time \"{}\", expected HH:MM", value))
+}
+
+impl OfficeHours {
+    pub fn validate(&self) -> Result<(), String> {
+        parse_time(&self.start)?;
+        parse_time(&self.end)?;
+        if let Some(day) = self.weekdays.iter().find(|d| !(1..=7).contains(*d)) {
+            return Err(format!("Invalid weekday {}, expected 1 (Monday) to 7 (Sunday)", day));
+        }
+        Ok(())
+    }
+
+    /// Whether `now` (local time) falls within office hours. Hours spanning
+    /// midnight belong to the day they start on.
+    pub fn is_open(&self, now: NaiveDateTime) -> bool {
+        let (Ok(start), Ok(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return true;
+        };
+        let time = now.time();
+        let weekday = now.weekday();
+        let open_on = |day: chrono::Weekday| self.weekdays.contains(&day.number_from_monday());
+        if start <= end {
+            open_on(weekday) && time >= start && time < end
+        } else {
+            (open_on(weekday) && time >= start) || (open_on(weekday.pred()) && time < end)
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("{}–{}", self.start.trim(), self.end.trim())
+    }
+}
+
+impl ReplyTemplates {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(hours) = &self.office_hours {
+            hours.validate()?;
+        }
+        let vars: HashMap<String, String> = VARIABLES.iter().map(|v| (v.to_string(), String::new())).collect();
+        let notice = self.office_hours.as_ref().map_or("", |h| h.notice.as_str());
+        let busy = self.busy.as_deref().unwrap_or("");
+        for template in [self.greeting.as_str(), busy, self.hub_unavailable.as_str(), self.over_quota.as_str(), notice] {
+            prompts::render(template, &vars).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Fill in a template; None when it is empty.
+    pub fn render(&self, template: &str, sender_name: &str, chat_tool_name: &str) -> Option<String> {
+        if template.trim().is_empty() {
+            return None;
+        }
+        let vars: HashMap<String, String> = [
+            ("sender_name", sender_name.to_string()),
+            ("chat_tool_name", chat_tool_name.to_string()),
+            ("office_hours", self.office_hours.as_ref().map(OfficeHours::describe).unwrap_or_default()),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        Some(prompts::render(template, &vars).unwrap_or_else(|_| template.to_string()))
+    }
+
+    /// The reply to a sender writing while an earlier batch is being answered.
+    pub fn busy_reply(&self, locale: Locale, sender_name: &str, chat_tool_name: &str) -> Option<String> {
+        match &self.busy {
+            Some(template) => self.render(template, sender_name, chat_tool_name),
+            None => Some(i18n::text(locale, Msg::BusyReply).to_string()),
+        }
+    }
+
+    /// The office-hours notice to send at `now`, if outside office hours.
+    pub fn closed_notice(&self, now: NaiveDateTime, sender_name: &str, chat_tool_name: &str) -> Option<String> {
+        let hours = self.office_hours.as_ref()?;
+        if hours.is_open(now) {
+            return None;
+        }
+        self.render(&hours.notice, sender_name, chat_tool_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn office_hours_and_rendering() {
+        let templates = ReplyTemplates {
+            greeting: "Hi {{sender_name}}, this is {{chat_tool_name}}.".into(),
+            office_hours: Some(OfficeHours {
+                start: "22:00".into(),
+                end: "06:00".into(),
+                weekdays: vec![5],
+                notice: "We are open {{office_hours}}.".into(),
+            }),
+            ..ReplyTemplates::default()
+        };
+        assert!(templates.validate().is_ok());
+
+        // 2026-10-16 is a Friday
+        let hours = templates.office_hours.as_ref().unwrap();
+        assert!(hours.is_open(at("2026-10-16", "23:00")));
+        assert!(hours.is_open(at("2026-10-17", "05:59")));
+        assert!(!hours.is_open(at("2026-10-17", "23:00")));
+        assert!(!hours.is_open(at("2026-10-16", "12:00")));
+
+        assert_eq!(
+            templates.render(&templates.greeting, "Ann", "Support").as_deref(),
+            Some("Hi Ann, this is Support.")
+        );
+        assert_eq!(
+            templates.closed_notice(at("2026-10-16", "12:00"), "Ann", "Support").as_deref(),
+            Some("We are open 22:00–06:00.")
+        );
+        assert_eq!(templates.render(&templates.hub_unavailable, "Ann", "Support"), None);
+
+        assert_eq!(templates.busy_reply(Locale::Zh, "Ann", "Support").as_deref(), Some("正在处理前面的消息，请稍候"));
+
+        let bad = ReplyTemplates { busy: Some("Wait, {{name}}".into()), ..ReplyTemplates::default() };
+        assert!(bad.validate().is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Direct messages about finished task runs.
+//!
+//! A contact subscribed with a `RunNotificationSubscription`, typically the
+//! user's own account on a chat tool, is sent the run's title, the start of
+//! its summary or its error, and a deep link when an orchestrated run
+//! completes or fails. Cancelled runs are not reported, and neither are the
+//! runs the bridge starts to answer messages, which reply in the chat anyway.
+
+use tauri::AppHandle;
+
+use crate::db::{chat_tool_repo, task_run_repo};
+use crate::error::{AppError, AppResult};
+use crate::i18n::{self, Locale, Msg};
+use crate::models::chat_tool::RunNotificationSubscription;
+use crate::models::notification::{Notification, NotificationTarget};
+use crate::notifications;
+use crate::state::AppState;
+use crate::telemetry;
+
+/// Summaries are cut to this many characters.
+const SUMMARY_EXCERPT_CHARS: usize = 600;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Completed { summary: Option<String> },
+    Failed { error: Option<String> },
+}
+
+/// Whether the subscription covers a run of `workspace_id` with `outcome`.
+pub fn wants(subscription: &RunNotificationSubscription, workspace_id: Option<&str>, outcome: &Outcome) -> bool {
+    let wanted = match outcome {
+        Outcome::Completed { .. } => subscription.on_completed,
+        Outcome::Failed { .. } => subscription.on_failed,
+    };
+    wanted && subscription.workspace_id.as_deref().map_or(true, |ws| workspace_id == Some(ws))
+}
+
+fn excerpt(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(SUMMARY_EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+pub fn message(locale: Locale, task_run_id: &str, title: &str, outcome: &Outcome) -> Notification {
+    let (kind, title_msg, body) = match outcome {
+        Outcome::Completed { summary } => (
+            "run_completed",
+            Msg::RunCompletedTitle,
+            summary.as_deref().map(excerpt).unwrap_or_default(),
+        ),
+        Outcome::Failed { error } => (
+            "run_failed",
+            Msg::RunFailedTitle,
+            error
+                .as_deref()
+                .map(|e| i18n::tr(locale, Msg::RunFailedBody, &[("error", e)]))
+                .unwrap_or_default(),
+        ),
+    };
+    Notification {
+        kind: kind.into(),
+        title: i18n::tr(locale, title_msg, &[("title", title)]),
+        body,
+        task_run_id: Some(task_run_id.to_string()),
+        link: Some(notifications::task_run_link(task_run_id)),
+    }
+}
+
+/// Tell subscribed contacts that an orchestrated run ended with `result`.
+/// Sends in the background; failures are logged.
+pub fn notify_finished(app: &AppHandle, state: &AppState, task_run_id: &str, result: &AppResult<()>) {
+    if matches!(result, Err(AppError::AgentCancelled { .. })) {
+        return;
+    }
+    let error = result.as_ref().err().map(|e| e.to_string());
+    let app = app.clone();
+    let state = state.clone();
+    let task_run_id = task_run_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let state_clone = state.clone();
+        let id = task_run_id.clone();
+        let loaded = telemetry::spawn_blocking(move || {
+            let contacts = chat_tool_repo::list_run_notification_contacts(&state_clone)?;
+            if contacts.is_empty() {
+                return Ok(None);
+            }
+            Ok::<_, AppError>(Some((task_run_repo::get_task_run(&state_clone, &id)?, contacts)))
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+        .and_then(|loaded| loaded);
+        let (run, contacts) = match loaded {
+            Ok(Some(loaded)) => loaded,
+            Ok(None) => return,
+            Err(e) => {
+                log::warn!("[RunNotifications] Failed to load subscribers of {}: {}", task_run_id, e);
+                return;
+            }
+        };
+
+        let outcome = match (error, run.status.as_str()) {
+            (Some(error), _) => Outcome::Failed { error: Some(error) },
+            (None, "completed") => Outcome::Completed { summary: run.result_summary.clone() },
+            (None, "failed") => Outcome::Failed { error: None },
+            _ => return,
+        };
+        let targets: Vec<NotificationTarget> = contacts
+            .into_iter()
+            .filter(|c| !c.is_blocked)
+            .filter(|c| {
+                c.run_notifications
+                    .as_ref()
+                    .is_some_and(|s| wants(s, run.workspace_id.as_deref(), &outcome))
+            })
+            .map(|c| NotificationTarget::ChatTool { chat_tool_id: c.chat_tool_id, to_id: c.external_id })
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+        let notification = message(i18n::current(&state), &run.id, &run.title, &outcome);
+        notifications::dispatch(&app, &state, &targets, &notification).await;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscriptions_filter_by_outcome_and_workspace() {
+        let completed = Outcome::Completed { summary: None };
+        let failed = Outcome::Failed { error: None };
+        let all = RunNotificationSubscription::default();
+        assert!(wants(&all, None, &completed));
+        assert!(wants(&all, Some("ws1"), &failed));
+
+        let failures_in_ws1 = RunNotificationSubscription {
+            on_completed: false,
+            on_failed: true,
+            workspace_id: Some("ws1".into()),
+        };
+        assert!(wants(&failures_in_ws1, Some("ws1"), &failed));
+        assert!(!wants(&failures_in_ws1, Some("ws1"), &completed));
+        assert!(!wants(&failures_in_ws1, Some("ws2"), &failed));
+        assert!(!wants(&failures_in_ws1, None, &failed));
+    }
+
+    #[test]
+    fn messages_carry_an_excerpt_and_a_deep_link() {
+        let summary = format!("  {}  ", "x".repeat(SUMMARY_EXCERPT_CHARS + 50));
+        let done = message(Locale::En, "tr1", "Refactor", &Outcome::Completed { summary: Some(summary) });
+        assert_eq!(done.kind, "run_completed");
+        assert_eq!(done.title, "Task completed: Refactor");
+        assert_eq!(done.body.chars().count(), SUMMARY_EXCERPT_CHARS + 1);
+        assert!(done.body.ends_with('…'));
+        assert_eq!(done.link.as_deref(), Some("agent-hub://task-runs/tr1"));
+
+        let failed = message(Locale::Zh, "tr2", "部署", &Outcome::Failed { error: Some("timeout".into()) });
+        assert_eq!(failed.kind, "run_failed");
+        assert_eq!(failed.title, "任务失败：部署");
+        assert_eq!(failed.body, "错误：timeout");
+    }
+}
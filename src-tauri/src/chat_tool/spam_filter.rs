@@ -0,0 +1,135 @@
+//! Spam and abuse filtering for incoming chat tool messages.
+//!
+//! Each incoming message is checked when it arrives, before keyword commands
+//! and auto-reply: per-sender rate limits, a keyword blocklist, and link and
+//! attachment heuristics. When the LLM classifier is on, messages that pass
+//! are also classified by the Control Hub just before their batch is
+//! forwarded. Filtered messages keep a `filter_reason` in the database and
+//! are never forwarded to the Control Hub.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpamFilterConfig {
+    pub enabled: bool,
+    /// Messages a sender may send per minute; 0 disables the limit
+    pub max_messages_per_minute: u32,
+    /// Case-insensitive substrings that filter a message
+    pub blocked_keywords: Vec<String>,
+    /// Links a message may contain; more are treated as spam
+    pub max_links: u32,
+    /// Filter non-text messages from senders who have not written before
+    pub filter_first_contact_attachments: bool,
+    /// Also ask the Control Hub to classify messages that pass the checks above
+    pub llm_classifier: bool,
+}
+
+impl Default for SpamFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_messages_per_minute: 10,
+            blocked_keywords: Vec::new(),
+            max_links: 3,
+            filter_first_contact_attachments: false,
+            llm_classifier: false,
+        }
+    }
+}
+
+/// What is known about the sender when a message arrives.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SenderHistory {
+    /// Incoming messages in the last minute, this one included
+    pub last_minute: u32,
+    /// All incoming messages, this one included
+    pub total: u32,
+}
+
+fn count_links(text: &str) -> u32 {
+    let lower = text.to_lowercase();
+    ["http://", "https://", "www."]
+        .iter()
+        .map(|marker| lower.matches(marker).count() as u32)
+        .sum::<u32>()
+        // "https://www.x" is one link, not two
+        .saturating_sub(lower.matches("://www.").count() as u32)
+}
+
+/// Why the message should be filtered, or None to let it through.
+pub fn check(config: &SpamFilterConfig, content: &str, content_type: &str, sender: SenderHistory) -> Option<String> {
+    if !config.enabled {
+        return None;
+    }
+    if config.max_messages_per_minute > 0 && sender.last_minute > config.max_messages_per_minute {
+        return Some(format!(
+            "Rate limit: {} messages in the last minute (max {})",
+            sender.last_minute, config.max_messages_per_minute
+        ));
+    }
+    let lower = content.to_lowercase();
+    if let Some(keyword) = config
+        .blocked_keywords
+        .iter()
+        .map(|k| k.trim())
+        .find(|k| !k.is_empty() && lower.contains(&k.to_lowercase()))
+    {
+        return Some(format!("Blocked keyword \"{}\"", keyword));
+    }
+    let links = count_links(content);
+    if links > config.max_links {
+        return Some(format!("{} links (max {})", links, config.max_links));
+    }
+    if config.filter_first_contact_attachments && content_type != "text" && sender.total <= 1 {
+        return Some(format!("Attachment ({}) from a first-time sender", content_type));
+    }
+    None
+}
+
+/// Prompt asking the Control Hub to classify one message.
+pub fn classifier_prompt(sender_name: &str, content: &str) -> String {
+    format!(
+        "Classify the following incoming chat message as spam/abuse or legitimate. \
+         Do not answer the message. Reply with exactly one word: SPAM or OK.\n\n\
+         [Message from {}]: {}",
+        sender_name, content
+    )
+}
+
+/// Whether a classifier reply marks the message as spam. Anything but a
+/// clear SPAM verdict lets the message through.
+pub fn is_spam_verdict(reply: &str) -> bool {
+    reply
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .find(|w| !w.is_empty())
+        .is_some_and(|w| w.eq_ignore_ascii_case("spam"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_rate_keywords_links_and_attachments() {
+        let config = SpamFilterConfig {
+            blocked_keywords: vec!["Crypto Giveaway".into(), " ".into()],
+            filter_first_contact_attachments: true,
+            ..SpamFilterConfig::default()
+        };
+        let known = SenderHistory { last_minute: 1, total: 5 };
+
+        assert_eq!(check(&config, "hello", "text", known), None);
+        assert!(check(&config, "hello", "text", SenderHistory { last_minute: 11, total: 20 }).is_some());
+        assert!(check(&config, "Join our crypto giveaway!", "text", known).is_some());
+        assert_eq!(check(&config, "see https://www.a.com and http://b.com", "text", known), None);
+        assert!(check(&config, "http://a http://b http://c www.d", "text", known).is_some());
+        assert!(check(&config, "", "image", SenderHistory { last_minute: 1, total: 1 }).is_some());
+        assert_eq!(check(&config, "", "image", known), None);
+
+        assert!(is_spam_verdict("SPAM"));
+        assert!(is_spam_verdict("**spam** - it advertises"));
+        assert!(!is_spam_verdict("OK"));
+        assert!(!is_spam_verdict("This is not spam"));
+    }
+}
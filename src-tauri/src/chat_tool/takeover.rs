@@ -0,0 +1,140 @@
+//! Human takeover of a chat tool conversation.
+//!
+//! While an operator has taken over a contact, messages from it are stored
+//! and shown in the UI but get no auto-reply; the operator answers with
+//! `send_chat_tool_message`. When the takeover ends the Control Hub is sent
+//! the transcript of that stretch of the conversation so it can carry on
+//! from where the operator left off.
+
+use tauri::Emitter;
+
+use crate::db::chat_tool_repo;
+use crate::error::{AppError, AppResult};
+use crate::models::chat_tool::{ChatTool, ChatToolContact, ChatToolMessage};
+use crate::state::AppState;
+use crate::telemetry;
+
+use super::bridge;
+
+/// Most recent messages of a takeover handed to the Control Hub.
+const MAX_HANDBACK_MESSAGES: usize = 100;
+
+/// Message telling the Control Hub what happened while a human had taken over.
+pub fn handback_prompt(contact_name: &str, started_at: &str, messages: &[ChatToolMessage]) -> String {
+    let incoming = messages.iter().filter(|m| m.direction == "incoming").count();
+    let mut prompt = format!(
+        "[Human takeover ended] Since {} UTC a human operator answered {} directly instead of you. \
+         {} message(s) were exchanged: {} from {} and {} from the operator. \
+         Use the transcript below to continue the conversation from where the operator left off. \
+         Do not reply to {} now; answer only with a short acknowledgement.\n",
+        started_at,
+        contact_name,
+        messages.len(),
+        incoming,
+        contact_name,
+        messages.len() - incoming,
+        contact_name,
+    );
+    let skipped = messages.len().saturating_sub(MAX_HANDBACK_MESSAGES);
+    if skipped > 0 {
+        prompt.push_str(&format!("\n[{} earlier message(s) omitted]\n", skipped));
+    }
+    for message in &messages[skipped..] {
+        let from = if message.direction == "incoming" { contact_name } else { "Operator" };
+        prompt.push_str(&format!("\n[{}] {}: {}", message.created_at, from, message.content));
+    }
+    prompt
+}
+
+/// Send the Control Hub the transcript of a takeover that just ended.
+/// Returns false when the chat tool has no Control Hub or nothing was said.
+pub async fn hand_back(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    chat_tool: &ChatTool,
+    contact: &ChatToolContact,
+    started_at: &str,
+) -> AppResult<bool> {
+    let state_clone = state.clone();
+    let (id, external_id, since) = (chat_tool.id.clone(), contact.external_id.clone(), started_at.to_string());
+    let messages = telemetry::spawn_blocking(move || {
+        chat_tool_repo::list_contact_messages(&state_clone, &id, &external_id, Some(&since), None)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    if messages.is_empty() {
+        return Ok(false);
+    }
+
+    let prompt = handback_prompt(&contact.name, started_at, &messages);
+    let reply = bridge::forward_to_control_hub(
+        app,
+        state,
+        &chat_tool.id,
+        &chat_tool.name,
+        chat_tool.workspace_id.as_deref(),
+        &prompt,
+    )
+    .await?;
+    Ok(reply.is_some())
+}
+
+/// Hand the conversation back in the background and tell the UI how it went.
+pub fn spawn_hand_back(app: tauri::AppHandle, state: AppState, chat_tool: ChatTool, contact: ChatToolContact, started_at: String) {
+    tokio::spawn(async move {
+        let handed_back = match hand_back(&app, &state, &chat_tool, &contact, &started_at).await {
+            Ok(handed_back) => handed_back,
+            Err(e) => {
+                log::warn!("[Bridge:{}] Failed to hand {} back to the Control Hub: {}", chat_tool.id, contact.name, e);
+                false
+            }
+        };
+        let _ = app.emit(
+            "chat_tool:takeover_ended",
+            serde_json::json!({
+                "chatToolId": chat_tool.id,
+                "contactId": contact.id,
+                "handedBack": handed_back
+            }),
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(direction: &str, content: &str) -> ChatToolMessage {
+        ChatToolMessage {
+            id: content.into(),
+            chat_tool_id: "t1".into(),
+            direction: direction.into(),
+            external_sender_id: Some("wx_1".into()),
+            external_sender_name: None,
+            content: content.into(),
+            content_type: "text".into(),
+            agent_response: None,
+            is_processed: true,
+            error_message: None,
+            created_at: "2026-01-01 10:00:00".into(),
+            filter_reason: None,
+            delivery_status: None,
+            delivery_error: None,
+            delivery_attempts: 0,
+        }
+    }
+
+    #[test]
+    fn handback_prompt_attributes_and_limits_the_transcript() {
+        let messages = vec![message("incoming", "Is my order late?"), message("outgoing", "It ships today")];
+        let prompt = handback_prompt("Ann", "2026-01-01 09:59:00", &messages);
+        assert!(prompt.contains("2 message(s) were exchanged: 1 from Ann and 1 from the operator"));
+        assert!(prompt.ends_with("Ann: Is my order late?\n[2026-01-01 10:00:00] Operator: It ships today"));
+
+        let many: Vec<_> = (0..MAX_HANDBACK_MESSAGES + 5).map(|i| message("incoming", &format!("m{}", i))).collect();
+        let prompt = handback_prompt("Ann", "2026-01-01 09:59:00", &many);
+        assert!(prompt.contains("[5 earlier message(s) omitted]"));
+        assert!(!prompt.contains("Ann: m4\n"));
+        assert!(prompt.contains("Ann: m5\n"));
+    }
+}
@@ -0,0 +1,109 @@
+//! Transcripts of a chat tool's conversation with one contact, for record
+//! keeping and customer-service review. Incoming messages are attributed to
+//! the contact and outgoing ones (auto-replies, notifications, manual sends)
+//! to the chat tool.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppResult;
+use crate::models::chat_tool::{ChatTool, ChatToolContact, ChatToolMessage};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    Markdown,
+    Csv,
+    Json,
+}
+
+impl TranscriptFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    at: &'a str,
+    direction: &'a str,
+    from: &'a str,
+    content_type: &'a str,
+    text: &'a str,
+}
+
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn render(
+    format: TranscriptFormat,
+    chat_tool: &ChatTool,
+    contact: &ChatToolContact,
+    messages: &[ChatToolMessage],
+) -> AppResult<String> {
+    let lines: Vec<Line> = messages
+        .iter()
+        .map(|m| Line {
+            at: &m.created_at,
+            direction: &m.direction,
+            from: if m.direction == "incoming" { &contact.name } else { &chat_tool.name },
+            content_type: &m.content_type,
+            text: &m.content,
+        })
+        .collect();
+
+    Ok(match format {
+        TranscriptFormat::Markdown => {
+            let mut out = format!(
+                "# Conversation with {}\n\n- Chat tool: {}\n- Contact id: {}\n- Messages: {}\n",
+                contact.name,
+                chat_tool.name,
+                contact.external_id,
+                lines.len()
+            );
+            for line in &lines {
+                out.push_str(&format!("\n**{}** · {}\n\n", line.from, line.at));
+                for text_line in line.text.lines() {
+                    out.push_str(&format!("> {}\n", text_line));
+                }
+            }
+            out
+        }
+        TranscriptFormat::Csv => {
+            let mut out = String::from("timestamp,direction,from,content_type,text\n");
+            for line in &lines {
+                let fields = [line.at, line.direction, line.from, line.content_type, line.text];
+                out.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+                out.push('\n');
+            }
+            out
+        }
+        TranscriptFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+            "chat_tool_id": chat_tool.id,
+            "chat_tool_name": chat_tool.name,
+            "contact_external_id": contact.external_id,
+            "contact_name": contact.name,
+            "messages": lines,
+        }))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("hello"), "hello");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\"\nbye"), "\"say \"\"hi\"\"\nbye\"");
+    }
+}
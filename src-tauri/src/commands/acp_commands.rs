@@ -1,12 +1,13 @@
 use serde::Serialize;
 use tauri::Emitter;
 
-use crate::acp::{client, discovery, manager, provisioner};
+use crate::acp::agent_terminal::{self, AgentTerminalInfo};
+use crate::acp::{agent_auth, agent_versions, client, discovery, manager, provisioner};
 use crate::acp::builtin;
 use crate::commands::settings_commands;
 use crate::db::agent_repo;
 use crate::error::{AppError, AppResult};
-use crate::models::agent::DiscoveredAgent;
+use crate::models::agent::{AgentVersions, DiscoveredAgent};
 use crate::state::AppState;
 
 #[derive(Debug, Clone, Serialize)]
@@ -54,7 +55,7 @@ pub async fn spawn_agent(
     }
     drop(processes);
 
-    let process = manager::spawn_agent_process(&agent_id, &command, &args, &std::collections::HashMap::new(), &command).await?;
+    let process = manager::spawn_agent_process(&state, &agent_id, &command, &args, &std::collections::HashMap::new(), &command).await?;
     let stdin_handle = process.stdin.clone();
 
     let mut processes = state.agent_processes.lock().await;
@@ -172,6 +173,26 @@ pub async fn stop_agent(
     Ok(())
 }
 
+/// Terminals of running agents, those waiting for input first.
+#[tauri::command]
+pub async fn list_agent_terminals(state: tauri::State<'_, AppState>) -> AppResult<Vec<AgentTerminalInfo>> {
+    Ok(agent_terminal::list(state.inner()))
+}
+
+/// Type into an agent's terminal, e.g. the answer to a login prompt. `data`
+/// is written as is; end a reply with "\n".
+#[tauri::command(rename_all = "camelCase")]
+pub async fn write_agent_terminal(
+    state: tauri::State<'_, AppState>,
+    terminal_id: String,
+    data: String,
+) -> AppResult<()> {
+    let terminal = agent_terminal::get(state.inner(), &terminal_id)?;
+    tokio::task::spawn_blocking(move || terminal.write_input(&data))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
 /// Get models from an ACP agent by creating a temporary session.
 /// This can be called after an agent is initialized to discover available models.
 /// Note: This creates a temporary ACP session to query models. The session may be
@@ -340,11 +361,12 @@ pub async fn resume_acp_session(
     let acp_session_id = if let Some(temp_id) = temp_acp_id {
         log::info!("Reusing temporary ACP session: {}", temp_id);
 
-        // Move the temp session to the actual session
+        // Move the temp session to the actual session. It stays new, so the
+        // first prompt carries the session's summary
         let mut acp_sessions = state.acp_sessions.lock().await;
         if let Some(mut info) = acp_sessions.remove(&temp_session_key) {
             info.session_id = session_id.clone();
-            info.mark_active();
+            info.touch();
             acp_sessions.insert(session_id.clone(), info);
             temp_id
         } else {
@@ -496,7 +518,8 @@ pub struct EnsureAgentReadyResult {
 /// 4. session/new to fetch available models (stored as temp session for later reuse)
 ///
 /// If the agent is already running and has cached models, returns cached models
-/// unless force_refresh is true.
+/// unless force_refresh is true. An agent that is not logged in is logged in
+/// with its login command and started again.
 #[tauri::command(rename_all = "camelCase")]
 pub async fn ensure_agent_ready(
     app: tauri::AppHandle,
@@ -504,12 +527,38 @@ pub async fn ensure_agent_ready(
     agent_id: String,
     force_refresh: Option<bool>,
 ) -> AppResult<EnsureAgentReadyResult> {
+    let started_at = std::time::Instant::now();
     let force_refresh = force_refresh.unwrap_or(false);
+    match ensure_agent_ready_inner(&app, state.inner(), agent_id.clone(), force_refresh).await {
+        Err(e) if agent_auth::is_auth_error(&e) => {
+            log::info!("Agent {} is not logged in: {}", agent_id, e);
+            let state_clone = state.inner().clone();
+            let aid = agent_id.clone();
+            let agent = tokio::task::spawn_blocking(move || agent_repo::get_agent(&state_clone, &aid))
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))??;
+            if !agent_auth::login(&app, state.inner(), &agent, None, started_at).await? {
+                return Err(e);
+            }
+            // The agent reads its credentials when it starts
+            manager::stop_agent_and_sessions(state.inner(), &agent_id).await;
+            ensure_agent_ready_inner(&app, state.inner(), agent_id, true).await
+        }
+        result => result,
+    }
+}
+
+async fn ensure_agent_ready_inner(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    agent_id: String,
+    force_refresh: bool,
+) -> AppResult<EnsureAgentReadyResult> {
     log::info!("ensure_agent_ready: Starting for agent {}, force_refresh={}", agent_id, force_refresh);
 
     // Get agent config from DB
     let agent_config = {
-        let state_clone = state.inner().clone();
+        let state_clone = state.clone();
         let aid = agent_id.clone();
         tokio::task::spawn_blocking(move || agent_repo::get_agent(&state_clone, &aid))
             .await
@@ -517,7 +566,7 @@ pub async fn ensure_agent_ready(
     };
 
     let acp_command = agent_config.acp_command.clone().ok_or_else(|| {
-        AppError::Internal(format!("Agent {} has no ACP command configured", agent_id))
+        AppError::AgentNotConfigured { agent_id: agent_id.clone() }
     })?;
 
     // Check if already running — and if agent type or CLI version changed
@@ -564,18 +613,7 @@ pub async fn ensure_agent_ready(
     // Stop old process if agent type changed
     if needs_restart {
         log::info!("Stopping old agent process for {}", agent_id);
-        let mut processes = state.agent_processes.lock().await;
-        if let Some(mut old_process) = processes.remove(&agent_id) {
-            let _ = manager::stop_agent_process(&mut old_process).await;
-        }
-        drop(processes);
-        let mut stdins = state.agent_stdins.lock().await;
-        stdins.remove(&agent_id);
-        drop(stdins);
-        // Clean up temp ACP sessions for this agent
-        let mut acp_sessions = state.acp_sessions.lock().await;
-        acp_sessions.retain(|k, info| !(info.agent_id == agent_id || k == &format!("temp:{}", agent_id)));
-        drop(acp_sessions);
+        manager::stop_agent_and_sessions(state, &agent_id).await;
     }
 
     let process_running = {
@@ -651,6 +689,7 @@ pub async fn ensure_agent_ready(
 
         // --- Spawn ---
         let mut process = manager::spawn_agent_process(
+            state,
             &agent_id,
             &resolved.command,
             &resolved.args,
@@ -776,7 +815,7 @@ pub async fn ensure_agent_ready(
     // --- Fetch models via session/new (stored as temp session) ---
     log::info!("Fetching models for agent {}", agent_id);
 
-    let cwd = settings_commands::resolve_working_directory(state.inner());
+    let cwd = settings_commands::resolve_working_directory(state);
 
     let mut processes = state.agent_processes.lock().await;
     let process = processes
@@ -1033,11 +1072,26 @@ pub async fn uninstall_registry_agent(
 
     // Remove from installed manifest
     discovery::mark_uninstalled(&registry_id);
+    agent_versions::unpin(&registry_id);
 
     // Re-discover
     discover_agents_inner().await
 }
 
+/// Installed, latest and published versions of a registry agent's CLI.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_agent_versions(agent_type: String) -> AppResult<AgentVersions> {
+    agent_versions::list(&agent_type).await
+}
+
+/// Install `version` of a registry agent and keep it until changed, or with
+/// no version go back to the registry's. Running agent processes keep their
+/// version until they restart.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_agent_version(agent_type: String, version: Option<String>) -> AppResult<AgentVersions> {
+    agent_versions::set(&agent_type, version.as_deref()).await
+}
+
 /// Thin helper – resolve a command in a given PATH (used only in this module).
 fn which_in_path(cmd: &str, path_env: &str) -> Option<String> {
     #[cfg(target_os = "windows")]
@@ -0,0 +1,29 @@
+use crate::db::{activity_repo, event_log_repo};
+use crate::error::{AppError, AppResult};
+use crate::models::activity::{ActivityEntry, ActivityQuery};
+use crate::models::event::{EventLogQuery, LoggedEvent};
+use crate::state::AppState;
+
+/// A page of the activity feed, newest first.
+#[tauri::command]
+pub async fn list_activity(
+    state: tauri::State<'_, AppState>,
+    query: ActivityQuery,
+) -> AppResult<Vec<ActivityEntry>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || activity_repo::list_activity(&state, &query))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Events of kept topics, oldest first, for a window catching up on what it missed.
+#[tauri::command]
+pub async fn list_logged_events(
+    state: tauri::State<'_, AppState>,
+    query: EventLogQuery,
+) -> AppResult<Vec<LoggedEvent>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || event_log_repo::list_events(&state, &query))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
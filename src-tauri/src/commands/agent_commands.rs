@@ -1,8 +1,11 @@
-use crate::db::{agent_md, agent_repo};
+use crate::activity;
+use crate::db::{agent_context_repo, agent_manifest, agent_md, agent_repo, permission_policy_repo, workspace_repo};
 use crate::error::{AppError, AppResult};
-use crate::models::agent::{AgentConfig, CreateAgentRequest, UpdateAgentRequest};
+use crate::models::agent::{AgentConfig, AgentContext, CreateAgentRequest, ManifestSyncReport, UpdateAgentRequest};
+use crate::models::bulk::BulkResult;
+use crate::models::permission::PermissionPolicy;
 use crate::state::AppState;
-use crate::acp::{client, discovery, manager, provisioner};
+use crate::acp::{client, discovery, manager, orchestrator, permissions, provisioner, skill_cache};
 
 #[tauri::command(rename_all = "camelCase")]
 pub async fn list_agents(
@@ -25,25 +28,38 @@ pub async fn get_agent(state: tauri::State<'_, AppState>, id: String) -> AppResu
 
 #[tauri::command]
 pub async fn create_agent(
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
     request: CreateAgentRequest,
 ) -> AppResult<AgentConfig> {
     let state = state.inner().clone();
-    tokio::task::spawn_blocking(move || {
-        let agent = agent_repo::create_agent(&state, request)?;
+    let state_clone = state.clone();
+    let agent = tokio::task::spawn_blocking(move || {
+        let agent = agent_repo::create_agent(&state_clone, request)?;
         // Write markdown file and update DB with path
         if let Ok(md_path) = agent_md::write_agent_md(&agent) {
             let path_str = md_path.to_string_lossy().to_string();
-            let _ = agent_repo::update_agent_md_path(&state, &agent.id, &path_str);
+            let _ = agent_repo::update_agent_md_path(&state_clone, &agent.id, &path_str);
         }
         // Regenerate agents registry
-        if let Ok(all_agents) = agent_repo::list_agents(&state, None) {
+        if let Ok(all_agents) = agent_repo::list_agents(&state_clone, None) {
             let _ = agent_md::write_agents_registry(&all_agents);
         }
-        agent_repo::get_agent(&state, &agent.id)
+        agent_repo::get_agent(&state_clone, &agent.id)
     })
     .await
-    .map_err(|e| crate::error::AppError::Internal(e.to_string()))?
+    .map_err(|e| crate::error::AppError::Internal(e.to_string()))??;
+
+    activity::record(
+        &app,
+        &state,
+        agent.workspace_id.as_deref(),
+        activity::AGENT_ADDED,
+        Some(&agent.id),
+        format!("Agent \"{}\" added", agent.name),
+    )
+    .await;
+    Ok(agent)
 }
 
 #[tauri::command]
@@ -97,6 +113,28 @@ pub async fn set_control_hub(
         .map_err(|e| crate::error::AppError::Internal(e.to_string()))?
 }
 
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_secondary_hub(
+    state: tauri::State<'_, AppState>,
+    agent_id: String,
+) -> AppResult<AgentConfig> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || agent_repo::set_secondary_hub(&state, &agent_id))
+        .await
+        .map_err(|e| crate::error::AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn clear_secondary_hub(
+    state: tauri::State<'_, AppState>,
+    agent_id: String,
+) -> AppResult<AgentConfig> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || agent_repo::clear_secondary_hub(&state, &agent_id))
+        .await
+        .map_err(|e| crate::error::AppError::Internal(e.to_string()))?
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub async fn get_control_hub(
     state: tauri::State<'_, AppState>,
@@ -108,6 +146,167 @@ pub async fn get_control_hub(
         .map_err(|e| crate::error::AppError::Internal(e.to_string()))?
 }
 
+/// Get the context an agent carries over between orchestrations in a workspace.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_agent_context(
+    state: tauri::State<'_, AppState>,
+    agent_id: String,
+    workspace_id: Option<String>,
+) -> AppResult<Option<AgentContext>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        agent_context_repo::get_agent_context(&state, &agent_id, workspace_id.as_deref())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Forget an agent's carried-over session and summary so the next run starts fresh.
+/// Without a workspace, context is reset in every workspace.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn reset_agent_context(
+    state: tauri::State<'_, AppState>,
+    agent_id: String,
+    workspace_id: Option<String>,
+) -> AppResult<u64> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        agent_context_repo::reset_agent_context(&state, &agent_id, workspace_id.as_deref())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Permission decisions remembered for an agent, or for every agent.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_permission_policies(
+    state: tauri::State<'_, AppState>,
+    agent_id: Option<String>,
+) -> AppResult<Vec<PermissionPolicy>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || permission_policy_repo::list_policies(&state, agent_id.as_deref()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Forget a remembered permission decision; similar requests ask again.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn delete_permission_policy(state: tauri::State<'_, AppState>, id: String) -> AppResult<()> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || permission_policy_repo::delete_policy(&state, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Permission presets an agent or a run can be given, with their rules.
+#[tauri::command]
+pub async fn list_permission_presets() -> AppResult<Vec<permissions::PresetInfo>> {
+    Ok(permissions::presets())
+}
+
+/// Reconcile a workspace's agents with the `agents.yaml` in its working
+/// directory: create new entries, update managed agents and disable those
+/// whose entry was removed.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn sync_agents_from_manifest(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    workspace_id: String,
+) -> AppResult<ManifestSyncReport> {
+    let state = state.inner().clone();
+    let state_clone = state.clone();
+    let ws_id = workspace_id.clone();
+    let (report, created) = tokio::task::spawn_blocking(move || {
+        let workspace = workspace_repo::get_workspace(&state_clone, &ws_id)?;
+        if workspace.working_directory.trim().is_empty() {
+            return Err(AppError::InvalidRequest(format!(
+                "Workspace \"{}\" has no working directory",
+                workspace.name
+            )));
+        }
+        let (report, changed) = agent_manifest::sync_workspace(&state_clone, &ws_id, &workspace.working_directory)?;
+        for agent in &changed {
+            if let Ok(md_path) = agent_md::write_agent_md(agent) {
+                let path_str = md_path.to_string_lossy().to_string();
+                let _ = agent_repo::update_agent_md_path(&state_clone, &agent.id, &path_str);
+            }
+        }
+        if !changed.is_empty() {
+            if let Ok(all_agents) = agent_repo::list_agents(&state_clone, None) {
+                let _ = agent_md::write_agents_registry(&all_agents);
+            }
+        }
+        let created: Vec<AgentConfig> = changed.into_iter().filter(|a| report.created.contains(&a.name)).collect();
+        Ok((report, created))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    for agent in &created {
+        activity::record(
+            &app,
+            &state,
+            Some(&workspace_id),
+            activity::AGENT_ADDED,
+            Some(&agent.id),
+            format!("Agent \"{}\" added from agents.yaml", agent.name),
+        )
+        .await;
+    }
+    Ok(report)
+}
+
+/// Write AGENTS.md into a workspace's working directory, describing its
+/// enabled agents for other tools and for planning. Returns the file path.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn generate_agents_md(state: tauri::State<'_, AppState>, workspace_id: String) -> AppResult<String> {
+    let state = state.inner().clone();
+    let (state_clone, ws_id) = (state.clone(), workspace_id.clone());
+    let (workspace, agents) = tokio::task::spawn_blocking(move || {
+        let workspace = workspace_repo::get_workspace(&state_clone, &ws_id)?;
+        if workspace.working_directory.trim().is_empty() {
+            return Err(AppError::InvalidRequest(format!(
+                "Workspace \"{}\" has no working directory",
+                workspace.name
+            )));
+        }
+        let agents = agent_repo::list_agents(&state_clone, Some(&ws_id))?;
+        Ok((workspace, agents))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    let (discovery, _) = skill_cache::skills(&state, Some(&workspace_id), &workspace.working_directory, false).await?;
+    tokio::task::spawn_blocking(move || {
+        let enabled: Vec<&AgentConfig> = agents.iter().filter(|a| a.is_enabled).collect();
+        let catalog = orchestrator::build_structured_agent_catalog(&enabled, Some(&discovery));
+        let sections: Vec<_> = enabled
+            .iter()
+            .map(|a| (*a, orchestrator::catalog_skills(a, Some(&discovery))))
+            .collect();
+        let content = agent_md::render_agents_md(&workspace.name, &catalog, &sections);
+        let path = agent_md::write_workspace_agents_md(std::path::Path::new(&workspace.working_directory), &content)?;
+        log::info!("Wrote {} for workspace {}", path.display(), workspace_id);
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Enable or disable several agents at once, without health checks.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_agents_enabled(
+    state: tauri::State<'_, AppState>,
+    agent_ids: Vec<String>,
+    enabled: bool,
+) -> AppResult<BulkResult> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || agent_repo::set_agents_enabled(&state, &agent_ids, enabled))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .map(BulkResult::from)
+}
+
 /// Enable a previously disabled agent, performing a health check before confirming.
 /// If the health check fails, the agent is reverted to disabled with the new error.
 #[tauri::command(rename_all = "camelCase")]
@@ -171,6 +370,7 @@ pub async fn enable_agent(
         let extra_env = discovery::get_agent_env_for_command(&resolved.agent_type).await;
 
         let mut process = manager::spawn_agent_process(
+            &state,
             &agent_id,
             &resolved.command,
             &resolved.args,
@@ -213,7 +413,7 @@ pub async fn enable_agent(
             let aid = agent_id.clone();
             let reason = err_msg.clone();
             let _ = tokio::task::spawn_blocking(move || {
-                agent_repo::disable_agent(&state_clone, &aid, &reason)
+                agent_repo::disable_agent(&state_clone, &aid, &reason, false)
             }).await;
 
             Err(AppError::Internal(format!(
@@ -1,5 +1,6 @@
 use tauri::Emitter;
 
+use crate::acp::session_summary;
 use crate::db::agent_repo;
 use crate::db::message_repo;
 use crate::db::session_repo;
@@ -8,8 +9,12 @@ use crate::commands::settings_commands;
 use crate::error::{AppError, AppResult};
 use crate::models::agent::AgentConfig;
 use crate::models::message::ChatMessage;
+use crate::models::prompt::PromptRef;
 use crate::models::session::Session;
-use crate::state::AppState;
+use crate::memory;
+use crate::prompts;
+use crate::redaction;
+use crate::state::{AcpSessionState, AppState};
 
 #[tauri::command(rename_all = "camelCase")]
 pub async fn send_prompt(
@@ -17,7 +22,17 @@ pub async fn send_prompt(
     state: tauri::State<'_, AppState>,
     session_id: String,
     content: String,
+    prompt: Option<PromptRef>,
 ) -> AppResult<ChatMessage> {
+    // A library prompt is rendered and sent in place of (or ahead of) the typed text
+    let content = if prompt.is_some() {
+        let state_clone = state.inner().clone();
+        tokio::task::spawn_blocking(move || prompts::compose(&state_clone, prompt.as_ref(), &content))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??
+    } else {
+        content
+    };
     log::info!("send_prompt called: session_id={}, content_len={}", session_id, content.len());
 
     // Save user message to DB
@@ -32,6 +47,8 @@ pub async fn send_prompt(
         .unwrap_or_else(|_| "[]".into()),
         tool_calls_json: None,
         created_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        pinned_at: None,
+        reactions: Vec::new(),
     };
 
     let state_clone = state.inner().clone();
@@ -61,6 +78,11 @@ pub async fn send_prompt(
     };
     log::info!("Agent config found: acp_command={:?}", agent_config.acp_command);
 
+    // Replies and session summaries are read off the same channel; hold the
+    // agent until this reply has been read. Taken before the process check
+    // so an idle shutdown cannot stop the agent in between
+    let agent_guard = session_summary::agent_lock(&state, &agent_id).await.lock_owned().await;
+
     // Ensure agent process is running
     let process_running = {
         let processes = state.agent_processes.lock().await;
@@ -149,7 +171,7 @@ pub async fn send_prompt(
         let extra_env = crate::acp::discovery::get_agent_env_for_command(&acp_command).await;
 
         // Spawn the agent process
-        let process = crate::acp::manager::spawn_agent_process(&agent_id, &acp_command, &args, &extra_env, &acp_command).await?;
+        let process = crate::acp::manager::spawn_agent_process(&state, &agent_id, &acp_command, &args, &extra_env, &acp_command).await?;
         let stdin_handle = process.stdin.clone();
         log::info!("Agent process spawned: {}", agent_id);
 
@@ -185,6 +207,8 @@ pub async fn send_prompt(
         acp_sessions.get(&session_id).cloned()
     };
 
+    // A new ACP session starts without the conversation so far
+    let mut new_acp_session = true;
     let acp_session_id = if let Some(info) = acp_session_info {
         // Session exists in our state - check if it's usable
         if info.is_usable() {
@@ -194,6 +218,8 @@ pub async fn send_prompt(
             if let Some(s) = acp_sessions.get_mut(&session_id) {
                 s.mark_active();
             }
+            // A session created on resume has not had a prompt yet
+            new_acp_session = info.state == AcpSessionState::New;
             info.acp_session_id.clone()
         } else {
             log::info!("Existing session state is {}, creating new session", info.state);
@@ -253,27 +279,54 @@ pub async fn send_prompt(
         }
     };
 
+    // Carry the session's summary and pinned messages into a new ACP session
+    let mut agent_prompt = content.clone();
+    if new_acp_session {
+        let state_clone = state.inner().clone();
+        let session_id_clone = session_id.clone();
+        match tokio::task::spawn_blocking(move || message_repo::list_pinned_messages(&state_clone, &session_id_clone)).await {
+            Ok(Ok(pinned)) => {
+                if let Some(context) = pinned_context(&pinned) {
+                    log::info!("Injecting {} pinned message(s) into the new ACP session", pinned.len());
+                    agent_prompt = format!("{}\n\n{}", context, agent_prompt);
+                }
+            }
+            Ok(Err(e)) => log::warn!("Failed to load pinned messages of session {}: {}", session_id, e),
+            Err(e) => log::warn!("Spawn blocking failed: {}", e),
+        }
+        if let Some(context) = session_summary::resume_context(&session) {
+            log::info!("Seeding the new ACP session with the summary of session {}", session_id);
+            agent_prompt = format!("{}\n\n{}", context, agent_prompt);
+        }
+    }
+
     // Send prompt to agent
     let mut processes = state.agent_processes.lock().await;
     if let Some(process) = processes.get_mut(&agent_id) {
         let request_id = chrono::Utc::now().timestamp();
         log::info!("Sending prompt to agent: acp_session_id={}, request_id={}", acp_session_id, request_id);
-        crate::acp::client::send_prompt(process, &acp_session_id, &content, request_id)
+        crate::acp::client::send_prompt(process, &acp_session_id, &agent_prompt, request_id)
             .await?;
     }
     drop(processes);
+    if let Some(info) = state.acp_sessions.lock().await.get_mut(&session_id) {
+        info.mark_active();
+    }
 
     // Spawn a task to read streaming responses
     let app_clone = app.clone();
     let state_clone = state.inner().clone();
     let session_id_clone = session_id.clone();
     let agent_id_clone = agent_id.clone();
+    let user_text = content.clone();
 
     log::info!("Spawning handle_agent_responses task");
     tokio::spawn(async move {
         log::info!("handle_agent_responses task started");
-        handle_agent_responses(app_clone, state_clone, agent_id_clone, session_id_clone).await;
+        handle_agent_responses(app_clone, state_clone.clone(), agent_id_clone, session_id_clone.clone(), user_text).await;
         log::info!("handle_agent_responses task completed");
+        drop(agent_guard);
+        session_summary::summarize_if_due(&state_clone, &session_id_clone).await;
     });
 
     log::info!("send_prompt completed successfully");
@@ -285,10 +338,28 @@ async fn handle_agent_responses(
     state: AppState,
     agent_id: String,
     session_id: String,
+    user_text: String,
 ) {
     log::info!("handle_agent_responses started: agent_id={}, session_id={}", agent_id, session_id);
 
     let timeout_deadline = std::time::Instant::now() + std::time::Duration::from_secs(300);
+    // Reply text, kept for long-term memory
+    let mut reply_text = String::new();
+    // The reply as shown, saved while it streams
+    let mut reply_stream = Some(message_repo::MessageStream::new(&state, &session_id));
+    // Replies in workspaces that redact outputs are masked before they are shown or stored
+    let mut redactor = {
+        let state_clone = state.clone();
+        let aid = agent_id.clone();
+        tokio::task::spawn_blocking(move || {
+            let workspace_id = agent_repo::get_agent(&state_clone, &aid).ok().and_then(|a| a.workspace_id);
+            redaction::for_workspace(&state_clone, workspace_id.as_deref())
+        })
+        .await
+        .ok()
+        .flatten()
+        .map(redaction::StreamRedactor::new)
+    };
 
     loop {
         // Non-blocking receive: lock the HashMap briefly, try_recv, release immediately.
@@ -326,7 +397,7 @@ async fn handle_agent_responses(
         };
 
         match msg {
-            Some(msg) => {
+            Some(mut msg) => {
                 let method = msg.get("method").and_then(|m| m.as_str()).unwrap_or("");
                 log::debug!("Processing message with method: '{}'", method);
 
@@ -344,6 +415,19 @@ async fn handle_agent_responses(
 
                         match update_type {
                             "agent_message_chunk" | "user_message_chunk" => {
+                                if update_type == "agent_message_chunk" {
+                                    if let Some(text) = msg.pointer_mut("/params/update/content/text") {
+                                        if let Some(chunk) = text.as_str() {
+                                            reply_text.push_str(chunk);
+                                            if let Some(redactor) = redactor.as_mut() {
+                                                *text = redactor.push(chunk).into();
+                                            }
+                                        }
+                                        if let (Some(stream), Some(visible)) = (reply_stream.as_mut(), text.as_str()) {
+                                            stream.push(visible);
+                                        }
+                                    }
+                                }
                                 let _ = app.emit("acp:agent_message_chunk", &msg);
                             }
                             "agent_thought_chunk" => {
@@ -380,24 +464,55 @@ async fn handle_agent_responses(
                         // No method field - this is a JSON-RPC response to one of our requests
                         if let Some(result) = msg.get("result") {
                             log::info!("Agent response completed, result: {:?}", result);
+                            if let Some(redactor) = redactor.as_mut() {
+                                let rest = redactor.finish();
+                                if !rest.is_empty() {
+                                    let _ = app.emit("acp:agent_message_chunk", serde_json::json!({
+                                        "params": { "update": {
+                                            "sessionUpdate": "agent_message_chunk",
+                                            "content": { "type": "text", "text": rest },
+                                        } },
+                                    }));
+                                }
+                                reply_text = redactor.redactor().redact(&reply_text);
+                            }
+                            // Store the streamed reply so the message can be pinned and
+                            // injected later; the result only holds the stop reason
+                            let content_json = if reply_text.is_empty() {
+                                serde_json::to_string(result)
+                            } else {
+                                serde_json::to_string(&[serde_json::json!({ "type": "text", "text": reply_text })])
+                            };
+                            let stream = reply_stream.take();
                             let agent_msg = ChatMessage {
-                                id: uuid::Uuid::new_v4().to_string(),
+                                id: stream.as_ref().map_or_else(|| uuid::Uuid::new_v4().to_string(), |s| s.id().to_string()),
                                 session_id: session_id.clone(),
                                 role: "Agent".into(),
-                                content_json: serde_json::to_string(result)
-                                    .unwrap_or_else(|_| "[]".into()),
+                                content_json: content_json.unwrap_or_else(|_| "[]".into()),
                                 tool_calls_json: None,
                                 created_at: chrono::Utc::now()
                                     .format("%Y-%m-%d %H:%M:%S")
                                     .to_string(),
+                                pinned_at: None,
+                                reactions: Vec::new(),
                             };
+                            match stream {
+                                Some(stream) => stream.finish(agent_msg.clone()),
+                                None => message_repo::queue_message(&state, agent_msg.clone()),
+                            }
+                            let _ = app.emit("acp:message_complete", &agent_msg);
+
                             let state_clone = state.clone();
-                            let msg_clone = agent_msg.clone();
-                            let _ = tokio::task::spawn_blocking(move || {
-                                message_repo::save_message(&state_clone, &msg_clone)
+                            let sid = session_id.clone();
+                            let user_text = user_text.clone();
+                            let reply = std::mem::take(&mut reply_text);
+                            if let Ok(Err(e)) = tokio::task::spawn_blocking(move || {
+                                memory::remember_chat_exchange(&state_clone, &sid, &user_text, &reply)
                             })
-                            .await;
-                            let _ = app.emit("acp:message_complete", &agent_msg);
+                            .await
+                            {
+                                log::warn!("Failed to remember chat exchange: {}", e);
+                            }
                             log::info!("handle_agent_responses ending (result received)");
                             break;
                         } else if let Some(error) = msg.get("error") {
@@ -418,6 +533,11 @@ async fn handle_agent_responses(
             }
         }
     }
+
+    // Keep what streamed of a reply that ended without a result
+    if let Some(stream) = reply_stream {
+        stream.close();
+    }
 }
 
 #[tauri::command(rename_all = "camelCase")]
@@ -450,6 +570,44 @@ pub async fn get_messages(
         .map_err(|e| AppError::Internal(e.to_string()))?
 }
 
+/// Pin or unpin a message
+#[tauri::command(rename_all = "camelCase")]
+pub async fn pin_message(
+    state: tauri::State<'_, AppState>,
+    message_id: String,
+    pinned: bool,
+) -> AppResult<ChatMessage> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || message_repo::set_message_pinned(&state, &message_id, pinned))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Add or remove a reaction on a message
+#[tauri::command(rename_all = "camelCase")]
+pub async fn react_to_message(
+    state: tauri::State<'_, AppState>,
+    message_id: String,
+    reaction: String,
+    reacted: bool,
+) -> AppResult<ChatMessage> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || message_repo::set_message_reaction(&state, &message_id, &reaction, reacted))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_pinned_messages(
+    state: tauri::State<'_, AppState>,
+    session_id: String,
+) -> AppResult<Vec<ChatMessage>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || message_repo::list_pinned_messages(&state, &session_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
 /// Respond to a permission request from the agent
 #[tauri::command(rename_all = "camelCase")]
 pub async fn respond_permission(
@@ -593,6 +751,38 @@ pub async fn open_file_with_default_app(
     Ok(())
 }
 
+/// Most characters of pinned messages carried into a new ACP session; the
+/// newest pins are kept when they do not all fit.
+const MAX_PINNED_CONTEXT_CHARS: usize = 12_000;
+
+/// Preamble recalling the pinned messages of a conversation, if any have text.
+fn pinned_context(pinned: &[ChatMessage]) -> Option<String> {
+    let mut budget = MAX_PINNED_CONTEXT_CHARS;
+    let mut sections = Vec::new();
+    for message in pinned.iter().rev() {
+        let text = message.text();
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let section = format!("--- {} ({}) ---\n{}", message.role, message.created_at, text);
+        let len = section.chars().count();
+        if len > budget {
+            break;
+        }
+        budget -= len;
+        sections.push(section);
+    }
+    if sections.is_empty() {
+        return None;
+    }
+    sections.reverse();
+    Some(format!(
+        "Pinned messages from earlier in this conversation:\n\n{}\n\n---",
+        sections.join("\n\n")
+    ))
+}
+
 /// Helper function to create a new ACP session following the ACP protocol.
 /// This creates a session/new request and tracks the session in state.
 async fn create_new_acp_session(
@@ -1,12 +1,18 @@
 use tauri::Emitter;
 use tokio_util::sync::CancellationToken;
 
-use crate::chat_tool::bridge;
+use crate::chat_tool::{bridge, delivery, metrics, quota, takeover};
 use crate::chat_tool::manager;
+use crate::chat_tool::contact_list::{self, ContactListFormat};
+use crate::chat_tool::transcript::{self, TranscriptFormat};
 use crate::db::chat_tool_repo;
+use crate::db::migrations::get_output_dir;
 use crate::error::{AppError, AppResult};
+use crate::events;
+use crate::models::bulk::{BulkItemResult, BulkResult};
 use crate::models::chat_tool::{
-    BridgeCommand, ChatTool, ChatToolContact, ChatToolMessage, CreateChatToolRequest,
+    BridgeCapabilities, BridgeCommand, ChatTool, ChatToolContact, ChatToolEscalation, ChatToolMessage,
+    ChatToolMetrics, ContactImport, CreateChatToolRequest, MetricsRange, RunNotificationSubscription,
     UpdateChatToolRequest,
 };
 use crate::state::AppState;
@@ -40,6 +46,9 @@ pub async fn create_chat_tool(
     state: tauri::State<'_, AppState>,
     request: CreateChatToolRequest,
 ) -> AppResult<ChatTool> {
+    if let Some(env_profile) = &request.env_profile {
+        env_profile.validate().map_err(AppError::InvalidRequest)?;
+    }
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || chat_tool_repo::create_chat_tool(&state, request))
         .await
@@ -52,12 +61,32 @@ pub async fn update_chat_tool(
     id: String,
     request: UpdateChatToolRequest,
 ) -> AppResult<ChatTool> {
+    if let Some(env_profile) = &request.env_profile {
+        env_profile.validate().map_err(AppError::InvalidRequest)?;
+    }
+    if let Some(templates) = &request.reply_templates {
+        templates.validate().map_err(AppError::InvalidRequest)?;
+    }
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || chat_tool_repo::update_chat_tool(&state, &id, request))
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?
 }
 
+/// Duplicate a chat tool so another account of the same plugin type can be
+/// logged in alongside it.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn clone_chat_tool(
+    state: tauri::State<'_, AppState>,
+    id: String,
+    name: Option<String>,
+) -> AppResult<ChatTool> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || chat_tool_repo::clone_chat_tool(&state, &id, name))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub async fn delete_chat_tool(
     state: tauri::State<'_, AppState>,
@@ -87,17 +116,6 @@ pub async fn start_chat_tool(
     state: tauri::State<'_, AppState>,
     id: String,
 ) -> AppResult<()> {
-    // Check if already running
-    {
-        let processes = state.chat_tool_processes.lock().await;
-        if processes.contains_key(&id) {
-            return Err(AppError::InvalidRequest(format!(
-                "Chat tool {} is already running",
-                id
-            )));
-        }
-    }
-
     // Get chat tool config
     let state_clone = state.inner().clone();
     let id_clone = id.clone();
@@ -106,6 +124,26 @@ pub async fn start_chat_tool(
     })
     .await
     .map_err(|e| AppError::Internal(e.to_string()))??;
+    let env_profile = chat_tool.env_profile().map_err(AppError::InvalidRequest)?;
+    let data_dir = manager::resolve_bridge_data_dir(&id, &env_profile);
+
+    // The process map stays locked from the checks until the new process is
+    // in it, so two starts can't both pass them
+    let mut processes = state.chat_tool_processes.lock().await;
+    if processes.contains_key(&id) {
+        return Err(AppError::InvalidRequest(format!(
+            "Chat tool {} is already running",
+            id
+        )));
+    }
+    // Two running bridges must never share a data directory (login state)
+    if let Some(other) = processes.values().find(|p| p.data_dir == data_dir) {
+        return Err(AppError::InvalidRequest(format!(
+            "Chat tool {} is already running with data directory {}",
+            other.chat_tool_id,
+            data_dir.display()
+        )));
+    }
 
     // Update status to starting
     let state_clone = state.inner().clone();
@@ -118,11 +156,12 @@ pub async fn start_chat_tool(
 
     // Spawn bridge process
     let spawn_result =
-        manager::spawn_bridge(&id, &chat_tool.plugin_type, &chat_tool.config_json).await;
+        manager::spawn_bridge(&state, &id, &chat_tool.plugin_type, &chat_tool.config_json, &env_profile).await;
 
     let (process, stdout) = match spawn_result {
         Ok(r) => r,
         Err(e) => {
+            drop(processes);
             // Revert status to error
             let state_clone = state.inner().clone();
             let id_clone = id.clone();
@@ -144,9 +183,9 @@ pub async fn start_chat_tool(
     let cancel_token = CancellationToken::new();
 
     // Store process and token
+    processes.insert(id.clone(), process);
+    drop(processes);
     {
-        let mut processes = state.chat_tool_processes.lock().await;
-        processes.insert(id.clone(), process);
         let mut cancellations = state.chat_tool_cancellations.lock().await;
         cancellations.insert(id.clone(), cancel_token.clone());
     }
@@ -167,6 +206,35 @@ pub async fn stop_chat_tool(
     state: tauri::State<'_, AppState>,
     id: String,
 ) -> AppResult<()> {
+    stop(&app, state.inner(), id).await
+}
+
+/// Stop every running chat tool.
+#[tauri::command]
+pub async fn stop_all_chat_tools(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> AppResult<BulkResult> {
+    let mut ids: Vec<String> = {
+        let cancellations = state.chat_tool_cancellations.lock().await;
+        cancellations.keys().cloned().collect()
+    };
+    {
+        let processes = state.chat_tool_processes.lock().await;
+        ids.extend(processes.keys().filter(|id| !ids.contains(id)).cloned().collect::<Vec<_>>());
+    }
+
+    let mut items = Vec::with_capacity(ids.len());
+    for id in ids {
+        items.push(match stop(&app, state.inner(), id.clone()).await {
+            Ok(()) => BulkItemResult::ok(&id),
+            Err(e) => BulkItemResult::failed(&id, e),
+        });
+    }
+    Ok(BulkResult::from(items))
+}
+
+async fn stop(app: &tauri::AppHandle, state: &AppState, id: String) -> AppResult<()> {
     // Cancel event loop
     {
         let mut cancellations = state.chat_tool_cancellations.lock().await;
@@ -204,7 +272,7 @@ pub async fn stop_chat_tool(
     }
 
     // Update status in DB
-    let state_clone = state.inner().clone();
+    let state_clone = state.clone();
     let id_clone = id.clone();
     tokio::task::spawn_blocking(move || {
         chat_tool_repo::update_chat_tool_status(&state_clone, &id_clone, "stopped", None)
@@ -213,9 +281,7 @@ pub async fn stop_chat_tool(
     .map_err(|e| AppError::Internal(e.to_string()))??;
 
     // Emit event so frontend updates immediately
-    let _ = app.emit(
-        "chat_tool:status_changed",
-        serde_json::json!({
+    events::emit(app, &events::CHAT_TOOL_STATUS_CHANGED, serde_json::json!({
             "chatToolId": id,
             "status": "stopped",
             "message": null
@@ -262,6 +328,16 @@ pub async fn get_chat_tool_qr_code(
     Ok(qr_codes.get(&id).cloned())
 }
 
+/// Capabilities negotiated with a running bridge, or None if it is not running.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_chat_tool_capabilities(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> AppResult<Option<BridgeCapabilities>> {
+    let processes = state.chat_tool_processes.lock().await;
+    Ok(processes.get(&id).map(|p| p.effective_capabilities()))
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub async fn list_chat_tool_messages(
     state: tauri::State<'_, AppState>,
@@ -279,6 +355,33 @@ pub async fn list_chat_tool_messages(
     .map_err(|e| AppError::Internal(e.to_string()))?
 }
 
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_chat_tool_escalations(
+    state: tauri::State<'_, AppState>,
+    chat_tool_id: String,
+    limit: Option<i64>,
+) -> AppResult<Vec<ChatToolEscalation>> {
+    let state = state.inner().clone();
+    let limit = limit.unwrap_or(50);
+    tokio::task::spawn_blocking(move || chat_tool_repo::list_escalations(&state, &chat_tool_id, limit))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Response times, Control Hub latency and queue depth of a chat tool over
+/// the last hour, day, week or month, as totals and a time series
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_chat_tool_metrics(
+    state: tauri::State<'_, AppState>,
+    chat_tool_id: String,
+    range: MetricsRange,
+) -> AppResult<ChatToolMetrics> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || metrics::load(&state, &chat_tool_id, range))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub async fn send_chat_tool_message(
     state: tauri::State<'_, AppState>,
@@ -289,36 +392,22 @@ pub async fn send_chat_tool_message(
 ) -> AppResult<()> {
     let ct = content_type.unwrap_or_else(|| "text".to_string());
 
-    // Send through bridge
-    let processes = state.chat_tool_processes.lock().await;
-    let process = processes.get(&chat_tool_id).ok_or_else(|| {
-        AppError::InvalidRequest(format!("Chat tool {} is not running", chat_tool_id))
-    })?;
-
-    let cmd = BridgeCommand::SendMessage {
-        to_id: to_id.clone(),
-        content: content.clone(),
-        content_type: ct.clone(),
-    };
-    manager::send_bridge_command(process, &cmd).await?;
-    drop(processes);
+    {
+        let processes = state.chat_tool_processes.lock().await;
+        let process = processes.get(&chat_tool_id).ok_or_else(|| {
+            AppError::InvalidRequest(format!("Chat tool {} is not running", chat_tool_id))
+        })?;
 
-    // Save outgoing message
-    let state_clone = state.inner().clone();
-    tokio::task::spawn_blocking(move || {
-        chat_tool_repo::save_chat_tool_message(
-            &state_clone,
-            &chat_tool_id,
-            "outgoing",
-            Some(&to_id),
-            None,
-            &content,
-            &ct,
-        )
-    })
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))??;
+        if ct != "text" && !process.effective_capabilities().media {
+            return Err(AppError::InvalidRequest(format!(
+                "Bridge for chat tool {} does not support '{}' messages",
+                chat_tool_id, ct
+            )));
+        }
+    }
 
+    // Store and send through the bridge with delivery tracking
+    delivery::send_tracked(state.inner(), &chat_tool_id, &to_id, &content, &ct).await?;
     Ok(())
 }
 
@@ -346,3 +435,251 @@ pub async fn set_chat_tool_contact_blocked(
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?
 }
+
+/// Load a CSV or JSON contact list, e.g. an existing customer list with
+/// blocked contacts, groups and quotas, into a chat tool. Contacts are
+/// matched by external id; rows that cannot be read are skipped and
+/// reported.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn import_chat_tool_contacts(
+    state: tauri::State<'_, AppState>,
+    chat_tool_id: String,
+    path: String,
+) -> AppResult<ContactImport> {
+    let format = ContactListFormat::of_path(std::path::Path::new(&path)).ok_or_else(|| {
+        AppError::InvalidRequest(format!("Contact lists must be .csv or .json files: {}", path))
+    })?;
+    let text = tokio::fs::read_to_string(&path).await?;
+    let (records, errors) = contact_list::parse(format, &text)?;
+
+    let state = state.inner().clone();
+    let (created, updated) = tokio::task::spawn_blocking(move || {
+        chat_tool_repo::get_chat_tool(&state, &chat_tool_id)?;
+        chat_tool_repo::import_contacts(&state, &chat_tool_id, &records)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    log::info!(
+        "Imported contact list {}: {} created, {} updated, {} skipped",
+        path,
+        created,
+        updated,
+        errors.len()
+    );
+    Ok(ContactImport { created, updated, errors })
+}
+
+/// Write a chat tool's contacts with their block flags, groups and quotas to
+/// `output/chat_tools/<chat_tool_id>/` as a CSV or JSON list that
+/// `import_chat_tool_contacts` reads back. Returns the file path.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_chat_tool_contacts(
+    state: tauri::State<'_, AppState>,
+    chat_tool_id: String,
+    format: ContactListFormat,
+) -> AppResult<String> {
+    let state = state.inner().clone();
+    let (chat_tool, contacts) = tokio::task::spawn_blocking(move || {
+        let chat_tool = chat_tool_repo::get_chat_tool(&state, &chat_tool_id)?;
+        let contacts = chat_tool_repo::list_contacts(&state, &chat_tool.id)?;
+        Ok::<_, AppError>((chat_tool, contacts))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    let content = contact_list::render(format, &contacts)?;
+    let dir = get_output_dir().join("chat_tools").join(&chat_tool.id);
+    tokio::fs::create_dir_all(&dir).await?;
+    let path = dir.join(format!(
+        "contacts-{}.{}",
+        chrono::Utc::now().format("%Y%m%d%H%M%S"),
+        format.extension()
+    ));
+    tokio::fs::write(&path, content).await?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Set a contact's daily auto-reply quotas; None removes a limit.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_chat_tool_contact_quota(
+    state: tauri::State<'_, AppState>,
+    contact_id: String,
+    max_replies_per_day: Option<i64>,
+    max_tokens_per_day: Option<i64>,
+) -> AppResult<ChatToolContact> {
+    quota::validate(max_replies_per_day, max_tokens_per_day).map_err(AppError::InvalidRequest)?;
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        chat_tool_repo::set_contact_quota(&state, &contact_id, max_replies_per_day, max_tokens_per_day)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Reset a contact's usage for today so it gets its full quotas again.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn reset_chat_tool_contact_usage(
+    state: tauri::State<'_, AppState>,
+    contact_id: String,
+) -> AppResult<ChatToolContact> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || chat_tool_repo::reset_contact_usage(&state, &contact_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Send a contact, e.g. the user's own account, a message when an
+/// orchestrated run completes or fails; `None` unsubscribes it.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_chat_tool_contact_run_notifications(
+    state: tauri::State<'_, AppState>,
+    contact_id: String,
+    subscription: Option<RunNotificationSubscription>,
+) -> AppResult<ChatToolContact> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        chat_tool_repo::set_contact_run_notifications(&state, &contact_id, subscription.as_ref())?;
+        chat_tool_repo::get_contact(&state, &contact_id)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Longest snooze: one week.
+const MAX_SNOOZE_MINUTES: i64 = 7 * 24 * 60;
+
+/// Suspend auto-replies to a contact for `duration_minutes`, e.g. while a
+/// human answers them; 0 resumes them now. Messages from the contact are
+/// still saved. Emits `chat_tool:contact_snooze_ended` when the snooze runs out.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn snooze_contact(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    chat_tool_id: String,
+    contact_id: String,
+    duration_minutes: i64,
+) -> AppResult<ChatToolContact> {
+    if !(0..=MAX_SNOOZE_MINUTES).contains(&duration_minutes) {
+        return Err(AppError::InvalidRequest(format!(
+            "Snooze duration must be between 0 and {} minutes",
+            MAX_SNOOZE_MINUTES
+        )));
+    }
+    let state = state.inner().clone();
+    let state_clone = state.clone();
+    let contact = tokio::task::spawn_blocking(move || {
+        let contact = chat_tool_repo::get_contact(&state_clone, &contact_id)?;
+        if contact.chat_tool_id != chat_tool_id {
+            return Err(AppError::NotFound(format!("Contact {} of chat tool {}", contact_id, chat_tool_id)));
+        }
+        chat_tool_repo::snooze_contact(&state_clone, &contact_id, duration_minutes)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    if let Some(until) = contact.snoozed_until.clone() {
+        let contact_id = contact.id.clone();
+        let chat_tool_id = contact.chat_tool_id.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = state.shutdown_token.cancelled() => return,
+                _ = tokio::time::sleep(std::time::Duration::from_secs(duration_minutes as u64 * 60)) => {}
+            }
+            let state_clone = state.clone();
+            let id = contact_id.clone();
+            let ended = tokio::task::spawn_blocking(move || chat_tool_repo::end_contact_snooze(&state_clone, &id, &until)).await;
+            if let Ok(Ok(true)) = ended {
+                log::info!("[Bridge:{}] Snooze of contact {} ended, auto-replies resume", chat_tool_id, contact_id);
+                let _ = app.emit(
+                    "chat_tool:contact_snooze_ended",
+                    serde_json::json!({ "chatToolId": chat_tool_id, "contactId": contact_id }),
+                );
+            }
+        });
+    }
+    Ok(contact)
+}
+
+/// Start or end a human takeover of the conversation with a contact. While
+/// it runs the contact gets no auto-replies and the operator answers with
+/// `send_chat_tool_message`; ending it hands the transcript to the Control
+/// Hub in the background and emits `chat_tool:takeover_ended`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_chat_tool_takeover(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    chat_tool_id: String,
+    contact_id: String,
+    active: bool,
+) -> AppResult<ChatToolContact> {
+    let state = state.inner().clone();
+    let state_clone = state.clone();
+    let (chat_tool, before, after) = tokio::task::spawn_blocking(move || {
+        let chat_tool = chat_tool_repo::get_chat_tool(&state_clone, &chat_tool_id)?;
+        let before = chat_tool_repo::get_contact(&state_clone, &contact_id)?;
+        if before.chat_tool_id != chat_tool_id {
+            return Err(AppError::NotFound(format!("Contact {} of chat tool {}", contact_id, chat_tool_id)));
+        }
+        let after = chat_tool_repo::set_contact_takeover(&state_clone, &contact_id, active)?;
+        Ok((chat_tool, before, after))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    if let (false, Some(started_at)) = (active, before.takeover_started_at.clone()) {
+        takeover::spawn_hand_back(app, state, chat_tool, after.clone(), started_at);
+    }
+    Ok(after)
+}
+
+/// Write the conversation with a contact to `output/chat_tools/<chat_tool_id>/`
+/// as a Markdown, CSV or JSON transcript, optionally limited to messages with
+/// `created_at` in [since, until]. Returns the file path.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_chat_tool_history(
+    state: tauri::State<'_, AppState>,
+    chat_tool_id: String,
+    contact_id: String,
+    format: TranscriptFormat,
+    since: Option<String>,
+    until: Option<String>,
+) -> AppResult<String> {
+    let state = state.inner().clone();
+    let (chat_tool, contact, messages) = tokio::task::spawn_blocking(move || {
+        let chat_tool = chat_tool_repo::get_chat_tool(&state, &chat_tool_id)?;
+        let contact = chat_tool_repo::get_contact(&state, &contact_id)?;
+        if contact.chat_tool_id != chat_tool.id {
+            return Err(AppError::InvalidRequest(format!(
+                "Contact {} does not belong to chat tool {}",
+                contact_id, chat_tool_id
+            )));
+        }
+        let messages = chat_tool_repo::list_contact_messages(
+            &state,
+            &chat_tool.id,
+            &contact.external_id,
+            since.as_deref(),
+            until.as_deref(),
+        )?;
+        Ok((chat_tool, contact, messages))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    let content = transcript::render(format, &chat_tool, &contact, &messages)?;
+    let file_stem: String = contact
+        .external_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let dir = get_output_dir().join("chat_tools").join(&chat_tool.id);
+    tokio::fs::create_dir_all(&dir).await?;
+    let path = dir.join(format!(
+        "{}-{}.{}",
+        file_stem,
+        chrono::Utc::now().format("%Y%m%d%H%M%S"),
+        format.extension()
+    ));
+    tokio::fs::write(&path, content).await?;
+    Ok(path.to_string_lossy().to_string())
+}
@@ -0,0 +1,24 @@
+use tauri::Manager;
+
+use crate::db::connection::{self, DbStats};
+use crate::diagnostics;
+use crate::error::AppResult;
+use crate::models::diagnostics::DiagnosticReport;
+use crate::state::AppState;
+
+/// Check the app's health; `createBundle` also writes a redacted zip for bug reports.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn run_diagnostics(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    create_bundle: Option<bool>,
+) -> AppResult<DiagnosticReport> {
+    let log_dir = app.path().app_log_dir().ok();
+    diagnostics::run(state.inner(), create_bundle.unwrap_or(false), log_dir).await
+}
+
+/// Connection lock waits, busy retries and write queue counters since start.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_db_stats(state: tauri::State<'_, AppState>) -> AppResult<DbStats> {
+    Ok(connection::stats(state.inner()))
+}
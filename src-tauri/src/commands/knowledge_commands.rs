@@ -0,0 +1,73 @@
+use crate::config;
+use crate::db::knowledge_repo;
+use crate::error::{AppError, AppResult};
+use crate::knowledge;
+use crate::models::knowledge::{IngestReport, KnowledgeChunkMatch, KnowledgeContextConfig, KnowledgeDocument};
+use crate::state::AppState;
+
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// Ingest files or directories of .md, .txt and .pdf documents into a workspace's knowledge base.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn ingest_knowledge(
+    state: tauri::State<'_, AppState>,
+    workspace_id: String,
+    paths: Vec<String>,
+) -> AppResult<IngestReport> {
+    if paths.is_empty() {
+        return Err(AppError::InvalidRequest("No paths to ingest".into()));
+    }
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || knowledge::ingest_paths(&state, &workspace_id, &paths))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_knowledge_documents(
+    state: tauri::State<'_, AppState>,
+    workspace_id: String,
+) -> AppResult<Vec<KnowledgeDocument>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || knowledge_repo::list_documents(&state, &workspace_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Chunks of a workspace's knowledge base ranked by similarity to `query`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn search_knowledge(
+    state: tauri::State<'_, AppState>,
+    workspace_id: String,
+    query: String,
+    limit: Option<usize>,
+) -> AppResult<Vec<KnowledgeChunkMatch>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        knowledge::retrieve(&state, &workspace_id, &query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn delete_knowledge_document(state: tauri::State<'_, AppState>, id: String) -> AppResult<()> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || knowledge_repo::delete_document(&state, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn get_knowledge_config(state: tauri::State<'_, AppState>) -> AppResult<KnowledgeContextConfig> {
+    Ok(knowledge::context_config(state.inner()))
+}
+
+#[tauri::command]
+pub async fn set_knowledge_config(state: tauri::State<'_, AppState>, config: KnowledgeContextConfig) -> AppResult<()> {
+    let patch = serde_json::json!({ "knowledge_context": config });
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || config::update(&state, patch).map(|_| ()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
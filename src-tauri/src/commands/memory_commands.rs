@@ -0,0 +1,55 @@
+use crate::db::memory_repo;
+use crate::error::{AppError, AppResult};
+use crate::memory;
+use crate::models::memory::{Memory, MemoryMatch};
+use crate::state::AppState;
+
+const DEFAULT_LIST_LIMIT: i64 = 100;
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_memories(
+    state: tauri::State<'_, AppState>,
+    workspace_id: Option<String>,
+    limit: Option<i64>,
+) -> AppResult<Vec<Memory>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        memory_repo::list_memories(&state, workspace_id.as_deref(), limit.unwrap_or(DEFAULT_LIST_LIMIT))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Memories of a workspace ranked by similarity to `query`, as planning sees them.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn search_memories(
+    state: tauri::State<'_, AppState>,
+    workspace_id: Option<String>,
+    query: String,
+    limit: Option<usize>,
+) -> AppResult<Vec<MemoryMatch>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        memory::recall(&state, workspace_id.as_deref(), &query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn delete_memory(state: tauri::State<'_, AppState>, id: String) -> AppResult<()> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || memory_repo::delete_memory(&state, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Forget a workspace's memories, or every memory when no workspace is given.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn clear_memories(state: tauri::State<'_, AppState>, workspace_id: Option<String>) -> AppResult<usize> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || memory_repo::clear_memories(&state, workspace_id.as_deref()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
@@ -1,8 +1,18 @@
 pub mod acp_commands;
+pub mod activity_commands;
 pub mod agent_commands;
 pub mod chat_commands;
 pub mod chat_tool_commands;
+pub mod diagnostics_commands;
+pub mod knowledge_commands;
+pub mod memory_commands;
+pub mod onboarding_commands;
 pub mod orchestration_commands;
+pub mod pipeline_commands;
+pub mod pricing_commands;
+pub mod prompt_commands;
 pub mod session_commands;
 pub mod settings_commands;
+pub mod sync_commands;
+pub mod template_commands;
 pub mod workspace_commands;
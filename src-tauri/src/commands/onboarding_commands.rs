@@ -0,0 +1,24 @@
+use crate::error::{AppError, AppResult};
+use crate::models::onboarding::{BootstrapReport, RepoOnboardingProposal};
+use crate::onboarding;
+use crate::repo_onboarding;
+use crate::state::AppState;
+
+/// Check and set up what a first run needs; safe to call again.
+#[tauri::command]
+pub async fn bootstrap_environment(state: tauri::State<'_, AppState>) -> AppResult<BootstrapReport> {
+    onboarding::bootstrap(state.inner()).await
+}
+
+/// Create a workspace on an existing repository and propose agents for it.
+/// The proposed agents are not registered until the user confirms them.
+#[tauri::command]
+pub async fn create_workspace_from_repo(
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> AppResult<RepoOnboardingProposal> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || repo_onboarding::create_workspace_from_repo(&state, &path))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
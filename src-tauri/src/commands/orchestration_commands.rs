@@ -1,77 +1,165 @@
-use crate::acp::{orchestrator, skill_discovery};
-use crate::db::{agent_repo, settings_repo, task_run_repo};
+use crate::acp::{assignment_caps, inline_artifacts, orchestrator, permissions, run_context, run_changes, run_queue, skill_cache, skill_discovery, summary_stream, tool_payloads};
+use crate::calendar;
+use crate::config;
+use crate::db::{artifact_repo, assignment_event_repo, permission_policy_repo, pinned_output_repo, response_cache_repo, schedule_run_repo, task_run_repo};
 use crate::error::{AppError, AppResult};
-use crate::models::agent::AgentConfig;
-use crate::models::task_run::{CreateTaskRunRequest, ScheduleTaskRequest, TaskAssignment, TaskRun};
+use crate::models::bulk::{BulkItemResult, BulkResult};
+use crate::models::notification::NotificationTarget;
+use crate::redaction;
+use crate::run_environment;
+use crate::run_report;
+use crate::scheduler;
+use crate::startup_recovery;
+use crate::models::task_run::{
+    AssignmentOverrides, AssignmentTimeline, CancelReason, CancellationStats, CreateTaskRunRequest, EnvironmentChange, FileWriteReview, PinOutputRequest, PinnedOutput, QueuedRun, RunChanges, RunEnvironment, RunPriority, ScheduleRun, ScheduledTask, StartedTaskRun, ScheduleStats, ScheduleTaskRequest, TaskArtifact, TaskAssignment, TaskPlan, TaskRun, TaskRunSearch,
+};
 use crate::state::{AppState, ConfirmationAction};
-use tokio_util::sync::CancellationToken;
 
 #[tauri::command(rename_all = "camelCase")]
 pub async fn start_orchestration(
     app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
     request: CreateTaskRunRequest,
-) -> AppResult<TaskRun> {
-    // Verify control hub exists (workspace-scoped)
-    let hub: AgentConfig = {
+) -> AppResult<StartedTaskRun> {
+    orchestrator::start_task_run(&app, state.inner(), request).await
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn cancel_orchestration(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    reason: Option<CancelReason>,
+) -> AppResult<()> {
+    {
+        let mut tokens = state.active_task_runs.lock().await;
+        if let Some(token) = tokens.remove(&task_run_id) {
+            token.cancel();
+        }
+    }
+    run_queue::remove(&app, state.inner(), &task_run_id).await;
+
+    let state_clone = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        task_run_repo::cancel_task_runs(&state_clone, &[task_run_id], reason.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    Ok(())
+}
+
+/// Emergency stop: cancel every task run of the workspace that is in
+/// progress or waiting to start, with the reason recorded on each.
+/// Scheduled runs are left alone.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn cancel_all_running(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    workspace_id: Option<String>,
+    reason: Option<CancelReason>,
+) -> AppResult<BulkResult> {
+    let runs = {
         let state_clone = state.inner().clone();
-        let ws_id = request.workspace_id.clone();
-        tokio::task::spawn_blocking(move || agent_repo::get_control_hub(&state_clone, ws_id.as_deref()))
+        tokio::task::spawn_blocking(move || task_run_repo::list_task_runs(&state_clone, workspace_id.as_deref()))
             .await
             .map_err(|e| AppError::Internal(e.to_string()))??
-            .ok_or_else(|| AppError::Internal("No Control Hub agent configured for this workspace. Set an agent as Control Hub first.".into()))?
-    };
-
-    let task_run_id = uuid::Uuid::new_v4().to_string();
-    let title = if request.title.is_empty() {
-        request.user_prompt.chars().take(100).collect::<String>()
-    } else {
-        request.title.clone()
     };
-
-    // Create task run record
-    let task_run: TaskRun = {
-        let state_clone = state.inner().clone();
-        let trid = task_run_id.clone();
-        let t = title.clone();
-        let up = request.user_prompt.clone();
-        let hub_id = hub.id.clone();
-        let ws_id = request.workspace_id.clone();
-        tokio::task::spawn_blocking(move || {
-            task_run_repo::create_task_run(&state_clone, &trid, &t, &up, &hub_id, "pending", ws_id.as_deref())
+    let ids: Vec<String> = runs
+        .into_iter()
+        .filter(|run| match run.status.as_str() {
+            "analyzing" | "running" | "awaiting_confirmation" | "awaiting_plan_approval" => true,
+            "pending" | "waiting_for_workspace" => run.schedule_type == "none",
+            _ => false,
         })
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))??
-    };
+        .map(|run| run.id)
+        .collect();
 
-    // Create cancellation token
-    let cancel_token = CancellationToken::new();
     {
         let mut tokens = state.active_task_runs.lock().await;
-        tokens.insert(task_run_id.clone(), cancel_token);
+        for id in &ids {
+            if let Some(token) = tokens.remove(id) {
+                token.cancel();
+            }
+        }
+    }
+    for id in &ids {
+        run_queue::remove(&app, state.inner(), id).await;
     }
 
-    // Spawn orchestration in background
     let state_clone = state.inner().clone();
-    let trid = task_run_id.clone();
-    let prompt = request.user_prompt.clone();
-    let ws_id = request.workspace_id.clone();
-    tokio::spawn(async move {
-        orchestrator::run_orchestration(app, state_clone, trid, prompt, ws_id).await;
-    });
+    let ids_clone = ids.clone();
+    let updated = tokio::task::spawn_blocking(move || {
+        task_run_repo::cancel_task_runs(&state_clone, &ids_clone, reason.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let items = match updated {
+        Ok(()) => ids.iter().map(|id| BulkItemResult::ok(id)).collect(),
+        Err(e) => ids.iter().map(|id| BulkItemResult::failed(id, &e)).collect::<Vec<_>>(),
+    };
+    Ok(BulkResult::from(items))
+}
 
-    Ok(task_run)
+/// Task runs waiting for a free slot, in the order they will start.
+#[tauri::command]
+pub async fn list_queue(state: tauri::State<'_, AppState>) -> AppResult<Vec<QueuedRun>> {
+    Ok(state.run_queue.lock().await.list())
 }
 
+/// Move a waiting task run to `position` in the queue; 0 starts it next.
 #[tauri::command(rename_all = "camelCase")]
-pub async fn cancel_orchestration(
+pub async fn move_queued_run(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    position: usize,
+) -> AppResult<Vec<QueuedRun>> {
+    if !run_queue::move_to(&app, state.inner(), &task_run_id, position).await {
+        return Err(AppError::NotFound(format!("Task run {} is not queued", task_run_id)));
+    }
+    Ok(state.run_queue.lock().await.list())
+}
+
+/// Change the priority of a queued or running task run.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_run_priority(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    priority: RunPriority,
+) -> AppResult<Vec<QueuedRun>> {
+    if !run_queue::set_priority(&app, state.inner(), &task_run_id, priority).await {
+        return Err(AppError::NotFound(format!("Task run {} is not queued or running", task_run_id)));
+    }
+    Ok(state.run_queue.lock().await.list())
+}
+
+/// Let a task run waiting for its workspace start while another run holds it.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn override_workspace_lock(
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
     task_run_id: String,
 ) -> AppResult<()> {
-    let mut tokens = state.active_task_runs.lock().await;
-    if let Some(token) = tokens.remove(&task_run_id) {
-        token.cancel();
+    if !run_queue::ignore_lock(&app, state.inner(), &task_run_id).await {
+        return Err(AppError::NotFound(format!("Task run {} is not queued", task_run_id)));
+    }
+    Ok(())
+}
+
+/// Drop a waiting task run from the queue; it ends as cancelled.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn drop_queued_run(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+) -> AppResult<()> {
+    if !run_queue::remove(&app, state.inner(), &task_run_id).await {
+        return Err(AppError::NotFound(format!("Task run {} is not queued", task_run_id)));
     }
+    state.active_task_runs.lock().await.remove(&task_run_id);
 
     let state_clone = state.inner().clone();
     tokio::task::spawn_blocking(move || {
@@ -79,7 +167,6 @@ pub async fn cancel_orchestration(
     })
     .await
     .map_err(|e| AppError::Internal(e.to_string()))??;
-
     Ok(())
 }
 
@@ -94,6 +181,57 @@ pub async fn list_task_runs(
         .map_err(|e| AppError::Internal(e.to_string()))?
 }
 
+/// Task runs matching the search filters, archived ones included unless filtered out.
+#[tauri::command]
+pub async fn search_task_runs(
+    state: tauri::State<'_, AppState>,
+    search: TaskRunSearch,
+) -> AppResult<Vec<TaskRun>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || task_run_repo::search_task_runs(&state, &search))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Replace a task run's labels.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_task_run_labels(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    labels: Vec<String>,
+) -> AppResult<TaskRun> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || task_run_repo::set_task_run_labels(&state, &task_run_id, &labels))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_task_run_labels(
+    state: tauri::State<'_, AppState>,
+    workspace_id: Option<String>,
+) -> AppResult<Vec<String>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || task_run_repo::list_task_run_labels(&state, workspace_id.as_deref()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Archive or unarchive finished task runs. Archived runs stay searchable
+/// but are left out of `list_task_runs`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn archive_task_runs(
+    state: tauri::State<'_, AppState>,
+    task_run_ids: Vec<String>,
+    archived: bool,
+) -> AppResult<BulkResult> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || task_run_repo::set_task_runs_archived(&state, &task_run_ids, archived))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .map(BulkResult::from)
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub async fn get_task_run(
     state: tauri::State<'_, AppState>,
@@ -128,6 +266,33 @@ pub async fn get_task_assignments(
         .map_err(|e| AppError::Internal(e.to_string()))?
 }
 
+/// Re-execute an assignment of an ended run as a new assignment of that run.
+/// Returns the new assignment, which runs in the background.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn retry_assignment(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    assignment_id: String,
+) -> AppResult<TaskAssignment> {
+    orchestrator::retry_assignment(&app, state.inner(), &assignment_id).await
+}
+
+/// An assignment's recorded output chunks and tool calls, in order.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_assignment_timeline(
+    state: tauri::State<'_, AppState>,
+    assignment_id: String,
+) -> AppResult<AssignmentTimeline> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let assignment = task_run_repo::get_assignment(&state, &assignment_id)?;
+        let events = assignment_event_repo::list_events_for_assignment(&state, &assignment)?;
+        Ok(AssignmentTimeline { assignment, events })
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
 /// User confirms orchestration results — proceed to summary
 #[tauri::command(rename_all = "camelCase")]
 pub async fn confirm_orchestration(
@@ -146,6 +311,104 @@ pub async fn confirm_orchestration(
     }
 }
 
+/// User dismisses orchestration results — skip the summary and mark the run for review
+#[tauri::command(rename_all = "camelCase")]
+pub async fn dismiss_confirmation(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+) -> AppResult<()> {
+    let mut confirmations = state.pending_confirmations.lock().await;
+    if let Some(tx) = confirmations.remove(&task_run_id) {
+        let _ = tx.send(ConfirmationAction::Dismiss);
+        Ok(())
+    } else {
+        Err(AppError::NotFound(format!(
+            "No pending confirmation for task run {}",
+            task_run_id
+        )))
+    }
+}
+
+/// Approve (or reject) the plan of a task run that is awaiting plan approval.
+/// A rejected plan cancels the run.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn approve_plan(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    approved: bool,
+) -> AppResult<()> {
+    let mut approvals = state.pending_plan_approvals.lock().await;
+    if let Some(tx) = approvals.remove(&task_run_id) {
+        let _ = tx.send(approved);
+        Ok(())
+    } else {
+        Err(AppError::NotFound(format!(
+            "No pending plan approval for task run {}",
+            task_run_id
+        )))
+    }
+}
+
+/// Change the agent settings a planned assignment overrides while its plan
+/// awaits approval; `None` runs it with the agent's own settings.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_planned_overrides(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    assignment_index: usize,
+    overrides: Option<AssignmentOverrides>,
+) -> AppResult<TaskPlan> {
+    let overrides = overrides.filter(|o| !o.is_empty());
+    if let Some(overrides) = &overrides {
+        overrides.validate().map_err(AppError::InvalidRequest)?;
+    }
+    let state_clone = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let task_run = task_run_repo::get_task_run(&state_clone, &task_run_id)?;
+        if task_run.status != "awaiting_plan_approval" {
+            return Err(AppError::InvalidRequest("Overrides can only be edited while the plan awaits approval".into()));
+        }
+        let mut plan: TaskPlan = task_run
+            .task_plan_json
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()?
+            .ok_or_else(|| AppError::InvalidRequest(format!("Task run {} has no plan", task_run_id)))?;
+        let assignment = plan
+            .assignments
+            .get_mut(assignment_index)
+            .ok_or_else(|| AppError::NotFound(format!("Assignment {} of the plan", assignment_index)))?;
+        assignment.overrides = overrides;
+        task_run_repo::update_task_run_plan(&state_clone, &task_run_id, &serde_json::to_string(&plan)?)?;
+        Ok(plan)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Stop the summary of a task run being written: accepting keeps the text
+/// so far, otherwise the built-in summary is used. The run completes either way.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn stop_summary(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    accept: bool,
+) -> AppResult<()> {
+    summary_stream::stop(&state, &task_run_id, accept).await
+}
+
+/// When a pending confirmation times out (RFC 3339), if the run is awaiting one
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_confirmation_deadline(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+) -> AppResult<Option<String>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || task_run_repo::get_confirmation_deadline(&state, &task_run_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
 /// Rate a completed task run (1-5 stars)
 #[tauri::command(rename_all = "camelCase")]
 pub async fn rate_task_run(
@@ -187,7 +450,10 @@ pub async fn regenerate_agent(
     }
 }
 
-/// User responds to a permission request during orchestration
+/// User responds to a permission request during orchestration. The answer
+/// covers every request batched into the prompt; `remember` ("run" or
+/// "agent") also applies it to similar requests for the rest of the run, or
+/// from now on for the agent.
 #[tauri::command(rename_all = "camelCase")]
 pub async fn respond_orch_permission(
     state: tauri::State<'_, AppState>,
@@ -195,34 +461,153 @@ pub async fn respond_orch_permission(
     _agent_id: String,
     request_id: String,
     option_id: String,
+    remember: Option<String>,
 ) -> AppResult<()> {
-    let perm_key = (task_run_id.clone(), request_id.clone());
-    let mut perms = state.pending_orch_permissions.lock().await;
-    if let Some(tx) = perms.remove(&perm_key) {
-        let _ = tx.send(option_id);
+    if let Some(scope) = remember.as_deref().filter(|s| !matches!(*s, "run" | "agent")) {
+        return Err(AppError::InvalidRequest(format!("Unknown permission scope '{scope}'")));
+    }
+    let (pending, allow) = {
+        let mut perms = state.orch_permissions.lock().await;
+        let pending = perms.take(&task_run_id, &request_id).ok_or_else(|| {
+            AppError::NotFound(format!(
+                "No pending permission for task run {}, request {}",
+                task_run_id, request_id
+            ))
+        })?;
+        let allow = permissions::is_allow(&pending.waiters[0].options, &option_id);
+        if remember.as_deref() == Some("run") {
+            perms.remember(&task_run_id, &pending.request_key, allow);
+        }
+        (pending, allow)
+    };
+    if remember.as_deref() == Some("agent") {
+        let state_clone = state.inner().clone();
+        let (agent_id, request_key) = (pending.agent_id.clone(), pending.request_key.clone());
+        tokio::task::spawn_blocking(move || permission_policy_repo::set_policy(&state_clone, &agent_id, &request_key, allow))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+    }
+    for waiter in pending.waiters {
+        let _ = waiter.tx.send(permissions::option_for(&waiter.options, &option_id, allow));
+    }
+    Ok(())
+}
+
+/// Approve or reject a file write held for review
+#[tauri::command(rename_all = "camelCase")]
+pub async fn respond_file_write(
+    state: tauri::State<'_, AppState>,
+    write_id: String,
+    approved: bool,
+) -> AppResult<()> {
+    let mut pending = state.pending_file_writes.lock().await;
+    if let Some(write) = pending.remove(&write_id) {
+        let _ = write.tx.send(approved);
         Ok(())
     } else {
-        Err(AppError::NotFound(format!(
-            "No pending permission for task run {}, request {}",
-            task_run_id, request_id
-        )))
+        Err(AppError::NotFound(format!("No pending file write {}", write_id)))
     }
 }
 
+/// Approve or reject every file write of a task run that is awaiting review.
+/// Returns the number of writes resolved.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn respond_all_file_writes(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    approved: bool,
+) -> AppResult<usize> {
+    let mut pending = state.pending_file_writes.lock().await;
+    let write_ids: Vec<String> = pending
+        .iter()
+        .filter(|(_, w)| w.review.task_run_id == task_run_id)
+        .map(|(id, _)| id.clone())
+        .collect();
+    for id in &write_ids {
+        if let Some(write) = pending.remove(id) {
+            let _ = write.tx.send(approved);
+        }
+    }
+    Ok(write_ids.len())
+}
+
+/// List file writes awaiting review, optionally for one task run
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_pending_file_writes(
+    state: tauri::State<'_, AppState>,
+    task_run_id: Option<String>,
+) -> AppResult<Vec<FileWriteReview>> {
+    let pending = state.pending_file_writes.lock().await;
+    let mut reviews: Vec<FileWriteReview> = pending
+        .values()
+        .filter(|w| task_run_id.as_ref().map_or(true, |id| w.review.task_run_id == *id))
+        .map(|w| w.review.clone())
+        .collect();
+    reviews.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(reviews)
+}
+
 /// Cancel a single agent within an orchestration task run
 #[tauri::command(rename_all = "camelCase")]
 pub async fn cancel_agent(
     state: tauri::State<'_, AppState>,
     task_run_id: String,
     agent_id: String,
+    reason: Option<CancelReason>,
 ) -> AppResult<()> {
-    let agent_cancels = state.agent_cancellations.lock().await;
-    if let Some(token) = agent_cancels.get(&(task_run_id.clone(), agent_id.clone())) {
+    {
+        let agent_cancels = state.agent_cancellations.lock().await;
+        let Some(token) = agent_cancels.get(&(task_run_id.clone(), agent_id.clone())) else {
+            return Err(AppError::NotFound("No active agent".into()));
+        };
         token.cancel();
-        Ok(())
-    } else {
-        Err(AppError::NotFound("No active agent".into()))
     }
+    let state_clone = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        task_run_repo::set_agent_cancel_reason(&state_clone, &task_run_id, &agent_id, reason.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Cancelled runs and assignments per reason. Pass a workspace id to count
+/// only its runs.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_cancellation_stats(
+    state: tauri::State<'_, AppState>,
+    workspace_id: Option<String>,
+) -> AppResult<Vec<CancellationStats>> {
+    let state_clone = state.inner().clone();
+    tokio::task::spawn_blocking(move || task_run_repo::get_cancellation_stats(&state_clone, workspace_id.as_deref()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Change the output token and cost caps of a running assignment; None removes a cap
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_assignment_caps(
+    state: tauri::State<'_, AppState>,
+    assignment_id: String,
+    max_tokens_out: Option<i64>,
+    max_cost: Option<f64>,
+) -> AppResult<TaskAssignment> {
+    let caps = assignment_caps::AssignmentCaps { max_tokens_out, max_cost };
+    caps.validate().map_err(AppError::InvalidRequest)?;
+    let state_clone = state.inner().clone();
+    let id = assignment_id.clone();
+    let assignment = tokio::task::spawn_blocking(move || task_run_repo::get_assignment(&state_clone, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    if !assignment_caps::update(&state, &assignment.task_run_id, &assignment.agent_id, caps).await {
+        return Err(AppError::InvalidRequest("Caps can only be changed while the assignment runs".into()));
+    }
+    let state_clone = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        task_run_repo::set_assignment_caps(&state_clone, &assignment_id, max_tokens_out, max_cost)?;
+        task_run_repo::get_assignment(&state_clone, &assignment_id)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
 }
 
 // ============== Scheduling Commands ==============
@@ -289,6 +674,20 @@ pub async fn schedule_task(
         _ => unreachable!(),
     };
 
+    let failure_policy_json = match &request.failure_policy {
+        Some(policy) => {
+            for target in &policy.notify_on_failure {
+                if let NotificationTarget::Webhook { url } = target {
+                    if !url.starts_with("http://") && !url.starts_with("https://") {
+                        return Err(AppError::InvalidRequest(format!("Invalid webhook URL: {}", url)));
+                    }
+                }
+            }
+            Some(serde_json::to_string(policy)?)
+        }
+        None => None,
+    };
+
     // Update the task schedule
     let state_clone = state.inner().clone();
     let task_run_id = request.task_run_id.clone();
@@ -305,6 +704,7 @@ pub async fn schedule_task(
             st.as_deref(),
             rpj.as_deref(),
             nra.as_deref(),
+            failure_policy_json.as_deref(),
         )
     })
     .await
@@ -361,53 +761,358 @@ pub async fn clear_schedule(
     Ok(())
 }
 
-/// Discover skills from the skills/ directories in the workspace and global config.
-/// Results are cached; pass `force_refresh: true` to re-scan.
+/// List fired and skipped occurrences of scheduled tasks, newest first.
+/// Pass a task run id to restrict the history to one schedule.
 #[tauri::command(rename_all = "camelCase")]
-pub async fn discover_workspace_skills(
+pub async fn list_schedule_history(
     state: tauri::State<'_, AppState>,
-    force_refresh: Option<bool>,
-) -> AppResult<skill_discovery::SkillDiscoveryResult> {
-    let force = force_refresh.unwrap_or(false);
+    task_run_id: Option<String>,
+    limit: Option<i64>,
+) -> AppResult<Vec<ScheduleRun>> {
+    let state_clone = state.inner().clone();
+    let limit = limit.unwrap_or(50).clamp(1, 500);
+    tokio::task::spawn_blocking(move || {
+        schedule_run_repo::list_schedule_history(&state_clone, task_run_id.as_deref(), limit)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
 
-    // Resolve working directory
-    let cwd = if let Ok(Some(setting)) = settings_repo::get_setting(state.inner(), "working_directory") {
-        if !setting.value.is_empty() {
-            setting.value
-        } else {
-            std::env::current_dir()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_else(|_| ".".into())
+/// Success-rate statistics per schedule, least reliable first.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_schedule_stats(
+    state: tauri::State<'_, AppState>,
+    task_run_id: Option<String>,
+) -> AppResult<Vec<ScheduleStats>> {
+    let state_clone = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        schedule_run_repo::get_schedule_stats(&state_clone, task_run_id.as_deref())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Scheduled tasks with their next fire time, latest occurrence and owning
+/// workspace. Pass a workspace id to list only its schedules.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_scheduled_tasks(
+    state: tauri::State<'_, AppState>,
+    workspace_id: Option<String>,
+) -> AppResult<Vec<ScheduledTask>> {
+    scheduler::list_scheduled_tasks(state.inner(), workspace_id).await
+}
+
+/// Fire an occurrence of a scheduled task now instead of at its next run.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn trigger_schedule_now(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    schedule_id: String,
+) -> AppResult<()> {
+    scheduler::trigger_now(&app, state.inner(), &schedule_id).await
+}
+
+/// Runs left unfinished by the previous session that wait for the user to
+/// resume or fail them (the `prompt` startup recovery policy).
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_recoverable_runs(state: tauri::State<'_, AppState>) -> AppResult<Vec<TaskRun>> {
+    startup_recovery::list(state.inner()).await
+}
+
+/// Resume held unfinished runs, or with `resume` false mark them as failed.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn recover_task_runs(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    task_run_ids: Vec<String>,
+    resume: bool,
+) -> AppResult<BulkResult> {
+    Ok(BulkResult::from(startup_recovery::decide(&app, state.inner(), &task_run_ids, resume).await))
+}
+
+/// Export active schedules as an iCalendar feed. The .ics text is returned and,
+/// when `path` is given, also written to that file.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_schedules_ics(
+    state: tauri::State<'_, AppState>,
+    workspace_id: Option<String>,
+    path: Option<String>,
+) -> AppResult<String> {
+    let state_clone = state.inner().clone();
+    let runs = tokio::task::spawn_blocking(move || {
+        task_run_repo::list_task_runs(&state_clone, workspace_id.as_deref())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    let ics = calendar::build_schedule_calendar(&runs, chrono::Utc::now());
+    if let Some(path) = path {
+        tokio::fs::write(&path, &ics).await?;
+    }
+    Ok(ics)
+}
+
+/// Write a task run as a self-contained HTML report for sharing. Without
+/// `path` the user picks where to save it; returns the file written, or None
+/// when the user cancelled.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn publish_run_report(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    path: Option<String>,
+) -> AppResult<Option<String>> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let state_clone = state.inner().clone();
+    let id = task_run_id.clone();
+    let (run, assignments, environment, patterns) = tokio::task::spawn_blocking(move || -> AppResult<_> {
+        let run = task_run_repo::get_task_run(&state_clone, &id)?;
+        let assignments = task_run_repo::list_assignments_for_run(&state_clone, &id)?;
+        let environment = task_run_repo::get_run_environment(&state_clone, &id)?;
+        let patterns = redaction::for_workspace(&state_clone, run.workspace_id.as_deref());
+        Ok((run, assignments, environment, patterns))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            let file_name: String = run
+                .title
+                .chars()
+                .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+                .take(60)
+                .collect();
+            app.dialog()
+                .file()
+                .set_title("Save Run Report")
+                .set_file_name(format!("{}.html", file_name))
+                .add_filter("HTML", &["html"])
+                .save_file(move |file| {
+                    let _ = tx.send(file.map(|f| f.to_string()));
+                });
+            match rx.await.map_err(|_| AppError::Internal("Dialog channel closed".into()))? {
+                Some(path) => path,
+                None => return Ok(None),
+            }
         }
-    } else {
-        std::env::current_dir()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| ".".into())
     };
 
-    // Check cache
-    if !force {
-        let cache = state.discovered_skills.lock().await;
-        if let Some(ref cached) = *cache {
-            if cached.scanned_directories.iter().any(|d| d.contains(&cwd)) {
-                return Ok(cached.clone());
+    let plan = run.task_plan_json.as_deref().and_then(|json| serde_json::from_str(json).ok());
+    let redactor = run_report::Redactor::new(
+        dirs::home_dir().map(|h| h.to_string_lossy().to_string()),
+        &config::current(&state).report_redactions,
+        patterns,
+    );
+    let html = run_report::render(&run, plan.as_ref(), &assignments, environment.as_ref(), &redactor);
+    tokio::fs::write(&path, html).await?;
+    Ok(Some(path))
+}
+
+/// What a task run ran with; None for runs from before snapshots were kept.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_run_environment(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+) -> AppResult<Option<RunEnvironment>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || task_run_repo::get_run_environment(&state, &task_run_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// What changed in the environment from `task_run_id` to `other_task_run_id`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn compare_run_environments(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    other_task_run_id: String,
+) -> AppResult<Vec<EnvironmentChange>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let load = |id: &str| {
+            task_run_repo::get_run_environment(&state, id)?
+                .ok_or_else(|| AppError::InvalidRequest(format!("Run {} has no environment snapshot", id)))
+        };
+        Ok(run_environment::diff(&load(&task_run_id)?, &load(&other_task_run_id)?))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Pins an assignment output or A2A result to the run's clipboard.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn pin_output(
+    state: tauri::State<'_, AppState>,
+    request: PinOutputRequest,
+) -> AppResult<PinnedOutput> {
+    let state_clone = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let (source, agent_id, label, content) = match &request.assignment_id {
+            Some(assignment_id) => {
+                let assignment = task_run_repo::get_assignment(&state_clone, assignment_id)?;
+                if assignment.task_run_id != request.task_run_id {
+                    return Err(AppError::InvalidRequest("Assignment belongs to another run".into()));
+                }
+                let content = request.content.or(assignment.output_text).unwrap_or_default();
+                let label = request.label.unwrap_or(assignment.agent_name);
+                ("assignment", Some(assignment.agent_id), label, content)
             }
+            None => {
+                let content = request.content.unwrap_or_default();
+                let label = request.label.unwrap_or_else(|| "A2A result".into());
+                ("a2a", request.agent_id, label, content)
+            }
+        };
+        if content.trim().is_empty() {
+            return Err(AppError::InvalidRequest("Nothing to pin".into()));
         }
-    }
+        pinned_output_repo::create_pinned_output(
+            &state_clone,
+            &request.task_run_id,
+            source,
+            request.assignment_id.as_deref(),
+            agent_id.as_deref(),
+            &label,
+            &content,
+        )
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_pinned_outputs(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+) -> AppResult<Vec<PinnedOutput>> {
+    let state_clone = state.inner().clone();
+    tokio::task::spawn_blocking(move || pinned_output_repo::list_pinned_outputs(&state_clone, &task_run_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn unpin_output(state: tauri::State<'_, AppState>, id: String) -> AppResult<()> {
+    let state_clone = state.inner().clone();
+    tokio::task::spawn_blocking(move || pinned_output_repo::delete_pinned_output(&state_clone, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
 
-    // Run discovery (blocking I/O)
-    let cwd_clone = cwd.clone();
-    let result = tokio::task::spawn_blocking(move || {
-        skill_discovery::discover_skills(&cwd_clone)
+/// Writes a run's pinned outputs to a markdown file, asking for a path when
+/// none is given. Returns `None` when the dialog is cancelled.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_pinned_outputs(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    path: Option<String>,
+) -> AppResult<Option<String>> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let state_clone = state.inner().clone();
+    let id = task_run_id.clone();
+    let (run, pinned) = tokio::task::spawn_blocking(move || -> AppResult<_> {
+        let run = task_run_repo::get_task_run(&state_clone, &id)?;
+        let pinned = pinned_output_repo::list_pinned_outputs(&state_clone, &id)?;
+        Ok((run, pinned))
     })
     .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    .map_err(|e| AppError::Internal(e.to_string()))??;
 
-    // Cache the result
-    {
-        let mut cache = state.discovered_skills.lock().await;
-        *cache = Some(result.clone());
-    }
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            let file_name: String = run
+                .title
+                .chars()
+                .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+                .take(60)
+                .collect();
+            app.dialog()
+                .file()
+                .set_title("Export Pinned Outputs")
+                .set_file_name(format!("{}-pinned.md", file_name))
+                .add_filter("Markdown", &["md"])
+                .save_file(move |file| {
+                    let _ = tx.send(file.map(|f| f.to_string()));
+                });
+            match rx.await.map_err(|_| AppError::Internal("Dialog channel closed".into()))? {
+                Some(path) => path,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    let markdown = format!("# {}{}", run.title, run_context::pinned_section(&pinned));
+    tokio::fs::write(&path, markdown).await?;
+    Ok(Some(path))
+}
+
+/// Files a task run produced, such as assignment output files.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_task_artifacts(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+) -> AppResult<Vec<TaskArtifact>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || artifact_repo::list_artifacts_for_run(&state, &task_run_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
 
+/// Per-file code changes a run made through reviewed writes.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_run_changes(task_run_id: String) -> AppResult<RunChanges> {
+    let changes = tokio::task::spawn_blocking(move || run_changes::collect(&task_run_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    Ok(changes)
+}
+
+/// Full `rawInput` / `rawOutput` of a tool call whose event carried only a preview.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_tool_payload(
+    state: tauri::State<'_, AppState>,
+    artifact_id: String,
+) -> AppResult<serde_json::Value> {
+    tool_payloads::load(state.inner(), &artifact_id).await
+}
+
+/// An image artifact from an agent's output as a data URL, for previews.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_artifact_preview(
+    state: tauri::State<'_, AppState>,
+    artifact_id: String,
+) -> AppResult<String> {
+    inline_artifacts::preview(state.inner(), &artifact_id).await
+}
+
+/// Drop every cached agent response. Returns the number of entries removed.
+#[tauri::command]
+pub async fn clear_response_cache(state: tauri::State<'_, AppState>) -> AppResult<usize> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || response_cache_repo::clear(&state))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Discover skills from the skills/ directories in the workspace and global config.
+/// Results are cached per workspace until the skills change; pass
+/// `force_refresh: true` to re-scan anyway.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn discover_workspace_skills(
+    state: tauri::State<'_, AppState>,
+    workspace_id: Option<String>,
+    force_refresh: Option<bool>,
+) -> AppResult<skill_discovery::SkillDiscoveryResult> {
+    let force = force_refresh.unwrap_or(false);
+    let cwd = orchestrator::resolve_orchestrator_working_directory(state.inner(), workspace_id.as_deref());
+    let (result, _) = skill_cache::skills(state.inner(), workspace_id.as_deref(), &cwd, force).await?;
     Ok(result)
 }
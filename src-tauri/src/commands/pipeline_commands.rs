@@ -0,0 +1,192 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::acp::pipeline;
+use crate::db::{pipeline_repo, task_run_repo};
+use crate::error::{AppError, AppResult};
+use crate::models::pipeline::{CreatePipelineRequest, Pipeline, PipelineRun, UpdatePipelineRequest};
+use crate::state::AppState;
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_pipelines(
+    state: tauri::State<'_, AppState>,
+    workspace_id: Option<String>,
+) -> AppResult<Vec<Pipeline>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || pipeline_repo::list_pipelines(&state, workspace_id.as_deref()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn get_pipeline(state: tauri::State<'_, AppState>, id: String) -> AppResult<Pipeline> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || pipeline_repo::get_pipeline(&state, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn create_pipeline(
+    state: tauri::State<'_, AppState>,
+    request: CreatePipelineRequest,
+) -> AppResult<Pipeline> {
+    if request.stages.is_empty() {
+        return Err(AppError::InvalidRequest("A pipeline needs at least one stage".into()));
+    }
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || pipeline_repo::create_pipeline(&state, request))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn update_pipeline(
+    state: tauri::State<'_, AppState>,
+    id: String,
+    request: UpdatePipelineRequest,
+) -> AppResult<Pipeline> {
+    if matches!(&request.stages, Some(stages) if stages.is_empty()) {
+        return Err(AppError::InvalidRequest("A pipeline needs at least one stage".into()));
+    }
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || pipeline_repo::update_pipeline(&state, &id, request))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn delete_pipeline(state: tauri::State<'_, AppState>, id: String) -> AppResult<()> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || pipeline_repo::delete_pipeline(&state, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Start a pipeline run in the background. `input` is fed to the first stage.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn run_pipeline(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    pipeline_id: String,
+    input: Option<String>,
+) -> AppResult<PipelineRun> {
+    if state.shutdown_token.is_cancelled() {
+        return Err(AppError::InvalidRequest("Application is shutting down".into()));
+    }
+
+    let pipeline = {
+        let state_clone = state.inner().clone();
+        let pid = pipeline_id.clone();
+        tokio::task::spawn_blocking(move || pipeline_repo::get_pipeline(&state_clone, &pid))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??
+    };
+    if pipeline.stages().is_empty() {
+        return Err(AppError::InvalidRequest(format!("Pipeline {} has no stages", pipeline.name)));
+    }
+
+    let pipeline_run_id = uuid::Uuid::new_v4().to_string();
+    let input = input.unwrap_or_default();
+    let pipeline_run = {
+        let state_clone = state.inner().clone();
+        let prid = pipeline_run_id.clone();
+        let pid = pipeline_id.clone();
+        let inp = input.clone();
+        let ws_id = pipeline.workspace_id.clone();
+        tokio::task::spawn_blocking(move || {
+            pipeline_repo::create_pipeline_run(&state_clone, &prid, &pid, &inp, ws_id.as_deref())
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??
+    };
+
+    let cancel_token = CancellationToken::new();
+    {
+        let mut runs = state.active_pipeline_runs.lock().await;
+        runs.insert(pipeline_run_id.clone(), cancel_token.clone());
+    }
+
+    let state_clone = state.inner().clone();
+    let ws_id = pipeline.workspace_id.clone();
+    tokio::spawn(async move {
+        pipeline::run_pipeline(app, state_clone, pipeline_run_id, pipeline, input, ws_id, cancel_token).await;
+    });
+
+    Ok(pipeline_run)
+}
+
+/// Cancel a pipeline run, including the orchestration of its current stage.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn cancel_pipeline_run(
+    state: tauri::State<'_, AppState>,
+    pipeline_run_id: String,
+) -> AppResult<()> {
+    let token = {
+        let runs = state.active_pipeline_runs.lock().await;
+        runs.get(&pipeline_run_id).cloned()
+    };
+    let Some(token) = token else {
+        return Err(AppError::NotFound(format!("Pipeline run {} is not active", pipeline_run_id)));
+    };
+    token.cancel();
+
+    // Mark the current stage's task run as cancelled, as cancel_orchestration does
+    let state_clone = state.inner().clone();
+    let prid = pipeline_run_id.clone();
+    tokio::task::spawn_blocking(move || -> AppResult<()> {
+        let run = pipeline_repo::get_pipeline_run(&state_clone, &prid)?;
+        let stage_ids: Vec<String> = serde_json::from_str(&run.stage_task_run_ids_json).unwrap_or_default();
+        if let Some(current) = stage_ids.last() {
+            let task_run = task_run_repo::get_task_run(&state_clone, current)?;
+            if !matches!(task_run.status.as_str(), "completed" | "failed" | "cancelled") {
+                task_run_repo::update_task_run_status(&state_clone, current, "cancelled")?;
+            }
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    Ok(())
+}
+
+/// Approve (or reject) the stage a pipeline run is waiting on.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn approve_pipeline_stage(
+    state: tauri::State<'_, AppState>,
+    pipeline_run_id: String,
+    approved: bool,
+) -> AppResult<()> {
+    let mut approvals = state.pending_pipeline_approvals.lock().await;
+    if let Some(tx) = approvals.remove(&pipeline_run_id) {
+        let _ = tx.send(approved);
+        Ok(())
+    } else {
+        Err(AppError::NotFound(format!(
+            "No pending approval for pipeline run {}",
+            pipeline_run_id
+        )))
+    }
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_pipeline_runs(
+    state: tauri::State<'_, AppState>,
+    pipeline_id: String,
+) -> AppResult<Vec<PipelineRun>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || pipeline_repo::list_pipeline_runs(&state, &pipeline_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_pipeline_run(
+    state: tauri::State<'_, AppState>,
+    pipeline_run_id: String,
+) -> AppResult<PipelineRun> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || pipeline_repo::get_pipeline_run(&state, &pipeline_run_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
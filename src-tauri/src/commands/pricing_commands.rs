@@ -0,0 +1,36 @@
+use crate::config;
+use crate::db::task_run_repo;
+use crate::error::{AppError, AppResult};
+use crate::models::pricing::{ModelPricing, PricingTable, RunCostEstimate};
+use crate::state::AppState;
+
+/// The effective pricing table: built-in rates with user overrides applied.
+#[tauri::command]
+pub async fn get_model_pricing(state: tauri::State<'_, AppState>) -> AppResult<PricingTable> {
+    Ok(PricingTable::load(state.inner()))
+}
+
+/// Replace the user's pricing overrides. An empty list restores the built-in rates.
+#[tauri::command]
+pub async fn set_model_pricing_overrides(
+    state: tauri::State<'_, AppState>,
+    overrides: Vec<ModelPricing>,
+) -> AppResult<()> {
+    // Arrays are replaced as a whole by the merge patch
+    let patch = serde_json::json!({ "model_pricing_overrides": overrides });
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || config::update(&state, patch).map(|_| ()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn estimate_task_run_cost(state: tauri::State<'_, AppState>, task_run_id: String) -> AppResult<RunCostEstimate> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let assignments = task_run_repo::list_assignments_for_run(&state, &task_run_id)?;
+        Ok(PricingTable::load(&state).estimate_run(&task_run_id, &assignments))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
@@ -0,0 +1,77 @@
+use crate::db::prompt_repo;
+use crate::error::{AppError, AppResult};
+use crate::models::prompt::{CreatePromptRequest, Prompt, PromptRef, PromptVersion, UpdatePromptRequest};
+use crate::prompts;
+use crate::state::AppState;
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_prompts(
+    state: tauri::State<'_, AppState>,
+    workspace_id: Option<String>,
+    tag: Option<String>,
+) -> AppResult<Vec<Prompt>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || prompt_repo::list_prompts(&state, workspace_id.as_deref(), tag.as_deref()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn get_prompt(state: tauri::State<'_, AppState>, id: String) -> AppResult<Prompt> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || prompt_repo::get_prompt(&state, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_prompt_versions(
+    state: tauri::State<'_, AppState>,
+    prompt_id: String,
+) -> AppResult<Vec<PromptVersion>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || prompt_repo::list_versions(&state, &prompt_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn create_prompt(state: tauri::State<'_, AppState>, request: CreatePromptRequest) -> AppResult<Prompt> {
+    if request.name.trim().is_empty() {
+        return Err(AppError::InvalidRequest("Prompt name is required".into()));
+    }
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || prompt_repo::create_prompt(&state, &request))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Update a prompt. New content is saved as a new version.
+#[tauri::command]
+pub async fn update_prompt(
+    state: tauri::State<'_, AppState>,
+    id: String,
+    request: UpdatePromptRequest,
+) -> AppResult<Prompt> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || prompt_repo::update_prompt(&state, &id, &request))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn delete_prompt(state: tauri::State<'_, AppState>, id: String) -> AppResult<()> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || prompt_repo::delete_prompt(&state, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Render a prompt with its variables filled in, e.g. for a preview.
+#[tauri::command]
+pub async fn render_prompt(state: tauri::State<'_, AppState>, prompt: PromptRef) -> AppResult<String> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || prompts::resolve(&state, &prompt))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
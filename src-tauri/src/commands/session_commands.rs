@@ -1,5 +1,7 @@
+use crate::acp::session_summary;
 use crate::db::session_repo;
 use crate::error::AppResult;
+use crate::models::bulk::BulkResult;
 use crate::models::session::{CreateSessionRequest, Session};
 use crate::state::AppState;
 
@@ -49,3 +51,22 @@ pub async fn delete_session(
         .await
         .map_err(|e| crate::error::AppError::Internal(e.to_string()))?
 }
+
+#[tauri::command]
+pub async fn delete_sessions(
+    state: tauri::State<'_, AppState>,
+    ids: Vec<String>,
+) -> AppResult<BulkResult> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || session_repo::delete_sessions(&state, &ids))
+        .await
+        .map_err(|e| crate::error::AppError::Internal(e.to_string()))?
+        .map(BulkResult::from)
+}
+
+/// Ask the session's agent for a rolling summary of the conversation and
+/// store it on the session. The agent must be running.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn summarize_session(state: tauri::State<'_, AppState>, session_id: String) -> AppResult<Session> {
+    session_summary::summarize_session(state.inner(), &session_id).await
+}
@@ -1,9 +1,13 @@
 use std::collections::HashMap;
 
+use crate::config::{self, AppConfig};
 use crate::db::settings_repo;
 use crate::error::{AppError, AppResult};
+use crate::observer::{self, ObserverStatus};
 use crate::state::AppState;
 
+/// Flat key/value view of all settings. Typed settings appear under their
+/// legacy keys; prefer `get_app_config` for those.
 #[tauri::command]
 pub async fn get_settings(
     state: tauri::State<'_, AppState>,
@@ -11,20 +15,51 @@ pub async fn get_settings(
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || {
         let settings = settings_repo::get_all_settings(&state)?;
-        Ok(settings.into_iter().map(|s| (s.key, s.value)).collect())
+        let mut map: HashMap<String, String> = settings
+            .into_iter()
+            .filter(|s| s.key != config::APP_CONFIG_SETTING)
+            .map(|s| (s.key, s.value))
+            .collect();
+        map.extend(config::current(&state).legacy_values());
+        Ok(map)
     })
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?
 }
 
+/// Set one flat setting. Keys that belong to the typed schema update `AppConfig`.
 #[tauri::command]
 pub async fn update_settings(
     state: tauri::State<'_, AppState>,
     key: String,
     value: String,
 ) -> AppResult<()> {
+    if key == config::APP_CONFIG_SETTING {
+        return Err(AppError::InvalidRequest("Use update_app_config to change app_config".into()));
+    }
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || match AppConfig::field_for_legacy_key(&key) {
+        Some(field) => {
+            let patch = serde_json::json!({ field: config::legacy_value(&value) });
+            config::update(&state, patch).map(|_| ())
+        }
+        None => settings_repo::set_setting(&state, &key, &value),
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn get_app_config(state: tauri::State<'_, AppState>) -> AppResult<AppConfig> {
+    Ok(config::current(state.inner()))
+}
+
+/// Apply a JSON merge patch to the typed settings and return the result.
+/// `null` resets a field to its default.
+#[tauri::command]
+pub async fn update_app_config(state: tauri::State<'_, AppState>, patch: serde_json::Value) -> AppResult<AppConfig> {
     let state = state.inner().clone();
-    tokio::task::spawn_blocking(move || settings_repo::set_setting(&state, &key, &value))
+    tokio::task::spawn_blocking(move || config::update(&state, patch))
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?
 }
@@ -55,7 +90,7 @@ pub async fn select_working_directory(
         let state_clone = state.inner().clone();
         let path_clone = path.clone();
         tokio::task::spawn_blocking(move || {
-            settings_repo::set_setting(&state_clone, "working_directory", &path_clone)
+            config::update(&state_clone, serde_json::json!({ "working_directory": path_clone })).map(|_| ())
         })
         .await
         .map_err(|e| AppError::Internal(e.to_string()))??;
@@ -64,27 +99,42 @@ pub async fn select_working_directory(
     Ok(selected)
 }
 
+#[tauri::command]
+pub async fn get_observer_status() -> AppResult<ObserverStatus> {
+    Ok(observer::status())
+}
+
+/// Turn the `observer_mode` setting on or off. Callable while observing so
+/// the mode can be left again, unless the app was launched with `--observer`.
+#[tauri::command]
+pub async fn set_observer_mode(state: tauri::State<'_, AppState>, enabled: bool) -> AppResult<ObserverStatus> {
+    if !enabled && observer::status().forced {
+        return Err(AppError::ObserverMode(format!(
+            "leaving observer mode (launched with {})",
+            observer::LAUNCH_FLAG
+        )));
+    }
+    let state = state.inner().clone();
+    let config = tokio::task::spawn_blocking(move || config::update(&state, serde_json::json!({ "observer_mode": enabled })))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    observer::apply(&config);
+    Ok(observer::status())
+}
+
 /// Get the current working directory setting.
 #[tauri::command(rename_all = "camelCase")]
 pub async fn get_working_directory(
     state: tauri::State<'_, AppState>,
 ) -> AppResult<Option<String>> {
-    let state_clone = state.inner().clone();
-    tokio::task::spawn_blocking(move || {
-        let setting = settings_repo::get_setting(&state_clone, "working_directory")?;
-        Ok(setting.map(|s| s.value))
-    })
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?
+    Ok(config::current(state.inner()).working_directory)
 }
 
 /// Resolve the effective working directory.
 /// Returns the user-configured trusted directory, or falls back to current_dir().
 pub fn resolve_working_directory(state: &AppState) -> String {
-    if let Ok(Some(setting)) = settings_repo::get_setting(state, "working_directory") {
-        if !setting.value.is_empty() {
-            return setting.value;
-        }
+    if let Some(dir) = config::current(state).working_directory {
+        return dir;
     }
     std::env::current_dir()
         .map(|p| p.to_string_lossy().to_string())
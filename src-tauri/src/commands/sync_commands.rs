@@ -0,0 +1,49 @@
+use crate::error::{AppError, AppResult};
+use crate::models::sync::{SyncConfig, SyncReport};
+use crate::state::AppState;
+use crate::sync;
+
+/// The sync configuration without its passphrase, or None when sync is off.
+#[tauri::command]
+pub async fn get_sync_config(state: tauri::State<'_, AppState>) -> AppResult<Option<SyncConfig>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        Ok(sync::load_config(&state)?.map(|config| SyncConfig {
+            passphrase: String::new(),
+            ..config
+        }))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Save the sync configuration; None turns sync off. An empty passphrase keeps
+/// the stored one.
+#[tauri::command]
+pub async fn set_sync_config(state: tauri::State<'_, AppState>, config: Option<SyncConfig>) -> AppResult<()> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let config = match config {
+            Some(mut config) => {
+                if config.passphrase.is_empty() {
+                    config.passphrase = sync::load_config(&state)?
+                        .map(|existing| existing.passphrase)
+                        .unwrap_or_default();
+                }
+                if config.passphrase.is_empty() {
+                    return Err(AppError::InvalidRequest("A sync passphrase is required".into()));
+                }
+                Some(config)
+            }
+            None => None,
+        };
+        sync::save_config(&state, config)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn sync_now(state: tauri::State<'_, AppState>) -> AppResult<SyncReport> {
+    sync::sync_now(state.inner()).await
+}
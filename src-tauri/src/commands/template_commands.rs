@@ -0,0 +1,61 @@
+use crate::acp::templates;
+use crate::db::{task_run_repo, template_repo};
+use crate::error::{AppError, AppResult};
+use crate::models::task_run::StartedTaskRun;
+use crate::models::template::{OrchestrationTemplate, PromoteRunOptions, RunTemplateRequest};
+use crate::state::AppState;
+
+/// Freeze the validated plan of a completed run into a reusable template.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn promote_run_to_template(
+    state: tauri::State<'_, AppState>,
+    task_run_id: String,
+    options: Option<PromoteRunOptions>,
+) -> AppResult<OrchestrationTemplate> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let task_run = task_run_repo::get_task_run(&state, &task_run_id)?;
+        let template = templates::build_template(&task_run, options.unwrap_or_default())?;
+        template_repo::create_template(&state, &template)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_templates(
+    state: tauri::State<'_, AppState>,
+    workspace_id: Option<String>,
+) -> AppResult<Vec<OrchestrationTemplate>> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || template_repo::list_templates(&state, workspace_id.as_deref()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn get_template(state: tauri::State<'_, AppState>, id: String) -> AppResult<OrchestrationTemplate> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || template_repo::get_template(&state, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn delete_template(state: tauri::State<'_, AppState>, id: String) -> AppResult<()> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || template_repo::delete_template(&state, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Start an orchestration from a template. The template's plan is used as-is,
+/// so the control hub only confirms and summarizes.
+#[tauri::command]
+pub async fn run_template(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    request: RunTemplateRequest,
+) -> AppResult<StartedTaskRun> {
+    templates::start_template_run(&app, state.inner(), request).await
+}
@@ -1,7 +1,9 @@
-use crate::db::{settings_repo, workspace_repo};
+use crate::config;
+use crate::db::workspace_repo;
 use crate::error::{AppError, AppResult};
-use crate::models::workspace::{CreateWorkspaceRequest, UpdateWorkspaceRequest, Workspace};
+use crate::models::workspace::{CreateWorkspaceRequest, SandboxMode, UpdateWorkspaceRequest, Workspace};
 use crate::state::AppState;
+use crate::workspace_sandbox;
 
 #[tauri::command]
 pub async fn list_workspaces(state: tauri::State<'_, AppState>) -> AppResult<Vec<Workspace>> {
@@ -40,7 +42,27 @@ pub async fn delete_workspace(
     id: String,
 ) -> AppResult<()> {
     let state = state.inner().clone();
-    tokio::task::spawn_blocking(move || workspace_repo::delete_workspace(&state, &id))
+    tokio::task::spawn_blocking(move || {
+        let workspace = workspace_repo::get_workspace(&state, &id)?;
+        workspace_repo::delete_workspace(&state, &id)?;
+        workspace_sandbox::remove(&state, &workspace);
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Mirror a workspace on a copy or git worktree of its working directory,
+/// to try risky orchestrations without touching the project. With no mode
+/// a worktree is used when the directory is a clean git repository.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn clone_workspace_sandbox(
+    state: tauri::State<'_, AppState>,
+    workspace_id: String,
+    mode: Option<SandboxMode>,
+) -> AppResult<Workspace> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || workspace_sandbox::clone_workspace_sandbox(&state, &workspace_id, mode))
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?
 }
@@ -79,6 +101,9 @@ pub async fn select_workspace_directory(
                     name: None,
                     icon: None,
                     working_directory: Some(p),
+                    hub_persona: None,
+                    redact_outputs: None,
+                    exclusive_runs: None,
                 },
             )
         })
@@ -89,7 +114,7 @@ pub async fn select_workspace_directory(
         let state_clone = state.inner().clone();
         let p2 = path.clone();
         tokio::task::spawn_blocking(move || {
-            settings_repo::set_setting(&state_clone, "working_directory", &p2)
+            config::update(&state_clone, serde_json::json!({ "working_directory": p2 })).map(|_| ())
         })
         .await
         .map_err(|e| AppError::Internal(e.to_string()))??;
@@ -0,0 +1,495 @@
+//! Typed application settings.
+//!
+//! `AppConfig` is stored as one JSON document under the `app_config` setting
+//! and cached in `AppState::config`, a watch channel: readers take the current
+//! value without touching the database, background tasks can `subscribe` to
+//! changes, and the frontend receives `settings:changed`. Updates are JSON
+//! merge patches applied while holding the channel's write lock, so concurrent
+//! updates of different fields never overwrite each other.
+//!
+//! On first start the flat legacy keys (`theme`, `fontSize`, `review_writes`,
+//! ...) are folded into the document and removed. Keys outside the schema stay
+//! plain key/value settings.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Emitter;
+
+use crate::acp::web_search::WebSearchConfig;
+use crate::chaos::ChaosConfig;
+use crate::chat_tool::keywords::CommandKeywords;
+use crate::chat_tool::spam_filter::SpamFilterConfig;
+use crate::redaction::RedactionConfig;
+use crate::db::connection::DatabaseConfig;
+use crate::db::settings_repo;
+use crate::error::{AppError, AppResult};
+use crate::models::knowledge::KnowledgeContextConfig;
+use crate::models::notification::{NotificationTarget, StuckRunReminderConfig};
+use crate::models::pricing::{normalize_model, ModelPricing};
+use crate::state::AppState;
+use crate::telemetry::TelemetryConfig;
+
+pub const APP_CONFIG_SETTING: &str = "app_config";
+pub const SCHEMA_VERSION: u32 = 1;
+pub const CONFIG_CHANGED_EVENT: &str = "settings:changed";
+
+/// Legacy setting key -> `AppConfig` field
+const LEGACY_KEYS: &[(&str, &str)] = &[
+    ("theme", "theme"),
+    ("language", "language"),
+    ("fontSize", "font_size"),
+    ("working_directory", "working_directory"),
+    ("active_workspace_id", "active_workspace_id"),
+    ("review_writes", "review_writes"),
+    ("file_conflict_mode", "file_conflict_mode"),
+    ("response_cache_ttl_minutes", "response_cache_ttl_minutes"),
+    ("memory_enabled", "memory_enabled"),
+    ("knowledge_context", "knowledge_context"),
+    ("model_pricing_overrides", "model_pricing_overrides"),
+    ("chat_tool_command_keywords", "chat_tool_command_keywords"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub schema_version: u32,
+    /// "dark" or "light"
+    pub theme: String,
+    pub language: String,
+    pub font_size: u32,
+    /// Default working directory when no workspace sets one
+    pub working_directory: Option<String>,
+    pub active_workspace_id: Option<String>,
+    /// Hold agent file writes for user review before applying them
+    pub review_writes: bool,
+    /// "warn" reports conflicting parallel writes, "serialize" also queues them
+    pub file_conflict_mode: String,
+    /// Response cache lifetime; 0 disables the cache
+    pub response_cache_ttl_minutes: i64,
+    /// How long a finished run waits for the user to confirm its results
+    pub confirmation_timeout_minutes: u64,
+    /// On confirmation timeout: "confirm" summarizes, "pause" stops the run's
+    /// agents and keeps waiting, "fail" fails the run
+    pub confirmation_timeout_action: String,
+    /// Keep an isolated run's scratch directory after it ends instead of deleting it
+    pub keep_run_scratch: bool,
+    /// Task runs started from the intake queue at once; 0 means no limit
+    pub max_concurrent_runs: usize,
+    /// When every slot is taken and a run of a higher priority waits, pause
+    /// the lowest-priority active run at its next sequence group until a
+    /// slot is free again
+    pub preempt_runs: bool,
+    /// Let the control hub revise the rest of a run's plan when an assignment fails
+    pub replan_on_failure: bool,
+    /// A start repeating the prompt of a run started this many seconds ago
+    /// that is still active returns that run; 0 turns the check off
+    pub duplicate_run_window_secs: u64,
+    /// A hub plan with an assignment less confident than this waits for the
+    /// user's approval instead of running; 0 never asks
+    pub plan_approval_confidence: f64,
+    /// Wind a run down after this many minutes of wall-clock time: no new
+    /// assignments start, running agents wrap up and the run is summarized
+    /// as timed out. Runs may set their own limit; 0 sets none
+    pub max_run_minutes: u64,
+    /// Runs the previous session left unfinished: "auto_resume" resumes
+    /// them on launch, "prompt" waits for the user to resume or fail each
+    /// one, "mark_failed" fails them
+    pub startup_recovery: String,
+    pub stuck_run_reminder: StuckRunReminderConfig,
+    pub memory_enabled: bool,
+    /// Summarize a chat session after this many new messages; 0 only
+    /// summarizes on request
+    pub session_summary_every_messages: usize,
+    /// Give agent processes a terminal whose prompts, e.g. of a login flow,
+    /// the user can answer
+    pub agent_terminal_attach: bool,
+    /// Command that logs in an agent type (registry id or command name), over
+    /// the built-in ones; words are separated by whitespace
+    pub agent_login_commands: HashMap<String, String>,
+    /// Stop an agent's chat process after this many minutes without prompts;
+    /// agents may override it, 0 keeps processes running
+    pub agent_idle_shutdown_minutes: u64,
+    /// Terms masked in shared run reports, besides credentials and the home directory
+    pub report_redactions: Vec<String>,
+    /// Secrets masked in agent outputs of workspaces that redact them
+    pub redaction: RedactionConfig,
+    /// Web search MCP tool given to agents without built-in browsing
+    pub web_search: WebSearchConfig,
+    pub knowledge_context: KnowledgeContextConfig,
+    pub model_pricing_overrides: Vec<ModelPricing>,
+    /// Context window in tokens per model id, over the built-in table
+    pub model_context_windows: HashMap<String, i64>,
+    /// Chat tool keyword commands; an empty keyword disables the command
+    pub chat_tool_command_keywords: CommandKeywords,
+    /// Filtering of incoming chat tool messages before auto-reply
+    pub chat_tool_spam_filter: SpamFilterConfig,
+    pub telemetry: TelemetryConfig,
+    /// SQLite busy handling and write batching
+    pub database: DatabaseConfig,
+    /// Fault injection; only honoured in builds with the `chaos` feature
+    pub chaos: ChaosConfig,
+    /// Disable commands that change anything, for demo or shared-dashboard machines
+    pub observer_mode: bool,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            theme: "dark".into(),
+            language: "en".into(),
+            font_size: 14,
+            working_directory: None,
+            active_workspace_id: None,
+            review_writes: false,
+            file_conflict_mode: "warn".into(),
+            response_cache_ttl_minutes: 0,
+            confirmation_timeout_minutes: 60,
+            confirmation_timeout_action: "confirm".into(),
+            keep_run_scratch: false,
+            max_concurrent_runs: 3,
+            preempt_runs: false,
+            replan_on_failure: false,
+            duplicate_run_window_secs: 10,
+            plan_approval_confidence: 0.5,
+            max_run_minutes: 0,
+            startup_recovery: "auto_resume".into(),
+            stuck_run_reminder: StuckRunReminderConfig::default(),
+            memory_enabled: true,
+            session_summary_every_messages: 20,
+            agent_terminal_attach: true,
+            agent_login_commands: HashMap::new(),
+            agent_idle_shutdown_minutes: 30,
+            report_redactions: Vec::new(),
+            redaction: RedactionConfig::default(),
+            web_search: WebSearchConfig::default(),
+            knowledge_context: KnowledgeContextConfig::default(),
+            model_pricing_overrides: Vec::new(),
+            model_context_windows: HashMap::new(),
+            chat_tool_command_keywords: CommandKeywords::default(),
+            chat_tool_spam_filter: SpamFilterConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            database: DatabaseConfig::default(),
+            chaos: ChaosConfig::default(),
+            observer_mode: false,
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn validate(&self) -> AppResult<()> {
+        let invalid = |message: String| Err(AppError::InvalidRequest(message));
+        if !matches!(self.theme.as_str(), "dark" | "light") {
+            return invalid(format!("Unknown theme '{}'", self.theme));
+        }
+        if !(8..=32).contains(&self.font_size) {
+            return invalid(format!("Font size {} is outside 8-32", self.font_size));
+        }
+        if !matches!(self.file_conflict_mode.as_str(), "warn" | "serialize") {
+            return invalid(format!("Unknown file conflict mode '{}'", self.file_conflict_mode));
+        }
+        if self.response_cache_ttl_minutes < 0 {
+            return invalid("Response cache TTL cannot be negative".into());
+        }
+        if !(1..=10_080).contains(&self.confirmation_timeout_minutes) {
+            return invalid("Confirmation timeout must be between 1 minute and 7 days".into());
+        }
+        if !matches!(self.confirmation_timeout_action.as_str(), "confirm" | "pause" | "fail") {
+            return invalid(format!("Unknown confirmation timeout action '{}'", self.confirmation_timeout_action));
+        }
+        if self.max_concurrent_runs > 32 {
+            return invalid("At most 32 task runs can run at once".into());
+        }
+        if self.duplicate_run_window_secs > 3_600 {
+            return invalid("Duplicate run window cannot exceed one hour".into());
+        }
+        if !(0.0..=1.0).contains(&self.plan_approval_confidence) {
+            return invalid(format!("Plan approval confidence {} is outside 0-1", self.plan_approval_confidence));
+        }
+        if self.session_summary_every_messages > 1_000 {
+            return invalid("Sessions are summarized at least every 1000 messages".into());
+        }
+        if self.stuck_run_reminder.after_minutes == 0 {
+            return invalid("Stuck run reminder delay must be at least 1 minute".into());
+        }
+        for target in &self.stuck_run_reminder.targets {
+            if let NotificationTarget::Webhook { url } = target {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return invalid(format!("Invalid webhook URL: {}", url));
+                }
+            }
+        }
+        if !(1..=20).contains(&self.knowledge_context.top_k) {
+            return invalid("Knowledge top_k must be between 1 and 20".into());
+        }
+        if let Some(endpoint) = &self.telemetry.otlp_endpoint {
+            if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+                return invalid(format!("OTLP endpoint '{}' must be an http(s) URL", endpoint));
+            }
+        }
+        for (name, p) in self.chaos.probabilities() {
+            if !(0.0..=1.0).contains(&p) {
+                return invalid(format!("Chaos {} must be between 0 and 1", name));
+            }
+        }
+        for entry in &self.model_pricing_overrides {
+            if normalize_model(&entry.model).is_empty() {
+                return invalid("Pricing entry is missing a model".into());
+            }
+            let rates = [
+                Some(entry.input_per_mtok),
+                Some(entry.output_per_mtok),
+                entry.cache_write_per_mtok,
+                entry.cache_read_per_mtok,
+            ];
+            if rates.iter().flatten().any(|r| !r.is_finite() || *r < 0.0) {
+                return invalid(format!("Invalid rate for model '{}'", entry.model));
+            }
+        }
+        if crate::startup_recovery::RecoveryPolicy::parse(&self.startup_recovery).is_none() {
+            return invalid(format!("Unknown startup recovery policy '{}'", self.startup_recovery));
+        }
+        if self.max_run_minutes > 10_080 {
+            return invalid("Runs are limited to at most 7 days".into());
+        }
+        if self.agent_idle_shutdown_minutes > 10_080 {
+            return invalid("Idle agents are stopped after at most 7 days".into());
+        }
+        for (agent_type, command) in &self.agent_login_commands {
+            if command.trim().is_empty() {
+                return invalid(format!("Login command of agent type '{}' is empty", agent_type));
+            }
+        }
+        for (model, window) in &self.model_context_windows {
+            if normalize_model(model).is_empty() {
+                return invalid("Context window entry is missing a model".into());
+            }
+            if *window < 1 {
+                return invalid(format!("Context window of model '{}' must be at least 1 token", model));
+            }
+        }
+        self.redaction.validate()?;
+        self.database.validate()?;
+        self.web_search.validate()
+    }
+
+    /// Field name for a legacy flat setting key, if it is part of the schema.
+    pub fn field_for_legacy_key(key: &str) -> Option<&'static str> {
+        LEGACY_KEYS.iter().find(|(legacy, _)| *legacy == key).map(|(_, field)| *field)
+    }
+
+    /// The schema fields as flat legacy key/value strings.
+    pub fn legacy_values(&self) -> Vec<(String, String)> {
+        let Ok(Value::Object(fields)) = serde_json::to_value(self) else {
+            return Vec::new();
+        };
+        LEGACY_KEYS
+            .iter()
+            .filter_map(|(legacy, field)| {
+                let value = match fields.get(*field)? {
+                    Value::Null => return None,
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                Some((legacy.to_string(), value))
+            })
+            .collect()
+    }
+}
+
+/// A flat setting string as JSON: booleans, numbers, objects and arrays are
+/// parsed, anything else is kept as a string.
+pub fn legacy_value(raw: &str) -> Value {
+    match serde_json::from_str::<Value>(raw) {
+        Ok(Value::String(_)) | Err(_) => Value::String(raw.to_string()),
+        Ok(value) => value,
+    }
+}
+
+/// RFC 7386 merge: objects merge recursively, `null` resets a field to its
+/// default, anything else replaces.
+fn merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                if value.is_null() {
+                    target.remove(&key);
+                } else {
+                    merge(target.entry(key).or_insert(Value::Null), value);
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+fn apply_patch(config: &AppConfig, patch: Value) -> AppResult<AppConfig> {
+    if !patch.is_object() {
+        return Err(AppError::InvalidRequest("Settings patch must be a JSON object".into()));
+    }
+    let mut value = serde_json::to_value(config)?;
+    merge(&mut value, patch);
+    let mut next: AppConfig = serde_json::from_value(value)
+        .map_err(|e| AppError::InvalidRequest(format!("Invalid settings: {}", e)))?;
+    next.schema_version = SCHEMA_VERSION;
+    if next.working_directory.as_deref().is_some_and(|d| d.trim().is_empty()) {
+        next.working_directory = None;
+    }
+    if next.telemetry.otlp_endpoint.as_deref().is_some_and(|e| e.trim().is_empty()) {
+        next.telemetry.otlp_endpoint = None;
+    }
+    Ok(next)
+}
+
+/// Build the config from legacy flat keys; values that do not fit the schema
+/// are dropped with a warning.
+fn from_legacy(state: &AppState) -> AppResult<AppConfig> {
+    let mut config = AppConfig::default();
+    for setting in settings_repo::get_all_settings(state)? {
+        let Some(field) = AppConfig::field_for_legacy_key(&setting.key) else {
+            continue;
+        };
+        let patch = serde_json::json!({ field: legacy_value(&setting.value) });
+        match apply_patch(&config, patch).and_then(|next| next.validate().map(|_| next)) {
+            Ok(next) => config = next,
+            Err(e) => log::warn!("[Config] Dropping legacy setting '{}': {}", setting.key, e),
+        }
+    }
+    Ok(config)
+}
+
+/// Rebuild a stored document that no longer parses, keeping every top-level
+/// field that still fits the schema.
+fn salvage(raw: &str) -> AppConfig {
+    let mut config = AppConfig::default();
+    let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(raw) else {
+        return config;
+    };
+    for (field, value) in fields {
+        let patch = serde_json::json!({ field.clone(): value });
+        match apply_patch(&config, patch).and_then(|next| next.validate().map(|_| next)) {
+            Ok(next) => config = next,
+            Err(e) => log::warn!("[Config] Dropping unreadable setting '{}': {}", field, e),
+        }
+    }
+    config
+}
+
+/// Keep an unreadable `app_config` under a backup key before replacing it
+/// with what could be salvaged, so no setting is lost without a trace.
+fn recover_unreadable(state: &AppState, raw: &str, error: serde_json::Error) -> AppResult<AppConfig> {
+    let backup_key = format!("{}_unreadable_{}", APP_CONFIG_SETTING, chrono::Utc::now().format("%Y%m%dT%H%M%S"));
+    settings_repo::set_setting(state, &backup_key, raw)?;
+    log::error!(
+        "[Config] Stored settings are unreadable ({}); saved them as '{}' and kept the fields that still parse",
+        error, backup_key
+    );
+    let config = salvage(raw);
+    settings_repo::set_setting(state, APP_CONFIG_SETTING, &serde_json::to_string(&config)?)?;
+    Ok(config)
+}
+
+fn load(state: &AppState) -> AppResult<AppConfig> {
+    if let Some(setting) = settings_repo::get_setting(state, APP_CONFIG_SETTING)? {
+        return match serde_json::from_str(&setting.value) {
+            Ok(config) => Ok(config),
+            Err(e) => recover_unreadable(state, &setting.value, e),
+        };
+    }
+    let config = from_legacy(state)?;
+    settings_repo::set_setting(state, APP_CONFIG_SETTING, &serde_json::to_string(&config)?)?;
+    for (legacy, _) in LEGACY_KEYS {
+        settings_repo::delete_setting(state, legacy)?;
+    }
+    log::info!("[Config] Migrated legacy settings into {}", APP_CONFIG_SETTING);
+    Ok(config)
+}
+
+/// Load the stored config into `state`, migrating legacy keys on first start.
+pub fn init(state: &AppState) {
+    match load(state) {
+        Ok(config) => {
+            state.config.send_replace(config);
+        }
+        Err(e) => log::error!("[Config] Failed to load settings, using defaults: {}", e),
+    }
+}
+
+pub fn current(state: &AppState) -> AppConfig {
+    state.config.borrow().clone()
+}
+
+pub fn subscribe(state: &AppState) -> tokio::sync::watch::Receiver<AppConfig> {
+    state.config.subscribe()
+}
+
+/// Merge `patch` into the config, validate and persist it. Blocking.
+pub fn update(state: &AppState, patch: Value) -> AppResult<AppConfig> {
+    let mut result = Err(AppError::Internal("Settings update did not run".into()));
+    state.config.send_if_modified(|config| {
+        let next = match apply_patch(config, patch).and_then(|next| next.validate().map(|_| next)) {
+            Ok(next) => next,
+            Err(e) => {
+                result = Err(e);
+                return false;
+            }
+        };
+        let stored = serde_json::to_string(&next)
+            .map_err(AppError::from)
+            .and_then(|json| settings_repo::set_setting(state, APP_CONFIG_SETTING, &json));
+        if let Err(e) = stored {
+            result = Err(e);
+            return false;
+        }
+        *config = next.clone();
+        result = Ok(next);
+        true
+    });
+    result
+}
+
+/// Forward config changes to the frontend as `settings:changed`.
+pub async fn emit_changes(app: tauri::AppHandle, state: AppState) {
+    let mut changes = subscribe(&state);
+    while changes.changed().await.is_ok() {
+        let config = changes.borrow_and_update().clone();
+        if let Err(e) = app.emit(CONFIG_CHANGED_EVENT, &config) {
+            log::warn!("[Config] Failed to emit settings change: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patches_merge_and_legacy_values_parse() {
+        let config = AppConfig::default();
+        let next = apply_patch(
+            &config,
+            serde_json::json!({ "font_size": legacy_value("16"), "knowledge_context": { "planner": true } }),
+        )
+        .unwrap();
+        assert_eq!(next.font_size, 16);
+        assert!(next.knowledge_context.planner);
+        assert_eq!(next.knowledge_context.top_k, config.knowledge_context.top_k);
+        assert_eq!(legacy_value("dark"), Value::String("dark".into()));
+        assert_eq!(legacy_value("false"), Value::Bool(false));
+
+        let reset = apply_patch(&next, serde_json::json!({ "font_size": null })).unwrap();
+        assert_eq!(reset.font_size, 14);
+        assert!(apply_patch(&config, serde_json::json!({ "font_size": "big" })).is_err());
+    }
+
+    #[test]
+    fn salvage_keeps_fields_that_still_parse() {
+        let config = salvage(r#"{ "font_size": 18, "knowledge_context": "on", "theme": "light" }"#);
+        assert_eq!(config.font_size, 18);
+        assert_eq!(config.theme, "light");
+        assert_eq!(config.knowledge_context.top_k, AppConfig::default().knowledge_context.top_k);
+        assert_eq!(salvage("not json").font_size, AppConfig::default().font_size);
+    }
+}
@@ -0,0 +1,99 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::activity::{ActivityEntry, ActivityQuery};
+use crate::state::AppState;
+
+const ACTIVITY_COLS: &str = "id, workspace_id, kind, subject_id, summary, created_at";
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<ActivityEntry> {
+    Ok(ActivityEntry {
+        id: row.get(0)?,
+        workspace_id: row.get(1)?,
+        kind: row.get(2)?,
+        subject_id: row.get(3)?,
+        summary: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+pub fn record_activity(
+    state: &AppState,
+    workspace_id: Option<&str>,
+    kind: &str,
+    subject_id: Option<&str>,
+    summary: &str,
+) -> AppResult<ActivityEntry> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO activity_log (id, workspace_id, kind, subject_id, summary) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![id, workspace_id, kind, subject_id, summary],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+
+    db.query_row(
+        &format!("SELECT {ACTIVITY_COLS} FROM activity_log WHERE id = ?1"),
+        params![id],
+        row_to_entry,
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+pub fn list_activity(state: &AppState, query: &ActivityQuery) -> AppResult<Vec<ActivityEntry>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+
+    let mut conditions: Vec<String> = Vec::new();
+    let mut params_vec: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    if let Some(ws_id) = &query.workspace_id {
+        params_vec.push(Box::new(ws_id.clone()));
+        conditions.push(format!("workspace_id = ?{}", params_vec.len()));
+    }
+    if !query.kinds.is_empty() {
+        let mut placeholders = Vec::new();
+        for kind in &query.kinds {
+            params_vec.push(Box::new(kind.clone()));
+            placeholders.push(format!("?{}", params_vec.len()));
+        }
+        conditions.push(format!("kind IN ({})", placeholders.join(", ")));
+    }
+    if let Some(subject_id) = &query.subject_id {
+        params_vec.push(Box::new(subject_id.clone()));
+        conditions.push(format!("subject_id = ?{}", params_vec.len()));
+    }
+    if let Some(since) = &query.since {
+        params_vec.push(Box::new(since.clone()));
+        conditions.push(format!("created_at >= ?{}", params_vec.len()));
+    }
+    if let Some(until) = &query.until {
+        params_vec.push(Box::new(until.clone()));
+        conditions.push(format!("created_at <= ?{}", params_vec.len()));
+    }
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    params_vec.push(Box::new(query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)));
+    let limit_idx = params_vec.len();
+    params_vec.push(Box::new(query.offset.unwrap_or(0).max(0)));
+    let offset_idx = params_vec.len();
+
+    let sql = format!(
+        "SELECT {ACTIVITY_COLS} FROM activity_log {where_clause} \
+         ORDER BY created_at DESC, rowid DESC LIMIT ?{limit_idx} OFFSET ?{offset_idx}"
+    );
+    let mut stmt = db.prepare(&sql).map_err(|e| AppError::Database(e.to_string()))?;
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let entries = stmt
+        .query_map(params_refs.as_slice(), row_to_entry)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(entries)
+}
@@ -0,0 +1,108 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::agent::AgentContext;
+use crate::state::AppState;
+
+/// Maximum length of the rolling summary kept per agent/workspace.
+const MAX_SUMMARY_CHARS: usize = 4000;
+
+const CONTEXT_COLS: &str = "agent_id, workspace_id, acp_session_id, summary, run_count, last_task_run_id, created_at, updated_at";
+
+fn row_to_context(row: &rusqlite::Row) -> rusqlite::Result<AgentContext> {
+    Ok(AgentContext {
+        agent_id: row.get(0)?,
+        workspace_id: row.get(1)?,
+        acp_session_id: row.get(2)?,
+        summary: row.get(3)?,
+        run_count: row.get(4)?,
+        last_task_run_id: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+pub fn get_agent_context(
+    state: &AppState,
+    agent_id: &str,
+    workspace_id: Option<&str>,
+) -> AppResult<Option<AgentContext>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let result = db.query_row(
+        &format!("SELECT {CONTEXT_COLS} FROM agent_contexts WHERE agent_id = ?1 AND workspace_id = ?2"),
+        params![agent_id, workspace_id.unwrap_or("")],
+        row_to_context,
+    );
+
+    match result {
+        Ok(ctx) => Ok(Some(ctx)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+/// Record the outcome of a run: remember the session and append to the summary.
+pub fn save_agent_context(
+    state: &AppState,
+    agent_id: &str,
+    workspace_id: Option<&str>,
+    acp_session_id: &str,
+    task_run_id: &str,
+    prompt: &str,
+    response: &str,
+) -> AppResult<()> {
+    let previous = get_agent_context(state, agent_id, workspace_id)?
+        .map(|c| c.summary)
+        .unwrap_or_default();
+    let summary = append_summary(&previous, prompt, response);
+
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO agent_contexts (agent_id, workspace_id, acp_session_id, summary, run_count, last_task_run_id) \
+         VALUES (?1, ?2, ?3, ?4, 1, ?5) \
+         ON CONFLICT(agent_id, workspace_id) DO UPDATE SET \
+             acp_session_id = excluded.acp_session_id, \
+             summary = excluded.summary, \
+             run_count = CASE WHEN last_task_run_id = excluded.last_task_run_id THEN run_count ELSE run_count + 1 END, \
+             last_task_run_id = excluded.last_task_run_id, \
+             updated_at = datetime('now')",
+        params![agent_id, workspace_id.unwrap_or(""), acp_session_id, summary, task_run_id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Delete carried-over context. With no workspace given, all workspaces are reset.
+pub fn reset_agent_context(state: &AppState, agent_id: &str, workspace_id: Option<&str>) -> AppResult<u64> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let count = match workspace_id {
+        Some(ws) => db.execute(
+            "DELETE FROM agent_contexts WHERE agent_id = ?1 AND workspace_id = ?2",
+            params![agent_id, ws],
+        ),
+        None => db.execute("DELETE FROM agent_contexts WHERE agent_id = ?1", params![agent_id]),
+    }
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(count as u64)
+}
+
+/// Append one prompt/response exchange to the summary, keeping only the
+/// most recent `MAX_SUMMARY_CHARS` characters.
+fn append_summary(previous: &str, prompt: &str, response: &str) -> String {
+    let prompt_excerpt: String = prompt.chars().take(500).collect();
+    let response_excerpt: String = response.chars().take(1500).collect();
+    let entry = format!("### Task\n{}\n\n### Result\n{}\n", prompt_excerpt.trim(), response_excerpt.trim());
+
+    let combined = if previous.is_empty() {
+        entry
+    } else {
+        format!("{}\n{}", previous, entry)
+    };
+
+    let len = combined.chars().count();
+    if len <= MAX_SUMMARY_CHARS {
+        combined
+    } else {
+        combined.chars().skip(len - MAX_SUMMARY_CHARS).collect()
+    }
+}
@@ -0,0 +1,294 @@
+//! Declarative agent setups: an `agents.yaml` in the workspace root that can
+//! be version-controlled with the project.
+//!
+//! ```yaml
+//! agents:
+//!   - name: coder
+//!     command: npx
+//!     args: ["@zed-industries/claude-code-acp"]
+//!     model: sonnet
+//!     skills:
+//!       - id: rust
+//!         description: Rust backend work
+//!         task_keywords: [rust, cargo]
+//!     warmup_prompt: Read AGENTS.md and confirm you are ready.
+//!     policies:
+//!       max_concurrency: 2
+//!       carry_over_context: true
+//! ```
+//!
+//! Syncing creates agents for new entries, updates the agents an entry
+//! manages (adopting an unmanaged agent of the same name), and disables
+//! managed agents whose entry was removed. Fields an entry omits keep their
+//! current value.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::db::agent_repo;
+use crate::error::{AppError, AppResult};
+use crate::models::agent::{AgentConfig, AgentSkill, CreateAgentRequest, ManifestSyncReport, UpdateAgentRequest};
+use crate::state::AppState;
+
+pub const MANIFEST_FILE: &str = "agents.yaml";
+
+/// Disabled reason of managed agents whose entry left the manifest.
+const REMOVED_REASON: &str = "Removed from agents.yaml";
+const DISABLED_REASON: &str = "Disabled in agents.yaml";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentManifest {
+    #[serde(default)]
+    pub agents: Vec<ManifestAgent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestAgent {
+    /// Identifies the entry; unique within the manifest
+    pub name: String,
+    pub command: Option<String>,
+    pub args: Option<Vec<String>>,
+    pub model: Option<String>,
+    pub icon: Option<String>,
+    pub description: Option<String>,
+    pub system_prompt: Option<String>,
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<i64>,
+    pub capabilities: Option<Vec<String>>,
+    pub skills: Option<Vec<ManifestSkill>>,
+    pub warmup_prompt: Option<String>,
+    #[serde(default)]
+    pub policies: ManifestPolicies,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestSkill {
+    pub id: String,
+    pub name: Option<String>,
+    #[serde(default = "default_skill_type")]
+    pub skill_type: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub task_keywords: Vec<String>,
+    #[serde(default)]
+    pub constraints: Vec<String>,
+}
+
+fn default_skill_type() -> String {
+    "skill".into()
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestPolicies {
+    pub max_concurrency: Option<i64>,
+    pub carry_over_context: Option<bool>,
+    /// false keeps the agent disabled
+    pub enabled: Option<bool>,
+}
+
+pub fn manifest_path(working_directory: &str) -> PathBuf {
+    Path::new(working_directory).join(MANIFEST_FILE)
+}
+
+pub fn parse(text: &str) -> Result<AgentManifest, String> {
+    let manifest: AgentManifest = serde_yaml::from_str(text).map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))?;
+    let mut names: Vec<&str> = Vec::new();
+    for entry in &manifest.agents {
+        let name = entry.name.trim();
+        if name.is_empty() {
+            return Err("Every agent in agents.yaml needs a name".into());
+        }
+        if names.contains(&name) {
+            return Err(format!("Agent \"{}\" appears more than once in agents.yaml", name));
+        }
+        if entry.policies.max_concurrency.is_some_and(|n| n < 1) {
+            return Err(format!("Agent \"{}\": max_concurrency must be at least 1", name));
+        }
+        names.push(name);
+    }
+    Ok(manifest)
+}
+
+/// The agent an entry manages, or an unmanaged agent of the same name to adopt.
+fn find_agent<'a>(entry: &ManifestAgent, agents: &'a [AgentConfig]) -> Option<&'a AgentConfig> {
+    let name = entry.name.trim();
+    agents
+        .iter()
+        .find(|a| a.manifest_name.as_deref() == Some(name))
+        .or_else(|| agents.iter().find(|a| a.manifest_name.is_none() && a.name == name))
+}
+
+/// Update applying an entry to `existing`; omitted fields are left as they are.
+fn update_request(entry: &ManifestAgent, existing: &AgentConfig) -> UpdateAgentRequest {
+    let skills = entry.skills.as_ref().map(|skills| {
+        let skills: Vec<AgentSkill> = skills
+            .iter()
+            .map(|s| AgentSkill {
+                id: s.id.clone(),
+                name: s.name.clone().unwrap_or_else(|| s.id.clone()),
+                skill_type: s.skill_type.clone(),
+                description: s.description.clone(),
+                task_keywords: s.task_keywords.clone(),
+                constraints: s.constraints.clone(),
+                skill_source: "manifest".into(),
+                license: None,
+                compatibility: None,
+                metadata: Default::default(),
+            })
+            .collect();
+        serde_json::to_string(&skills).unwrap_or_else(|_| "[]".into())
+    });
+
+    let (is_enabled, disabled_reason) = match entry.policies.enabled {
+        Some(false) => (Some(false), Some(DISABLED_REASON.to_string())),
+        // Back in the manifest, or no longer disabled by it
+        _ if matches!(existing.disabled_reason.as_deref(), Some(REMOVED_REASON | DISABLED_REASON)) => (Some(true), None),
+        _ => (None, None),
+    };
+
+    UpdateAgentRequest {
+        name: Some(entry.name.trim().to_string()),
+        icon: entry.icon.clone(),
+        description: entry.description.clone(),
+        model: entry.model.clone(),
+        temperature: entry.temperature,
+        max_tokens: entry.max_tokens,
+        system_prompt: entry.system_prompt.clone(),
+        capabilities_json: entry.capabilities.as_ref().map(|c| serde_json::to_string(c).unwrap_or_else(|_| "[]".into())),
+        skills_json: skills,
+        acp_command: entry.command.clone(),
+        acp_args_json: entry.args.as_ref().map(|a| serde_json::to_string(a).unwrap_or_else(|_| "[]".into())),
+        max_concurrency: entry.policies.max_concurrency,
+        is_enabled,
+        disabled_reason,
+        carry_over_context: entry.policies.carry_over_context,
+        warmup_prompt: entry.warmup_prompt.clone(),
+        ..UpdateAgentRequest::default()
+    }
+}
+
+/// Managed agents whose entry is gone and that are still enabled.
+fn removed<'a>(manifest: &AgentManifest, agents: &'a [AgentConfig]) -> Vec<&'a AgentConfig> {
+    agents
+        .iter()
+        .filter(|a| a.is_enabled)
+        .filter(|a| {
+            a.manifest_name
+                .as_deref()
+                .is_some_and(|name| !manifest.agents.iter().any(|e| e.name.trim() == name))
+        })
+        .collect()
+}
+
+/// Everything about an agent a sync can change.
+fn synced_fields(agent: &AgentConfig) -> serde_json::Value {
+    let mut value = serde_json::to_value(agent).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        for volatile in ["status", "md_file_path", "available_models_json", "created_at", "updated_at"] {
+            fields.remove(volatile);
+        }
+    }
+    value
+}
+
+/// Reconcile a workspace's agents with its manifest.
+pub fn sync_workspace(
+    state: &AppState,
+    workspace_id: &str,
+    working_directory: &str,
+) -> AppResult<(ManifestSyncReport, Vec<AgentConfig>)> {
+    let path = manifest_path(working_directory);
+    let text = std::fs::read_to_string(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => AppError::NotFound(format!("No {} in {}", MANIFEST_FILE, working_directory)),
+        _ => AppError::Io(e),
+    })?;
+    let manifest = parse(&text).map_err(AppError::InvalidRequest)?;
+    let agents = agent_repo::list_agents(state, Some(workspace_id))?;
+
+    let mut report = ManifestSyncReport {
+        manifest_path: path.to_string_lossy().to_string(),
+        ..ManifestSyncReport::default()
+    };
+    let mut changed = Vec::new();
+    for entry in &manifest.agents {
+        let name = entry.name.trim();
+        let (existing, created) = match find_agent(entry, &agents) {
+            Some(agent) => (agent.clone(), false),
+            None => (agent_repo::create_agent(state, CreateAgentRequest::named(name, Some(workspace_id)))?, true),
+        };
+        if existing.manifest_name.as_deref() != Some(name) {
+            agent_repo::set_manifest_name(state, &existing.id, Some(name))?;
+        }
+        let updated = agent_repo::update_agent(state, &existing.id, update_request(entry, &existing))?;
+        if created {
+            report.created.push(updated.name.clone());
+        } else if synced_fields(&updated) != synced_fields(&existing) {
+            report.updated.push(updated.name.clone());
+        } else {
+            report.unchanged.push(updated.name.clone());
+            continue;
+        }
+        changed.push(updated);
+    }
+
+    for agent in removed(&manifest, &agents) {
+        agent_repo::disable_agent(state, &agent.id, REMOVED_REASON, false)?;
+        report.disabled.push(agent.name.clone());
+        changed.push(agent_repo::get_agent(state, &agent.id)?);
+    }
+    Ok((report, changed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(name: &str, manifest_name: Option<&str>) -> AgentConfig {
+        let mut agent: AgentConfig = serde_json::from_value(serde_json::json!({
+            "id": format!("id-{}", name), "name": name, "icon": "code", "description": "", "status": "Idle",
+            "execution_mode": "RunNow", "model": "m", "temperature": 0.7, "max_tokens": 4096, "system_prompt": "",
+            "capabilities_json": "[]", "acp_command": null, "acp_args_json": null, "is_control_hub": false,
+            "md_file_path": null, "max_concurrency": 1, "available_models_json": null, "is_enabled": true,
+            "disabled_reason": null, "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        agent.manifest_name = manifest_name.map(|s| s.to_string());
+        agent
+    }
+
+    #[test]
+    fn reconciles_entries_with_agents() {
+        let manifest = parse(
+            "agents:\n  - name: coder\n    command: npx\n    args: [acp]\n    skills:\n      - id: rust\n    \
+             policies:\n      max_concurrency: 2\n  - name: reviewer\n    policies:\n      enabled: false\n",
+        )
+        .unwrap();
+        assert!(parse("agents:\n  - name: a\n    comand: npx\n").is_err());
+        assert!(parse("agents:\n  - name: a\n  - name: a\n").is_err());
+
+        let mut gone = agent("old", Some("old"));
+        gone.is_enabled = true;
+        let mut back = agent("reviewer", None);
+        back.disabled_reason = Some(REMOVED_REASON.into());
+        let agents = vec![agent("coder", None), gone, back, agent("manual", None)];
+
+        assert_eq!(find_agent(&manifest.agents[0], &agents).map(|a| a.id.as_str()), Some("id-coder"));
+        let update = update_request(&manifest.agents[0], &agents[0]);
+        assert_eq!(update.acp_args_json.as_deref(), Some("[\"acp\"]"));
+        assert_eq!(update.max_concurrency, Some(2));
+        assert!(update.skills_json.unwrap().contains("\"skill_source\":\"manifest\""));
+        assert_eq!(update.model, None);
+
+        let update = update_request(&manifest.agents[1], &agents[2]);
+        assert_eq!((update.is_enabled, update.disabled_reason.as_deref()), (Some(false), Some(DISABLED_REASON)));
+
+        let removed: Vec<&str> = removed(&manifest, &agents).iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(removed, vec!["old"]);
+    }
+}
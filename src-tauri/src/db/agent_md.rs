@@ -1,5 +1,6 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::acp::container;
 use crate::db::migrations::{get_agents_dir, get_base_dir};
 use crate::error::{AppError, AppResult};
 use crate::models::agent::{AgentConfig, AgentSkill};
@@ -49,6 +50,7 @@ acp_command: "{acp_command}"
 acp_args: [{acp_args}]
 is_control_hub: {is_control_hub}
 is_enabled: {is_enabled}
+carry_over_context: {carry_over_context}
 ---
 
 {system_prompt}
@@ -67,6 +69,7 @@ is_enabled: {is_enabled}
         acp_args = args_str,
         is_control_hub = agent.is_control_hub,
         is_enabled = agent.is_enabled,
+        carry_over_context = agent.carry_over_context,
         system_prompt = agent.system_prompt,
     );
 
@@ -105,6 +108,9 @@ pub fn read_agent_md(path: &str) -> AppResult<AgentConfig> {
     let is_enabled: bool = extract_field(&frontmatter, "is_enabled")
         .and_then(|v| v.parse().ok())
         .unwrap_or(true);
+    let carry_over_context: bool = extract_field(&frontmatter, "carry_over_context")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(false);
     let acp_command = extract_field(&frontmatter, "acp_command")
         .filter(|s| !s.is_empty());
 
@@ -137,11 +143,23 @@ pub fn read_agent_md(path: &str) -> AppResult<AgentConfig> {
         acp_command,
         acp_args_json,
         is_control_hub,
+        is_secondary_hub: false,
         md_file_path: Some(path.to_string()),
         max_concurrency,
         available_models_json: None,
         is_enabled,
         disabled_reason: None,
+        disabled_transient: false,
+        carry_over_context,
+        warmup_prompt: None,
+        manifest_name: None,
+        ssh_host: None,
+        ssh_user: None,
+        ssh_key_path: None,
+        container_image: None,
+        container_workspace_access: container::READ_ONLY.into(),
+        idle_shutdown_minutes: None,
+        permission_preset: None,
         workspace_id: None,
         created_at: String::new(),
         updated_at: String::new(),
@@ -238,6 +256,76 @@ pub fn read_agents_registry() -> AppResult<String> {
         .map_err(|e| AppError::Io(e))
 }
 
+/// Name of the agent catalog written into a workspace root.
+pub const AGENTS_MD_FILE: &str = "AGENTS.md";
+
+/// Render a workspace's AGENTS.md: the XML catalog the control hub plans
+/// with, then a readable section per agent with its skills and constraints.
+pub fn render_agents_md(workspace_name: &str, catalog_xml: &str, agents: &[(&AgentConfig, Vec<AgentSkill>)]) -> String {
+    let mut content = format!(
+        "# Agents\n\n\
+         Agents of the {} workspace, generated by Agent Hub. Regenerate this file \
+         instead of editing it by hand.\n\n\
+         ## Catalog\n\n```xml\n{}\n```\n",
+        workspace_name, catalog_xml
+    );
+
+    for (agent, skills) in agents {
+        content.push_str(&format!("\n## {}\n\n", agent.name));
+        if !agent.description.is_empty() {
+            content.push_str(&format!("{}\n\n", agent.description.trim()));
+        }
+        content.push_str(&format!("- **ID**: `{}`\n", agent.id));
+        content.push_str(&format!("- **Model**: {}\n", agent.model));
+        if agent.is_control_hub {
+            content.push_str("- **Role**: Control hub\n");
+        } else if agent.is_secondary_hub {
+            content.push_str("- **Role**: Secondary control hub\n");
+        }
+
+        if !skills.is_empty() {
+            content.push_str("\n### Skills\n\n");
+            for skill in skills {
+                content.push_str(&format!("- **{}** (`{}`, {})", skill.name, skill.id, skill.skill_type));
+                if !skill.description.is_empty() {
+                    content.push_str(&format!(": {}", skill.description));
+                }
+                content.push('\n');
+                if !skill.task_keywords.is_empty() {
+                    content.push_str(&format!("  - Keywords: {}\n", skill.task_keywords.join(", ")));
+                }
+                if !skill.constraints.is_empty() {
+                    content.push_str(&format!("  - Allowed tools: {}\n", skill.constraints.join(", ")));
+                }
+            }
+        }
+
+        content.push_str("\n### Constraints\n\n");
+        content.push_str(&format!("- At most {} assignment(s) at a time\n", agent.max_concurrency));
+        if let Some(host) = agent.ssh_host.as_deref().filter(|h| !h.trim().is_empty()) {
+            content.push_str(&format!("- Runs on `{}` over SSH\n", host.trim()));
+        } else if let Some(image) = agent.container_image.as_deref().filter(|i| !i.trim().is_empty()) {
+            let access = if agent.container_workspace_access == container::READ_WRITE { "read-write" } else { "read-only" };
+            content.push_str(&format!("- Runs in a `{}` container with {} workspace access\n", image.trim(), access));
+        }
+    }
+
+    content
+}
+
+/// Write AGENTS.md into a workspace root.
+pub fn write_workspace_agents_md(root: &Path, content: &str) -> AppResult<PathBuf> {
+    if !root.is_dir() {
+        return Err(AppError::InvalidRequest(format!(
+            "Workspace directory {} does not exist",
+            root.display()
+        )));
+    }
+    let file_path = root.join(AGENTS_MD_FILE);
+    std::fs::write(&file_path, content).map_err(AppError::Io)?;
+    Ok(file_path)
+}
+
 fn parse_frontmatter(content: &str) -> Option<(String, String)> {
     let trimmed = content.trim_start();
     if !trimmed.starts_with("---") {
@@ -295,3 +383,34 @@ fn extract_array_field(frontmatter: &str, key: &str) -> Vec<String> {
     }
     Vec::new()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_catalog_and_agent_sections() {
+        let mut agent: AgentConfig = serde_json::from_value(serde_json::json!({
+            "id": "a1", "name": "Coder", "icon": "code", "description": "Writes Rust", "status": "Idle",
+            "execution_mode": "RunNow", "model": "m", "temperature": 0.7, "max_tokens": 4096, "system_prompt": "",
+            "capabilities_json": "[]", "acp_command": null, "acp_args_json": null, "is_control_hub": false,
+            "md_file_path": null, "max_concurrency": 2, "available_models_json": null, "is_enabled": true,
+            "disabled_reason": null, "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        agent.container_image = Some("rust:1".into());
+        let skill: AgentSkill = serde_json::from_value(serde_json::json!({
+            "id": "rust", "name": "Rust", "skill_type": "skill", "description": "",
+            "task_keywords": ["cargo"], "constraints": ["Bash", "Edit"]
+        }))
+        .unwrap();
+
+        let md = render_agents_md("Main", "<available_agents>\n</available_agents>", &[(&agent, vec![skill])]);
+        assert!(md.starts_with("# Agents\n\nAgents of the Main workspace"));
+        assert!(md.contains("```xml\n<available_agents>\n</available_agents>\n```\n"));
+        assert!(md.contains("\n## Coder\n\nWrites Rust\n\n- **ID**: `a1`\n- **Model**: m\n"));
+        assert!(md.contains("- **Rust** (`rust`, skill)\n  - Keywords: cargo\n  - Allowed tools: Bash, Edit\n"));
+        assert!(md.contains("- At most 2 assignment(s) at a time\n- Runs in a `rust:1` container with read-only workspace access\n"));
+        assert!(write_workspace_agents_md(Path::new("/nonexistent/agent-hub"), &md).is_err());
+    }
+}
@@ -1,7 +1,10 @@
 use rusqlite::params;
 
+use crate::acp::container;
+use crate::acp::permissions::PermissionPreset;
 use crate::error::{AppError, AppResult};
 use crate::models::agent::{AgentConfig, CreateAgentRequest, DiscoveredAgent, UpdateAgentRequest};
+use crate::models::bulk::BulkItemResult;
 use crate::state::AppState;
 
 fn row_to_agent(row: &rusqlite::Row) -> rusqlite::Result<AgentConfig> {
@@ -29,10 +32,40 @@ fn row_to_agent(row: &rusqlite::Row) -> rusqlite::Result<AgentConfig> {
         created_at: row.get(20)?,
         updated_at: row.get(21)?,
         workspace_id: row.get(22)?,
+        carry_over_context: row.get::<_, i32>(23)? != 0,
+        is_secondary_hub: row.get::<_, i32>(24)? != 0,
+        warmup_prompt: row.get(25)?,
+        manifest_name: row.get(26)?,
+        ssh_host: row.get(27)?,
+        ssh_user: row.get(28)?,
+        ssh_key_path: row.get(29)?,
+        container_image: row.get(30)?,
+        container_workspace_access: row.get(31)?,
+        disabled_transient: row.get::<_, i32>(32)? != 0,
+        idle_shutdown_minutes: row.get(33)?,
+        permission_preset: row.get::<_, Option<String>>(34)?.and_then(|p| PermissionPreset::parse(&p)),
     })
 }
 
-const SELECT_COLS: &str = "id, name, icon, description, status, execution_mode, model, temperature, max_tokens, system_prompt, capabilities_json, skills_json, acp_command, acp_args_json, is_control_hub, md_file_path, max_concurrency, available_models_json, is_enabled, disabled_reason, created_at, updated_at, workspace_id";
+const SELECT_COLS: &str = "id, name, icon, description, status, execution_mode, model, temperature, max_tokens, system_prompt, capabilities_json, skills_json, acp_command, acp_args_json, is_control_hub, md_file_path, max_concurrency, available_models_json, is_enabled, disabled_reason, created_at, updated_at, workspace_id, carry_over_context, is_secondary_hub, warmup_prompt, manifest_name, ssh_host, ssh_user, ssh_key_path, container_image, container_workspace_access, disabled_transient, idle_shutdown_minutes, permission_preset";
+
+/// Trimmed value, or `None` when it is blank.
+fn non_blank(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn check_workspace_access(access: &str) -> AppResult<()> {
+    if access == container::READ_ONLY || access == container::READ_WRITE {
+        Ok(())
+    } else {
+        Err(AppError::InvalidRequest(format!(
+            "Container workspace access must be {} or {}, not {}",
+            container::READ_ONLY,
+            container::READ_WRITE,
+            access
+        )))
+    }
+}
 
 pub fn list_agents(state: &AppState, workspace_id: Option<&str>) -> AppResult<Vec<AgentConfig>> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
@@ -75,11 +108,12 @@ pub fn get_agent(state: &AppState, id: &str) -> AppResult<AgentConfig> {
 }
 
 pub fn create_agent(state: &AppState, req: CreateAgentRequest) -> AppResult<AgentConfig> {
+    check_workspace_access(&req.container_workspace_access)?;
     let id = uuid::Uuid::new_v4().to_string();
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
 
     db.execute(
-        "INSERT INTO agents (id, name, icon, description, execution_mode, model, temperature, max_tokens, system_prompt, capabilities_json, skills_json, acp_command, acp_args_json, is_control_hub, max_concurrency, workspace_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        "INSERT INTO agents (id, name, icon, description, execution_mode, model, temperature, max_tokens, system_prompt, capabilities_json, skills_json, acp_command, acp_args_json, is_control_hub, max_concurrency, workspace_id, carry_over_context, warmup_prompt, ssh_host, ssh_user, ssh_key_path, container_image, container_workspace_access) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)",
         params![
             id,
             req.name,
@@ -97,6 +131,13 @@ pub fn create_agent(state: &AppState, req: CreateAgentRequest) -> AppResult<Agen
             req.is_control_hub as i32,
             req.max_concurrency,
             req.workspace_id,
+            req.carry_over_context as i32,
+            req.warmup_prompt.filter(|p| !p.trim().is_empty()),
+            non_blank(req.ssh_host),
+            non_blank(req.ssh_user),
+            non_blank(req.ssh_key_path),
+            non_blank(req.container_image),
+            req.container_workspace_access,
         ],
     )
     .map_err(|e| AppError::Database(e.to_string()))?;
@@ -126,6 +167,17 @@ pub fn update_agent(state: &AppState, id: &str, req: UpdateAgentRequest) -> AppR
     let max_concurrency = req.max_concurrency.unwrap_or(existing.max_concurrency);
     let available_models_json = req.available_models_json.or(existing.available_models_json);
     let is_enabled = req.is_enabled.unwrap_or(existing.is_enabled);
+    let carry_over_context = req.carry_over_context.unwrap_or(existing.carry_over_context);
+    let warmup_prompt = match req.warmup_prompt {
+        Some(prompt) => Some(prompt).filter(|p| !p.trim().is_empty()),
+        None => existing.warmup_prompt,
+    };
+    let ssh_host = req.ssh_host.map_or(existing.ssh_host, |h| non_blank(Some(h)));
+    let ssh_user = req.ssh_user.map_or(existing.ssh_user, |u| non_blank(Some(u)));
+    let ssh_key_path = req.ssh_key_path.map_or(existing.ssh_key_path, |k| non_blank(Some(k)));
+    let container_image = req.container_image.map_or(existing.container_image, |i| non_blank(Some(i)));
+    let container_workspace_access = req.container_workspace_access.unwrap_or(existing.container_workspace_access);
+    check_workspace_access(&container_workspace_access)?;
     let disabled_reason = if req.is_enabled == Some(true) {
         // Clearing disabled_reason when re-enabling
         req.disabled_reason
@@ -134,10 +186,22 @@ pub fn update_agent(state: &AppState, id: &str, req: UpdateAgentRequest) -> AppR
     } else {
         existing.disabled_reason
     };
+    let disabled_transient = existing.disabled_transient && !is_enabled;
+    let idle_shutdown_minutes = match req.idle_shutdown_minutes {
+        Some(minutes) => Some(minutes).filter(|m| *m >= 0),
+        None => existing.idle_shutdown_minutes,
+    };
+    let permission_preset = match req.permission_preset.as_deref().map(str::trim) {
+        Some("") => None,
+        Some(name) => Some(PermissionPreset::parse(name).ok_or_else(|| {
+            AppError::InvalidRequest(format!("Unknown permission preset {}", name))
+        })?),
+        None => existing.permission_preset,
+    };
 
     db.execute(
-        "UPDATE agents SET name=?1, icon=?2, description=?3, status=?4, execution_mode=?5, model=?6, temperature=?7, max_tokens=?8, system_prompt=?9, capabilities_json=?10, skills_json=?11, acp_command=?12, acp_args_json=?13, is_control_hub=?14, max_concurrency=?15, available_models_json=?16, is_enabled=?17, disabled_reason=?18, updated_at=datetime('now') WHERE id=?19",
-        params![name, icon, description, status, execution_mode, model, temperature, max_tokens, system_prompt, capabilities_json, skills_json, acp_command, acp_args_json, is_control_hub as i32, max_concurrency, available_models_json, is_enabled as i32, disabled_reason, id],
+        "UPDATE agents SET name=?1, icon=?2, description=?3, status=?4, execution_mode=?5, model=?6, temperature=?7, max_tokens=?8, system_prompt=?9, capabilities_json=?10, skills_json=?11, acp_command=?12, acp_args_json=?13, is_control_hub=?14, max_concurrency=?15, available_models_json=?16, is_enabled=?17, disabled_reason=?18, carry_over_context=?19, warmup_prompt=?20, ssh_host=?21, ssh_user=?22, ssh_key_path=?23, container_image=?24, container_workspace_access=?25, disabled_transient=?26, idle_shutdown_minutes=?27, permission_preset=?28, updated_at=datetime('now') WHERE id=?29",
+        params![name, icon, description, status, execution_mode, model, temperature, max_tokens, system_prompt, capabilities_json, skills_json, acp_command, acp_args_json, is_control_hub as i32, max_concurrency, available_models_json, is_enabled as i32, disabled_reason, carry_over_context as i32, warmup_prompt, ssh_host, ssh_user, ssh_key_path, container_image, container_workspace_access, disabled_transient as i32, idle_shutdown_minutes, permission_preset.map(PermissionPreset::as_str), id],
     )
     .map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -145,16 +209,41 @@ pub fn update_agent(state: &AppState, id: &str, req: UpdateAgentRequest) -> AppR
     get_agent(state, id)
 }
 
-pub fn disable_agent(state: &AppState, id: &str, reason: &str) -> AppResult<()> {
+/// Disable an agent after an error. `transient` marks an error that is likely
+/// to pass, so the agent may be re-enabled when a run finds no other agent.
+pub fn disable_agent(state: &AppState, id: &str, reason: &str, transient: bool) -> AppResult<()> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     db.execute(
-        "UPDATE agents SET is_enabled = 0, disabled_reason = ?1, updated_at = datetime('now') WHERE id = ?2",
-        params![reason, id],
+        "UPDATE agents SET is_enabled = 0, disabled_reason = ?1, disabled_transient = ?2, updated_at = datetime('now') WHERE id = ?3",
+        params![reason, transient as i32, id],
     )
     .map_err(|e| AppError::Database(e.to_string()))?;
     Ok(())
 }
 
+/// Enable or disable several agents in one transaction. Enabling clears the
+/// disabled reason; unlike `enable_agent` no health check is run.
+pub fn set_agents_enabled(state: &AppState, ids: &[String], enabled: bool) -> AppResult<Vec<BulkItemResult>> {
+    let mut db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let tx = db.transaction().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut results = Vec::with_capacity(ids.len());
+    for id in ids {
+        let sql = if enabled {
+            "UPDATE agents SET is_enabled = 1, disabled_reason = NULL, disabled_transient = 0, updated_at = datetime('now') WHERE id = ?1"
+        } else {
+            "UPDATE agents SET is_enabled = 0, updated_at = datetime('now') WHERE id = ?1"
+        };
+        let changed = tx.execute(sql, params![id]).map_err(|e| AppError::Database(e.to_string()))?;
+        results.push(if changed == 0 {
+            BulkItemResult::failed(id, format!("Agent {id} not found"))
+        } else {
+            BulkItemResult::ok(id)
+        });
+    }
+    tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(results)
+}
+
 pub fn set_control_hub(state: &AppState, id: &str) -> AppResult<AgentConfig> {
     // Verify agent exists and get its workspace_id
     let agent = get_agent(state, id)?;
@@ -174,9 +263,9 @@ pub fn set_control_hub(state: &AppState, id: &str) -> AppResult<AgentConfig> {
     }
     .map_err(|e| AppError::Database(e.to_string()))?;
 
-    // Set the specified agent as control hub
+    // Set the specified agent as control hub (it can no longer be its own fallback)
     db.execute(
-        "UPDATE agents SET is_control_hub = 1, updated_at = datetime('now') WHERE id = ?1",
+        "UPDATE agents SET is_control_hub = 1, is_secondary_hub = 0, updated_at = datetime('now') WHERE id = ?1",
         params![id],
     )
     .map_err(|e| AppError::Database(e.to_string()))?;
@@ -207,6 +296,84 @@ pub fn get_control_hub(state: &AppState, workspace_id: Option<&str>) -> AppResul
     }
 }
 
+/// Make an agent the workspace's secondary Control Hub, replacing any other.
+pub fn set_secondary_hub(state: &AppState, id: &str) -> AppResult<AgentConfig> {
+    let agent = get_agent(state, id)?;
+    if agent.is_control_hub {
+        return Err(AppError::InvalidRequest(format!(
+            "Agent {} is already the Control Hub",
+            agent.name
+        )));
+    }
+
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    match &agent.workspace_id {
+        Some(ws_id) => db.execute(
+            "UPDATE agents SET is_secondary_hub = 0 WHERE workspace_id = ?1",
+            params![ws_id],
+        ),
+        None => db.execute(
+            "UPDATE agents SET is_secondary_hub = 0 WHERE workspace_id IS NULL",
+            [],
+        ),
+    }
+    .map_err(|e| AppError::Database(e.to_string()))?;
+
+    db.execute(
+        "UPDATE agents SET is_secondary_hub = 1, updated_at = datetime('now') WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+
+    drop(db);
+    get_agent(state, id)
+}
+
+pub fn clear_secondary_hub(state: &AppState, id: &str) -> AppResult<AgentConfig> {
+    {
+        let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+        db.execute(
+            "UPDATE agents SET is_secondary_hub = 0, updated_at = datetime('now') WHERE id = ?1",
+            params![id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    get_agent(state, id)
+}
+
+pub fn get_secondary_hub(state: &AppState, workspace_id: Option<&str>) -> AppResult<Option<AgentConfig>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let result = match workspace_id {
+        Some(ws_id) => db.query_row(
+            &format!("SELECT {SELECT_COLS} FROM agents WHERE is_secondary_hub = 1 AND is_control_hub = 0 AND workspace_id = ?1 LIMIT 1"),
+            params![ws_id],
+            row_to_agent,
+        ),
+        None => db.query_row(
+            &format!("SELECT {SELECT_COLS} FROM agents WHERE is_secondary_hub = 1 AND is_control_hub = 0 AND workspace_id IS NULL LIMIT 1"),
+            [],
+            row_to_agent,
+        ),
+    };
+
+    match result {
+        Ok(agent) => Ok(Some(agent)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+/// Mark an agent as managed by the agents.yaml entry `manifest_name`.
+pub fn set_manifest_name(state: &AppState, id: &str, manifest_name: Option<&str>) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE agents SET manifest_name = ?1, updated_at = datetime('now') WHERE id = ?2",
+        params![manifest_name, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
 pub fn update_agent_md_path(state: &AppState, id: &str, md_path: &str) -> AppResult<()> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     db.execute(
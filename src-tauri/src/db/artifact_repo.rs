@@ -0,0 +1,100 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::task_run::{ArtifactMetadata, TaskArtifact};
+use crate::state::AppState;
+
+const ARTIFACT_COLS: &str = "id, task_run_id, assignment_id, agent_id, kind, path, size_bytes, created_at, metadata_json";
+
+fn row_to_artifact(row: &rusqlite::Row) -> rusqlite::Result<TaskArtifact> {
+    Ok(TaskArtifact {
+        id: row.get(0)?,
+        task_run_id: row.get(1)?,
+        assignment_id: row.get(2)?,
+        agent_id: row.get(3)?,
+        kind: row.get(4)?,
+        path: row.get(5)?,
+        size_bytes: row.get(6)?,
+        metadata: row
+            .get::<_, Option<String>>(8)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        created_at: row.get(7)?,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_artifact(
+    state: &AppState,
+    task_run_id: &str,
+    assignment_id: Option<&str>,
+    agent_id: Option<&str>,
+    kind: &str,
+    path: &str,
+    size_bytes: i64,
+    metadata: Option<&ArtifactMetadata>,
+) -> AppResult<TaskArtifact> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let metadata_json = metadata.map(serde_json::to_string).transpose()?;
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO task_artifacts (id, task_run_id, assignment_id, agent_id, kind, path, size_bytes, metadata_json) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![id, task_run_id, assignment_id, agent_id, kind, path, size_bytes, metadata_json],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+
+    db.query_row(
+        &format!("SELECT {ARTIFACT_COLS} FROM task_artifacts WHERE id = ?1"),
+        params![id],
+        row_to_artifact,
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+pub fn list_artifacts_for_run(state: &AppState, task_run_id: &str) -> AppResult<Vec<TaskArtifact>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!("SELECT {ARTIFACT_COLS} FROM task_artifacts WHERE task_run_id = ?1 ORDER BY created_at"))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let artifacts = stmt
+        .query_map(params![task_run_id], row_to_artifact)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(artifacts)
+}
+
+pub fn get_artifact(state: &AppState, id: &str) -> AppResult<TaskArtifact> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        &format!("SELECT {ARTIFACT_COLS} FROM task_artifacts WHERE id = ?1"),
+        params![id],
+        row_to_artifact,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Artifact {id} not found")),
+        _ => AppError::Database(e.to_string()),
+    })
+}
+
+pub fn find_artifact_by_path(state: &AppState, task_run_id: &str, path: &str) -> AppResult<Option<TaskArtifact>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    match db.query_row(
+        &format!("SELECT {ARTIFACT_COLS} FROM task_artifacts WHERE task_run_id = ?1 AND path = ?2"),
+        params![task_run_id, path],
+        row_to_artifact,
+    ) {
+        Ok(artifact) => Ok(Some(artifact)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+pub fn set_artifact_size(state: &AppState, id: &str, size_bytes: i64) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute("UPDATE task_artifacts SET size_bytes = ?1 WHERE id = ?2", params![size_bytes, id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::task_run::{AssignmentEvent, TaskAssignment};
+use crate::state::AppState;
+
+const ASSIGNMENT_EVENT_COLS: &str = "id, kind, content, tool_call_id, tool_status, created_at";
+
+fn row_to_assignment_event(row: &rusqlite::Row) -> rusqlite::Result<AssignmentEvent> {
+    Ok(AssignmentEvent {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        content: row.get(2)?,
+        tool_call_id: row.get(3)?,
+        tool_status: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+/// The prompt an event was recorded for.
+#[derive(Debug, Clone)]
+pub struct EventSource {
+    pub task_run_id: String,
+    /// None for prompts outside an assignment, such as the control hub's
+    pub assignment_id: Option<String>,
+    pub agent_id: String,
+}
+
+/// Queue an event on the write queue; it is committed with the next batch.
+pub fn queue_event(
+    state: &AppState,
+    source: &EventSource,
+    kind: &'static str,
+    content: String,
+    tool_call_id: Option<String>,
+    tool_status: Option<String>,
+) {
+    let EventSource { task_run_id, assignment_id, agent_id } = source.clone();
+    state.db_writes.submit("timeline event", move |db| {
+        db.execute(
+            "INSERT INTO assignment_events (task_run_id, assignment_id, agent_id, kind, content, tool_call_id, tool_status) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![task_run_id, assignment_id, agent_id, kind, content, tool_call_id, tool_status],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    });
+}
+
+/// Events recorded for an assignment. Events from before they named their
+/// assignment are matched by the assignment's agent and its start and
+/// completion time instead; timestamps on assignments have second
+/// precision, so that window extends to the end of the completion second.
+pub fn list_events_for_assignment(state: &AppState, assignment: &TaskAssignment) -> AppResult<Vec<AssignmentEvent>> {
+    state.db_writes.flush();
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {ASSIGNMENT_EVENT_COLS} FROM assignment_events \
+             WHERE assignment_id = ?1 \
+             OR (assignment_id IS NULL AND task_run_id = ?2 AND agent_id = ?3 \
+                 AND ?4 IS NOT NULL AND created_at >= ?4 \
+                 AND (?5 IS NULL OR created_at < datetime(?5, '+1 second'))) \
+             ORDER BY id"
+        ))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let events = stmt
+        .query_map(
+            params![assignment.id, assignment.task_run_id, assignment.agent_id, assignment.started_at, assignment.completed_at],
+            row_to_assignment_event,
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(events)
+}
@@ -1,13 +1,15 @@
 use rusqlite::params;
 
+use crate::chat_tool::spam_filter::SenderHistory;
 use crate::error::{AppError, AppResult};
 use crate::models::chat_tool::{
-    ChatTool, ChatToolContact, ChatToolMessage, CreateChatToolRequest, UpdateChatToolRequest,
+    ChatTool, ChatToolContact, ChatToolEscalation, ChatToolMessage, ChatToolMetricSample, ContactRecord,
+    CreateChatToolRequest, RunNotificationSubscription, UpdateChatToolRequest,
 };
 use crate::state::AppState;
 
 const CHAT_TOOL_COLS: &str =
-    "id, name, plugin_type, config_json, linked_agent_id, status, status_message, auto_reply_mode, workspace_id, messages_received, messages_sent, last_active_at, created_at, updated_at";
+    "id, name, plugin_type, config_json, linked_agent_id, status, status_message, auto_reply_mode, workspace_id, messages_received, messages_sent, last_active_at, created_at, updated_at, env_json, owner_contact_id, escalation_threshold, typing_indicator, reply_chunk_chars, reply_templates_json";
 
 fn row_to_chat_tool(row: &rusqlite::Row) -> rusqlite::Result<ChatTool> {
     Ok(ChatTool {
@@ -25,6 +27,12 @@ fn row_to_chat_tool(row: &rusqlite::Row) -> rusqlite::Result<ChatTool> {
         last_active_at: row.get(11)?,
         created_at: row.get(12)?,
         updated_at: row.get(13)?,
+        env_json: row.get(14)?,
+        owner_contact_id: row.get(15)?,
+        escalation_threshold: row.get(16)?,
+        typing_indicator: row.get::<_, i32>(17)? != 0,
+        reply_chunk_chars: row.get(18)?,
+        reply_templates_json: row.get(19)?,
     })
 }
 
@@ -84,14 +92,15 @@ pub fn get_chat_tool(state: &AppState, id: &str) -> AppResult<ChatTool> {
 
 pub fn create_chat_tool(state: &AppState, req: CreateChatToolRequest) -> AppResult<ChatTool> {
     let id = uuid::Uuid::new_v4().to_string();
+    let env_json = serde_json::to_string(&req.env_profile.unwrap_or_default())?;
     let db = state
         .db
         .lock()
         .map_err(|e| AppError::Database(e.to_string()))?;
 
     db.execute(
-        "INSERT INTO chat_tools (id, name, plugin_type, config_json, linked_agent_id, auto_reply_mode, workspace_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-        params![id, req.name, req.plugin_type, req.config_json, req.linked_agent_id, req.auto_reply_mode, req.workspace_id],
+        "INSERT INTO chat_tools (id, name, plugin_type, config_json, linked_agent_id, auto_reply_mode, workspace_id, env_json) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![id, req.name, req.plugin_type, req.config_json, req.linked_agent_id, req.auto_reply_mode, req.workspace_id, env_json],
     )
     .map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -99,6 +108,75 @@ pub fn create_chat_tool(state: &AppState, req: CreateChatToolRequest) -> AppResu
     get_chat_tool(state, &id)
 }
 
+/// Parts of config keys and environment variable names that hold
+/// credentials or login state, matched case-insensitively.
+const CREDENTIAL_KEYS: &[&str] =
+    &["token", "secret", "password", "passwd", "credential", "session", "cookie", "apikey", "api_key"];
+
+fn is_credential(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    CREDENTIAL_KEYS.iter().any(|part| key.contains(part))
+}
+
+/// `value` without credential keys, at any depth.
+fn strip_credentials(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|key, _| !is_credential(key));
+            map.values_mut().for_each(strip_credentials);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_credentials),
+        _ => {}
+    }
+}
+
+/// The name of a clone of `source`: `name` when given, otherwise the first
+/// free "<source> (copy)", "<source> (copy 2)" and so on in its workspace.
+fn clone_name(state: &AppState, source: &ChatTool, name: Option<String>) -> AppResult<String> {
+    let taken: Vec<String> =
+        list_chat_tools(state, source.workspace_id.as_deref())?.into_iter().map(|tool| tool.name).collect();
+    if let Some(name) = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) {
+        if taken.contains(&name) {
+            return Err(AppError::InvalidRequest(format!("A chat tool named '{}' already exists", name)));
+        }
+        return Ok(name);
+    }
+    let name = (1..)
+        .map(|n| match n {
+            1 => format!("{} (copy)", source.name),
+            n => format!("{} (copy {})", source.name, n),
+        })
+        .find(|candidate| !taken.contains(candidate))
+        .expect("an unused copy name");
+    Ok(name)
+}
+
+/// Create a new, stopped chat tool with the same plugin, config, agent link and
+/// environment as `id`. The clone never inherits a custom data directory or
+/// credentials from the config and environment, so it logs in on its own.
+pub fn clone_chat_tool(state: &AppState, id: &str, name: Option<String>) -> AppResult<ChatTool> {
+    let source = get_chat_tool(state, id)?;
+    let mut env_profile = source.env_profile().map_err(AppError::InvalidRequest)?;
+    env_profile.data_dir = None;
+    env_profile.env.retain(|name, _| !is_credential(name));
+    let mut config: serde_json::Value = serde_json::from_str(&source.config_json)
+        .map_err(|e| AppError::InvalidRequest(format!("Chat tool {} has an invalid config: {}", source.name, e)))?;
+    strip_credentials(&mut config);
+
+    create_chat_tool(
+        state,
+        CreateChatToolRequest {
+            name: clone_name(state, &source, name)?,
+            plugin_type: source.plugin_type,
+            config_json: config.to_string(),
+            linked_agent_id: source.linked_agent_id,
+            auto_reply_mode: source.auto_reply_mode,
+            workspace_id: source.workspace_id,
+            env_profile: Some(env_profile),
+        },
+    )
+}
+
 pub fn update_chat_tool(
     state: &AppState,
     id: &str,
@@ -137,6 +215,51 @@ pub fn update_chat_tool(
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
     }
+    if let Some(owner_contact_id) = &req.owner_contact_id {
+        let owner_contact_id = Some(owner_contact_id.as_str()).filter(|c| !c.is_empty());
+        db.execute(
+            "UPDATE chat_tools SET owner_contact_id = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![owner_contact_id, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    if let Some(threshold) = req.escalation_threshold {
+        let threshold = Some(threshold).filter(|t| *t > 0.0);
+        db.execute(
+            "UPDATE chat_tools SET escalation_threshold = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![threshold, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    if let Some(typing_indicator) = req.typing_indicator {
+        db.execute(
+            "UPDATE chat_tools SET typing_indicator = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![typing_indicator as i32, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    if let Some(chunk_chars) = req.reply_chunk_chars {
+        let chunk_chars = Some(chunk_chars).filter(|c| *c > 0);
+        db.execute(
+            "UPDATE chat_tools SET reply_chunk_chars = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![chunk_chars, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    if let Some(templates) = &req.reply_templates {
+        db.execute(
+            "UPDATE chat_tools SET reply_templates_json = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![serde_json::to_string(templates)?, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    if let Some(env_profile) = &req.env_profile {
+        db.execute(
+            "UPDATE chat_tools SET env_json = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![serde_json::to_string(env_profile)?, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
 
     drop(db);
     get_chat_tool(state, id)
@@ -209,7 +332,7 @@ pub fn increment_message_count(state: &AppState, id: &str, direction: &str) -> A
 // ── Messages ──
 
 const MESSAGE_COLS: &str =
-    "id, chat_tool_id, direction, external_sender_id, external_sender_name, content, content_type, agent_response, is_processed, error_message, created_at";
+    "id, chat_tool_id, direction, external_sender_id, external_sender_name, content, content_type, agent_response, is_processed, error_message, created_at, filter_reason, delivery_status, delivery_error, delivery_attempts";
 
 fn row_to_message(row: &rusqlite::Row) -> rusqlite::Result<ChatToolMessage> {
     Ok(ChatToolMessage {
@@ -224,6 +347,10 @@ fn row_to_message(row: &rusqlite::Row) -> rusqlite::Result<ChatToolMessage> {
         is_processed: row.get::<_, i32>(8)? != 0,
         error_message: row.get(9)?,
         created_at: row.get(10)?,
+        filter_reason: row.get(11)?,
+        delivery_status: row.get(12)?,
+        delivery_error: row.get(13)?,
+        delivery_attempts: row.get(14)?,
     })
 }
 
@@ -313,6 +440,100 @@ pub fn mark_message_error(state: &AppState, message_id: &str, error: &str) -> Ap
     Ok(())
 }
 
+/// Hold back a message the spam filter caught.
+pub fn flag_message(state: &AppState, message_id: &str, reason: &str) -> AppResult<()> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE chat_tool_messages SET filter_reason = ?1 WHERE id = ?2",
+        params![reason, message_id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+pub fn get_chat_tool_message(state: &AppState, message_id: &str) -> AppResult<ChatToolMessage> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        &format!("SELECT {MESSAGE_COLS} FROM chat_tool_messages WHERE id = ?1"),
+        params![message_id],
+        row_to_message,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => {
+            AppError::NotFound(format!("Chat tool message {} not found", message_id))
+        }
+        _ => AppError::Database(e.to_string()),
+    })
+}
+
+/// Record a delivery status for an outgoing message. A failure also counts
+/// the attempt; any other status clears the last error.
+pub fn set_delivery_status(
+    state: &AppState,
+    message_id: &str,
+    status: &str,
+    error: Option<&str>,
+) -> AppResult<ChatToolMessage> {
+    {
+        let db = state
+            .db
+            .lock()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        db.execute(
+            "UPDATE chat_tool_messages
+             SET delivery_status = ?1, delivery_error = ?2,
+                 delivery_attempts = delivery_attempts + (?1 = 'failed')
+             WHERE id = ?3",
+            params![status, error, message_id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    get_chat_tool_message(state, message_id)
+}
+
+/// Whether `content` was sent to `to_id` within the last `hours` hours.
+pub fn sent_recently(state: &AppState, chat_tool_id: &str, to_id: &str, content: &str, hours: i64) -> AppResult<bool> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        "SELECT EXISTS(SELECT 1 FROM chat_tool_messages
+         WHERE chat_tool_id = ?1 AND external_sender_id = ?2 AND direction = 'outgoing'
+           AND content = ?3 AND created_at >= datetime('now', ?4))",
+        params![chat_tool_id, to_id, content, format!("-{} hours", hours)],
+        |row| row.get(0),
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+/// Incoming messages from a sender in the last minute and in total.
+pub fn sender_history(state: &AppState, chat_tool_id: &str, sender_id: &str) -> AppResult<SenderHistory> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        "SELECT COALESCE(SUM(created_at >= datetime('now', '-60 seconds')), 0), COUNT(*)
+         FROM chat_tool_messages
+         WHERE chat_tool_id = ?1 AND external_sender_id = ?2 AND direction = 'incoming'",
+        params![chat_tool_id, sender_id],
+        |row| {
+            Ok(SenderHistory {
+                last_minute: row.get(0)?,
+                total: row.get(1)?,
+            })
+        },
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
 /// List unprocessed incoming messages for a chat tool, ordered oldest first.
 pub fn list_unprocessed_messages(
     state: &AppState,
@@ -324,7 +545,7 @@ pub fn list_unprocessed_messages(
         .map_err(|e| AppError::Database(e.to_string()))?;
     let mut stmt = db
         .prepare(&format!(
-            "SELECT {MESSAGE_COLS} FROM chat_tool_messages WHERE chat_tool_id = ?1 AND direction = 'incoming' AND is_processed = 0 AND error_message IS NULL ORDER BY created_at ASC"
+            "SELECT {MESSAGE_COLS} FROM chat_tool_messages WHERE chat_tool_id = ?1 AND direction = 'incoming' AND is_processed = 0 AND error_message IS NULL AND filter_reason IS NULL ORDER BY created_at ASC"
         ))
         .map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -337,6 +558,89 @@ pub fn list_unprocessed_messages(
     Ok(messages)
 }
 
+/// Number of incoming messages still waiting for a reply.
+pub fn count_unprocessed_messages(state: &AppState, chat_tool_id: &str) -> AppResult<i64> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        "SELECT COUNT(*) FROM chat_tool_messages WHERE chat_tool_id = ?1 AND direction = 'incoming' AND is_processed = 0 AND error_message IS NULL AND filter_reason IS NULL",
+        params![chat_tool_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+/// Receipt times (UTC) of the incoming messages since `since`, oldest first.
+pub fn list_incoming_times(state: &AppState, chat_tool_id: &str, since: &str) -> AppResult<Vec<String>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare("SELECT created_at FROM chat_tool_messages WHERE chat_tool_id = ?1 AND direction = 'incoming' AND created_at >= ?2 ORDER BY created_at")
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let times = stmt
+        .query_map(params![chat_tool_id, since], |row| row.get(0))
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(times)
+}
+
+/// Days of batch samples kept for metrics.
+const METRICS_RETENTION_DAYS: i64 = 31;
+
+/// Queue a batch sample on the write queue; older samples past the
+/// retention are dropped with it.
+pub fn queue_metric_sample(state: &AppState, chat_tool_id: String, sample: ChatToolMetricSample) {
+    state.db_writes.submit("chat tool metric", move |db| {
+        db.execute(
+            "INSERT INTO chat_tool_metrics (chat_tool_id, batch_size, queue_depth, hub_latency_ms, response_ms_total, response_ms_max, outcome) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                chat_tool_id,
+                sample.batch_size,
+                sample.queue_depth,
+                sample.hub_latency_ms,
+                sample.response_ms_total,
+                sample.response_ms_max,
+                sample.outcome,
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        db.execute(
+            "DELETE FROM chat_tool_metrics WHERE chat_tool_id = ?1 AND recorded_at < datetime('now', ?2)",
+            params![chat_tool_id, format!("-{METRICS_RETENTION_DAYS} days")],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    });
+}
+
+/// Batch samples recorded since `since`, oldest first.
+pub fn list_metric_samples(state: &AppState, chat_tool_id: &str, since: &str) -> AppResult<Vec<ChatToolMetricSample>> {
+    state.db_writes.flush();
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(
+            "SELECT batch_size, queue_depth, hub_latency_ms, response_ms_total, response_ms_max, outcome, recorded_at \
+             FROM chat_tool_metrics WHERE chat_tool_id = ?1 AND recorded_at >= ?2 ORDER BY recorded_at, id",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let samples = stmt
+        .query_map(params![chat_tool_id, since], |row| {
+            Ok(ChatToolMetricSample {
+                batch_size: row.get(0)?,
+                queue_depth: row.get(1)?,
+                hub_latency_ms: row.get(2)?,
+                response_ms_total: row.get(3)?,
+                response_ms_max: row.get(4)?,
+                outcome: row.get(5)?,
+                recorded_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(samples)
+}
+
 /// Mark multiple messages as processed in a single transaction.
 pub fn mark_messages_processed_batch(
     state: &AppState,
@@ -361,8 +665,14 @@ pub fn mark_messages_processed_batch(
 
 // ── Contacts ──
 
+// Usage counters read as zero once their day is over, snoozes as NULL once they end
 const CONTACT_COLS: &str =
-    "id, chat_tool_id, external_id, name, avatar_url, contact_type, is_blocked, created_at, updated_at";
+    "id, chat_tool_id, external_id, name, avatar_url, contact_type, is_blocked, created_at, updated_at, \
+     max_replies_per_day, max_tokens_per_day, \
+     CASE WHEN usage_date = date('now', 'localtime') THEN replies_today ELSE 0 END, \
+     CASE WHEN usage_date = date('now', 'localtime') THEN tokens_today ELSE 0 END, \
+     CASE WHEN snoozed_until > datetime('now') THEN snoozed_until END, takeover_started_at, groups_json, \
+     run_notifications_json";
 
 fn row_to_contact(row: &rusqlite::Row) -> rusqlite::Result<ChatToolContact> {
     Ok(ChatToolContact {
@@ -373,6 +683,19 @@ fn row_to_contact(row: &rusqlite::Row) -> rusqlite::Result<ChatToolContact> {
         avatar_url: row.get(4)?,
         contact_type: row.get(5)?,
         is_blocked: row.get::<_, i32>(6)? != 0,
+        max_replies_per_day: row.get(9)?,
+        max_tokens_per_day: row.get(10)?,
+        replies_today: row.get(11)?,
+        tokens_today: row.get(12)?,
+        snoozed_until: row.get(13)?,
+        takeover_started_at: row.get(14)?,
+        groups: row
+            .get::<_, Option<String>>(15)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        run_notifications: row
+            .get::<_, Option<String>>(16)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
         created_at: row.get(7)?,
         updated_at: row.get(8)?,
     })
@@ -406,6 +729,67 @@ pub fn upsert_contacts(
     Ok(())
 }
 
+/// Add or update contacts from an imported contact list in one transaction.
+/// Values a record leaves out keep the contact's current ones; new contacts
+/// without a name are named after their id. Returns how many contacts were
+/// created and how many updated.
+pub fn import_contacts(
+    state: &AppState,
+    chat_tool_id: &str,
+    records: &[ContactRecord],
+) -> AppResult<(usize, usize)> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let tx = db.transaction().map_err(|e| AppError::Database(e.to_string()))?;
+
+    let (mut created, mut updated) = (0, 0);
+    for record in records {
+        let exists: bool = tx
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM chat_tool_contacts WHERE chat_tool_id = ?1 AND external_id = ?2)",
+                params![chat_tool_id, record.external_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let groups_json = record.groups.as_ref().map(serde_json::to_string).transpose()?;
+        tx.execute(
+            "INSERT INTO chat_tool_contacts
+               (id, chat_tool_id, external_id, name, contact_type, is_blocked, groups_json, max_replies_per_day, max_tokens_per_day)
+             VALUES (?1, ?2, ?3, COALESCE(?4, ?3), COALESCE(?5, 'personal'), COALESCE(?6, 0), ?7, ?8, ?9)
+             ON CONFLICT(chat_tool_id, external_id) DO UPDATE SET
+               name = COALESCE(?4, name),
+               contact_type = COALESCE(?5, contact_type),
+               is_blocked = COALESCE(?6, is_blocked),
+               groups_json = COALESCE(?7, groups_json),
+               max_replies_per_day = COALESCE(?8, max_replies_per_day),
+               max_tokens_per_day = COALESCE(?9, max_tokens_per_day),
+               updated_at = datetime('now')",
+            params![
+                uuid::Uuid::new_v4().to_string(),
+                chat_tool_id,
+                record.external_id,
+                record.name,
+                record.contact_type,
+                record.is_blocked,
+                groups_json,
+                record.max_replies_per_day,
+                record.max_tokens_per_day,
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        if exists {
+            updated += 1;
+        } else {
+            created += 1;
+        }
+    }
+
+    tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    Ok((created, updated))
+}
+
 pub fn list_contacts(
     state: &AppState,
     chat_tool_id: &str,
@@ -429,6 +813,193 @@ pub fn list_contacts(
     Ok(contacts)
 }
 
+pub fn get_contact(state: &AppState, contact_id: &str) -> AppResult<ChatToolContact> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        &format!("SELECT {CONTACT_COLS} FROM chat_tool_contacts WHERE id = ?1"),
+        params![contact_id],
+        row_to_contact,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Contact {contact_id} not found")),
+        _ => AppError::Database(e.to_string()),
+    })
+}
+
+/// The contact for a sender, if the chat tool knows it.
+pub fn find_contact(state: &AppState, chat_tool_id: &str, external_id: &str) -> AppResult<Option<ChatToolContact>> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let result = db.query_row(
+        &format!("SELECT {CONTACT_COLS} FROM chat_tool_contacts WHERE chat_tool_id = ?1 AND external_id = ?2"),
+        params![chat_tool_id, external_id],
+        row_to_contact,
+    );
+    match result {
+        Ok(contact) => Ok(Some(contact)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+/// Count an auto-reply and its tokens against a sender's daily quotas,
+/// adding the sender to the contacts if the bridge never listed it.
+pub fn record_contact_usage(
+    state: &AppState,
+    chat_tool_id: &str,
+    external_id: &str,
+    name: &str,
+    tokens: i64,
+) -> AppResult<()> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let id = uuid::Uuid::new_v4().to_string();
+    db.execute(
+        "INSERT INTO chat_tool_contacts (id, chat_tool_id, external_id, name, replies_today, tokens_today, usage_date)
+         VALUES (?1, ?2, ?3, ?4, 1, ?5, date('now', 'localtime'))
+         ON CONFLICT(chat_tool_id, external_id) DO UPDATE SET
+           replies_today = CASE WHEN usage_date = excluded.usage_date THEN replies_today + 1 ELSE 1 END,
+           tokens_today = CASE WHEN usage_date = excluded.usage_date THEN tokens_today + excluded.tokens_today
+                          ELSE excluded.tokens_today END,
+           usage_date = excluded.usage_date",
+        params![id, chat_tool_id, external_id, name, tokens],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+pub fn set_contact_quota(
+    state: &AppState,
+    contact_id: &str,
+    max_replies_per_day: Option<i64>,
+    max_tokens_per_day: Option<i64>,
+) -> AppResult<ChatToolContact> {
+    {
+        let db = state
+            .db
+            .lock()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        db.execute(
+            "UPDATE chat_tool_contacts SET max_replies_per_day = ?1, max_tokens_per_day = ?2, updated_at = datetime('now')
+             WHERE id = ?3",
+            params![max_replies_per_day, max_tokens_per_day, contact_id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    get_contact(state, contact_id)
+}
+
+/// Clear today's usage so the contact gets its full quotas again.
+pub fn reset_contact_usage(state: &AppState, contact_id: &str) -> AppResult<ChatToolContact> {
+    {
+        let db = state
+            .db
+            .lock()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        db.execute(
+            "UPDATE chat_tool_contacts SET replies_today = 0, tokens_today = 0, usage_date = NULL, updated_at = datetime('now')
+             WHERE id = ?1",
+            params![contact_id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    get_contact(state, contact_id)
+}
+
+/// Suspend auto-replies to a contact for `minutes`; 0 resumes them now.
+pub fn snooze_contact(state: &AppState, contact_id: &str, minutes: i64) -> AppResult<ChatToolContact> {
+    {
+        let db = state
+            .db
+            .lock()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let modifier = format!("+{} minutes", minutes);
+        db.execute(
+            "UPDATE chat_tool_contacts
+             SET snoozed_until = CASE WHEN ?1 > 0 THEN datetime('now', ?2) END, updated_at = datetime('now')
+             WHERE id = ?3",
+            params![minutes, modifier, contact_id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    get_contact(state, contact_id)
+}
+
+/// Start or end a human takeover of the conversation with a contact.
+/// Starting one that is already running keeps its start time.
+pub fn set_contact_takeover(state: &AppState, contact_id: &str, active: bool) -> AppResult<ChatToolContact> {
+    {
+        let db = state
+            .db
+            .lock()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        db.execute(
+            "UPDATE chat_tool_contacts
+             SET takeover_started_at = CASE WHEN ?1 THEN COALESCE(takeover_started_at, datetime('now')) END,
+                 updated_at = datetime('now')
+             WHERE id = ?2",
+            params![active, contact_id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    get_contact(state, contact_id)
+}
+
+/// Clear a snooze that ran out. Returns false when the contact was snoozed
+/// again or resumed in the meantime.
+pub fn end_contact_snooze(state: &AppState, contact_id: &str, snoozed_until: &str) -> AppResult<bool> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let changed = db
+        .execute(
+            "UPDATE chat_tool_contacts SET snoozed_until = NULL, updated_at = datetime('now')
+             WHERE id = ?1 AND snoozed_until = ?2",
+            params![contact_id, snoozed_until],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(changed > 0)
+}
+
+/// Messages from and to one contact, oldest first, optionally limited to
+/// `created_at` in [since, until].
+pub fn list_contact_messages(
+    state: &AppState,
+    chat_tool_id: &str,
+    external_id: &str,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> AppResult<Vec<ChatToolMessage>> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {MESSAGE_COLS} FROM chat_tool_messages
+             WHERE chat_tool_id = ?1 AND external_sender_id = ?2
+               AND (?3 IS NULL OR created_at >= ?3) AND (?4 IS NULL OR created_at <= ?4)
+             ORDER BY created_at ASC, rowid ASC"
+        ))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let messages = stmt
+        .query_map(params![chat_tool_id, external_id, since, until], row_to_message)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(messages)
+}
+
 pub fn set_contact_blocked(
     state: &AppState,
     contact_id: &str,
@@ -446,6 +1017,48 @@ pub fn set_contact_blocked(
     Ok(())
 }
 
+/// Subscribe a contact to run status messages, or unsubscribe it with `None`.
+pub fn set_contact_run_notifications(
+    state: &AppState,
+    contact_id: &str,
+    subscription: Option<&RunNotificationSubscription>,
+) -> AppResult<()> {
+    let json = subscription.map(serde_json::to_string).transpose()?;
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let updated = db
+        .execute(
+            "UPDATE chat_tool_contacts SET run_notifications_json = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![json, contact_id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    if updated == 0 {
+        return Err(AppError::NotFound(format!("Contact {contact_id} not found")));
+    }
+    Ok(())
+}
+
+/// Contacts subscribed to run status messages, across chat tools.
+pub fn list_run_notification_contacts(state: &AppState) -> AppResult<Vec<ChatToolContact>> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {CONTACT_COLS} FROM chat_tool_contacts WHERE run_notifications_json IS NOT NULL"
+        ))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let contacts = stmt
+        .query_map([], row_to_contact)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(contacts)
+}
+
 pub fn update_last_active(state: &AppState, id: &str) -> AppResult<()> {
     let db = state
         .db
@@ -458,3 +1071,125 @@ pub fn update_last_active(state: &AppState, id: &str) -> AppResult<()> {
     .map_err(|e| AppError::Database(e.to_string()))?;
     Ok(())
 }
+
+const ESCALATION_COLS: &str = "id, chat_tool_id, external_sender_id, external_sender_name, original_content, hub_reply, reason, confidence, owner_contact_id, created_at";
+
+fn row_to_escalation(row: &rusqlite::Row) -> rusqlite::Result<ChatToolEscalation> {
+    Ok(ChatToolEscalation {
+        id: row.get(0)?,
+        chat_tool_id: row.get(1)?,
+        external_sender_id: row.get(2)?,
+        external_sender_name: row.get(3)?,
+        original_content: row.get(4)?,
+        hub_reply: row.get(5)?,
+        reason: row.get(6)?,
+        confidence: row.get(7)?,
+        owner_contact_id: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn save_escalation(
+    state: &AppState,
+    chat_tool_id: &str,
+    external_sender_id: Option<&str>,
+    external_sender_name: Option<&str>,
+    original_content: &str,
+    hub_reply: &str,
+    reason: &str,
+    confidence: Option<f64>,
+    owner_contact_id: &str,
+) -> AppResult<ChatToolEscalation> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO chat_tool_escalations (id, chat_tool_id, external_sender_id, external_sender_name, original_content, hub_reply, reason, confidence, owner_contact_id) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![id, chat_tool_id, external_sender_id, external_sender_name, original_content, hub_reply, reason, confidence, owner_contact_id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+
+    db.query_row(
+        &format!("SELECT {ESCALATION_COLS} FROM chat_tool_escalations WHERE id = ?1"),
+        params![id],
+        row_to_escalation,
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+pub fn list_escalations(state: &AppState, chat_tool_id: &str, limit: i64) -> AppResult<Vec<ChatToolEscalation>> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {ESCALATION_COLS} FROM chat_tool_escalations WHERE chat_tool_id = ?1 ORDER BY created_at DESC LIMIT ?2"
+        ))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let escalations = stmt
+        .query_map(params![chat_tool_id, limit], row_to_escalation)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(escalations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations;
+    use crate::models::chat_tool::ChatToolEnvProfile;
+
+    #[test]
+    fn clones_settings_without_credentials() {
+        let state = AppState::new(migrations::open_in_memory().unwrap());
+        let env_profile = ChatToolEnvProfile {
+            proxy: Some("http://proxy:8080".into()),
+            data_dir: Some("/data/wechat".into()),
+            env: [("LOG_LEVEL".to_string(), "debug".to_string()), ("BOT_TOKEN".to_string(), "t".to_string())]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let source = create_chat_tool(
+            &state,
+            CreateChatToolRequest {
+                name: "WeChat".into(),
+                plugin_type: "wechat".into(),
+                config_json: serde_json::json!({
+                    "botName": "helper",
+                    "token": "abc",
+                    "puppet": { "sessionData": "s", "endpoint": "https://x.io", "apiKey": "k" },
+                })
+                .to_string(),
+                linked_agent_id: None,
+                auto_reply_mode: "mentions".into(),
+                workspace_id: None,
+                env_profile: Some(env_profile),
+            },
+        )
+        .unwrap();
+
+        let clone = clone_chat_tool(&state, &source.id, None).unwrap();
+        assert_ne!(clone.id, source.id);
+        assert_eq!(clone.name, "WeChat (copy)");
+        assert_eq!((clone.plugin_type.as_str(), clone.auto_reply_mode.as_str()), ("wechat", "mentions"));
+        let config: serde_json::Value = serde_json::from_str(&clone.config_json).unwrap();
+        assert_eq!(config, serde_json::json!({ "botName": "helper", "puppet": { "endpoint": "https://x.io" } }));
+        let env = clone.env_profile().unwrap();
+        assert_eq!(env.proxy.as_deref(), Some("http://proxy:8080"));
+        assert_eq!(env.data_dir, None);
+        assert_eq!(env.env.keys().collect::<Vec<_>>(), ["LOG_LEVEL"]);
+
+        assert_eq!(clone_chat_tool(&state, &source.id, None).unwrap().name, "WeChat (copy 2)");
+        assert!(clone_chat_tool(&state, &source.id, Some("WeChat".into())).is_err());
+        assert_eq!(clone_chat_tool(&state, &source.id, Some(" Support ".into())).unwrap().name, "Support");
+    }
+}
@@ -0,0 +1,76 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// A spawned agent or bridge process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChildProcessRecord {
+    pub pid: u32,
+    /// OS start time, see `reaper::process_start_time`
+    pub started_at: String,
+    /// "agent" or "bridge"
+    pub kind: String,
+    /// Agent or chat tool id
+    pub owner_id: String,
+    pub command: String,
+    /// Process id of the app instance that spawned it
+    pub app_pid: u32,
+    /// OS start time of that instance; None for records made before it was kept
+    pub app_started_at: Option<String>,
+}
+
+pub fn record(state: &AppState, process: &ChildProcessRecord) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT OR REPLACE INTO child_processes (pid, started_at, kind, owner_id, command, app_pid, app_started_at) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            process.pid,
+            process.started_at,
+            process.kind,
+            process.owner_id,
+            process.command,
+            process.app_pid,
+            process.app_started_at,
+        ],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+pub fn list(state: &AppState) -> AppResult<Vec<ChildProcessRecord>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(
+            "SELECT pid, started_at, kind, owner_id, command, app_pid, app_started_at \
+             FROM child_processes ORDER BY created_at",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let records = stmt
+        .query_map([], |row| {
+            Ok(ChildProcessRecord {
+                pid: row.get(0)?,
+                started_at: row.get(1)?,
+                kind: row.get(2)?,
+                owner_id: row.get(3)?,
+                command: row.get(4)?,
+                app_pid: row.get(5)?,
+                app_started_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(records)
+}
+
+pub fn delete(state: &AppState, pid: u32, started_at: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "DELETE FROM child_processes WHERE pid = ?1 AND started_at = ?2",
+        params![pid, started_at],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
@@ -0,0 +1,238 @@
+//! The app's SQLite connection and how contended it is.
+//!
+//! Repositories share one connection behind `Db`, a mutex that counts how
+//! often callers had to wait for it and for how long. Another process holding
+//! SQLite's write lock (the CLI, a backup tool) makes statements fail with
+//! `SQLITE_BUSY`; the busy handler retries them with backoff for up to
+//! `database.busy_timeout_ms` and counts the retries. Frequent small writes go
+//! through `write_queue` so they take the lock once per batch.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LockResult, Mutex, MutexGuard, TryLockError};
+use std::time::{Duration, Instant};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// How long a statement retries while another process holds the database
+    pub busy_timeout_ms: u64,
+    /// How long queued writes wait for more writes to commit with
+    pub write_batch_ms: u64,
+    /// How often streamed replies and agent output are written while they stream
+    pub stream_flush_ms: u64,
+    /// Waits for the connection longer than this are logged
+    pub slow_lock_ms: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            busy_timeout_ms: 5000,
+            write_batch_ms: 25,
+            stream_flush_ms: 2000,
+            slow_lock_ms: 250,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    pub fn validate(&self) -> AppResult<()> {
+        if self.busy_timeout_ms > 60_000 {
+            return Err(AppError::InvalidRequest("Database busy_timeout_ms must be at most 60000".into()));
+        }
+        if self.write_batch_ms > 1000 {
+            return Err(AppError::InvalidRequest("Database write_batch_ms must be at most 1000".into()));
+        }
+        if !(100..=60_000).contains(&self.stream_flush_ms) {
+            return Err(AppError::InvalidRequest("Database stream_flush_ms must be between 100 and 60000".into()));
+        }
+        Ok(())
+    }
+}
+
+static BUSY_TIMEOUT_MS: AtomicU64 = AtomicU64::new(5000);
+static WRITE_BATCH_MS: AtomicU64 = AtomicU64::new(25);
+static STREAM_FLUSH_MS: AtomicU64 = AtomicU64::new(2000);
+static SLOW_LOCK_MS: AtomicU64 = AtomicU64::new(250);
+static BUSY_RETRIES: AtomicU64 = AtomicU64::new(0);
+static BUSY_TIMEOUTS: AtomicU64 = AtomicU64::new(0);
+
+/// Use the given settings from now on.
+pub fn apply(config: &DatabaseConfig) {
+    BUSY_TIMEOUT_MS.store(config.busy_timeout_ms, Ordering::Relaxed);
+    WRITE_BATCH_MS.store(config.write_batch_ms, Ordering::Relaxed);
+    STREAM_FLUSH_MS.store(config.stream_flush_ms, Ordering::Relaxed);
+    SLOW_LOCK_MS.store(config.slow_lock_ms, Ordering::Relaxed);
+}
+
+/// Follow `database` config changes.
+pub async fn follow_config(state: AppState) {
+    let mut changes = crate::config::subscribe(&state);
+    apply(&changes.borrow_and_update().database);
+    while changes.changed().await.is_ok() {
+        apply(&changes.borrow_and_update().database);
+    }
+}
+
+pub(crate) fn write_batch_window() -> Duration {
+    Duration::from_millis(WRITE_BATCH_MS.load(Ordering::Relaxed))
+}
+
+pub(crate) fn stream_flush_interval() -> Duration {
+    Duration::from_millis(STREAM_FLUSH_MS.load(Ordering::Relaxed))
+}
+
+/// Sleep before busy retry `attempt`, or `None` once the retries so far
+/// would exceed `timeout`. Delays double from 1ms up to 100ms.
+fn busy_delay(attempt: i32, timeout: Duration) -> Option<Duration> {
+    let delay = |n: i32| Duration::from_millis(1 << n.clamp(0, 7)).min(Duration::from_millis(100));
+    let waited: Duration = (0..attempt).map(delay).sum();
+    let next = delay(attempt);
+    (waited + next <= timeout).then_some(next)
+}
+
+fn busy_handler(attempt: i32) -> bool {
+    let timeout = Duration::from_millis(BUSY_TIMEOUT_MS.load(Ordering::Relaxed));
+    match busy_delay(attempt, timeout) {
+        Some(delay) => {
+            BUSY_RETRIES.fetch_add(1, Ordering::Relaxed);
+            std::thread::sleep(delay);
+            true
+        }
+        None => {
+            BUSY_TIMEOUTS.fetch_add(1, Ordering::Relaxed);
+            log::warn!("[DB] Database stayed busy for {}ms; giving up", timeout.as_millis());
+            false
+        }
+    }
+}
+
+/// Contention counters since start, for diagnostics and the settings view.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DbStats {
+    pub lock_acquisitions: u64,
+    /// Acquisitions that found the connection in use
+    pub contended_locks: u64,
+    pub lock_wait_ms_total: u64,
+    pub lock_wait_ms_max: u64,
+    /// Statements retried because another process held the database
+    pub busy_retries: u64,
+    /// Statements that failed after `busy_timeout_ms` of retries
+    pub busy_timeouts: u64,
+    /// Queued writes not yet committed
+    pub queued_writes: u64,
+    pub write_batches: u64,
+    pub batched_writes: u64,
+    pub failed_writes: u64,
+}
+
+/// The shared connection. `lock` works like `Mutex::lock` and records waits.
+pub struct Db {
+    conn: Mutex<Connection>,
+    acquisitions: AtomicU64,
+    contended: AtomicU64,
+    wait_us_total: AtomicU64,
+    wait_us_max: AtomicU64,
+}
+
+impl Db {
+    pub fn new(conn: Connection) -> Self {
+        if let Err(e) = conn.busy_handler(Some(busy_handler)) {
+            log::warn!("[DB] Failed to install busy handler: {}", e);
+        }
+        Self {
+            conn: Mutex::new(conn),
+            acquisitions: AtomicU64::new(0),
+            contended: AtomicU64::new(0),
+            wait_us_total: AtomicU64::new(0),
+            wait_us_max: AtomicU64::new(0),
+        }
+    }
+
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, Connection>> {
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        match self.conn.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(TryLockError::Poisoned(e)) => Err(e),
+            Err(TryLockError::WouldBlock) => {
+                let started = Instant::now();
+                let guard = self.conn.lock();
+                self.record_wait(started.elapsed());
+                guard
+            }
+        }
+    }
+
+    fn record_wait(&self, waited: Duration) {
+        let us = waited.as_micros() as u64;
+        self.contended.fetch_add(1, Ordering::Relaxed);
+        self.wait_us_total.fetch_add(us, Ordering::Relaxed);
+        self.wait_us_max.fetch_max(us, Ordering::Relaxed);
+        if us / 1000 > SLOW_LOCK_MS.load(Ordering::Relaxed) {
+            log::warn!("[DB] Waited {}ms for the database connection", us / 1000);
+        }
+    }
+
+    /// Lock and busy counters; the write queue adds its own.
+    pub fn stats(&self) -> DbStats {
+        DbStats {
+            lock_acquisitions: self.acquisitions.load(Ordering::Relaxed),
+            contended_locks: self.contended.load(Ordering::Relaxed),
+            lock_wait_ms_total: self.wait_us_total.load(Ordering::Relaxed) / 1000,
+            lock_wait_ms_max: self.wait_us_max.load(Ordering::Relaxed) / 1000,
+            busy_retries: BUSY_RETRIES.load(Ordering::Relaxed),
+            busy_timeouts: BUSY_TIMEOUTS.load(Ordering::Relaxed),
+            ..Default::default()
+        }
+    }
+}
+
+/// Contention counters of the app's connection and write queue.
+pub fn stats(state: &AppState) -> DbStats {
+    let mut stats = state.db.stats();
+    state.db_writes.add_stats(&mut stats);
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn busy_delays_back_off_until_the_timeout() {
+        let timeout = Duration::from_millis(5000);
+        assert_eq!(busy_delay(0, timeout), Some(Duration::from_millis(1)));
+        assert_eq!(busy_delay(3, timeout), Some(Duration::from_millis(8)));
+        assert_eq!(busy_delay(9, timeout), Some(Duration::from_millis(100)));
+        // 1+2+...+64 = 127ms waited; the next 100ms would pass 200ms
+        assert_eq!(busy_delay(6, Duration::from_millis(200)), Some(Duration::from_millis(64)));
+        assert_eq!(busy_delay(7, Duration::from_millis(200)), None);
+        assert_eq!(busy_delay(0, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn counts_contended_locks() {
+        let db = std::sync::Arc::new(Db::new(Connection::open_in_memory().unwrap()));
+        let guard = db.lock().unwrap();
+        let waiter = {
+            let db = db.clone();
+            std::thread::spawn(move || {
+                db.lock().unwrap().execute_batch("SELECT 1").unwrap();
+            })
+        };
+        std::thread::sleep(Duration::from_millis(20));
+        drop(guard);
+        waiter.join().unwrap();
+
+        let stats = db.stats();
+        assert_eq!(stats.lock_acquisitions, 2);
+        assert_eq!(stats.contended_locks, 1);
+        assert!(stats.lock_wait_ms_max >= 10);
+    }
+}
@@ -0,0 +1,83 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::event::{EventLogQuery, LoggedEvent};
+use crate::state::AppState;
+
+const DEFAULT_LIMIT: i64 = 200;
+const MAX_LIMIT: i64 = 1000;
+/// Older events are dropped once the log holds this many
+const MAX_EVENTS: i64 = 10_000;
+
+fn row_to_event(row: &rusqlite::Row) -> rusqlite::Result<LoggedEvent> {
+    let payload_json: String = row.get(3)?;
+    Ok(LoggedEvent {
+        id: row.get(0)?,
+        topic: row.get(1)?,
+        subject_id: row.get(2)?,
+        payload: serde_json::from_str(&payload_json).unwrap_or(serde_json::Value::Null),
+        created_at: row.get(4)?,
+    })
+}
+
+/// Append an event, dropping the oldest beyond `MAX_EVENTS`.
+pub fn append_event(state: &AppState, topic: &str, subject_id: Option<&str>, payload_json: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO event_log (topic, subject_id, payload_json) VALUES (?1, ?2, ?3)",
+        params![topic, subject_id, payload_json],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "DELETE FROM event_log WHERE id <= ?1",
+        params![db.last_insert_rowid() - MAX_EVENTS],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+pub fn list_events(state: &AppState, query: &EventLogQuery) -> AppResult<Vec<LoggedEvent>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+
+    let mut conditions: Vec<String> = Vec::new();
+    let mut params_vec: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    if let Some(after_id) = query.after_id {
+        params_vec.push(Box::new(after_id));
+        conditions.push(format!("id > ?{}", params_vec.len()));
+    }
+    if !query.topics.is_empty() {
+        let mut placeholders = Vec::new();
+        for topic in &query.topics {
+            params_vec.push(Box::new(topic.clone()));
+            placeholders.push(format!("?{}", params_vec.len()));
+        }
+        conditions.push(format!("topic IN ({})", placeholders.join(", ")));
+    }
+    if let Some(subject_id) = &query.subject_id {
+        params_vec.push(Box::new(subject_id.clone()));
+        conditions.push(format!("subject_id = ?{}", params_vec.len()));
+    }
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    params_vec.push(Box::new(query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)));
+    let limit_idx = params_vec.len();
+
+    let sql = format!(
+        "SELECT id, topic, subject_id, payload_json, created_at FROM event_log {where_clause} \
+         ORDER BY id LIMIT ?{limit_idx}"
+    );
+    let mut stmt = db.prepare(&sql).map_err(|e| AppError::Database(e.to_string()))?;
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let events = stmt
+        .query_map(params_refs.as_slice(), row_to_event)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(events)
+}
@@ -0,0 +1,144 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::knowledge::KnowledgeDocument;
+use crate::state::AppState;
+
+const DOCUMENT_COLS: &str = "id, workspace_id, path, title, file_type, size_bytes, chunk_count, content_hash, created_at, updated_at";
+
+fn row_to_document(row: &rusqlite::Row) -> rusqlite::Result<KnowledgeDocument> {
+    Ok(KnowledgeDocument {
+        id: row.get(0)?,
+        workspace_id: row.get(1)?,
+        path: row.get(2)?,
+        title: row.get(3)?,
+        file_type: row.get(4)?,
+        size_bytes: row.get(5)?,
+        chunk_count: row.get(6)?,
+        content_hash: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+/// A chunk row joined with its document, as used for retrieval
+pub struct ChunkRow {
+    pub document_id: String,
+    pub document_title: String,
+    pub path: String,
+    pub chunk_index: i64,
+    pub content: String,
+    pub embedding: Vec<u8>,
+}
+
+pub fn list_documents(state: &AppState, workspace_id: &str) -> AppResult<Vec<KnowledgeDocument>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!("SELECT {DOCUMENT_COLS} FROM knowledge_documents WHERE workspace_id = ?1 ORDER BY path"))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let documents = stmt
+        .query_map(params![workspace_id], row_to_document)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(documents)
+}
+
+pub fn find_document(state: &AppState, workspace_id: &str, path: &str) -> AppResult<Option<KnowledgeDocument>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let result = db.query_row(
+        &format!("SELECT {DOCUMENT_COLS} FROM knowledge_documents WHERE workspace_id = ?1 AND path = ?2"),
+        params![workspace_id, path],
+        row_to_document,
+    );
+    match result {
+        Ok(document) => Ok(Some(document)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+/// Insert or re-index a document, replacing all of its chunks.
+/// `chunks` holds (content, embedding bytes) in document order.
+#[allow(clippy::too_many_arguments)]
+pub fn save_document(
+    state: &AppState,
+    workspace_id: &str,
+    path: &str,
+    title: &str,
+    file_type: &str,
+    size_bytes: i64,
+    content_hash: &str,
+    chunks: &[(String, Vec<u8>)],
+) -> AppResult<KnowledgeDocument> {
+    let existing_id = find_document(state, workspace_id, path)?.map(|d| d.id);
+    let id = existing_id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    {
+        let mut db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+        let tx = db.transaction().map_err(|e| AppError::Database(e.to_string()))?;
+        if existing_id.is_some() {
+            tx.execute(
+                "UPDATE knowledge_documents SET title = ?1, file_type = ?2, size_bytes = ?3, chunk_count = ?4, content_hash = ?5, updated_at = datetime('now') WHERE id = ?6",
+                params![title, file_type, size_bytes, chunks.len() as i64, content_hash, id],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+            tx.execute("DELETE FROM knowledge_chunks WHERE document_id = ?1", params![id])
+                .map_err(|e| AppError::Database(e.to_string()))?;
+        } else {
+            tx.execute(
+                "INSERT INTO knowledge_documents (id, workspace_id, path, title, file_type, size_bytes, chunk_count, content_hash) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![id, workspace_id, path, title, file_type, size_bytes, chunks.len() as i64, content_hash],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+        for (index, (content, embedding)) in chunks.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO knowledge_chunks (id, document_id, workspace_id, chunk_index, content, embedding) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![uuid::Uuid::new_v4().to_string(), id, workspace_id, index as i64, content, embedding],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+        tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    }
+
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        &format!("SELECT {DOCUMENT_COLS} FROM knowledge_documents WHERE id = ?1"),
+        params![id],
+        row_to_document,
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+pub fn list_chunks(state: &AppState, workspace_id: &str) -> AppResult<Vec<ChunkRow>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(
+            "SELECT c.document_id, d.title, d.path, c.chunk_index, c.content, c.embedding \
+             FROM knowledge_chunks c JOIN knowledge_documents d ON d.id = c.document_id \
+             WHERE c.workspace_id = ?1",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let chunks = stmt
+        .query_map(params![workspace_id], |row| {
+            Ok(ChunkRow {
+                document_id: row.get(0)?,
+                document_title: row.get(1)?,
+                path: row.get(2)?,
+                chunk_index: row.get(3)?,
+                content: row.get(4)?,
+                embedding: row.get(5)?,
+            })
+        })
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(chunks)
+}
+
+pub fn delete_document(state: &AppState, id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute("DELETE FROM knowledge_documents WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::memory::Memory;
+use crate::state::AppState;
+
+const MEMORY_COLS: &str = "id, workspace_id, source_kind, source_id, title, content, created_at, updated_at";
+
+fn row_to_memory(row: &rusqlite::Row) -> rusqlite::Result<Memory> {
+    Ok(Memory {
+        id: row.get(0)?,
+        workspace_id: row.get(1)?,
+        source_kind: row.get(2)?,
+        source_id: row.get(3)?,
+        title: row.get(4)?,
+        content: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+/// Insert or replace the memory of a source (one memory per task run or chat session).
+pub fn upsert_memory(
+    state: &AppState,
+    workspace_id: Option<&str>,
+    source_kind: &str,
+    source_id: &str,
+    title: &str,
+    content: &str,
+    embedding: &[u8],
+) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO memories (id, workspace_id, source_kind, source_id, title, content, embedding) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7) \
+         ON CONFLICT(source_kind, source_id) DO UPDATE SET \
+         workspace_id = excluded.workspace_id, title = excluded.title, content = excluded.content, \
+         embedding = excluded.embedding, updated_at = datetime('now')",
+        params![uuid::Uuid::new_v4().to_string(), workspace_id, source_kind, source_id, title, content, embedding],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+pub fn get_memory_by_source(state: &AppState, source_kind: &str, source_id: &str) -> AppResult<Option<Memory>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let result = db.query_row(
+        &format!("SELECT {MEMORY_COLS} FROM memories WHERE source_kind = ?1 AND source_id = ?2"),
+        params![source_kind, source_id],
+        row_to_memory,
+    );
+    match result {
+        Ok(memory) => Ok(Some(memory)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+/// Memories, newest first. `None` lists every workspace.
+pub fn list_memories(state: &AppState, workspace_id: Option<&str>, limit: i64) -> AppResult<Vec<Memory>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+
+    let (sql, params_vec): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(ws_id) = workspace_id {
+        (
+            format!("SELECT {MEMORY_COLS} FROM memories WHERE workspace_id = ?1 ORDER BY updated_at DESC LIMIT ?2"),
+            vec![Box::new(ws_id.to_string()), Box::new(limit)],
+        )
+    } else {
+        (
+            format!("SELECT {MEMORY_COLS} FROM memories ORDER BY updated_at DESC LIMIT ?1"),
+            vec![Box::new(limit)],
+        )
+    };
+
+    let mut stmt = db.prepare(&sql).map_err(|e| AppError::Database(e.to_string()))?;
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let memories = stmt
+        .query_map(params_refs.as_slice(), row_to_memory)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(memories)
+}
+
+/// Every memory of one workspace (`None` = no workspace) with its embedding bytes.
+pub fn list_with_embeddings(state: &AppState, workspace_id: Option<&str>) -> AppResult<Vec<(Memory, Vec<u8>)>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!("SELECT {MEMORY_COLS}, embedding FROM memories WHERE workspace_id IS ?1"))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let rows = stmt
+        .query_map(params![workspace_id], |row| Ok((row_to_memory(row)?, row.get(8)?)))
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(rows)
+}
+
+pub fn delete_memory(state: &AppState, id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute("DELETE FROM memories WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Delete all memories of a workspace (`None` = every workspace). Returns the number removed.
+pub fn clear_memories(state: &AppState, workspace_id: Option<&str>) -> AppResult<usize> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let removed = match workspace_id {
+        Some(ws_id) => db.execute("DELETE FROM memories WHERE workspace_id = ?1", params![ws_id]),
+        None => db.execute("DELETE FROM memories", []),
+    };
+    removed.map_err(|e| AppError::Database(e.to_string()))
+}
@@ -1,9 +1,47 @@
 use rusqlite::params;
 
+use crate::db::stream_buffer::StreamBuffer;
 use crate::error::{AppError, AppResult};
 use crate::models::message::ChatMessage;
 use crate::state::AppState;
 
+/// Longest reaction, such as an emoji sequence or a short code.
+const MAX_REACTION_LEN: usize = 32;
+
+const MESSAGE_COLS: &str = "id, session_id, role, content_json, tool_calls_json, created_at, pinned_at, \
+     (SELECT group_concat(reaction) FROM message_reactions WHERE message_id = messages.id)";
+
+fn row_to_message(row: &rusqlite::Row) -> rusqlite::Result<ChatMessage> {
+    let reactions: Option<String> = row.get(7)?;
+    let mut reactions: Vec<String> = reactions
+        .map(|r| r.split(',').map(str::to_string).collect())
+        .unwrap_or_default();
+    reactions.sort();
+    Ok(ChatMessage {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        role: row.get(2)?,
+        content_json: row.get(3)?,
+        tool_calls_json: row.get(4)?,
+        created_at: row.get(5)?,
+        pinned_at: row.get(6)?,
+        reactions,
+    })
+}
+
+fn query_messages(state: &AppState, sql: &str, session_id: &str) -> AppResult<Vec<ChatMessage>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db.prepare(sql).map_err(|e| AppError::Database(e.to_string()))?;
+
+    let messages = stmt
+        .query_map(params![session_id], row_to_message)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(messages)
+}
+
 pub fn save_message(state: &AppState, msg: &ChatMessage) -> AppResult<()> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     db.execute(
@@ -14,28 +52,159 @@ pub fn save_message(state: &AppState, msg: &ChatMessage) -> AppResult<()> {
     Ok(())
 }
 
+/// Queue a message save on the write queue; it is committed with the next
+/// batch. A message saved before under the same id is replaced.
+pub fn queue_message(state: &AppState, msg: ChatMessage) {
+    state.db_writes.submit("message save", move |db| {
+        db.execute(
+            "INSERT INTO messages (id, session_id, role, content_json, tool_calls_json) VALUES (?1, ?2, ?3, ?4, ?5) \
+             ON CONFLICT(id) DO UPDATE SET content_json = excluded.content_json, tool_calls_json = excluded.tool_calls_json",
+            params![msg.id, msg.session_id, msg.role, msg.content_json, msg.tool_calls_json],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    });
+}
+
+/// An agent reply saved while it streams. The text so far is written under
+/// the reply's id whenever its `StreamBuffer` is due, so an interrupted reply
+/// keeps what arrived; `finish` replaces it with the final message.
+pub struct MessageStream {
+    state: AppState,
+    id: String,
+    session_id: String,
+    text: String,
+    buffer: StreamBuffer,
+}
+
+impl MessageStream {
+    pub fn new(state: &AppState, session_id: &str) -> Self {
+        Self {
+            state: state.clone(),
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: session_id.to_string(),
+            text: String::new(),
+            buffer: StreamBuffer::default(),
+        }
+    }
+
+    /// Id the final message must be saved under.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn push(&mut self, text: &str) {
+        self.text.push_str(text);
+        if self.buffer.push(text) {
+            self.buffer.take();
+            self.write();
+        }
+    }
+
+    /// Save the final message over the streamed text.
+    pub fn finish(self, msg: ChatMessage) {
+        queue_message(&self.state, msg);
+    }
+
+    /// Write text not saved yet when the stream ends without a final message.
+    pub fn close(mut self) {
+        if !self.buffer.is_empty() {
+            self.buffer.take();
+            self.write();
+        }
+    }
+
+    fn write(&self) {
+        let content_json = serde_json::json!([{ "type": "text", "text": self.text }]).to_string();
+        queue_message(
+            &self.state,
+            ChatMessage {
+                id: self.id.clone(),
+                session_id: self.session_id.clone(),
+                role: "Agent".into(),
+                content_json,
+                tool_calls_json: None,
+                created_at: String::new(),
+                pinned_at: None,
+                reactions: Vec::new(),
+            },
+        );
+    }
+}
+
 pub fn get_messages(state: &AppState, session_id: &str) -> AppResult<Vec<ChatMessage>> {
+    state.db_writes.flush();
+    query_messages(
+        state,
+        &format!("SELECT {MESSAGE_COLS} FROM messages WHERE session_id = ?1 ORDER BY created_at ASC"),
+        session_id,
+    )
+}
+
+pub fn get_message(state: &AppState, id: &str) -> AppResult<ChatMessage> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
-    let mut stmt = db
-        .prepare("SELECT id, session_id, role, content_json, tool_calls_json, created_at FROM messages WHERE session_id = ?1 ORDER BY created_at ASC")
-        .map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        &format!("SELECT {MESSAGE_COLS} FROM messages WHERE id = ?1"),
+        params![id],
+        row_to_message,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Message {id} not found")),
+        _ => AppError::Database(e.to_string()),
+    })
+}
 
-    let messages = stmt
-        .query_map(params![session_id], |row| {
-            Ok(ChatMessage {
-                id: row.get(0)?,
-                session_id: row.get(1)?,
-                role: row.get(2)?,
-                content_json: row.get(3)?,
-                tool_calls_json: row.get(4)?,
-                created_at: row.get(5)?,
-            })
-        })
-        .map_err(|e| AppError::Database(e.to_string()))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| AppError::Database(e.to_string()))?;
+/// Pinned messages of a session, oldest first.
+pub fn list_pinned_messages(state: &AppState, session_id: &str) -> AppResult<Vec<ChatMessage>> {
+    query_messages(
+        state,
+        &format!("SELECT {MESSAGE_COLS} FROM messages WHERE session_id = ?1 AND pinned_at IS NOT NULL ORDER BY created_at ASC"),
+        session_id,
+    )
+}
 
-    Ok(messages)
+pub fn set_message_pinned(state: &AppState, id: &str, pinned: bool) -> AppResult<ChatMessage> {
+    {
+        let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+        // Re-pinning keeps the original pin time
+        let sql = if pinned {
+            "UPDATE messages SET pinned_at = COALESCE(pinned_at, datetime('now')) WHERE id = ?1"
+        } else {
+            "UPDATE messages SET pinned_at = NULL WHERE id = ?1"
+        };
+        let changed = db.execute(sql, params![id]).map_err(|e| AppError::Database(e.to_string()))?;
+        if changed == 0 {
+            return Err(AppError::NotFound(format!("Message {id} not found")));
+        }
+    }
+    get_message(state, id)
+}
+
+/// Add or remove a reaction on a message.
+pub fn set_message_reaction(state: &AppState, id: &str, reaction: &str, reacted: bool) -> AppResult<ChatMessage> {
+    let reaction = reaction.trim();
+    if reaction.is_empty() || reaction.chars().count() > MAX_REACTION_LEN || reaction.contains(',') {
+        return Err(AppError::InvalidRequest(format!(
+            "A reaction must be 1-{MAX_REACTION_LEN} characters without commas"
+        )));
+    }
+    get_message(state, id)?;
+    {
+        let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+        if reacted {
+            db.execute(
+                "INSERT OR IGNORE INTO message_reactions (message_id, reaction) VALUES (?1, ?2)",
+                params![id, reaction],
+            )
+        } else {
+            db.execute(
+                "DELETE FROM message_reactions WHERE message_id = ?1 AND reaction = ?2",
+                params![id, reaction],
+            )
+        }
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    get_message(state, id)
 }
 
 pub fn delete_messages_for_session(state: &AppState, session_id: &str) -> AppResult<()> {
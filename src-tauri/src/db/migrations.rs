@@ -29,8 +29,23 @@ pub fn init_db() -> AppResult<Connection> {
     let path = get_db_path();
     let conn = Connection::open(&path)
         .map_err(|e| AppError::Database(format!("Failed to open database: {e}")))?;
+    prepare(&conn)?;
 
-    conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")
+    Ok(conn)
+}
+
+/// An in-memory database with every migration applied.
+#[cfg(test)]
+pub fn open_in_memory() -> AppResult<Connection> {
+    let conn = Connection::open_in_memory()
+        .map_err(|e| AppError::Database(format!("Failed to open database: {e}")))?;
+    prepare(&conn)?;
+    Ok(conn)
+}
+
+fn prepare(conn: &Connection) -> AppResult<()> {
+    // WAL lets readers in other processes run alongside the app's writes
+    conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL; PRAGMA foreign_keys=ON;")
         .map_err(|e| AppError::Database(format!("Failed to set pragmas: {e}")))?;
 
     // Create migration tracking table
@@ -43,9 +58,7 @@ pub fn init_db() -> AppResult<Connection> {
     )
     .map_err(|e| AppError::Database(format!("Failed to create migrations table: {e}")))?;
 
-    run_migrations(&conn)?;
-
-    Ok(conn)
+    run_migrations(conn)
 }
 
 fn run_migrations(conn: &Connection) -> AppResult<()> {
@@ -61,6 +74,68 @@ fn run_migrations(conn: &Connection) -> AppResult<()> {
         ("009_agent_skills", include_str!("../../migrations/009_agent_skills.sql")),
         ("010_workspaces", include_str!("../../migrations/010_workspaces.sql")),
         ("011_chat_tools", include_str!("../../migrations/011_chat_tools.sql")),
+        ("012_agent_context", include_str!("../../migrations/012_agent_context.sql")),
+        ("013_pipelines", include_str!("../../migrations/013_pipelines.sql")),
+        ("014_schedule_runs", include_str!("../../migrations/014_schedule_runs.sql")),
+        ("015_schedule_retry", include_str!("../../migrations/015_schedule_retry.sql")),
+        ("016_assignment_working_directory", include_str!("../../migrations/016_assignment_working_directory.sql")),
+        ("017_orchestration_templates", include_str!("../../migrations/017_orchestration_templates.sql")),
+        ("018_assignment_events", include_str!("../../migrations/018_assignment_events.sql")),
+        ("019_chat_tool_env", include_str!("../../migrations/019_chat_tool_env.sql")),
+        ("020_chat_tool_escalations", include_str!("../../migrations/020_chat_tool_escalations.sql")),
+        ("021_sync_records", include_str!("../../migrations/021_sync_records.sql")),
+        ("022_prompts", include_str!("../../migrations/022_prompts.sql")),
+        ("023_response_cache", include_str!("../../migrations/023_response_cache.sql")),
+        ("024_task_artifacts", include_str!("../../migrations/024_task_artifacts.sql")),
+        ("025_memories", include_str!("../../migrations/025_memories.sql")),
+        ("026_knowledge", include_str!("../../migrations/026_knowledge.sql")),
+        ("027_pending_confirmations", include_str!("../../migrations/027_pending_confirmations.sql")),
+        ("028_secondary_control_hub", include_str!("../../migrations/028_secondary_control_hub.sql")),
+        ("029_activity_log", include_str!("../../migrations/029_activity_log.sql")),
+        ("030_chat_tool_message_filter", include_str!("../../migrations/030_chat_tool_message_filter.sql")),
+        ("031_chat_tool_delivery_status", include_str!("../../migrations/031_chat_tool_delivery_status.sql")),
+        ("032_chat_tool_reply_delivery", include_str!("../../migrations/032_chat_tool_reply_delivery.sql")),
+        ("033_chat_tool_reply_templates", include_str!("../../migrations/033_chat_tool_reply_templates.sql")),
+        ("034_chat_tool_contact_quotas", include_str!("../../migrations/034_chat_tool_contact_quotas.sql")),
+        ("035_agent_warmup_prompt", include_str!("../../migrations/035_agent_warmup_prompt.sql")),
+        ("036_agent_manifest", include_str!("../../migrations/036_agent_manifest.sql")),
+        ("037_assignment_caps", include_str!("../../migrations/037_assignment_caps.sql")),
+        ("038_child_processes", include_str!("../../migrations/038_child_processes.sql")),
+        ("039_chat_tool_contact_snooze", include_str!("../../migrations/039_chat_tool_contact_snooze.sql")),
+        ("040_chat_tool_contact_takeover", include_str!("../../migrations/040_chat_tool_contact_takeover.sql")),
+        ("041_agent_ssh", include_str!("../../migrations/041_agent_ssh.sql")),
+        ("042_agent_container", include_str!("../../migrations/042_agent_container.sql")),
+        ("043_task_run_labels", include_str!("../../migrations/043_task_run_labels.sql")),
+        ("044_assignment_retry", include_str!("../../migrations/044_assignment_retry.sql")),
+        ("045_message_pins_reactions", include_str!("../../migrations/045_message_pins_reactions.sql")),
+        ("046_skill_cache", include_str!("../../migrations/046_skill_cache.sql")),
+        ("047_event_log", include_str!("../../migrations/047_event_log.sql")),
+        ("048_session_summary", include_str!("../../migrations/048_session_summary.sql")),
+        ("049_agent_disabled_transient", include_str!("../../migrations/049_agent_disabled_transient.sql")),
+        ("050_permission_policies", include_str!("../../migrations/050_permission_policies.sql")),
+        ("051_task_run_context_run", include_str!("../../migrations/051_task_run_context_run.sql")),
+        ("052_chat_tool_contact_groups", include_str!("../../migrations/052_chat_tool_contact_groups.sql")),
+        ("053_agent_idle_shutdown", include_str!("../../migrations/053_agent_idle_shutdown.sql")),
+        ("054_workspace_hub_persona", include_str!("../../migrations/054_workspace_hub_persona.sql")),
+        ("055_workspace_redact_outputs", include_str!("../../migrations/055_workspace_redact_outputs.sql")),
+        ("056_workspace_exclusive_runs", include_str!("../../migrations/056_workspace_exclusive_runs.sql")),
+        ("057_pinned_outputs", include_str!("../../migrations/057_pinned_outputs.sql")),
+        ("058_assignment_overrides", include_str!("../../migrations/058_assignment_overrides.sql")),
+        ("059_chat_tool_metrics", include_str!("../../migrations/059_chat_tool_metrics.sql")),
+        ("060_contact_run_notifications", include_str!("../../migrations/060_contact_run_notifications.sql")),
+        ("061_artifact_metadata", include_str!("../../migrations/061_artifact_metadata.sql")),
+        ("062_run_environment", include_str!("../../migrations/062_run_environment.sql")),
+        ("063_permission_presets", include_str!("../../migrations/063_permission_presets.sql")),
+        ("064_run_time_limits", include_str!("../../migrations/064_run_time_limits.sql")),
+        ("065_cancel_reasons", include_str!("../../migrations/065_cancel_reasons.sql")),
+        ("066_workspace_sandboxes", include_str!("../../migrations/066_workspace_sandboxes.sql")),
+        ("067_feedback_corrections", include_str!("../../migrations/067_feedback_corrections.sql")),
+        ("068_assignment_event_assignments", include_str!("../../migrations/068_assignment_event_assignments.sql")),
+        ("069_task_run_statuses", include_str!("../../migrations/069_task_run_statuses.sql")),
+        ("070_assignment_capped_status", include_str!("../../migrations/070_assignment_capped_status.sql")),
+        ("071_schedule_run_needs_review", include_str!("../../migrations/071_schedule_run_needs_review.sql")),
+        ("072_assignment_event_notices", include_str!("../../migrations/072_assignment_event_notices.sql")),
+        ("073_child_process_app_start", include_str!("../../migrations/073_child_process_app_start.sql")),
     ];
 
     for (name, sql) in migrations {
@@ -153,3 +228,79 @@ fn run_workspace_migration(conn: &Connection, base_sql: &str) -> AppResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::{assignment_caps, fallback_planner};
+    use crate::db::task_run_repo;
+    use crate::state::AppState;
+
+    fn migrated_state() -> AppState {
+        let conn = open_in_memory().unwrap();
+        conn.execute("INSERT INTO agents (id, name) VALUES ('hub', 'Hub'), ('worker', 'Worker')", [])
+            .unwrap();
+        AppState::new(conn)
+    }
+
+    #[test]
+    fn task_runs_accept_every_run_status() {
+        let state = migrated_state();
+        task_run_repo::create_task_run(&state, "run-1", "Title", "Prompt", "hub", "pending", None).unwrap();
+        for status in ["waiting_for_workspace", "awaiting_plan_approval", "needs_review"] {
+            task_run_repo::update_task_run_status(&state, "run-1", status).unwrap();
+            assert_eq!(task_run_repo::get_task_run(&state, "run-1").unwrap().status, status);
+        }
+    }
+
+    #[test]
+    fn task_runs_can_be_served_by_the_builtin_planner() {
+        let state = migrated_state();
+        let run = task_run_repo::create_task_run(
+            &state, "run-1", "Title", "Prompt", fallback_planner::BUILTIN_PLANNER_ID, "pending", None,
+        )
+        .unwrap();
+        assert!(fallback_planner::is_builtin(&run.control_hub_agent_id));
+    }
+
+    #[test]
+    fn assignments_can_end_capped() {
+        let state = migrated_state();
+        task_run_repo::create_task_run(&state, "run-1", "Title", "Prompt", "hub", "running", None).unwrap();
+        task_run_repo::create_task_assignment(&state, "a-1", "run-1", "worker", "Worker", 0, "Input").unwrap();
+        task_run_repo::update_task_assignment(
+            &state, "a-1", assignment_caps::CAPPED_STATUS, Some("Partial"), None, 10, 500, 0, 0, 1_000, None,
+        )
+        .unwrap();
+        assert_eq!(task_run_repo::get_assignment(&state, "a-1").unwrap().status, assignment_caps::CAPPED_STATUS);
+    }
+
+    #[test]
+    fn rebuilding_task_runs_keeps_referencing_rows() {
+        let state = migrated_state();
+        task_run_repo::create_task_run(&state, "run-1", "Title", "Prompt", "hub", "pending", None).unwrap();
+        let db = state.db.lock().unwrap();
+        db.execute(
+            "INSERT INTO task_assignments (id, task_run_id, agent_id) VALUES ('a-1', 'run-1', 'worker')",
+            [],
+        )
+        .unwrap();
+        let violations: i64 = db
+            .query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(violations, 0);
+        db.execute("DELETE FROM task_runs WHERE id = 'run-1'", []).unwrap();
+        let assignments: i64 = db
+            .query_row("SELECT COUNT(*) FROM task_assignments", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(assignments, 0, "deleting a run still cascades to its assignments");
+        let workspace_index: i64 = db
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'idx_task_runs_workspace'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(workspace_index, 1, "the rebuild keeps the workspace index");
+    }
+}
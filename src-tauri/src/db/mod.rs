@@ -1,9 +1,30 @@
+pub mod activity_repo;
+pub mod agent_context_repo;
+pub mod artifact_repo;
+pub mod assignment_event_repo;
+pub mod agent_manifest;
 pub mod agent_md;
 pub mod agent_repo;
 pub mod chat_tool_repo;
+pub mod event_log_repo;
+pub mod child_process_repo;
+pub mod connection;
+pub mod knowledge_repo;
+pub mod memory_repo;
 pub mod message_repo;
 pub mod migrations;
+pub mod permission_policy_repo;
+pub mod pinned_output_repo;
+pub mod pipeline_repo;
+pub mod prompt_repo;
+pub mod response_cache_repo;
+pub mod schedule_run_repo;
 pub mod session_repo;
 pub mod settings_repo;
+pub mod skill_cache_repo;
+pub mod stream_buffer;
+pub mod sync_repo;
 pub mod task_run_repo;
+pub mod template_repo;
 pub mod workspace_repo;
+pub mod write_queue;
@@ -0,0 +1,75 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::permission::PermissionPolicy;
+use crate::state::AppState;
+
+const POLICY_COLS: &str = "id, agent_id, request_key, allow, created_at";
+
+fn row_to_policy(row: &rusqlite::Row) -> rusqlite::Result<PermissionPolicy> {
+    Ok(PermissionPolicy {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        request_key: row.get(2)?,
+        allow: row.get::<_, i32>(3)? != 0,
+        created_at: row.get(4)?,
+    })
+}
+
+/// The remembered decision of an agent for requests with this key.
+pub fn get_decision(state: &AppState, agent_id: &str, request_key: &str) -> AppResult<Option<bool>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let result = db.query_row(
+        "SELECT allow FROM permission_policies WHERE agent_id = ?1 AND request_key = ?2",
+        params![agent_id, request_key],
+        |row| row.get::<_, i32>(0),
+    );
+    match result {
+        Ok(allow) => Ok(Some(allow != 0)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+/// Remember a decision, replacing the agent's previous one for the key.
+pub fn set_policy(state: &AppState, agent_id: &str, request_key: &str, allow: bool) -> AppResult<PermissionPolicy> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO permission_policies (id, agent_id, request_key, allow) VALUES (?1, ?2, ?3, ?4) \
+         ON CONFLICT(agent_id, request_key) DO UPDATE SET allow = excluded.allow, created_at = datetime('now')",
+        params![uuid::Uuid::new_v4().to_string(), agent_id, request_key, allow as i32],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        &format!("SELECT {POLICY_COLS} FROM permission_policies WHERE agent_id = ?1 AND request_key = ?2"),
+        params![agent_id, request_key],
+        row_to_policy,
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+pub fn list_policies(state: &AppState, agent_id: Option<&str>) -> AppResult<Vec<PermissionPolicy>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {POLICY_COLS} FROM permission_policies WHERE ?1 IS NULL OR agent_id = ?1 ORDER BY agent_id, request_key"
+        ))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let policies = stmt
+        .query_map(params![agent_id], row_to_policy)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(policies)
+}
+
+pub fn delete_policy(state: &AppState, id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let changed = db
+        .execute("DELETE FROM permission_policies WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    if changed == 0 {
+        return Err(AppError::NotFound(format!("Permission policy {id}")));
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::task_run::PinnedOutput;
+use crate::state::AppState;
+
+const PINNED_COLS: &str = "id, task_run_id, source, assignment_id, agent_id, label, content, created_at";
+
+fn row_to_pinned(row: &rusqlite::Row) -> rusqlite::Result<PinnedOutput> {
+    Ok(PinnedOutput {
+        id: row.get(0)?,
+        task_run_id: row.get(1)?,
+        source: row.get(2)?,
+        assignment_id: row.get(3)?,
+        agent_id: row.get(4)?,
+        label: row.get(5)?,
+        content: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+pub fn create_pinned_output(
+    state: &AppState,
+    task_run_id: &str,
+    source: &str,
+    assignment_id: Option<&str>,
+    agent_id: Option<&str>,
+    label: &str,
+    content: &str,
+) -> AppResult<PinnedOutput> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO pinned_outputs (id, task_run_id, source, assignment_id, agent_id, label, content) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![id, task_run_id, source, assignment_id, agent_id, label, content],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+
+    db.query_row(
+        &format!("SELECT {PINNED_COLS} FROM pinned_outputs WHERE id = ?1"),
+        params![id],
+        row_to_pinned,
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+pub fn list_pinned_outputs(state: &AppState, task_run_id: &str) -> AppResult<Vec<PinnedOutput>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!("SELECT {PINNED_COLS} FROM pinned_outputs WHERE task_run_id = ?1 ORDER BY created_at, rowid"))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let pinned = stmt
+        .query_map(params![task_run_id], row_to_pinned)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(pinned)
+}
+
+/// The pinned outputs with these ids, in the given order; unknown ids are skipped.
+pub fn get_pinned_outputs(state: &AppState, ids: &[String]) -> AppResult<Vec<PinnedOutput>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!("SELECT {PINNED_COLS} FROM pinned_outputs WHERE id = ?1"))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let mut pinned = Vec::new();
+    for id in ids {
+        match stmt.query_row(params![id], row_to_pinned) {
+            Ok(output) => pinned.push(output),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {}
+            Err(e) => return Err(AppError::Database(e.to_string())),
+        }
+    }
+    Ok(pinned)
+}
+
+pub fn delete_pinned_output(state: &AppState, id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let deleted = db
+        .execute("DELETE FROM pinned_outputs WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Pinned output {} not found", id)));
+    }
+    Ok(())
+}
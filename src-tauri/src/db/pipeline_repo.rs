@@ -0,0 +1,212 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::pipeline::{CreatePipelineRequest, Pipeline, PipelineRun, UpdatePipelineRequest};
+use crate::state::AppState;
+
+const PIPELINE_COLS: &str = "id, name, description, stages_json, workspace_id, created_at, updated_at";
+const PIPELINE_RUN_COLS: &str = "id, pipeline_id, status, current_stage, initial_input, stage_task_run_ids_json, error_message, workspace_id, created_at, updated_at";
+
+fn row_to_pipeline(row: &rusqlite::Row) -> rusqlite::Result<Pipeline> {
+    Ok(Pipeline {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        stages_json: row.get(3)?,
+        workspace_id: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+fn row_to_pipeline_run(row: &rusqlite::Row) -> rusqlite::Result<PipelineRun> {
+    Ok(PipelineRun {
+        id: row.get(0)?,
+        pipeline_id: row.get(1)?,
+        status: row.get(2)?,
+        current_stage: row.get(3)?,
+        initial_input: row.get(4)?,
+        stage_task_run_ids_json: row.get(5)?,
+        error_message: row.get(6)?,
+        workspace_id: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+pub fn list_pipelines(state: &AppState, workspace_id: Option<&str>) -> AppResult<Vec<Pipeline>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+
+    let (sql, params_vec): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(ws_id) = workspace_id {
+        (
+            format!("SELECT {PIPELINE_COLS} FROM pipelines WHERE workspace_id = ?1 ORDER BY created_at DESC"),
+            vec![Box::new(ws_id.to_string())],
+        )
+    } else {
+        (
+            format!("SELECT {PIPELINE_COLS} FROM pipelines ORDER BY created_at DESC"),
+            vec![],
+        )
+    };
+
+    let mut stmt = db.prepare(&sql).map_err(|e| AppError::Database(e.to_string()))?;
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let pipelines = stmt
+        .query_map(params_refs.as_slice(), row_to_pipeline)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(pipelines)
+}
+
+pub fn get_pipeline(state: &AppState, id: &str) -> AppResult<Pipeline> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        &format!("SELECT {PIPELINE_COLS} FROM pipelines WHERE id = ?1"),
+        params![id],
+        row_to_pipeline,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Pipeline {id} not found")),
+        _ => AppError::Database(e.to_string()),
+    })
+}
+
+pub fn create_pipeline(state: &AppState, req: CreatePipelineRequest) -> AppResult<Pipeline> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let stages_json = serde_json::to_string(&req.stages)?;
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+
+    db.execute(
+        "INSERT INTO pipelines (id, name, description, stages_json, workspace_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![id, req.name, req.description, stages_json, req.workspace_id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+
+    drop(db);
+    get_pipeline(state, &id)
+}
+
+pub fn update_pipeline(state: &AppState, id: &str, req: UpdatePipelineRequest) -> AppResult<Pipeline> {
+    let existing = get_pipeline(state, id)?;
+    let name = req.name.unwrap_or(existing.name);
+    let description = req.description.unwrap_or(existing.description);
+    let stages_json = match req.stages {
+        Some(stages) => serde_json::to_string(&stages)?,
+        None => existing.stages_json,
+    };
+
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE pipelines SET name = ?1, description = ?2, stages_json = ?3, updated_at = datetime('now') WHERE id = ?4",
+        params![name, description, stages_json, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+
+    drop(db);
+    get_pipeline(state, id)
+}
+
+pub fn delete_pipeline(state: &AppState, id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute("DELETE FROM pipelines WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+// ============== Pipeline runs ==============
+
+/// Mark pipeline runs left active by a previous session as failed.
+/// Their driver task is gone; the individual stage task runs resume on their own.
+pub fn reset_stale_pipeline_runs(state: &AppState) -> AppResult<u64> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let count = db
+        .execute(
+            "UPDATE pipeline_runs SET status = 'failed', error_message = 'Interrupted by app restart', updated_at = datetime('now') \
+             WHERE status IN ('pending', 'running', 'awaiting_approval')",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(count as u64)
+}
+
+pub fn create_pipeline_run(
+    state: &AppState,
+    id: &str,
+    pipeline_id: &str,
+    initial_input: &str,
+    workspace_id: Option<&str>,
+) -> AppResult<PipelineRun> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO pipeline_runs (id, pipeline_id, status, initial_input, workspace_id) VALUES (?1, ?2, 'pending', ?3, ?4)",
+        params![id, pipeline_id, initial_input, workspace_id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+
+    drop(db);
+    get_pipeline_run(state, id)
+}
+
+pub fn get_pipeline_run(state: &AppState, id: &str) -> AppResult<PipelineRun> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        &format!("SELECT {PIPELINE_RUN_COLS} FROM pipeline_runs WHERE id = ?1"),
+        params![id],
+        row_to_pipeline_run,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Pipeline run {id} not found")),
+        _ => AppError::Database(e.to_string()),
+    })
+}
+
+pub fn list_pipeline_runs(state: &AppState, pipeline_id: &str) -> AppResult<Vec<PipelineRun>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {PIPELINE_RUN_COLS} FROM pipeline_runs WHERE pipeline_id = ?1 ORDER BY created_at DESC"
+        ))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let runs = stmt
+        .query_map(params![pipeline_id], row_to_pipeline_run)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(runs)
+}
+
+pub fn update_pipeline_run_status(
+    state: &AppState,
+    id: &str,
+    status: &str,
+    error_message: Option<&str>,
+) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE pipeline_runs SET status = ?1, error_message = ?2, updated_at = datetime('now') WHERE id = ?3",
+        params![status, error_message, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Record that a stage has started with the given task run.
+pub fn record_pipeline_stage(
+    state: &AppState,
+    id: &str,
+    stage_index: i64,
+    stage_task_run_ids_json: &str,
+) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE pipeline_runs SET current_stage = ?1, stage_task_run_ids_json = ?2, updated_at = datetime('now') WHERE id = ?3",
+        params![stage_index, stage_task_run_ids_json, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::prompt::{CreatePromptRequest, Prompt, PromptVersion, UpdatePromptRequest};
+use crate::state::AppState;
+
+const PROMPT_COLS: &str = "p.id, p.name, p.description, p.tags_json, p.current_version, v.content, p.workspace_id, p.created_at, p.updated_at";
+const PROMPT_FROM: &str = "prompts p JOIN prompt_versions v ON v.prompt_id = p.id AND v.version = p.current_version";
+
+fn row_to_prompt(row: &rusqlite::Row) -> rusqlite::Result<Prompt> {
+    Ok(Prompt {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        tags_json: row.get(3)?,
+        current_version: row.get(4)?,
+        content: row.get(5)?,
+        workspace_id: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+fn row_to_version(row: &rusqlite::Row) -> rusqlite::Result<PromptVersion> {
+    Ok(PromptVersion {
+        prompt_id: row.get(0)?,
+        version: row.get(1)?,
+        content: row.get(2)?,
+        created_at: row.get(3)?,
+    })
+}
+
+/// List prompts, optionally limited to a workspace and to prompts carrying `tag`.
+pub fn list_prompts(state: &AppState, workspace_id: Option<&str>, tag: Option<&str>) -> AppResult<Vec<Prompt>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+
+    let (sql, params_vec): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(ws_id) = workspace_id {
+        (
+            format!("SELECT {PROMPT_COLS} FROM {PROMPT_FROM} WHERE p.workspace_id = ?1 ORDER BY p.name"),
+            vec![Box::new(ws_id.to_string())],
+        )
+    } else {
+        (format!("SELECT {PROMPT_COLS} FROM {PROMPT_FROM} ORDER BY p.name"), vec![])
+    };
+
+    let mut stmt = db.prepare(&sql).map_err(|e| AppError::Database(e.to_string()))?;
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let prompts = stmt
+        .query_map(params_refs.as_slice(), row_to_prompt)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(match tag {
+        Some(tag) => prompts.into_iter().filter(|p| p.tags().iter().any(|t| t == tag)).collect(),
+        None => prompts,
+    })
+}
+
+pub fn get_prompt(state: &AppState, id: &str) -> AppResult<Prompt> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        &format!("SELECT {PROMPT_COLS} FROM {PROMPT_FROM} WHERE p.id = ?1"),
+        params![id],
+        row_to_prompt,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Prompt {id} not found")),
+        _ => AppError::Database(e.to_string()),
+    })
+}
+
+/// Versions of a prompt, newest first.
+pub fn list_versions(state: &AppState, prompt_id: &str) -> AppResult<Vec<PromptVersion>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare("SELECT prompt_id, version, content, created_at FROM prompt_versions WHERE prompt_id = ?1 ORDER BY version DESC")
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let versions = stmt
+        .query_map(params![prompt_id], row_to_version)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(versions)
+}
+
+pub fn get_version(state: &AppState, prompt_id: &str, version: i64) -> AppResult<PromptVersion> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        "SELECT prompt_id, version, content, created_at FROM prompt_versions WHERE prompt_id = ?1 AND version = ?2",
+        params![prompt_id, version],
+        row_to_version,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => {
+            AppError::NotFound(format!("Prompt {prompt_id} has no version {version}"))
+        }
+        _ => AppError::Database(e.to_string()),
+    })
+}
+
+pub fn create_prompt(state: &AppState, req: &CreatePromptRequest) -> AppResult<Prompt> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let tags_json = serde_json::to_string(&req.tags)?;
+    {
+        let mut db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+        let tx = db.transaction().map_err(|e| AppError::Database(e.to_string()))?;
+        tx.execute(
+            "INSERT INTO prompts (id, name, description, tags_json, current_version, workspace_id) VALUES (?1, ?2, ?3, ?4, 1, ?5)",
+            params![id, req.name, req.description, tags_json, req.workspace_id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        tx.execute(
+            "INSERT INTO prompt_versions (prompt_id, version, content) VALUES (?1, 1, ?2)",
+            params![id, req.content],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    get_prompt(state, &id)
+}
+
+pub fn update_prompt(state: &AppState, id: &str, req: &UpdatePromptRequest) -> AppResult<Prompt> {
+    let existing = get_prompt(state, id)?;
+    let name = req.name.as_deref().unwrap_or(&existing.name);
+    let description = req.description.as_deref().unwrap_or(&existing.description);
+    let tags_json = match &req.tags {
+        Some(tags) => serde_json::to_string(tags)?,
+        None => existing.tags_json.clone(),
+    };
+    let new_content = req.content.as_deref().filter(|c| *c != existing.content);
+    let version = existing.current_version + i64::from(new_content.is_some());
+
+    {
+        let mut db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+        let tx = db.transaction().map_err(|e| AppError::Database(e.to_string()))?;
+        if let Some(content) = new_content {
+            tx.execute(
+                "INSERT INTO prompt_versions (prompt_id, version, content) VALUES (?1, ?2, ?3)",
+                params![id, version, content],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+        tx.execute(
+            "UPDATE prompts SET name = ?1, description = ?2, tags_json = ?3, current_version = ?4, updated_at = datetime('now') WHERE id = ?5",
+            params![name, description, tags_json, version, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    get_prompt(state, id)
+}
+
+pub fn delete_prompt(state: &AppState, id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute("DELETE FROM prompts WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
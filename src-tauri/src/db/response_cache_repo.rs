@@ -0,0 +1,62 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// Cached output for an input, if it was stored less than `max_age_secs` ago.
+pub fn get_response(
+    state: &AppState,
+    agent_id: &str,
+    model: &str,
+    input_hash: &str,
+    max_age_secs: i64,
+) -> AppResult<Option<String>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let result = db.query_row(
+        "SELECT output_text FROM response_cache \
+         WHERE agent_id = ?1 AND model = ?2 AND input_hash = ?3 \
+         AND created_at > datetime('now', ?4)",
+        params![agent_id, model, input_hash, format!("-{} seconds", max_age_secs)],
+        |row| row.get(0),
+    );
+    match result {
+        Ok(output) => Ok(Some(output)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+/// Store a response, replacing any older entry for the same input.
+pub fn save_response(
+    state: &AppState,
+    agent_id: &str,
+    model: &str,
+    input_hash: &str,
+    output_text: &str,
+    source_assignment_id: &str,
+) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT OR REPLACE INTO response_cache (agent_id, model, input_hash, output_text, source_assignment_id) \
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![agent_id, model, input_hash, output_text, source_assignment_id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Drop entries older than `max_age_secs`. Returns the number removed.
+pub fn prune(state: &AppState, max_age_secs: i64) -> AppResult<usize> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "DELETE FROM response_cache WHERE created_at <= datetime('now', ?1)",
+        params![format!("-{} seconds", max_age_secs)],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))
+}
+
+pub fn clear(state: &AppState) -> AppResult<usize> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute("DELETE FROM response_cache", [])
+        .map_err(|e| AppError::Database(e.to_string()))
+}
@@ -0,0 +1,185 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::task_run::{ScheduleRun, ScheduleStats};
+use crate::state::AppState;
+
+const SCHEDULE_RUN_COLS: &str = "id, schedule_task_run_id, task_run_id, scheduled_for, fired_at, finished_at, outcome, skipped_reason, error_message, duration_ms, attempt";
+
+fn row_to_schedule_run(row: &rusqlite::Row) -> rusqlite::Result<ScheduleRun> {
+    Ok(ScheduleRun {
+        id: row.get(0)?,
+        schedule_task_run_id: row.get(1)?,
+        task_run_id: row.get(2)?,
+        scheduled_for: row.get(3)?,
+        fired_at: row.get(4)?,
+        finished_at: row.get(5)?,
+        outcome: row.get(6)?,
+        skipped_reason: row.get(7)?,
+        error_message: row.get(8)?,
+        duration_ms: row.get(9)?,
+        attempt: row.get(10)?,
+    })
+}
+
+fn row_to_schedule_stats(row: &rusqlite::Row) -> rusqlite::Result<ScheduleStats> {
+    let completed: i64 = row.get(2)?;
+    let failed: i64 = row.get(3)?;
+    let cancelled: i64 = row.get(4)?;
+    let finished = completed + failed + cancelled;
+    Ok(ScheduleStats {
+        schedule_task_run_id: row.get(0)?,
+        total_runs: row.get(1)?,
+        completed,
+        failed,
+        cancelled,
+        skipped: row.get(5)?,
+        success_rate: (finished > 0).then(|| completed as f64 / finished as f64),
+        avg_duration_ms: row.get(6)?,
+        last_fired_at: row.get(7)?,
+        last_outcome: row.get(8)?,
+    })
+}
+
+/// Record that a scheduled task fired (or was retried). Returns the new schedule run id.
+pub fn record_schedule_fired(
+    state: &AppState,
+    schedule_task_run_id: &str,
+    task_run_id: &str,
+    scheduled_for: Option<&str>,
+    attempt: i64,
+) -> AppResult<String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO schedule_runs (id, schedule_task_run_id, task_run_id, scheduled_for, outcome, attempt) \
+         VALUES (?1, ?2, ?3, ?4, 'running', ?5)",
+        params![id, schedule_task_run_id, task_run_id, scheduled_for, attempt],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(id)
+}
+
+/// Record the outcome of a fired occurrence.
+pub fn finish_schedule_run(
+    state: &AppState,
+    id: &str,
+    outcome: &str,
+    error_message: Option<&str>,
+) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE schedule_runs SET outcome = ?1, error_message = ?2, finished_at = datetime('now'), \
+         duration_ms = CAST((julianday('now') - julianday(fired_at)) * 86400000 AS INTEGER) \
+         WHERE id = ?3",
+        params![outcome, error_message, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Record that a due occurrence was skipped. The scheduler sees an occurrence
+/// as due on every tick until it runs, so a skip is recorded only once per
+/// (schedule, scheduled_for, reason).
+pub fn record_schedule_skipped(
+    state: &AppState,
+    schedule_task_run_id: &str,
+    scheduled_for: Option<&str>,
+    reason: &str,
+) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO schedule_runs (id, schedule_task_run_id, scheduled_for, outcome, skipped_reason, finished_at) \
+         SELECT ?1, ?2, ?3, 'skipped', ?4, datetime('now') \
+         WHERE NOT EXISTS ( \
+             SELECT 1 FROM schedule_runs WHERE schedule_task_run_id = ?2 \
+             AND scheduled_for IS ?3 AND outcome = 'skipped' AND skipped_reason = ?4)",
+        params![uuid::Uuid::new_v4().to_string(), schedule_task_run_id, scheduled_for, reason],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Mark occurrences left running by a previous session as failed.
+pub fn reset_stale_schedule_runs(state: &AppState) -> AppResult<u64> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let count = db
+        .execute(
+            "UPDATE schedule_runs SET outcome = 'failed', error_message = 'Interrupted by app restart', \
+             finished_at = datetime('now') WHERE outcome = 'running'",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(count as u64)
+}
+
+/// List fired occurrences, newest first. With no schedule given, all schedules are included.
+pub fn list_schedule_history(
+    state: &AppState,
+    schedule_task_run_id: Option<&str>,
+    limit: i64,
+) -> AppResult<Vec<ScheduleRun>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+
+    let (sql, params_vec): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(id) = schedule_task_run_id {
+        (
+            format!("SELECT {SCHEDULE_RUN_COLS} FROM schedule_runs WHERE schedule_task_run_id = ?1 ORDER BY fired_at DESC LIMIT ?2"),
+            vec![Box::new(id.to_string()), Box::new(limit)],
+        )
+    } else {
+        (
+            format!("SELECT {SCHEDULE_RUN_COLS} FROM schedule_runs ORDER BY fired_at DESC LIMIT ?1"),
+            vec![Box::new(limit)],
+        )
+    };
+
+    let mut stmt = db.prepare(&sql).map_err(|e| AppError::Database(e.to_string()))?;
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let runs = stmt
+        .query_map(params_refs.as_slice(), row_to_schedule_run)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(runs)
+}
+
+/// Per-schedule reliability statistics. With no schedule given, stats for
+/// every schedule with recorded history are returned, least reliable first.
+pub fn get_schedule_stats(state: &AppState, schedule_task_run_id: Option<&str>) -> AppResult<Vec<ScheduleStats>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+
+    let sql = format!(
+        "SELECT sr.schedule_task_run_id, \
+                COUNT(*), \
+                SUM(CASE WHEN sr.outcome = 'completed' THEN 1 ELSE 0 END), \
+                SUM(CASE WHEN sr.outcome = 'failed' THEN 1 ELSE 0 END), \
+                SUM(CASE WHEN sr.outcome = 'cancelled' THEN 1 ELSE 0 END), \
+                SUM(CASE WHEN sr.outcome = 'skipped' THEN 1 ELSE 0 END), \
+                AVG(CASE WHEN sr.outcome IN ('completed', 'failed', 'cancelled') THEN sr.duration_ms END), \
+                MAX(sr.fired_at), \
+                (SELECT outcome FROM schedule_runs l WHERE l.schedule_task_run_id = sr.schedule_task_run_id \
+                 ORDER BY l.fired_at DESC LIMIT 1) \
+         FROM schedule_runs sr {} \
+         GROUP BY sr.schedule_task_run_id",
+        if schedule_task_run_id.is_some() { "WHERE sr.schedule_task_run_id = ?1" } else { "" }
+    );
+
+    let mut stmt = db.prepare(&sql).map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stats = match schedule_task_run_id {
+        Some(id) => stmt.query_map(params![id], row_to_schedule_stats),
+        None => stmt.query_map([], row_to_schedule_stats),
+    }
+    .map_err(|e| AppError::Database(e.to_string()))?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| AppError::Database(e.to_string()))?;
+
+    stats.sort_by(|a, b| {
+        a.success_rate
+            .unwrap_or(1.0)
+            .partial_cmp(&b.success_rate.unwrap_or(1.0))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(stats)
+}
@@ -1,6 +1,7 @@
 use rusqlite::params;
 
 use crate::error::{AppError, AppResult};
+use crate::models::bulk::BulkItemResult;
 use crate::models::session::{CreateSessionRequest, Session};
 use crate::state::AppState;
 
@@ -14,10 +15,14 @@ fn row_to_session(row: &rusqlite::Row) -> rusqlite::Result<Session> {
         created_at: row.get(5)?,
         updated_at: row.get(6)?,
         workspace_id: row.get(7)?,
+        summary: row.get(8)?,
+        summary_message_count: row.get(9)?,
+        summary_updated_at: row.get(10)?,
     })
 }
 
-const SESSION_COLS: &str = "id, agent_id, title, mode, acp_session_id, created_at, updated_at, workspace_id";
+const SESSION_COLS: &str = "id, agent_id, title, mode, acp_session_id, created_at, updated_at, workspace_id, \
+                            summary, summary_message_count, summary_updated_at";
 
 pub fn create_session(state: &AppState, req: CreateSessionRequest) -> AppResult<Session> {
     let id = uuid::Uuid::new_v4().to_string();
@@ -80,6 +85,25 @@ pub fn delete_session(state: &AppState, id: &str) -> AppResult<()> {
     Ok(())
 }
 
+/// Delete several sessions (and their messages) in one transaction.
+pub fn delete_sessions(state: &AppState, ids: &[String]) -> AppResult<Vec<BulkItemResult>> {
+    let mut db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let tx = db.transaction().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut results = Vec::with_capacity(ids.len());
+    for id in ids {
+        let changed = tx
+            .execute("DELETE FROM sessions WHERE id = ?1", params![id])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        results.push(if changed == 0 {
+            BulkItemResult::failed(id, format!("Session {id} not found"))
+        } else {
+            BulkItemResult::ok(id)
+        });
+    }
+    tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(results)
+}
+
 pub fn update_session_acp_id(state: &AppState, id: &str, acp_session_id: &str) -> AppResult<()> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     db.execute(
@@ -89,3 +113,20 @@ pub fn update_session_acp_id(state: &AppState, id: &str, acp_session_id: &str) -
     .map_err(|e| AppError::Database(e.to_string()))?;
     Ok(())
 }
+
+/// Store the rolling summary of a session and how many messages it covers.
+pub fn update_session_summary(state: &AppState, id: &str, summary: &str, message_count: i64) -> AppResult<Session> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let changed = db
+        .execute(
+            "UPDATE sessions SET summary = ?1, summary_message_count = ?2, summary_updated_at = datetime('now') \
+             WHERE id = ?3",
+            params![summary, message_count, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    if changed == 0 {
+        return Err(AppError::NotFound(format!("Session {id} not found")));
+    }
+    drop(db);
+    get_session(state, id)
+}
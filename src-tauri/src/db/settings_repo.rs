@@ -55,3 +55,10 @@ pub fn get_all_settings(state: &AppState) -> AppResult<Vec<AppSettings>> {
 
     Ok(settings)
 }
+
+pub fn delete_setting(state: &AppState, key: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute("DELETE FROM settings WHERE key = ?1", params![key])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
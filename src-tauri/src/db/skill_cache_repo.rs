@@ -0,0 +1,43 @@
+use rusqlite::params;
+
+use crate::acp::skill_discovery::SkillDiscoveryResult;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// A stored skill scan with the directory and fingerprint it was made for.
+pub struct StoredScan {
+    pub working_directory: String,
+    pub fingerprint: String,
+    pub result: SkillDiscoveryResult,
+}
+
+/// The stored scan for a workspace key. An entry that no longer parses is
+/// treated as missing.
+pub fn get_scan(state: &AppState, workspace_key: &str) -> AppResult<Option<StoredScan>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let result = db.query_row(
+        "SELECT working_directory, fingerprint, result_json FROM skill_cache WHERE workspace_key = ?1",
+        params![workspace_key],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)),
+    );
+    match result {
+        Ok((working_directory, fingerprint, json)) => Ok(serde_json::from_str(&json)
+            .ok()
+            .map(|result| StoredScan { working_directory, fingerprint, result })),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+/// Store a scan, replacing the workspace key's previous one.
+pub fn save_scan(state: &AppState, workspace_key: &str, scan: &StoredScan) -> AppResult<()> {
+    let json = serde_json::to_string(&scan.result)?;
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT OR REPLACE INTO skill_cache (workspace_key, working_directory, fingerprint, result_json, updated_at) \
+         VALUES (?1, ?2, ?3, ?4, datetime('now'))",
+        params![workspace_key, scan.working_directory, scan.fingerprint, json],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! Write-behind buffering of streamed text.
+//!
+//! A long agent stream arrives as hundreds of small chunks. `StreamBuffer`
+//! collects them and says when they are due for a write: once
+//! `database.stream_flush_ms` has passed since the last one, or once
+//! `MAX_PENDING_BYTES` wait. Owners write whatever is left when the stream
+//! ends. Chat replies use it through `message_repo::MessageStream`, assignment
+//! output through `acp::timeline::TimelineRecorder`.
+
+use std::time::{Duration, Instant};
+
+use crate::db::connection;
+
+/// Pending text that is written regardless of the interval.
+const MAX_PENDING_BYTES: usize = 64 * 1024;
+
+pub struct StreamBuffer {
+    pending: String,
+    last_flush: Instant,
+    interval: Duration,
+}
+
+impl Default for StreamBuffer {
+    fn default() -> Self {
+        Self::with_interval(connection::stream_flush_interval())
+    }
+}
+
+impl StreamBuffer {
+    pub fn with_interval(interval: Duration) -> Self {
+        Self {
+            pending: String::new(),
+            last_flush: Instant::now(),
+            interval,
+        }
+    }
+
+    /// Add a chunk; true when the pending text is due for a write.
+    pub fn push(&mut self, text: &str) -> bool {
+        self.pending.push_str(text);
+        self.is_due()
+    }
+
+    pub fn is_due(&self) -> bool {
+        !self.pending.is_empty()
+            && (self.pending.len() >= MAX_PENDING_BYTES || self.last_flush.elapsed() >= self.interval)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// The pending text, leaving the buffer empty and the interval restarted.
+    pub fn take(&mut self) -> String {
+        self.last_flush = Instant::now();
+        std::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn due_after_the_interval_or_when_large() {
+        let mut buffer = StreamBuffer::with_interval(Duration::from_secs(60));
+        assert!(!buffer.push("Hello"));
+        assert!(buffer.push(&"x".repeat(MAX_PENDING_BYTES)));
+        assert_eq!(buffer.take().len(), 5 + MAX_PENDING_BYTES);
+        assert!(buffer.is_empty());
+        assert!(!buffer.is_due());
+
+        let mut buffer = StreamBuffer::with_interval(Duration::ZERO);
+        assert!(!buffer.is_due());
+        assert!(buffer.push("a"));
+    }
+}
@@ -0,0 +1,161 @@
+use rusqlite::params;
+use rusqlite::types::Value;
+
+use crate::error::{AppError, AppResult};
+use crate::models::sync::SyncRecordState;
+use crate::state::AppState;
+
+const SYNC_RECORD_COLS: &str = "entity, entity_id, clock_json, content_hash, deleted, modified_at, modified_by";
+
+fn row_to_sync_record(row: &rusqlite::Row) -> rusqlite::Result<SyncRecordState> {
+    let clock_json: String = row.get(2)?;
+    Ok(SyncRecordState {
+        entity: row.get(0)?,
+        entity_id: row.get(1)?,
+        clock: serde_json::from_str(&clock_json).unwrap_or_default(),
+        content_hash: row.get(3)?,
+        deleted: row.get::<_, i32>(4)? != 0,
+        modified_at: row.get(5)?,
+        modified_by: row.get(6)?,
+    })
+}
+
+pub fn list_records(state: &AppState, entity: &str) -> AppResult<Vec<SyncRecordState>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!("SELECT {SYNC_RECORD_COLS} FROM sync_records WHERE entity = ?1"))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let records = stmt
+        .query_map(params![entity], row_to_sync_record)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(records)
+}
+
+pub fn get_record(state: &AppState, entity: &str, entity_id: &str) -> AppResult<Option<SyncRecordState>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let result = db.query_row(
+        &format!("SELECT {SYNC_RECORD_COLS} FROM sync_records WHERE entity = ?1 AND entity_id = ?2"),
+        params![entity, entity_id],
+        row_to_sync_record,
+    );
+
+    match result {
+        Ok(r) => Ok(Some(r)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+pub fn save_record(state: &AppState, record: &SyncRecordState) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT OR REPLACE INTO sync_records (entity, entity_id, clock_json, content_hash, deleted, modified_at, modified_by) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            record.entity,
+            record.entity_id,
+            serde_json::to_string(&record.clock)?,
+            record.content_hash,
+            record.deleted as i32,
+            record.modified_at,
+            record.modified_by,
+        ],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Read `columns` of every row in `table` matching `filter`, as JSON values in
+/// column order. The first column must be the row's id.
+pub fn read_rows(
+    state: &AppState,
+    table: &str,
+    columns: &[&str],
+    filter: &str,
+) -> AppResult<Vec<Vec<serde_json::Value>>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!("SELECT {} FROM {} WHERE {}", columns.join(", "), table, filter))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            (0..columns.len())
+                .map(|i| row.get::<_, Value>(i).map(sql_to_json))
+                .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(rows)
+}
+
+/// Insert or update a row from JSON values in `columns` order. `insert_defaults`
+/// are extra (column, SQL expression) pairs used only when the row is new.
+pub fn upsert_row(
+    state: &AppState,
+    table: &str,
+    columns: &[&str],
+    values: &[serde_json::Value],
+    insert_defaults: &[(&str, &str)],
+) -> AppResult<()> {
+    let insert_columns: Vec<&str> = columns
+        .iter()
+        .copied()
+        .chain(insert_defaults.iter().map(|(c, _)| *c))
+        .collect();
+    let placeholders: Vec<String> = (1..=columns.len())
+        .map(|i| format!("?{}", i))
+        .chain(insert_defaults.iter().map(|(_, expr)| expr.to_string()))
+        .collect();
+    let updates: Vec<String> = columns[1..].iter().map(|c| format!("{c} = excluded.{c}")).collect();
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT({}) DO UPDATE SET {}",
+        table,
+        insert_columns.join(", "),
+        placeholders.join(", "),
+        columns[0],
+        updates.join(", ")
+    );
+
+    let params: Vec<Value> = values.iter().map(json_to_sql).collect();
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(&sql, rusqlite::params_from_iter(params))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Run a statement taking the row id as ?1 (used for replicated deletions).
+pub fn execute_for_id(state: &AppState, sql: &str, id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(sql, params![id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+fn sql_to_json(value: Value) -> serde_json::Value {
+    match value {
+        Value::Null | Value::Blob(_) => serde_json::Value::Null,
+        Value::Integer(i) => i.into(),
+        Value::Real(f) => f.into(),
+        Value::Text(s) => s.into(),
+    }
+}
+
+fn json_to_sql(value: &serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
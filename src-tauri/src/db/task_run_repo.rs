@@ -1,7 +1,11 @@
 use rusqlite::params;
 
+use crate::acp::permissions::PermissionPreset;
 use crate::error::{AppError, AppResult};
-use crate::models::task_run::{TaskAssignment, TaskRun};
+use crate::models::bulk::BulkItemResult;
+use crate::models::task_run::{
+    AssignmentOverrides, CancelReason, CancellationStats, RunEnvironment, TaskAssignment, TaskRun, TaskRunSearch,
+};
 use crate::state::AppState;
 
 fn row_to_task_run(row: &rusqlite::Row) -> rusqlite::Result<TaskRun> {
@@ -27,6 +31,22 @@ fn row_to_task_run(row: &rusqlite::Row) -> rusqlite::Result<TaskRun> {
         next_run_at: row.get(18)?,
         is_paused: row.get::<_, i32>(19)? != 0,
         workspace_id: row.get(20)?,
+        failure_policy_json: row.get(21)?,
+        served_by_hub_agent_id: row.get(22)?,
+        archived_at: row.get(23)?,
+        context_run_id: row.get(24)?,
+        permission_preset: row.get::<_, Option<String>>(25)?.and_then(|p| PermissionPreset::parse(&p)),
+        max_duration_minutes: row.get(26)?,
+        timed_out: row.get::<_, i32>(27)? != 0,
+        cancel_reason: row.get::<_, Option<String>>(28)?.and_then(|r| CancelReason::parse(&r)),
+        labels: row
+            .get::<_, Option<String>>(29)?
+            .map(|labels| {
+                let mut labels: Vec<String> = labels.split(',').map(str::to_string).collect();
+                labels.sort();
+                labels
+            })
+            .unwrap_or_default(),
     })
 }
 
@@ -50,12 +70,37 @@ fn row_to_assignment(row: &rusqlite::Row) -> rusqlite::Result<TaskAssignment> {
         duration_ms: row.get(15)?,
         error_message: row.get(16)?,
         created_at: row.get(17)?,
+        working_directory: row.get(18)?,
+        cached: row.get(19)?,
+        max_tokens_out: row.get(20)?,
+        max_cost: row.get(21)?,
+        retry_of_assignment_id: row.get(22)?,
+        overrides: row
+            .get::<_, Option<String>>(23)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        cancel_reason: row.get::<_, Option<String>>(24)?.and_then(|r| CancelReason::parse(&r)),
     })
 }
 
-const TASK_RUN_COLS: &str = "id, title, user_prompt, control_hub_agent_id, status, task_plan_json, result_summary, total_tokens_in, total_tokens_out, total_cache_creation_tokens, total_cache_read_tokens, total_duration_ms, created_at, updated_at, rating, schedule_type, scheduled_time, recurrence_pattern, next_run_at, is_paused, workspace_id";
-const ASSIGNMENT_COLS: &str = "id, task_run_id, agent_id, agent_name, sequence_order, input_text, output_text, status, model_used, tokens_in, tokens_out, cache_creation_tokens, cache_read_tokens, started_at, completed_at, duration_ms, error_message, created_at";
+/// Most runs a search returns.
+const MAX_SEARCH_RESULTS: usize = 500;
+const MAX_LABEL_LEN: usize = 40;
+/// Statuses of runs that are not finished and cannot be archived.
+const ACTIVE_STATUSES: &[&str] = &[
+    "pending",
+    "waiting_for_workspace",
+    "analyzing",
+    "running",
+    "awaiting_confirmation",
+    "awaiting_plan_approval",
+];
 
+const TASK_RUN_COLS: &str = "id, title, user_prompt, control_hub_agent_id, status, task_plan_json, result_summary, total_tokens_in, total_tokens_out, total_cache_creation_tokens, total_cache_read_tokens, total_duration_ms, created_at, updated_at, rating, schedule_type, scheduled_time, recurrence_pattern, next_run_at, is_paused, workspace_id, failure_policy, served_by_hub_agent_id, archived_at, context_run_id, permission_preset, max_duration_minutes, timed_out, cancel_reason, \
+     (SELECT group_concat(label) FROM task_run_labels WHERE task_run_id = task_runs.id)";
+const ASSIGNMENT_COLS: &str = "id, task_run_id, agent_id, agent_name, sequence_order, input_text, output_text, status, model_used, tokens_in, tokens_out, cache_creation_tokens, cache_read_tokens, started_at, completed_at, duration_ms, error_message, created_at, working_directory, cached, max_tokens_out, max_cost, retry_of_assignment_id, overrides_json, cancel_reason";
+
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn create_task_run(
     state: &AppState,
     id: &str,
@@ -80,6 +125,76 @@ pub fn create_task_run(
     .map_err(|e| AppError::Database(e.to_string()))
 }
 
+/// Record the permission preset a run was started with.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn set_permission_preset(state: &AppState, id: &str, preset: PermissionPreset) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_runs SET permission_preset = ?2 WHERE id = ?1",
+        params![id, preset.as_str()],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Record the wall-clock limit a run was started with.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn set_max_duration(state: &AppState, id: &str, minutes: u64) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_runs SET max_duration_minutes = ?2 WHERE id = ?1",
+        params![id, minutes as i64],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Mark a run as wound down at its wall-clock limit.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn set_timed_out(state: &AppState, id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute("UPDATE task_runs SET timed_out = 1 WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Count one corrective action of the control hub's feedback against the run.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn record_feedback_correction(state: &AppState, id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_runs SET feedback_corrections = feedback_corrections + 1 WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Corrective actions the control hub's feedback has used in the run so far.
+pub fn get_feedback_corrections(state: &AppState, id: &str) -> AppResult<usize> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let count: i64 = db
+        .query_row("SELECT feedback_corrections FROM task_runs WHERE id = ?1", params![id], |row| row.get(0))
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("TaskRun {id} not found")),
+            _ => AppError::Database(e.to_string()),
+        })?;
+    Ok(count.max(0) as usize)
+}
+
+/// Attach an earlier run to plan the run with.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn set_context_run(state: &AppState, id: &str, context_run_id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_runs SET context_run_id = ?2 WHERE id = ?1",
+        params![id, context_run_id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn update_task_run_status(
     state: &AppState,
     id: &str,
@@ -94,6 +209,128 @@ pub fn update_task_run_status(
     Ok(())
 }
 
+/// Set the status of several task runs in one transaction.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn update_task_run_statuses(state: &AppState, ids: &[String], status: &str) -> AppResult<()> {
+    let mut db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let tx = db.transaction().map_err(|e| AppError::Database(e.to_string()))?;
+    for id in ids {
+        tx.execute(
+            "UPDATE task_runs SET status = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![status, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Cancel several task runs in one transaction, recording the reason on
+/// them and on their unfinished assignments.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn cancel_task_runs(state: &AppState, ids: &[String], reason: CancelReason) -> AppResult<()> {
+    let mut db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let tx = db.transaction().map_err(|e| AppError::Database(e.to_string()))?;
+    for id in ids {
+        tx.execute(
+            "UPDATE task_runs SET status = 'cancelled', cancel_reason = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![reason.as_str(), id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        tx.execute(
+            "UPDATE task_assignments SET cancel_reason = ?1 WHERE task_run_id = ?2 AND status IN ('pending', 'running')",
+            params![reason.as_str(), id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Record why an agent's unfinished assignment in a run is being cancelled;
+/// its status follows once the agent stops.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn set_agent_cancel_reason(state: &AppState, task_run_id: &str, agent_id: &str, reason: CancelReason) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_assignments SET cancel_reason = ?1 WHERE task_run_id = ?2 AND agent_id = ?3 AND status IN ('pending', 'running')",
+        params![reason.as_str(), task_run_id, agent_id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Cancellations by reason, most cancelled runs first. Runs cancelled
+/// before reasons were recorded are left out.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn get_cancellation_stats(state: &AppState, workspace_id: Option<&str>) -> AppResult<Vec<CancellationStats>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(
+            "SELECT reason, SUM(runs), SUM(assignments), SUM(tokens_in), SUM(tokens_out) FROM ( \
+                 SELECT cancel_reason AS reason, 1 AS runs, 0 AS assignments, total_tokens_in AS tokens_in, total_tokens_out AS tokens_out \
+                 FROM task_runs WHERE status = 'cancelled' AND cancel_reason IS NOT NULL AND (?1 IS NULL OR workspace_id = ?1) \
+                 UNION ALL \
+                 SELECT a.cancel_reason, 0, 1, \
+                        CASE WHEN r.status = 'cancelled' THEN 0 ELSE a.tokens_in END, \
+                        CASE WHEN r.status = 'cancelled' THEN 0 ELSE a.tokens_out END \
+                 FROM task_assignments a JOIN task_runs r ON r.id = a.task_run_id \
+                 WHERE a.status = 'cancelled' AND a.cancel_reason IS NOT NULL AND (?1 IS NULL OR r.workspace_id = ?1) \
+             ) GROUP BY reason ORDER BY SUM(runs) DESC, SUM(assignments) DESC",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let rows = stmt
+        .query_map(params![workspace_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(reason, runs, assignments, tokens_in, tokens_out)| {
+            Some(CancellationStats { reason: CancelReason::parse(&reason)?, runs, assignments, tokens_in, tokens_out })
+        })
+        .collect())
+}
+
+/// Record the Control Hub that planned or summarized the run.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn set_served_by_hub(state: &AppState, id: &str, hub_agent_id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_runs SET served_by_hub_agent_id = ?1, updated_at = datetime('now') WHERE id = ?2",
+        params![hub_agent_id, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Record what the run runs with; a resumed run keeps the snapshot of its start.
+pub fn set_run_environment(state: &AppState, id: &str, environment: &RunEnvironment) -> AppResult<()> {
+    let json = serde_json::to_string(environment)?;
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_runs SET environment_json = ?1 WHERE id = ?2 AND environment_json IS NULL",
+        params![json, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// The run's environment snapshot; None for runs from before snapshots were kept.
+pub fn get_run_environment(state: &AppState, id: &str) -> AppResult<Option<RunEnvironment>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let json: Option<String> = db
+        .query_row("SELECT environment_json FROM task_runs WHERE id = ?1", params![id], |row| row.get(0))
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("TaskRun {id} not found")),
+            _ => AppError::Database(e.to_string()),
+        })?;
+    Ok(json.and_then(|j| serde_json::from_str(&j).ok()))
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn update_task_run_plan(
     state: &AppState,
     id: &str,
@@ -108,6 +345,7 @@ pub fn update_task_run_plan(
     Ok(())
 }
 
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn update_task_run_summary(
     state: &AppState,
     id: &str,
@@ -122,6 +360,7 @@ pub fn update_task_run_summary(
     Ok(())
 }
 
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn update_task_run_prompt(
     state: &AppState,
     id: &str,
@@ -137,6 +376,7 @@ pub fn update_task_run_prompt(
 }
 
 /// Rate a completed task run (1-5 stars)
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn rate_task_run(
     state: &AppState,
     id: &str,
@@ -151,6 +391,7 @@ pub fn rate_task_run(
     Ok(())
 }
 
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn update_task_run_totals(
     state: &AppState,
     id: &str,
@@ -169,6 +410,26 @@ pub fn update_task_run_totals(
     Ok(())
 }
 
+/// Add an assignment's token usage to its run's totals
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn add_task_run_tokens(
+    state: &AppState,
+    id: &str,
+    tokens_in: i64,
+    tokens_out: i64,
+    cache_creation_tokens: i64,
+    cache_read_tokens: i64,
+) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_runs SET total_tokens_in = total_tokens_in + ?1, total_tokens_out = total_tokens_out + ?2, total_cache_creation_tokens = total_cache_creation_tokens + ?3, total_cache_read_tokens = total_cache_read_tokens + ?4, updated_at = datetime('now') WHERE id = ?5",
+        params![tokens_in, tokens_out, cache_creation_tokens, cache_read_tokens, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn get_task_run(state: &AppState, id: &str) -> AppResult<TaskRun> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     db.query_row(
@@ -182,17 +443,18 @@ pub fn get_task_run(state: &AppState, id: &str) -> AppResult<TaskRun> {
     })
 }
 
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn list_task_runs(state: &AppState, workspace_id: Option<&str>) -> AppResult<Vec<TaskRun>> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
 
     let (sql, params_vec): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(ws_id) = workspace_id {
         (
-            format!("SELECT {TASK_RUN_COLS} FROM task_runs WHERE workspace_id = ?1 ORDER BY created_at DESC"),
+            format!("SELECT {TASK_RUN_COLS} FROM task_runs WHERE workspace_id = ?1 AND archived_at IS NULL ORDER BY created_at DESC"),
             vec![Box::new(ws_id.to_string())],
         )
     } else {
         (
-            format!("SELECT {TASK_RUN_COLS} FROM task_runs ORDER BY created_at DESC"),
+            format!("SELECT {TASK_RUN_COLS} FROM task_runs WHERE archived_at IS NULL ORDER BY created_at DESC"),
             vec![],
         )
     };
@@ -209,6 +471,166 @@ pub fn list_task_runs(state: &AppState, workspace_id: Option<&str>) -> AppResult
     Ok(runs)
 }
 
+/// Task runs matching every set filter of the search, newest first.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn search_task_runs(state: &AppState, search: &TaskRunSearch) -> AppResult<Vec<TaskRun>> {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    let mut bind = |value: Box<dyn rusqlite::types::ToSql>| {
+        values.push(value);
+        format!("?{}", values.len())
+    };
+
+    if let Some(ws_id) = &search.workspace_id {
+        conditions.push(format!("workspace_id = {}", bind(Box::new(ws_id.clone()))));
+    }
+    if !search.statuses.is_empty() {
+        let placeholders: Vec<String> = search.statuses.iter().map(|s| bind(Box::new(s.clone()))).collect();
+        conditions.push(format!("status IN ({})", placeholders.join(", ")));
+    }
+    for label in normalize_labels(&search.labels)? {
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM task_run_labels l WHERE l.task_run_id = task_runs.id AND l.label = {})",
+            bind(Box::new(label))
+        ));
+    }
+    if let Some(after) = &search.created_after {
+        conditions.push(format!("created_at >= {}", bind(Box::new(after.replace('T', " ")))));
+    }
+    if let Some(before) = &search.created_before {
+        conditions.push(format!("created_at < {}", bind(Box::new(before.replace('T', " ")))));
+    }
+    if let Some(agent_id) = &search.agent_id {
+        let p = bind(Box::new(agent_id.clone()));
+        conditions.push(format!(
+            "(control_hub_agent_id = {p} OR served_by_hub_agent_id = {p}              OR EXISTS (SELECT 1 FROM task_assignments a WHERE a.task_run_id = task_runs.id AND a.agent_id = {p}))"
+        ));
+    }
+    if let Some(text) = search.text.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        let escaped = text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let p = bind(Box::new(format!("%{}%", escaped)));
+        conditions.push(format!(
+            "(title LIKE {p} ESCAPE '\\' OR result_summary LIKE {p} ESCAPE '\\')"
+        ));
+    }
+    match search.archived {
+        Some(true) => conditions.push("archived_at IS NOT NULL".into()),
+        Some(false) => conditions.push("archived_at IS NULL".into()),
+        None => {}
+    }
+
+    let filter = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    let limit = search.limit.unwrap_or(MAX_SEARCH_RESULTS).min(MAX_SEARCH_RESULTS);
+    let sql = format!("SELECT {TASK_RUN_COLS} FROM task_runs {filter} ORDER BY created_at DESC LIMIT {limit}");
+
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db.prepare(&sql).map_err(|e| AppError::Database(e.to_string()))?;
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> = values.iter().map(|p| p.as_ref()).collect();
+    let runs = stmt
+        .query_map(params_refs.as_slice(), row_to_task_run)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(runs)
+}
+
+/// Trimmed, lowercased and deduplicated labels.
+fn normalize_labels(labels: &[String]) -> AppResult<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for label in labels {
+        let label = label.trim().to_lowercase();
+        if label.is_empty() {
+            continue;
+        }
+        if label.contains(',') || label.chars().count() > MAX_LABEL_LEN {
+            return Err(AppError::InvalidRequest(format!(
+                "Label '{}' must be at most {} characters without commas",
+                label, MAX_LABEL_LEN
+            )));
+        }
+        if !normalized.contains(&label) {
+            normalized.push(label);
+        }
+    }
+    Ok(normalized)
+}
+
+/// Replace the labels of a task run.
+pub fn set_task_run_labels(state: &AppState, id: &str, labels: &[String]) -> AppResult<TaskRun> {
+    let labels = normalize_labels(labels)?;
+    get_task_run(state, id)?;
+    {
+        let mut db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+        let tx = db.transaction().map_err(|e| AppError::Database(e.to_string()))?;
+        tx.execute("DELETE FROM task_run_labels WHERE task_run_id = ?1", params![id])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        for label in &labels {
+            tx.execute(
+                "INSERT INTO task_run_labels (task_run_id, label) VALUES (?1, ?2)",
+                params![id, label],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+        tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    get_task_run(state, id)
+}
+
+/// Labels in use on the workspace's task runs, or on all of them.
+pub fn list_task_run_labels(state: &AppState, workspace_id: Option<&str>) -> AppResult<Vec<String>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(
+            "SELECT DISTINCT l.label FROM task_run_labels l JOIN task_runs t ON t.id = l.task_run_id \
+             WHERE ?1 IS NULL OR t.workspace_id = ?1 ORDER BY l.label",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let labels = stmt
+        .query_map(params![workspace_id], |row| row.get(0))
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(labels)
+}
+
+/// Archive or unarchive several task runs in one transaction. Runs that are
+/// still in progress cannot be archived.
+pub fn set_task_runs_archived(state: &AppState, ids: &[String], archived: bool) -> AppResult<Vec<BulkItemResult>> {
+    let mut db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let tx = db.transaction().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut results = Vec::with_capacity(ids.len());
+    for id in ids {
+        let status: Option<String> = match tx.query_row("SELECT status FROM task_runs WHERE id = ?1", params![id], |row| row.get(0)) {
+            Ok(status) => Some(status),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(AppError::Database(e.to_string())),
+        };
+        let result = match status {
+            None => BulkItemResult::failed(id, format!("TaskRun {id} not found")),
+            Some(status) if archived && ACTIVE_STATUSES.contains(&status.as_str()) => {
+                BulkItemResult::failed(id, format!("TaskRun {id} is still {status}"))
+            }
+            Some(_) => {
+                let sql = if archived {
+                    "UPDATE task_runs SET archived_at = COALESCE(archived_at, datetime('now')) WHERE id = ?1"
+                } else {
+                    "UPDATE task_runs SET archived_at = NULL WHERE id = ?1"
+                };
+                tx.execute(sql, params![id]).map_err(|e| AppError::Database(e.to_string()))?;
+                BulkItemResult::ok(id)
+            }
+        };
+        results.push(result);
+    }
+    tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(results)
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn create_task_assignment(
     state: &AppState,
     id: &str,
@@ -233,6 +655,65 @@ pub fn create_task_assignment(
     .map_err(|e| AppError::Database(e.to_string()))
 }
 
+/// Record the sub-directory an assignment runs in
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn set_assignment_working_directory(state: &AppState, id: &str, working_directory: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_assignments SET working_directory = ?1 WHERE id = ?2",
+        params![working_directory, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Record an assignment's output token and cost caps
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn set_assignment_caps(state: &AppState, id: &str, max_tokens_out: Option<i64>, max_cost: Option<f64>) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_assignments SET max_tokens_out = ?1, max_cost = ?2 WHERE id = ?3",
+        params![max_tokens_out, max_cost, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Record the agent settings an assignment runs with instead of the agent's own
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn set_assignment_overrides(state: &AppState, id: &str, overrides: &AssignmentOverrides) -> AppResult<()> {
+    let json = serde_json::to_string(overrides)?;
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_assignments SET overrides_json = ?1 WHERE id = ?2",
+        params![json, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Record the earlier assignment a retry re-executes
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn set_assignment_retry_of(state: &AppState, id: &str, retry_of: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE task_assignments SET retry_of_assignment_id = ?1 WHERE id = ?2",
+        params![retry_of, id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Flag an assignment whose output came from the response cache
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn mark_assignment_cached(state: &AppState, id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute("UPDATE task_assignments SET cached = 1 WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn update_task_assignment(
     state: &AppState,
     id: &str,
@@ -246,33 +727,53 @@ pub fn update_task_assignment(
     duration_ms: i64,
     error_message: Option<&str>,
 ) -> AppResult<()> {
-    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
-
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
     let (started_at, completed_at) = match status {
         "running" => (Some(now.clone()), None),
-        "completed" | "failed" | "skipped" => (None, Some(now)),
+        "completed" | "capped" | "failed" | "skipped" => (None, Some(now)),
         _ => (None, None),
     };
 
-    if status == "running" {
-        db.execute(
-            "UPDATE task_assignments SET status = ?1, started_at = COALESCE(started_at, ?2) WHERE id = ?3",
-            params![status, started_at, id],
-        )
-        .map_err(|e| AppError::Database(e.to_string()))?;
-    } else {
-        db.execute(
-            "UPDATE task_assignments SET status=?1, output_text=?2, model_used=?3, tokens_in=?4, tokens_out=?5, cache_creation_tokens=?6, cache_read_tokens=?7, duration_ms=?8, error_message=?9, completed_at=?10 WHERE id=?11",
-            params![status, output_text, model_used, tokens_in, tokens_out, cache_creation_tokens, cache_read_tokens, duration_ms, error_message, completed_at, id],
-        )
-        .map_err(|e| AppError::Database(e.to_string()))?;
-    }
+    // Through the write queue, so it lands after the agent's queued timeline events
+    let id = id.to_string();
+    let status = status.to_string();
+    let output_text = output_text.map(str::to_string);
+    let model_used = model_used.map(str::to_string);
+    let error_message = error_message.map(str::to_string);
+    state.db_writes.execute("assignment status", move |db| {
+        if status == "running" {
+            db.execute(
+                "UPDATE task_assignments SET status = ?1, started_at = COALESCE(started_at, ?2) WHERE id = ?3",
+                params![status, started_at, id],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        } else {
+            db.execute(
+                "UPDATE task_assignments SET status=?1, output_text=?2, model_used=?3, tokens_in=?4, tokens_out=?5, cache_creation_tokens=?6, cache_read_tokens=?7, duration_ms=?8, error_message=?9, completed_at=?10 WHERE id=?11",
+                params![status, output_text, model_used, tokens_in, tokens_out, cache_creation_tokens, cache_read_tokens, duration_ms, error_message, completed_at, id],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+        Ok(())
+    })
+}
 
-    Ok(())
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn get_assignment(state: &AppState, id: &str) -> AppResult<TaskAssignment> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        &format!("SELECT {ASSIGNMENT_COLS} FROM task_assignments WHERE id = ?1"),
+        params![id],
+        row_to_assignment,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Assignment {id} not found")),
+        _ => AppError::Database(e.to_string()),
+    })
 }
 
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn list_assignments_for_run(state: &AppState, task_run_id: &str) -> AppResult<Vec<TaskAssignment>> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     let mut stmt = db
@@ -288,14 +789,17 @@ pub fn list_assignments_for_run(state: &AppState, task_run_id: &str) -> AppResul
     Ok(assignments)
 }
 
-/// List all task runs that are in non-terminal states (pending, analyzing, running, awaiting_confirmation).
+/// List all task runs that are in non-terminal states (pending, waiting_for_workspace, analyzing, running,
+/// awaiting_confirmation, awaiting_plan_approval).
 /// Used on startup to find orphaned tasks that need to be resumed.
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn list_incomplete_task_runs(state: &AppState) -> AppResult<Vec<TaskRun>> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     let mut stmt = db
         .prepare(&format!(
             "SELECT {TASK_RUN_COLS} FROM task_runs \
-             WHERE status IN ('pending', 'analyzing', 'running', 'awaiting_confirmation') \
+             WHERE status IN ('pending', 'waiting_for_workspace', 'analyzing', 'running', 'awaiting_confirmation', \
+             'awaiting_plan_approval') \
              ORDER BY created_at ASC"
         ))
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -309,9 +813,33 @@ pub fn list_incomplete_task_runs(state: &AppState) -> AppResult<Vec<TaskRun>> {
     Ok(runs)
 }
 
+/// Task runs waiting on the user: awaiting confirmation or plan approval,
+/// unchanged for at least `minutes`.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn list_waiting_task_runs(state: &AppState, minutes: u64) -> AppResult<Vec<TaskRun>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {TASK_RUN_COLS} FROM task_runs \
+             WHERE status IN ('awaiting_confirmation', 'awaiting_plan_approval') \
+               AND updated_at <= datetime('now', ?1) \
+             ORDER BY updated_at ASC"
+        ))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let runs = stmt
+        .query_map(params![format!("-{} minutes", minutes)], row_to_task_run)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(runs)
+}
+
 // ============== Scheduling functions ==============
 
 /// Update the schedule configuration for a task run
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn update_schedule(
     state: &AppState,
     task_run_id: &str,
@@ -319,11 +847,12 @@ pub fn update_schedule(
     scheduled_time: Option<&str>,
     recurrence_pattern_json: Option<&str>,
     next_run_at: Option<&str>,
+    failure_policy_json: Option<&str>,
 ) -> AppResult<TaskRun> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     db.execute(
-        "UPDATE task_runs SET schedule_type = ?1, scheduled_time = ?2, recurrence_pattern = ?3, next_run_at = ?4, is_paused = 0, updated_at = datetime('now') WHERE id = ?5",
-        params![schedule_type, scheduled_time, recurrence_pattern_json, next_run_at, task_run_id],
+        "UPDATE task_runs SET schedule_type = ?1, scheduled_time = ?2, recurrence_pattern = ?3, next_run_at = ?4, failure_policy = ?5, is_paused = 0, updated_at = datetime('now') WHERE id = ?6",
+        params![schedule_type, scheduled_time, recurrence_pattern_json, next_run_at, failure_policy_json, task_run_id],
     )
     .map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -336,10 +865,11 @@ pub fn update_schedule(
 }
 
 /// Clear the schedule for a task run
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn clear_schedule(state: &AppState, task_run_id: &str) -> AppResult<()> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     db.execute(
-        "UPDATE task_runs SET schedule_type = 'none', scheduled_time = NULL, recurrence_pattern = NULL, next_run_at = NULL, is_paused = 0, updated_at = datetime('now') WHERE id = ?1",
+        "UPDATE task_runs SET schedule_type = 'none', scheduled_time = NULL, recurrence_pattern = NULL, next_run_at = NULL, failure_policy = NULL, is_paused = 0, updated_at = datetime('now') WHERE id = ?1",
         params![task_run_id],
     )
     .map_err(|e| AppError::Database(e.to_string()))?;
@@ -347,6 +877,7 @@ pub fn clear_schedule(state: &AppState, task_run_id: &str) -> AppResult<()> {
 }
 
 /// Pause a scheduled task
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn pause_scheduled_task(state: &AppState, task_run_id: &str) -> AppResult<()> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     db.execute(
@@ -358,6 +889,7 @@ pub fn pause_scheduled_task(state: &AppState, task_run_id: &str) -> AppResult<()
 }
 
 /// Resume a paused scheduled task
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn resume_scheduled_task(state: &AppState, task_run_id: &str) -> AppResult<()> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     db.execute(
@@ -368,8 +900,30 @@ pub fn resume_scheduled_task(state: &AppState, task_run_id: &str) -> AppResult<(
     Ok(())
 }
 
+/// Scheduled tasks, optionally of one workspace: active ones by next run,
+/// then paused ones.
+pub fn list_scheduled_tasks(state: &AppState, workspace_id: Option<&str>) -> AppResult<Vec<TaskRun>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {TASK_RUN_COLS} FROM task_runs \
+             WHERE schedule_type != 'none' AND (?1 IS NULL OR workspace_id = ?1) \
+             ORDER BY is_paused ASC, next_run_at IS NULL, datetime(next_run_at) ASC"
+        ))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let runs = stmt
+        .query_map(params![workspace_id], row_to_task_run)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(runs)
+}
+
 /// Get all scheduled tasks that are due for execution
 /// Returns tasks where next_run_at <= now and is_paused = 0
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn list_due_scheduled_tasks(state: &AppState) -> AppResult<Vec<TaskRun>> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
     let mut stmt = db
@@ -515,6 +1069,7 @@ fn parse_time(time_str: &str) -> Option<(u32, u32)> {
 /// Update next_run_at for a task after it has been executed
 /// For one-time tasks, this clears the schedule
 /// For recurring tasks, this calculates the next run time
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn update_next_run_after_execution(state: &AppState, task_run_id: &str) -> AppResult<()> {
     let task = get_task_run(state, task_run_id)?;
 
@@ -552,3 +1107,41 @@ pub fn update_next_run_after_execution(state: &AppState, task_run_id: &str) -> A
 
     Ok(())
 }
+
+/// Deadline (RFC 3339) of a run's pending confirmation, if one is stored.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn get_confirmation_deadline(state: &AppState, task_run_id: &str) -> AppResult<Option<String>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    match db.query_row(
+        "SELECT deadline_at FROM pending_confirmations WHERE task_run_id = ?1",
+        params![task_run_id],
+        |row| row.get(0),
+    ) {
+        Ok(deadline) => Ok(Some(deadline)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn set_confirmation_deadline(state: &AppState, task_run_id: &str, deadline_at: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO pending_confirmations (task_run_id, deadline_at) VALUES (?1, ?2) \
+         ON CONFLICT(task_run_id) DO UPDATE SET deadline_at = excluded.deadline_at",
+        params![task_run_id, deadline_at],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn clear_confirmation_deadline(state: &AppState, task_run_id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "DELETE FROM pending_confirmations WHERE task_run_id = ?1",
+        params![task_run_id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
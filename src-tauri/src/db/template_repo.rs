@@ -0,0 +1,93 @@
+use rusqlite::params;
+
+use crate::error::{AppError, AppResult};
+use crate::models::template::OrchestrationTemplate;
+use crate::state::AppState;
+
+const TEMPLATE_COLS: &str = "id, name, description, source_task_run_id, prompt_template, analysis, assignments_json, variables_json, workspace_id, created_at, updated_at";
+
+fn row_to_template(row: &rusqlite::Row) -> rusqlite::Result<OrchestrationTemplate> {
+    Ok(OrchestrationTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        source_task_run_id: row.get(3)?,
+        prompt_template: row.get(4)?,
+        analysis: row.get(5)?,
+        assignments_json: row.get(6)?,
+        variables_json: row.get(7)?,
+        workspace_id: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+pub fn list_templates(state: &AppState, workspace_id: Option<&str>) -> AppResult<Vec<OrchestrationTemplate>> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+
+    let (sql, params_vec): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(ws_id) = workspace_id {
+        (
+            format!("SELECT {TEMPLATE_COLS} FROM orchestration_templates WHERE workspace_id = ?1 ORDER BY created_at DESC"),
+            vec![Box::new(ws_id.to_string())],
+        )
+    } else {
+        (
+            format!("SELECT {TEMPLATE_COLS} FROM orchestration_templates ORDER BY created_at DESC"),
+            vec![],
+        )
+    };
+
+    let mut stmt = db.prepare(&sql).map_err(|e| AppError::Database(e.to_string()))?;
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let templates = stmt
+        .query_map(params_refs.as_slice(), row_to_template)
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(templates)
+}
+
+pub fn get_template(state: &AppState, id: &str) -> AppResult<OrchestrationTemplate> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.query_row(
+        &format!("SELECT {TEMPLATE_COLS} FROM orchestration_templates WHERE id = ?1"),
+        params![id],
+        row_to_template,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Template {id} not found")),
+        _ => AppError::Database(e.to_string()),
+    })
+}
+
+pub fn create_template(state: &AppState, template: &OrchestrationTemplate) -> AppResult<OrchestrationTemplate> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "INSERT INTO orchestration_templates (id, name, description, source_task_run_id, prompt_template, analysis, assignments_json, variables_json, workspace_id) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            template.id,
+            template.name,
+            template.description,
+            template.source_task_run_id,
+            template.prompt_template,
+            template.analysis,
+            template.assignments_json,
+            template.variables_json,
+            template.workspace_id,
+        ],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+
+    drop(db);
+    get_template(state, &template.id)
+}
+
+pub fn delete_template(state: &AppState, id: &str) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute("DELETE FROM orchestration_templates WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use rusqlite::params;
 
 use crate::error::{AppError, AppResult};
-use crate::models::workspace::{CreateWorkspaceRequest, UpdateWorkspaceRequest, Workspace};
+use crate::models::workspace::{CreateWorkspaceRequest, SandboxMode, UpdateWorkspaceRequest, Workspace};
 use crate::state::AppState;
 
 fn row_to_workspace(row: &rusqlite::Row) -> rusqlite::Result<Workspace> {
@@ -10,12 +10,17 @@ fn row_to_workspace(row: &rusqlite::Row) -> rusqlite::Result<Workspace> {
         name: row.get(1)?,
         icon: row.get(2)?,
         working_directory: row.get(3)?,
+        hub_persona: row.get(6)?,
+        redact_outputs: row.get(7)?,
+        exclusive_runs: row.get(8)?,
+        sandbox_of: row.get(9)?,
+        sandbox_mode: row.get::<_, Option<String>>(10)?.and_then(|m| SandboxMode::parse(&m)),
         created_at: row.get(4)?,
         updated_at: row.get(5)?,
     })
 }
 
-const WORKSPACE_COLS: &str = "id, name, icon, working_directory, created_at, updated_at";
+const WORKSPACE_COLS: &str = "id, name, icon, working_directory, created_at, updated_at, hub_persona, redact_outputs, exclusive_runs, sandbox_of, sandbox_mode";
 
 pub fn list_workspaces(state: &AppState) -> AppResult<Vec<Workspace>> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
@@ -127,11 +132,44 @@ pub fn update_workspace(
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
     }
+    if let Some(persona) = &req.hub_persona {
+        let persona = Some(persona.trim()).filter(|p| !p.is_empty());
+        db.execute(
+            "UPDATE workspaces SET hub_persona = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![persona, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    if let Some(redact) = req.redact_outputs {
+        db.execute(
+            "UPDATE workspaces SET redact_outputs = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![redact, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
+    if let Some(exclusive) = req.exclusive_runs {
+        db.execute(
+            "UPDATE workspaces SET exclusive_runs = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![exclusive, id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    }
 
     drop(db);
     get_workspace(state, id)
 }
 
+/// Mark a workspace as a sandbox of another.
+pub fn set_sandbox_of(state: &AppState, id: &str, source_id: &str, mode: SandboxMode) -> AppResult<()> {
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute(
+        "UPDATE workspaces SET sandbox_of = ?1, sandbox_mode = ?2 WHERE id = ?3",
+        params![source_id, mode.as_str(), id],
+    )
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
 pub fn delete_workspace(state: &AppState, id: &str) -> AppResult<()> {
     let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -149,6 +187,8 @@ pub fn delete_workspace(state: &AppState, id: &str) -> AppResult<()> {
     // Agents with this workspace_id will be CASCADE-deleted by the FK
     db.execute("DELETE FROM workspaces WHERE id = ?1", params![id])
         .map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute("DELETE FROM skill_cache WHERE workspace_key = ?1", params![id])
+        .map_err(|e| AppError::Database(e.to_string()))?;
 
     Ok(())
 }
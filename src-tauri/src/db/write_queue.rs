@@ -0,0 +1,271 @@
+//! Batched writes for high-frequency updates.
+//!
+//! Timeline events, agent message saves and assignment status updates arrive
+//! in bursts from many agents at once. Taking the connection for each of them
+//! stalls commands waiting on the same lock, so they are handed to a writer
+//! thread that commits them in one transaction per batch. Writes apply in the
+//! order they were queued. `submit` returns at once and the write waits up to
+//! `database.write_batch_ms` for company; `execute` blocks until its write is
+//! committed and does not wait for more. Readers that must see queued writes
+//! call `flush` first.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+
+use rusqlite::Connection;
+
+use crate::db::connection::{self, Db, DbStats};
+use crate::error::{AppError, AppResult};
+
+/// Writes committed in one transaction at most.
+const MAX_BATCH: usize = 500;
+
+type Job = Box<dyn FnOnce(&Connection) -> AppResult<()> + Send>;
+
+enum Message {
+    Write {
+        label: &'static str,
+        job: Job,
+        reply: Option<mpsc::SyncSender<AppResult<()>>>,
+    },
+    Flush(mpsc::SyncSender<()>),
+}
+
+impl Message {
+    /// Someone blocks until this message is handled.
+    fn is_waited(&self) -> bool {
+        matches!(self, Message::Write { reply: Some(_), .. } | Message::Flush(_))
+    }
+}
+
+#[derive(Default)]
+struct QueueStats {
+    queued: AtomicU64,
+    batches: AtomicU64,
+    writes: AtomicU64,
+    failed: AtomicU64,
+}
+
+pub struct WriteQueue {
+    db: Arc<Db>,
+    tx: mpsc::Sender<Message>,
+    stats: Arc<QueueStats>,
+}
+
+impl WriteQueue {
+    /// Start the writer thread for `db`.
+    pub fn start(db: Arc<Db>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let stats = Arc::new(QueueStats::default());
+        let worker_db = db.clone();
+        let worker_stats = stats.clone();
+        let spawned = std::thread::Builder::new()
+            .name("db-writer".into())
+            .spawn(move || run(&worker_db, rx, &worker_stats));
+        if let Err(e) = spawned {
+            log::error!("[DB] Failed to start the writer thread: {}", e);
+        }
+        Self { db, tx, stats }
+    }
+
+    /// Queue a write without waiting for it; failures are logged.
+    pub fn submit<F>(&self, label: &'static str, job: F)
+    where
+        F: FnOnce(&Connection) -> AppResult<()> + Send + 'static,
+    {
+        self.send(label, Box::new(job), None);
+    }
+
+    /// Queue a write and block until it is committed. Not for async contexts.
+    pub fn execute<F>(&self, label: &'static str, job: F) -> AppResult<()>
+    where
+        F: FnOnce(&Connection) -> AppResult<()> + Send + 'static,
+    {
+        let (reply, result) = mpsc::sync_channel(1);
+        self.send(label, Box::new(job), Some(reply));
+        result
+            .recv()
+            .map_err(|_| AppError::Database("Database writer stopped".into()))?
+    }
+
+    /// Block until every write queued so far is committed.
+    pub fn flush(&self) {
+        if self.stats.queued.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let (done, wait) = mpsc::sync_channel(1);
+        if self.tx.send(Message::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+
+    pub fn add_stats(&self, stats: &mut DbStats) {
+        stats.queued_writes = self.stats.queued.load(Ordering::Relaxed);
+        stats.write_batches = self.stats.batches.load(Ordering::Relaxed);
+        stats.batched_writes = self.stats.writes.load(Ordering::Relaxed);
+        stats.failed_writes = self.stats.failed.load(Ordering::Relaxed);
+    }
+
+    fn send(&self, label: &'static str, job: Job, reply: Option<mpsc::SyncSender<AppResult<()>>>) {
+        self.stats.queued.fetch_add(1, Ordering::Relaxed);
+        if let Err(mpsc::SendError(message)) = self.tx.send(Message::Write { label, job, reply }) {
+            // The writer thread is gone; write directly
+            commit(&self.db, vec![message], &self.stats);
+        }
+    }
+}
+
+fn run(db: &Db, rx: mpsc::Receiver<Message>, stats: &QueueStats) {
+    while let Ok(first) = rx.recv() {
+        let mut batch = vec![first];
+        let deadline = std::time::Instant::now() + connection::write_batch_window();
+        while batch.len() < MAX_BATCH && !batch.iter().any(Message::is_waited) {
+            let left = deadline.saturating_duration_since(std::time::Instant::now());
+            match rx.recv_timeout(left) {
+                Ok(message) => batch.push(message),
+                Err(_) => break,
+            }
+        }
+        while batch.len() < MAX_BATCH {
+            match rx.try_recv() {
+                Ok(message) => batch.push(message),
+                Err(_) => break,
+            }
+        }
+        commit(db, batch, stats);
+    }
+}
+
+/// Apply a batch in one transaction, then answer the writers waiting on it.
+fn commit(db: &Db, batch: Vec<Message>, stats: &QueueStats) {
+    let mut replies = Vec::new();
+    let mut flushes = Vec::new();
+    let mut writes = 0;
+    match db.lock() {
+        Ok(conn) => {
+            let tx = match conn.unchecked_transaction() {
+                Ok(tx) => Some(tx),
+                Err(e) => {
+                    log::warn!("[DB] Queued writes run without a transaction: {}", e);
+                    None
+                }
+            };
+            let target: &Connection = tx.as_deref().unwrap_or(&*conn);
+            for message in batch {
+                match message {
+                    Message::Write { label, job, reply } => {
+                        writes += 1;
+                        let result = job(target);
+                        if let Err(e) = &result {
+                            stats.failed.fetch_add(1, Ordering::Relaxed);
+                            if reply.is_none() {
+                                log::warn!("[DB] Queued {} write failed: {}", label, e);
+                            }
+                        }
+                        if let Some(reply) = reply {
+                            replies.push((reply, result));
+                        }
+                    }
+                    Message::Flush(done) => flushes.push(done),
+                }
+            }
+            if let Some(Err(e)) = tx.map(|tx| tx.commit()) {
+                log::warn!("[DB] Failed to commit {} queued writes: {}", writes, e);
+                stats.failed.fetch_add(writes, Ordering::Relaxed);
+                for (_, result) in replies.iter_mut() {
+                    *result = Err(AppError::Database(e.to_string()));
+                }
+            }
+        }
+        Err(e) => {
+            for message in batch {
+                match message {
+                    Message::Write { reply, .. } => {
+                        writes += 1;
+                        if let Some(reply) = reply {
+                            replies.push((reply, Err(AppError::Database(e.to_string()))));
+                        }
+                    }
+                    Message::Flush(done) => flushes.push(done),
+                }
+            }
+            stats.failed.fetch_add(writes, Ordering::Relaxed);
+        }
+    }
+    stats.queued.fetch_sub(writes, Ordering::Relaxed);
+    stats.batches.fetch_add(1, Ordering::Relaxed);
+    stats.writes.fetch_add(writes, Ordering::Relaxed);
+    for (reply, result) in replies {
+        let _ = reply.send(result);
+    }
+    for done in flushes {
+        let _ = done.send(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> WriteQueue {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (n INTEGER NOT NULL)").unwrap();
+        WriteQueue::start(Arc::new(Db::new(conn)))
+    }
+
+    fn insert(n: i64) -> impl FnOnce(&Connection) -> AppResult<()> {
+        move |db| {
+            db.execute("INSERT INTO t (n) VALUES (?1)", [n])
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn commits_queued_writes_in_order_before_an_awaited_one() {
+        let queue = queue();
+        for n in 0..10 {
+            queue.submit("test", insert(n));
+        }
+        queue.execute("test", insert(10)).unwrap();
+
+        let numbers: Vec<i64> = {
+            let db = queue.db.lock().unwrap();
+            let mut stmt = db.prepare("SELECT n FROM t ORDER BY rowid").unwrap();
+            let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+            rows.collect::<Result<_, _>>().unwrap()
+        };
+        assert_eq!(numbers, (0..=10).collect::<Vec<_>>());
+
+        let mut stats = DbStats::default();
+        queue.add_stats(&mut stats);
+        assert_eq!(stats.batched_writes, 11);
+        assert_eq!(stats.queued_writes, 0);
+        assert!(stats.write_batches < 11);
+    }
+
+    #[test]
+    fn failed_write_does_not_undo_its_batch() {
+        let queue = queue();
+        queue.submit("test", insert(1));
+        queue.submit("test", |db| {
+            db.execute("INSERT INTO t (n) VALUES (NULL)", [])
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            Ok(())
+        });
+        assert!(queue.execute("test", |db| {
+            db.execute("INSERT INTO missing (n) VALUES (1)", [])
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            Ok(())
+        })
+        .is_err());
+        queue.flush();
+
+        let count: i64 = queue.db.lock().unwrap().query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+        let mut stats = DbStats::default();
+        queue.add_stats(&mut stats);
+        assert_eq!(stats.failed_writes, 2);
+    }
+}
@@ -0,0 +1,490 @@
+//! Health checks for bug reports.
+//!
+//! `run` inspects the database, the agent and bridge processes the app
+//! holds, its ACP sessions and the commands agents and chat tools are started
+//! with, and reports each finding as ok, warning or error. It only looks:
+//! nothing is stopped or repaired. On request it also writes a diagnostic
+//! bundle, a zip under `~/.iaagenthub/diagnostics` holding the report, the
+//! app config, the agent setup, agent stderr and the tail of the app logs,
+//! with credentials and the home directory redacted.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::acp::orchestrator::orch_task_run_id;
+use crate::acp::{builtin, discovery};
+use crate::chat_tool::manager as bridge_manager;
+use crate::config;
+use crate::db::connection::{self, DbStats};
+use crate::db::migrations::get_base_dir;
+use crate::db::{agent_repo, chat_tool_repo, task_run_repo};
+use crate::error::{AppError, AppResult};
+use crate::models::diagnostics::{CheckStatus, DiagnosticCheck, DiagnosticReport};
+use crate::state::AppState;
+
+/// Task run statuses of runs that have agents working.
+const ACTIVE_RUN_STATUSES: &[&str] = &["analyzing", "running"];
+
+/// Lines kept from the end of each log file in a bundle.
+const LOG_TAIL_LINES: usize = 2000;
+
+const REDACTED: &str = "[redacted]";
+
+/// Key fragments marking a value as a credential.
+const SECRET_KEYS: &[&str] = &["key", "token", "secret", "password", "passwd", "auth", "cookie", "credential"];
+
+/// Prefixes of well-known credential formats.
+const SECRET_PREFIXES: &[&str] = &["sk-", "sk_", "ghp_", "gho_", "github_pat_", "xoxb-", "xoxp-", "AIza"];
+
+fn check(category: &str, subject: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> DiagnosticCheck {
+    DiagnosticCheck::new(category, subject, status, detail)
+}
+
+fn check_database(state: &AppState) -> Vec<DiagnosticCheck> {
+    let mut checks = vec![check_contention(&connection::stats(state))];
+    let db = match state.db.lock() {
+        Ok(db) => db,
+        Err(e) => {
+            checks.push(check("database", "connection", CheckStatus::Error, e.to_string()));
+            return checks;
+        }
+    };
+
+    let integrity: Result<Vec<String>, _> = db
+        .prepare("PRAGMA integrity_check")
+        .and_then(|mut stmt| stmt.query_map([], |row| row.get(0))?.collect());
+    checks.push(match integrity {
+        Ok(rows) if rows == ["ok"] => check("database", "integrity", CheckStatus::Ok, "Integrity check passed"),
+        Ok(rows) => check("database", "integrity", CheckStatus::Error, rows.join("\n")),
+        Err(e) => check("database", "integrity", CheckStatus::Error, e.to_string()),
+    });
+
+    let violations: Result<i64, _> = db
+        .prepare("PRAGMA foreign_key_check")
+        .and_then(|mut stmt| Ok(stmt.query_map([], |_| Ok(()))?.count() as i64));
+    checks.push(match violations {
+        Ok(0) => check("database", "foreign keys", CheckStatus::Ok, "No dangling references"),
+        Ok(n) => check("database", "foreign keys", CheckStatus::Warning, format!("{} rows reference missing records", n)),
+        Err(e) => check("database", "foreign keys", CheckStatus::Error, e.to_string()),
+    });
+
+    let migrations: Result<i64, _> = db.query_row("SELECT COUNT(*) FROM _migrations", [], |row| row.get(0));
+    checks.push(match migrations {
+        Ok(n) => check("database", "migrations", CheckStatus::Ok, format!("{} migrations applied", n)),
+        Err(e) => check("database", "migrations", CheckStatus::Error, e.to_string()),
+    });
+    checks
+}
+
+fn check_contention(stats: &DbStats) -> DiagnosticCheck {
+    let detail = format!(
+        "{} of {} connection locks waited (max {}ms), {} busy retries, {} writes in {} batches, {} queued",
+        stats.contended_locks,
+        stats.lock_acquisitions,
+        stats.lock_wait_ms_max,
+        stats.busy_retries,
+        stats.batched_writes,
+        stats.write_batches,
+        stats.queued_writes,
+    );
+    if stats.busy_timeouts > 0 || stats.failed_writes > 0 {
+        let failures = format!("{} statements gave up while busy, {} queued writes failed", stats.busy_timeouts, stats.failed_writes);
+        check("database", "contention", CheckStatus::Error, format!("{}; {}", failures, detail))
+    } else if stats.lock_wait_ms_max > 1000 {
+        check("database", "contention", CheckStatus::Warning, detail)
+    } else {
+        check("database", "contention", CheckStatus::Ok, detail)
+    }
+}
+
+async fn check_processes(state: &AppState) -> Vec<DiagnosticCheck> {
+    let mut checks = Vec::new();
+    let active_runs: Vec<String> = state.active_task_runs.lock().await.keys().cloned().collect();
+
+    let mut processes = state.agent_processes.lock().await;
+    for (key, process) in processes.iter_mut() {
+        let finding = match process.child.try_wait() {
+            Ok(Some(status)) => {
+                check("processes", key.clone(), CheckStatus::Error, format!("Agent process exited with {} but is still registered", status))
+            }
+            Err(e) => check("processes", key.clone(), CheckStatus::Error, format!("Cannot query process: {}", e)),
+            Ok(None) => match orch_task_run_id(key) {
+                Some(run) if !active_runs.iter().any(|r| r == run) => check(
+                    "processes",
+                    key.clone(),
+                    CheckStatus::Warning,
+                    format!("Orphaned: task run {} is no longer active", run),
+                ),
+                _ => check("processes", key.clone(), CheckStatus::Ok, format!("Running ({})", process.agent_type)),
+            },
+        };
+        checks.push(finding);
+    }
+    drop(processes);
+
+    let mut bridges = state.chat_tool_processes.lock().await;
+    for (id, process) in bridges.iter_mut() {
+        checks.push(if bridge_manager::check_process_alive(process) {
+            check("processes", id.clone(), CheckStatus::Ok, format!("{} bridge running", process.plugin_type))
+        } else {
+            check("processes", id.clone(), CheckStatus::Error, format!("{} bridge exited but is still registered", process.plugin_type))
+        });
+    }
+    checks
+}
+
+/// Process an ACP session belongs to: orchestration sessions are keyed
+/// "orch_session:{process_key}[@dir]", chat sessions use the agent's process.
+fn session_process_key<'a>(session_key: &'a str, agent_id: &'a str) -> &'a str {
+    match session_key.strip_prefix("orch_session:") {
+        Some(rest) => rest.split('@').next().unwrap_or(rest),
+        None => agent_id,
+    }
+}
+
+async fn check_sessions(state: &AppState) -> Vec<DiagnosticCheck> {
+    let mut checks = Vec::new();
+    let process_keys: Vec<String> = state.agent_processes.lock().await.keys().cloned().collect();
+    let sessions = state.acp_sessions.lock().await;
+    let stale: Vec<&String> = sessions
+        .iter()
+        .filter(|(key, info)| !process_keys.iter().any(|p| p == session_process_key(key, &info.agent_id)))
+        .map(|(key, _)| key)
+        .collect();
+    checks.push(if stale.is_empty() {
+        check("sessions", "ACP sessions", CheckStatus::Ok, format!("{} sessions, all with a running agent", sessions.len()))
+    } else {
+        check(
+            "sessions",
+            "ACP sessions",
+            CheckStatus::Warning,
+            format!("{} of {} sessions have no running agent: {}", stale.len(), sessions.len(), stale.iter().map(|k| k.as_str()).collect::<Vec<_>>().join(", ")),
+        )
+    });
+    drop(sessions);
+
+    let active_runs: Vec<String> = state.active_task_runs.lock().await.keys().cloned().collect();
+    let state_clone = state.clone();
+    let runs = crate::telemetry::spawn_blocking(move || task_run_repo::list_task_runs(&state_clone, None)).await;
+    match runs {
+        Ok(Ok(runs)) => {
+            let stuck: Vec<String> = runs
+                .iter()
+                .filter(|r| ACTIVE_RUN_STATUSES.contains(&r.status.as_str()) && !active_runs.contains(&r.id))
+                .map(|r| format!("{} ({})", r.id, r.status))
+                .collect();
+            checks.push(if stuck.is_empty() {
+                check("sessions", "task runs", CheckStatus::Ok, "No interrupted task runs")
+            } else {
+                check(
+                    "sessions",
+                    "task runs",
+                    CheckStatus::Warning,
+                    format!("{} runs are marked in progress but not running: {}", stuck.len(), stuck.join(", ")),
+                )
+            });
+        }
+        Ok(Err(e)) => checks.push(check("sessions", "task runs", CheckStatus::Error, e.to_string())),
+        Err(e) => checks.push(check("sessions", "task runs", CheckStatus::Error, e.to_string())),
+    }
+    checks
+}
+
+/// Whether an agent command can be started without downloading anything.
+async fn resolve_agent_command(command: &str) -> (CheckStatus, String) {
+    let path = Path::new(command);
+    if path.is_absolute() {
+        return if path.exists() {
+            (CheckStatus::Ok, command.to_string())
+        } else {
+            (CheckStatus::Error, format!("{} does not exist", command))
+        };
+    }
+    let basename = path.file_name().and_then(|n| n.to_str()).unwrap_or(command);
+    let builtin = builtin::get_builtin_agent();
+    if basename == builtin.command {
+        return if builtin.available {
+            (CheckStatus::Ok, "Built-in agent".into())
+        } else {
+            (CheckStatus::Error, "Built-in agent is not installed".into())
+        };
+    }
+    if let Some(resolved) = discovery::resolve_command(basename) {
+        return (CheckStatus::Ok, resolved);
+    }
+    match discovery::get_registry_entry_by_command(basename).await {
+        Some(entry) => {
+            if let Some(cached) = discovery::check_downloaded_binary(&entry.id) {
+                return (CheckStatus::Ok, cached.to_string_lossy().to_string());
+            }
+            match entry.distribution {
+                discovery::Distribution::Npx(npx) if discovery::resolve_command("npx").is_some() => {
+                    (CheckStatus::Ok, format!("Runs through npx ({})", npx.package))
+                }
+                discovery::Distribution::Npx(_) => (CheckStatus::Error, "Needs npx, which is not on PATH".into()),
+                discovery::Distribution::Binary(_) => {
+                    (CheckStatus::Warning, "Not downloaded yet; fetched on first start".into())
+                }
+            }
+        }
+        None => (CheckStatus::Error, format!("{} not found on PATH", basename)),
+    }
+}
+
+async fn check_agents(state: &AppState) -> Vec<DiagnosticCheck> {
+    let state_clone = state.clone();
+    let agents = match crate::telemetry::spawn_blocking(move || agent_repo::list_agents(&state_clone, None)).await {
+        Ok(Ok(agents)) => agents,
+        Ok(Err(e)) => return vec![check("agents", "agents", CheckStatus::Error, e.to_string())],
+        Err(e) => return vec![check("agents", "agents", CheckStatus::Error, e.to_string())],
+    };
+    let mut checks = Vec::new();
+    for agent in agents.iter().filter(|a| a.is_enabled) {
+        let (status, detail) = match agent.acp_command.as_deref().filter(|c| !c.trim().is_empty()) {
+            Some(command) => resolve_agent_command(command).await,
+            None => (CheckStatus::Warning, "No ACP command configured".into()),
+        };
+        checks.push(check("agents", agent.name.clone(), status, detail));
+    }
+    checks
+}
+
+/// Bridge scripts of the chat tools in use must exist and parse.
+async fn check_bridges(state: &AppState) -> Vec<DiagnosticCheck> {
+    let state_clone = state.clone();
+    let tools = match crate::telemetry::spawn_blocking(move || chat_tool_repo::list_chat_tools(&state_clone, None)).await {
+        Ok(Ok(tools)) => tools,
+        Ok(Err(e)) => return vec![check("bridges", "chat tools", CheckStatus::Error, e.to_string())],
+        Err(e) => return vec![check("bridges", "chat tools", CheckStatus::Error, e.to_string())],
+    };
+    let mut plugin_types: Vec<String> = tools.into_iter().map(|t| t.plugin_type).collect();
+    plugin_types.sort();
+    plugin_types.dedup();
+
+    let node = discovery::resolve_command("node");
+    let mut checks = Vec::new();
+    for plugin_type in plugin_types {
+        let path = match bridge_manager::get_bridge_path(&plugin_type) {
+            Ok(path) => path,
+            Err(e) => {
+                checks.push(check("bridges", plugin_type, CheckStatus::Error, e.to_string()));
+                continue;
+            }
+        };
+        let Some(node) = &node else {
+            checks.push(check("bridges", plugin_type, CheckStatus::Error, "node not found on PATH"));
+            continue;
+        };
+        let output = tokio::process::Command::new(node)
+            .arg("--check")
+            .arg(&path)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .await;
+        checks.push(match output {
+            Ok(output) if output.status.success() => check("bridges", plugin_type, CheckStatus::Ok, path),
+            Ok(output) => check(
+                "bridges",
+                plugin_type,
+                CheckStatus::Error,
+                format!("{} does not parse: {}", path, String::from_utf8_lossy(&output.stderr).trim()),
+            ),
+            Err(e) => check("bridges", plugin_type, CheckStatus::Error, format!("Failed to run node: {}", e)),
+        });
+    }
+    checks
+}
+
+/// Run every check, and write a bundle with the logs in `log_dir` when
+/// `create_bundle` is set.
+pub async fn run(state: &AppState, create_bundle: bool, log_dir: Option<PathBuf>) -> AppResult<DiagnosticReport> {
+    let state_clone = state.clone();
+    let mut checks = crate::telemetry::spawn_blocking(move || check_database(&state_clone))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    checks.extend(check_processes(state).await);
+    checks.extend(check_sessions(state).await);
+    checks.extend(check_agents(state).await);
+    checks.extend(check_bridges(state).await);
+
+    let count = |status: CheckStatus| checks.iter().filter(|c| c.status == status).count();
+    let mut report = DiagnosticReport {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        errors: count(CheckStatus::Error),
+        warnings: count(CheckStatus::Warning),
+        checks,
+        bundle_path: None,
+    };
+    if create_bundle {
+        let path = write_bundle(state, &report, log_dir.as_deref()).await?;
+        report.bundle_path = Some(path.to_string_lossy().to_string());
+    }
+    Ok(report)
+}
+
+async fn write_bundle(state: &AppState, report: &DiagnosticReport, log_dir: Option<&Path>) -> AppResult<PathBuf> {
+    let home = dirs::home_dir().map(|h| h.to_string_lossy().to_string());
+    let home = home.as_deref();
+
+    let mut files: Vec<(String, String)> = Vec::new();
+    files.push(("report.json".into(), redact_text(&serde_json::to_string_pretty(report)?, home)));
+
+    let config = serde_json::to_value(config::current(state))?;
+    files.push(("config.json".into(), redact_text(&serde_json::to_string_pretty(&redact_json(config))?, home)));
+
+    let state_clone = state.clone();
+    let agents = crate::telemetry::spawn_blocking(move || agent_repo::list_agents(&state_clone, None))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    let agents: Vec<serde_json::Value> = agents
+        .iter()
+        .map(|a| {
+            serde_json::json!({
+                "id": a.id,
+                "name": a.name,
+                "model": a.model,
+                "acp_command": a.acp_command,
+                "acp_args_json": a.acp_args_json,
+                "is_enabled": a.is_enabled,
+                "disabled_reason": a.disabled_reason,
+                "is_control_hub": a.is_control_hub,
+                "workspace_id": a.workspace_id,
+            })
+        })
+        .collect();
+    files.push(("agents.json".into(), redact_text(&serde_json::to_string_pretty(&redact_json(serde_json::json!(agents)))?, home)));
+
+    {
+        let processes = state.agent_processes.lock().await;
+        for (key, process) in processes.iter() {
+            let stderr = process.stderr_lines.lock().await.join("\n");
+            if !stderr.is_empty() {
+                files.push((format!("stderr/{}.log", key.replace([':', '/', '\\'], "_")), redact_text(&stderr, home)));
+            }
+        }
+    }
+
+    if let Some(dir) = log_dir {
+        for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|e| e == "log") {
+                let text = std::fs::read_to_string(&path).unwrap_or_default();
+                let lines: Vec<&str> = text.lines().collect();
+                let tail = lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].join("\n");
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                files.push((format!("logs/{}", name), redact_text(&tail, home)));
+            }
+        }
+    }
+
+    let dir = get_base_dir().join("diagnostics");
+    let path = dir.join(format!("diagnostics-{}.zip", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    let zip_path = path.clone();
+    tokio::task::spawn_blocking(move || write_zip(&dir, &zip_path, &files))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    Ok(path)
+}
+
+fn write_zip(dir: &Path, path: &Path, files: &[(String, String)]) -> AppResult<()> {
+    std::fs::create_dir_all(dir)?;
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path)?);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in files {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| AppError::Internal(format!("Failed to write {}: {}", name, e)))?;
+        zip.write_all(content.as_bytes())?;
+    }
+    zip.finish().map_err(|e| AppError::Internal(format!("Failed to write bundle: {}", e)))?;
+    Ok(())
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SECRET_KEYS.iter().any(|fragment| key.contains(fragment))
+}
+
+/// Replace the values of credential-like keys, at any depth.
+fn redact_json(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::String(s) if is_secret_key(&key) && !s.is_empty() => REDACTED.into(),
+                    other => redact_json(other),
+                };
+                (key, value)
+            })
+            .collect(),
+        serde_json::Value::Array(items) => items.into_iter().map(redact_json).collect(),
+        other => other,
+    }
+}
+
+/// Mask credentials in free text: known token formats, `Bearer <token>`,
+/// and `NAME=value` / `"name": "value"` pairs with a credential-like name.
+/// The home directory becomes `~`.
+pub(crate) fn redact_text(text: &str, home: Option<&str>) -> String {
+    let text = match home.filter(|h| h.len() > 1) {
+        Some(home) => text.replace(home, "~"),
+        None => text.to_string(),
+    };
+    let mut out = String::with_capacity(text.len());
+    let mut redact_next = false;
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end();
+        let trailing = &piece[word.len()..];
+        let bare = word.trim_matches(|c: char| matches!(c, '"' | '\'' | ',' | '(' | ')'));
+        let is_scheme = bare.eq_ignore_ascii_case("bearer") || bare.eq_ignore_ascii_case("basic");
+        let masked = if bare.is_empty() || is_scheme {
+            None
+        } else if redact_next || (SECRET_PREFIXES.iter().any(|p| bare.starts_with(p)) && bare.len() > 12) {
+            Some(word.replace(bare, REDACTED))
+        } else if let Some((name, _)) = bare.split_once('=').filter(|(n, v)| is_secret_key(n) && !v.is_empty()) {
+            Some(word.replace(bare, &format!("{}={}", name, REDACTED)))
+        } else {
+            None
+        };
+        if !bare.is_empty() {
+            redact_next = is_scheme || (bare.ends_with(':') && is_secret_key(bare.trim_end_matches(':')));
+        }
+        out.push_str(masked.as_deref().unwrap_or(word));
+        out.push_str(trailing);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_credentials_and_home() {
+        let json = redact_json(serde_json::json!({
+            "name": "a", "env": { "ANTHROPIC_API_KEY": "sk-ant-123", "DEBUG": "1" }, "tokens_in": 5
+        }));
+        assert_eq!(json["env"]["ANTHROPIC_API_KEY"], REDACTED);
+        assert_eq!(json["env"]["DEBUG"], "1");
+        assert_eq!(json["tokens_in"], 5);
+
+        let text = redact_text(
+            "spawn /home/ann/bin/agent OPENAI_API_KEY=abc123 with Authorization: Bearer xyz789 and sk-ant-api03-abcdefgh",
+            Some("/home/ann"),
+        );
+        assert_eq!(
+            text,
+            "spawn ~/bin/agent OPENAI_API_KEY=[redacted] with Authorization: Bearer [redacted] and [redacted]"
+        );
+    }
+
+    #[test]
+    fn maps_sessions_to_processes() {
+        assert_eq!(orch_task_run_id("orch:run-1:agent-1"), Some("run-1"));
+        assert_eq!(orch_task_run_id("agent-1"), None);
+        assert_eq!(session_process_key("orch_session:orch:r:a@/tmp/x", "a"), "orch:r:a");
+        assert_eq!(session_process_key("session-1", "a"), "a");
+    }
+}
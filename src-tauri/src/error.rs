@@ -1,25 +1,41 @@
+//! Application errors.
+//!
+//! Every variant has a stable machine-readable `code`. Commands return errors
+//! to the frontend as `{ code, message, agent_id?, task_run_id?, ... }`, so the
+//! UI can react to the kind of failure instead of parsing the message.
+
 use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum AppError {
+    // Storage
     #[error("Database error: {0}")]
     Database(String),
 
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    // Requests
     #[error("Not found: {0}")]
     NotFound(String),
 
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
 
-    #[error("ACP error: {0}")]
-    Acp(String),
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
 
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+    /// A mutating command called while the app is in observer mode
+    #[error("Observer mode: {0} is disabled")]
+    ObserverMode(String),
 
-    #[error("Serialization error: {0}")]
-    Serde(#[from] serde_json::Error),
+    // Agents
+    #[error("ACP error: {0}")]
+    Acp(String),
 
     #[error("Agent not running: {0}")]
     AgentNotRunning(String),
@@ -27,26 +43,191 @@ pub enum AppError {
     #[error("Agent already running: {0}")]
     AgentAlreadyRunning(String),
 
-    #[error("Transport error: {0}")]
-    Transport(String),
+    #[error("Agent {agent_id} has no ACP command configured")]
+    AgentNotConfigured { agent_id: String },
 
-    #[error("Permission denied: {0}")]
-    PermissionDenied(String),
+    /// The agent's process went away while it was being talked to
+    #[error("Agent {agent_id} process not found (key={process_key})")]
+    AgentProcessLost { agent_id: String, process_key: String },
+
+    #[error("Agent cancelled")]
+    AgentCancelled { agent_id: String, task_run_id: Option<String> },
+
+    #[error("Agent returned no response. Check that the agent is running and configured correctly.")]
+    AgentNoResponse { agent_id: String, task_run_id: Option<String> },
+
+    /// JSON-RPC error the agent answered a prompt with
+    #[error("Agent error (code {code}): {message}")]
+    AgentFailed {
+        agent_id: String,
+        task_run_id: Option<String>,
+        code: i64,
+        message: String,
+    },
 
     #[error("Version upgrade required: {0}")]
     VersionUpgradeRequired(String),
 
+    // Transport
+    #[error("Transport error: {0}")]
+    Transport(String),
+
+    /// Messages say what was waited for, e.g. "Timeout (90s) waiting for ..."
+    #[error("{0}")]
+    Timeout(String),
+
+    // Chat tools
+    #[error("Chat tool bridge {chat_tool_id}: {message}")]
+    Bridge { chat_tool_id: String, message: String },
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
 
+impl AppError {
+    /// Stable identifier of the kind of error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Database(_) => "database",
+            Self::Io(_) => "io",
+            Self::Serde(_) => "serialization",
+            Self::NotFound(_) => "not_found",
+            Self::InvalidRequest(_) => "invalid_request",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::ObserverMode(_) => "observer_mode",
+            Self::Acp(_) => "acp",
+            Self::AgentNotRunning(_) => "agent_not_running",
+            Self::AgentAlreadyRunning(_) => "agent_already_running",
+            Self::AgentNotConfigured { .. } => "agent_not_configured",
+            Self::AgentProcessLost { .. } => "agent_process_lost",
+            Self::AgentCancelled { .. } => "agent_cancelled",
+            Self::AgentNoResponse { .. } => "agent_no_response",
+            Self::AgentFailed { .. } => "agent_failed",
+            Self::VersionUpgradeRequired(_) => "version_upgrade_required",
+            Self::Transport(_) => "transport",
+            Self::Timeout(_) => "timeout",
+            Self::Bridge { .. } => "bridge",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    /// Whether the error is likely to pass on its own: the agent or its
+    /// provider was slow, overloaded or unreachable rather than misconfigured.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Timeout(_) | Self::Transport(_) | Self::AgentProcessLost { .. } | Self::AgentNoResponse { .. } => true,
+            Self::AgentFailed { message, .. } => {
+                let message = message.to_lowercase();
+                ["overloaded", "rate limit", "rate_limit", "too many requests", "unavailable", "429", "503", "529"]
+                    .iter()
+                    .any(|m| message.contains(m))
+            }
+            _ => false,
+        }
+    }
+
+    pub fn agent_id(&self) -> Option<&str> {
+        match self {
+            Self::AgentNotConfigured { agent_id }
+            | Self::AgentProcessLost { agent_id, .. }
+            | Self::AgentCancelled { agent_id, .. }
+            | Self::AgentNoResponse { agent_id, .. }
+            | Self::AgentFailed { agent_id, .. } => Some(agent_id),
+            _ => None,
+        }
+    }
+
+    pub fn task_run_id(&self) -> Option<&str> {
+        match self {
+            Self::AgentCancelled { task_run_id, .. }
+            | Self::AgentNoResponse { task_run_id, .. }
+            | Self::AgentFailed { task_run_id, .. } => task_run_id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Shape of an error as the frontend receives it.
+#[derive(Serialize)]
+struct ErrorPayload<'a> {
+    code: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    agent_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    task_run_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chat_tool_id: Option<&'a str>,
+    /// JSON-RPC error code of `agent_failed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    agent_error_code: Option<i64>,
+}
+
 impl Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        ErrorPayload {
+            code: self.code(),
+            message: self.to_string(),
+            agent_id: self.agent_id(),
+            task_run_id: self.task_run_id(),
+            chat_tool_id: match self {
+                Self::Bridge { chat_tool_id, .. } => Some(chat_tool_id),
+                _ => None,
+            },
+            agent_error_code: match self {
+                Self::AgentFailed { code, .. } => Some(*code),
+                _ => None,
+            },
+        }
+        .serialize(serializer)
     }
 }
 
 pub type AppResult<T> = Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_message_and_context() {
+        let error = AppError::AgentFailed {
+            agent_id: "a1".into(),
+            task_run_id: Some("t1".into()),
+            code: -32603,
+            message: "overloaded".into(),
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "agent_failed",
+                "message": "Agent error (code -32603): overloaded",
+                "agent_id": "a1",
+                "task_run_id": "t1",
+                "agent_error_code": -32603,
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(AppError::NotFound("Agent a1".into())).unwrap(),
+            serde_json::json!({ "code": "not_found", "message": "Not found: Agent a1" })
+        );
+    }
+
+    #[test]
+    fn classifies_transient_errors() {
+        let failed = |message: &str| AppError::AgentFailed {
+            agent_id: "a1".into(),
+            task_run_id: None,
+            code: -32603,
+            message: message.into(),
+        };
+        assert!(failed("Provider overloaded, try again").is_transient());
+        assert!(failed("HTTP 429: Rate limit exceeded").is_transient());
+        assert!(AppError::Timeout("Timeout (90s) waiting for the agent".into()).is_transient());
+        assert!(!failed("Invalid API key").is_transient());
+        assert!(!AppError::AgentNotConfigured { agent_id: "a1".into() }.is_transient());
+    }
+}
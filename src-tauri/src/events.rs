@@ -0,0 +1,447 @@
+//! Events emitted to the frontend.
+//!
+//! Each event the orchestrator and the chat tool bridges emit has a registered
+//! `Topic`: its name, the payload fields the frontend reads and how emits are
+//! handled. Topics carrying a current state (a status, a QR code) skip a
+//! payload identical to the previous one for the same subject, and can be
+//! coalesced so a subject gets at most one emit per interval: the latest
+//! payload is held and emitted when the interval ends. Lifecycle topics are
+//! also kept in `event_log` so a reloaded window can catch up. Emit failures
+//! are logged and never abort the caller.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::db::event_log_repo;
+use crate::state::AppState;
+
+/// An event name and how its emits are handled.
+#[derive(Debug)]
+pub struct Topic {
+    pub name: &'static str,
+    /// Top-level payload fields the frontend reads
+    pub fields: &'static [&'static str],
+    /// Payload field naming what the event is about; deduplication and
+    /// coalescing are per subject
+    pub subject: Option<&'static str>,
+    /// Skip a payload identical to the subject's previous one
+    pub dedup: bool,
+    /// At most one emit per subject in this many milliseconds (0: no limit)
+    pub min_interval_ms: u64,
+    /// Keep emits in the event log
+    pub persist: bool,
+}
+
+impl Topic {
+    const fn new(name: &'static str, fields: &'static [&'static str]) -> Self {
+        Self { name, fields, subject: None, dedup: false, min_interval_ms: 0, persist: false }
+    }
+
+    const fn subject(mut self, field: &'static str) -> Self {
+        self.subject = Some(field);
+        self
+    }
+
+    const fn dedup(mut self) -> Self {
+        self.dedup = true;
+        self
+    }
+
+    const fn coalesce(mut self, min_interval_ms: u64) -> Self {
+        self.min_interval_ms = min_interval_ms;
+        self
+    }
+
+    const fn persist(mut self) -> Self {
+        self.persist = true;
+        self
+    }
+
+    fn gated(&self) -> bool {
+        self.dedup || self.min_interval_ms > 0
+    }
+}
+
+// Orchestration runs
+pub const RUN_STARTED: Topic =
+    Topic::new("orchestration:started", &["taskRunId", "status", "workspaceId", "resumed"]).subject("taskRunId").persist();
+pub const RUN_RESUMING: Topic = Topic::new("orchestration:resuming", &["taskRunId", "status"]);
+/// Runs left unfinished by the previous session, sent on launch before they
+/// are resumed, held or failed per `policy`
+pub const RUNS_RECOVERABLE: Topic = Topic::new("orchestration:recoverable", &["runs", "policy"]);
+pub const SKILLS_DISCOVERED: Topic = Topic::new("orchestration:skills_discovered", &["taskRunId", "skillsCount"]);
+pub const PLAN_READY: Topic =
+    Topic::new("orchestration:plan_ready", &["taskRunId", "plan", "confidence", "requiresApproval"]).subject("taskRunId").persist();
+pub const PLAN_VALIDATED: Topic = Topic::new("orchestration:plan_validated", &["taskRunId", "validation"]);
+pub const PLAN_REVISED: Topic =
+    Topic::new("orchestration:plan_revised", &["taskRunId", "afterSequenceOrder", "analysis", "plan"])
+        .subject("taskRunId")
+        .persist();
+pub const PROMPT_SPLIT: Topic = Topic::new(
+    "orchestration:prompt_split",
+    &["taskRunId", "agentId", "model", "parts", "estimatedTokens", "omittedTokens"],
+);
+pub const HUB_FAILOVER: Topic =
+    Topic::new("orchestration:hub_failover", &["taskRunId", "fromAgentId", "toAgentId", "error"]).subject("taskRunId").persist();
+pub const AGENT_STARTED: Topic = Topic::new(
+    "orchestration:agent_started",
+    &[
+        "taskRunId", "agentId", "agentName", "assignmentId", "sequenceOrder", "model", "acpSessionId", "isCorrection",
+        "isRegeneration", "resumed", "retryOfAssignmentId",
+    ],
+);
+pub const AGENT_CHUNK: Topic = Topic::new("orchestration:agent_chunk", &["taskRunId", "agentId", "text"]);
+pub const AGENT_THOUGHT: Topic = Topic::new("orchestration:agent_thought", &["taskRunId", "agentId", "text"]);
+pub const AGENT_TOOL_CALL: Topic = Topic::new(
+    "orchestration:agent_tool_call",
+    &[
+        "taskRunId", "agentId", "toolCallId", "title", "name", "status", "rawInput", "rawInputArtifactId", "rawOutput",
+        "rawOutputArtifactId",
+    ],
+);
+pub const AGENT_COMPLETED: Topic = Topic::new(
+    "orchestration:agent_completed",
+    &[
+        "taskRunId", "agentId", "agentName", "assignmentId", "status", "output", "error", "errorCode", "acpSessionId",
+        "cached", "durationMs", "tokensIn", "tokensOut", "cacheCreationTokens", "cacheReadTokens",
+    ],
+);
+/// An image or file found in an agent's output and kept as a task artifact
+pub const AGENT_ARTIFACT: Topic =
+    Topic::new("orchestration:agent_artifact", &["taskRunId", "assignmentId", "agentId", "artifact"])
+        .subject("taskRunId")
+        .persist();
+pub const AGENT_CAPPED: Topic =
+    Topic::new("orchestration:agent_capped", &["taskRunId", "agentId", "tokensOut", "cost", "wrappingUp"]);
+pub const AGENT_NUDGED: Topic =
+    Topic::new("orchestration:agent_nudged", &["taskRunId", "agentId", "nudgeCount", "maxNudges"]);
+pub const AGENT_AUTO_DISABLED: Topic =
+    Topic::new("orchestration:agent_auto_disabled", &["taskRunId", "agentId", "agentName", "reason"]).subject("agentId").persist();
+/// Every agent was disabled when a run started; `action` is "reenabled_agents"
+/// or "run_on_hub"
+pub const AGENTS_RECOVERY: Topic = Topic::new(
+    "orchestration:agents_recovery",
+    &["taskRunId", "workspaceId", "action", "agentIds", "message"],
+)
+.subject("taskRunId")
+.persist();
+pub const AGENT_UPGRADING: Topic =
+    Topic::new("orchestration:agent_upgrading", &["taskRunId", "agentId", "agentName", "package"]);
+pub const AGENT_UPGRADED: Topic =
+    Topic::new("orchestration:agent_upgraded", &["taskRunId", "agentId", "agentName", "package"]);
+pub const AGENT_UPGRADE_FAILED: Topic =
+    Topic::new("orchestration:agent_upgrade_failed", &["taskRunId", "agentId", "agentName", "error"]);
+pub const AWAITING_CONFIRMATION: Topic = Topic::new(
+    "orchestration:awaiting_confirmation",
+    &["taskRunId", "agentId", "agentName", "output", "agentOutputs"],
+);
+pub const CONFIRMATION_PAUSED: Topic = Topic::new("orchestration:confirmation_paused", &["taskRunId"]);
+pub const AWAITING_PLAN_APPROVAL: Topic =
+    Topic::new("orchestration:awaiting_plan_approval", &["taskRunId", "confidence", "threshold"]).subject("taskRunId").persist();
+pub const FEEDBACK: Topic = Topic::new("orchestration:feedback", &["taskRunId", "message", "actionCount"]);
+pub const FEEDBACK_ACTION: Topic = Topic::new(
+    "orchestration:feedback_action",
+    &["taskRunId", "action", "agentId", "agentName", "correctionsUsed", "maxCorrections"],
+);
+pub const NEEDS_REVIEW: Topic = Topic::new("orchestration:needs_review", &["taskRunId"]).subject("taskRunId").persist();
+pub const ORCH_PERMISSION: Topic = Topic::new(
+    "orchestration:orch_permission",
+    &["taskRunId", "agentId", "requestId", "sessionId", "toolCall", "options", "requestKey"],
+);
+/// A request joined the prompt `requestId` of a similar one; `count` is the
+/// number of requests the prompt now answers
+pub const ORCH_PERMISSION_BATCHED: Topic = Topic::new(
+    "orchestration:orch_permission_batched",
+    &["taskRunId", "agentId", "requestId", "count", "toolCall"],
+);
+/// A request answered by a decision remembered for the run or the agent, by
+/// the assignment's permission profile or by the run's or agent's preset
+pub const PERMISSION_REMEMBERED: Topic = Topic::new(
+    "orchestration:permission_remembered",
+    &["taskRunId", "agentId", "requestKey", "allowed", "scope"],
+);
+pub const A2A_CALL: Topic =
+    Topic::new("orchestration:a2a_call", &["taskRunId", "callerAgentId", "targetAgentId", "iteration", "prompt"]);
+pub const A2A_RESULT: Topic = Topic::new(
+    "orchestration:a2a_result",
+    &["taskRunId", "callerAgentId", "targetAgentId", "iteration", "resultPreview"],
+);
+pub const SUMMARY_CHUNK: Topic = Topic::new("orchestration:summary_chunk", &["taskRunId", "agentId", "text"]);
+pub const RUN_COMPLETED: Topic = Topic::new(
+    "orchestration:completed",
+    &[
+        "taskRunId", "timedOut", "summary", "totalDurationMs", "totalTokensIn", "totalTokensOut",
+        "totalCacheCreationTokens", "totalCacheReadTokens",
+    ],
+)
+.subject("taskRunId")
+.persist();
+/// A run reached its wall-clock limit: no more assignments start and it is
+/// summarized without confirmation; `notStarted` planned assignments were skipped
+pub const RUN_TIMED_OUT: Topic =
+    Topic::new("orchestration:timed_out", &["taskRunId", "limitMs", "notStarted"]).subject("taskRunId").persist();
+pub const RUN_ERROR: Topic =
+    Topic::new("orchestration:error", &["taskRunId", "error", "errorCode"]).subject("taskRunId").persist();
+pub const TASK_RUN_CREATED: Topic = Topic::new("orchestration:task_run_created", &["taskRun"]);
+pub const TASK_RUN_UPDATED: Topic =
+    Topic::new("orchestration:task_run_updated", &["taskRunId", "status"]).subject("taskRunId").dedup();
+/// A run paused at a sequence group to give its slot to `byTaskRunId`, a
+/// run of a higher priority
+pub const RUN_PREEMPTED: Topic =
+    Topic::new("orchestration:preempted", &["taskRunId", "byTaskRunId"]).subject("taskRunId").persist();
+pub const RUN_PREEMPTION_ENDED: Topic =
+    Topic::new("orchestration:preemption_ended", &["taskRunId"]).subject("taskRunId").persist();
+/// A run of a workspace with `exclusive_runs` on started; the workspace's
+/// other runs wait until it is unlocked
+pub const WORKSPACE_LOCKED: Topic =
+    Topic::new("orchestration:workspace_locked", &["workspaceId", "taskRunId"]).subject("workspaceId").persist();
+pub const WORKSPACE_UNLOCKED: Topic =
+    Topic::new("orchestration:workspace_unlocked", &["workspaceId", "taskRunId"]).subject("workspaceId").persist();
+
+// Agent processes
+pub const ACP_AGENT_STARTED: Topic = Topic::new("acp:agent_started", &["agent_id", "status"]);
+/// An agent's process was stopped after going unused, see `idle_agents`
+pub const ACP_AGENT_STOPPED: Topic = Topic::new("acp:agent_stopped", &["agent_id", "reason"]);
+/// The agent's JSON-RPC request, forwarded as is
+pub const AGENT_TERMINAL_OUTPUT: Topic = Topic::new("acp:terminal_output", &["terminalId", "agentId", "data"]);
+pub const AGENT_TERMINAL_WAITING: Topic =
+    Topic::new("acp:terminal_waiting", &["terminalId", "agentId", "prompt"]).subject("terminalId").persist();
+pub const AGENT_TERMINAL_RESUMED: Topic = Topic::new("acp:terminal_resumed", &["terminalId", "agentId"]);
+pub const AGENT_TERMINAL_CLOSED: Topic = Topic::new("acp:terminal_closed", &["terminalId", "agentId"]);
+pub const AGENT_AUTH_REQUIRED: Topic = Topic::new(
+    "acp:auth_required",
+    &["agentId", "agentName", "taskRunId", "terminalId", "url", "code"],
+)
+.subject("agentId")
+.persist();
+pub const AGENT_AUTH_COMPLETED: Topic =
+    Topic::new("acp:auth_completed", &["agentId", "agentName", "taskRunId", "success", "error"])
+        .subject("agentId")
+        .persist();
+pub const ACP_PERMISSION_REQUEST: Topic = Topic::new("acp:permission_request", &["jsonrpc", "id", "method", "params"]);
+
+// Chat tool bridges
+pub const CHAT_TOOL_STATUS_CHANGED: Topic = Topic::new("chat_tool:status_changed", &["chatToolId", "status", "message"])
+    .subject("chatToolId")
+    .dedup()
+    .coalesce(250)
+    .persist();
+pub const CHAT_TOOL_ERROR: Topic = Topic::new("chat_tool:error", &["chatToolId", "error"]);
+pub const CHAT_TOOL_CAPABILITIES: Topic =
+    Topic::new("chat_tool:capabilities", &["chatToolId", "bridgeVersion", "capabilities"]);
+pub const CHAT_TOOL_INCOMPATIBLE: Topic = Topic::new(
+    "chat_tool:incompatible",
+    &["chatToolId", "message", "protocolVersion", "minSupportedVersion", "maxSupportedVersion"],
+);
+pub const CHAT_TOOL_PROTOCOL_MISMATCH: Topic = Topic::new(
+    "chat_tool:protocol_mismatch",
+    &["chatToolId", "eventType", "error", "appProtocolVersion", "bridgeProtocolVersion"],
+);
+pub const CHAT_TOOL_QR_CODE: Topic = Topic::new("chat_tool:qr_code", &["chatToolId", "url"]).subject("chatToolId").dedup();
+pub const CHAT_TOOL_LOGIN: Topic =
+    Topic::new("chat_tool:login", &["chatToolId", "userId", "userName"]).subject("chatToolId").persist();
+pub const CHAT_TOOL_LOGOUT: Topic = Topic::new("chat_tool:logout", &["chatToolId"]).subject("chatToolId").persist();
+pub const CHAT_TOOL_MESSAGE_RECEIVED: Topic = Topic::new("chat_tool:message_received", &["chatToolId", "message"]);
+pub const CHAT_TOOL_MESSAGE_PROCESSED: Topic =
+    Topic::new("chat_tool:message_processed", &["chatToolId", "messageId", "agentResponse"]);
+pub const CHAT_TOOL_MESSAGE_FILTERED: Topic =
+    Topic::new("chat_tool:message_filtered", &["chatToolId", "messageId", "senderId", "reason"]);
+pub const CHAT_TOOL_QUOTA_EXCEEDED: Topic =
+    Topic::new("chat_tool:quota_exceeded", &["chatToolId", "senderId", "contactId", "reason"]);
+pub const CHAT_TOOL_ESCALATED: Topic =
+    Topic::new("chat_tool:escalated", &["chatToolId", "escalation"]).subject("chatToolId").persist();
+
+/// Every registered topic.
+pub const TOPICS: &[&Topic] = &[
+    &RUN_STARTED, &RUN_RESUMING, &RUNS_RECOVERABLE, &SKILLS_DISCOVERED, &PLAN_READY, &PLAN_VALIDATED, &PLAN_REVISED, &PROMPT_SPLIT,
+    &HUB_FAILOVER, &AGENT_STARTED, &AGENT_CHUNK, &AGENT_THOUGHT, &AGENT_TOOL_CALL, &AGENT_COMPLETED, &AGENT_ARTIFACT,
+    &AGENT_CAPPED, &AGENT_NUDGED, &AGENT_AUTO_DISABLED, &AGENTS_RECOVERY, &AGENT_UPGRADING, &AGENT_UPGRADED, &AGENT_UPGRADE_FAILED,
+    &AWAITING_CONFIRMATION, &CONFIRMATION_PAUSED, &AWAITING_PLAN_APPROVAL, &FEEDBACK, &FEEDBACK_ACTION, &NEEDS_REVIEW,
+    &ORCH_PERMISSION, &ORCH_PERMISSION_BATCHED, &PERMISSION_REMEMBERED,
+    &A2A_CALL, &A2A_RESULT, &SUMMARY_CHUNK, &RUN_COMPLETED, &RUN_TIMED_OUT, &RUN_ERROR, &TASK_RUN_CREATED, &TASK_RUN_UPDATED,
+    &WORKSPACE_LOCKED, &WORKSPACE_UNLOCKED, &RUN_PREEMPTED, &RUN_PREEMPTION_ENDED,
+    &ACP_AGENT_STARTED,
+    &ACP_AGENT_STOPPED, &AGENT_TERMINAL_OUTPUT, &AGENT_TERMINAL_WAITING, &AGENT_TERMINAL_RESUMED, &AGENT_TERMINAL_CLOSED,
+    &AGENT_AUTH_REQUIRED, &AGENT_AUTH_COMPLETED,
+    &ACP_PERMISSION_REQUEST, &CHAT_TOOL_STATUS_CHANGED, &CHAT_TOOL_ERROR, &CHAT_TOOL_CAPABILITIES,
+    &CHAT_TOOL_INCOMPATIBLE, &CHAT_TOOL_PROTOCOL_MISMATCH, &CHAT_TOOL_QR_CODE, &CHAT_TOOL_LOGIN, &CHAT_TOOL_LOGOUT,
+    &CHAT_TOOL_MESSAGE_RECEIVED, &CHAT_TOOL_MESSAGE_PROCESSED, &CHAT_TOOL_MESSAGE_FILTERED, &CHAT_TOOL_QUOTA_EXCEEDED,
+    &CHAT_TOOL_ESCALATED,
+];
+
+/// Subjects not emitted to for this long are forgotten once the gate grows.
+const GATE_IDLE_SECS: u64 = 600;
+const GATE_PRUNE_LEN: usize = 4096;
+
+#[derive(Debug, PartialEq)]
+enum Admit {
+    Now,
+    /// Held; emit the latest payload after this delay
+    Later(Duration),
+    Skip,
+}
+
+struct Last {
+    payload: Value,
+    at: Instant,
+    /// Held for the end of the interval
+    pending: Option<Value>,
+}
+
+/// Last emit per topic and subject of the deduplicated and coalesced topics.
+#[derive(Default)]
+pub struct EventGate {
+    last: HashMap<(&'static str, String), Last>,
+}
+
+impl EventGate {
+    fn admit(&mut self, topic: &Topic, subject: &str, payload: &Value, now: Instant) -> Admit {
+        let key = (topic.name, subject.to_string());
+        let Some(last) = self.last.get_mut(&key) else {
+            if self.last.len() >= GATE_PRUNE_LEN {
+                let idle = Duration::from_secs(GATE_IDLE_SECS);
+                self.last.retain(|_, l| l.pending.is_some() || now.duration_since(l.at) < idle);
+            }
+            self.last.insert(key, Last { payload: payload.clone(), at: now, pending: None });
+            return Admit::Now;
+        };
+        if topic.dedup && last.pending.as_ref().unwrap_or(&last.payload) == payload {
+            return Admit::Skip;
+        }
+        let interval = Duration::from_millis(topic.min_interval_ms);
+        let since = now.duration_since(last.at);
+        if since < interval {
+            // An emit already held will send this payload instead
+            let held = last.pending.replace(payload.clone()).is_some();
+            return if held { Admit::Skip } else { Admit::Later(interval - since) };
+        }
+        last.payload = payload.clone();
+        last.at = now;
+        Admit::Now
+    }
+
+    /// The held payload to emit once the interval has ended.
+    fn release(&mut self, topic: &Topic, subject: &str, now: Instant) -> Option<Value> {
+        let last = self.last.get_mut(&(topic.name, subject.to_string()))?;
+        let payload = last.pending.take()?;
+        if topic.dedup && payload == last.payload {
+            return None;
+        }
+        last.payload = payload.clone();
+        last.at = now;
+        Some(payload)
+    }
+}
+
+fn subject_of(topic: &Topic, payload: &Value) -> Option<String> {
+    match payload.get(topic.subject?)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// Top-level payload fields the topic does not declare.
+fn undeclared_fields<'a>(topic: &Topic, payload: &'a Value) -> Vec<&'a str> {
+    payload
+        .as_object()
+        .map(|fields| fields.keys().map(String::as_str).filter(|k| !topic.fields.contains(k)).collect())
+        .unwrap_or_default()
+}
+
+/// Emit an event of a registered topic.
+pub fn emit(app: &AppHandle, topic: &'static Topic, payload: Value) {
+    if cfg!(debug_assertions) {
+        let undeclared = undeclared_fields(topic, &payload);
+        if !undeclared.is_empty() {
+            log::warn!("[Events] {} payload has undeclared fields {:?}", topic.name, undeclared);
+        }
+    }
+    let subject = subject_of(topic, &payload);
+    let state = app.try_state::<AppState>();
+    if !topic.gated() {
+        send(app, state.as_deref(), topic, subject.as_deref(), &payload);
+        return;
+    }
+    let Some(state) = state else {
+        send(app, None, topic, subject.as_deref(), &payload);
+        return;
+    };
+    let key = subject.clone().unwrap_or_default();
+    let admit = match state.event_gate.lock() {
+        Ok(mut gate) => gate.admit(topic, &key, &payload, Instant::now()),
+        Err(_) => Admit::Now,
+    };
+    match admit {
+        Admit::Now => send(app, Some(state.inner()), topic, subject.as_deref(), &payload),
+        Admit::Skip => {}
+        Admit::Later(delay) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(delay).await;
+                let state = app.state::<AppState>();
+                let released = state.event_gate.lock().ok().and_then(|mut gate| gate.release(topic, &key, Instant::now()));
+                if let Some(payload) = released {
+                    send(&app, Some(state.inner()), topic, subject.as_deref(), &payload);
+                }
+            });
+        }
+    }
+}
+
+fn send(app: &AppHandle, state: Option<&AppState>, topic: &Topic, subject: Option<&str>, payload: &Value) {
+    if let Err(e) = app.emit(topic.name, payload) {
+        log::warn!("[Events] Failed to emit {}: {}", topic.name, e);
+    }
+    if !topic.persist {
+        return;
+    }
+    let Some(state) = state.cloned() else { return };
+    let (name, subject, payload_json) = (topic.name, subject.map(str::to_string), payload.to_string());
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = event_log_repo::append_event(&state, name, subject.as_deref(), &payload_json) {
+            log::warn!("[Events] Failed to log {}: {}", name, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn topics_are_unique_and_name_their_subject() {
+        let mut names: Vec<&str> = TOPICS.iter().map(|t| t.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), TOPICS.len());
+        for topic in TOPICS {
+            assert!(topic.subject.map_or(true, |s| topic.fields.contains(&s)), "{}", topic.name);
+        }
+        assert_eq!(undeclared_fields(&RUN_ERROR, &json!({"taskRunId": "r", "errr": "x"})), ["errr"]);
+    }
+
+    #[test]
+    fn dedups_and_coalesces_per_subject() {
+        let mut gate = EventGate::default();
+        let t0 = Instant::now();
+        let topic = &CHAT_TOOL_STATUS_CHANGED;
+        let status = |s: &str| json!({"chatToolId": "a", "status": s});
+
+        assert_eq!(gate.admit(topic, "a", &status("connecting"), t0), Admit::Now);
+        assert_eq!(gate.admit(topic, "b", &status("connecting"), t0), Admit::Now);
+        let later = t0 + Duration::from_millis(100);
+        assert_eq!(gate.admit(topic, "a", &status("connecting"), later), Admit::Skip);
+        assert_eq!(gate.admit(topic, "a", &status("connected"), later), Admit::Later(Duration::from_millis(150)));
+        assert_eq!(gate.admit(topic, "a", &status("error"), later), Admit::Skip);
+        let end = t0 + Duration::from_millis(250);
+        assert_eq!(gate.release(topic, "a", end), Some(status("error")));
+        assert_eq!(gate.release(topic, "a", end), None);
+        assert_eq!(gate.admit(topic, "a", &status("error"), end + Duration::from_secs(1)), Admit::Skip);
+        assert_eq!(gate.admit(topic, "a", &status("connected"), end + Duration::from_secs(1)), Admit::Now);
+    }
+}
@@ -0,0 +1,225 @@
+//! Message catalogs for text the backend generates for people: chat tool
+//! replies, run summaries and notifications.
+//!
+//! The locale follows the `language` setting; tags starting with "zh" select
+//! Chinese, anything else English. Messages carry `{name}` placeholders
+//! filled by `tr`.
+
+use crate::config;
+use crate::state::AppState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Zh,
+}
+
+impl Locale {
+    pub fn from_tag(tag: &str) -> Self {
+        if tag.trim().to_ascii_lowercase().starts_with("zh") {
+            Self::Zh
+        } else {
+            Self::En
+        }
+    }
+}
+
+/// Locale selected in settings.
+pub fn current(state: &AppState) -> Locale {
+    Locale::from_tag(&config::current(state).language)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    // Chat tool replies
+    BusyReply,
+    EscalationForward,
+    CommandFailed,
+    HelpHeader,
+    HelpStatus,
+    HelpTasks,
+    HelpRun,
+    StatusActive,
+    StatusAwaiting,
+    StatusScheduled,
+    StatusNextRun,
+    TasksNone,
+    TasksMore,
+    RunUsage,
+    RunTemplateNotFound,
+    RunStarted,
+    // Run summary
+    SummaryTitle,
+    SummaryDuration,
+    SummaryTokens,
+    SummaryPlan,
+    SummaryExecutions,
+    SummaryTableHeader,
+    SummaryResult,
+    SummaryWithoutHub,
+    SummaryCodeChanges,
+    SummaryChangesTableHeader,
+    SummaryDiffTruncated,
+    SummaryTimedOut,
+    // Notifications
+    WaitingForPlanApproval,
+    WaitingForConfirmation,
+    RunWaitingTitle,
+    RunWaitingBody,
+    ScheduleFailedTitle,
+    ScheduleFailedBody,
+    RunCompletedTitle,
+    RunFailedTitle,
+    RunFailedBody,
+}
+
+impl Msg {
+    #[cfg(test)]
+    const ALL: [Msg; 36] = [
+        Msg::BusyReply,
+        Msg::EscalationForward,
+        Msg::CommandFailed,
+        Msg::HelpHeader,
+        Msg::HelpStatus,
+        Msg::HelpTasks,
+        Msg::HelpRun,
+        Msg::StatusActive,
+        Msg::StatusAwaiting,
+        Msg::StatusScheduled,
+        Msg::StatusNextRun,
+        Msg::TasksNone,
+        Msg::TasksMore,
+        Msg::RunUsage,
+        Msg::RunTemplateNotFound,
+        Msg::RunStarted,
+        Msg::SummaryTitle,
+        Msg::SummaryDuration,
+        Msg::SummaryTokens,
+        Msg::SummaryPlan,
+        Msg::SummaryExecutions,
+        Msg::SummaryTableHeader,
+        Msg::SummaryResult,
+        Msg::SummaryWithoutHub,
+        Msg::SummaryCodeChanges,
+        Msg::SummaryChangesTableHeader,
+        Msg::SummaryDiffTruncated,
+        Msg::SummaryTimedOut,
+        Msg::WaitingForPlanApproval,
+        Msg::WaitingForConfirmation,
+        Msg::RunWaitingTitle,
+        Msg::RunWaitingBody,
+        Msg::ScheduleFailedTitle,
+        Msg::ScheduleFailedBody,
+        Msg::RunCompletedTitle,
+        Msg::RunFailedTitle,
+        Msg::RunFailedBody,
+    ];
+}
+
+/// [English, Chinese] text of a message.
+fn catalog(msg: Msg) -> [&'static str; 2] {
+    match msg {
+        Msg::BusyReply => ["Still working on your previous message, please wait", "正在处理前面的消息，请稍候"],
+        Msg::EscalationForward => [
+            "[Needs attention] {chat_tool}\nFrom {sender}:\n{content}\n\nReason: {reason}",
+            "【需要人工处理】{chat_tool}\n来自 {sender}：\n{content}\n\n原因：{reason}",
+        ],
+        Msg::CommandFailed => ["Command failed: {error}", "命令执行失败：{error}"],
+        Msg::HelpHeader => ["Available commands:", "可用命令："],
+        Msg::HelpStatus => ["{keyword} — show run status", "{keyword} — 查看运行状态"],
+        Msg::HelpTasks => ["{keyword} — list active tasks", "{keyword} — 列出进行中的任务"],
+        Msg::HelpRun => [
+            "{keyword} <template> [variable=value ...] — start a task from a template",
+            "{keyword} <模板名> [变量=值 ...] — 按模板启动任务",
+        ],
+        Msg::StatusActive => ["Active tasks: {count}", "进行中的任务：{count}"],
+        Msg::StatusAwaiting => ["Awaiting confirmation: {count}", "等待确认：{count}"],
+        Msg::StatusScheduled => ["Scheduled tasks: {count} ({paused} paused)", "定时任务：{count}（已暂停 {paused}）"],
+        Msg::StatusNextRun => ["Next run: {at} — {title}", "下次执行：{at} — {title}"],
+        Msg::TasksNone => ["No active tasks", "当前没有进行中的任务"],
+        Msg::TasksMore => ["…and {count} more", "…另有 {count} 个任务"],
+        Msg::RunUsage => ["Usage: {keyword} <template> [variable=value ...]", "用法：{keyword} <模板名> [变量=值 ...]"],
+        Msg::RunTemplateNotFound => ["Template not found: {name}", "未找到模板：{name}"],
+        Msg::RunStarted => ["Task started: {title}", "已启动任务：{title}"],
+        Msg::SummaryTitle => ["Task", "任务"],
+        Msg::SummaryDuration => ["Duration", "耗时"],
+        Msg::SummaryTokens => ["Total Tokens", "Token 总量"],
+        Msg::SummaryPlan => ["Plan", "计划"],
+        Msg::SummaryExecutions => ["Agent Executions", "智能体执行"],
+        Msg::SummaryTableHeader => [
+            "| # | Agent | Model | Tokens In | Tokens Out | Duration | Status |",
+            "| # | 智能体 | 模型 | 输入 Token | 输出 Token | 耗时 | 状态 |",
+        ],
+        Msg::SummaryResult => ["Result", "结果"],
+        Msg::SummaryWithoutHub => [
+            "Summary generated without a Control Hub: the agents' results follow.",
+            "未配置 Control Hub，以下为各智能体的结果。",
+        ],
+        Msg::SummaryCodeChanges => ["Code Changes", "代码变更"],
+        Msg::SummaryChangesTableHeader => ["| File | Added | Removed |", "| 文件 | 新增 | 删除 |"],
+        Msg::SummaryDiffTruncated => [
+            "{count} more lines; the full diffs are in {dir}",
+            "另有 {count} 行；完整 diff 见 {dir}",
+        ],
+        Msg::SummaryTimedOut => [
+            "Timed out after {limit}: the run was wound down and {not_started} planned assignment(s) did not start. \
+             This summary covers the work finished in time.",
+            "运行超时（{limit}）：任务已提前收尾，{not_started} 个计划中的任务未开始。本摘要仅涵盖按时完成的工作。",
+        ],
+        Msg::WaitingForPlanApproval => ["plan approval", "计划审批"],
+        Msg::WaitingForConfirmation => ["confirmation", "确认"],
+        Msg::RunWaitingTitle => ["Task waiting for {waiting_for}: {title}", "任务等待{waiting_for}：{title}"],
+        Msg::RunWaitingBody => [
+            "\"{title}\" has been waiting for your {waiting_for} since {since} UTC.",
+            "“{title}”自 {since} UTC 起一直在等待您的{waiting_for}。",
+        ],
+        Msg::ScheduleFailedTitle => ["Scheduled task failed: {title}", "定时任务失败：{title}"],
+        Msg::ScheduleFailedBody => [
+            "The scheduled run of \"{title}\" failed after {attempts} attempt(s).",
+            "“{title}”的定时运行在 {attempts} 次尝试后失败。",
+        ],
+        Msg::RunCompletedTitle => ["Task completed: {title}", "任务已完成：{title}"],
+        Msg::RunFailedTitle => ["Task failed: {title}", "任务失败：{title}"],
+        Msg::RunFailedBody => ["Error: {error}", "错误：{error}"],
+    }
+}
+
+/// Text of a message without placeholders.
+pub fn text(locale: Locale, msg: Msg) -> &'static str {
+    catalog(msg)[locale as usize]
+}
+
+/// Text of a message with its `{name}` placeholders filled in.
+pub fn tr(locale: Locale, msg: Msg, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(text(locale, msg).to_string(), |out, (name, value)| out.replace(&format!("{{{}}}", name), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn catalogs_agree_on_placeholders() {
+        for msg in Msg::ALL {
+            let [en, zh] = catalog(msg);
+            assert_eq!(placeholders(en), placeholders(zh), "{:?}", msg);
+        }
+        assert_eq!(Locale::from_tag("zh-CN"), Locale::Zh);
+        assert_eq!(Locale::from_tag("en"), Locale::En);
+        assert_eq!(
+            tr(Locale::En, Msg::StatusScheduled, &[("count", "3"), ("paused", "1")]),
+            "Scheduled tasks: 3 (1 paused)"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Local IPC between the running app and the `agent-hub` CLI.
+//!
+//! The app listens on a loopback TCP port and writes the port and a random
+//! token to `ipc.json` in the base directory. A client connects, sends one
+//! JSON request line carrying the token, and reads one JSON response line.
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+use crate::acp::orchestrator;
+use crate::db::migrations::get_base_dir;
+use crate::error::{AppError, AppResult};
+use crate::models::task_run::CreateTaskRunRequest;
+use crate::state::AppState;
+
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest request line the app reads from a client
+const MAX_REQUEST_BYTES: u64 = 1024 * 1024;
+
+/// Where a running app advertises its IPC endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcEndpoint {
+    pub port: u16,
+    pub token: String,
+    pub pid: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum IpcMethod {
+    Ping,
+    /// Start an orchestration in the running app
+    Run(Box<CreateTaskRunRequest>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IpcRequest {
+    token: String,
+    call: IpcMethod,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IpcResponse {
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+pub fn endpoint_path() -> PathBuf {
+    get_base_dir().join("ipc.json")
+}
+
+/// Accept CLI connections until the app shuts down.
+pub async fn serve(app: tauri::AppHandle, state: AppState) -> AppResult<()> {
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0)).await?;
+    let endpoint = IpcEndpoint {
+        port: listener.local_addr()?.port(),
+        token: uuid::Uuid::new_v4().simple().to_string(),
+        pid: std::process::id(),
+    };
+    write_endpoint(&endpoint).await?;
+    log::info!("[IPC] Listening on 127.0.0.1:{}", endpoint.port);
+
+    loop {
+        let (stream, _) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            _ = state.shutdown_token.cancelled() => break,
+        };
+        let app = app.clone();
+        let state = state.clone();
+        let token = endpoint.token.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(&app, &state, stream, &token).await {
+                log::warn!("[IPC] Connection failed: {}", e);
+            }
+        });
+    }
+
+    let _ = tokio::fs::remove_file(endpoint_path()).await;
+    Ok(())
+}
+
+/// Write `ipc.json` readable by the current user only, since the token grants
+/// control of the app.
+async fn write_endpoint(endpoint: &IpcEndpoint) -> AppResult<()> {
+    let path = endpoint_path();
+    // A file left by an earlier run may have wider permissions
+    let _ = tokio::fs::remove_file(&path).await;
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(&path).await?;
+    file.write_all(serde_json::to_string(endpoint)?.as_bytes()).await?;
+    Ok(())
+}
+
+async fn handle_connection(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    stream: tokio::net::TcpStream,
+    token: &str,
+) -> AppResult<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    let mut reader = tokio::io::BufReader::new(reader).take(MAX_REQUEST_BYTES);
+    tokio::time::timeout(CLIENT_TIMEOUT, reader.read_line(&mut line))
+        .await
+        .map_err(|_| AppError::Transport("IPC client sent no request".into()))??;
+
+    let response = match parse_request(&line, token) {
+        Ok(call) => match dispatch(app, state, call).await {
+            Ok(result) => IpcResponse { result: Some(result), error: None },
+            Err(e) => IpcResponse { result: None, error: Some(e.to_string()) },
+        },
+        Err(error) => IpcResponse { result: None, error: Some(error) },
+    };
+
+    let mut out = serde_json::to_string(&response)?;
+    out.push('\n');
+    writer.write_all(out.as_bytes()).await?;
+    Ok(())
+}
+
+/// Check one request line against the endpoint token.
+fn parse_request(line: &str, token: &str) -> Result<IpcMethod, String> {
+    if !line.ends_with('\n') && line.len() as u64 >= MAX_REQUEST_BYTES {
+        return Err("IPC request is too large".into());
+    }
+    let request = serde_json::from_str::<IpcRequest>(line).map_err(|e| format!("Malformed IPC request: {}", e))?;
+    if !tokens_match(&request.token, token) {
+        return Err("Invalid IPC token".into());
+    }
+    Ok(request.call)
+}
+
+/// Compare tokens without returning early on the first differing byte.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn dispatch(app: &tauri::AppHandle, state: &AppState, method: IpcMethod) -> AppResult<serde_json::Value> {
+    match method {
+        IpcMethod::Ping => Ok(serde_json::json!({ "pid": std::process::id() })),
+        IpcMethod::Run(request) => Ok(serde_json::to_value(orchestrator::start_task_run(app, state, *request).await?)?),
+    }
+}
+
+/// Call the running app. Returns `Ok(None)` when no app is reachable.
+pub fn call(method: IpcMethod) -> AppResult<Option<serde_json::Value>> {
+    let Ok(text) = std::fs::read_to_string(endpoint_path()) else {
+        return Ok(None);
+    };
+    let endpoint: IpcEndpoint = serde_json::from_str(&text)?;
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], endpoint.port));
+    // A stale endpoint file from an app that exited is treated as "not running"
+    let Ok(mut stream) = std::net::TcpStream::connect_timeout(&addr, CLIENT_TIMEOUT) else {
+        return Ok(None);
+    };
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut request = serde_json::to_string(&IpcRequest { token: endpoint.token, call: method })?;
+    request.push('\n');
+    stream.write_all(request.as_bytes())?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    let response: IpcResponse = serde_json::from_str(&line)?;
+    match response.error {
+        Some(error) => Err(AppError::Transport(error)),
+        None => Ok(Some(response.result.unwrap_or(serde_json::Value::Null))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(token: &str, call: IpcMethod) -> String {
+        let mut line = serde_json::to_string(&IpcRequest { token: token.into(), call }).unwrap();
+        line.push('\n');
+        line
+    }
+
+    #[test]
+    fn accepts_request_with_matching_token() {
+        let call = parse_request(&line("secret", IpcMethod::Ping), "secret").unwrap();
+        assert!(matches!(call, IpcMethod::Ping));
+    }
+
+    #[test]
+    fn rejects_wrong_token() {
+        assert_eq!(
+            parse_request(&line("secreT", IpcMethod::Ping), "secret").unwrap_err(),
+            "Invalid IPC token"
+        );
+        assert_eq!(
+            parse_request(&line("secret-longer", IpcMethod::Ping), "secret").unwrap_err(),
+            "Invalid IPC token"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_request() {
+        let err = parse_request("{\"token\":\"secret\"}\n", "secret").unwrap_err();
+        assert!(err.starts_with("Malformed IPC request"));
+    }
+
+    #[test]
+    fn tokens_match_compares_whole_token() {
+        assert!(tokens_match("abc", "abc"));
+        assert!(!tokens_match("abd", "abc"));
+        assert!(!tokens_match("ab", "abc"));
+    }
+}
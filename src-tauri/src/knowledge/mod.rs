@@ -0,0 +1,297 @@
+//! Per-workspace knowledge base built from local documents.
+//!
+//! Markdown, text and PDF files are split into overlapping chunks of a few
+//! paragraphs, embedded with the same local hashing model as memory, and
+//! stored per workspace. Retrieval scans the workspace's chunks for the ones
+//! most similar to a query. The `knowledge_context` config decides whether
+//! the planner and agents get the top chunks for their prompt.
+
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::db::knowledge_repo;
+use crate::error::{AppError, AppResult};
+use crate::memory::embedding;
+use crate::models::knowledge::{IngestReport, KnowledgeChunkMatch, KnowledgeContextConfig};
+use crate::state::AppState;
+
+const SUPPORTED_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "pdf"];
+const CHUNK_CHARS: usize = 1200;
+const CHUNK_OVERLAP_CHARS: usize = 200;
+/// Chunks less similar than this are left out of prompts.
+const MIN_RELEVANCE: f32 = 0.12;
+
+pub fn context_config(state: &AppState) -> KnowledgeContextConfig {
+    config::current(state).knowledge_context
+}
+
+fn file_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "md" | "markdown" => Some("md"),
+        "txt" => Some("txt"),
+        "pdf" => Some("pdf"),
+        _ => None,
+    }
+}
+
+fn extract_text(path: &Path, file_type: &str) -> AppResult<String> {
+    if file_type == "pdf" {
+        // pdf-extract panics on some malformed files instead of returning an error
+        match std::panic::catch_unwind(|| pdf_extract::extract_text(path)) {
+            Ok(result) => result.map_err(|e| AppError::InvalidRequest(format!("Unreadable PDF: {}", e))),
+            Err(_) => Err(AppError::InvalidRequest("Unreadable PDF: the parser failed on this file".into())),
+        }
+    } else {
+        Ok(std::fs::read_to_string(path)?)
+    }
+}
+
+fn content_hash(text: &str) -> String {
+    ring::digest::digest(&ring::digest::SHA256, text.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Document title: the first Markdown heading, else the file name.
+fn title_for(path: &Path, text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("# "))
+        .map(|line| line.trim_start_matches("# ").trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| path.file_name().unwrap_or_default().to_string_lossy().to_string())
+}
+
+/// Split text into chunks of whole paragraphs up to `CHUNK_CHARS`, each
+/// starting with the tail of the previous one. Oversized paragraphs are cut.
+fn chunk_text(text: &str) -> Vec<String> {
+    let mut pieces: Vec<String> = Vec::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let chars: Vec<char> = paragraph.chars().collect();
+        for part in chars.chunks(CHUNK_CHARS) {
+            pieces.push(part.iter().collect());
+        }
+    }
+
+    let mut chunks: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for piece in pieces {
+        if !current.is_empty() && current.chars().count() + piece.chars().count() > CHUNK_CHARS {
+            let count = current.chars().count();
+            let overlap: String = current.chars().skip(count.saturating_sub(CHUNK_OVERLAP_CHARS)).collect();
+            chunks.push(std::mem::replace(&mut current, overlap));
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(&piece);
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Supported files under `path` (the path itself when it is a file).
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        collect_dir(path, files);
+    } else if is_supported(path) {
+        files.push(path.to_path_buf());
+    }
+}
+
+/// Symbolic links inside a directory are not followed, so a link cycle or a
+/// link out of the tree cannot pull in unrelated files.
+fn collect_dir(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<(PathBuf, std::fs::FileType)> =
+        entries.flatten().filter_map(|e| Some((e.path(), e.file_type().ok()?))).collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    for (entry, entry_type) in entries {
+        let hidden = entry.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        if hidden || entry_type.is_symlink() {
+            continue;
+        }
+        if entry_type.is_dir() {
+            collect_dir(&entry, files);
+        } else if is_supported(&entry) {
+            files.push(entry);
+        }
+    }
+}
+
+fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| SUPPORTED_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+fn ingest_file(state: &AppState, workspace_id: &str, path: &Path, report: &mut IngestReport) -> AppResult<()> {
+    let path_str = path.to_string_lossy().to_string();
+    let file_type = file_type(path).unwrap_or("txt");
+    let text = extract_text(path, file_type)?;
+    let hash = content_hash(&text);
+    if knowledge_repo::find_document(state, workspace_id, &path_str)?.is_some_and(|d| d.content_hash == hash) {
+        report.unchanged.push(path_str);
+        return Ok(());
+    }
+
+    let title = title_for(path, &text);
+    let chunks: Vec<(String, Vec<u8>)> = chunk_text(&text)
+        .into_iter()
+        .map(|chunk| {
+            let vector = embedding::embed(&format!("{}\n{}", title, chunk));
+            (chunk, embedding::to_bytes(&vector))
+        })
+        .collect();
+    let size_bytes = std::fs::metadata(path).map(|m| m.len() as i64).unwrap_or(text.len() as i64);
+    let document = knowledge_repo::save_document(
+        state, workspace_id, &path_str, &title, file_type, size_bytes, &hash, &chunks,
+    )?;
+    report.ingested.push(document);
+    Ok(())
+}
+
+/// Ingest files and directories (recursively) into a workspace's knowledge
+/// base. Unchanged documents are skipped; unreadable ones are reported. Blocking.
+pub fn ingest_paths(state: &AppState, workspace_id: &str, paths: &[String]) -> IngestReport {
+    let mut report = IngestReport::default();
+    for raw in paths {
+        let root = PathBuf::from(raw);
+        if !root.exists() {
+            report.failed.push(format!("{}: not found", raw));
+            continue;
+        }
+        let mut files = Vec::new();
+        collect_files(&root, &mut files);
+        if files.is_empty() && root.is_file() {
+            report.failed.push(format!("{}: unsupported file type", raw));
+        }
+        for file in files {
+            if let Err(e) = ingest_file(state, workspace_id, &file, &mut report) {
+                report.failed.push(format!("{}: {}", file.display(), e));
+            }
+        }
+    }
+    report
+}
+
+/// A workspace's chunks ranked by similarity to `query`. Blocking.
+pub fn retrieve(state: &AppState, workspace_id: &str, query: &str, top_k: usize) -> AppResult<Vec<KnowledgeChunkMatch>> {
+    let query_vector = embedding::embed(query);
+    let mut matches: Vec<KnowledgeChunkMatch> = knowledge_repo::list_chunks(state, workspace_id)?
+        .into_iter()
+        .map(|chunk| KnowledgeChunkMatch {
+            score: embedding::similarity(&query_vector, &embedding::from_bytes(&chunk.embedding)),
+            document_id: chunk.document_id,
+            document_title: chunk.document_title,
+            path: chunk.path,
+            chunk_index: chunk.chunk_index,
+            content: chunk.content,
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.truncate(top_k);
+    Ok(matches)
+}
+
+fn context_section(state: &AppState, workspace_id: &str, query: &str, top_k: usize) -> String {
+    let matches = match retrieve(state, workspace_id, query, top_k) {
+        Ok(matches) => matches,
+        Err(e) => {
+            log::warn!("[Knowledge] Retrieval failed: {}", e);
+            return String::new();
+        }
+    };
+    let relevant: Vec<&KnowledgeChunkMatch> = matches.iter().filter(|m| m.score >= MIN_RELEVANCE).collect();
+    if relevant.is_empty() {
+        return String::new();
+    }
+
+    let mut section = String::from("\n## Workspace Knowledge\n\nExcerpts from the workspace's documents that may be relevant.\n");
+    for m in relevant {
+        section.push_str(&format!("\n### {} ({}, part {})\n{}\n", m.document_title, m.path, m.chunk_index + 1, m.content));
+    }
+    section
+}
+
+/// Knowledge section for a planning prompt, if enabled for the planner. Blocking.
+pub fn planning_context(state: &AppState, workspace_id: Option<&str>, user_prompt: &str) -> String {
+    let config = context_config(state);
+    match workspace_id {
+        Some(ws_id) if config.planner => context_section(state, ws_id, user_prompt, config.top_k),
+        _ => String::new(),
+    }
+}
+
+/// Knowledge section for an agent's assignment input, if enabled for agents. Blocking.
+pub fn assignment_context(state: &AppState, workspace_id: Option<&str>, task_description: &str) -> String {
+    let config = context_config(state);
+    match workspace_id {
+        Some(ws_id) if config.agents => context_section(state, ws_id, task_description, config.top_k),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_overlap_and_respect_size() {
+        let paragraph = "word ".repeat(100);
+        let text = vec![paragraph.trim(); 6].join("\n\n");
+        let chunks = chunk_text(&text);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.chars().count() <= CHUNK_CHARS + CHUNK_OVERLAP_CHARS + 2));
+        let tail: String = chunks[0].chars().skip(chunks[0].chars().count() - CHUNK_OVERLAP_CHARS).collect();
+        assert!(chunks[1].starts_with(&tail));
+    }
+
+    #[test]
+    fn short_paragraphs_share_one_chunk() {
+        assert_eq!(chunk_text("First.\n\n  \n\nSecond."), ["First.\n\nSecond."]);
+        assert!(chunk_text(" \n\n ").is_empty());
+    }
+
+    #[test]
+    fn chunks_break_between_paragraphs_and_overlap() {
+        let a = "a".repeat(800);
+        let b = "b".repeat(800);
+        let chunks = chunk_text(&format!("{}\n\n{}", a, b));
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0], a);
+        assert_eq!(chunks[1], format!("{}\n\n{}", "a".repeat(CHUNK_OVERLAP_CHARS), b));
+    }
+
+    #[test]
+    fn oversized_paragraphs_are_cut_on_char_boundaries() {
+        let chunks = chunk_text(&"é".repeat(CHUNK_CHARS * 2 + 10));
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|c| c.chars().count() <= CHUNK_CHARS + CHUNK_OVERLAP_CHARS + 2));
+        assert_eq!(chunks[0].chars().count(), CHUNK_CHARS);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn collect_files_skips_symlinks_and_hidden_entries() {
+        let root = std::env::temp_dir().join(format!("knowledge-collect-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("docs/.hidden")).unwrap();
+        std::fs::write(root.join("docs/guide.md"), "# Guide").unwrap();
+        std::fs::write(root.join("docs/image.png"), "").unwrap();
+        std::fs::write(root.join("docs/.hidden/secret.md"), "").unwrap();
+        std::os::unix::fs::symlink(&root, root.join("docs/loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("docs/guide.md"), root.join("docs/link.md")).unwrap();
+
+        let mut files = Vec::new();
+        collect_files(&root, &mut files);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(files, [root.join("docs/guide.md")]);
+    }
+}
@@ -1,11 +1,35 @@
 pub mod acp;
+pub mod activity;
+pub mod calendar;
+pub mod chaos;
 pub mod chat_tool;
 pub mod commands;
+pub mod config;
 pub mod db;
+pub mod diagnostics;
 pub mod error;
+pub mod events;
+pub mod i18n;
+pub mod ipc;
+pub mod knowledge;
+pub mod memory;
 pub mod models;
+pub mod notifications;
+pub mod observer;
+pub mod onboarding;
+pub mod prompts;
+pub mod reaper;
+pub mod redaction;
+pub mod repo_onboarding;
+pub mod run_environment;
+pub mod run_report;
 pub mod scheduler;
+pub mod startup_recovery;
+pub mod shutdown;
 pub mod state;
+pub mod sync;
+pub mod telemetry;
+pub mod workspace_sandbox;
 
 use state::AppState;
 use tauri::Manager;
@@ -17,6 +41,8 @@ pub fn run() {
 
     // Create app state before building
     let app_state = AppState::new(conn);
+    observer::init_from_args(std::env::args());
+    observer::apply(&config::current(&app_state));
 
     // Reset stale chat tool statuses from previous session
     match db::chat_tool_repo::reset_stale_statuses(&app_state) {
@@ -29,12 +55,35 @@ pub fn run() {
         _ => {}
     }
 
+    // Pipeline runs cannot be resumed across restarts
+    match db::pipeline_repo::reset_stale_pipeline_runs(&app_state) {
+        Ok(count) if count > 0 => {
+            log::info!("Marked {} interrupted pipeline runs as failed", count);
+        }
+        Err(e) => {
+            log::warn!("Failed to reset stale pipeline runs: {}", e);
+        }
+        _ => {}
+    }
+
+    // Scheduled occurrences in flight when the app closed never recorded an outcome
+    match db::schedule_run_repo::reset_stale_schedule_runs(&app_state) {
+        Ok(count) if count > 0 => {
+            log::info!("Marked {} interrupted schedule runs as failed", count);
+        }
+        Err(e) => {
+            log::warn!("Failed to reset stale schedule runs: {}", e);
+        }
+        _ => {}
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_os::init())
+        .plugin(tauri_plugin_deep_link::init())
         .setup(|app| {
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -51,6 +100,24 @@ pub fn run() {
                 )?;
             }
 
+            // agent-hub:// links in notifications open the linked view
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                #[cfg(any(windows, target_os = "linux"))]
+                if let Err(e) = app.deep_link().register_all() {
+                    log::warn!("Failed to register deep link scheme: {}", e);
+                }
+                let app_handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    for url in event.urls() {
+                        notifications::open_deep_link(&app_handle, url.as_str());
+                    }
+                });
+            }
+
+            // Agent terminal prompts are raised off the async runtime
+            acp::agent_terminal::forward_events(app.handle().clone(), app.state::<AppState>().inner());
+
             // Start the scheduler using Tauri's async runtime
             let app_handle = app.handle().clone();
             let state = app.state::<AppState>().inner().clone();
@@ -62,35 +129,86 @@ pub fn run() {
                 *scheduler = Some(scheduler_state);
             });
 
-            // Resume incomplete orchestration tasks from previous session
-            let app_handle2 = app.handle().clone();
-            let state2 = app.state::<AppState>().inner().clone();
+            // Accept requests from the agent-hub CLI
+            let app_handle3 = app.handle().clone();
+            let state3 = app.state::<AppState>().inner().clone();
             tauri::async_runtime::spawn(async move {
-                acp::orchestrator::resume_incomplete_tasks(app_handle2, state2).await;
+                if let Err(e) = ipc::serve(app_handle3, state3).await {
+                    log::warn!("CLI IPC listener stopped: {}", e);
+                }
             });
 
+            // Export tracing spans when an OTLP endpoint is configured
+            let state5 = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(telemetry::init(state5));
+
+            // Apply database busy and batching settings as they change
+            let state8 = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(db::connection::follow_config(state8));
+
+            // Switch observer mode as the setting changes
+            let state9 = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(observer::follow_config(state9));
+
+            // Forward settings changes to the frontend
+            let app_handle4 = app.handle().clone();
+            let state4 = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(config::emit_changes(app_handle4, state4));
+
+            // Kill agent and bridge processes left behind by a crash, then
+            // keep cleaning up after finished runs
+            let state6 = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(reaper::run(state6));
+
+            // Stop chat agent processes that went unused for too long
+            let app_handle7 = app.handle().clone();
+            let state7 = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(acp::idle_agents::run(app_handle7, state7));
+
+            // Resume, hold or fail the runs the previous session left unfinished
+            let app_handle2 = app.handle().clone();
+            let state2 = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(startup_recovery::recover(app_handle2, state2));
+
             Ok(())
         })
         .manage(app_state)
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(observer::guard(tauri::generate_handler![
             // Agent commands
+            commands::activity_commands::list_activity,
+            commands::activity_commands::list_logged_events,
             commands::agent_commands::list_agents,
             commands::agent_commands::get_agent,
             commands::agent_commands::create_agent,
             commands::agent_commands::update_agent,
             commands::agent_commands::delete_agent,
             commands::agent_commands::set_control_hub,
+            commands::agent_commands::set_secondary_hub,
+            commands::agent_commands::clear_secondary_hub,
             commands::agent_commands::get_control_hub,
             commands::agent_commands::enable_agent,
+            commands::agent_commands::set_agents_enabled,
+            commands::agent_commands::sync_agents_from_manifest,
+            commands::agent_commands::generate_agents_md,
+            commands::agent_commands::get_agent_context,
+            commands::agent_commands::reset_agent_context,
+            commands::agent_commands::list_permission_policies,
+            commands::agent_commands::delete_permission_policy,
+            commands::agent_commands::list_permission_presets,
             // Session commands
             commands::session_commands::create_session,
             commands::session_commands::list_sessions,
             commands::session_commands::load_session,
             commands::session_commands::delete_session,
+            commands::session_commands::delete_sessions,
+            commands::session_commands::summarize_session,
             // Chat commands
             commands::chat_commands::send_prompt,
             commands::chat_commands::cancel_prompt,
             commands::chat_commands::get_messages,
+            commands::chat_commands::pin_message,
+            commands::chat_commands::react_to_message,
+            commands::chat_commands::list_pinned_messages,
             commands::chat_commands::respond_permission,
             commands::chat_commands::save_generated_file,
             commands::chat_commands::open_file_with_default_app,
@@ -101,55 +219,185 @@ pub fn run() {
             commands::acp_commands::create_acp_session,
             commands::acp_commands::get_agent_status,
             commands::acp_commands::stop_agent,
+            commands::acp_commands::list_agent_terminals,
+            commands::acp_commands::write_agent_terminal,
             commands::acp_commands::get_agent_models,
             commands::acp_commands::end_acp_session,
             commands::acp_commands::resume_acp_session,
             commands::acp_commands::ensure_agent_ready,
             commands::acp_commands::install_registry_agent,
             commands::acp_commands::uninstall_registry_agent,
+            commands::acp_commands::list_agent_versions,
+            commands::acp_commands::set_agent_version,
             // Orchestration commands
             commands::orchestration_commands::start_orchestration,
             commands::orchestration_commands::cancel_orchestration,
+            commands::orchestration_commands::cancel_all_running,
+            commands::orchestration_commands::list_queue,
+            commands::orchestration_commands::move_queued_run,
+            commands::orchestration_commands::drop_queued_run,
+            commands::orchestration_commands::override_workspace_lock,
+            commands::orchestration_commands::set_run_priority,
+            commands::orchestration_commands::search_task_runs,
+            commands::orchestration_commands::set_task_run_labels,
+            commands::orchestration_commands::list_task_run_labels,
+            commands::orchestration_commands::archive_task_runs,
             commands::orchestration_commands::cancel_agent,
+            commands::orchestration_commands::get_cancellation_stats,
+            commands::orchestration_commands::set_assignment_caps,
+            commands::orchestration_commands::retry_assignment,
             commands::orchestration_commands::list_task_runs,
             commands::orchestration_commands::get_task_run,
             commands::orchestration_commands::update_task_run_status,
             commands::orchestration_commands::get_task_assignments,
+            commands::orchestration_commands::get_assignment_timeline,
             commands::orchestration_commands::confirm_orchestration,
+            commands::orchestration_commands::dismiss_confirmation,
+            commands::orchestration_commands::approve_plan,
+            commands::orchestration_commands::set_planned_overrides,
+            commands::orchestration_commands::stop_summary,
+            commands::orchestration_commands::get_confirmation_deadline,
             commands::orchestration_commands::regenerate_agent,
             commands::orchestration_commands::respond_orch_permission,
+            commands::orchestration_commands::respond_file_write,
+            commands::orchestration_commands::respond_all_file_writes,
+            commands::orchestration_commands::list_pending_file_writes,
             commands::orchestration_commands::rate_task_run,
             commands::orchestration_commands::schedule_task,
             commands::orchestration_commands::pause_scheduled_task,
             commands::orchestration_commands::resume_scheduled_task,
             commands::orchestration_commands::clear_schedule,
+            commands::orchestration_commands::list_schedule_history,
+            commands::orchestration_commands::get_schedule_stats,
+            commands::orchestration_commands::list_scheduled_tasks,
+            commands::orchestration_commands::trigger_schedule_now,
+            commands::orchestration_commands::list_recoverable_runs,
+            commands::orchestration_commands::recover_task_runs,
+            commands::orchestration_commands::export_schedules_ics,
+            commands::orchestration_commands::publish_run_report,
+            commands::orchestration_commands::get_run_environment,
+            commands::orchestration_commands::compare_run_environments,
+            commands::orchestration_commands::pin_output,
+            commands::orchestration_commands::list_pinned_outputs,
+            commands::orchestration_commands::unpin_output,
+            commands::orchestration_commands::export_pinned_outputs,
+            commands::orchestration_commands::list_task_artifacts,
+            commands::orchestration_commands::get_tool_payload,
+            commands::orchestration_commands::get_artifact_preview,
+            commands::orchestration_commands::get_run_changes,
+            commands::orchestration_commands::clear_response_cache,
             commands::orchestration_commands::discover_workspace_skills,
+            // Pipeline commands
+            commands::pipeline_commands::list_pipelines,
+            commands::pipeline_commands::get_pipeline,
+            commands::pipeline_commands::create_pipeline,
+            commands::pipeline_commands::update_pipeline,
+            commands::pipeline_commands::delete_pipeline,
+            commands::pipeline_commands::run_pipeline,
+            commands::pipeline_commands::cancel_pipeline_run,
+            commands::pipeline_commands::approve_pipeline_stage,
+            commands::pipeline_commands::list_pipeline_runs,
+            commands::pipeline_commands::get_pipeline_run,
+            commands::template_commands::promote_run_to_template,
+            commands::template_commands::list_templates,
+            commands::template_commands::get_template,
+            commands::template_commands::delete_template,
+            commands::template_commands::run_template,
+            // Prompt library
+            commands::prompt_commands::list_prompts,
+            commands::prompt_commands::get_prompt,
+            commands::prompt_commands::list_prompt_versions,
+            commands::prompt_commands::create_prompt,
+            commands::prompt_commands::update_prompt,
+            commands::prompt_commands::delete_prompt,
+            commands::prompt_commands::render_prompt,
+            // Long-term memory
+            commands::memory_commands::list_memories,
+            commands::memory_commands::search_memories,
+            commands::memory_commands::delete_memory,
+            commands::memory_commands::clear_memories,
+            commands::knowledge_commands::ingest_knowledge,
+            commands::knowledge_commands::list_knowledge_documents,
+            commands::knowledge_commands::search_knowledge,
+            commands::knowledge_commands::delete_knowledge_document,
+            commands::knowledge_commands::get_knowledge_config,
+            commands::knowledge_commands::set_knowledge_config,
+            commands::diagnostics_commands::run_diagnostics,
+            commands::diagnostics_commands::get_db_stats,
+            commands::onboarding_commands::bootstrap_environment,
+            commands::onboarding_commands::create_workspace_from_repo,
+            commands::pricing_commands::get_model_pricing,
+            commands::pricing_commands::set_model_pricing_overrides,
+            commands::pricing_commands::estimate_task_run_cost,
             // Settings commands
             commands::settings_commands::get_settings,
             commands::settings_commands::update_settings,
+            commands::settings_commands::get_app_config,
+            commands::settings_commands::update_app_config,
             commands::settings_commands::select_working_directory,
             commands::settings_commands::get_working_directory,
+            commands::settings_commands::get_observer_status,
+            commands::settings_commands::set_observer_mode,
+            // Sync commands
+            commands::sync_commands::get_sync_config,
+            commands::sync_commands::set_sync_config,
+            commands::sync_commands::sync_now,
             // Workspace commands
             commands::workspace_commands::list_workspaces,
             commands::workspace_commands::create_workspace,
             commands::workspace_commands::update_workspace,
             commands::workspace_commands::delete_workspace,
+            commands::workspace_commands::clone_workspace_sandbox,
             commands::workspace_commands::select_workspace_directory,
             // Chat tool commands
             commands::chat_tool_commands::list_chat_tools,
             commands::chat_tool_commands::get_chat_tool,
             commands::chat_tool_commands::create_chat_tool,
             commands::chat_tool_commands::update_chat_tool,
+            commands::chat_tool_commands::clone_chat_tool,
             commands::chat_tool_commands::delete_chat_tool,
             commands::chat_tool_commands::start_chat_tool,
             commands::chat_tool_commands::stop_chat_tool,
+            commands::chat_tool_commands::stop_all_chat_tools,
             commands::chat_tool_commands::logout_chat_tool,
             commands::chat_tool_commands::get_chat_tool_qr_code,
+            commands::chat_tool_commands::get_chat_tool_capabilities,
             commands::chat_tool_commands::list_chat_tool_messages,
+            commands::chat_tool_commands::list_chat_tool_escalations,
+            commands::chat_tool_commands::get_chat_tool_metrics,
             commands::chat_tool_commands::send_chat_tool_message,
             commands::chat_tool_commands::list_chat_tool_contacts,
             commands::chat_tool_commands::set_chat_tool_contact_blocked,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+            commands::chat_tool_commands::set_chat_tool_contact_quota,
+            commands::chat_tool_commands::reset_chat_tool_contact_usage,
+            commands::chat_tool_commands::set_chat_tool_contact_run_notifications,
+            commands::chat_tool_commands::snooze_contact,
+            commands::chat_tool_commands::set_chat_tool_takeover,
+            commands::chat_tool_commands::export_chat_tool_history,
+            commands::chat_tool_commands::import_chat_tool_contacts,
+            commands::chat_tool_commands::export_chat_tool_contacts,
+        ]))
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Hold the exit until the shutdown coordinator has stopped
+            // orchestrations, bridges and agents, then exit for real.
+            if let tauri::RunEvent::ExitRequested { api, code, .. } = event {
+                if shutdown::is_complete() {
+                    return;
+                }
+                if !shutdown::begin() {
+                    // Asked again while shutting down: let this one through.
+                    log::warn!("[Shutdown] Exit requested again, exiting without waiting");
+                    return;
+                }
+                api.prevent_exit();
+                let app_handle = app.clone();
+                let state = app.state::<AppState>().inner().clone();
+                tauri::async_runtime::spawn(async move {
+                    shutdown::shutdown(&app_handle, &state).await;
+                    app_handle.exit(code.unwrap_or(0));
+                });
+            }
+        });
 }
@@ -1,6 +1,16 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use app_lib::acp::web_search;
+
 fn main() {
+  // Agents' sessions start the app binary as their web search MCP server
+  if std::env::args().nth(1).as_deref() == Some(web_search::SERVE_ARG) {
+    if let Err(e) = web_search::serve_from_env() {
+      eprintln!("error: {}", e);
+      std::process::exit(1);
+    }
+    return;
+  }
   app_lib::run();
 }
@@ -0,0 +1,107 @@
+//! Local text embeddings by feature hashing.
+//!
+//! Words (and single characters plus character pairs for CJK text) are hashed
+//! into a fixed number of signed buckets and the vector is L2-normalized, so
+//! the dot product of two embeddings is their cosine similarity. No model is
+//! needed, which keeps memory fully offline.
+
+pub const DIMENSIONS: usize = 512;
+
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "that", "this", "from", "are", "was", "were", "has", "have", "had",
+    "not", "but", "you", "your", "our", "all", "can", "will", "into", "its", "use", "using", "please",
+];
+
+pub fn embed(text: &str) -> Vec<f32> {
+    let mut vector = vec![0f32; DIMENSIONS];
+    for token in tokens(text) {
+        let hash = fnv1a(token.as_bytes());
+        let index = (hash % DIMENSIONS as u64) as usize;
+        let sign = if hash & (1 << 63) == 0 { 1.0 } else { -1.0 };
+        vector[index] += sign;
+    }
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+/// Cosine similarity of two normalized embeddings.
+pub fn similarity(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+pub fn to_bytes(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub fn from_bytes(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF)
+}
+
+fn tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut prev_cjk: Option<char> = None;
+
+    let flush = |word: &mut String, tokens: &mut Vec<String>| {
+        if word.chars().count() >= 2 && !STOPWORDS.contains(&word.as_str()) {
+            tokens.push(std::mem::take(word));
+        } else {
+            word.clear();
+        }
+    };
+
+    for c in text.chars().flat_map(|c| c.to_lowercase()) {
+        if is_cjk(c) {
+            flush(&mut word, &mut tokens);
+            tokens.push(c.to_string());
+            if let Some(prev) = prev_cjk {
+                tokens.push(format!("{}{}", prev, c));
+            }
+            prev_cjk = Some(c);
+        } else {
+            prev_cjk = None;
+            if c.is_alphanumeric() || c == '_' {
+                word.push(c);
+            } else {
+                flush(&mut word, &mut tokens);
+            }
+        }
+    }
+    flush(&mut word, &mut tokens);
+    tokens
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325u64, |hash, b| (hash ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn related_texts_score_higher() {
+        let query = embed("weekly sales report for the EU region");
+        let related = embed("Compiled the EU sales report covering last week");
+        let unrelated = embed("Fix the login button color on the settings page");
+        assert!(similarity(&query, &related) > similarity(&query, &unrelated));
+    }
+
+    #[test]
+    fn cjk_text_and_bytes_round_trip() {
+        let a = embed("生成每周销售报告");
+        let b = embed("销售报告已生成");
+        assert!(similarity(&a, &b) > 0.3);
+        assert_eq!(from_bytes(&to_bytes(&a)), a);
+    }
+}
@@ -0,0 +1,128 @@
+//! Long-term memory of past task runs and chat conversations.
+//!
+//! Each completed task run and each chat session keeps one memory per
+//! workspace: a short text and its embedding. Before planning, the memories
+//! most similar to the new request are added to the control hub's prompt.
+//! The index is an in-process scan over the workspace's embeddings, which is
+//! plenty for the few thousand memories a workspace accumulates.
+//!
+//! Set the `memory_enabled` config to false to stop recording and retrieval.
+
+pub mod embedding;
+
+use crate::config;
+use crate::db::{memory_repo, session_repo, task_run_repo};
+use crate::error::AppResult;
+use crate::models::memory::MemoryMatch;
+use crate::state::AppState;
+
+pub const SOURCE_TASK_RUN: &str = "task_run";
+pub const SOURCE_CHAT: &str = "chat";
+
+/// Longest memory text kept; chat memories keep their most recent part.
+const MAX_MEMORY_CHARS: usize = 4000;
+const PLANNING_MEMORY_LIMIT: usize = 3;
+/// Memories less similar than this are not worth the prompt space.
+const MIN_RELEVANCE: f32 = 0.15;
+
+pub fn is_enabled(state: &AppState) -> bool {
+    config::current(state).memory_enabled
+}
+
+fn store(
+    state: &AppState,
+    workspace_id: Option<&str>,
+    source_kind: &str,
+    source_id: &str,
+    title: &str,
+    content: &str,
+) -> AppResult<()> {
+    let vector = embedding::embed(&format!("{}\n{}", title, content));
+    memory_repo::upsert_memory(
+        state,
+        workspace_id,
+        source_kind,
+        source_id,
+        title,
+        content,
+        &embedding::to_bytes(&vector),
+    )
+}
+
+/// Remember a completed task run's request and summary. Blocking.
+pub fn remember_task_run(state: &AppState, task_run_id: &str) -> AppResult<()> {
+    if !is_enabled(state) {
+        return Ok(());
+    }
+    let run = task_run_repo::get_task_run(state, task_run_id)?;
+    let Some(summary) = run.result_summary.as_deref().filter(|s| !s.trim().is_empty()) else {
+        return Ok(());
+    };
+    let content: String = format!("Request: {}\n\nOutcome: {}", run.user_prompt, summary)
+        .chars()
+        .take(MAX_MEMORY_CHARS)
+        .collect();
+    store(state, run.workspace_id.as_deref(), SOURCE_TASK_RUN, &run.id, &run.title, &content)
+}
+
+/// Add the latest exchange to the chat session's memory. Blocking.
+pub fn remember_chat_exchange(state: &AppState, session_id: &str, user_text: &str, reply: &str) -> AppResult<()> {
+    if !is_enabled(state) || reply.trim().is_empty() {
+        return Ok(());
+    }
+    let session = session_repo::get_session(state, session_id)?;
+    let previous = memory_repo::get_memory_by_source(state, SOURCE_CHAT, session_id)?
+        .map(|m| m.content)
+        .unwrap_or_default();
+    let combined = format!("{}\n\nUser: {}\nAgent: {}", previous, user_text.trim(), reply.trim());
+    let count = combined.chars().count();
+    let content: String = combined
+        .chars()
+        .skip(count.saturating_sub(MAX_MEMORY_CHARS))
+        .collect::<String>()
+        .trim()
+        .to_string();
+    store(state, session.workspace_id.as_deref(), SOURCE_CHAT, session_id, &session.title, &content)
+}
+
+/// Memories of a workspace ranked by similarity to `query`. Blocking.
+pub fn recall(state: &AppState, workspace_id: Option<&str>, query: &str, limit: usize) -> AppResult<Vec<MemoryMatch>> {
+    let query_vector = embedding::embed(query);
+    let mut matches: Vec<MemoryMatch> = memory_repo::list_with_embeddings(state, workspace_id)?
+        .into_iter()
+        .map(|(memory, bytes)| MemoryMatch {
+            score: embedding::similarity(&query_vector, &embedding::from_bytes(&bytes)),
+            memory,
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.truncate(limit);
+    Ok(matches)
+}
+
+/// "Relevant Memories" section for a planning prompt; empty when nothing relevant is found. Blocking.
+pub fn planning_context(state: &AppState, workspace_id: Option<&str>, user_prompt: &str) -> String {
+    if !is_enabled(state) {
+        return String::new();
+    }
+    let matches = match recall(state, workspace_id, user_prompt, PLANNING_MEMORY_LIMIT) {
+        Ok(matches) => matches,
+        Err(e) => {
+            log::warn!("[Memory] Recall failed: {}", e);
+            return String::new();
+        }
+    };
+    let relevant: Vec<&MemoryMatch> = matches.iter().filter(|m| m.score >= MIN_RELEVANCE).collect();
+    if relevant.is_empty() {
+        return String::new();
+    }
+
+    let mut section = String::from("\n## Relevant Memories\n\nFrom earlier work in this workspace; use them only if they help.\n");
+    for m in relevant {
+        section.push_str(&format!(
+            "\n### {} ({}, {})\n{}\n",
+            m.memory.title, m.memory.source_kind, m.memory.updated_at, m.memory.content
+        ));
+    }
+    section
+}
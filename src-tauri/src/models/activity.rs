@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub id: String,
+    pub workspace_id: Option<String>,
+    /// e.g. "run_started", "agent_added"; see `activity`
+    pub kind: String,
+    /// Task run, agent or chat tool the event is about
+    pub subject_id: Option<String>,
+    pub summary: String,
+    pub created_at: String,
+}
+
+/// Filter and page for `list_activity`; entries come newest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActivityQuery {
+    pub workspace_id: Option<String>,
+    /// Only these kinds; empty for all
+    pub kinds: Vec<String>,
+    pub subject_id: Option<String>,
+    /// Inclusive bounds on `created_at` ("YYYY-MM-DD HH:MM:SS")
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::acp::permissions::PermissionPreset;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentSkill {
     pub id: String,
@@ -42,11 +44,49 @@ pub struct AgentConfig {
     pub acp_command: Option<String>,
     pub acp_args_json: Option<String>,
     pub is_control_hub: bool,
+    /// Takes over planning and summaries when the workspace's Control Hub fails
+    #[serde(default)]
+    pub is_secondary_hub: bool,
     pub md_file_path: Option<String>,
     pub max_concurrency: i64,
     pub available_models_json: Option<String>,
     pub is_enabled: bool,
     pub disabled_reason: Option<String>,
+    /// Auto-disabled by an error that is likely to pass, such as a timeout
+    #[serde(default)]
+    pub disabled_transient: bool,
+    /// Reuse this agent's orchestration session across runs in the same workspace
+    #[serde(default)]
+    pub carry_over_context: bool,
+    /// Run right after the agent is spawned for a task run; the output is
+    /// prepended to its first assignment
+    #[serde(default)]
+    pub warmup_prompt: Option<String>,
+    /// Name of the workspace agents.yaml entry managing this agent
+    #[serde(default)]
+    pub manifest_name: Option<String>,
+    /// Run the ACP command on this host over SSH instead of locally
+    #[serde(default)]
+    pub ssh_host: Option<String>,
+    #[serde(default)]
+    pub ssh_user: Option<String>,
+    /// Private key for the SSH connection; the SSH config decides when unset
+    #[serde(default)]
+    pub ssh_key_path: Option<String>,
+    /// Run the ACP command in a Docker container of this image
+    #[serde(default)]
+    pub container_image: Option<String>,
+    /// "read_only" or "read_write" mount of the workspace in the container
+    #[serde(default = "default_container_workspace_access")]
+    pub container_workspace_access: String,
+    /// Minutes without prompts after which the agent's chat process is
+    /// stopped; None follows `agent_idle_shutdown_minutes`, 0 never stops it
+    #[serde(default)]
+    pub idle_shutdown_minutes: Option<i64>,
+    /// Preset answering the agent's permission requests in task runs
+    /// unless the run chose one
+    #[serde(default)]
+    pub permission_preset: Option<PermissionPreset>,
     pub created_at: String,
     pub updated_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -81,6 +121,20 @@ pub struct CreateAgentRequest {
     #[serde(default = "default_max_concurrency")]
     pub max_concurrency: i64,
     #[serde(default)]
+    pub carry_over_context: bool,
+    #[serde(default)]
+    pub warmup_prompt: Option<String>,
+    #[serde(default)]
+    pub ssh_host: Option<String>,
+    #[serde(default)]
+    pub ssh_user: Option<String>,
+    #[serde(default)]
+    pub ssh_key_path: Option<String>,
+    #[serde(default)]
+    pub container_image: Option<String>,
+    #[serde(default = "default_container_workspace_access")]
+    pub container_workspace_access: String,
+    #[serde(default)]
     pub workspace_id: Option<String>,
 }
 
@@ -104,6 +158,78 @@ pub struct UpdateAgentRequest {
     pub available_models_json: Option<String>,
     pub is_enabled: Option<bool>,
     pub disabled_reason: Option<String>,
+    pub carry_over_context: Option<bool>,
+    /// Empty string clears the warm-up prompt
+    pub warmup_prompt: Option<String>,
+    /// Empty string clears the host and the agent runs locally again
+    pub ssh_host: Option<String>,
+    /// Empty string clears the user
+    pub ssh_user: Option<String>,
+    /// Empty string clears the key path
+    pub ssh_key_path: Option<String>,
+    /// Empty string clears the image and the agent runs on the host again
+    pub container_image: Option<String>,
+    pub container_workspace_access: Option<String>,
+    /// A negative value clears the override and the app-wide default applies
+    pub idle_shutdown_minutes: Option<i64>,
+    /// Empty string clears the preset
+    pub permission_preset: Option<String>,
+}
+
+impl CreateAgentRequest {
+    /// A request with the same defaults the app uses for new agents.
+    pub fn named(name: &str, workspace_id: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            icon: default_icon(),
+            description: String::new(),
+            execution_mode: default_execution_mode(),
+            model: default_model(),
+            temperature: default_temperature(),
+            max_tokens: default_max_tokens(),
+            system_prompt: String::new(),
+            capabilities_json: default_capabilities(),
+            skills_json: default_skills(),
+            acp_command: None,
+            acp_args_json: None,
+            is_control_hub: false,
+            max_concurrency: default_max_concurrency(),
+            carry_over_context: false,
+            warmup_prompt: None,
+            ssh_host: None,
+            ssh_user: None,
+            ssh_key_path: None,
+            container_image: None,
+            container_workspace_access: default_container_workspace_access(),
+            workspace_id: workspace_id.map(|s| s.to_string()),
+        }
+    }
+}
+
+/// Outcome of reconciling a workspace's agents with its agents.yaml; agent names.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestSyncReport {
+    pub manifest_path: String,
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub disabled: Vec<String>,
+    pub unchanged: Vec<String>,
+}
+
+/// Context carried over between orchestration runs for one agent in one workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentContext {
+    pub agent_id: String,
+    /// Empty string for agents without a workspace
+    pub workspace_id: String,
+    /// ACP session to resume via session/load, if the agent supports it
+    pub acp_session_id: Option<String>,
+    /// Rolling summary of previous runs, used when the session can't be resumed
+    pub summary: String,
+    pub run_count: i64,
+    pub last_task_run_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -138,6 +264,24 @@ pub struct DiscoveredAgent {
     pub cli_version: Option<String>,
 }
 
+/// Installed, latest and published versions of a registry agent's CLI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentVersions {
+    /// Registry entry ID (e.g. "gemini")
+    pub agent_type: String,
+    pub name: String,
+    /// npm package of NPX agents, without a version
+    pub package: Option<String>,
+    /// Version installed in the adapters directory
+    pub installed: Option<String>,
+    /// Version the registry currently points at
+    pub latest: String,
+    /// Version the user chose, kept until they choose another or unpin
+    pub pinned: Option<String>,
+    /// Published versions, newest first; only known for NPX agents
+    pub available: Vec<String>,
+}
+
 fn default_icon() -> String {
     "code".into()
 }
@@ -162,3 +306,6 @@ fn default_skills() -> String {
 fn default_max_concurrency() -> i64 {
     1
 }
+fn default_container_workspace_access() -> String {
+    "read_only".into()
+}
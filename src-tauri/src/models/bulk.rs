@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// Outcome of one item in a bulk command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkItemResult {
+    pub id: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+impl BulkItemResult {
+    pub fn ok(id: &str) -> Self {
+        Self { id: id.to_string(), ok: true, error: None }
+    }
+
+    pub fn failed(id: &str, error: impl ToString) -> Self {
+        Self { id: id.to_string(), ok: false, error: Some(error.to_string()) }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkResult {
+    pub items: Vec<BulkItemResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+impl From<Vec<BulkItemResult>> for BulkResult {
+    fn from(items: Vec<BulkItemResult>) -> Self {
+        let succeeded = items.iter().filter(|i| i.ok).count();
+        let failed = items.len() - succeeded;
+        Self { items, succeeded, failed }
+    }
+}
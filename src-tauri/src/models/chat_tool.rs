@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::chat_tool::reply_templates::ReplyTemplates;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatTool {
     pub id: String,
@@ -16,6 +20,66 @@ pub struct ChatTool {
     pub last_active_at: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Environment profile for the bridge process, as JSON
+    pub env_json: String,
+    /// Contact that receives messages the Control Hub escalates to a human
+    pub owner_contact_id: Option<String>,
+    /// Control Hub replies with a lower self-reported confidence are escalated
+    pub escalation_threshold: Option<f64>,
+    /// Show a typing indicator while the Control Hub generates a reply
+    pub typing_indicator: bool,
+    /// Replies longer than this are sent as several messages; None sends one
+    pub reply_chunk_chars: Option<i64>,
+    /// Canned replies (greeting, office hours, busy, hub unavailable), as JSON
+    pub reply_templates_json: String,
+}
+
+impl ChatTool {
+    pub fn env_profile(&self) -> Result<ChatToolEnvProfile, String> {
+        serde_json::from_str(&self.env_json)
+            .map_err(|e| format!("Chat tool {} has an invalid environment profile: {}", self.name, e))
+    }
+
+    pub fn reply_templates(&self) -> ReplyTemplates {
+        serde_json::from_str(&self.reply_templates_json).unwrap_or_default()
+    }
+}
+
+/// Environment variables a bridge needs from the backend; a profile may not override them.
+pub const RESERVED_BRIDGE_ENV: [&str; 4] = ["CHAT_TOOL_CONFIG", "CHAT_TOOL_ID", "CHAT_TOOL_DATA_DIR", "PATH"];
+
+/// Environment a chat tool's bridge process runs with
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChatToolEnvProfile {
+    /// HTTP(S) proxy URL, exported as HTTP_PROXY / HTTPS_PROXY
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Directory holding the bridge's login state; defaults to a directory per chat tool
+    #[serde(default)]
+    pub data_dir: Option<String>,
+    /// Locale exported as LANG / LC_ALL, e.g. "zh_CN.UTF-8"
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Extra environment variables
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+impl ChatToolEnvProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(dir) = &self.data_dir {
+            if !std::path::Path::new(dir).is_absolute() {
+                return Err(format!("Data directory must be an absolute path: {}", dir));
+            }
+        }
+        if let Some(key) = self.env.keys().find(|k| RESERVED_BRIDGE_ENV.contains(&k.as_str())) {
+            return Err(format!("Environment variable {} is set by the app and cannot be overridden", key));
+        }
+        if let Some(key) = self.env.keys().find(|k| k.is_empty() || k.contains('=') || k.contains('\0')) {
+            return Err(format!("Invalid environment variable name: {:?}", key));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +93,8 @@ pub struct CreateChatToolRequest {
     #[serde(default = "default_auto_reply_mode")]
     pub auto_reply_mode: String,
     pub workspace_id: Option<String>,
+    #[serde(default)]
+    pub env_profile: Option<ChatToolEnvProfile>,
 }
 
 fn default_plugin_type() -> String {
@@ -49,6 +115,20 @@ pub struct UpdateChatToolRequest {
     pub config_json: Option<String>,
     pub linked_agent_id: Option<String>,
     pub auto_reply_mode: Option<String>,
+    pub env_profile: Option<ChatToolEnvProfile>,
+    /// Empty string clears the owner contact
+    #[serde(default)]
+    pub owner_contact_id: Option<String>,
+    /// Zero or less disables confidence-based escalation
+    #[serde(default)]
+    pub escalation_threshold: Option<f64>,
+    #[serde(default)]
+    pub typing_indicator: Option<bool>,
+    /// Zero or less sends each reply as a single message
+    #[serde(default)]
+    pub reply_chunk_chars: Option<i64>,
+    #[serde(default)]
+    pub reply_templates: Option<ReplyTemplates>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,6 +144,117 @@ pub struct ChatToolMessage {
     pub is_processed: bool,
     pub error_message: Option<String>,
     pub created_at: String,
+    /// Set when the spam filter held the message back
+    #[serde(default)]
+    pub filter_reason: Option<String>,
+    /// pending / sent / delivered / read / failed for tracked outgoing messages
+    #[serde(default)]
+    pub delivery_status: Option<String>,
+    /// Reason the bridge gave for the last failed send
+    #[serde(default)]
+    pub delivery_error: Option<String>,
+    #[serde(default)]
+    pub delivery_attempts: i64,
+}
+
+/// A chat tool message batch the Control Hub handed to the owner contact
+/// instead of auto-replying
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatToolEscalation {
+    pub id: String,
+    pub chat_tool_id: String,
+    pub external_sender_id: Option<String>,
+    pub external_sender_name: Option<String>,
+    pub original_content: String,
+    pub hub_reply: String,
+    pub reason: String,
+    pub confidence: Option<f64>,
+    pub owner_contact_id: String,
+    pub created_at: String,
+}
+
+/// Window of chat tool metrics, ending now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsRange {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl MetricsRange {
+    pub fn span(&self) -> chrono::Duration {
+        match self {
+            MetricsRange::Hour => chrono::Duration::hours(1),
+            MetricsRange::Day => chrono::Duration::days(1),
+            MetricsRange::Week => chrono::Duration::weeks(1),
+            MetricsRange::Month => chrono::Duration::days(30),
+        }
+    }
+
+    /// Width of one point of the range's time series.
+    pub fn bucket(&self) -> chrono::Duration {
+        match self {
+            MetricsRange::Hour => chrono::Duration::minutes(5),
+            MetricsRange::Day => chrono::Duration::hours(1),
+            MetricsRange::Week => chrono::Duration::hours(6),
+            MetricsRange::Month => chrono::Duration::days(1),
+        }
+    }
+}
+
+/// How one batch of a chat tool's inbound messages was handled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatToolMetricSample {
+    pub batch_size: i64,
+    /// Messages waiting when the batch was picked up
+    pub queue_depth: i64,
+    /// Time the Control Hub took to reply; None when it was not reached
+    pub hub_latency_ms: Option<i64>,
+    /// Sum over the batch of the time from receipt to reply sent
+    pub response_ms_total: Option<i64>,
+    /// Time from receipt to reply sent of the batch's oldest message
+    pub response_ms_max: Option<i64>,
+    /// "replied", "escalated", "no_hub" or "failed"
+    pub outcome: String,
+    pub recorded_at: String,
+}
+
+/// Responsiveness of a chat tool over a time window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatToolMetrics {
+    pub chat_tool_id: String,
+    pub range: MetricsRange,
+    pub since: String,
+    pub messages_received: i64,
+    pub messages_replied: i64,
+    pub batches: i64,
+    pub escalated_batches: i64,
+    /// Batches the Control Hub failed on or was missing for
+    pub failed_batches: i64,
+    /// Mean time from receipt to reply sent over replied messages
+    pub avg_response_ms: Option<f64>,
+    /// 95th percentile of the batches' longest response times
+    pub p95_response_ms: Option<i64>,
+    pub max_response_ms: Option<i64>,
+    pub avg_hub_latency_ms: Option<f64>,
+    pub p95_hub_latency_ms: Option<i64>,
+    pub max_queue_depth: i64,
+    /// Messages waiting for a reply now
+    pub backlog: i64,
+    pub buckets: Vec<ChatToolMetricsBucket>,
+}
+
+/// One point of a chat tool's metrics time series
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatToolMetricsBucket {
+    pub start: String,
+    pub received: i64,
+    pub replied: i64,
+    pub avg_response_ms: Option<f64>,
+    pub avg_hub_latency_ms: Option<f64>,
+    pub max_queue_depth: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,14 +266,157 @@ pub struct ChatToolContact {
     pub avatar_url: Option<String>,
     pub contact_type: String,
     pub is_blocked: bool,
+    /// Daily auto-reply quotas; None is unlimited
+    pub max_replies_per_day: Option<i64>,
+    pub max_tokens_per_day: Option<i64>,
+    /// Usage counted against the quotas today
+    pub replies_today: i64,
+    pub tokens_today: i64,
+    /// Auto-replies are suspended until then (UTC), e.g. while a human answers
+    pub snoozed_until: Option<String>,
+    /// Set while a human operator has taken over the conversation (UTC)
+    pub takeover_started_at: Option<String>,
+    /// Groups the contact is in, e.g. from an imported customer list
+    pub groups: Vec<String>,
+    /// Task run outcomes the contact gets a direct message about
+    pub run_notifications: Option<RunNotificationSubscription>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+impl ChatToolContact {
+    /// Whether auto-replies are off because of a snooze or a human takeover.
+    pub fn auto_reply_paused(&self) -> bool {
+        self.snoozed_until.is_some() || self.takeover_started_at.is_some()
+    }
+}
+
+/// Which finished task runs a contact, e.g. the user's own account, is told
+/// about through the chat tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunNotificationSubscription {
+    pub on_completed: bool,
+    pub on_failed: bool,
+    /// Only runs of this workspace; None is every workspace
+    pub workspace_id: Option<String>,
+}
+
+impl Default for RunNotificationSubscription {
+    fn default() -> Self {
+        Self {
+            on_completed: true,
+            on_failed: true,
+            workspace_id: None,
+        }
+    }
+}
+
+/// One contact of an imported or exported contact list. Values left out
+/// keep what an existing contact already has.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContactRecord {
+    pub external_id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub contact_type: Option<String>,
+    #[serde(default, alias = "blocked")]
+    pub is_blocked: Option<bool>,
+    #[serde(default)]
+    pub groups: Option<Vec<String>>,
+    #[serde(default)]
+    pub max_replies_per_day: Option<i64>,
+    #[serde(default)]
+    pub max_tokens_per_day: Option<i64>,
+}
+
+impl From<&ChatToolContact> for ContactRecord {
+    fn from(contact: &ChatToolContact) -> Self {
+        Self {
+            external_id: contact.external_id.clone(),
+            name: Some(contact.name.clone()),
+            contact_type: Some(contact.contact_type.clone()),
+            is_blocked: Some(contact.is_blocked),
+            groups: Some(contact.groups.clone()),
+            max_replies_per_day: contact.max_replies_per_day,
+            max_tokens_per_day: contact.max_tokens_per_day,
+        }
+    }
+}
+
+/// Outcome of importing a contact list.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContactImport {
+    pub created: usize,
+    pub updated: usize,
+    /// Rows that were skipped and why
+    pub errors: Vec<String>,
+}
+
+/// NDJSON protocol version spoken by this build of the app.
+pub const BRIDGE_PROTOCOL_VERSION: u32 = 1;
+/// Oldest bridge protocol version the app still understands.
+pub const MIN_BRIDGE_PROTOCOL_VERSION: u32 = 1;
+
+/// Optional features a bridge may announce in its `hello` handshake.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeCapabilities {
+    pub protocol_version: u32,
+    pub qrcode: bool,
+    pub contacts: bool,
+    pub media: bool,
+    pub groups: bool,
+    pub typing: bool,
+}
+
+impl BridgeCapabilities {
+    /// Build from the capability names announced by the bridge.
+    /// Unknown names are ignored so newer bridges stay compatible.
+    pub fn from_announced(protocol_version: u32, names: &[String]) -> Self {
+        let has = |name: &str| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+        Self {
+            protocol_version,
+            qrcode: has("qrcode"),
+            contacts: has("contacts"),
+            media: has("media"),
+            groups: has("groups"),
+            typing: has("typing"),
+        }
+    }
+
+    /// Capabilities assumed for bridges that predate the handshake, which
+    /// already sent media messages.
+    pub fn legacy() -> Self {
+        Self {
+            protocol_version: 0,
+            qrcode: true,
+            contacts: true,
+            media: true,
+            groups: false,
+            typing: false,
+        }
+    }
+
+    /// Whether the given protocol version can be spoken by this app.
+    pub fn is_supported_version(version: u32) -> bool {
+        (MIN_BRIDGE_PROTOCOL_VERSION..=BRIDGE_PROTOCOL_VERSION).contains(&version)
+    }
+}
+
 /// Events emitted by the Bridge subprocess via stdout NDJSON
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum BridgeEvent {
+    /// First event sent by the bridge: protocol version and capabilities.
+    Hello {
+        protocol_version: u32,
+        #[serde(default)]
+        capabilities: Vec<String>,
+        #[serde(default)]
+        bridge_version: Option<String>,
+    },
     Status {
         status: String,
     },
@@ -114,6 +448,14 @@ pub enum BridgeEvent {
     Pong {
         ts: i64,
     },
+    /// Delivery acknowledgement for a `send_message` that carried a `ref_id`:
+    /// status is "sent", "delivered", "read" or "failed".
+    MessageStatus {
+        ref_id: String,
+        status: String,
+        #[serde(default)]
+        error: Option<String>,
+    },
 }
 
 fn default_content_type() -> String {
@@ -138,11 +480,18 @@ fn default_contact_type() -> String {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum BridgeCommand {
+    /// Reply to the bridge's `hello` with the version the app will speak.
+    HelloAck {
+        protocol_version: u32,
+    },
     SendMessage {
         to_id: String,
         content: String,
         #[serde(default = "default_content_type")]
         content_type: String,
+        /// Id of the stored outgoing message, echoed back in `message_status`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ref_id: Option<String>,
     },
     GetContacts,
     Logout,
@@ -150,4 +499,63 @@ pub enum BridgeCommand {
     Ping {
         ts: i64,
     },
+    /// Show or clear a typing indicator; only sent to bridges announcing `typing`.
+    Typing {
+        to_id: String,
+        active: bool,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(data_dir: Option<&str>, env: &[(&str, &str)]) -> ChatToolEnvProfile {
+        ChatToolEnvProfile {
+            data_dir: data_dir.map(str::to_string),
+            env: env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_absolute_data_dir_and_plain_env() {
+        let dir = std::env::temp_dir().join("bridge-login");
+        assert!(profile(Some(&dir.to_string_lossy()), &[("NODE_OPTIONS", "--max-old-space-size=512")]).validate().is_ok());
+        assert!(ChatToolEnvProfile::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_relative_data_dir() {
+        let err = profile(Some("bridge/login"), &[]).validate().unwrap_err();
+        assert!(err.contains("absolute path"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_rejects_reserved_and_malformed_env() {
+        for key in RESERVED_BRIDGE_ENV {
+            let err = profile(None, &[(key, "x")]).validate().unwrap_err();
+            assert!(err.contains(key), "{}", err);
+        }
+        for key in ["", "A=B", "NUL\0"] {
+            assert!(profile(None, &[(key, "x")]).validate().is_err(), "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn test_env_profile_reports_invalid_json() {
+        let mut tool: ChatTool = serde_json::from_value(serde_json::json!({
+            "id": "ct1", "name": "WeChat", "plugin_type": "wechat", "config_json": "{}",
+            "linked_agent_id": null, "status": "stopped", "status_message": null, "auto_reply_mode": "all",
+            "workspace_id": null, "messages_received": 0, "messages_sent": 0, "last_active_at": null,
+            "created_at": "", "updated_at": "", "env_json": "{\"locale\": \"zh_CN.UTF-8\"}",
+            "owner_contact_id": null, "escalation_threshold": null, "typing_indicator": false,
+            "reply_chunk_chars": null, "reply_templates_json": "{}"
+        }))
+        .unwrap();
+        assert_eq!(tool.env_profile().unwrap().locale.as_deref(), Some("zh_CN.UTF-8"));
+
+        tool.env_json = "{\"env\": [1]}".into();
+        assert!(tool.env_profile().unwrap_err().contains("WeChat"));
+    }
 }
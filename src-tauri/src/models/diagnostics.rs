@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// One finding of `run_diagnostics`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticCheck {
+    /// "database", "processes", "sessions", "agents" or "bridges"
+    pub category: String,
+    /// What was checked, e.g. an agent or chat tool name
+    pub subject: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl DiagnosticCheck {
+    pub fn new(category: &str, subject: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { category: category.to_string(), subject: subject.into(), status, detail: detail.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticReport {
+    pub generated_at: String,
+    pub app_version: String,
+    pub os: String,
+    pub checks: Vec<DiagnosticCheck>,
+    pub errors: usize,
+    pub warnings: usize,
+    /// Zip of the report, config, agents and recent logs with secrets redacted
+    pub bundle_path: Option<String>,
+}
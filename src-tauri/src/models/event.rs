@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// An emitted event of a kept topic; see `events`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    pub id: i64,
+    /// e.g. "orchestration:completed"
+    pub topic: String,
+    /// Task run, agent or chat tool the event is about
+    pub subject_id: Option<String>,
+    /// The payload as emitted
+    pub payload: serde_json::Value,
+    pub created_at: String,
+}
+
+/// Filter and page for `list_logged_events`; events come oldest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventLogQuery {
+    /// Only events logged after this one, to catch up from the last seen
+    pub after_id: Option<i64>,
+    /// Only these topics; empty for all
+    pub topics: Vec<String>,
+    pub subject_id: Option<String>,
+    pub limit: Option<i64>,
+}
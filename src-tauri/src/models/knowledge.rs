@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// A local document ingested into a workspace's knowledge base.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeDocument {
+    pub id: String,
+    pub workspace_id: String,
+    /// Absolute path the document was read from
+    pub path: String,
+    pub title: String,
+    /// "md", "txt" or "pdf"
+    pub file_type: String,
+    pub size_bytes: i64,
+    pub chunk_count: i64,
+    pub content_hash: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeChunkMatch {
+    pub document_id: String,
+    pub document_title: String,
+    pub path: String,
+    pub chunk_index: i64,
+    pub content: String,
+    /// Cosine similarity to the query, 0..1
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IngestReport {
+    /// Documents added or re-indexed
+    pub ingested: Vec<KnowledgeDocument>,
+    /// Paths whose content had not changed since the last ingest
+    pub unchanged: Vec<String>,
+    /// "path: reason" for files that could not be read
+    pub failed: Vec<String>,
+}
+
+/// When retrieved knowledge is added to prompts. Part of `AppConfig`; off by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeContextConfig {
+    /// Add chunks relevant to the user prompt to the planning prompt
+    #[serde(default)]
+    pub planner: bool,
+    /// Add chunks relevant to each assignment to the agent's input
+    #[serde(default)]
+    pub agents: bool,
+    #[serde(default = "default_top_k")]
+    pub top_k: usize,
+}
+
+impl Default for KnowledgeContextConfig {
+    fn default() -> Self {
+        Self {
+            planner: false,
+            agents: false,
+            top_k: default_top_k(),
+        }
+    }
+}
+
+fn default_top_k() -> usize {
+    4
+}
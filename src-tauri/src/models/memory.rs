@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// A remembered summary of a task run or chat conversation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Memory {
+    pub id: String,
+    pub workspace_id: Option<String>,
+    /// "task_run" or "chat"
+    pub source_kind: String,
+    /// Task run id or chat session id
+    pub source_id: String,
+    pub title: String,
+    pub content: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryMatch {
+    pub memory: Memory,
+    /// Cosine similarity to the query, 0..1
+    pub score: f32,
+}
@@ -8,6 +8,27 @@ pub struct ChatMessage {
     pub content_json: String,
     pub tool_calls_json: Option<String>,
     pub created_at: String,
+    /// Set while the message is pinned
+    #[serde(default)]
+    pub pinned_at: Option<String>,
+    /// Reactions on the message, sorted
+    #[serde(default)]
+    pub reactions: Vec<String>,
+}
+
+impl ChatMessage {
+    /// Text blocks of the message.
+    pub fn text(&self) -> String {
+        match serde_json::from_str::<serde_json::Value>(&self.content_json) {
+            Ok(serde_json::Value::Array(blocks)) => blocks
+                .iter()
+                .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,7 +1,21 @@
+pub mod activity;
 pub mod agent;
+pub mod bulk;
 pub mod chat_tool;
+pub mod diagnostics;
+pub mod event;
+pub mod knowledge;
+pub mod memory;
 pub mod message;
+pub mod notification;
+pub mod onboarding;
+pub mod permission;
+pub mod pipeline;
+pub mod pricing;
+pub mod prompt;
 pub mod session;
 pub mod settings;
+pub mod sync;
 pub mod task_run;
+pub mod template;
 pub mod workspace;
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// Where a notification is delivered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationTarget {
+    /// Shown by the frontend as a desktop notification
+    Desktop,
+    /// POSTed as JSON to the given URL
+    Webhook { url: String },
+    /// Sent as a direct message through a running chat tool bridge
+    ChatTool { chat_tool_id: String, to_id: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    /// Machine-readable kind, e.g. "schedule_failed"
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_run_id: Option<String>,
+    /// Deep link that opens the related view, e.g. "agent-hub://task-runs/<id>"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+/// Reminders for task runs left waiting on the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StuckRunReminderConfig {
+    pub enabled: bool,
+    /// How long a run may wait for confirmation or plan approval before a reminder
+    pub after_minutes: u64,
+    pub targets: Vec<NotificationTarget>,
+}
+
+impl Default for StuckRunReminderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            after_minutes: 30,
+            targets: vec![NotificationTarget::Desktop],
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::agent::CreateAgentRequest;
+use crate::models::workspace::Workspace;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Completed,
+    Failed,
+}
+
+/// One check or setup action of `bootstrap_environment`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootstrapStep {
+    /// "node", "npm", "builtin_agent", "credentials", "workspace" or "control_hub"
+    pub id: String,
+    pub status: StepStatus,
+    /// What was found or done, or why the step failed
+    pub detail: String,
+}
+
+impl BootstrapStep {
+    pub fn completed(id: &str, detail: impl Into<String>) -> Self {
+        Self { id: id.to_string(), status: StepStatus::Completed, detail: detail.into() }
+    }
+
+    pub fn failed(id: &str, detail: impl Into<String>) -> Self {
+        Self { id: id.to_string(), status: StepStatus::Failed, detail: detail.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootstrapReport {
+    pub steps: Vec<BootstrapStep>,
+    pub workspace_id: Option<String>,
+    pub control_hub_id: Option<String>,
+    /// Every step completed
+    pub ready: bool,
+}
+
+/// What `create_workspace_from_repo` found in a repository.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoInspection {
+    /// e.g. "Rust", "TypeScript"
+    pub languages: Vec<String>,
+    /// e.g. "cargo", "pnpm"
+    pub package_managers: Vec<String>,
+    /// Commands that run the repository's tests
+    pub test_commands: Vec<String>,
+    /// AGENTS.md at the repository root
+    pub agents_md_path: Option<String>,
+    /// Ids of the skills in the repository's skills/ directory
+    pub skill_ids: Vec<String>,
+    /// agents.yaml at the repository root; syncing it registers its agents
+    pub has_agents_manifest: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoOnboardingProposal {
+    pub workspace: Workspace,
+    /// False when a workspace already used the repository
+    pub workspace_created: bool,
+    pub control_hub_id: Option<String>,
+    pub inspection: RepoInspection,
+    /// Agents to register once the user confirms them; none when the
+    /// repository has an agents.yaml
+    pub agents: Vec<CreateAgentRequest>,
+}
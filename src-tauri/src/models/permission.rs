@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// A permission decision remembered for an agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionPolicy {
+    pub id: String,
+    pub agent_id: String,
+    /// Kind of request the decision applies to, e.g. "read" or "execute:git"
+    pub request_key: String,
+    pub allow: bool,
+    pub created_at: String,
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// Placeholder in a stage prompt that is replaced with the previous stage's summary.
+pub const PREVIOUS_SUMMARY_PLACEHOLDER: &str = "{{previous_summary}}";
+
+/// One stage of a pipeline: a task template run as a full orchestration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineStage {
+    pub name: String,
+    /// Prompt for this stage. `{{previous_summary}}` is replaced with the
+    /// previous stage's summary; if absent, the summary is appended.
+    pub prompt_template: String,
+    /// Pause for user approval before this stage starts
+    #[serde(default)]
+    pub requires_approval: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub stages_json: String,
+    pub workspace_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Pipeline {
+    pub fn stages(&self) -> Vec<PipelineStage> {
+        serde_json::from_str(&self.stages_json).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePipelineRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub stages: Vec<PipelineStage>,
+    pub workspace_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePipelineRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub stages: Option<Vec<PipelineStage>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineRun {
+    pub id: String,
+    pub pipeline_id: String,
+    pub status: String,
+    /// Index of the stage currently running (or next to run)
+    pub current_stage: i64,
+    /// Input given to the first stage
+    pub initial_input: String,
+    /// Task run IDs of the stages started so far, in order
+    pub stage_task_run_ids_json: String,
+    pub error_message: Option<String>,
+    pub workspace_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
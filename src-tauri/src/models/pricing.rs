@@ -0,0 +1,267 @@
+//! Model pricing registry.
+//!
+//! A built-in table of per-million-token rates, keyed by normalized model id,
+//! which users can extend or override through the `model_pricing_overrides`
+//! config. Every cost figure in the app goes through `PricingTable::cost`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::models::task_run::TaskAssignment;
+use crate::state::AppState;
+
+/// USD rates per million tokens for one model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    /// "anthropic", "openai", "google", or anything for user entries
+    pub provider: String,
+    /// Model id without provider prefix or date suffix, e.g. "claude-sonnet-4"
+    pub model: String,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    /// Defaults to the input rate when the provider has no separate price
+    #[serde(default)]
+    pub cache_write_per_mtok: Option<f64>,
+    #[serde(default)]
+    pub cache_read_per_mtok: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub tokens_in: i64,
+    pub tokens_out: i64,
+    pub cache_creation_tokens: i64,
+    pub cache_read_tokens: i64,
+}
+
+impl ModelPricing {
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let rate = |tokens: i64, per_mtok: f64| tokens.max(0) as f64 * per_mtok / 1_000_000.0;
+        rate(usage.tokens_in, self.input_per_mtok)
+            + rate(usage.tokens_out, self.output_per_mtok)
+            + rate(usage.cache_creation_tokens, self.cache_write_per_mtok.unwrap_or(self.input_per_mtok))
+            + rate(usage.cache_read_tokens, self.cache_read_per_mtok.unwrap_or(self.input_per_mtok))
+    }
+}
+
+/// (provider, model, input, output, cache write, cache read), USD per million tokens.
+type PricingRow = (&'static str, &'static str, f64, f64, Option<f64>, Option<f64>);
+
+const BUILTIN_PRICING: &[PricingRow] = &[
+    ("anthropic", "claude-opus-4-5", 5.0, 25.0, Some(6.25), Some(0.5)),
+    ("anthropic", "claude-opus-4", 15.0, 75.0, Some(18.75), Some(1.5)),
+    ("anthropic", "claude-sonnet-4", 3.0, 15.0, Some(3.75), Some(0.3)),
+    ("anthropic", "claude-haiku-4-5", 1.0, 5.0, Some(1.25), Some(0.1)),
+    ("anthropic", "claude-3-7-sonnet", 3.0, 15.0, Some(3.75), Some(0.3)),
+    ("anthropic", "claude-3-5-sonnet", 3.0, 15.0, Some(3.75), Some(0.3)),
+    ("anthropic", "claude-3-5-haiku", 0.8, 4.0, Some(1.0), Some(0.08)),
+    ("anthropic", "opus", 5.0, 25.0, Some(6.25), Some(0.5)),
+    ("anthropic", "sonnet", 3.0, 15.0, Some(3.75), Some(0.3)),
+    ("anthropic", "haiku", 1.0, 5.0, Some(1.25), Some(0.1)),
+    ("openai", "gpt-5", 1.25, 10.0, None, Some(0.125)),
+    ("openai", "gpt-5-mini", 0.25, 2.0, None, Some(0.025)),
+    ("openai", "gpt-5-nano", 0.05, 0.4, None, Some(0.005)),
+    ("openai", "gpt-4.1", 2.0, 8.0, None, Some(0.5)),
+    ("openai", "gpt-4.1-mini", 0.4, 1.6, None, Some(0.1)),
+    ("openai", "gpt-4.1-nano", 0.1, 0.4, None, Some(0.025)),
+    ("openai", "gpt-4o", 2.5, 10.0, None, Some(1.25)),
+    ("openai", "gpt-4o-mini", 0.15, 0.6, None, Some(0.075)),
+    ("openai", "gpt-4-turbo", 10.0, 30.0, None, None),
+    ("openai", "o3", 2.0, 8.0, None, Some(0.5)),
+    ("openai", "o4-mini", 1.1, 4.4, None, Some(0.275)),
+    ("google", "gemini-2.5-pro", 1.25, 10.0, None, Some(0.31)),
+    ("google", "gemini-2.5-flash", 0.3, 2.5, None, Some(0.075)),
+    ("google", "gemini-2.0-flash", 0.1, 0.4, None, Some(0.025)),
+];
+
+/// Canonical form of a model id: lowercase, without a provider prefix
+/// ("anthropic/"), context tag ("[1m]"), version pin ("@...") or date /
+/// "-latest" suffix.
+pub fn normalize_model(model: &str) -> String {
+    let mut id = model.trim().to_lowercase();
+    if let Some((_, rest)) = id.rsplit_once('/') {
+        id = rest.to_string();
+    }
+    for marker in ['[', '@'] {
+        if let Some(pos) = id.find(marker) {
+            id.truncate(pos);
+        }
+    }
+    if let Some(stripped) = id.strip_suffix("-latest") {
+        id = stripped.to_string();
+    }
+    // "-20250514" or "-2024-08-06"; compared as bytes since the id may hold
+    // multi-byte characters anywhere before the suffix
+    let bytes = id.as_bytes();
+    for date_len in [8usize, 10] {
+        if bytes.len() > date_len + 1 {
+            let start = bytes.len() - date_len;
+            let tail = &bytes[start..];
+            let is_date = tail.iter().all(|b| b.is_ascii_digit() || (date_len == 10 && *b == b'-'))
+                && tail.iter().filter(|b| b.is_ascii_digit()).count() == 8;
+            if is_date && bytes[start - 1] == b'-' {
+                id.truncate(start - 1);
+                break;
+            }
+        }
+    }
+    id.trim_end_matches('-').to_string()
+}
+
+/// Built-in pricing merged with user overrides.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingTable {
+    pub entries: Vec<ModelPricing>,
+}
+
+impl PricingTable {
+    pub fn builtin() -> Self {
+        let entries = BUILTIN_PRICING
+            .iter()
+            .map(|&(provider, model, input, output, cache_write, cache_read)| ModelPricing {
+                provider: provider.to_string(),
+                model: model.to_string(),
+                input_per_mtok: input,
+                output_per_mtok: output,
+                cache_write_per_mtok: cache_write,
+                cache_read_per_mtok: cache_read,
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Replace entries with the same normalized model, add the rest.
+    pub fn with_overrides(mut self, overrides: Vec<ModelPricing>) -> Self {
+        for mut entry in overrides {
+            entry.model = normalize_model(&entry.model);
+            self.entries.retain(|e| e.model != entry.model);
+            self.entries.push(entry);
+        }
+        self
+    }
+
+    /// Built-in table with the configured overrides applied.
+    pub fn load(state: &AppState) -> Self {
+        Self::builtin().with_overrides(config::current(state).model_pricing_overrides)
+    }
+
+    /// Pricing for a model: an exact match on the normalized id, else the
+    /// longest entry it extends ("claude-sonnet-4-5" uses "claude-sonnet-4").
+    pub fn lookup(&self, model: &str) -> Option<&ModelPricing> {
+        let id = normalize_model(model);
+        if id.is_empty() {
+            return None;
+        }
+        self.entries.iter().find(|e| e.model == id).or_else(|| {
+            self.entries
+                .iter()
+                .filter(|e| {
+                    id.strip_prefix(e.model.as_str())
+                        .is_some_and(|rest| rest.starts_with('-') || rest.starts_with('.'))
+                })
+                .max_by_key(|e| e.model.len())
+        })
+    }
+
+    /// Cost in USD, or `None` when the model has no known pricing.
+    pub fn cost(&self, model: &str, usage: &TokenUsage) -> Option<f64> {
+        self.lookup(model).map(|pricing| pricing.cost(usage))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssignmentCost {
+    pub assignment_id: String,
+    pub agent_name: String,
+    pub model: Option<String>,
+    /// `None` when the model has no known pricing
+    pub cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunCostEstimate {
+    pub task_run_id: String,
+    /// Sum over the priced assignments
+    pub total_usd: f64,
+    pub assignments: Vec<AssignmentCost>,
+    /// Models used in the run that have no pricing entry
+    pub unpriced_models: Vec<String>,
+}
+
+impl PricingTable {
+    /// Cost of a run's assignments. Cached responses cost nothing.
+    pub fn estimate_run(&self, task_run_id: &str, assignments: &[TaskAssignment]) -> RunCostEstimate {
+        let mut estimate = RunCostEstimate {
+            task_run_id: task_run_id.to_string(),
+            total_usd: 0.0,
+            assignments: Vec::new(),
+            unpriced_models: Vec::new(),
+        };
+        for assignment in assignments {
+            let usage = TokenUsage {
+                tokens_in: assignment.tokens_in,
+                tokens_out: assignment.tokens_out,
+                cache_creation_tokens: assignment.cache_creation_tokens,
+                cache_read_tokens: assignment.cache_read_tokens,
+            };
+            let model = assignment.model_used.clone().filter(|m| !m.trim().is_empty());
+            let cost_usd = if assignment.cached {
+                Some(0.0)
+            } else {
+                model.as_deref().and_then(|m| self.cost(m, &usage))
+            };
+            match (cost_usd, &model) {
+                (Some(cost), _) => estimate.total_usd += cost,
+                (None, Some(m)) if !estimate.unpriced_models.contains(m) => estimate.unpriced_models.push(m.clone()),
+                _ => {}
+            }
+            estimate.assignments.push(AssignmentCost {
+                assignment_id: assignment.id.clone(),
+                agent_name: assignment.agent_name.clone(),
+                model,
+                cost_usd,
+            });
+        }
+        estimate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_and_matches_model_variants() {
+        assert_eq!(normalize_model("anthropic/Claude-Sonnet-4-5-20250929"), "claude-sonnet-4-5");
+        assert_eq!(normalize_model("gpt-4o-2024-08-06"), "gpt-4o");
+        assert_eq!(normalize_model("claude-opus-4-1[1m]"), "claude-opus-4-1");
+
+        let table = PricingTable::builtin();
+        assert_eq!(table.lookup("claude-sonnet-4-5-20250929").unwrap().model, "claude-sonnet-4");
+        assert_eq!(table.lookup("claude-opus-4-5").unwrap().model, "claude-opus-4-5");
+        assert_eq!(table.lookup("gpt-4o-mini").unwrap().model, "gpt-4o-mini");
+        assert!(table.lookup("gpt-4").is_none());
+    }
+
+    #[test]
+    fn normalizes_non_ascii_model_ids() {
+        // The date suffix window starts inside a multi-byte character
+        assert_eq!(normalize_model("ab-éééééx"), "ab-éééééx");
+        assert_eq!(normalize_model("Modèle-20250101"), "modèle");
+        assert_eq!(normalize_model("模型-2024-08-06"), "模型");
+    }
+
+    #[test]
+    fn overrides_replace_builtin_rates() {
+        let table = PricingTable::builtin().with_overrides(vec![ModelPricing {
+            provider: "openai".into(),
+            model: "openai/gpt-4o".into(),
+            input_per_mtok: 1.0,
+            output_per_mtok: 2.0,
+            cache_write_per_mtok: None,
+            cache_read_per_mtok: None,
+        }]);
+        let usage = TokenUsage { tokens_in: 1_000_000, tokens_out: 500_000, cache_creation_tokens: 0, cache_read_tokens: 1_000_000 };
+        assert_eq!(table.cost("gpt-4o", &usage), Some(3.0));
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// A reusable prompt snippet. `content` is the text of `current_version` and
+/// may contain `{{variable}}` placeholders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prompt {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub tags_json: String,
+    pub current_version: i64,
+    pub content: String,
+    pub workspace_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Prompt {
+    pub fn tags(&self) -> Vec<String> {
+        serde_json::from_str(&self.tags_json).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptVersion {
+    pub prompt_id: String,
+    pub version: i64,
+    pub content: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePromptRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub workspace_id: Option<String>,
+}
+
+/// Changing `content` adds a new version; the other fields are edited in place.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdatePromptRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub content: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// Reference to a library prompt, used in place of raw prompt text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptRef {
+    pub prompt_id: String,
+    /// Defaults to the prompt's current version
+    #[serde(default)]
+    pub version: Option<i64>,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
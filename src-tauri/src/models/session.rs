@@ -11,6 +11,14 @@ pub struct Session {
     pub updated_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub workspace_id: Option<String>,
+    /// Rolling summary of the conversation, seeded into a new ACP session
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Messages the summary covers, oldest first
+    #[serde(default)]
+    pub summary_message_count: i64,
+    #[serde(default)]
+    pub summary_updated_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Per-device edit counters of a replicated record (device_id -> counter)
+pub type VectorClock = BTreeMap<String, u64>;
+
+/// Where devices exchange their encrypted sync envelopes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncTarget {
+    /// A directory shared between devices (e.g. by a file sync service)
+    Directory { path: String },
+    /// An HTTP relay storing one envelope per device
+    Relay { url: String },
+}
+
+/// Sync settings. The passphrase never leaves this device and is kept in the
+/// OS keychain; envelopes are encrypted with a key derived from it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConfig {
+    pub target: SyncTarget,
+    /// Empty when returned to the frontend; an empty value on save keeps the stored one
+    #[serde(default)]
+    pub passphrase: String,
+}
+
+/// Sync state of one replicated record on this device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncRecordState {
+    pub entity: String,
+    pub entity_id: String,
+    pub clock: VectorClock,
+    pub content_hash: String,
+    pub deleted: bool,
+    pub modified_at: String,
+    pub modified_by: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub device_id: String,
+    /// Local edits found since the last sync
+    pub local_changes: usize,
+    /// Remote records written to this device (including deletions)
+    pub applied: usize,
+    /// Records edited concurrently on two devices
+    pub conflicts: usize,
+    /// Remote records that could not be applied; retried on the next sync
+    pub failed: usize,
+    /// Records sent in this device's envelope
+    pub pushed: usize,
+    /// Other devices whose envelopes were read
+    pub peers: usize,
+    /// Envelopes skipped because they could not be decrypted or parsed
+    pub rejected: usize,
+    pub synced_at: String,
+}
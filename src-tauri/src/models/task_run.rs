@@ -1,5 +1,11 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::acp::permissions::PermissionPreset;
+use crate::models::notification::NotificationTarget;
+use crate::models::prompt::PromptRef;
+
 /// Recurrence pattern for scheduled tasks
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecurrencePattern {
@@ -57,12 +63,82 @@ pub struct TaskRun {
     pub is_paused: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub workspace_id: Option<String>,
+    /// Retry and notification policy for scheduled runs, as JSON
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_policy_json: Option<String>,
+    /// The Control Hub that planned and summarized the run; differs from
+    /// `control_hub_agent_id` after a failover to the secondary hub
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_by_hub_agent_id: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// When the run was archived; archived runs are left out of the default listing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<String>,
+    /// Earlier run whose summary and outputs were attached to this run's
+    /// planning prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_run_id: Option<String>,
+    /// Permission preset the run was started with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_preset: Option<PermissionPreset>,
+    /// Wall-clock limit the run was started with, in minutes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_minutes: Option<i64>,
+    /// The run reached its wall-clock limit and was wound down
+    #[serde(default)]
+    pub timed_out: bool,
+    /// Why the run was cancelled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancel_reason: Option<CancelReason>,
+}
+
+impl TaskRun {
+    /// The hub to continue the run with: the one that served it so far.
+    pub fn serving_hub_id(&self) -> &str {
+        self.served_by_hub_agent_id.as_deref().unwrap_or(&self.control_hub_agent_id)
+    }
+
+    pub fn failure_policy(&self) -> ScheduleFailurePolicy {
+        self.failure_policy_json
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
 }
 
 fn default_schedule_type() -> String {
     "none".to_string()
 }
 
+/// Why the user cancelled a run or an assignment, kept for analytics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelReason {
+    #[default]
+    UserCancelled,
+    /// Spending more than the result is worth
+    TooExpensive,
+    /// Heading the wrong way, e.g. a rejected plan
+    WrongDirection,
+}
+
+impl CancelReason {
+    pub const ALL: [CancelReason; 3] = [CancelReason::UserCancelled, CancelReason::TooExpensive, CancelReason::WrongDirection];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CancelReason::UserCancelled => "user_cancelled",
+            CancelReason::TooExpensive => "too_expensive",
+            CancelReason::WrongDirection => "wrong_direction",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.as_str() == name)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskAssignment {
     pub id: String,
@@ -83,12 +159,101 @@ pub struct TaskAssignment {
     pub duration_ms: i64,
     pub error_message: Option<String>,
     pub created_at: String,
+    /// Sub-directory of the workspace root the agent worked in
+    pub working_directory: Option<String>,
+    /// Output was served from the response cache instead of the agent
+    #[serde(default)]
+    pub cached: bool,
+    /// Cap on the agent's output tokens; the assignment ends as "capped" when reached
+    #[serde(default)]
+    pub max_tokens_out: Option<i64>,
+    /// Cap on the assignment's cost in USD
+    #[serde(default)]
+    pub max_cost: Option<f64>,
+    /// Earlier assignment this one re-executed
+    #[serde(default)]
+    pub retry_of_assignment_id: Option<String>,
+    /// Agent settings the assignment ran with instead of the agent's own
+    #[serde(default, skip_serializing_if = "AssignmentOverrides::is_empty")]
+    pub overrides: AssignmentOverrides,
+    /// Why the assignment was cancelled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancel_reason: Option<CancelReason>,
+}
+
+/// A file produced by a task run, stored under its output directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskArtifact {
+    pub id: String,
+    pub task_run_id: String,
+    pub assignment_id: Option<String>,
+    pub agent_id: Option<String>,
+    /// "output_file", "tool_payload", "inline_image" or "generated_file"
+    pub kind: String,
+    /// Absolute path of the file
+    pub path: String,
+    pub size_bytes: i64,
+    #[serde(default)]
+    pub metadata: Option<ArtifactMetadata>,
+    pub created_at: String,
+}
+
+/// What the UI needs to preview an artifact.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactMetadata {
+    pub mime_type: String,
+    /// Pixel dimensions of images whose header could be read
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// An assignment output or A2A result pinned to a run's clipboard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedOutput {
+    pub id: String,
+    pub task_run_id: String,
+    /// "assignment" or "a2a"
+    pub source: String,
+    pub assignment_id: Option<String>,
+    pub agent_id: Option<String>,
+    pub label: String,
+    pub content: String,
+    pub created_at: String,
+}
+
+/// Request to pin an output. An assignment's output is read from the
+/// assignment unless `content` is given; an A2A result needs `content`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinOutputRequest {
+    pub task_run_id: String,
+    #[serde(default)]
+    pub assignment_id: Option<String>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub content: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskPlan {
     pub analysis: String,
     pub assignments: Vec<PlannedAssignment>,
+    /// Give the run a scratch directory for its agents' intermediate files
+    #[serde(default)]
+    pub isolated: bool,
+}
+
+impl TaskPlan {
+    /// The hub's confidence in its least certain assignment, if it gave any.
+    pub fn confidence(&self) -> Option<f64> {
+        self.assignments
+            .iter()
+            .filter_map(|a| a.confidence)
+            .map(|c| c.clamp(0.0, 1.0))
+            .reduce(f64::min)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,15 +267,185 @@ pub struct PlannedAssignment {
     pub matched_skills: Vec<String>,
     #[serde(default)]
     pub selection_reason: String,
+    /// Sub-directory of the workspace root the agent works in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_directory: Option<String>,
+    /// File (relative to the run's output directory) the agent's text is
+    /// streamed into; later assignments get a reference instead of the text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_file: Option<String>,
+    /// Output tokens after which the agent is asked to wrap up and stopped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens_out: Option<i64>,
+    /// Cost in USD after which the agent is asked to wrap up and stopped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost: Option<f64>,
+    /// The hub's confidence in the assignment, 0 to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// What the hub assumed that the request does not say
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assumptions: Vec<String>,
+    /// Agent settings used for this assignment only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<AssignmentOverrides>,
+    /// Workspace files sent with the agent's prompt
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<PromptAttachment>,
+    /// Parts of dependencies' outputs the agent needs, by agent_id: JSON
+    /// keys or Markdown headings. Dependencies not listed are passed whole
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dependency_sections: BTreeMap<String, Vec<String>>,
+}
+
+/// A workspace file sent with an assignment's prompt: an image when the
+/// agent takes images, else a link to the file or its embedded text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptAttachment {
+    /// Path relative to the workspace root
+    pub path: String,
+    /// What the file is for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Agent settings an assignment runs with instead of the agent's own. The
+/// stored agent configuration is not changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssignmentOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Instructions the agent follows instead of its system prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_profile: Option<PermissionProfile>,
+}
+
+impl AssignmentOverrides {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+            return Err("model override must not be empty".into());
+        }
+        if self.temperature.is_some_and(|t| !(0.0..=2.0).contains(&t)) {
+            return Err("temperature override must be between 0 and 2".into());
+        }
+        if self.system_prompt.as_deref().is_some_and(|p| p.trim().is_empty()) {
+            return Err("system prompt override must not be empty".into());
+        }
+        Ok(())
+    }
+}
+
+/// How an assignment's permission requests are answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionProfile {
+    /// From remembered decisions, else by the user
+    Ask,
+    /// Reads and searches are allowed; edits and commands are rejected
+    ReadOnly,
+    /// Every request is allowed
+    AutoApprove,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTaskRunRequest {
+    #[serde(default)]
     pub user_prompt: String,
     #[serde(default)]
     pub title: String,
     #[serde(default)]
     pub workspace_id: Option<String>,
+    /// Library prompt to run; `user_prompt`, if any, is appended to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<PromptRef>,
+    #[serde(default)]
+    pub priority: RunPriority,
+    /// Earlier run to plan this one with, e.g. to fix the issues it found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attach_run_context: Option<String>,
+    /// Start even while an exclusive run holds the workspace
+    #[serde(default)]
+    pub ignore_workspace_lock: bool,
+    /// Pinned outputs appended to the prompt
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_output_ids: Vec<String>,
+    /// Permission preset for every agent of the run, instead of their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_preset: Option<PermissionPreset>,
+    /// Wall-clock limit of the run in minutes; None follows
+    /// `max_run_minutes`, 0 sets no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_minutes: Option<u64>,
+}
+
+/// Filters of a task history search; unset fields match every run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaskRunSearch {
+    pub workspace_id: Option<String>,
+    pub statuses: Vec<String>,
+    /// Runs carrying all of these labels
+    pub labels: Vec<String>,
+    /// Created at or after this UTC date or datetime
+    pub created_after: Option<String>,
+    /// Created before this UTC date or datetime
+    pub created_before: Option<String>,
+    /// Runs the agent planned, summarized or had an assignment in
+    pub agent_id: Option<String>,
+    /// Case-insensitive match in the title or the result summary
+    pub text: Option<String>,
+    /// Only archived runs (true) or only unarchived ones (false); both when unset
+    pub archived: Option<bool>,
+    pub limit: Option<usize>,
+}
+
+/// Where a run came from; a waiting run of a higher priority starts first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunPriority {
+    ChatTool,
+    Scheduled,
+    #[default]
+    Interactive,
+    /// Set by the user to run ahead of everything else
+    Urgent,
+}
+
+/// The run a start request got: a new one, or the active run it duplicates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartedTaskRun {
+    #[serde(flatten)]
+    pub task_run: TaskRun,
+    /// The request repeated a run started moments before, which is returned instead
+    #[serde(default)]
+    pub duplicate: bool,
+}
+
+/// A task run waiting in the intake queue for a free slot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedRun {
+    pub task_run_id: String,
+    pub title: String,
+    pub workspace_id: Option<String>,
+    pub priority: RunPriority,
+    pub queued_at: String,
+    /// Holds the workspace while it runs: its workspace has `exclusive_runs` on
+    #[serde(default)]
+    pub exclusive: bool,
+    /// Starts whether or not the workspace is held
+    #[serde(default)]
+    pub ignore_workspace_lock: bool,
+    /// Held back by another run of its workspace rather than by the run limit
+    #[serde(default)]
+    pub waiting_for_workspace: bool,
 }
 
 /// Request to schedule a task for future execution
@@ -125,4 +460,205 @@ pub struct ScheduleTaskRequest {
     /// Recurrence pattern as JSON for recurring execution
     #[serde(skip_serializing_if = "Option::is_none")]
     pub recurrence_pattern: Option<RecurrencePattern>,
+    /// What to do when a scheduled run fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_policy: Option<ScheduleFailurePolicy>,
+}
+
+/// Retry and notification policy applied when a scheduled run fails
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleFailurePolicy {
+    /// Number of retries after the first failed attempt
+    #[serde(default)]
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each further retry
+    #[serde(default = "default_retry_backoff_secs")]
+    pub backoff_secs: u64,
+    /// Notified once the occurrence has failed its final attempt
+    #[serde(default)]
+    pub notify_on_failure: Vec<NotificationTarget>,
+}
+
+impl Default for ScheduleFailurePolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            backoff_secs: default_retry_backoff_secs(),
+            notify_on_failure: Vec::new(),
+        }
+    }
+}
+
+fn default_retry_backoff_secs() -> u64 {
+    60
+}
+
+/// One fired (or skipped) occurrence of a scheduled task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleRun {
+    pub id: String,
+    /// The scheduled task run that owns the schedule
+    pub schedule_task_run_id: String,
+    /// The task run that executed this occurrence; None when skipped
+    pub task_run_id: Option<String>,
+    /// The next_run_at value this occurrence was due at
+    pub scheduled_for: Option<String>,
+    pub fired_at: String,
+    pub finished_at: Option<String>,
+    /// 'running', 'completed', 'failed', 'cancelled', or 'skipped'
+    pub outcome: String,
+    pub skipped_reason: Option<String>,
+    pub error_message: Option<String>,
+    pub duration_ms: i64,
+    /// 1 for the scheduled firing, 2+ for retries of the same occurrence
+    pub attempt: i64,
+}
+
+/// A scheduled task with when it fires next and how it last went
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    pub task_run_id: String,
+    pub title: String,
+    /// 'once' or 'recurring'
+    pub schedule_type: String,
+    pub scheduled_time: Option<String>,
+    pub recurrence_pattern: Option<RecurrencePattern>,
+    /// None once a one-time task has run
+    pub next_run_at: Option<String>,
+    pub is_paused: bool,
+    /// An occurrence is running now
+    pub is_running: bool,
+    pub workspace_id: Option<String>,
+    pub workspace_name: Option<String>,
+    /// The latest fired or skipped occurrence
+    pub last_run: Option<ScheduleRun>,
+}
+
+/// Reliability statistics for a scheduled task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleStats {
+    pub schedule_task_run_id: String,
+    pub total_runs: i64,
+    pub completed: i64,
+    pub failed: i64,
+    pub cancelled: i64,
+    pub skipped: i64,
+    /// Completed runs divided by finished (completed, failed, cancelled) runs;
+    /// None until a run has finished
+    pub success_rate: Option<f64>,
+    pub avg_duration_ms: Option<f64>,
+    pub last_fired_at: Option<String>,
+    pub last_outcome: Option<String>,
+}
+
+/// Cancelled runs and assignments of one reason. Tokens are those spent
+/// before cancelling: a cancelled run's totals, and for assignments
+/// cancelled on their own, the assignment's.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancellationStats {
+    pub reason: CancelReason,
+    pub runs: i64,
+    pub assignments: i64,
+    pub tokens_in: i64,
+    pub tokens_out: i64,
+}
+
+/// One recorded step of an agent prompt: a run of streamed text or thought,
+/// or a tool call update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssignmentEvent {
+    pub id: i64,
+    /// "text", "thought", "tool_call" or "notice"
+    pub kind: String,
+    /// Text for text/thought events; JSON with name, title, input and output preview for tool calls
+    pub content: String,
+    pub tool_call_id: Option<String>,
+    pub tool_status: Option<String>,
+    pub created_at: String,
+}
+
+/// An assignment with the events recorded while it ran, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssignmentTimeline {
+    pub assignment: TaskAssignment,
+    pub events: Vec<AssignmentEvent>,
+}
+
+/// An agent file write awaiting user review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileWriteReview {
+    pub write_id: String,
+    pub task_run_id: String,
+    pub agent_id: String,
+    pub path: String,
+    /// Unified diff against the current file content
+    pub diff: String,
+    pub is_new_file: bool,
+    pub created_at: String,
+}
+
+/// Changes a run made to one file through reviewed writes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    /// Agents whose approved writes changed the file, in order
+    pub agent_ids: Vec<String>,
+    pub is_new_file: bool,
+    pub additions: usize,
+    pub deletions: usize,
+    /// Approved diffs in the order they were applied
+    pub diff: String,
+    /// Writes to the file the user rejected
+    pub rejected_writes: usize,
+}
+
+/// Code changes of a task run, one entry per file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunChanges {
+    pub files: Vec<FileChange>,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// What a task run ran with, recorded when it starts planning so runs of
+/// the same request can be compared when one behaves differently.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunEnvironment {
+    pub captured_at: String,
+    pub app_version: String,
+    /// Operating system and architecture, e.g. "macos aarch64"
+    pub os: String,
+    /// Version of the agent registry the agent versions were looked up in
+    pub registry_version: Option<String>,
+    /// The agents the run could assign to, hub included
+    pub agents: Vec<AgentEnvironment>,
+    /// Settings that change how runs behave
+    pub settings: BTreeMap<String, String>,
+}
+
+/// One agent in a `RunEnvironment`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentEnvironment {
+    pub agent_id: String,
+    pub name: String,
+    pub model: String,
+    pub command: Option<String>,
+    /// Registry entry the command resolves to
+    pub registry_id: Option<String>,
+    /// Version of that entry in the registry
+    pub registry_version: Option<String>,
+    /// Adapter version installed locally, for NPX agents
+    pub installed_version: Option<String>,
+    /// "local", "ssh:<host>" or "container:<image>"
+    pub runs_on: String,
+    pub is_control_hub: bool,
+}
+
+/// A value that differs between two run environments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentChange {
+    /// e.g. "app_version", "agent.Coder.model" or "setting.review_writes"
+    pub key: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
 }
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::task_run::{AssignmentOverrides, RunPriority};
+
+/// How a template assignment picks its agent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "by", rename_all = "snake_case")]
+pub enum AgentPin {
+    /// Always the same agent
+    Id { agent_id: String },
+    /// Any enabled agent with this skill or capability
+    Tag { tag: String },
+}
+
+/// One frozen assignment of a template. `task_description` may contain
+/// `{{variable}}` placeholders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateAssignment {
+    pub agent: AgentPin,
+    /// Agent that ran this assignment in the source run; `depends_on` refers to these ids
+    pub source_agent_id: String,
+    pub task_description: String,
+    pub sequence_order: i64,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub matched_skills: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_directory: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens_out: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<AssignmentOverrides>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    /// Value from the source run, used when a run does not supply one
+    pub default_value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrchestrationTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub source_task_run_id: Option<String>,
+    /// User prompt of the source run, with variables as placeholders
+    pub prompt_template: String,
+    pub analysis: String,
+    pub assignments_json: String,
+    pub variables_json: String,
+    pub workspace_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl OrchestrationTemplate {
+    pub fn assignments(&self) -> Vec<TemplateAssignment> {
+        serde_json::from_str(&self.assignments_json).unwrap_or_default()
+    }
+
+    pub fn variables(&self) -> Vec<TemplateVariable> {
+        serde_json::from_str(&self.variables_json).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromoteRunOptions {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    /// Pin agents by their first matched skill instead of by id
+    #[serde(default)]
+    pub pin_by_tag: bool,
+    /// Extra variables to extract: name -> value as it appears in the run
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunTemplateRequest {
+    pub template_id: String,
+    /// Variable values; missing variables use their defaults
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub workspace_id: Option<String>,
+    #[serde(default)]
+    pub priority: RunPriority,
+}
@@ -6,10 +6,48 @@ pub struct Workspace {
     pub name: String,
     pub icon: String,
     pub working_directory: String,
+    /// Tone, language or domain context the Control Hub is given in this workspace
+    #[serde(default)]
+    pub hub_persona: Option<String>,
+    /// Mask secrets in the agent outputs of the workspace
+    #[serde(default = "default_redact_outputs")]
+    pub redact_outputs: bool,
+    /// Queue a run while another orchestration of the workspace is active
+    #[serde(default)]
+    pub exclusive_runs: bool,
+    /// Workspace this sandbox was cloned from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox_of: Option<String>,
+    /// How the sandbox's working directory was made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox_mode: Option<SandboxMode>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// How a sandbox workspace's working directory is made from the original's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxMode {
+    /// A copy of every file
+    Copy,
+    /// A detached git worktree at the original's HEAD
+    Worktree,
+}
+
+impl SandboxMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SandboxMode::Copy => "copy",
+            SandboxMode::Worktree => "worktree",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        [SandboxMode::Copy, SandboxMode::Worktree].into_iter().find(|m| m.as_str() == name)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateWorkspaceRequest {
     pub name: String,
@@ -25,9 +63,20 @@ fn default_icon() -> String {
     "folder".into()
 }
 
+fn default_redact_outputs() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateWorkspaceRequest {
     pub name: Option<String>,
     pub icon: Option<String>,
     pub working_directory: Option<String>,
+    /// An empty persona clears it
+    #[serde(default)]
+    pub hub_persona: Option<String>,
+    #[serde(default)]
+    pub redact_outputs: Option<bool>,
+    #[serde(default)]
+    pub exclusive_runs: Option<bool>,
 }
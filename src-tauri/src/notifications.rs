@@ -0,0 +1,91 @@
+//! Notification routing
+//!
+//! Delivers a notification to desktop, webhook, and chat tool targets.
+//! Delivery failures are logged per target and never abort the caller.
+
+use tauri::{AppHandle, Emitter};
+
+use crate::chat_tool::delivery;
+use crate::error::{AppError, AppResult};
+use crate::models::notification::{Notification, NotificationTarget};
+use crate::state::AppState;
+
+pub const DEEP_LINK_SCHEME: &str = "agent-hub";
+
+/// Deep link that opens a task run.
+pub fn task_run_link(task_run_id: &str) -> String {
+    format!("{}://task-runs/{}", DEEP_LINK_SCHEME, task_run_id)
+}
+
+/// Route an opened deep link to the frontend. Unknown links are ignored.
+pub fn open_deep_link(app: &AppHandle, url: &str) {
+    let Some(task_run_id) = url
+        .strip_prefix(&format!("{}://task-runs/", DEEP_LINK_SCHEME))
+        .map(|rest| rest.trim_end_matches('/'))
+        .filter(|id| !id.is_empty() && !id.contains('/'))
+    else {
+        log::warn!("[Notifications] Ignoring unknown deep link {}", url);
+        return;
+    };
+    if let Err(e) = app.emit("navigation:open_task_run", serde_json::json!({ "taskRunId": task_run_id })) {
+        log::warn!("[Notifications] Failed to open deep link {}: {}", url, e);
+    }
+}
+
+/// Deliver a notification to every target.
+pub async fn dispatch(app: &AppHandle, state: &AppState, targets: &[NotificationTarget], notification: &Notification) {
+    for target in targets {
+        let result = match target {
+            NotificationTarget::Desktop => app
+                .emit("notification:show", notification)
+                .map_err(|e| AppError::Internal(e.to_string())),
+            NotificationTarget::Webhook { url } => send_webhook(url, notification).await,
+            NotificationTarget::ChatTool { chat_tool_id, to_id } => {
+                send_chat_tool_dm(state, chat_tool_id, to_id, notification).await
+            }
+        };
+
+        if let Err(e) = result {
+            log::warn!(
+                "[Notifications] Failed to deliver '{}' notification to {:?}: {}",
+                notification.kind,
+                target,
+                e
+            );
+        }
+    }
+}
+
+async fn send_webhook(url: &str, notification: &Notification) -> AppResult<()> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| AppError::Transport(format!("HTTP client error: {e}")))?;
+
+    let resp = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_string(notification)?)
+        .send()
+        .await
+        .map_err(|e| AppError::Transport(format!("Webhook request error: {e}")))?;
+
+    if !resp.status().is_success() {
+        return Err(AppError::Transport(format!("Webhook returned HTTP {}", resp.status())));
+    }
+    Ok(())
+}
+
+async fn send_chat_tool_dm(
+    state: &AppState,
+    chat_tool_id: &str,
+    to_id: &str,
+    notification: &Notification,
+) -> AppResult<()> {
+    let mut content = format!("{}\n\n{}", notification.title, notification.body);
+    if let Some(link) = &notification.link {
+        content.push_str(&format!("\n\n{}", link));
+    }
+    delivery::send_tracked(state, chat_tool_id, to_id, &content, "text").await?;
+    Ok(())
+}
@@ -0,0 +1,182 @@
+//! Read-only observer mode for demo and shared-dashboard machines.
+//!
+//! While observing, the invoke handler rejects every command that is not in
+//! `READ_ONLY_COMMANDS` with `AppError::ObserverMode`: the UI still lists and
+//! monitors agents, runs and chat tools, but nothing can be created, changed,
+//! started or cancelled from it. Work already running, the scheduler and chat
+//! tool bridges carry on. The mode is the `observer_mode` setting, or forced
+//! on for the whole process by launching with `--observer`, in which case it
+//! cannot be switched off from the app.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Invoke;
+use tauri::Runtime;
+
+use crate::config::AppConfig;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+pub const LAUNCH_FLAG: &str = "--observer";
+
+/// Commands observers may call. Anything else counts as mutating, so new
+/// commands stay blocked until they are listed here.
+const READ_ONLY_COMMANDS: &[&str] = &[
+    "compare_run_environments",
+    "discover_workspace_skills",
+    "estimate_task_run_cost",
+    "get_agent",
+    "get_agent_context",
+    "get_agent_status",
+    "get_app_config",
+    "get_artifact_preview",
+    "get_assignment_timeline",
+    "get_cancellation_stats",
+    "get_chat_tool",
+    "get_chat_tool_capabilities",
+    "get_chat_tool_metrics",
+    "get_confirmation_deadline",
+    "get_control_hub",
+    "get_db_stats",
+    "get_knowledge_config",
+    "get_messages",
+    "get_model_pricing",
+    "get_observer_status",
+    "get_pipeline",
+    "get_pipeline_run",
+    "get_prompt",
+    "get_run_changes",
+    "get_run_environment",
+    "get_schedule_stats",
+    "get_settings",
+    "get_sync_config",
+    "get_task_assignments",
+    "get_task_run",
+    "get_template",
+    "get_tool_payload",
+    "get_working_directory",
+    "list_activity",
+    "list_agent_terminals",
+    "list_agent_versions",
+    "list_agents",
+    "list_chat_tool_contacts",
+    "list_chat_tool_escalations",
+    "list_chat_tool_messages",
+    "list_chat_tools",
+    "list_knowledge_documents",
+    "list_logged_events",
+    "list_memories",
+    "list_pending_file_writes",
+    "list_permission_policies",
+    "list_permission_presets",
+    "list_pinned_messages",
+    "list_pinned_outputs",
+    "list_pipeline_runs",
+    "list_pipelines",
+    "list_prompt_versions",
+    "list_prompts",
+    "list_queue",
+    "list_recoverable_runs",
+    "list_schedule_history",
+    "list_scheduled_tasks",
+    "list_sessions",
+    "list_task_artifacts",
+    "list_task_run_labels",
+    "list_task_runs",
+    "list_templates",
+    "list_workspaces",
+    "load_session",
+    "render_prompt",
+    "search_knowledge",
+    "search_memories",
+    "search_task_runs",
+];
+
+/// Always callable so the setting can be switched back off.
+const TOGGLE_COMMAND: &str = "set_observer_mode";
+
+static FORCED: AtomicBool = AtomicBool::new(false);
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObserverStatus {
+    pub active: bool,
+    /// Set by the launch flag; the setting cannot turn it off
+    pub forced: bool,
+}
+
+/// Force observer mode if the process was launched with `--observer`.
+pub fn init_from_args(args: impl IntoIterator<Item = String>) {
+    if args.into_iter().any(|arg| arg == LAUNCH_FLAG) {
+        FORCED.store(true, Ordering::Relaxed);
+        log::info!("[Observer] Launched in observer mode; mutating commands are disabled");
+    }
+}
+
+pub fn apply(config: &AppConfig) {
+    ENABLED.store(config.observer_mode, Ordering::Relaxed);
+}
+
+/// Follow `observer_mode` setting changes.
+pub async fn follow_config(state: AppState) {
+    let mut changes = crate::config::subscribe(&state);
+    apply(&changes.borrow_and_update());
+    while changes.changed().await.is_ok() {
+        apply(&changes.borrow_and_update());
+    }
+}
+
+pub fn status() -> ObserverStatus {
+    let forced = FORCED.load(Ordering::Relaxed);
+    ObserverStatus {
+        active: forced || ENABLED.load(Ordering::Relaxed),
+        forced,
+    }
+}
+
+fn allowed(command: &str) -> bool {
+    command == TOGGLE_COMMAND || READ_ONLY_COMMANDS.contains(&command)
+}
+
+/// Whether `command` may run now.
+pub fn check(command: &str) -> AppResult<()> {
+    if !status().active || allowed(command) {
+        return Ok(());
+    }
+    Err(AppError::ObserverMode(command.to_string()))
+}
+
+/// Wrap the app's invoke handler so observers cannot call mutating commands.
+pub fn guard<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        if let Err(e) = check(invoke.message.command()) {
+            log::debug!("[Observer] Rejected {}", invoke.message.command());
+            invoke.resolver.reject(e);
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_listing_and_monitoring_commands_are_allowed() {
+        for command in ["list_task_runs", "get_task_run", "search_memories", "get_chat_tool_metrics", "set_observer_mode"] {
+            assert!(allowed(command), "{}", command);
+        }
+        for command in ["create_agent", "update_app_config", "cancel_orchestration", "get_agent_models", "get_chat_tool_qr_code"] {
+            assert!(!allowed(command), "{}", command);
+        }
+    }
+
+    #[test]
+    fn read_only_commands_are_sorted_and_unique() {
+        assert!(READ_ONLY_COMMANDS.windows(2).all(|w| w[0] < w[1]));
+    }
+}
@@ -0,0 +1,249 @@
+//! First-run setup.
+//!
+//! `bootstrap` takes a fresh install to a usable state: it checks for Node.js
+//! and npm, deploys the built-in agent, looks for credentials of at least one
+//! model provider, and creates a default workspace with a Control Hub agent.
+//! Every step runs even when an earlier one fails, and steps whose result
+//! already exists complete without changing it, so the onboarding UI can run
+//! it again after the user fixes something.
+
+use std::path::PathBuf;
+
+use crate::acp::{builtin, discovery};
+use crate::config;
+use crate::db::{agent_repo, workspace_repo};
+use crate::error::{AppError, AppResult};
+use crate::models::agent::CreateAgentRequest;
+use crate::models::onboarding::{BootstrapReport, BootstrapStep, StepStatus};
+use crate::models::workspace::CreateWorkspaceRequest;
+use crate::state::AppState;
+
+/// Oldest Node.js major version the built-in agent runs on.
+const MIN_NODE_MAJOR: u32 = 18;
+
+const DEFAULT_WORKSPACE_NAME: &str = "Default";
+const CONTROL_HUB_NAME: &str = "Control Hub";
+
+/// Environment variables holding a provider credential.
+const CREDENTIAL_VARS: &[&str] = &[
+    "ANTHROPIC_API_KEY",
+    "CLAUDE_CODE_OAUTH_TOKEN",
+    "OPENAI_API_KEY",
+    "GEMINI_API_KEY",
+    "GOOGLE_API_KEY",
+];
+
+/// Files agent CLIs store their login in, relative to the home directory.
+const CREDENTIAL_FILES: &[&str] = &[".claude/.credentials.json", ".codex/auth.json", ".gemini/oauth_creds.json"];
+
+/// Run `<command> --version` on the enriched PATH.
+async fn tool_version(command: &str) -> Result<String, String> {
+    let path = discovery::resolve_command(command).ok_or_else(|| format!("{} not found on PATH", command))?;
+    let output = tokio::process::Command::new(&path)
+        .arg("--version")
+        .env("PATH", discovery::get_enriched_path())
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", command, e))?;
+    if !output.status.success() {
+        return Err(format!("{} --version exited with {}", command, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Major version of a `node --version` output such as "v20.11.1".
+fn node_major(version: &str) -> Option<u32> {
+    version.trim().trim_start_matches('v').split('.').next()?.parse().ok()
+}
+
+async fn check_node() -> BootstrapStep {
+    match tool_version("node").await {
+        Ok(version) => match node_major(&version) {
+            Some(major) if major >= MIN_NODE_MAJOR => BootstrapStep::completed("node", format!("Node.js {}", version)),
+            _ => BootstrapStep::failed(
+                "node",
+                format!("Node.js {} is too old; version {} or later is required", version, MIN_NODE_MAJOR),
+            ),
+        },
+        Err(e) => BootstrapStep::failed("node", format!("{}. Install Node.js {} or later.", e, MIN_NODE_MAJOR)),
+    }
+}
+
+async fn check_npm() -> BootstrapStep {
+    match tool_version("npm").await {
+        Ok(version) => BootstrapStep::completed("npm", format!("npm {}", version)),
+        Err(e) => BootstrapStep::failed("npm", e),
+    }
+}
+
+async fn install_builtin_agent(npm_available: bool) -> BootstrapStep {
+    if builtin::get_builtin_agent().available {
+        return BootstrapStep::completed("builtin_agent", "Built-in agent already installed");
+    }
+    if !npm_available {
+        return BootstrapStep::failed("builtin_agent", "Installing the built-in agent requires npm");
+    }
+    match builtin::deploy().await {
+        Ok(()) => BootstrapStep::completed("builtin_agent", "Installed the built-in agent"),
+        Err(e) => BootstrapStep::failed("builtin_agent", e),
+    }
+}
+
+/// Where credentials were found: variable names and credential files.
+fn find_credentials(home: Option<PathBuf>) -> Vec<String> {
+    let mut found: Vec<String> = CREDENTIAL_VARS
+        .iter()
+        .filter(|var| std::env::var(var).is_ok_and(|v| !v.trim().is_empty()))
+        .map(|var| var.to_string())
+        .collect();
+    if let Some(home) = home {
+        found.extend(
+            CREDENTIAL_FILES
+                .iter()
+                .filter(|file| home.join(file).is_file())
+                .map(|file| format!("~/{}", file)),
+        );
+        // Claude Code on macOS keeps its token in the keychain and only
+        // records the signed-in account here
+        let claude_json = std::fs::read_to_string(home.join(".claude.json")).unwrap_or_default();
+        if claude_json.contains("\"oauthAccount\"") {
+            found.push("~/.claude.json".into());
+        }
+    }
+    found
+}
+
+fn check_credentials() -> BootstrapStep {
+    let found = find_credentials(dirs::home_dir());
+    if found.is_empty() {
+        BootstrapStep::failed(
+            "credentials",
+            "No provider credentials found. Sign in with an agent CLI (e.g. `claude login`) or set an API key such as ANTHROPIC_API_KEY.",
+        )
+    } else {
+        BootstrapStep::completed("credentials", format!("Found {}", found.join(", ")))
+    }
+}
+
+/// The active workspace, else the first one, else a new default workspace.
+fn ensure_workspace(state: &AppState) -> AppResult<(String, BootstrapStep)> {
+    let workspaces = workspace_repo::list_workspaces(state)?;
+    let active = config::current(state).active_workspace_id;
+    let existing = active
+        .and_then(|id| workspaces.iter().find(|w| w.id == id))
+        .or_else(|| workspaces.first());
+    let (workspace, detail) = match existing {
+        Some(workspace) => (workspace.clone(), format!("Using workspace \"{}\"", workspace.name)),
+        None => {
+            let working_directory = config::current(state)
+                .working_directory
+                .or_else(|| dirs::home_dir().map(|h| h.to_string_lossy().to_string()))
+                .unwrap_or_default();
+            let workspace = workspace_repo::create_workspace(
+                state,
+                CreateWorkspaceRequest {
+                    name: DEFAULT_WORKSPACE_NAME.into(),
+                    icon: "folder".into(),
+                    working_directory,
+                    agent_ids: Vec::new(),
+                },
+            )?;
+            let detail = format!("Created workspace \"{}\" in {}", workspace.name, workspace.working_directory);
+            (workspace, detail)
+        }
+    };
+    if config::current(state).active_workspace_id.as_deref() != Some(workspace.id.as_str()) {
+        config::update(state, serde_json::json!({ "active_workspace_id": workspace.id }))?;
+    }
+    Ok((workspace.id, BootstrapStep::completed("workspace", detail)))
+}
+
+/// The workspace's Control Hub, created on the built-in agent if it has none.
+pub(crate) fn ensure_control_hub(state: &AppState, workspace_id: &str) -> AppResult<(String, BootstrapStep)> {
+    if let Some(hub) = agent_repo::get_control_hub(state, Some(workspace_id))? {
+        let detail = format!("Using Control Hub \"{}\"", hub.name);
+        return Ok((hub.id, BootstrapStep::completed("control_hub", detail)));
+    }
+    let builtin = builtin::get_builtin_agent();
+    let request = CreateAgentRequest {
+        icon: "psychology".into(),
+        description: "Plans tasks and delegates them to the workspace's agents".into(),
+        acp_command: Some(builtin.command),
+        acp_args_json: Some(builtin.args_json),
+        is_control_hub: true,
+        ..CreateAgentRequest::named(CONTROL_HUB_NAME, Some(workspace_id))
+    };
+    let hub = agent_repo::create_agent(state, request)?;
+    let detail = format!("Created Control Hub \"{}\" on {}", hub.name, builtin.name);
+    Ok((hub.id, BootstrapStep::completed("control_hub", detail)))
+}
+
+/// Check and set up everything a first run needs.
+pub async fn bootstrap(state: &AppState) -> AppResult<BootstrapReport> {
+    let mut steps = vec![check_node().await, check_npm().await];
+    let npm_available = steps[1].status == StepStatus::Completed;
+    steps.push(install_builtin_agent(npm_available).await);
+    steps.push(check_credentials());
+
+    let state_clone = state.clone();
+    let (workspace, hub) = tokio::task::spawn_blocking(move || {
+        let workspace = ensure_workspace(&state_clone);
+        let hub = match &workspace {
+            Ok((workspace_id, _)) => Some(ensure_control_hub(&state_clone, workspace_id)),
+            Err(_) => None,
+        };
+        (workspace, hub)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let workspace_id = match workspace {
+        Ok((id, step)) => {
+            steps.push(step);
+            Some(id)
+        }
+        Err(e) => {
+            steps.push(BootstrapStep::failed("workspace", e.to_string()));
+            None
+        }
+    };
+    let control_hub_id = match hub {
+        Some(Ok((id, step))) => {
+            steps.push(step);
+            Some(id)
+        }
+        Some(Err(e)) => {
+            steps.push(BootstrapStep::failed("control_hub", e.to_string()));
+            None
+        }
+        None => {
+            steps.push(BootstrapStep::failed("control_hub", "Needs a workspace"));
+            None
+        }
+    };
+
+    let ready = steps.iter().all(|s| s.status == StepStatus::Completed);
+    Ok(BootstrapReport { steps, workspace_id, control_hub_id, ready })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_node_versions_and_finds_credential_files() {
+        assert_eq!(node_major("v20.11.1\n"), Some(20));
+        assert_eq!(node_major("garbage"), None);
+
+        let home = std::env::temp_dir().join(format!("onboarding-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(home.join(".codex")).unwrap();
+        std::fs::write(home.join(".codex/auth.json"), "{}").unwrap();
+        std::fs::write(home.join(".claude.json"), "{\"oauthAccount\": {}}").unwrap();
+        let found = find_credentials(Some(home.clone()));
+        assert!(found.contains(&"~/.codex/auth.json".to_string()));
+        assert!(found.contains(&"~/.claude.json".to_string()));
+        assert!(!found.contains(&"~/.gemini/oauth_creds.json".to_string()));
+        std::fs::remove_dir_all(home).unwrap();
+    }
+}
@@ -0,0 +1,74 @@
+//! Rendering of library prompts. Placeholders are `{{name}}`; a placeholder
+//! can carry a default as `{{name|default text}}`.
+
+use std::collections::HashMap;
+
+use crate::acp::templates;
+use crate::db::prompt_repo;
+use crate::error::{AppError, AppResult};
+use crate::models::prompt::PromptRef;
+use crate::state::AppState;
+
+/// Load the referenced prompt version and fill in its variables. Blocking.
+pub fn resolve(state: &AppState, prompt: &PromptRef) -> AppResult<String> {
+    let content = match prompt.version {
+        Some(version) => prompt_repo::get_version(state, &prompt.prompt_id, version)?.content,
+        None => prompt_repo::get_prompt(state, &prompt.prompt_id)?.content,
+    };
+    render(&content, &prompt.variables)
+}
+
+/// Combine a referenced prompt with free text typed alongside it; the free
+/// text follows the rendered prompt. Blocking.
+pub fn compose(state: &AppState, prompt: Option<&PromptRef>, text: &str) -> AppResult<String> {
+    let Some(prompt) = prompt else {
+        return Ok(text.to_string());
+    };
+    let rendered = resolve(state, prompt)?;
+    Ok(if text.trim().is_empty() {
+        rendered
+    } else {
+        format!("{}\n\n{}", rendered, text)
+    })
+}
+
+/// Substitute `{{name}}` placeholders. Variables without a value or a default
+/// are reported together.
+pub fn render(content: &str, variables: &HashMap<String, String>) -> AppResult<String> {
+    let values: HashMap<&str, &str> = variables.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    let (out, missing) = templates::fill_placeholders(content, &values);
+    if missing.is_empty() {
+        Ok(out)
+    } else {
+        Err(AppError::InvalidRequest(format!(
+            "Missing prompt variables: {}",
+            missing.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn substitutes_values_and_defaults() {
+        let out = render("Review {{ repo }} on {{branch|main}}.", &vars(&[("repo", "hub")])).unwrap();
+        assert_eq!(out, "Review hub on main.");
+    }
+
+    #[test]
+    fn reports_every_missing_variable_once() {
+        let err = render("{{a}} {{b}} {{a}}", &HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("Missing prompt variables: a, b"));
+    }
+
+    #[test]
+    fn leaves_unclosed_braces_alone() {
+        assert_eq!(render("keep {{ this", &HashMap::new()).unwrap(), "keep {{ this");
+    }
+}
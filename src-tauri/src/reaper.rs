@@ -0,0 +1,316 @@
+//! Cleanup of agent and bridge processes left behind.
+//!
+//! Every spawned agent or bridge is recorded in `child_processes` with its
+//! OS start time, which tells the process apart from a later one that reused
+//! its pid, and with the pid and start time of the app instance. On startup
+//! the processes recorded by an earlier app instance are killed when they
+//! survived it. While the app runs a periodic pass kills recorded processes
+//! the app no longer tracks and stops orchestration processes whose run has
+//! finished. Agent containers of an earlier instance are removed on startup
+//! too. Everything cleaned is logged.
+
+use std::collections::HashSet;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::acp::{container, orchestrator};
+use crate::db::child_process_repo::{self, ChildProcessRecord};
+use crate::db::task_run_repo;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use crate::telemetry;
+
+const REAP_INTERVAL_SECS: u64 = 300;
+/// How long a process gets to exit after SIGTERM before it is killed.
+const TERM_GRACE_MS: u64 = 2_000;
+
+/// Task run statuses whose processes are still needed.
+const LIVE_RUN_STATUSES: &[&str] = &["pending", "waiting_for_workspace", "analyzing", "running", "awaiting_confirmation"];
+
+/// Start time of a process from the stat line of `/proc/<pid>/stat`, in
+/// clock ticks since boot (field 22). The command name in field 2 may hold
+/// spaces and parentheses, so fields are counted from its closing one.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_stat_start_time(stat: &str) -> Option<String> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    rest.split_whitespace().nth(19).map(str::to_string)
+}
+
+/// OS start time of a running process, or `None` when there is no such process.
+pub fn process_start_time(pid: u32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        parse_stat_start_time(&stat)
+    }
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        command_output(Command::new("ps").args(["-o", "lstart=", "-p", &pid.to_string()]))
+    }
+    #[cfg(windows)]
+    {
+        let script = format!("(Get-Process -Id {} -ErrorAction Stop).StartTime.ToFileTimeUtc()", pid);
+        command_output(Command::new("powershell").args(["-NoProfile", "-Command", &script]))
+    }
+}
+
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+fn command_output(command: &mut Command) -> Option<String> {
+    let output = command.stdin(std::process::Stdio::null()).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !text.is_empty()).then_some(text)
+}
+
+/// Whether the recorded process is still the one running under its pid.
+fn is_alive(record: &ChildProcessRecord) -> bool {
+    process_start_time(record.pid).as_deref() == Some(record.started_at.as_str())
+}
+
+/// Kill a recorded process and its direct children.
+fn kill(record: &ChildProcessRecord) {
+    let pid = record.pid.to_string();
+    #[cfg(unix)]
+    {
+        // npx-launched agents run the real agent as a child
+        let _ = Command::new("pkill").args(["-TERM", "-P", &pid]).status();
+        let _ = Command::new("kill").args(["-TERM", &pid]).status();
+        let deadline = std::time::Instant::now() + Duration::from_millis(TERM_GRACE_MS);
+        while is_alive(record) && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        if is_alive(record) {
+            let _ = Command::new("pkill").args(["-KILL", "-P", &pid]).status();
+            let _ = Command::new("kill").args(["-KILL", &pid]).status();
+        }
+    }
+    #[cfg(windows)]
+    {
+        let _ = Command::new("taskkill").args(["/PID", &pid, "/T", "/F"]).status();
+    }
+}
+
+fn kill_and_log(record: &ChildProcessRecord, reason: &str) {
+    kill(record);
+    if is_alive(record) {
+        log::warn!(
+            "[Reaper] Could not kill {} process {} of {} ({}): {}",
+            record.kind, record.pid, record.owner_id, record.command, reason
+        );
+    } else {
+        log::info!(
+            "[Reaper] Killed {} process {} of {} ({}): {}",
+            record.kind, record.pid, record.owner_id, record.command, reason
+        );
+    }
+}
+
+/// Record a spawned process. Failures are logged: a missing record only
+/// means the process cannot be reaped after a crash.
+pub async fn record(state: &AppState, kind: &str, owner_id: &str, command: &str, child: &tokio::process::Child) {
+    let Some(pid) = child.id() else {
+        return;
+    };
+    let state = state.clone();
+    let (kind, owner_id, command) = (kind.to_string(), owner_id.to_string(), command.to_string());
+    let result = telemetry::spawn_blocking(move || {
+        let Some(started_at) = process_start_time(pid) else {
+            log::debug!("[Reaper] No start time for {} process {}, not recording it", kind, pid);
+            return Ok(());
+        };
+        let app_pid = std::process::id();
+        let app_started_at = process_start_time(app_pid);
+        let process = ChildProcessRecord { pid, started_at, kind, owner_id, command, app_pid, app_started_at };
+        child_process_repo::record(&state, &process)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()));
+    if let Err(e) = result.and_then(|r| r) {
+        log::warn!("[Reaper] Failed to record process {}: {}", pid, e);
+    }
+}
+
+/// Whether the app instance that recorded the process is still running.
+/// Its start time tells it from a later process that reused its pid; records
+/// without one go by the pid alone.
+fn instance_is_running(record: &ChildProcessRecord) -> bool {
+    let started_at = process_start_time(record.app_pid);
+    match &record.app_started_at {
+        Some(recorded) => started_at.as_ref() == Some(recorded),
+        None => record.app_pid == std::process::id() || started_at.is_some(),
+    }
+}
+
+/// Kill processes an earlier app instance left running and forget its records.
+/// Instances that are still running keep theirs.
+fn reap_previous_instances(state: &AppState) -> AppResult<usize> {
+    let mut reaped = 0;
+    for record in child_process_repo::list(state)? {
+        if instance_is_running(&record) {
+            continue;
+        }
+        if is_alive(&record) {
+            kill_and_log(&record, "left behind by an earlier run of the app");
+            reaped += 1;
+        }
+        child_process_repo::delete(state, record.pid, &record.started_at)?;
+    }
+    Ok(reaped)
+}
+
+/// Forget exited processes and kill live ones the app no longer tracks.
+/// A process is only killed when it was untracked on the previous pass too,
+/// since a fresh one is recorded before its caller starts tracking it.
+fn reap_untracked(state: &AppState, tracked: &HashSet<u32>, untracked: &mut HashSet<u32>) -> AppResult<usize> {
+    let previously_untracked = std::mem::take(untracked);
+    let mut reaped = 0;
+    for record in child_process_repo::list(state)? {
+        if !is_alive(&record) {
+            child_process_repo::delete(state, record.pid, &record.started_at)?;
+            continue;
+        }
+        // Processes of another running instance are its own business
+        if record.app_pid != std::process::id() || tracked.contains(&record.pid) {
+            continue;
+        }
+        if !previously_untracked.contains(&record.pid) {
+            untracked.insert(record.pid);
+            continue;
+        }
+        kill_and_log(&record, "no longer tracked by the app");
+        child_process_repo::delete(state, record.pid, &record.started_at)?;
+        reaped += 1;
+    }
+    Ok(reaped)
+}
+
+/// Pids of the processes the app currently holds.
+async fn tracked_pids(state: &AppState) -> HashSet<u32> {
+    let mut pids: HashSet<u32> = {
+        let processes = state.agent_processes.lock().await;
+        processes.values().filter_map(|p| p.child.id()).collect()
+    };
+    let bridges = state.chat_tool_processes.lock().await;
+    pids.extend(bridges.values().filter_map(|p| p.child.id()));
+    pids
+}
+
+/// Stop the processes of orchestration runs that are not running any more.
+async fn reap_finished_runs(state: &AppState) {
+    let runs: HashSet<String> = {
+        let processes = state.agent_processes.lock().await;
+        processes.keys().filter_map(|k| orchestrator::orch_task_run_id(k)).map(str::to_string).collect()
+    };
+    for run in runs {
+        if state.active_task_runs.lock().await.contains_key(&run) {
+            continue;
+        }
+        let state_clone = state.clone();
+        let run_clone = run.clone();
+        let status = match telemetry::spawn_blocking(move || task_run_repo::get_task_run(&state_clone, &run_clone)).await {
+            Ok(Ok(task_run)) => Some(task_run.status),
+            Ok(Err(AppError::NotFound(_))) => None,
+            Ok(Err(e)) => {
+                log::warn!("[Reaper] Failed to load task run {}: {}", run, e);
+                continue;
+            }
+            Err(e) => {
+                log::warn!("[Reaper] Spawn blocking failed for task run {}: {}", run, e);
+                continue;
+            }
+        };
+        if status.as_deref().is_some_and(|s| LIVE_RUN_STATUSES.contains(&s)) {
+            continue;
+        }
+        log::info!(
+            "[Reaper] Stopping processes of task run {} ({})",
+            run,
+            status.as_deref().unwrap_or("deleted")
+        );
+        orchestrator::cleanup_task_processes(state, &run).await;
+    }
+}
+
+/// One periodic pass. Returns the pids found untracked, for the next pass.
+async fn reap(state: &AppState, mut untracked: HashSet<u32>) -> HashSet<u32> {
+    reap_finished_runs(state).await;
+    let tracked = tracked_pids(state).await;
+    let state_clone = state.clone();
+    let result = telemetry::spawn_blocking(move || {
+        let reaped = reap_untracked(&state_clone, &tracked, &mut untracked);
+        (reaped, untracked)
+    })
+    .await;
+    match result {
+        Ok((Ok(reaped), untracked)) => {
+            if reaped > 0 {
+                log::info!("[Reaper] Killed {} untracked process(es)", reaped);
+            }
+            untracked
+        }
+        Ok((Err(e), untracked)) => {
+            log::warn!("[Reaper] Periodic pass failed: {}", e);
+            untracked
+        }
+        Err(e) => {
+            log::warn!("[Reaper] Spawn blocking failed: {}", e);
+            HashSet::new()
+        }
+    }
+}
+
+/// Reap what an earlier instance left behind, then keep reaping until shutdown.
+pub async fn run(state: AppState) {
+    let state_clone = state.clone();
+    match telemetry::spawn_blocking(move || reap_previous_instances(&state_clone)).await {
+        Ok(Ok(0)) => log::info!("[Reaper] No processes left behind by an earlier run"),
+        Ok(Ok(reaped)) => log::info!("[Reaper] Killed {} process(es) left behind by an earlier run", reaped),
+        Ok(Err(e)) => log::warn!("[Reaper] Startup pass failed: {}", e),
+        Err(e) => log::warn!("[Reaper] Spawn blocking failed: {}", e),
+    }
+    if let Err(e) = telemetry::spawn_blocking(|| container::remove_orphans(|pid| process_start_time(pid).is_some())).await {
+        log::warn!("[Reaper] Spawn blocking failed: {}", e);
+    }
+
+    let mut interval = tokio::time::interval(Duration::from_secs(REAP_INTERVAL_SECS));
+    interval.tick().await;
+    let mut untracked = HashSet::new();
+    loop {
+        tokio::select! {
+            _ = state.shutdown_token.cancelled() => break,
+            _ = interval.tick() => untracked = reap(&state, untracked).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_start_time_from_stat_line() {
+        let stat = "4242 (node (agent) x) S 1 4242 4242 0 -1 4194560 100 0 0 0 5 2 0 0 20 0 11 0 987654 1000 50";
+        assert_eq!(parse_stat_start_time(stat).as_deref(), Some("987654"));
+        assert_eq!(parse_stat_start_time("garbage"), None);
+
+        assert!(process_start_time(std::process::id()).is_some());
+        assert_eq!(process_start_time(u32::MAX), None);
+    }
+
+    #[test]
+    fn tells_a_running_instance_from_one_that_reused_its_pid() {
+        let app_pid = std::process::id();
+        let record = |app_pid: u32, app_started_at: Option<String>| ChildProcessRecord {
+            pid: 4242,
+            started_at: "1".into(),
+            kind: "agent".into(),
+            owner_id: "agent-1".into(),
+            command: "agent --acp".into(),
+            app_pid,
+            app_started_at,
+        };
+        assert!(instance_is_running(&record(app_pid, process_start_time(app_pid))));
+        assert!(!instance_is_running(&record(app_pid, Some("earlier".into()))));
+        assert!(instance_is_running(&record(app_pid, None)));
+        assert!(!instance_is_running(&record(u32::MAX, None)));
+    }
+}
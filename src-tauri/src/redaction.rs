@@ -0,0 +1,295 @@
+//! Masking of secrets in agent outputs.
+//!
+//! Built-in pattern groups catch API keys, tokens and emails; further
+//! regular expressions can be configured. A workspace with `redact_outputs`
+//! on has its agents' outputs masked before they are stored, streamed to the
+//! frontend, written into summary.md or exported. Streamed text is masked a
+//! word at a time, so a secret split across chunks is still caught.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::db::workspace_repo;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+const REDACTED: &str = "[redacted]";
+
+/// Text held back from a stream waiting for the end of a word, at most.
+const MAX_PENDING_CHARS: usize = 512;
+
+/// Text still held back when a stream runs past `MAX_PENDING_CHARS` without
+/// a word break, so a secret across the cut is seen whole.
+const OVERLAP_CHARS: usize = 128;
+
+/// The end of released text that a secret may follow in the next chunk,
+/// e.g. `Bearer ` or `API_KEY: `.
+const LEAD_IN: &str = r"(?i)(?:\bbearer|[=:])\s*$";
+
+/// Built-in pattern groups. Patterns with a leading capture group keep it,
+/// e.g. the `Bearer ` before a token.
+const BUILT_IN: &[(&str, &[&str])] = &[
+    (
+        "api_keys",
+        &[
+            r"\b(?:sk|pk|rk)-[A-Za-z0-9_-]{16,}",
+            r"\bgh[pousr]_[A-Za-z0-9]{20,}",
+            r"\bgithub_pat_[A-Za-z0-9_]{20,}",
+            r"\bxox[abpr]-[A-Za-z0-9-]{10,}",
+            r"\bAIza[0-9A-Za-z_-]{30,}",
+            r"\bAKIA[0-9A-Z]{16}\b",
+        ],
+    ),
+    (
+        "tokens",
+        &[
+            r"\beyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}",
+            r"(?i)\b(bearer\s+)[A-Za-z0-9._~+/=-]{12,}",
+            r#"(?i)\b([A-Z0-9_]*(?:api_?key|secret|token|password|passwd)[A-Z0-9_]*[=:]\s*["']?)[^\s"']{6,}"#,
+        ],
+    ),
+    ("emails", &[r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b"]),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Built-in pattern groups in use: "api_keys", "tokens" and "emails"
+    pub built_in: Vec<String>,
+    /// Regular expressions of further text to mask
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            built_in: BUILT_IN.iter().map(|(name, _)| name.to_string()).collect(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl RedactionConfig {
+    pub fn validate(&self) -> AppResult<()> {
+        if let Some(unknown) = self.built_in.iter().find(|name| !BUILT_IN.iter().any(|(n, _)| n == name)) {
+            return Err(AppError::InvalidRequest(format!("Unknown built-in redaction pattern group '{}'", unknown)));
+        }
+        for pattern in self.patterns.iter().filter(|p| !p.trim().is_empty()) {
+            Regex::new(pattern)
+                .map_err(|e| AppError::InvalidRequest(format!("Invalid redaction pattern '{}': {}", pattern, e)))?;
+        }
+        Ok(())
+    }
+}
+
+/// The compiled patterns of a redaction config.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    /// Each pattern and whether it keeps its first capture group
+    rules: Vec<(Regex, bool)>,
+}
+
+impl Redactor {
+    pub fn new(config: &RedactionConfig) -> Self {
+        let built_in = BUILT_IN
+            .iter()
+            .filter(|(name, _)| config.built_in.iter().any(|n| n == name))
+            .flat_map(|(_, patterns)| patterns.iter())
+            .filter_map(|pattern| Regex::new(pattern).ok())
+            .map(|regex| {
+                let keeps_prefix = regex.captures_len() > 1;
+                (regex, keeps_prefix)
+            });
+        let custom = config
+            .patterns
+            .iter()
+            .filter(|p| !p.trim().is_empty())
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some((regex, false)),
+                Err(e) => {
+                    log::warn!("Skipping invalid redaction pattern '{}': {}", pattern, e);
+                    None
+                }
+            });
+        Self { rules: built_in.chain(custom).collect() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (regex, keeps_prefix) in &self.rules {
+            let replacement = if *keeps_prefix { "${1}[redacted]" } else { REDACTED };
+            if let std::borrow::Cow::Owned(replaced) = regex.replace_all(&text, replacement) {
+                text = replaced;
+            }
+        }
+        text
+    }
+}
+
+/// Masks text streamed in chunks. Text is released up to the last
+/// whitespace, so a word split across chunks is masked whole; a word that
+/// a secret may follow, like `Bearer`, is held back with it.
+#[derive(Debug)]
+pub struct StreamRedactor {
+    redactor: Redactor,
+    lead_in: Regex,
+    pending: String,
+}
+
+impl StreamRedactor {
+    pub fn new(redactor: Redactor) -> Self {
+        let lead_in = Regex::new(LEAD_IN).expect("valid lead-in pattern");
+        Self { redactor, lead_in, pending: String::new() }
+    }
+
+    pub fn redactor(&self) -> &Redactor {
+        &self.redactor
+    }
+
+    /// The masked text that can be released after `chunk`; may be empty.
+    pub fn push(&mut self, chunk: &str) -> String {
+        self.pending.push_str(chunk);
+        let end = match self.word_end() {
+            Some(end) => end,
+            None if self.pending.len() > MAX_PENDING_CHARS => self.overlap_end(),
+            None => return String::new(),
+        };
+        let ready: String = self.pending.drain(..end).collect();
+        self.redactor.redact(&ready)
+    }
+
+    /// The end of the last whole word of the pending text that no secret
+    /// can follow.
+    fn word_end(&self) -> Option<usize> {
+        let mut text = self.pending.as_str();
+        loop {
+            let (i, c) = text.char_indices().rfind(|(_, c)| c.is_whitespace())?;
+            let end = i + c.len_utf8();
+            match self.lead_in.find(&text[..end]) {
+                Some(m) => text = &text[..m.start()],
+                None => return Some(end),
+            }
+        }
+    }
+
+    /// The end of the text to release from a long run without a word break:
+    /// all but the last `OVERLAP_CHARS`, moved back before any secret found
+    /// across the cut.
+    fn overlap_end(&self) -> usize {
+        let mut end = self.pending.len() - OVERLAP_CHARS;
+        while !self.pending.is_char_boundary(end) {
+            end -= 1;
+        }
+        loop {
+            let across = self
+                .redactor
+                .rules
+                .iter()
+                .flat_map(|(regex, _)| regex.find_iter(&self.pending))
+                .find(|m| m.start() < end && m.end() > end);
+            match across {
+                Some(m) => end = m.start(),
+                None => break,
+            }
+        }
+        // A single secret filling the whole window is released masked.
+        if end == 0 {
+            self.pending.len()
+        } else {
+            end
+        }
+    }
+
+    /// The masked text still held back, at the end of the stream.
+    pub fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        self.redactor.redact(&rest)
+    }
+}
+
+/// The redactor for agent outputs of the workspace; None when its outputs
+/// are not masked. Blocking.
+pub fn for_workspace(state: &AppState, workspace_id: Option<&str>) -> Option<Redactor> {
+    if let Some(ws_id) = workspace_id {
+        match workspace_repo::get_workspace(state, ws_id) {
+            Ok(workspace) if !workspace.redact_outputs => return None,
+            Ok(_) => {}
+            Err(e) => log::warn!("Failed to load workspace {} for redaction: {}", ws_id, e),
+        }
+    }
+    let redactor = Redactor::new(&config::current(state).redaction);
+    (!redactor.is_empty()).then_some(redactor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_built_in_and_custom_patterns() {
+        let config = RedactionConfig { patterns: vec![r"ACME-\d{4}".into(), " ".into()], ..Default::default() };
+        assert!(config.validate().is_ok());
+        let redactor = Redactor::new(&config);
+        assert_eq!(
+            redactor.redact(
+                "key sk-proj-abcdefghijklmnop1234, Authorization: Bearer abc.def-ghi_jkl, \
+                 DB_PASSWORD=hunter22 mail ann@example.co.uk about ACME-1234"
+            ),
+            "key [redacted], Authorization: Bearer [redacted], DB_PASSWORD=[redacted] mail [redacted] about [redacted]"
+        );
+        assert_eq!(redactor.redact("the token is short"), "the token is short");
+
+        let emails_only = Redactor::new(&RedactionConfig { built_in: vec!["emails".into()], patterns: Vec::new() });
+        assert_eq!(emails_only.redact("sk-abcdefghijklmnopqrst to a@b.io"), "sk-abcdefghijklmnopqrst to [redacted]");
+
+        assert!(RedactionConfig { built_in: vec!["phones".into()], ..Default::default() }.validate().is_err());
+        assert!(RedactionConfig { patterns: vec!["(".into()], ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn masks_secrets_split_across_chunks() {
+        let mut stream = StreamRedactor::new(Redactor::new(&RedactionConfig::default()));
+        let mut out = String::new();
+        for chunk in ["Use ghp_abcdefghij", "klmnopqrstuvwx to ", "push, or ask bob@", "example.com"] {
+            out.push_str(&stream.push(chunk));
+        }
+        assert_eq!(out, "Use [redacted] to push, or ask ");
+        out.push_str(&stream.finish());
+        assert_eq!(out, "Use [redacted] to push, or ask [redacted]");
+    }
+
+    #[test]
+    fn holds_back_words_a_secret_may_follow() {
+        let cases = [
+            (["Authorization: Bearer ", "abcdefghijklmnop and more"], "Authorization: Bearer [redacted] and more"),
+            (["export TOKEN=", "hunter22secret done"], "export TOKEN=[redacted] done"),
+            (["set API_KEY: ", "abcdef123456 now"], "set API_KEY: [redacted] now"),
+        ];
+        for (chunks, expected) in cases {
+            let mut stream = StreamRedactor::new(Redactor::new(&RedactionConfig::default()));
+            let mut out = String::new();
+            for chunk in chunks {
+                out.push_str(&stream.push(chunk));
+            }
+            out.push_str(&stream.finish());
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn long_runs_without_a_word_break_keep_an_overlap() {
+        let mut stream = StreamRedactor::new(Redactor::new(&RedactionConfig::default()));
+        let long = "x".repeat(600);
+        let first = stream.push(&format!("{}-sk-abcdefgh", long));
+        assert_eq!(first.len(), 600 + 12 - OVERLAP_CHARS);
+        let mut out = first;
+        out.push_str(&stream.push("ijklmnopqrst end"));
+        out.push_str(&stream.finish());
+        assert_eq!(out, format!("{}-[redacted] end", long));
+    }
+}
@@ -0,0 +1,342 @@
+//! Workspace onboarding from an existing repository.
+//!
+//! `create_workspace_from_repo` inspects a repository's root for its
+//! languages, package managers and test commands, an AGENTS.md, project
+//! skills and an agents.yaml. It creates a workspace on the repository, or
+//! reuses the one already there, gives it a Control Hub and proposes agents
+//! with skills for the languages found. Nothing else is registered until the
+//! user confirms the proposed agents.
+
+use std::path::Path;
+
+use crate::acp::{builtin, skill_discovery};
+use crate::db::{agent_manifest, agent_md, workspace_repo};
+use crate::error::{AppError, AppResult};
+use crate::models::agent::{AgentSkill, CreateAgentRequest};
+use crate::models::onboarding::{RepoInspection, RepoOnboardingProposal};
+use crate::models::workspace::CreateWorkspaceRequest;
+use crate::onboarding;
+use crate::state::AppState;
+
+/// The `test` script `npm init` writes, which runs no tests.
+const NPM_PLACEHOLDER_TEST: &str = "no test specified";
+
+fn push_unique(list: &mut Vec<String>, item: &str) {
+    if !list.iter().any(|i| i == item) {
+        list.push(item.to_string());
+    }
+}
+
+fn node_package_manager(root: &Path) -> &'static str {
+    if root.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if root.join("yarn.lock").exists() {
+        "yarn"
+    } else if root.join("bun.lockb").exists() || root.join("bun.lock").exists() {
+        "bun"
+    } else {
+        "npm"
+    }
+}
+
+fn has_npm_test_script(root: &Path) -> bool {
+    let Ok(text) = std::fs::read_to_string(root.join("package.json")) else {
+        return false;
+    };
+    let manifest: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+    manifest["scripts"]["test"].as_str().is_some_and(|s| !s.contains(NPM_PLACEHOLDER_TEST))
+}
+
+fn python_package_manager(root: &Path) -> &'static str {
+    if root.join("poetry.lock").exists() {
+        "poetry"
+    } else if root.join("uv.lock").exists() {
+        "uv"
+    } else if root.join("Pipfile").exists() {
+        "pipenv"
+    } else {
+        "pip"
+    }
+}
+
+fn uses_pytest(root: &Path) -> bool {
+    ["pytest.ini", "conftest.py", "tests"].iter().any(|f| root.join(f).exists())
+        || std::fs::read_to_string(root.join("pyproject.toml")).is_ok_and(|t| t.contains("pytest"))
+}
+
+fn has_extension(root: &Path, extensions: &[&str]) -> bool {
+    std::fs::read_dir(root).is_ok_and(|entries| {
+        entries.filter_map(|e| e.ok()).any(|e| {
+            e.path().extension().and_then(|x| x.to_str()).is_some_and(|x| extensions.contains(&x))
+        })
+    })
+}
+
+fn has_make_target(root: &Path, target: &str) -> bool {
+    std::fs::read_to_string(root.join("Makefile"))
+        .is_ok_and(|t| t.lines().any(|l| l.strip_prefix(target).is_some_and(|rest| rest.starts_with(':'))))
+}
+
+/// Inspect the root of a repository. Blocking.
+pub fn inspect(root: &Path) -> RepoInspection {
+    let mut found = RepoInspection::default();
+    let has = |name: &str| root.join(name).exists();
+
+    if has("Cargo.toml") {
+        push_unique(&mut found.languages, "Rust");
+        push_unique(&mut found.package_managers, "cargo");
+        push_unique(&mut found.test_commands, "cargo test");
+    }
+    if has("package.json") {
+        push_unique(&mut found.languages, if has("tsconfig.json") { "TypeScript" } else { "JavaScript" });
+        let manager = node_package_manager(root);
+        push_unique(&mut found.package_managers, manager);
+        if has_npm_test_script(root) {
+            let command = if manager == "bun" { "bun run test".to_string() } else { format!("{} test", manager) };
+            push_unique(&mut found.test_commands, &command);
+        }
+    }
+    if has("pyproject.toml") || has("requirements.txt") || has("setup.py") {
+        push_unique(&mut found.languages, "Python");
+        let manager = python_package_manager(root);
+        push_unique(&mut found.package_managers, manager);
+        if uses_pytest(root) {
+            let command = match manager {
+                "poetry" | "uv" | "pipenv" => format!("{} run pytest", manager),
+                _ => "pytest".to_string(),
+            };
+            push_unique(&mut found.test_commands, &command);
+        }
+    }
+    if has("go.mod") {
+        push_unique(&mut found.languages, "Go");
+        push_unique(&mut found.package_managers, "go");
+        push_unique(&mut found.test_commands, "go test ./...");
+    }
+    if has("pom.xml") {
+        push_unique(&mut found.languages, "Java");
+        push_unique(&mut found.package_managers, "maven");
+        push_unique(&mut found.test_commands, "mvn test");
+    }
+    if has("build.gradle") || has("build.gradle.kts") {
+        push_unique(&mut found.languages, if has("build.gradle.kts") { "Kotlin" } else { "Java" });
+        push_unique(&mut found.package_managers, "gradle");
+        push_unique(&mut found.test_commands, if has("gradlew") { "./gradlew test" } else { "gradle test" });
+    }
+    if has("Gemfile") {
+        push_unique(&mut found.languages, "Ruby");
+        push_unique(&mut found.package_managers, "bundler");
+        if has("spec") {
+            push_unique(&mut found.test_commands, "bundle exec rspec");
+        } else if has("Rakefile") {
+            push_unique(&mut found.test_commands, "bundle exec rake test");
+        }
+    }
+    if has("composer.json") {
+        push_unique(&mut found.languages, "PHP");
+        push_unique(&mut found.package_managers, "composer");
+    }
+    if has_extension(root, &["sln", "csproj"]) {
+        push_unique(&mut found.languages, "C#");
+        push_unique(&mut found.package_managers, "dotnet");
+        push_unique(&mut found.test_commands, "dotnet test");
+    }
+    if has_make_target(root, "test") {
+        push_unique(&mut found.test_commands, "make test");
+    }
+
+    let agents_md = root.join(agent_md::AGENTS_MD_FILE);
+    if agents_md.is_file() {
+        found.agents_md_path = Some(agents_md.to_string_lossy().to_string());
+    }
+    let root_str = root.to_string_lossy();
+    found.skill_ids = skill_discovery::discover_skills(&root_str)
+        .skills
+        .into_iter()
+        .filter(|s| s.location == "project")
+        .map(|s| s.skill.id)
+        .collect();
+    found.has_agents_manifest = agent_manifest::manifest_path(&root_str).is_file();
+    found
+}
+
+fn skill(id: &str, name: &str, description: String, keywords: &[&str]) -> AgentSkill {
+    AgentSkill {
+        id: id.to_string(),
+        name: name.to_string(),
+        skill_type: "skill".into(),
+        description,
+        task_keywords: keywords.iter().map(|k| k.to_string()).collect(),
+        constraints: Vec::new(),
+        skill_source: "manual".into(),
+        license: None,
+        compatibility: None,
+        metadata: std::collections::HashMap::new(),
+    }
+}
+
+fn language_skill(language: &str) -> AgentSkill {
+    let id = match language {
+        "C#" => "csharp".to_string(),
+        other => other.to_lowercase(),
+    };
+    let keyword = language.to_lowercase();
+    skill(&id, language, format!("Writes and changes {} code", language), &[&keyword])
+}
+
+/// Agents for a repository: a developer for its languages, a tester when it
+/// has tests and a reviewer. Project skills are not listed; every agent but
+/// the Control Hub picks them up from the workspace.
+pub fn propose_agents(repo_name: &str, inspection: &RepoInspection, workspace_id: &str) -> Vec<CreateAgentRequest> {
+    if inspection.has_agents_manifest {
+        return Vec::new();
+    }
+    let builtin = builtin::get_builtin_agent();
+    let conventions = match &inspection.agents_md_path {
+        Some(_) => "Follow the conventions described in AGENTS.md at the repository root.".to_string(),
+        None => "Follow the conventions of the surrounding code.".to_string(),
+    };
+    let tests = inspection.test_commands.iter().map(|c| format!("`{}`", c)).collect::<Vec<_>>().join(", ");
+    let agent = |name: &str, icon: &str, description: String, system_prompt: String, skills: Vec<AgentSkill>| {
+        CreateAgentRequest {
+            icon: icon.into(),
+            description,
+            system_prompt,
+            skills_json: serde_json::to_string(&skills).unwrap_or_else(|_| "[]".into()),
+            acp_command: Some(builtin.command.clone()),
+            acp_args_json: Some(builtin.args_json.clone()),
+            ..CreateAgentRequest::named(name, Some(workspace_id))
+        }
+    };
+
+    let mut agents = Vec::new();
+    let languages = if inspection.languages.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", inspection.languages.join(", "))
+    };
+    let mut developer_prompt = format!("You implement changes in the {} repository. {}", repo_name, conventions);
+    if !tests.is_empty() {
+        developer_prompt.push_str(&format!(" Run the tests with {} before you finish.", tests));
+    }
+    agents.push(agent(
+        "Developer",
+        "code",
+        format!("Implements features and fixes in {}{}", repo_name, languages),
+        developer_prompt,
+        inspection.languages.iter().map(|l| language_skill(l)).collect(),
+    ));
+    if !tests.is_empty() {
+        agents.push(agent(
+            "Tester",
+            "bug_report",
+            format!("Runs the tests of {} and fixes failures", repo_name),
+            format!(
+                "You run the tests of the {} repository with {} and fix what fails. {}",
+                repo_name, tests, conventions
+            ),
+            vec![skill("testing", "Testing", format!("Runs {}", tests), &["test", "tests", "failing", "coverage"])],
+        ));
+    }
+    agents.push(agent(
+        "Reviewer",
+        "rate_review",
+        format!("Reviews changes to {} for correctness and style", repo_name),
+        format!(
+            "You review changes to the {} repository and report problems without editing files. {}",
+            repo_name, conventions
+        ),
+        vec![skill("review", "Code review", "Reviews diffs for bugs and style".into(), &["review", "audit"])],
+    ));
+    agents
+}
+
+/// Create a workspace on a repository and propose agents for it.
+pub fn create_workspace_from_repo(state: &AppState, path: &str) -> AppResult<RepoOnboardingProposal> {
+    let root = Path::new(path.trim())
+        .canonicalize()
+        .map_err(|e| AppError::InvalidRequest(format!("Cannot open {}: {}", path, e)))?;
+    if !root.is_dir() {
+        return Err(AppError::InvalidRequest(format!("{} is not a directory", root.display())));
+    }
+    let working_directory = root.to_string_lossy().to_string();
+    let repo_name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| working_directory.clone());
+    let inspection = inspect(&root);
+
+    let existing = workspace_repo::list_workspaces(state)?
+        .into_iter()
+        .find(|w| w.working_directory == working_directory);
+    let workspace_created = existing.is_none();
+    let workspace = match existing {
+        Some(workspace) => workspace,
+        None => workspace_repo::create_workspace(
+            state,
+            CreateWorkspaceRequest {
+                name: repo_name.clone(),
+                icon: "folder".into(),
+                working_directory,
+                agent_ids: Vec::new(),
+            },
+        )?,
+    };
+    let control_hub_id = match onboarding::ensure_control_hub(state, &workspace.id) {
+        Ok((id, _)) => Some(id),
+        Err(e) => {
+            log::warn!("Failed to set up a Control Hub for workspace {}: {}", workspace.id, e);
+            None
+        }
+    };
+    let agents = propose_agents(&repo_name, &inspection, &workspace.id);
+    log::info!(
+        "Onboarded repository {} as workspace {} ({}), proposing {} agent(s)",
+        root.display(),
+        workspace.id,
+        inspection.languages.join(", "),
+        agents.len()
+    );
+    Ok(RepoOnboardingProposal { workspace, workspace_created, control_hub_id, inspection, agents })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inspects_a_repository_and_proposes_agents() {
+        let root = std::env::temp_dir().join(format!("repo-onboarding-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("tests")).unwrap();
+        std::fs::create_dir_all(root.join("skills").join("release-notes")).unwrap();
+        let skill_md = "---\nname: release-notes\ndescription: Notes\n---\n";
+        std::fs::write(root.join("skills/release-notes/SKILL.md"), skill_md).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(root.join("package.json"), r#"{"scripts": {"test": "vitest"}}"#).unwrap();
+        std::fs::write(root.join("pnpm-lock.yaml"), "").unwrap();
+        std::fs::write(root.join("tsconfig.json"), "{}").unwrap();
+        std::fs::write(root.join("pyproject.toml"), "[tool.poetry]\n").unwrap();
+        std::fs::write(root.join("poetry.lock"), "").unwrap();
+        std::fs::write(root.join("Makefile"), "build:\n\tcargo build\ntest: build\n\tcargo test\n").unwrap();
+        std::fs::write(root.join("AGENTS.md"), "# Agents\n").unwrap();
+
+        let found = inspect(&root);
+        assert_eq!(found.languages, ["Rust", "TypeScript", "Python"]);
+        assert_eq!(found.package_managers, ["cargo", "pnpm", "poetry"]);
+        assert_eq!(found.test_commands, ["cargo test", "pnpm test", "poetry run pytest", "make test"]);
+        assert!(found.agents_md_path.is_some());
+        assert_eq!(found.skill_ids, ["release-notes"]);
+        assert!(!found.has_agents_manifest);
+
+        let agents = propose_agents("repo", &found, "ws");
+        let names: Vec<&str> = agents.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["Developer", "Tester", "Reviewer"]);
+        assert!(agents[0].system_prompt.contains("AGENTS.md") && agents[0].system_prompt.contains("`cargo test`"));
+        assert!(agents[0].skills_json.contains("\"id\":\"typescript\""));
+        assert!(agents.iter().all(|a| a.workspace_id.as_deref() == Some("ws")));
+
+        std::fs::write(root.join("agents.yaml"), "agents: []\n").unwrap();
+        assert!(propose_agents("repo", &inspect(&root), "ws").is_empty());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,206 @@
+//! Environment snapshots of task runs.
+//!
+//! When a run starts planning, the app version, OS, the versions and models
+//! of the agents it can assign to and the settings that change how runs
+//! behave are stored with it. Comparing the snapshots of two runs shows what
+//! changed between a run that worked and one that did not.
+
+use std::collections::BTreeMap;
+
+use crate::acp::discovery::{self, RegistryEntry, RegistryFile};
+use crate::config::AppConfig;
+use crate::db::task_run_repo;
+use crate::models::agent::AgentConfig;
+use crate::models::task_run::{AgentEnvironment, EnvironmentChange, RunEnvironment};
+use crate::state::AppState;
+use crate::telemetry;
+
+/// The settings recorded with a run.
+fn settings(config: &AppConfig) -> BTreeMap<String, String> {
+    let mut settings = BTreeMap::new();
+    let mut set = |key: &str, value: String| {
+        settings.insert(key.to_string(), value);
+    };
+    set("language", config.language.clone());
+    set("review_writes", config.review_writes.to_string());
+    set("file_conflict_mode", config.file_conflict_mode.clone());
+    set("response_cache_ttl_minutes", config.response_cache_ttl_minutes.to_string());
+    set("replan_on_failure", config.replan_on_failure.to_string());
+    set("plan_approval_confidence", config.plan_approval_confidence.to_string());
+    set("max_run_minutes", config.max_run_minutes.to_string());
+    set("memory_enabled", config.memory_enabled.to_string());
+    set("confirmation_timeout_action", config.confirmation_timeout_action.clone());
+    set("web_search", if config.web_search.enabled { config.web_search.backend.clone() } else { "off".to_string() });
+    set("knowledge_context.planner", config.knowledge_context.planner.to_string());
+    set("knowledge_context.agents", config.knowledge_context.agents.to_string());
+    set("knowledge_context.top_k", config.knowledge_context.top_k.to_string());
+    set("redaction.built_in", config.redaction.built_in.join(","));
+    set("redaction.patterns", config.redaction.patterns.len().to_string());
+    settings
+}
+
+fn agent_environment(
+    agent: &AgentConfig,
+    registry: Option<&RegistryFile>,
+    installed_version: &impl Fn(&RegistryEntry) -> Option<String>,
+) -> AgentEnvironment {
+    let entry = registry.zip(agent.acp_command.as_deref()).and_then(|(r, c)| discovery::find_entry_by_command(r, c));
+    let runs_on = match (&agent.ssh_host, &agent.container_image) {
+        (Some(host), _) => format!("ssh:{}", host),
+        (None, Some(image)) => format!("container:{}", image),
+        (None, None) => "local".to_string(),
+    };
+    AgentEnvironment {
+        agent_id: agent.id.clone(),
+        name: agent.name.clone(),
+        model: agent.model.clone(),
+        command: agent.acp_command.clone(),
+        registry_id: entry.map(|e| e.id.clone()),
+        registry_version: entry.map(|e| e.version.clone()),
+        installed_version: entry.and_then(installed_version),
+        runs_on,
+        is_control_hub: agent.is_control_hub,
+    }
+}
+
+fn snapshot(
+    config: &AppConfig,
+    agents: &[&AgentConfig],
+    registry: Option<&RegistryFile>,
+    installed_version: impl Fn(&RegistryEntry) -> Option<String>,
+) -> RunEnvironment {
+    RunEnvironment {
+        captured_at: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        registry_version: registry.map(|r| r.version.clone()),
+        agents: agents.iter().map(|a| agent_environment(a, registry, &installed_version)).collect(),
+        settings: settings(config),
+    }
+}
+
+/// Store the environment of a run about to plan with `agents`. Failures are
+/// logged; a run never fails for want of a snapshot.
+pub async fn record(state: &AppState, task_run_id: &str, agents: &[&AgentConfig]) {
+    let registry = discovery::cached_registry().await;
+    let environment = snapshot(&crate::config::current(state), agents, registry.as_ref(), discovery::installed_adapter_version);
+    let state = state.clone();
+    let id = task_run_id.to_string();
+    let result = telemetry::spawn_blocking(move || task_run_repo::set_run_environment(&state, &id, &environment)).await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => log::warn!("Failed to record environment of run {}: {}", task_run_id, e),
+        Err(e) => log::warn!("Failed to record environment of run {}: {}", task_run_id, e),
+    }
+}
+
+/// Flat `key -> value` view of a snapshot; agents are keyed by name.
+fn entries(environment: &RunEnvironment) -> BTreeMap<String, String> {
+    let mut entries = BTreeMap::new();
+    entries.insert("app_version".to_string(), environment.app_version.clone());
+    entries.insert("os".to_string(), environment.os.clone());
+    if let Some(version) = &environment.registry_version {
+        entries.insert("registry_version".to_string(), version.clone());
+    }
+    for agent in &environment.agents {
+        let mut put = |field: &str, value: Option<&String>| {
+            if let Some(value) = value {
+                entries.insert(format!("agent.{}.{}", agent.name, field), value.clone());
+            }
+        };
+        put("model", Some(&agent.model));
+        put("command", agent.command.as_ref());
+        put("registry_version", agent.registry_version.as_ref());
+        put("installed_version", agent.installed_version.as_ref());
+        put("runs_on", Some(&agent.runs_on));
+        if agent.is_control_hub {
+            put("control_hub", Some(&"true".to_string()));
+        }
+    }
+    for (key, value) in &environment.settings {
+        entries.insert(format!("setting.{}", key), value.clone());
+    }
+    entries
+}
+
+/// Values that differ between two snapshots, by key.
+pub fn diff(before: &RunEnvironment, after: &RunEnvironment) -> Vec<EnvironmentChange> {
+    let (before, after) = (entries(before), entries(after));
+    let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| before.get(*key) != after.get(*key))
+        .map(|key| EnvironmentChange {
+            key: key.clone(),
+            before: before.get(key).cloned(),
+            after: after.get(key).cloned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> RegistryFile {
+        serde_json::from_value(serde_json::json!({
+            "version": "2026.10.1",
+            "agents": [{
+                "id": "gemini",
+                "name": "Gemini CLI",
+                "version": "0.27.3",
+                "description": "",
+                "distribution": { "npx": { "package": "@google/gemini-cli@0.27.3" } },
+            }],
+        }))
+        .unwrap()
+    }
+
+    fn agent(name: &str, command: Option<&str>) -> AgentConfig {
+        let mut agent: AgentConfig = serde_json::from_value(serde_json::json!({
+            "id": format!("id-{}", name), "name": name, "icon": "", "description": "", "status": "idle",
+            "execution_mode": "acp", "model": "gemini-2.5-pro", "temperature": 0.7, "max_tokens": 4096,
+            "system_prompt": "", "capabilities_json": "[]", "skills_json": "[]", "is_control_hub": false,
+            "is_secondary_hub": false, "md_file_path": null, "max_concurrency": 1, "available_models_json": null,
+            "is_enabled": true, "disabled_reason": null, "created_at": "", "updated_at": "",
+        }))
+        .unwrap();
+        agent.acp_command = command.map(str::to_string);
+        agent
+    }
+
+    #[test]
+    fn snapshot_resolves_agent_versions_from_the_registry() {
+        let (gemini, custom) = (agent("Researcher", Some("/usr/local/bin/gemini")), agent("Custom", Some("my-agent")));
+        let registry = registry();
+        let env = snapshot(&AppConfig::default(), &[&gemini, &custom], Some(&registry), |_| Some("0.27.1".into()));
+
+        assert_eq!(env.registry_version.as_deref(), Some("2026.10.1"));
+        assert_eq!(env.agents[0].registry_id.as_deref(), Some("gemini"));
+        assert_eq!(env.agents[0].registry_version.as_deref(), Some("0.27.3"));
+        assert_eq!(env.agents[0].installed_version.as_deref(), Some("0.27.1"));
+        assert_eq!(env.agents[0].runs_on, "local");
+        assert_eq!(env.agents[1].registry_id, None);
+        assert_eq!(env.agents[1].installed_version, None);
+        assert_eq!(env.settings.get("review_writes").map(String::as_str), Some("false"));
+    }
+
+    #[test]
+    fn diff_lists_changed_added_and_removed_values() {
+        let researcher = agent("Researcher", Some("gemini"));
+        let before = snapshot(&AppConfig::default(), &[&researcher], None, |_| None);
+        let mut after = before.clone();
+        after.agents[0].model = "gemini-2.5-flash".into();
+        after.agents[0].installed_version = Some("0.28.0".into());
+        after.registry_version = Some("2026.10.2".into());
+
+        let keys: Vec<_> = diff(&before, &after).into_iter().map(|c| (c.key, c.before, c.after)).collect();
+        assert_eq!(keys, vec![
+            ("agent.Researcher.installed_version".into(), None, Some("0.28.0".into())),
+            ("agent.Researcher.model".into(), Some("gemini-2.5-pro".into()), Some("gemini-2.5-flash".into())),
+            ("registry_version".into(), None, Some("2026.10.2".into())),
+        ]);
+        assert!(diff(&before, &before).is_empty());
+    }
+}
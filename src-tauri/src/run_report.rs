@@ -0,0 +1,179 @@
+//! Static HTML report of a task run, for sharing with teammates who don't
+//! run agent-hub.
+//!
+//! The report is one self-contained file with inline styles and no scripts:
+//! the run's request, the hub's plan, each assignment with its output, and
+//! the summary, and what the run ran with when that was recorded.
+//! Credentials and the home directory are masked as in a diagnostics bundle,
+//! and so are the `report_redactions` terms and, in a workspace that redacts
+//! outputs, its redaction patterns.
+
+use crate::acp::orchestrator::format_duration;
+use crate::diagnostics::redact_text;
+use crate::models::task_run::{RunEnvironment, TaskAssignment, TaskPlan, TaskRun};
+use crate::redaction;
+
+const REDACTED: &str = "[redacted]";
+
+const STYLE: &str = "body{font:14px/1.5 -apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;max-width:960px;margin:2rem auto;padding:0 1rem;color:#1e293b}\
+h1{font-size:1.5rem}h2{font-size:1.2rem;margin-top:2rem;border-bottom:1px solid #e2e8f0}h3{font-size:1rem;margin-bottom:.25rem}\
+pre{white-space:pre-wrap;word-break:break-word;background:#f8fafc;border:1px solid #e2e8f0;border-radius:6px;padding:.75rem}\
+table{border-collapse:collapse;width:100%}td,th{border:1px solid #e2e8f0;padding:.35rem .5rem;text-align:left;vertical-align:top}\
+.meta{color:#64748b;font-size:12px}.status{font-weight:600}.failed{color:#e11d48}.completed{color:#059669}\
+footer{margin-top:3rem;color:#94a3b8;font-size:12px}";
+
+/// Masks what a shared report must not show.
+pub struct Redactor {
+    home: Option<String>,
+    terms: Vec<String>,
+    patterns: Option<redaction::Redactor>,
+}
+
+impl Redactor {
+    pub fn new(home: Option<String>, terms: &[String], patterns: Option<redaction::Redactor>) -> Self {
+        let terms = terms.iter().map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+        Self { home, terms, patterns }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let mut text = redact_text(text, self.home.as_deref());
+        if let Some(patterns) = &self.patterns {
+            text = patterns.redact(&text);
+        }
+        for term in &self.terms {
+            text = text.replace(term.as_str(), REDACTED);
+        }
+        text
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The report of a run with its plan and environment, if it has them, and assignments.
+pub fn render(
+    run: &TaskRun,
+    plan: Option<&TaskPlan>,
+    assignments: &[TaskAssignment],
+    environment: Option<&RunEnvironment>,
+    redactor: &Redactor,
+) -> String {
+    let text = |s: &str| escape(&redactor.apply(s));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n\
+         <p class=\"meta\">Started {created} &middot; <span class=\"status {status}\">{status}</span>{timed_out} &middot; \
+         {duration} &middot; {tokens_in} tokens in, {tokens_out} tokens out</p>\n\
+         <h2>Request</h2>\n<pre>{prompt}</pre>\n",
+        title = text(&run.title),
+        created = escape(&run.created_at),
+        status = escape(&run.status),
+        timed_out = if run.timed_out { " (timed out)" } else { "" },
+        duration = format_duration(run.total_duration_ms),
+        tokens_in = run.total_tokens_in,
+        tokens_out = run.total_tokens_out,
+        prompt = text(&run.user_prompt),
+    );
+
+    if let Some(plan) = plan {
+        html.push_str("<h2>Plan</h2>\n");
+        if !plan.analysis.trim().is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", text(plan.analysis.trim())));
+        }
+        html.push_str("<table>\n<tr><th>Step</th><th>Agent</th><th>Task</th></tr>\n");
+        for planned in &plan.assignments {
+            let agent = assignments
+                .iter()
+                .find(|a| a.agent_id == planned.agent_id)
+                .map_or(planned.agent_id.as_str(), |a| a.agent_name.as_str());
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                planned.sequence_order + 1,
+                text(agent),
+                text(&planned.task_description)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !assignments.is_empty() {
+        html.push_str("<h2>Assignments</h2>\n");
+    }
+    for assignment in assignments {
+        html.push_str(&format!(
+            "<h3>{}</h3>\n<p class=\"meta\"><span class=\"status {status}\">{status}</span> &middot; {} &middot; {}{}</p>\n",
+            text(&assignment.agent_name),
+            escape(assignment.model_used.as_deref().unwrap_or("unknown model")),
+            format_duration(assignment.duration_ms),
+            if assignment.cached { " &middot; cached" } else { "" },
+            status = escape(&assignment.status),
+        ));
+        html.push_str(&format!(
+            "<details><summary>Task</summary><pre>{}</pre></details>\n",
+            text(&assignment.input_text)
+        ));
+        if let Some(output) = assignment.output_text.as_deref().filter(|o| !o.trim().is_empty()) {
+            html.push_str(&format!("<pre>{}</pre>\n", text(output)));
+        }
+        if let Some(error) = assignment.error_message.as_deref() {
+            html.push_str(&format!("<p class=\"failed\">{}</p>\n", text(error)));
+        }
+    }
+
+    if let Some(summary) = run.result_summary.as_deref().filter(|s| !s.trim().is_empty()) {
+        html.push_str(&format!("<h2>Summary</h2>\n<pre>{}</pre>\n", text(summary)));
+    }
+    if let Some(environment) = environment {
+        html.push_str(&format!(
+            "<h2>Environment</h2>\n<p class=\"meta\">Agent Hub {} on {} &middot; registry {} &middot; recorded {}</p>\n",
+            escape(&environment.app_version),
+            escape(&environment.os),
+            escape(environment.registry_version.as_deref().unwrap_or("unknown")),
+            escape(&environment.captured_at),
+        ));
+        html.push_str("<table>\n<tr><th>Agent</th><th>Model</th><th>Version</th><th>Runs on</th></tr>\n");
+        for agent in &environment.agents {
+            let version = match (&agent.installed_version, &agent.registry_version) {
+                (Some(installed), Some(latest)) if installed != latest => format!("{} (registry {})", installed, latest),
+                (Some(version), _) | (None, Some(version)) => version.clone(),
+                (None, None) => "unknown".to_string(),
+            };
+            html.push_str(&format!(
+                "<tr><td>{}{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                text(&agent.name),
+                if agent.is_control_hub { " (hub)" } else { "" },
+                escape(&agent.model),
+                escape(&version),
+                text(&agent.runs_on),
+            ));
+        }
+        html.push_str("</table>\n<details><summary>Settings</summary><table>\n");
+        for (key, value) in &environment.settings {
+            html.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", escape(key), text(value)));
+        }
+        html.push_str("</table></details>\n");
+    }
+    html.push_str(&format!(
+        "<footer>Exported from Agent Hub on {}</footer>\n</body>\n</html>\n",
+        chrono::Utc::now().format("%Y-%m-%d %H:%M UTC")
+    ));
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_and_escapes_report_text() {
+        let redactor = Redactor::new(Some("/home/ann".into()), &["Acme Corp".into(), "  ".into()], None);
+        let text = redactor.apply("Acme Corp's key OPENAI_API_KEY=sk-abc in /home/ann/notes");
+        assert_eq!(text, "[redacted]'s key OPENAI_API_KEY=[redacted] in ~/notes");
+        assert_eq!(escape("<b>\"a\" & b</b>"), "&lt;b&gt;&quot;a&quot; &amp; b&lt;/b&gt;");
+    }
+}
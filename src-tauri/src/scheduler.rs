@@ -3,12 +3,20 @@
 //! This module provides a background scheduler that checks for due tasks
 //! and executes them via the orchestration system.
 
-use tauri::AppHandle;
+use std::collections::{HashMap, HashSet};
+
+use tauri::{AppHandle, Emitter};
 use tokio_util::sync::CancellationToken;
 
-use crate::acp::orchestrator;
-use crate::db::task_run_repo;
-use crate::error::AppResult;
+use crate::acp::run_queue;
+use crate::activity;
+use crate::config;
+use crate::db::{schedule_run_repo, task_run_repo, workspace_repo};
+use crate::error::{AppError, AppResult};
+use crate::i18n::{self, Msg};
+use crate::models::notification::Notification;
+use crate::models::task_run::{RunPriority, ScheduledTask, TaskRun};
+use crate::notifications;
 use crate::state::AppState;
 
 /// Scheduler state for managing the background task
@@ -44,6 +52,8 @@ pub fn start_scheduler(app: AppHandle, state: AppState) -> SchedulerState {
 
     let task_handle = tokio::spawn(async move {
         log::info!("[Scheduler] Starting task scheduler");
+        // Runs already reminded about, with the `updated_at` they were waiting since
+        let mut reminded: HashMap<String, String> = HashMap::new();
 
         loop {
             // Check every 60 seconds
@@ -52,6 +62,9 @@ pub fn start_scheduler(app: AppHandle, state: AppState) -> SchedulerState {
                     if let Err(e) = check_and_execute_scheduled_tasks(&app, &state).await {
                         log::error!("[Scheduler] Error checking scheduled tasks: {:?}", e);
                     }
+                    if let Err(e) = remind_waiting_runs(&app, &state, &mut reminded).await {
+                        log::error!("[Scheduler] Error checking waiting runs: {:?}", e);
+                    }
                 }
                 _ = cancel_token_clone.cancelled() => {
                     log::info!("[Scheduler] Scheduler stopped");
@@ -78,53 +91,330 @@ async fn check_and_execute_scheduled_tasks(app: &AppHandle, state: &AppState) ->
     }
 
     for task in due_tasks {
+        // An occurrence stays due until its run finishes, so skip (and record once)
+        // rather than firing a second concurrent run of the same task
+        if let Some(reason) = skip_reason(state, &task).await {
+            log::info!("[Scheduler] Skipping scheduled task {} ({})", task.id, reason);
+            let state_clone = state.clone();
+            let tid = task.id.clone();
+            let scheduled_for = task.next_run_at.clone();
+            if let Err(e) = tokio::task::spawn_blocking(move || {
+                schedule_run_repo::record_schedule_skipped(&state_clone, &tid, scheduled_for.as_deref(), reason)
+            })
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))
+            .and_then(|r| r)
+            {
+                log::error!("[Scheduler] Failed to record skipped run: {:?}", e);
+            }
+            continue;
+        }
+
         log::info!(
             "[Scheduler] Executing scheduled task: {} ({})",
             task.title,
             task.id
         );
+        spawn_occurrence(app, state, task).await;
+    }
+
+    Ok(())
+}
+
+/// Why an occurrence of `task` cannot start now, if it cannot.
+async fn skip_reason(state: &AppState, task: &TaskRun) -> Option<&'static str> {
+    if state.shutdown_token.is_cancelled() {
+        Some("shutting_down")
+    } else if state.active_schedules.lock().await.contains(&task.id) || matches!(
+        task.status.as_str(),
+        "pending"
+            | "waiting_for_workspace"
+            | "analyzing"
+            | "running"
+            | "awaiting_confirmation"
+            | "awaiting_plan_approval"
+    ) {
+        Some("already_running")
+    } else {
+        None
+    }
+}
 
-        // Execute the task via the orchestrator
-        let app_clone = app.clone();
-        let state_clone = state.clone();
+/// Execute an occurrence via the orchestrator in the background; the
+/// schedule counts as running until it ends.
+async fn spawn_occurrence(app: &AppHandle, state: &AppState, task: TaskRun) {
+    let app_clone = app.clone();
+    let state_clone = state.clone();
+    state.active_schedules.lock().await.insert(task.id.clone());
+
+    tokio::spawn(async move {
         let task_id = task.id.clone();
+        execute_scheduled_task(&app_clone, &state_clone, task).await;
+        state_clone.active_schedules.lock().await.remove(&task_id);
+    });
+}
 
-        // Clone task data for the async block
-        let task_clone = task.clone();
+/// Fire an occurrence of a scheduled task now, paused or not. It is recorded
+/// without a `scheduled_for` and, like a due occurrence, advances a
+/// recurring schedule and ends a one-time one.
+pub async fn trigger_now(app: &AppHandle, state: &AppState, schedule_id: &str) -> AppResult<()> {
+    let state_clone = state.clone();
+    let id = schedule_id.to_string();
+    let mut task = tokio::task::spawn_blocking(move || task_run_repo::get_task_run(&state_clone, &id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    if task.schedule_type == "none" {
+        return Err(AppError::InvalidRequest(format!("Task run {} is not scheduled", schedule_id)));
+    }
+    if let Some(reason) = skip_reason(state, &task).await {
+        return Err(AppError::InvalidRequest(format!(
+            "Schedule {} cannot fire now: {}",
+            schedule_id,
+            reason.replace('_', " ")
+        )));
+    }
+    log::info!("[Scheduler] Triggering scheduled task {} ({}) by hand", task.title, task.id);
+    task.next_run_at = None;
+    spawn_occurrence(app, state, task).await;
+    Ok(())
+}
 
-        tokio::spawn(async move {
-            // Reset task status to pending before execution
-            {
-                let state = state_clone.clone();
-                let tid = task_id.clone();
-                if let Err(e) = tokio::task::spawn_blocking(move || {
-                    task_run_repo::update_task_run_status(&state, &tid, "pending")
+/// Scheduled tasks, optionally of one workspace, with their next fire time,
+/// latest occurrence and owning workspace.
+pub async fn list_scheduled_tasks(state: &AppState, workspace_id: Option<String>) -> AppResult<Vec<ScheduledTask>> {
+    let running: HashSet<String> = state.active_schedules.lock().await.clone();
+    let state = state.clone();
+    tokio::task::spawn_blocking(move || -> AppResult<Vec<ScheduledTask>> {
+        let workspaces: HashMap<String, String> =
+            workspace_repo::list_workspaces(&state)?.into_iter().map(|w| (w.id, w.name)).collect();
+        task_run_repo::list_scheduled_tasks(&state, workspace_id.as_deref())?
+            .into_iter()
+            .map(|task| {
+                let last_run = schedule_run_repo::list_schedule_history(&state, Some(&task.id), 1)?.pop();
+                Ok(ScheduledTask {
+                    is_running: running.contains(&task.id),
+                    workspace_name: task.workspace_id.as_ref().and_then(|id| workspaces.get(id).cloned()),
+                    recurrence_pattern: task.recurrence_pattern_json.as_deref().and_then(|json| serde_json::from_str(json).ok()),
+                    task_run_id: task.id,
+                    title: task.title,
+                    schedule_type: task.schedule_type,
+                    scheduled_time: task.scheduled_time,
+                    next_run_at: task.next_run_at,
+                    is_paused: task.is_paused,
+                    workspace_id: task.workspace_id,
+                    last_run,
                 })
-                .await
-                {
-                    log::error!("[Scheduler] Failed to reset task status: {:?}", e);
-                    return;
-                }
-            }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Watchdog: notify once about each run left waiting for confirmation or
+/// plan approval longer than the configured delay.
+async fn remind_waiting_runs(
+    app: &AppHandle,
+    state: &AppState,
+    reminded: &mut HashMap<String, String>,
+) -> AppResult<()> {
+    let reminder = config::current(state).stuck_run_reminder;
+    if !reminder.enabled || reminder.targets.is_empty() {
+        return Ok(());
+    }
 
-            // Run orchestration
-            let ws_id = task_clone.workspace_id.clone();
-            orchestrator::run_orchestration(app_clone, state_clone.clone(), task_id, task_clone.user_prompt, ws_id).await;
+    let state_clone = state.clone();
+    let waiting = tokio::task::spawn_blocking(move || {
+        task_run_repo::list_waiting_task_runs(&state_clone, reminder.after_minutes)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
 
-            // After completion, update next_run_at for recurring tasks
-            let state = state_clone.clone();
-            let tid = task_clone.id.clone();
-            if let Err(e) = tokio::task::spawn_blocking(move || {
-                task_run_repo::update_next_run_after_execution(&state, &tid)
-            })
-            .await
-            {
-                log::error!("[Scheduler] Failed to update next run time: {:?}", e);
+    // Forget runs that moved on, so a later wait is reminded again
+    reminded.retain(|id, since| waiting.iter().any(|r| &r.id == id && &r.updated_at == since));
+
+    for run in waiting {
+        if reminded.contains_key(&run.id) {
+            continue;
+        }
+        log::info!("[Scheduler] Task run {} has been {} for over {} minutes", run.id, run.status, reminder.after_minutes);
+        let locale = i18n::current(state);
+        let waiting_for = i18n::text(
+            locale,
+            if run.status == "awaiting_plan_approval" { Msg::WaitingForPlanApproval } else { Msg::WaitingForConfirmation },
+        );
+        let args = [("title", run.title.as_str()), ("waiting_for", waiting_for), ("since", run.updated_at.as_str())];
+        let notification = Notification {
+            kind: "run_waiting".to_string(),
+            title: i18n::tr(locale, Msg::RunWaitingTitle, &args),
+            body: i18n::tr(locale, Msg::RunWaitingBody, &args),
+            task_run_id: Some(run.id.clone()),
+            link: Some(notifications::task_run_link(&run.id)),
+        };
+        notifications::dispatch(app, state, &reminder.targets, &notification).await;
+        reminded.insert(run.id, run.updated_at);
+    }
+    Ok(())
+}
+
+/// Run one due occurrence of a scheduled task, retrying failed attempts with
+/// exponential backoff per the task's failure policy, then advance the schedule.
+async fn execute_scheduled_task(app: &AppHandle, state: &AppState, task: TaskRun) {
+    let policy = task.failure_policy();
+    let mut attempt: u32 = 1;
+
+    let final_status = loop {
+        let Some(status) = run_attempt(app, state, &task, attempt).await else {
+            return;
+        };
+        if status != "failed" || attempt > policy.max_retries || state.shutdown_token.is_cancelled() {
+            break status;
+        }
+
+        let delay = retry_delay(policy.backoff_secs, attempt);
+        log::info!(
+            "[Scheduler] Scheduled task {} failed (attempt {}), retrying in {}s",
+            task.id,
+            attempt,
+            delay.as_secs()
+        );
+        let _ = app.emit(
+            "schedule:retrying",
+            serde_json::json!({
+                "taskRunId": task.id,
+                "attempt": attempt,
+                "maxRetries": policy.max_retries,
+                "delaySecs": delay.as_secs(),
+            }),
+        );
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = state.shutdown_token.cancelled() => break status,
+        }
+        attempt += 1;
+    };
+
+    if final_status == "failed" {
+        let _ = app.emit(
+            "schedule:failed",
+            serde_json::json!({ "taskRunId": task.id, "attempts": attempt }),
+        );
+        if !policy.notify_on_failure.is_empty() {
+            let locale = i18n::current(state);
+            let attempts = attempt.to_string();
+            let args = [("title", task.title.as_str()), ("attempts", attempts.as_str())];
+            let notification = Notification {
+                kind: "schedule_failed".to_string(),
+                title: i18n::tr(locale, Msg::ScheduleFailedTitle, &args),
+                body: i18n::tr(locale, Msg::ScheduleFailedBody, &args),
+                task_run_id: Some(task.id.clone()),
+                link: Some(notifications::task_run_link(&task.id)),
+            };
+            notifications::dispatch(app, state, &policy.notify_on_failure, &notification).await;
+        }
+    }
+
+    // After completion, update next_run_at for recurring tasks
+    let state_clone = state.clone();
+    let tid = task.id.clone();
+    if let Err(e) = tokio::task::spawn_blocking(move || {
+        task_run_repo::update_next_run_after_execution(&state_clone, &tid)
+    })
+    .await
+    {
+        log::error!("[Scheduler] Failed to update next run time: {:?}", e);
+    }
+}
+
+/// Execute a single attempt and record it in the schedule history.
+/// Returns the task run's final status, or None if the run could not start.
+async fn run_attempt(app: &AppHandle, state: &AppState, task: &TaskRun, attempt: u32) -> Option<String> {
+    // Reset task status to pending before execution
+    {
+        let state = state.clone();
+        let tid = task.id.clone();
+        if let Err(e) = tokio::task::spawn_blocking(move || {
+            task_run_repo::update_task_run_status(&state, &tid, "pending")
+        })
+        .await
+        {
+            log::error!("[Scheduler] Failed to reset task status: {:?}", e);
+            return None;
+        }
+    }
+
+    // Record the fired occurrence
+    let schedule_run_id = {
+        let state = state.clone();
+        let tid = task.id.clone();
+        let scheduled_for = task.next_run_at.clone();
+        match tokio::task::spawn_blocking(move || {
+            schedule_run_repo::record_schedule_fired(&state, &tid, &tid, scheduled_for.as_deref(), attempt as i64)
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+        .and_then(|r| r)
+        {
+            Ok(id) => Some(id),
+            Err(e) => {
+                log::error!("[Scheduler] Failed to record schedule run: {}", e);
+                None
             }
-        });
+        }
+    };
+    activity::record(
+        app,
+        state,
+        task.workspace_id.as_deref(),
+        activity::SCHEDULE_FIRED,
+        Some(&task.id),
+        format!("Schedule fired for \"{}\" (attempt {})", task.title, attempt),
+    )
+    .await;
+
+    // Run orchestration once the intake queue starts it
+    run_queue::enqueue_and_wait(app, state, task, RunPriority::Scheduled, task.user_prompt.clone(), CancellationToken::new())
+        .await;
+
+    // Record the outcome before the schedule update rewrites the status
+    let state = state.clone();
+    let tid = task.id.clone();
+    let result = tokio::task::spawn_blocking(move || -> AppResult<String> {
+        let status = task_run_repo::get_task_run(&state, &tid)?.status;
+        let (outcome, error) = attempt_outcome(&status);
+        if let Some(schedule_run_id) = schedule_run_id {
+            schedule_run_repo::finish_schedule_run(&state, &schedule_run_id, outcome, error.as_deref())?;
+        }
+        Ok(outcome.to_string())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))
+    .and_then(|r| r);
+
+    match result {
+        Ok(outcome) => Some(outcome),
+        Err(e) => {
+            log::error!("[Scheduler] Failed to record schedule run outcome: {:?}", e);
+            None
+        }
     }
+}
 
-    Ok(())
+/// The recorded outcome of an attempt whose run ended in `status`, with the
+/// error to record. A run left for review is not a failure, so it is neither
+/// retried nor notified about.
+fn attempt_outcome(status: &str) -> (&str, Option<String>) {
+    match status {
+        "completed" | "failed" | "cancelled" | "needs_review" => (status, None),
+        other => ("failed", Some(format!("Run ended in status '{}'", other))),
+    }
+}
+
+/// Backoff before retry `attempt + 1`: the base delay doubled per prior retry, capped at an hour.
+fn retry_delay(backoff_secs: u64, attempt: u32) -> std::time::Duration {
+    let factor = 1u64 << (attempt.saturating_sub(1)).min(16);
+    std::time::Duration::from_secs(backoff_secs.saturating_mul(factor).min(3600))
 }
 
 /// Calculate the next run time for display purposes
@@ -138,3 +428,27 @@ pub fn calculate_next_run_display(
 ) -> Option<String> {
     task_run_repo::calculate_next_run(frequency, time_str, interval, days_of_week, day_of_month, month)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations;
+
+    #[test]
+    fn dismissed_runs_are_recorded_for_review_not_as_failures() {
+        assert_eq!(attempt_outcome("needs_review"), ("needs_review", None));
+        assert_eq!(attempt_outcome("completed"), ("completed", None));
+        let (outcome, error) = attempt_outcome("running");
+        assert_eq!(outcome, "failed");
+        assert!(error.is_some());
+
+        let conn = migrations::open_in_memory().unwrap();
+        conn.execute("INSERT INTO agents (id, name) VALUES ('hub', 'Hub')", []).unwrap();
+        let state = AppState::new(conn);
+        task_run_repo::create_task_run(&state, "run-1", "Title", "Prompt", "hub", "pending", None).unwrap();
+        let id = schedule_run_repo::record_schedule_fired(&state, "run-1", "run-1", None, 1).unwrap();
+        schedule_run_repo::finish_schedule_run(&state, &id, "needs_review", None).unwrap();
+        let history = schedule_run_repo::list_schedule_history(&state, Some("run-1"), 1).unwrap();
+        assert_eq!(history[0].outcome, "needs_review");
+    }
+}
@@ -0,0 +1,213 @@
+//! Graceful application shutdown
+//!
+//! When the app is asked to exit, the shutdown coordinator stops background
+//! work in a well-defined order before the process actually exits:
+//! 1. Stop the scheduler so no new runs are started
+//! 2. Cancel pipeline runs, then in-flight orchestrations, leaving the
+//!    orchestrations' status resumable
+//! 3. Stop chat tool bridges
+//! 4. Stop remaining agent processes
+//! 5. Checkpoint the SQLite WAL so all writes land in the main DB file
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter};
+
+use crate::acp::manager as acp_manager;
+use crate::acp::run_queue;
+use crate::chat_tool::manager as chat_manager;
+use crate::db::chat_tool_repo;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// How long to wait for cancelled orchestrations to wind down.
+const ORCHESTRATION_GRACE_SECS: u64 = 10;
+
+/// Set once an exit request has started the shutdown.
+static SHUTDOWN_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Set once shutdown has finished and the app may really exit.
+static SHUTDOWN_COMPLETE: AtomicBool = AtomicBool::new(false);
+
+/// Claim the shutdown for an exit request. False when an earlier request
+/// already started it, in which case the repeated request exits right away.
+pub fn begin() -> bool {
+    !SHUTDOWN_STARTED.swap(true, Ordering::SeqCst)
+}
+
+/// Whether the coordinator has finished and exit can proceed.
+pub fn is_complete() -> bool {
+    SHUTDOWN_COMPLETE.load(Ordering::SeqCst)
+}
+
+/// Run the shutdown sequence. Safe to call more than once; only the first
+/// call does any work.
+pub async fn shutdown(app: &AppHandle, state: &AppState) {
+    if state.shutdown_token.is_cancelled() {
+        return;
+    }
+    state.shutdown_token.cancel();
+
+    log::info!("[Shutdown] Starting graceful shutdown");
+    let _ = app.emit("app:shutting_down", serde_json::json!({}));
+
+    // 1. Stop the scheduler
+    {
+        let mut scheduler = state.scheduler.lock().await;
+        if let Some(s) = scheduler.as_mut() {
+            s.stop();
+        }
+    }
+
+    // 2. Cancel pipeline runs so no further stage starts, then orchestrations
+    cancel_pipeline_runs(state).await;
+    cancel_orchestrations(state).await;
+
+    // 3. Stop chat tool bridges
+    stop_bridges(state).await;
+
+    // 4. Stop any agent processes that are still alive
+    stop_agent_processes(state).await;
+
+    // 5. Flush pending DB writes
+    let state_clone = state.clone();
+    match tokio::task::spawn_blocking(move || checkpoint_db(&state_clone)).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => log::warn!("[Shutdown] Failed to checkpoint database: {}", e),
+        Err(e) => log::warn!("[Shutdown] Checkpoint task failed: {}", e),
+    }
+
+    // 6. Export spans still buffered
+    let _ = tokio::task::spawn_blocking(crate::telemetry::shutdown).await;
+
+    SHUTDOWN_COMPLETE.store(true, Ordering::SeqCst);
+    log::info!("[Shutdown] Graceful shutdown complete");
+}
+
+/// Cancel running pipelines. Pipeline runs are not resumed, so they end as
+/// cancelled; their current stage is cancelled through its child token and
+/// left resumable like any other orchestration.
+async fn cancel_pipeline_runs(state: &AppState) {
+    let runs = state.active_pipeline_runs.lock().await;
+    if runs.is_empty() {
+        return;
+    }
+    log::info!("[Shutdown] Cancelling {} pipeline run(s)", runs.len());
+    for token in runs.values() {
+        token.cancel();
+    }
+}
+
+/// Cancel running orchestrations without marking them cancelled, so that
+/// startup recovery picks them up again on next launch. Completed
+/// assignments are already persisted; unfinished ones are re-run on resume.
+async fn cancel_orchestrations(state: &AppState) {
+    let queued = run_queue::clear(state).await;
+    if queued > 0 {
+        log::info!("[Shutdown] Left {} queued task run(s) pending for the next launch", queued);
+    }
+    {
+        let agent_cancels = state.agent_cancellations.lock().await;
+        for token in agent_cancels.values() {
+            token.cancel();
+        }
+    }
+    let count = {
+        let tokens = state.active_task_runs.lock().await;
+        for token in tokens.values() {
+            token.cancel();
+        }
+        tokens.len()
+    };
+    let pipelines = state.active_pipeline_runs.lock().await.len();
+    if count == 0 && pipelines == 0 {
+        return;
+    }
+    log::info!("[Shutdown] Cancelling {} in-flight orchestration(s)", count);
+
+    // Runs waiting for confirmation stay blocked on their channel; dropping it
+    // would auto-confirm, so they are left as-is and resumed later.
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(ORCHESTRATION_GRACE_SECS);
+    loop {
+        let remaining = {
+            let tokens = state.active_task_runs.lock().await;
+            let confirmations = state.pending_confirmations.lock().await;
+            let pipelines = state.active_pipeline_runs.lock().await.len();
+            tokens
+                .keys()
+                .filter(|id| !confirmations.contains_key(*id))
+                .count()
+                + pipelines
+        };
+        if remaining == 0 {
+            break;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            log::warn!(
+                "[Shutdown] {} orchestration(s) did not stop within {}s",
+                remaining,
+                ORCHESTRATION_GRACE_SECS
+            );
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+async fn stop_bridges(state: &AppState) {
+    {
+        let mut cancellations = state.chat_tool_cancellations.lock().await;
+        for (_, token) in cancellations.drain() {
+            token.cancel();
+        }
+    }
+
+    let stopped: Vec<String> = {
+        let mut processes = state.chat_tool_processes.lock().await;
+        let mut ids = Vec::new();
+        for (id, mut process) in processes.drain() {
+            if let Err(e) = chat_manager::stop_bridge_process(&mut process).await {
+                log::warn!("[Shutdown] Failed to stop bridge {}: {}", id, e);
+            }
+            ids.push(id);
+        }
+        ids
+    };
+    if stopped.is_empty() {
+        return;
+    }
+    log::info!("[Shutdown] Stopped {} chat tool bridge(s)", stopped.len());
+
+    let state_clone = state.clone();
+    let _ = tokio::task::spawn_blocking(move || {
+        for id in &stopped {
+            let _ = chat_tool_repo::update_chat_tool_status(&state_clone, id, "stopped", None);
+        }
+    })
+    .await;
+}
+
+async fn stop_agent_processes(state: &AppState) {
+    let mut processes = state.agent_processes.lock().await;
+    let count = processes.len();
+    for (id, mut process) in processes.drain() {
+        if let Err(e) = acp_manager::stop_agent_process(&mut process).await {
+            log::warn!("[Shutdown] Failed to stop agent process {}: {}", id, e);
+        }
+    }
+    if count > 0 {
+        log::info!("[Shutdown] Stopped {} agent process(es)", count);
+    }
+
+    let mut stdins = state.agent_stdins.lock().await;
+    stdins.clear();
+}
+
+fn checkpoint_db(state: &AppState) -> AppResult<()> {
+    state.db_writes.flush();
+    let db = state.db.lock().map_err(|e| AppError::Database(e.to_string()))?;
+    db.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
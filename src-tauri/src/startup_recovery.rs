@@ -0,0 +1,181 @@
+//! Runs left unfinished by the previous session.
+//!
+//! On launch, runs that were queued, planning, running or waiting for the
+//! user are announced with `orchestration:recoverable`, then handled per the
+//! `startup_recovery` setting: `auto_resume` resumes them all, `prompt`
+//! holds them until the user resumes or fails each one, and `mark_failed`
+//! fails them all, so no tokens are spent before the user looks.
+
+use crate::acp::orchestrator;
+use crate::config;
+use crate::db::task_run_repo;
+use crate::error::{AppError, AppResult};
+use crate::events;
+use crate::models::bulk::BulkItemResult;
+use crate::models::task_run::TaskRun;
+use crate::state::AppState;
+use crate::telemetry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryPolicy {
+    AutoResume,
+    Prompt,
+    MarkFailed,
+}
+
+impl RecoveryPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto_resume" => Some(Self::AutoResume),
+            "prompt" => Some(Self::Prompt),
+            "mark_failed" => Some(Self::MarkFailed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::AutoResume => "auto_resume",
+            Self::Prompt => "prompt",
+            Self::MarkFailed => "mark_failed",
+        }
+    }
+}
+
+fn payload(runs: &[TaskRun], policy: RecoveryPolicy) -> serde_json::Value {
+    let runs: Vec<_> = runs
+        .iter()
+        .map(|run| {
+            serde_json::json!({
+                "taskRunId": run.id,
+                "title": run.title,
+                "status": run.status,
+                "workspaceId": run.workspace_id,
+                "updatedAt": run.updated_at,
+            })
+        })
+        .collect();
+    serde_json::json!({ "runs": runs, "policy": policy.as_str() })
+}
+
+async fn mark_failed(state: &AppState, task_run_id: &str) -> AppResult<()> {
+    let state = state.clone();
+    let id = task_run_id.to_string();
+    telemetry::spawn_blocking(move || task_run_repo::update_task_run_status(&state, &id, "failed"))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Announce the unfinished runs and handle them per the setting. Called
+/// once during app startup.
+pub async fn recover(app: tauri::AppHandle, state: AppState) {
+    let runs = {
+        let state_clone = state.clone();
+        match telemetry::spawn_blocking(move || task_run_repo::list_incomplete_task_runs(&state_clone)).await {
+            Ok(Ok(runs)) => runs,
+            Ok(Err(e)) => {
+                log::error!("Failed to query incomplete task runs on startup: {}", e);
+                return;
+            }
+            Err(e) => {
+                log::error!("Spawn blocking failed for incomplete task query: {}", e);
+                return;
+            }
+        }
+    };
+    if runs.is_empty() {
+        log::info!("No incomplete orchestration tasks to recover on startup");
+        return;
+    }
+
+    let policy = RecoveryPolicy::parse(&config::current(&state).startup_recovery).unwrap_or(RecoveryPolicy::AutoResume);
+    log::info!("Found {} incomplete orchestration task(s) on startup ({})", runs.len(), policy.as_str());
+    events::emit(&app, &events::RUNS_RECOVERABLE, payload(&runs, policy));
+
+    match policy {
+        RecoveryPolicy::AutoResume => {
+            for run in runs {
+                orchestrator::resume_task_run(&app, &state, run).await;
+            }
+        }
+        RecoveryPolicy::Prompt => {
+            state.recoverable_runs.lock().await.extend(runs.into_iter().map(|run| run.id));
+        }
+        RecoveryPolicy::MarkFailed => {
+            for run in runs {
+                if let Err(e) = mark_failed(&state, &run.id).await {
+                    log::error!("Failed to mark unfinished run {} as failed: {}", run.id, e);
+                }
+            }
+        }
+    }
+}
+
+/// Runs held for the user to resume or fail.
+pub async fn list(state: &AppState) -> AppResult<Vec<TaskRun>> {
+    let ids: Vec<String> = state.recoverable_runs.lock().await.iter().cloned().collect();
+    let state = state.clone();
+    let mut runs = telemetry::spawn_blocking(move || -> AppResult<Vec<TaskRun>> {
+        ids.iter().map(|id| task_run_repo::get_task_run(&state, id)).collect()
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    runs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(runs)
+}
+
+/// Resume the held runs, or with `resume` false mark them as failed.
+pub async fn decide(app: &tauri::AppHandle, state: &AppState, task_run_ids: &[String], resume: bool) -> Vec<BulkItemResult> {
+    let mut items = Vec::new();
+    for id in task_run_ids {
+        if !state.recoverable_runs.lock().await.remove(id) {
+            items.push(BulkItemResult::failed(id, "Run is not waiting to be recovered"));
+            continue;
+        }
+        let result = if resume {
+            let state_clone = state.clone();
+            let trid = id.clone();
+            match telemetry::spawn_blocking(move || task_run_repo::get_task_run(&state_clone, &trid)).await {
+                Ok(Ok(run)) => {
+                    orchestrator::resume_task_run(app, state, run).await;
+                    Ok(())
+                }
+                Ok(Err(e)) => Err(e),
+                Err(e) => Err(AppError::Internal(e.to_string())),
+            }
+        } else {
+            mark_failed(state, id).await
+        };
+        items.push(match result {
+            Ok(()) => BulkItemResult::ok(id),
+            Err(e) => BulkItemResult::failed(id, e),
+        });
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies_round_trip_and_announce_runs() {
+        for policy in [RecoveryPolicy::AutoResume, RecoveryPolicy::Prompt, RecoveryPolicy::MarkFailed] {
+            assert_eq!(RecoveryPolicy::parse(policy.as_str()), Some(policy));
+        }
+        assert_eq!(RecoveryPolicy::parse("resume"), None);
+
+        let run: TaskRun = serde_json::from_value(serde_json::json!({
+            "id": "run-1", "title": "Nightly report", "user_prompt": "", "control_hub_agent_id": "hub",
+            "status": "running", "task_plan_json": null, "result_summary": null, "total_tokens_in": 0,
+            "total_tokens_out": 0, "total_cache_creation_tokens": 0, "total_cache_read_tokens": 0,
+            "total_duration_ms": 0, "created_at": "", "updated_at": "2026-10-16 22:00:00", "workspace_id": "ws-1",
+        }))
+        .unwrap();
+        let payload = payload(&[run], RecoveryPolicy::Prompt);
+        assert_eq!(payload["policy"], "prompt");
+        assert_eq!(payload["runs"][0]["taskRunId"], "run-1");
+        assert_eq!(payload["runs"][0]["status"], "running");
+        assert_eq!(payload["runs"][0]["workspaceId"], "ws-1");
+    }
+}
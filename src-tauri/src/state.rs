@@ -8,7 +8,9 @@ use tokio_util::sync::CancellationToken;
 
 use serde::{Deserialize, Serialize};
 
+use crate::acp::agent_terminal::{TerminalEvents, TerminalRegistry};
 use crate::acp::manager::AgentProcess;
+use crate::acp::permissions::OrchPermissions;
 use crate::chat_tool::manager::ChatToolProcess;
 use crate::scheduler::SchedulerState;
 
@@ -21,11 +23,20 @@ pub enum ConfirmationAction {
     RegenerateAgent(String),
     /// Re-run all agents
     RegenerateAll,
+    /// Stop without summarizing and leave the results for review
+    Dismiss,
 }
 
 /// Key for a pending orchestration permission request: (task_run_id, request_id)
 pub type OrchPermissionKey = (String, String);
 
+/// An agent file write held until the user approves or rejects it
+pub struct PendingFileWrite {
+    pub review: crate::models::task_run::FileWriteReview,
+    /// Receives true to apply the write, false to reject it
+    pub tx: tokio::sync::oneshot::Sender<bool>,
+}
+
 /// ACP session state following the ACP protocol specification.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum AcpSessionState {
@@ -71,6 +82,9 @@ pub struct AcpSessionInfo {
     pub created_at: String,
     /// Timestamp when session was last used
     pub last_used_at: String,
+    /// Model an assignment switched the session to, until switched back
+    #[serde(default)]
+    pub model_override: Option<String>,
 }
 
 impl AcpSessionInfo {
@@ -84,6 +98,7 @@ impl AcpSessionInfo {
             state: AcpSessionState::New,
             created_at: now.clone(),
             last_used_at: now,
+            model_override: None,
         }
     }
 
@@ -112,7 +127,9 @@ impl AcpSessionInfo {
 
 pub struct AppState {
     /// SQLite database connection
-    pub db: Arc<std::sync::Mutex<Connection>>,
+    pub db: Arc<crate::db::connection::Db>,
+    /// Batched writes of high-frequency updates
+    pub db_writes: Arc<crate::db::write_queue::WriteQueue>,
     /// Running agent processes keyed by agent ID
     pub agent_processes: Arc<Mutex<HashMap<String, AgentProcess>>>,
     /// Agent stdin handles for sending responses (keyed by agent ID)
@@ -123,16 +140,25 @@ pub struct AppState {
     pub discovered_agents: Arc<Mutex<Vec<crate::models::agent::DiscoveredAgent>>>,
     /// Active orchestration task runs with cancellation tokens
     pub active_task_runs: Arc<Mutex<HashMap<String, CancellationToken>>>,
+    /// Task runs waiting for a free slot
+    pub run_queue: Arc<Mutex<crate::acp::run_queue::RunQueue>>,
+    /// Prompts of recently started runs, for catching duplicate starts
+    pub recent_run_starts: Arc<Mutex<crate::acp::duplicate_runs::RecentStarts>>,
+    /// Last payload per event topic and subject, for deduplication and coalescing
+    pub event_gate: Arc<std::sync::Mutex<crate::events::EventGate>>,
     /// Per-agent cancellation tokens: (task_run_id, agent_id) -> CancellationToken
     pub agent_cancellations: Arc<Mutex<HashMap<(String, String), CancellationToken>>>,
     /// Pending confirmation channels: task_run_id -> oneshot sender
     pub pending_confirmations: Arc<Mutex<HashMap<String, tokio::sync::oneshot::Sender<ConfirmationAction>>>>,
-    /// Pending orchestration permission channels: (task_run_id, request_id) -> oneshot sender(option_id)
-    pub pending_orch_permissions: Arc<Mutex<HashMap<OrchPermissionKey, tokio::sync::oneshot::Sender<String>>>>,
+    /// Task runs whose plan waits for approval (task_run_id -> approved)
+    pub pending_plan_approvals: Arc<Mutex<HashMap<String, tokio::sync::oneshot::Sender<bool>>>>,
+    /// Orchestration permission requests waiting for the user, batched, and
+    /// the decisions remembered for the rest of a run
+    pub orch_permissions: Arc<Mutex<OrchPermissions>>,
     /// Scheduler state for background task execution
     pub scheduler: Arc<Mutex<Option<SchedulerState>>>,
-    /// Cached skill discovery result from workspace scanning
-    pub discovered_skills: Arc<Mutex<Option<crate::acp::skill_discovery::SkillDiscoveryResult>>>,
+    /// Skill discovery scans per workspace
+    pub discovered_skills: Arc<Mutex<crate::acp::skill_cache::SkillCache>>,
     /// Running chat tool bridge processes keyed by chat_tool_id
     pub chat_tool_processes: Arc<Mutex<HashMap<String, ChatToolProcess>>>,
     /// Cancellation tokens for chat tool bridge event loops
@@ -145,12 +171,61 @@ pub struct AppState {
     pub chat_tool_task_runs: Arc<Mutex<HashMap<String, String>>>,
     /// Set of chat_tool_ids currently processing a message (used for busy-reply)
     pub chat_tool_processing: Arc<Mutex<HashSet<String>>>,
+    /// Per Control Hub prompt locks (agent_id -> lock), so chat tools sharing a
+    /// hub do not read each other's responses off its message channel
+    pub chat_tool_hub_locks: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+    /// Per agent prompt locks of chat sessions (agent_id -> lock), held until
+    /// a reply or a session summary is read off the agent's message channel
+    pub chat_agent_locks: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+    /// Controlling terminals of running agent processes by terminal id
+    pub agent_terminals: Arc<TerminalRegistry>,
+    /// Agent terminal output and prompts, forwarded to the frontend
+    pub terminal_events: TerminalEvents,
+    /// When each agent type last logged in; held while a login runs so only
+    /// one runs at a time
+    pub agent_logins: Arc<Mutex<HashMap<String, std::time::Instant>>>,
+    /// Held while a sync round runs so rounds never overlap
+    pub sync_lock: Arc<Mutex<()>>,
+    /// Cancelled when the app begins shutting down
+    pub shutdown_token: CancellationToken,
+    /// Active pipeline runs with cancellation tokens (pipeline_run_id -> token)
+    pub active_pipeline_runs: Arc<Mutex<HashMap<String, CancellationToken>>>,
+    /// Pending pipeline stage approvals: pipeline_run_id -> oneshot sender(approved)
+    pub pending_pipeline_approvals: Arc<Mutex<HashMap<String, tokio::sync::oneshot::Sender<bool>>>>,
+    /// Scheduled task_run_ids whose occurrence is executing or waiting to retry
+    pub active_schedules: Arc<Mutex<HashSet<String>>>,
+    /// Runs left unfinished by the previous session, held for the user to resume or fail
+    pub recoverable_runs: Arc<Mutex<HashSet<String>>>,
+    /// Agent file writes awaiting review: write_id -> pending write
+    pub pending_file_writes: Arc<Mutex<HashMap<String, PendingFileWrite>>>,
+    /// In-flight agent writes: absolute path -> writers, in write order
+    pub file_writers: Arc<Mutex<HashMap<String, Vec<crate::acp::file_conflicts::FileWriter>>>>,
+    /// Files that assignments stream their text into: assignment_id -> path
+    pub output_streams: Arc<Mutex<HashMap<String, std::path::PathBuf>>>,
+    /// When active runs reach their wall-clock limit: task_run_id -> deadline
+    pub run_deadlines: Arc<Mutex<HashMap<String, std::time::Instant>>>,
+    /// Token and cost caps of running assignments: (task_run_id, agent_id) -> caps
+    pub assignment_caps: Arc<Mutex<HashMap<(String, String), crate::acp::assignment_caps::AssignmentCaps>>>,
+    /// Agent settings overridden for running assignments, by (task_run_id, agent_id)
+    pub assignment_overrides: Arc<Mutex<HashMap<(String, String), crate::models::task_run::AssignmentOverrides>>>,
+    /// Files attached to running assignments' prompts, by (task_run_id, agent_id)
+    pub assignment_attachments: Arc<Mutex<HashMap<(String, String), crate::acp::prompt_content::Attachments>>>,
+    /// Assignment each agent of a run is working on: (task_run_id, agent_id) -> assignment_id
+    pub running_assignments: Arc<Mutex<HashMap<(String, String), String>>>,
+    /// Warm-up output awaiting an agent's first assignment: orchestration process key -> output
+    pub agent_warmups: Arc<Mutex<HashMap<String, String>>>,
+    /// Run summaries being generated: task_run_id -> text so far and the user's stop
+    pub summary_streams: Arc<Mutex<HashMap<String, crate::acp::summary_stream::SummaryStream>>>,
+    /// Current typed settings; updated through `config::update`
+    pub config: Arc<tokio::sync::watch::Sender<crate::config::AppConfig>>,
 }
 
 impl AppState {
     pub fn new(conn: Connection) -> Self {
-        Self {
-            db: Arc::new(std::sync::Mutex::new(conn)),
+        let db = Arc::new(crate::db::connection::Db::new(conn));
+        let state = Self {
+            db_writes: Arc::new(crate::db::write_queue::WriteQueue::start(db.clone())),
+            db,
             agent_processes: Arc::new(Mutex::new(HashMap::new())),
             agent_stdins: Arc::new(Mutex::new(HashMap::new())),
             acp_sessions: Arc::new(Mutex::new(HashMap::new())),
@@ -158,16 +233,44 @@ impl AppState {
             active_task_runs: Arc::new(Mutex::new(HashMap::new())),
             agent_cancellations: Arc::new(Mutex::new(HashMap::new())),
             pending_confirmations: Arc::new(Mutex::new(HashMap::new())),
-            pending_orch_permissions: Arc::new(Mutex::new(HashMap::new())),
+            pending_plan_approvals: Arc::new(Mutex::new(HashMap::new())),
+            orch_permissions: Arc::new(Mutex::new(OrchPermissions::default())),
             scheduler: Arc::new(Mutex::new(None)),
-            discovered_skills: Arc::new(Mutex::new(None)),
+            discovered_skills: Arc::new(Mutex::new(Default::default())),
             chat_tool_processes: Arc::new(Mutex::new(HashMap::new())),
             chat_tool_cancellations: Arc::new(Mutex::new(HashMap::new())),
             chat_tool_qr_codes: Arc::new(Mutex::new(HashMap::new())),
             chat_tool_acp_sessions: Arc::new(Mutex::new(HashMap::new())),
             chat_tool_task_runs: Arc::new(Mutex::new(HashMap::new())),
             chat_tool_processing: Arc::new(Mutex::new(HashSet::new())),
-        }
+            chat_tool_hub_locks: Arc::new(Mutex::new(HashMap::new())),
+            chat_agent_locks: Arc::new(Mutex::new(HashMap::new())),
+            agent_terminals: Arc::new(TerminalRegistry::default()),
+            terminal_events: tokio::sync::broadcast::channel(256).0,
+            agent_logins: Arc::new(Mutex::new(HashMap::new())),
+            sync_lock: Arc::new(Mutex::new(())),
+            run_queue: Arc::new(Mutex::new(Default::default())),
+            recent_run_starts: Arc::new(Mutex::new(Default::default())),
+            event_gate: Arc::new(std::sync::Mutex::new(Default::default())),
+            shutdown_token: CancellationToken::new(),
+            active_pipeline_runs: Arc::new(Mutex::new(HashMap::new())),
+            pending_pipeline_approvals: Arc::new(Mutex::new(HashMap::new())),
+            active_schedules: Arc::new(Mutex::new(HashSet::new())),
+            recoverable_runs: Arc::new(Mutex::new(HashSet::new())),
+            pending_file_writes: Arc::new(Mutex::new(HashMap::new())),
+            file_writers: Arc::new(Mutex::new(HashMap::new())),
+            output_streams: Arc::new(Mutex::new(HashMap::new())),
+            run_deadlines: Arc::new(Mutex::new(HashMap::new())),
+            assignment_caps: Arc::new(Mutex::new(HashMap::new())),
+            assignment_overrides: Arc::new(Mutex::new(HashMap::new())),
+            assignment_attachments: Arc::new(Mutex::new(HashMap::new())),
+            running_assignments: Arc::new(Mutex::new(HashMap::new())),
+            agent_warmups: Arc::new(Mutex::new(HashMap::new())),
+            summary_streams: Arc::new(Mutex::new(HashMap::new())),
+            config: Arc::new(tokio::sync::watch::channel(crate::config::AppConfig::default()).0),
+        };
+        crate::config::init(&state);
+        state
     }
 }
 
@@ -176,6 +279,7 @@ impl Clone for AppState {
     fn clone(&self) -> Self {
         Self {
             db: Arc::clone(&self.db),
+            db_writes: Arc::clone(&self.db_writes),
             agent_processes: Arc::clone(&self.agent_processes),
             agent_stdins: Arc::clone(&self.agent_stdins),
             acp_sessions: Arc::clone(&self.acp_sessions),
@@ -183,7 +287,8 @@ impl Clone for AppState {
             active_task_runs: Arc::clone(&self.active_task_runs),
             agent_cancellations: Arc::clone(&self.agent_cancellations),
             pending_confirmations: Arc::clone(&self.pending_confirmations),
-            pending_orch_permissions: Arc::clone(&self.pending_orch_permissions),
+            pending_plan_approvals: Arc::clone(&self.pending_plan_approvals),
+            orch_permissions: Arc::clone(&self.orch_permissions),
             scheduler: Arc::clone(&self.scheduler),
             discovered_skills: Arc::clone(&self.discovered_skills),
             chat_tool_processes: Arc::clone(&self.chat_tool_processes),
@@ -192,6 +297,31 @@ impl Clone for AppState {
             chat_tool_acp_sessions: Arc::clone(&self.chat_tool_acp_sessions),
             chat_tool_task_runs: Arc::clone(&self.chat_tool_task_runs),
             chat_tool_processing: Arc::clone(&self.chat_tool_processing),
+            chat_tool_hub_locks: Arc::clone(&self.chat_tool_hub_locks),
+            chat_agent_locks: Arc::clone(&self.chat_agent_locks),
+            agent_terminals: Arc::clone(&self.agent_terminals),
+            terminal_events: self.terminal_events.clone(),
+            agent_logins: Arc::clone(&self.agent_logins),
+            sync_lock: Arc::clone(&self.sync_lock),
+            run_queue: Arc::clone(&self.run_queue),
+            recent_run_starts: Arc::clone(&self.recent_run_starts),
+            event_gate: Arc::clone(&self.event_gate),
+            shutdown_token: self.shutdown_token.clone(),
+            active_pipeline_runs: Arc::clone(&self.active_pipeline_runs),
+            pending_pipeline_approvals: Arc::clone(&self.pending_pipeline_approvals),
+            active_schedules: Arc::clone(&self.active_schedules),
+            recoverable_runs: Arc::clone(&self.recoverable_runs),
+            pending_file_writes: Arc::clone(&self.pending_file_writes),
+            file_writers: Arc::clone(&self.file_writers),
+            output_streams: Arc::clone(&self.output_streams),
+            run_deadlines: Arc::clone(&self.run_deadlines),
+            assignment_caps: Arc::clone(&self.assignment_caps),
+            assignment_overrides: Arc::clone(&self.assignment_overrides),
+            assignment_attachments: Arc::clone(&self.assignment_attachments),
+            running_assignments: Arc::clone(&self.running_assignments),
+            agent_warmups: Arc::clone(&self.agent_warmups),
+            summary_streams: Arc::clone(&self.summary_streams),
+            config: Arc::clone(&self.config),
         }
     }
 }
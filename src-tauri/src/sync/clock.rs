@@ -0,0 +1,75 @@
+//! Vector clock comparison and merging for replicated records.
+
+use crate::models::sync::VectorClock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockOrder {
+    Equal,
+    /// The first clock happened before the second
+    Before,
+    /// The first clock happened after the second
+    After,
+    /// Neither clock includes the other's edits
+    Concurrent,
+}
+
+pub fn compare(a: &VectorClock, b: &VectorClock) -> ClockOrder {
+    let mut a_ahead = false;
+    let mut b_ahead = false;
+    for device in a.keys().chain(b.keys()) {
+        let x = a.get(device).copied().unwrap_or(0);
+        let y = b.get(device).copied().unwrap_or(0);
+        a_ahead |= x > y;
+        b_ahead |= y > x;
+    }
+    match (a_ahead, b_ahead) {
+        (false, false) => ClockOrder::Equal,
+        (true, false) => ClockOrder::After,
+        (false, true) => ClockOrder::Before,
+        (true, true) => ClockOrder::Concurrent,
+    }
+}
+
+/// The element-wise maximum of two clocks.
+pub fn merge(a: &VectorClock, b: &VectorClock) -> VectorClock {
+    let mut merged = a.clone();
+    for (device, &counter) in b {
+        let entry = merged.entry(device.clone()).or_insert(0);
+        *entry = (*entry).max(counter);
+    }
+    merged
+}
+
+/// Record a local edit by `device_id`.
+pub fn increment(clock: &mut VectorClock, device_id: &str) {
+    *clock.entry(device_id.to_string()).or_insert(0) += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(entries: &[(&str, u64)]) -> VectorClock {
+        entries.iter().map(|(d, c)| (d.to_string(), *c)).collect()
+    }
+
+    #[test]
+    fn compare_orders_clocks() {
+        let a = clock(&[("a", 2), ("b", 1)]);
+        assert_eq!(compare(&a, &a), ClockOrder::Equal);
+        assert_eq!(compare(&a, &clock(&[("a", 1), ("b", 1)])), ClockOrder::After);
+        assert_eq!(compare(&clock(&[("a", 1)]), &a), ClockOrder::Before);
+        assert_eq!(compare(&a, &clock(&[("a", 1), ("b", 2)])), ClockOrder::Concurrent);
+        assert_eq!(compare(&VectorClock::new(), &clock(&[("a", 0)])), ClockOrder::Equal);
+    }
+
+    #[test]
+    fn merge_takes_maximum_and_dominates_both() {
+        let a = clock(&[("a", 2), ("b", 1)]);
+        let b = clock(&[("b", 3), ("c", 1)]);
+        let merged = merge(&a, &b);
+        assert_eq!(merged, clock(&[("a", 2), ("b", 3), ("c", 1)]));
+        assert_ne!(compare(&merged, &a), ClockOrder::Before);
+        assert_ne!(compare(&merged, &b), ClockOrder::Before);
+    }
+}
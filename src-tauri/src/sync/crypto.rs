@@ -0,0 +1,127 @@
+//! Envelope encryption for sync payloads. The key is derived from the user's
+//! passphrase with PBKDF2 and a per-envelope salt; payloads are sealed with
+//! ChaCha20-Poly1305, authenticating the sender's device id as well.
+
+use std::num::NonZeroU32;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{aead, pbkdf2};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+pub const ENVELOPE_VERSION: u32 = 1;
+const PBKDF2_ITERATIONS: u32 = 100_000;
+const SALT_LEN: usize = 16;
+
+/// What a device publishes: everything except `device_id` is opaque without the passphrase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub version: u32,
+    pub device_id: String,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+pub fn seal(passphrase: &str, device_id: &str, plaintext: &[u8]) -> AppResult<Envelope> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; aead::NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| AppError::Internal("Failed to generate random bytes".into()))?;
+
+    let key = derive_key(passphrase, &salt)?;
+    let mut in_out = plaintext.to_vec();
+    key.seal_in_place_append_tag(
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::from(device_id.as_bytes()),
+        &mut in_out,
+    )
+    .map_err(|_| AppError::Internal("Failed to encrypt sync payload".into()))?;
+
+    Ok(Envelope {
+        version: ENVELOPE_VERSION,
+        device_id: device_id.to_string(),
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(in_out),
+    })
+}
+
+pub fn open(passphrase: &str, envelope: &Envelope) -> AppResult<Vec<u8>> {
+    if envelope.version != ENVELOPE_VERSION {
+        return Err(AppError::InvalidRequest(format!(
+            "Unsupported sync envelope version {} from device {}",
+            envelope.version, envelope.device_id
+        )));
+    }
+    let decode = |field: &str| {
+        BASE64
+            .decode(field)
+            .map_err(|e| AppError::InvalidRequest(format!("Malformed sync envelope: {}", e)))
+    };
+    let salt = decode(&envelope.salt)?;
+    let nonce: [u8; aead::NONCE_LEN] = decode(&envelope.nonce)?
+        .try_into()
+        .map_err(|_| AppError::InvalidRequest("Malformed sync envelope nonce".into()))?;
+    let mut in_out = decode(&envelope.ciphertext)?;
+
+    let key = derive_key(passphrase, &salt)?;
+    let plaintext = key
+        .open_in_place(
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::from(envelope.device_id.as_bytes()),
+            &mut in_out,
+        )
+        .map_err(|_| {
+            AppError::InvalidRequest(format!(
+                "Cannot decrypt sync data from device {} (wrong passphrase?)",
+                envelope.device_id
+            ))
+        })?;
+    Ok(plaintext.to_vec())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> AppResult<aead::LessSafeKey> {
+    let mut key_bytes = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).expect("iterations are non-zero"),
+        salt,
+        passphrase.as_bytes(),
+        &mut key_bytes,
+    );
+    let unbound = aead::UnboundKey::new(&aead::CHACHA20_POLY1305, &key_bytes)
+        .map_err(|_| AppError::Internal("Failed to create sync key".into()))?;
+    Ok(aead::LessSafeKey::new(unbound))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_then_open_round_trips() {
+        let envelope = seal("correct horse", "device-a", b"{\"records\":[]}").unwrap();
+        assert_eq!(envelope.device_id, "device-a");
+        assert_eq!(open("correct horse", &envelope).unwrap(), b"{\"records\":[]}");
+    }
+
+    #[test]
+    fn wrong_passphrase_is_rejected() {
+        let envelope = seal("correct horse", "device-a", b"secret").unwrap();
+        let err = open("battery staple", &envelope).unwrap_err();
+        assert!(err.to_string().contains("wrong passphrase"));
+    }
+
+    #[test]
+    fn changed_device_id_fails_authentication() {
+        let mut envelope = seal("correct horse", "device-a", b"secret").unwrap();
+        envelope.device_id = "device-b".into();
+        assert!(open("correct horse", &envelope).is_err());
+    }
+}
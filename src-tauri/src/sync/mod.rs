@@ -0,0 +1,486 @@
+//! Optional end-to-end encrypted sync of agents, workspaces, templates and
+//! schedules between agent-hub instances.
+//!
+//! Each device publishes one encrypted envelope with the full state of every
+//! replicated record and reads the envelopes of the other devices. Records
+//! carry a vector clock; a remote version replaces the local one when its clock
+//! is newer, and concurrent edits are resolved the same way on every device
+//! (latest edit wins, device id breaks ties). Device-local and secret columns
+//! (process state, ACP commands, file paths, settings, chat tools) are never
+//! replicated. The passphrase is kept in the OS keychain, not in the database.
+
+pub mod clock;
+pub mod crypto;
+pub mod store;
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::{settings_repo, sync_repo};
+use crate::error::{AppError, AppResult};
+use crate::models::sync::{SyncConfig, SyncRecordState, SyncReport, VectorClock};
+use crate::state::AppState;
+use clock::ClockOrder;
+
+pub const SYNC_CONFIG_SETTING: &str = "sync_config";
+pub const SYNC_DEVICE_ID_SETTING: &str = "sync_device_id";
+pub const SYNC_LAST_SYNCED_SETTING: &str = "sync_last_synced_at";
+
+const KEYCHAIN_SERVICE: &str = "agent-hub";
+const KEYCHAIN_PASSPHRASE_USER: &str = "sync-passphrase";
+
+/// A table replicated by sync. The first column is the primary key.
+struct SyncedTable {
+    entity: &'static str,
+    table: &'static str,
+    columns: &'static [&'static str],
+    /// Rows of `table` that belong to this entity
+    filter: &'static str,
+    /// Device-local columns set when a replicated row is first inserted
+    insert_defaults: &'static [(&'static str, &'static str)],
+    /// Applies a replicated deletion; ?1 is the id
+    delete_sql: &'static str,
+}
+
+/// In dependency order, so referenced rows are applied first.
+const SYNCED_TABLES: [SyncedTable; 4] = [
+    SyncedTable {
+        entity: "workspace",
+        table: "workspaces",
+        columns: &["id", "name", "icon", "hub_persona", "redact_outputs", "exclusive_runs"],
+        filter: "1 = 1",
+        insert_defaults: &[],
+        delete_sql: "DELETE FROM workspaces WHERE id = ?1",
+    },
+    SyncedTable {
+        entity: "agent",
+        table: "agents",
+        columns: &[
+            "id",
+            "name",
+            "icon",
+            "description",
+            "execution_mode",
+            "model",
+            "temperature",
+            "max_tokens",
+            "system_prompt",
+            "capabilities_json",
+            "skills_json",
+            "is_control_hub",
+            "max_concurrency",
+            "carry_over_context",
+            "warmup_prompt",
+            "manifest_name",
+            "ssh_host",
+            "ssh_user",
+            "ssh_key_path",
+            "container_image",
+            "container_workspace_access",
+            "idle_shutdown_minutes",
+            "permission_preset",
+            "workspace_id",
+        ],
+        filter: "1 = 1",
+        insert_defaults: &[],
+        delete_sql: "DELETE FROM agents WHERE id = ?1",
+    },
+    SyncedTable {
+        entity: "template",
+        table: "orchestration_templates",
+        columns: &[
+            "id",
+            "name",
+            "description",
+            "source_task_run_id",
+            "prompt_template",
+            "analysis",
+            "assignments_json",
+            "variables_json",
+            "workspace_id",
+        ],
+        filter: "1 = 1",
+        insert_defaults: &[],
+        delete_sql: "DELETE FROM orchestration_templates WHERE id = ?1",
+    },
+    // Schedules arrive paused so a task does not run on every device;
+    // resume it on the device that should execute it.
+    SyncedTable {
+        entity: "schedule",
+        table: "task_runs",
+        columns: &[
+            "id",
+            "title",
+            "user_prompt",
+            "control_hub_agent_id",
+            "schedule_type",
+            "scheduled_time",
+            "recurrence_pattern",
+            "next_run_at",
+            "failure_policy",
+            "workspace_id",
+        ],
+        filter: "schedule_type != 'none'",
+        insert_defaults: &[("status", "'pending'"), ("is_paused", "1")],
+        delete_sql: "UPDATE task_runs SET schedule_type = 'none', scheduled_time = NULL, recurrence_pattern = NULL, \
+                     next_run_at = NULL, failure_policy = NULL, updated_at = datetime('now') WHERE id = ?1",
+    },
+];
+
+/// The decrypted content of an envelope.
+#[derive(Debug, Serialize, Deserialize)]
+struct SyncPayload {
+    device_id: String,
+    records: Vec<SyncRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SyncRecord {
+    entity: String,
+    id: String,
+    clock: VectorClock,
+    deleted: bool,
+    modified_at: String,
+    modified_by: String,
+    /// Column values; absent for deleted records
+    data: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// The stored sync configuration with its passphrase read from the keychain.
+/// A passphrase left in the settings row by an older version is moved to the
+/// keychain first.
+pub fn load_config(state: &AppState) -> AppResult<Option<SyncConfig>> {
+    let mut config: SyncConfig = match settings_repo::get_setting(state, SYNC_CONFIG_SETTING)? {
+        Some(setting) if !setting.value.is_empty() => serde_json::from_str(&setting.value)?,
+        _ => return Ok(None),
+    };
+    if !config.passphrase.is_empty() {
+        save_config(state, Some(config.clone()))?;
+        return Ok(Some(config));
+    }
+    config.passphrase = load_passphrase()?.unwrap_or_default();
+    Ok(Some(config))
+}
+
+/// Store the sync configuration; None turns sync off. The passphrase goes to
+/// the keychain and the settings row keeps only the target.
+pub fn save_config(state: &AppState, config: Option<SyncConfig>) -> AppResult<()> {
+    let value = match config {
+        Some(config) => {
+            store_passphrase(&config.passphrase)?;
+            serde_json::to_string(&SyncConfig {
+                passphrase: String::new(),
+                ..config
+            })?
+        }
+        None => {
+            delete_passphrase()?;
+            String::new()
+        }
+    };
+    settings_repo::set_setting(state, SYNC_CONFIG_SETTING, &value)
+}
+
+fn keychain_entry() -> AppResult<keyring::Entry> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_PASSPHRASE_USER)
+        .map_err(|e| AppError::Internal(format!("Keychain unavailable: {}", e)))
+}
+
+fn load_passphrase() -> AppResult<Option<String>> {
+    match keychain_entry()?.get_password() {
+        Ok(passphrase) => Ok(Some(passphrase)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(AppError::Internal(format!("Failed to read the sync passphrase from the keychain: {}", e))),
+    }
+}
+
+fn store_passphrase(passphrase: &str) -> AppResult<()> {
+    keychain_entry()?
+        .set_password(passphrase)
+        .map_err(|e| AppError::Internal(format!("Failed to store the sync passphrase in the keychain: {}", e)))
+}
+
+fn delete_passphrase() -> AppResult<()> {
+    match keychain_entry()?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(AppError::Internal(format!("Failed to remove the sync passphrase from the keychain: {}", e))),
+    }
+}
+
+/// This installation's sync identity, created on first use.
+pub fn device_id(state: &AppState) -> AppResult<String> {
+    if let Some(setting) = settings_repo::get_setting(state, SYNC_DEVICE_ID_SETTING)? {
+        return Ok(setting.value);
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    settings_repo::set_setting(state, SYNC_DEVICE_ID_SETTING, &id)?;
+    Ok(id)
+}
+
+/// Run one sync round: record local edits, merge every other device's
+/// envelope, then publish this device's merged state.
+pub async fn sync_now(state: &AppState) -> AppResult<SyncReport> {
+    let _guard = state.sync_lock.lock().await;
+
+    let (config, device_id, local_changes) = {
+        let state = state.clone();
+        tokio::task::spawn_blocking(move || {
+            let config = load_config(&state)?
+                .ok_or_else(|| AppError::InvalidRequest("Sync is not configured".into()))?;
+            if config.passphrase.is_empty() {
+                return Err(AppError::InvalidRequest("Sync passphrase is not set".into()));
+            }
+            let device_id = device_id(&state)?;
+            let (_, changes) = scan_local(&state, &device_id)?;
+            Ok::<_, AppError>((config, device_id, changes))
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??
+    };
+
+    let mut report = SyncReport {
+        device_id: device_id.clone(),
+        local_changes,
+        ..Default::default()
+    };
+
+    // Key derivation is deliberately slow. An envelope that cannot be read
+    // (wrong passphrase, corrupt or foreign data) is skipped, not fatal.
+    let envelopes = store::pull(&config.target, &device_id).await?;
+    let (payloads, rejected) = {
+        let passphrase = config.passphrase.clone();
+        tokio::task::spawn_blocking(move || open_envelopes(&passphrase, envelopes))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?
+    };
+    report.rejected = rejected;
+    report.peers = payloads.len();
+
+    let records = {
+        let state = state.clone();
+        let device_id = device_id.clone();
+        let (applied, conflicts, failed, records) = tokio::task::spawn_blocking(move || {
+            let mut counts = (0, 0, 0);
+            for payload in &payloads {
+                merge_payload(&state, payload, &mut counts)?;
+            }
+            let (records, _) = scan_local(&state, &device_id)?;
+            Ok::<_, AppError>((counts.0, counts.1, counts.2, records))
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+        report.applied = applied;
+        report.conflicts = conflicts;
+        report.failed = failed;
+        records
+    };
+
+    report.pushed = records.len();
+    let payload = serde_json::to_vec(&SyncPayload {
+        device_id: device_id.clone(),
+        records,
+    })?;
+    let envelope = {
+        let passphrase = config.passphrase.clone();
+        let device_id = device_id.clone();
+        tokio::task::spawn_blocking(move || crypto::seal(&passphrase, &device_id, &payload))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??
+    };
+    store::push(&config.target, &envelope).await?;
+
+    report.synced_at = chrono::Utc::now().to_rfc3339();
+    let state_clone = state.clone();
+    let synced_at = report.synced_at.clone();
+    let _ = tokio::task::spawn_blocking(move || {
+        settings_repo::set_setting(&state_clone, SYNC_LAST_SYNCED_SETTING, &synced_at)
+    })
+    .await;
+
+    log::info!(
+        "[Sync] Synced with {} device(s): {} local change(s), {} applied, {} conflict(s), {} failed, {} envelope(s) rejected",
+        report.peers, report.local_changes, report.applied, report.conflicts, report.failed, report.rejected
+    );
+    Ok(report)
+}
+
+/// Decrypt the other devices' envelopes, returning the readable payloads and
+/// the number of envelopes that were skipped.
+fn open_envelopes(passphrase: &str, envelopes: Vec<crypto::Envelope>) -> (Vec<SyncPayload>, usize) {
+    let mut payloads = Vec::new();
+    let mut rejected = 0;
+    for envelope in envelopes {
+        let payload = crypto::open(passphrase, &envelope)
+            .and_then(|plaintext| Ok(serde_json::from_slice::<SyncPayload>(&plaintext)?));
+        match payload {
+            Ok(payload) if payload.device_id == envelope.device_id => payloads.push(payload),
+            Ok(payload) => {
+                log::warn!("[Sync] Envelope from {} claims device {}, skipping", envelope.device_id, payload.device_id);
+                rejected += 1;
+            }
+            Err(e) => {
+                log::warn!("[Sync] Skipping envelope from {}: {}", envelope.device_id, e);
+                rejected += 1;
+            }
+        }
+    }
+    (payloads, rejected)
+}
+
+/// Compare every replicated row with its sync record, bumping this device's
+/// clock entry for rows that changed or disappeared. Returns the full record
+/// set and the number of local changes.
+fn scan_local(state: &AppState, device_id: &str) -> AppResult<(Vec<SyncRecord>, usize)> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut records = Vec::new();
+    let mut changes = 0;
+
+    for table in &SYNCED_TABLES {
+        let rows = sync_repo::read_rows(state, table.table, table.columns, table.filter)?;
+        let mut known: HashMap<String, SyncRecordState> = sync_repo::list_records(state, table.entity)?
+            .into_iter()
+            .map(|r| (r.entity_id.clone(), r))
+            .collect();
+
+        for values in rows {
+            let Some(id) = values[0].as_str().map(|s| s.to_string()) else {
+                continue;
+            };
+            let hash = content_hash(&values);
+            let mut record = known.remove(&id).unwrap_or_else(|| SyncRecordState {
+                entity: table.entity.to_string(),
+                entity_id: id.clone(),
+                clock: VectorClock::new(),
+                content_hash: String::new(),
+                deleted: false,
+                modified_at: String::new(),
+                modified_by: String::new(),
+            });
+            if record.content_hash != hash || record.deleted {
+                clock::increment(&mut record.clock, device_id);
+                record.content_hash = hash;
+                record.deleted = false;
+                record.modified_at = now.clone();
+                record.modified_by = device_id.to_string();
+                sync_repo::save_record(state, &record)?;
+                changes += 1;
+            }
+
+            let data = table
+                .columns
+                .iter()
+                .map(|c| c.to_string())
+                .zip(values)
+                .collect();
+            records.push(to_sync_record(record, Some(data)));
+        }
+
+        // Whatever is left was deleted locally
+        for (_, mut record) in known {
+            if !record.deleted {
+                clock::increment(&mut record.clock, device_id);
+                record.deleted = true;
+                record.content_hash = String::new();
+                record.modified_at = now.clone();
+                record.modified_by = device_id.to_string();
+                sync_repo::save_record(state, &record)?;
+                changes += 1;
+            }
+            records.push(to_sync_record(record, None));
+        }
+    }
+
+    Ok((records, changes))
+}
+
+/// Merge one device's records. `counts` accumulates (applied, conflicts, failed).
+fn merge_payload(state: &AppState, payload: &SyncPayload, counts: &mut (usize, usize, usize)) -> AppResult<()> {
+    for table in &SYNCED_TABLES {
+        for remote in payload.records.iter().filter(|r| r.entity == table.entity) {
+            let local = sync_repo::get_record(state, table.entity, &remote.id)?;
+            let local_clock = local.as_ref().map(|l| l.clock.clone()).unwrap_or_default();
+
+            let remote_wins = match clock::compare(&remote.clock, &local_clock) {
+                ClockOrder::Equal | ClockOrder::Before => continue,
+                ClockOrder::After => true,
+                ClockOrder::Concurrent => {
+                    counts.1 += 1;
+                    let local = local.as_ref().expect("concurrent clocks imply a local record");
+                    (remote.modified_at.as_str(), remote.modified_by.as_str())
+                        > (local.modified_at.as_str(), local.modified_by.as_str())
+                }
+            };
+            let merged_clock = clock::merge(&remote.clock, &local_clock);
+
+            if !remote_wins {
+                // Keep the local version; its merged clock now supersedes the remote one
+                if let Some(mut local) = local {
+                    local.clock = merged_clock;
+                    sync_repo::save_record(state, &local)?;
+                }
+                continue;
+            }
+
+            let applied = match &remote.data {
+                Some(data) if !remote.deleted => {
+                    let values: Vec<serde_json::Value> = table
+                        .columns
+                        .iter()
+                        .map(|c| data.get(*c).cloned().unwrap_or(serde_json::Value::Null))
+                        .collect();
+                    sync_repo::upsert_row(state, table.table, table.columns, &values, table.insert_defaults)
+                        .map(|_| content_hash(&values))
+                }
+                _ => sync_repo::execute_for_id(state, table.delete_sql, &remote.id).map(|_| String::new()),
+            };
+
+            match applied {
+                Ok(hash) => {
+                    sync_repo::save_record(
+                        state,
+                        &SyncRecordState {
+                            entity: table.entity.to_string(),
+                            entity_id: remote.id.clone(),
+                            clock: merged_clock,
+                            content_hash: hash,
+                            deleted: remote.deleted,
+                            modified_at: remote.modified_at.clone(),
+                            modified_by: remote.modified_by.clone(),
+                        },
+                    )?;
+                    counts.0 += 1;
+                }
+                Err(e) => {
+                    log::warn!(
+                        "[Sync] Failed to apply {} {} from device {}: {}",
+                        table.entity, remote.id, payload.device_id, e
+                    );
+                    counts.2 += 1;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn to_sync_record(record: SyncRecordState, data: Option<serde_json::Map<String, serde_json::Value>>) -> SyncRecord {
+    SyncRecord {
+        entity: record.entity,
+        id: record.entity_id,
+        clock: record.clock,
+        deleted: record.deleted,
+        modified_at: record.modified_at,
+        modified_by: record.modified_by,
+        data,
+    }
+}
+
+fn content_hash(values: &[serde_json::Value]) -> String {
+    let encoded = serde_json::to_string(values).unwrap_or_default();
+    ring::digest::digest(&ring::digest::SHA256, encoded.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -0,0 +1,99 @@
+//! Exchange of sync envelopes through a shared directory or an HTTP relay.
+//!
+//! Directory: each device writes `<device_id>.agenthub-sync` and reads the
+//! other devices' files.
+//!
+//! Relay: `PUT {url}/{device_id}` stores this device's envelope and
+//! `GET {url}` returns a JSON array of all stored envelopes.
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult};
+use crate::models::sync::SyncTarget;
+use crate::sync::crypto::Envelope;
+
+const ENVELOPE_EXTENSION: &str = "agenthub-sync";
+const RELAY_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub async fn push(target: &SyncTarget, envelope: &Envelope) -> AppResult<()> {
+    let body = serde_json::to_string(envelope)?;
+    match target {
+        SyncTarget::Directory { path } => {
+            let dir = Path::new(path);
+            tokio::fs::create_dir_all(dir).await?;
+            // Write then rename so readers never see a partial envelope
+            let file = dir.join(format!("{}.{}", envelope.device_id, ENVELOPE_EXTENSION));
+            let tmp = dir.join(format!(".{}.{}.tmp", envelope.device_id, ENVELOPE_EXTENSION));
+            tokio::fs::write(&tmp, body).await?;
+            tokio::fs::rename(&tmp, &file).await?;
+            Ok(())
+        }
+        SyncTarget::Relay { url } => {
+            let resp = relay_client()?
+                .put(format!("{}/{}", url.trim_end_matches('/'), envelope.device_id))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| AppError::Transport(format!("Sync relay request error: {e}")))?;
+            if !resp.status().is_success() {
+                return Err(AppError::Transport(format!("Sync relay returned HTTP {}", resp.status())));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Envelopes published by every device except `own_device_id`.
+pub async fn pull(target: &SyncTarget, own_device_id: &str) -> AppResult<Vec<Envelope>> {
+    let envelopes = match target {
+        SyncTarget::Directory { path } => {
+            let mut envelopes = Vec::new();
+            let mut entries = match tokio::fs::read_dir(path).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(envelopes),
+                Err(e) => return Err(e.into()),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let file = entry.path();
+                if file.extension().and_then(|e| e.to_str()) != Some(ENVELOPE_EXTENSION) {
+                    continue;
+                }
+                let parsed = tokio::fs::read_to_string(&file)
+                    .await
+                    .map_err(AppError::from)
+                    .and_then(|text| serde_json::from_str::<Envelope>(&text).map_err(AppError::from));
+                match parsed {
+                    Ok(envelope) => envelopes.push(envelope),
+                    Err(e) => log::warn!("[Sync] Skipping unreadable envelope {}: {}", file.display(), e),
+                }
+            }
+            envelopes
+        }
+        SyncTarget::Relay { url } => {
+            let resp = relay_client()?
+                .get(url.trim_end_matches('/'))
+                .send()
+                .await
+                .map_err(|e| AppError::Transport(format!("Sync relay request error: {e}")))?;
+            if !resp.status().is_success() {
+                return Err(AppError::Transport(format!("Sync relay returned HTTP {}", resp.status())));
+            }
+            let text = resp
+                .text()
+                .await
+                .map_err(|e| AppError::Transport(format!("Sync relay response error: {e}")))?;
+            serde_json::from_str::<Vec<Envelope>>(&text)?
+        }
+    };
+
+    Ok(envelopes.into_iter().filter(|e| e.device_id != own_device_id).collect())
+}
+
+fn relay_client() -> AppResult<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(RELAY_TIMEOUT)
+        .build()
+        .map_err(|e| AppError::Transport(format!("HTTP client error: {e}")))
+}
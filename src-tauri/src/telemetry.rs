@@ -0,0 +1,151 @@
+//! Tracing spans and the optional OTLP exporter.
+//!
+//! Orchestration, agent prompts, chat tool bridge events and task run DB
+//! calls are instrumented with `tracing` spans. They are exported only when
+//! `telemetry.otlp_endpoint` is configured (e.g. a local Jaeger or Tempo at
+//! `http://localhost:4318`); the exporter is swapped in place when the setting
+//! changes, so no restart is needed.
+
+use std::sync::{Mutex, OnceLock};
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, Layer, Registry};
+
+use crate::config;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    /// OTLP/HTTP collector, e.g. "http://localhost:4318"; `None` disables export
+    pub otlp_endpoint: Option<String>,
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: "agent-hub".into(),
+        }
+    }
+}
+
+type OtelLayer = tracing_opentelemetry::OpenTelemetryLayer<Registry, Tracer>;
+
+static LAYER_HANDLE: OnceLock<reload::Handle<Option<OtelLayer>, Registry>> = OnceLock::new();
+static PROVIDER: Mutex<Option<TracerProvider>> = Mutex::new(None);
+
+/// `tokio::task::spawn_blocking` that keeps the caller's span as parent.
+pub fn spawn_blocking<F, R>(f: F) -> tokio::task::JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let span = tracing::Span::current();
+    tokio::task::spawn_blocking(move || span.in_scope(f))
+}
+
+/// The traces URL for an OTLP/HTTP endpoint given with or without its path.
+fn traces_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    if endpoint.ends_with("/v1/traces") {
+        endpoint.to_string()
+    } else {
+        format!("{}/v1/traces", endpoint)
+    }
+}
+
+fn build_provider(config: &TelemetryConfig, endpoint: &str) -> AppResult<TracerProvider> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_url(endpoint))
+        .build()
+        .map_err(|e| AppError::Internal(format!("OTLP exporter: {}", e)))?;
+    let resource = opentelemetry_sdk::Resource::new(vec![KeyValue::new("service.name", config.service_name.clone())]);
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_resource(resource)
+        .build())
+}
+
+/// Flush and stop the current exporter, if any. Blocking.
+fn shutdown_provider(provider: TracerProvider) {
+    if let Err(e) = provider.shutdown() {
+        log::warn!("[Telemetry] Exporter shutdown failed: {}", e);
+    }
+}
+
+/// Point span export at the configured endpoint, or turn it off.
+async fn apply(config: &TelemetryConfig) {
+    let Some(handle) = LAYER_HANDLE.get() else {
+        return;
+    };
+    let provider = match config.otlp_endpoint.as_deref() {
+        Some(endpoint) => match build_provider(config, endpoint) {
+            Ok(provider) => Some(provider),
+            Err(e) => {
+                log::warn!("[Telemetry] {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let layer = provider
+        .as_ref()
+        .map(|p| tracing_opentelemetry::layer().with_tracer(p.tracer("agent-hub")));
+    if let Err(e) = handle.reload(layer) {
+        log::warn!("[Telemetry] Failed to swap exporter: {}", e);
+        return;
+    }
+    if provider.is_some() {
+        log::info!("[Telemetry] Exporting spans to {}", config.otlp_endpoint.as_deref().unwrap_or_default());
+    }
+
+    let previous = match PROVIDER.lock() {
+        Ok(mut current) => std::mem::replace(&mut *current, provider),
+        Err(_) => None,
+    };
+    if let Some(previous) = previous {
+        let _ = tokio::task::spawn_blocking(move || shutdown_provider(previous)).await;
+    }
+}
+
+/// Install the tracing subscriber and follow `telemetry` config changes.
+/// Spans from this crate only are exported.
+pub async fn init(state: AppState) {
+    let (layer, handle) = reload::Layer::new(None::<OtelLayer>);
+    let targets = Targets::new().with_target("app_lib", LevelFilter::DEBUG);
+    if let Err(e) = tracing_subscriber::registry().with(layer.with_filter(targets)).try_init() {
+        log::warn!("[Telemetry] Tracing subscriber already installed: {}", e);
+        return;
+    }
+    let _ = LAYER_HANDLE.set(handle);
+
+    let mut changes = config::subscribe(&state);
+    let mut current = changes.borrow_and_update().telemetry.clone();
+    apply(&current).await;
+    while changes.changed().await.is_ok() {
+        let next = changes.borrow_and_update().telemetry.clone();
+        if next != current {
+            apply(&next).await;
+            current = next;
+        }
+    }
+}
+
+/// Flush pending spans before exit. Blocking.
+pub fn shutdown() {
+    let provider = PROVIDER.lock().ok().and_then(|mut p| p.take());
+    if let Some(provider) = provider {
+        shutdown_provider(provider);
+    }
+}
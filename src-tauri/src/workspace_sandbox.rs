@@ -0,0 +1,294 @@
+//! Sandbox workspaces for trying risky orchestrations.
+//!
+//! `clone_workspace_sandbox` mirrors a workspace on a directory under the
+//! system temp directory: a detached git worktree at HEAD when the working
+//! directory is a git repository without uncommitted changes, otherwise a
+//! copy of its files without version control data, dependencies and build
+//! output. The new workspace gets copies of the original's agents
+//! and settings, so runs in it change only the sandbox. Deleting the sandbox
+//! workspace removes the worktree or copy.
+
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use crate::acp::discovery;
+use crate::db::{agent_repo, workspace_repo};
+use crate::error::{AppError, AppResult};
+use crate::models::workspace::{CreateWorkspaceRequest, SandboxMode, UpdateWorkspaceRequest, Workspace};
+use crate::state::AppState;
+
+/// Directory the sandboxes are made in.
+pub fn sandbox_root() -> PathBuf {
+    std::env::temp_dir().join("agent-hub-sandboxes")
+}
+
+fn git(dir: &Path, args: &[&str]) -> AppResult<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .env("PATH", discovery::get_enriched_path())
+        .output()
+        .map_err(|e| AppError::Internal(format!("Failed to run git: {e}")))?;
+    if !output.status.success() {
+        return Err(AppError::Internal(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// A worktree for a git repository without uncommitted changes; a copy for
+/// anything else. A worktree holds the committed files only, so files the
+/// repository ignores, like dependencies or a local `.env`, are not in it.
+fn auto_mode(dir: &Path) -> SandboxMode {
+    match git(dir, &["status", "--porcelain"]) {
+        Ok(status) if status.trim().is_empty() => SandboxMode::Worktree,
+        _ => SandboxMode::Copy,
+    }
+}
+
+/// Directories left out of a copy: version control data, and dependencies
+/// and build output that can be restored from the sources.
+const SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "target"];
+
+/// Copy `source` into `dest` recursively, leaving out `SKIPPED_DIRS`.
+/// Symlinks within `source` are recreated on Unix and skipped elsewhere. A
+/// link leading out of it would let runs change the original, so the file it
+/// points to is copied instead and a directory is left out. Returns the
+/// number of files copied.
+fn copy_dir(source: &Path, dest: &Path) -> std::io::Result<u64> {
+    copy_tree(source, source, dest)
+}
+
+fn copy_tree(root: &Path, source: &Path, dest: &Path) -> std::io::Result<u64> {
+    std::fs::create_dir_all(dest)?;
+    let mut copied = 0;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        let target = dest.join(entry.file_name());
+        if file_type.is_dir() {
+            if SKIPPED_DIRS.iter().any(|name| entry.file_name() == *name) {
+                log::debug!("Not copying {} into the sandbox", path.display());
+                continue;
+            }
+            copied += copy_tree(root, &path, &target)?;
+        } else if file_type.is_symlink() {
+            let link = std::fs::read_link(&path)?;
+            if stays_within(root, source, &link) {
+                #[cfg(unix)]
+                std::os::unix::fs::symlink(&link, &target)?;
+                #[cfg(not(unix))]
+                log::debug!("Not copying symlink {} into the sandbox", path.display());
+            } else if path.is_file() {
+                std::fs::copy(&path, &target)?;
+                copied += 1;
+            } else {
+                log::warn!("Not copying symlink {} into the sandbox: it leads out of {}", path.display(), root.display());
+            }
+        } else {
+            std::fs::copy(&path, &target)?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Whether the relative symlink target `link` of an entry in `dir` stays
+/// within `root`. Absolute targets never do, as they name the original.
+fn stays_within(root: &Path, dir: &Path, link: &Path) -> bool {
+    let Ok(relative) = dir.strip_prefix(root) else {
+        return false;
+    };
+    let mut depth = relative.components().count();
+    for component in link.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Make the sandbox directory `dest` from `source` and return the working
+/// directory in it, which for a worktree keeps the source's path inside the
+/// repository.
+fn make(source: &Path, dest: &Path, mode: SandboxMode) -> AppResult<PathBuf> {
+    match mode {
+        SandboxMode::Worktree => {
+            let prefix = git(source, &["rev-parse", "--show-prefix"])?;
+            git(source, &["worktree", "add", "--detach", &dest.to_string_lossy(), "HEAD"])?;
+            Ok(dest.join(prefix.trim()))
+        }
+        SandboxMode::Copy => {
+            let files = copy_dir(source, dest)?;
+            log::info!("Copied {} file(s) from {} into sandbox {}", files, source.display(), dest.display());
+            Ok(dest.to_path_buf())
+        }
+    }
+}
+
+/// The sandbox directory a sandbox workspace's working directory is in;
+/// None for directories outside the sandbox root, which are never removed.
+fn sandbox_dir(working_directory: &Path) -> Option<PathBuf> {
+    let root = sandbox_root();
+    let first = working_directory.strip_prefix(&root).ok()?.components().next()?;
+    Some(root.join(first))
+}
+
+fn discard(source: &Path, dest: &Path, mode: SandboxMode) {
+    if dest.exists() {
+        if let Err(e) = std::fs::remove_dir_all(dest) {
+            log::warn!("Failed to remove sandbox {}: {}", dest.display(), e);
+        }
+    }
+    if mode == SandboxMode::Worktree {
+        if let Err(e) = git(source, &["worktree", "prune"]) {
+            log::warn!("Failed to prune worktrees of {}: {}", source.display(), e);
+        }
+    }
+}
+
+/// Clone a workspace into a new sandbox workspace. With no `mode` the
+/// sandbox is a worktree when the working directory allows one.
+pub fn clone_workspace_sandbox(state: &AppState, workspace_id: &str, mode: Option<SandboxMode>) -> AppResult<Workspace> {
+    let source = workspace_repo::get_workspace(state, workspace_id)?;
+    let source_dir = Path::new(source.working_directory.trim());
+    if source.working_directory.trim().is_empty() || !source_dir.is_dir() {
+        return Err(AppError::InvalidRequest(format!("Workspace {} has no working directory to clone", source.name)));
+    }
+    let source_dir = source_dir.canonicalize()?;
+    let root = sandbox_root();
+    if root.starts_with(&source_dir) {
+        return Err(AppError::InvalidRequest(format!(
+            "{} contains the sandbox directory {}",
+            source_dir.display(),
+            root.display()
+        )));
+    }
+    std::fs::create_dir_all(&root)?;
+    let name = source_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "workspace".into());
+    let dest = root.join(format!("{}-{}", name, &uuid::Uuid::new_v4().to_string()[..8]));
+
+    let mode = mode.unwrap_or_else(|| auto_mode(&source_dir));
+    let working_directory = make(&source_dir, &dest, mode).inspect_err(|_| discard(&source_dir, &dest, mode))?;
+
+    let mirror = || -> AppResult<Workspace> {
+        let agent_ids = agent_repo::list_agents(state, Some(workspace_id))?.into_iter().map(|a| a.id).collect();
+        let sandbox = workspace_repo::create_workspace(
+            state,
+            CreateWorkspaceRequest {
+                name: format!("{} (sandbox)", source.name),
+                icon: "beaker".into(),
+                working_directory: working_directory.to_string_lossy().to_string(),
+                agent_ids,
+            },
+        )?;
+        let configure = || -> AppResult<Workspace> {
+            workspace_repo::update_workspace(
+                state,
+                &sandbox.id,
+                UpdateWorkspaceRequest {
+                    name: None,
+                    icon: None,
+                    working_directory: None,
+                    hub_persona: source.hub_persona.clone(),
+                    redact_outputs: Some(source.redact_outputs),
+                    exclusive_runs: Some(source.exclusive_runs),
+                },
+            )?;
+            workspace_repo::set_sandbox_of(state, &sandbox.id, workspace_id, mode)?;
+            workspace_repo::get_workspace(state, &sandbox.id)
+        };
+        // A half-configured sandbox would look like an ordinary workspace
+        configure().inspect_err(|_| {
+            if let Err(e) = workspace_repo::delete_workspace(state, &sandbox.id) {
+                log::warn!("Failed to remove incomplete sandbox workspace {}: {}", sandbox.id, e);
+            }
+        })
+    };
+    let sandbox = mirror().inspect_err(|_| discard(&source_dir, &dest, mode))?;
+    log::info!(
+        "Cloned workspace {} into sandbox workspace {} ({}) at {}",
+        workspace_id,
+        sandbox.id,
+        mode.as_str(),
+        sandbox.working_directory
+    );
+    Ok(sandbox)
+}
+
+/// Remove the worktree or copy of a sandbox workspace that was deleted.
+pub fn remove(state: &AppState, sandbox: &Workspace) {
+    let (Some(source_id), Some(mode)) = (sandbox.sandbox_of.as_deref(), sandbox.sandbox_mode) else {
+        return;
+    };
+    let Some(dir) = sandbox_dir(Path::new(&sandbox.working_directory)) else {
+        log::warn!("Not removing sandbox {} outside {}", sandbox.working_directory, sandbox_root().display());
+        return;
+    };
+    let source_dir = workspace_repo::get_workspace(state, source_id)
+        .map(|source| PathBuf::from(source.working_directory))
+        .unwrap_or_default();
+    discard(&source_dir, &dir, mode);
+    log::info!("Removed sandbox {} of deleted workspace {}", dir.display(), sandbox.id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_a_plain_directory_into_the_sandbox_root() {
+        let source = std::env::temp_dir().join(format!("sandbox-source-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(source.join("src/nested")).unwrap();
+        std::fs::write(source.join("README.md"), "# Project\n").unwrap();
+        std::fs::write(source.join("src/nested/main.rs"), "fn main() {}\n").unwrap();
+        assert_eq!(auto_mode(&source), SandboxMode::Copy);
+
+        let dest = sandbox_root().join(format!("test-{}", uuid::Uuid::new_v4()));
+        let working_directory = make(&source, &dest, SandboxMode::Copy).unwrap();
+        assert_eq!(working_directory, dest);
+        assert_eq!(std::fs::read_to_string(dest.join("src/nested/main.rs")).unwrap(), "fn main() {}\n");
+        assert_eq!(sandbox_dir(&dest.join("src")), Some(dest.clone()));
+        assert_eq!(sandbox_dir(&source), None);
+
+        discard(&source, &dest, SandboxMode::Copy);
+        assert!(!dest.exists() && source.join("README.md").exists());
+        std::fs::remove_dir_all(source).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copies_what_links_out_of_the_tree_point_to_and_skips_heavy_directories() {
+        let base = std::env::temp_dir().join(format!("sandbox-links-{}", uuid::Uuid::new_v4()));
+        let source = base.join("project");
+        std::fs::create_dir_all(source.join("src")).unwrap();
+        std::fs::create_dir_all(source.join("node_modules/dep")).unwrap();
+        std::fs::create_dir_all(source.join(".git")).unwrap();
+        std::fs::write(source.join("src/lib.rs"), "").unwrap();
+        std::fs::write(source.join("node_modules/dep/index.js"), "").unwrap();
+        std::fs::write(base.join("secret.env"), "KEY=1\n").unwrap();
+        std::os::unix::fs::symlink("lib.rs", source.join("src/alias.rs")).unwrap();
+        std::os::unix::fs::symlink("../../secret.env", source.join("src/relative.env")).unwrap();
+        std::os::unix::fs::symlink(base.join("secret.env"), source.join("absolute.env")).unwrap();
+        std::os::unix::fs::symlink(&base, source.join("outside")).unwrap();
+
+        let dest = base.join("sandbox");
+        assert_eq!(copy_dir(&source, &dest).unwrap(), 3);
+        assert_eq!(std::fs::read_link(dest.join("src/alias.rs")).unwrap(), Path::new("lib.rs"));
+        for copied in ["src/relative.env", "absolute.env"] {
+            let metadata = std::fs::symlink_metadata(dest.join(copied)).unwrap();
+            assert!(metadata.is_file(), "{} is a copy, not a link", copied);
+        }
+        assert!(!dest.join("outside").exists());
+        assert!(!dest.join("node_modules").exists() && !dest.join(".git").exists());
+        std::fs::remove_dir_all(base).unwrap();
+    }
+}
@@ -53,6 +53,9 @@ export function Providers({ children }: { children: React.ReactNode }) {
     // Initialize Tauri event listeners for orchestration
     initializeOrchestrationListeners();
 
+    // Keep settings in sync when they change elsewhere
+    useSettingsStore.getState().listenForChanges();
+
     console.log('[Providers] App initialized');
   }, [loadSettings, loadWorkingDirectory, scanForAgents, fetchAgents, fetchWorkspaces]);
 
@@ -5,13 +5,19 @@ import { Codicon } from "@/components/ui/Codicon";
 import { Button } from "@/components/ui/Button";
 import { cn } from "@/lib/cn";
 import { useChatToolStore } from "@/stores/chatToolStore";
-import type { ChatTool, ChatToolMessage, ChatToolContact } from "@/types/chatTool";
+import type {
+  ChatTool,
+  ChatToolMessage,
+  ChatToolContact,
+  ChatToolMetrics,
+  MetricsRange,
+} from "@/types/chatTool";
 
 interface Props {
   tool: ChatTool;
 }
 
-type Tab = "config" | "messages" | "contacts";
+type Tab = "config" | "messages" | "contacts" | "metrics";
 
 export function ChatToolConfigPanel({ tool }: Props) {
   const [activeTab, setActiveTab] = useState<Tab>("config");
@@ -24,6 +30,7 @@ export function ChatToolConfigPanel({ tool }: Props) {
   const qrCodeImage = useChatToolStore((s) => s.qrCodeImage);
   const qrCodeUrl = useChatToolStore((s) => s.qrCodeUrl);
   const setContactBlocked = useChatToolStore((s) => s.setContactBlocked);
+  const setContactRunNotifications = useChatToolStore((s) => s.setContactRunNotifications);
 
   const [starting, setStarting] = useState(false);
   const [stopping, setStopping] = useState(false);
@@ -225,7 +232,7 @@ export function ChatToolConfigPanel({ tool }: Props) {
 
       {/* Tabs */}
       <div className="flex border-b border-slate-200 dark:border-border-dark mb-4">
-        {(["config", "messages", "contacts"] as Tab[]).map((tab) => (
+        {(["config", "messages", "contacts", "metrics"] as Tab[]).map((tab) => (
           <button
             key={tab}
             onClick={() => setActiveTab(tab)}
@@ -247,8 +254,18 @@ export function ChatToolConfigPanel({ tool }: Props) {
       )}
       {activeTab === "messages" && <MessageLog messages={messages} />}
       {activeTab === "contacts" && (
-        <ContactList contacts={contacts} onToggleBlocked={setContactBlocked} />
+        <ContactList
+          contacts={contacts}
+          onToggleBlocked={setContactBlocked}
+          onToggleRunNotifications={(contactId, subscribed) =>
+            setContactRunNotifications(
+              contactId,
+              subscribed ? { on_completed: true, on_failed: true, workspace_id: tool.workspace_id } : null
+            )
+          }
+        />
       )}
+      {activeTab === "metrics" && <MetricsTab toolId={tool.id} />}
     </div>
   );
 }
@@ -357,6 +374,133 @@ function ConfigTab({
   );
 }
 
+const METRICS_RANGES: { value: MetricsRange; label: string }[] = [
+  { value: "hour", label: "1h" },
+  { value: "day", label: "24h" },
+  { value: "week", label: "7d" },
+  { value: "month", label: "30d" },
+];
+
+function MetricsTab({ toolId }: { toolId: string }) {
+  const getMetrics = useChatToolStore((s) => s.getMetrics);
+  const [range, setRange] = useState<MetricsRange>("day");
+  const [metrics, setMetrics] = useState<ChatToolMetrics | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    const load = () =>
+      getMetrics(toolId, range)
+        .then((m) => {
+          if (!cancelled) setMetrics(m);
+        })
+        .catch((e) => console.error("[ChatToolConfig] Load metrics failed:", e));
+    load();
+    const interval = setInterval(load, 30000);
+    return () => {
+      cancelled = true;
+      clearInterval(interval);
+    };
+  }, [toolId, range, getMetrics]);
+
+  const peak = Math.max(1, ...(metrics?.buckets.map((b) => b.received) ?? []));
+
+  return (
+    <div className="space-y-4">
+      <div className="flex gap-1">
+        {METRICS_RANGES.map((r) => (
+          <button
+            key={r.value}
+            onClick={() => setRange(r.value)}
+            className={cn(
+              "px-2 py-1 rounded text-[10px] font-semibold transition-colors",
+              range === r.value
+                ? "bg-primary/10 text-primary"
+                : "text-slate-400 dark:text-gray-500 hover:text-slate-600 dark:hover:text-gray-300"
+            )}
+          >
+            {r.label}
+          </button>
+        ))}
+      </div>
+
+      {!metrics ? (
+        <div className="text-center py-8 text-xs text-slate-400 dark:text-gray-500">Loading...</div>
+      ) : (
+        <>
+          <div className="grid grid-cols-3 gap-3">
+            <StatCard
+              label="Avg Response"
+              value={formatDuration(metrics.avg_response_ms)}
+              icon="watch"
+              color="text-blue-500"
+              isText
+            />
+            <StatCard
+              label="P95 Response"
+              value={formatDuration(metrics.p95_response_ms)}
+              icon="pulse"
+              color="text-amber-500"
+              isText
+            />
+            <StatCard
+              label="Hub Latency"
+              value={formatDuration(metrics.avg_hub_latency_ms)}
+              icon="rocket"
+              color="text-violet-500"
+              isText
+            />
+            <StatCard
+              label="Replied"
+              value={`${metrics.messages_replied} / ${metrics.messages_received}`}
+              icon="check"
+              color="text-emerald-500"
+              isText
+            />
+            <StatCard
+              label="Peak Queue"
+              value={metrics.max_queue_depth}
+              icon="list-ordered"
+              color="text-slate-400"
+            />
+            <StatCard
+              label="Backlog"
+              value={metrics.backlog}
+              icon="inbox"
+              color={metrics.backlog > 0 ? "text-rose-500" : "text-slate-400"}
+            />
+          </div>
+
+          <div>
+            <div className="flex justify-between text-[10px] text-slate-400 dark:text-gray-500 mb-1">
+              <span>Received per interval</span>
+              <span>
+                {metrics.escalated_batches} escalated · {metrics.failed_batches} failed
+              </span>
+            </div>
+            <div className="flex items-end gap-px h-20">
+              {metrics.buckets.map((b) => (
+                <div
+                  key={b.start}
+                  className="flex-1 bg-blue-400/60 dark:bg-blue-500/50 rounded-t-sm"
+                  style={{ height: `${(b.received / peak) * 100}%` }}
+                  title={`${b.start}: ${b.received} received, ${b.replied} replied, avg ${formatDuration(b.avg_response_ms)}, queue ${b.max_queue_depth}`}
+                />
+              ))}
+            </div>
+          </div>
+        </>
+      )}
+    </div>
+  );
+}
+
+function formatDuration(ms: number | null): string {
+  if (ms === null) return "—";
+  if (ms < 1000) return `${Math.round(ms)}ms`;
+  if (ms < 60000) return `${(ms / 1000).toFixed(1)}s`;
+  return `${(ms / 60000).toFixed(1)}m`;
+}
+
 function MessageLog({ messages }: { messages: ChatToolMessage[] }) {
   if (messages.length === 0) {
     return (
@@ -424,9 +568,11 @@ function MessageLog({ messages }: { messages: ChatToolMessage[] }) {
 function ContactList({
   contacts,
   onToggleBlocked,
+  onToggleRunNotifications,
 }: {
   contacts: ChatToolContact[];
   onToggleBlocked: (contactId: string, blocked: boolean) => Promise<void>;
+  onToggleRunNotifications: (contactId: string, subscribed: boolean) => Promise<void>;
 }) {
   const [search, setSearch] = useState("");
 
@@ -476,17 +622,35 @@ function ContactList({
                   </div>
                 </div>
               </div>
-              <button
-                onClick={() => onToggleBlocked(contact.id, !contact.is_blocked)}
-                className={cn(
-                  "text-[10px] px-2 py-0.5 rounded font-medium transition-colors",
-                  contact.is_blocked
-                    ? "bg-rose-100 dark:bg-rose-500/10 text-rose-500 hover:bg-rose-200"
-                    : "bg-slate-100 dark:bg-surface-dark text-slate-400 hover:bg-slate-200"
-                )}
-              >
-                {contact.is_blocked ? "Blocked" : "Block"}
-              </button>
+              <div className="flex items-center gap-1">
+                <button
+                  onClick={() => onToggleRunNotifications(contact.id, !contact.run_notifications)}
+                  title={
+                    contact.run_notifications
+                      ? "Stop sending task run results to this contact"
+                      : "Send task run results to this contact"
+                  }
+                  className={cn(
+                    "size-6 rounded flex items-center justify-center transition-colors",
+                    contact.run_notifications
+                      ? "text-primary bg-primary/10 hover:bg-primary/20"
+                      : "text-slate-300 dark:text-gray-600 hover:text-slate-500"
+                  )}
+                >
+                  <Codicon name={contact.run_notifications ? "bell-dot" : "bell"} className="text-[12px]" />
+                </button>
+                <button
+                  onClick={() => onToggleBlocked(contact.id, !contact.is_blocked)}
+                  className={cn(
+                    "text-[10px] px-2 py-0.5 rounded font-medium transition-colors",
+                    contact.is_blocked
+                      ? "bg-rose-100 dark:bg-rose-500/10 text-rose-500 hover:bg-rose-200"
+                      : "bg-slate-100 dark:bg-surface-dark text-slate-400 hover:bg-slate-200"
+                  )}
+                >
+                  {contact.is_blocked ? "Blocked" : "Block"}
+                </button>
+              </div>
             </div>
           ))}
         </div>
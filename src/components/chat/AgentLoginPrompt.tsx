@@ -0,0 +1,53 @@
+"use client";
+
+import { Codicon } from "@/components/ui/Codicon";
+import type { AgentLogin } from "@/types/agent";
+
+interface AgentLoginPromptProps {
+  login: AgentLogin;
+}
+
+/** An agent being logged in: where to sign in and the code to enter */
+export function AgentLoginPrompt({ login }: AgentLoginPromptProps) {
+  return (
+    <div className="bg-sky-50 dark:bg-sky-950/30 border-2 border-sky-300 dark:border-sky-700/50 rounded-lg px-4 py-3 shadow-lg shadow-sky-500/10 animate-in fade-in">
+      <div className="flex items-center gap-2">
+        <Codicon name="account" className="text-[16px] text-sky-500 flex-none" />
+        <span className="text-xs font-bold text-sky-700 dark:text-sky-400 shrink-0">Login Required</span>
+        <span className="text-xs text-slate-600 dark:text-gray-400 truncate">{login.agent_name}</span>
+      </div>
+
+      {!login.url && !login.code && (
+        <p className="mt-1.5 text-[11px] text-slate-600 dark:text-gray-400">Starting the agent&apos;s login…</p>
+      )}
+      {login.url && (
+        <p className="mt-1.5 text-[11px] text-slate-600 dark:text-gray-400">
+          Sign in at{" "}
+          <a
+            href={login.url}
+            target="_blank"
+            rel="noopener noreferrer"
+            className="font-mono text-sky-600 dark:text-sky-400 underline break-all"
+          >
+            {login.url}
+          </a>
+        </p>
+      )}
+      {login.code && (
+        <div className="mt-1.5 flex items-center gap-2 text-[11px] text-slate-600 dark:text-gray-400">
+          <span>Enter the code</span>
+          <code className="px-2 py-0.5 rounded bg-white dark:bg-slate-900 border border-slate-200 dark:border-slate-700 font-mono text-sm font-bold tracking-widest text-slate-800 dark:text-slate-100 select-all">
+            {login.code}
+          </code>
+          <button
+            onClick={() => navigator.clipboard?.writeText(login.code ?? "")}
+            className="size-6 flex items-center justify-center rounded hover:bg-sky-200 dark:hover:bg-sky-900/50 transition-colors"
+            title="Copy code"
+          >
+            <Codicon name="copy" className="text-[12px]" />
+          </button>
+        </div>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,78 @@
+"use client";
+
+import { useState } from "react";
+import { Codicon } from "@/components/ui/Codicon";
+import type { AgentTerminal } from "@/types/agent";
+
+// eslint-disable-next-line no-control-regex
+const ANSI_PATTERN = /\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07]*(\x07|\x1b\\)|\r/g;
+
+interface AgentTerminalPromptProps {
+  terminal: AgentTerminal;
+  agentName?: string;
+  onSubmit: (data: string) => Promise<void>;
+}
+
+/** An agent's terminal waiting for input, e.g. a login prompt */
+export function AgentTerminalPrompt({ terminal, agentName, onSubmit }: AgentTerminalPromptProps) {
+  const [input, setInput] = useState("");
+  const [isSubmitting, setIsSubmitting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const output = terminal.output.replace(ANSI_PATTERN, "").trimEnd();
+  // Prompts asking for a password or token should not echo the answer
+  const secret = /password|passphrase|token|secret|api key/i.test(terminal.prompt ?? "");
+
+  const handleSubmit = async () => {
+    setIsSubmitting(true);
+    setError(null);
+    try {
+      await onSubmit(input + "\n");
+      setInput("");
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setIsSubmitting(false);
+    }
+  };
+
+  return (
+    <div className="bg-sky-50 dark:bg-sky-950/30 border-2 border-sky-300 dark:border-sky-700/50 rounded-lg px-4 py-3 shadow-lg shadow-sky-500/10 animate-in fade-in">
+      <div className="flex items-center gap-2">
+        <Codicon name="terminal" className="text-[16px] text-sky-500 flex-none" />
+        <span className="text-xs font-bold text-sky-700 dark:text-sky-400 shrink-0">Input Required</span>
+        {agentName && <span className="text-xs text-slate-600 dark:text-gray-400 truncate">{agentName}</span>}
+      </div>
+
+      {output && (
+        <pre className="mt-2 max-h-40 overflow-y-auto whitespace-pre-wrap break-all rounded bg-slate-900 text-slate-100 text-[11px] font-mono px-2 py-1.5">
+          {output}
+        </pre>
+      )}
+
+      <form
+        className="mt-2 flex items-center gap-2"
+        onSubmit={(e) => {
+          e.preventDefault();
+          handleSubmit();
+        }}
+      >
+        <input
+          type={secret ? "password" : "text"}
+          value={input}
+          onChange={(e) => setInput(e.target.value)}
+          placeholder={terminal.prompt ?? "Input"}
+          autoFocus
+          className="flex-1 px-2 py-1 text-xs font-mono rounded border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-900 text-slate-700 dark:text-slate-300 focus:outline-none focus:ring-1 focus:ring-sky-500"
+        />
+        <button
+          type="submit"
+          disabled={isSubmitting}
+          className="px-3 py-1.5 text-xs font-medium bg-sky-500 hover:bg-sky-600 text-white rounded-lg transition-colors disabled:opacity-50"
+        >
+          Send
+        </button>
+      </form>
+      {error && <p className="mt-1 text-[11px] text-rose-500">{error}</p>}
+    </div>
+  );
+}
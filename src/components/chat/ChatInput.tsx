@@ -5,6 +5,7 @@ import { Codicon } from "@/components/ui/Codicon";
 import { useChatStore } from "@/stores/chatStore";
 import { useAgentStore } from "@/stores/agentStore";
 import { useOrchestrationStore, useFocusedTaskRunState } from "@/stores/orchestrationStore";
+import type { PermissionPreset } from "@/types/agent";
 
 export function ChatInput() {
   const [text, setText] = useState("");
@@ -18,6 +19,12 @@ export function ChatInput() {
   const controlHubAgentId = useAgentStore((s) => s.controlHubAgentId);
   const startOrchestration = useOrchestrationStore((s) => s.startOrchestration);
   const continueOrchestration = useOrchestrationStore((s) => s.continueOrchestration);
+  const attachedContextRun = useOrchestrationStore((s) => s.attachedContextRun);
+  const attachRunContext = useOrchestrationStore((s) => s.attachRunContext);
+  const attachedPinnedOutputs = useOrchestrationStore((s) => s.attachedPinnedOutputs);
+  const attachPinnedOutputs = useOrchestrationStore((s) => s.attachPinnedOutputs);
+  const runPermissionPreset = useOrchestrationStore((s) => s.runPermissionPreset);
+  const setRunPermissionPreset = useOrchestrationStore((s) => s.setRunPermissionPreset);
   const focused = useFocusedTaskRunState();
 
   const isOrchestrationMode = !!controlHubAgentId;
@@ -45,8 +52,10 @@ export function ChatInput() {
       return;
     }
 
-    // Continue mode: focused task completed/failed/cancelled, send supplementary instructions
-    if (isTaskCompleted) {
+    // Continue mode: focused task completed/failed/cancelled, send supplementary instructions.
+    // A follow-up with an earlier run or pinned outputs attached starts a new run instead.
+    const hasAttachment = !!attachedContextRun || attachedPinnedOutputs.length > 0;
+    if (isTaskCompleted && !(isOrchestrationMode && hasAttachment)) {
       console.log('[ChatInput] Continuing orchestration with additional instructions...');
       try {
         await continueOrchestration(trimmedText);
@@ -92,7 +101,8 @@ export function ChatInput() {
     } catch (e) {
       console.error('[ChatInput] Failed to send prompt:', e);
     }
-  }, [text, isStreaming, isFocusedBusy, isTaskCompleted, isOrchestrationMode,
+  }, [text, isStreaming, isFocusedBusy, isTaskCompleted, isOrchestrationMode, attachedContextRun,
+      attachedPinnedOutputs,
       currentSessionId, selectedAgentId, continueOrchestration, startOrchestration,
       ensureSession, sendPrompt]);
 
@@ -131,6 +141,60 @@ export function ChatInput() {
               </span>
             </div>
           )}
+          {isOrchestrationMode && attachedContextRun && (
+            <div
+              className="flex items-center gap-1.5 pl-2.5 pr-1 py-0.5 rounded-full bg-sky-500/10 border border-sky-500/20 min-w-0"
+              title="The next run is planned with this run's summary and outputs"
+            >
+              <Codicon name="history" className="text-[12px] text-sky-500" />
+              <span className="text-[10px] font-bold text-sky-500 truncate max-w-[240px]">
+                Follow-up to: {attachedContextRun.title || attachedContextRun.user_prompt.slice(0, 40)}
+              </span>
+              <button
+                onClick={() => attachRunContext(null)}
+                className="size-4 flex items-center justify-center rounded-full hover:bg-sky-500/20 text-sky-500"
+                title="Detach run"
+              >
+                <Codicon name="close" className="text-[10px]" />
+              </button>
+            </div>
+          )}
+          {isOrchestrationMode && attachedPinnedOutputs.length > 0 && (
+            <div
+              className="flex items-center gap-1.5 pl-2.5 pr-1 py-0.5 rounded-full bg-sky-500/10 border border-sky-500/20 min-w-0"
+              title="The pinned outputs are appended to the next run's prompt"
+            >
+              <Codicon name="pin" className="text-[12px] text-sky-500" />
+              <span className="text-[10px] font-bold text-sky-500 truncate max-w-[240px]">
+                {attachedPinnedOutputs.length} pinned output{attachedPinnedOutputs.length === 1 ? "" : "s"}
+              </span>
+              <button
+                onClick={() => attachPinnedOutputs([])}
+                className="size-4 flex items-center justify-center rounded-full hover:bg-sky-500/20 text-sky-500"
+                title="Detach pinned outputs"
+              >
+                <Codicon name="close" className="text-[10px]" />
+              </button>
+            </div>
+          )}
+          {isOrchestrationMode && (
+            <div
+              className="flex items-center gap-1.5 px-2.5 py-0.5 rounded-full bg-slate-500/10 border border-slate-500/20"
+              title="Permission preset answering the agents' requests in the next run"
+            >
+              <Codicon name="shield" className="text-[12px] text-slate-500" />
+              <select
+                value={runPermissionPreset ?? ""}
+                onChange={(e) => setRunPermissionPreset((e.target.value || null) as PermissionPreset | null)}
+                className="bg-transparent text-[10px] font-bold text-slate-500 uppercase tracking-wider outline-none"
+              >
+                <option value="">Agent permissions</option>
+                <option value="strict">Strict</option>
+                <option value="balanced">Balanced</option>
+                <option value="yolo">Yolo</option>
+              </select>
+            </div>
+          )}
         </div>
 
         <div className="relative bg-white dark:bg-surface-dark border border-slate-200 dark:border-border-dark rounded-xl shadow-2xl focus-within:ring-1 focus-within:ring-primary/50 transition-all">
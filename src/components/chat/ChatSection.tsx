@@ -7,8 +7,12 @@ import { OrchestrationPanel } from "@/components/orchestration/OrchestrationPane
 import { TaskHistoryPanel } from "@/components/orchestration/TaskHistoryPanel";
 import { TaskContextEditor } from "@/components/orchestration/TaskContextEditor";
 import { InlinePermission } from "@/components/chat/InlinePermission";
+import { AgentTerminalPrompt } from "@/components/chat/AgentTerminalPrompt";
+import { AgentLoginPrompt } from "@/components/chat/AgentLoginPrompt";
 import { useOrchestrationStore, buildTaskContext } from "@/stores/orchestrationStore";
 import { useWorkspaceStore } from "@/stores/workspaceStore";
+import { useAcpStore } from "@/stores/acpStore";
+import { useAgentStore } from "@/stores/agentStore";
 import { Codicon } from "@/components/ui/Codicon";
 import { cn } from "@/lib/cn";
 
@@ -98,6 +102,15 @@ export function ChatSection() {
   const clearRestoredTaskRunId = useOrchestrationStore((s) => s.clearRestoredTaskRunId);
   const resumeWithEditedContext = useOrchestrationStore((s) => s.resumeWithEditedContext);
   const dismissTaskRun = useOrchestrationStore((s) => s.dismissTaskRun);
+  const agentTerminals = useAcpStore((s) => s.agentTerminals);
+  const writeAgentTerminal = useAcpStore((s) => s.writeAgentTerminal);
+  const agentLogins = useAcpStore((s) => s.agentLogins);
+  const agents = useAgentStore((s) => s.agents);
+
+  const waitingTerminals = useMemo(
+    () => Object.values(agentTerminals).filter((t) => t.prompt !== null),
+    [agentTerminals]
+  );
 
   const [userCreatingNew, setUserCreatingNew] = useState(false);
 
@@ -154,6 +167,25 @@ export function ChatSection() {
         )}
       </div>
 
+      {/* Agent logins and terminals waiting for input, e.g. a login prompt */}
+      {(waitingTerminals.length > 0 || Object.keys(agentLogins).length > 0) && (
+        <div className="px-8 py-2 border-t border-slate-200 dark:border-border-dark/50 bg-slate-50 dark:bg-[#07070C]">
+          <div className="max-w-6xl mx-auto w-full flex flex-col gap-2">
+            {Object.values(agentLogins).map((login) => (
+              <AgentLoginPrompt key={login.agent_id} login={login} />
+            ))}
+            {waitingTerminals.map((terminal) => (
+              <AgentTerminalPrompt
+                key={terminal.terminal_id}
+                terminal={terminal}
+                agentName={agents.find((a) => a.id === terminal.agent_id)?.name}
+                onSubmit={(data) => writeAgentTerminal(terminal.terminal_id, data)}
+              />
+            ))}
+          </div>
+        </div>
+      )}
+
       {/* Permission dialogs — pinned between scroll area and input */}
       {pendingOrchPermissions.length > 0 && (
         <div className="px-8 py-2 border-t border-slate-200 dark:border-border-dark/50 bg-slate-50 dark:bg-[#07070C] flex flex-col gap-2">
@@ -190,13 +222,17 @@ export function ChatSection() {
                       sessionId: perm.sessionId,
                       toolCall: perm.toolCall,
                       options: perm.options,
+                      requestKey: perm.requestKey,
+                      batchCount: perm.batchCount,
                     }}
-                    onResponse={(optionId) => {
+                    rememberable
+                    onResponse={(optionId, _userMessage, remember) => {
                       respondToOrchPermission(
                         perm.taskRunId,
                         perm.agentId,
                         String(perm.requestId),
-                        optionId
+                        optionId,
+                        remember
                       );
                     }}
                     onDismiss={() => {
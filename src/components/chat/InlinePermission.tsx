@@ -2,6 +2,7 @@
 
 import { useState } from "react";
 import { Codicon } from "@/components/ui/Codicon";
+import type { PermissionScope } from "@/types/orchestration";
 
 interface PermissionRequest {
   id: number | string;
@@ -16,19 +17,26 @@ interface PermissionRequest {
     name: string;
     kind: string;
   }>;
+  /** Kind of request the decision can be remembered for */
+  requestKey?: string;
+  /** Similar requests answered together with this one */
+  batchCount?: number;
 }
 
 interface InlinePermissionProps {
   request: PermissionRequest;
-  onResponse: (optionId: string, userMessage?: string) => void;
+  onResponse: (optionId: string, userMessage?: string, remember?: PermissionScope) => void;
   onDismiss: () => void;
+  /** Offer to remember the decision for the run or the agent */
+  rememberable?: boolean;
 }
 
-export function InlinePermission({ request, onResponse, onDismiss }: InlinePermissionProps) {
+export function InlinePermission({ request, onResponse, onDismiss, rememberable }: InlinePermissionProps) {
   const [expanded, setExpanded] = useState(false);
   const [selectedOption, setSelectedOption] = useState<string>("allow");
   const [userMessage, setUserMessage] = useState("");
   const [isSubmitting, setIsSubmitting] = useState(false);
+  const [remember, setRemember] = useState<PermissionScope | "">("");
 
   const toolName = request.toolCall?.title || "Tool execution";
 
@@ -36,7 +44,7 @@ export function InlinePermission({ request, onResponse, onDismiss }: InlinePermi
     const optionId = optionOverride || selectedOption;
     setIsSubmitting(true);
     try {
-      await onResponse(optionId, userMessage.trim() || undefined);
+      await onResponse(optionId, userMessage.trim() || undefined, remember || undefined);
     } finally {
       setIsSubmitting(false);
     }
@@ -107,9 +115,32 @@ export function InlinePermission({ request, onResponse, onDismiss }: InlinePermi
       </div>
 
       {/* Tool name */}
-      <p className="text-xs font-mono text-slate-600 dark:text-gray-400 mt-1 truncate" title={toolName}>
-        {toolName}
-      </p>
+      <div className="flex items-center gap-2 mt-1 min-w-0">
+        <p className="text-xs font-mono text-slate-600 dark:text-gray-400 truncate" title={toolName}>
+          {toolName}
+        </p>
+        {(request.batchCount ?? 1) > 1 && (
+          <span className="shrink-0 px-1.5 py-0.5 text-[10px] font-medium rounded-full bg-amber-200 dark:bg-amber-900/50 text-amber-700 dark:text-amber-400">
+            {request.batchCount} similar requests
+          </span>
+        )}
+      </div>
+
+      {/* Remember the decision */}
+      {rememberable && (
+        <label className="mt-1.5 flex items-center gap-1.5 text-[11px] text-slate-600 dark:text-gray-400">
+          <span>Remember{request.requestKey ? ` "${request.requestKey}"` : ""}:</span>
+          <select
+            value={remember}
+            onChange={(e) => setRemember(e.target.value as PermissionScope | "")}
+            className="px-1.5 py-0.5 rounded border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-900 text-slate-700 dark:text-slate-300 focus:outline-none focus:ring-1 focus:ring-amber-500"
+          >
+            <option value="">Ask every time</option>
+            <option value="run">For this run</option>
+            <option value="agent">For this agent</option>
+          </select>
+        </label>
+      )}
 
       {/* Expanded content */}
       {expanded && (
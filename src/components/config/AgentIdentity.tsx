@@ -6,6 +6,7 @@ import { Button } from "@/components/ui/Button";
 import { Badge } from "@/components/ui/Badge";
 import { useState, useRef, useEffect } from "react";
 import { cn } from "@/lib/cn";
+import { errorMessage } from "@/types/error";
 
 const iconOptions = [
   { name: "code", icon: "code" },
@@ -29,6 +30,7 @@ export function AgentIdentity() {
   const selectedAgentId = useAgentStore((s) => s.selectedAgentId);
   const updateAgent = useAgentStore((s) => s.updateAgent);
   const setControlHub = useAgentStore((s) => s.setControlHub);
+  const setSecondaryHub = useAgentStore((s) => s.setSecondaryHub);
   const enableAgent = useAgentStore((s) => s.enableAgent);
   const disableAgent = useAgentStore((s) => s.disableAgent);
   const controlHubAgentId = useAgentStore((s) => s.controlHubAgentId);
@@ -99,7 +101,7 @@ export function AgentIdentity() {
       try {
         await enableAgent(agent.id);
       } catch (e) {
-        const msg = e instanceof Error ? e.message : String(e);
+        const msg = errorMessage(e);
         setEnableError(msg);
       } finally {
         setEnableLoading(false);
@@ -268,6 +270,14 @@ export function AgentIdentity() {
         >
           <Codicon name="star-full" /> {isHub ? "Hub Active" : "Set as Hub"}
         </Button>
+        {!isHub && agent && (
+          <Button
+            variant={agent.is_secondary_hub ? "primary" : "secondary"}
+            onClick={() => setSecondaryHub(agent.id, !agent.is_secondary_hub)}
+          >
+            <Codicon name="star-half" /> {agent.is_secondary_hub ? "Backup Hub" : "Set as Backup Hub"}
+          </Button>
+        )}
         <Button variant="secondary">
           <Codicon name="history" /> Logs
         </Button>
@@ -6,6 +6,15 @@ import { Select } from "@/components/ui/Select";
 import { Slider } from "@/components/ui/Slider";
 import { useAgentStore } from "@/stores/agentStore";
 import { useAcpStore } from "@/stores/acpStore";
+import type { PermissionPreset } from "@/types/agent";
+
+/** Presets answering the agent's permission requests in task runs */
+const PERMISSION_PRESET_OPTIONS = [
+  { label: "Ask", value: "" },
+  { label: "Strict", value: "strict" },
+  { label: "Balanced", value: "balanced" },
+  { label: "Yolo", value: "yolo" },
+];
 
 /** Extract the first URL from a string, if any. */
 function extractUrl(text: string): string | null {
@@ -40,6 +49,7 @@ export function ParameterWizard() {
   const agent = agents.find((a) => a.id === selectedAgentId);
   const model = agent?.model ?? "";
   const maxConcurrency = agent?.max_concurrency ?? 1;
+  const permissionPreset = agent?.permission_preset ?? "";
 
   // Manual model input state
   const [manualModel, setManualModel] = useState("");
@@ -251,6 +261,23 @@ export function ParameterWizard() {
               />
             </div>
           </div>
+
+          {/* Permission Preset */}
+          <div className="flex-1 space-y-2">
+            <div className="flex items-center justify-between h-7">
+              <label className="flex items-center gap-2 text-xs font-bold text-slate-500 dark:text-gray-400 uppercase tracking-wide">
+                <Codicon name="shield" />
+                Permissions
+              </label>
+            </div>
+            <Select
+              value={permissionPreset}
+              options={PERMISSION_PRESET_OPTIONS}
+              onChange={(v) =>
+                agent && updateAgent(agent.id, { permission_preset: v as PermissionPreset | "" })
+              }
+            />
+          </div>
         </div>
       </div>
     </div>
@@ -7,6 +7,7 @@ import { useOrchestrationStore } from "@/stores/orchestrationStore";
 import { useAgentStore } from "@/stores/agentStore";
 import { tauriInvoke } from "@/lib/tauri";
 import type { TaskRun, RecurrencePattern } from "@/types/orchestration";
+import { BUILTIN_PLANNER_ID } from "@/types/orchestration";
 
 type TaskStatus = "todo" | "inprogress" | "done" | "cancelled";
 type TimeFilter = "day" | "week" | "month" | "all";
@@ -147,6 +148,7 @@ function TaskCard({ task, agents, onClick, onCancel, isSelected }: TaskCardProps
   const [expanded, setExpanded] = useState(false);
   const statusBadge = getStatusBadge(task.status);
   const controlHubAgent = agents.find((a) => a.id === task.control_hub_agent_id);
+  const hubName = controlHubAgent?.name ?? (task.control_hub_agent_id === BUILTIN_PLANNER_ID ? "Built-in planner" : null);
   const recurrencePattern = parseRecurrencePattern(task.recurrence_pattern_json);
 
   // Check if task is scheduled
@@ -266,10 +268,10 @@ function TaskCard({ task, agents, onClick, onCancel, isSelected }: TaskCardProps
 
       {/* Meta info */}
       <div className="flex items-center gap-3 text-[10px] text-slate-400 dark:text-gray-500">
-        {controlHubAgent && (
+        {hubName && (
           <div className="flex items-center gap-1">
             <Codicon name="hubot" className="text-[12px]" />
-            <span className="truncate max-w-[80px]">{controlHubAgent.name}</span>
+            <span className="truncate max-w-[80px]">{hubName}</span>
           </div>
         )}
         {assignmentCount > 0 && (
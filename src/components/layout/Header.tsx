@@ -11,7 +11,13 @@ export function Header() {
   const [registryOpen, setRegistryOpen] = useState(false);
   const menuRef = useRef<HTMLDivElement>(null);
   const registryBtnRef = useRef<HTMLButtonElement>(null);
-  const { theme, toggleTheme } = useSettingsStore();
+  const { theme, toggleTheme, observer, setObserverMode } = useSettingsStore();
+
+  const toggleObserver = () => {
+    setObserverMode(!observer.active).catch((error) => {
+      console.error('Failed to switch observer mode:', error);
+    });
+  };
 
   useEffect(() => {
     const handler = (e: MouseEvent) => {
@@ -32,6 +38,14 @@ export function Header() {
         <h1 className="text-xl font-bold tracking-tight text-slate-900 dark:text-white">
           IAAgentHub
         </h1>
+        {observer.active && (
+          <span
+            className="px-2 py-0.5 rounded-full bg-amber-500/10 text-amber-600 dark:text-amber-400 text-[10px] font-bold uppercase tracking-widest"
+            title="Read-only: changes are disabled on this machine"
+          >
+            Observer
+          </span>
+        )}
       </div>
       <div className="flex items-center gap-2">
         {/* ACP Registry */}
@@ -55,6 +69,19 @@ export function Header() {
         <IconButton title="Settings">
           <Codicon name="gear" className="text-[20px]" />
         </IconButton>
+        <IconButton
+          title={
+            observer.forced
+              ? "Observer mode (set at launch)"
+              : observer.active
+                ? "Leave observer mode"
+                : "Enter observer mode"
+          }
+          onClick={toggleObserver}
+          disabled={observer.forced}
+        >
+          <Codicon name={observer.active ? "eye" : "eye-closed"} className="text-[20px]" />
+        </IconButton>
         <IconButton title="Toggle theme" onClick={toggleTheme}>
           {theme === 'dark' ? <Codicon name="color-mode" className="text-[20px]" /> : <Codicon name="lightbulb" className="text-[20px]" />}
         </IconButton>
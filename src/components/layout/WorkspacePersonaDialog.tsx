@@ -0,0 +1,72 @@
+"use client";
+
+import { useEffect, useState } from "react";
+import { useWorkspaceStore } from "@/stores/workspaceStore";
+import { Modal } from "@/components/ui/Modal";
+import type { Workspace } from "@/types/workspace";
+
+interface WorkspacePersonaDialogProps {
+  workspace: Workspace | null;
+  onClose: () => void;
+}
+
+/** Edit the persona the Control Hub takes on in a workspace */
+export function WorkspacePersonaDialog({ workspace, onClose }: WorkspacePersonaDialogProps) {
+  const [persona, setPersona] = useState("");
+  const [saving, setSaving] = useState(false);
+  const updateWorkspace = useWorkspaceStore((s) => s.updateWorkspace);
+
+  useEffect(() => {
+    setPersona(workspace?.hub_persona ?? "");
+  }, [workspace]);
+
+  const handleSave = async () => {
+    if (!workspace) return;
+    setSaving(true);
+    try {
+      await updateWorkspace(workspace.id, { hub_persona: persona.trim() });
+      onClose();
+    } catch (error) {
+      console.error("[WorkspacePersonaDialog] Failed to save:", error);
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  return (
+    <Modal open={!!workspace} onClose={onClose} title="Hub Persona">
+      <div className="space-y-4">
+        <div>
+          <p className="text-[10px] text-slate-400 dark:text-gray-600 mb-1.5">
+            Put before the Control Hub&apos;s planning, review and summary prompts in {workspace?.name}: its tone,
+            language or the domain it works in. Leave empty for none.
+          </p>
+          <textarea
+            value={persona}
+            onChange={(e) => setPersona(e.target.value)}
+            placeholder="Answer in German. You plan work for a legal team; keep summaries formal and cite sources."
+            rows={6}
+            className="w-full px-3 py-2 rounded-lg bg-slate-50 dark:bg-white/5 border border-slate-200 dark:border-border-dark text-sm text-slate-900 dark:text-white placeholder-slate-400 dark:placeholder-gray-600 focus:outline-none focus:border-primary resize-none"
+            autoFocus
+          />
+        </div>
+
+        <div className="flex justify-end gap-2 pt-2">
+          <button
+            onClick={onClose}
+            className="px-4 py-2 text-xs rounded-lg text-slate-500 dark:text-gray-400 hover:bg-slate-100 dark:hover:bg-white/5 transition-colors"
+          >
+            Cancel
+          </button>
+          <button
+            onClick={handleSave}
+            disabled={saving}
+            className="px-4 py-2 text-xs rounded-lg bg-primary text-background-dark font-medium hover:bg-primary/90 transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+          >
+            {saving ? "Saving..." : "Save"}
+          </button>
+        </div>
+      </div>
+    </Modal>
+  );
+}
@@ -5,9 +5,11 @@ import { useWorkspaceStore } from "@/stores/workspaceStore";
 import { useAgentStore } from "@/stores/agentStore";
 import { useOrchestrationStore } from "@/stores/orchestrationStore";
 import { useChatStore } from "@/stores/chatStore";
+import { showError } from "@/stores/toastStore";
 import { Codicon } from "@/components/ui/Codicon";
 import { cn } from "@/lib/cn";
 import { WorkspaceCreateDialog } from "./WorkspaceCreateDialog";
+import { WorkspacePersonaDialog } from "./WorkspacePersonaDialog";
 
 // Legacy icon values stored in DB that don't match codicon names
 const LEGACY_ICON_MAP: Record<string, string> = { star: "star-full" };
@@ -138,6 +140,7 @@ export function WorkspaceSidebar() {
   const sidebarExpanded = useWorkspaceStore((s) => s.sidebarExpanded);
   const toggleSidebar = useWorkspaceStore((s) => s.toggleSidebar);
   const selectWorkspaceDirectory = useWorkspaceStore((s) => s.selectWorkspaceDirectory);
+  const cloneWorkspaceSandbox = useWorkspaceStore((s) => s.cloneWorkspaceSandbox);
   const fetchTaskRuns = useOrchestrationStore((s) => s.fetchTaskRuns);
   const isOrchestrating = useOrchestrationStore((s) => s.isOrchestrating);
 
@@ -148,6 +151,7 @@ export function WorkspaceSidebar() {
     y: number;
   } | null>(null);
   const [renaming, setRenaming] = useState<string | null>(null);
+  const [personaWorkspaceId, setPersonaWorkspaceId] = useState<string | null>(null);
   const [renameValue, setRenameValue] = useState("");
   const renameInputRef = useRef<HTMLInputElement>(null);
 
@@ -371,6 +375,61 @@ export function WorkspaceSidebar() {
             <Codicon name="folder-opened" className="text-[12px]" />
             Set directory
           </button>
+          <button
+            onClick={() => {
+              setPersonaWorkspaceId(contextMenu.workspaceId);
+              setContextMenu(null);
+            }}
+            className="w-full text-left px-3 py-1.5 text-xs hover:bg-slate-100 dark:hover:bg-white/5 flex items-center gap-2"
+          >
+            <Codicon name="hubot" className="text-[12px]" />
+            Hub persona
+          </button>
+          <button
+            onClick={() => {
+              const ws = workspaces.find((w) => w.id === contextMenu.workspaceId);
+              if (ws) updateWorkspace(ws.id, { redact_outputs: !ws.redact_outputs });
+              setContextMenu(null);
+            }}
+            title="Mask API keys, tokens and emails in agent outputs"
+            className="w-full text-left px-3 py-1.5 text-xs hover:bg-slate-100 dark:hover:bg-white/5 flex items-center gap-2"
+          >
+            <Codicon
+              name={workspaces.find((w) => w.id === contextMenu.workspaceId)?.redact_outputs ? "check" : "blank"}
+              className="text-[12px]"
+            />
+            Redact secrets
+          </button>
+          <button
+            onClick={() => {
+              const ws = workspaces.find((w) => w.id === contextMenu.workspaceId);
+              if (ws) updateWorkspace(ws.id, { exclusive_runs: !ws.exclusive_runs });
+              setContextMenu(null);
+            }}
+            title="Queue a run while another run of this workspace is active"
+            className="w-full text-left px-3 py-1.5 text-xs hover:bg-slate-100 dark:hover:bg-white/5 flex items-center gap-2"
+          >
+            <Codicon
+              name={workspaces.find((w) => w.id === contextMenu.workspaceId)?.exclusive_runs ? "check" : "blank"}
+              className="text-[12px]"
+            />
+            One run at a time
+          </button>
+          {!workspaces.find((w) => w.id === contextMenu.workspaceId)?.sandbox_of && (
+            <button
+              onClick={() => {
+                cloneWorkspaceSandbox(contextMenu.workspaceId)
+                  .then((sandbox) => handleSwitchWorkspace(sandbox.id))
+                  .catch((error) => showError("创建沙盒工作区失败", error));
+                setContextMenu(null);
+              }}
+              title="Try runs on a copy or git worktree of this workspace's directory"
+              className="w-full text-left px-3 py-1.5 text-xs hover:bg-slate-100 dark:hover:bg-white/5 flex items-center gap-2"
+            >
+              <Codicon name="beaker" className="text-[12px]" />
+              Clone as sandbox
+            </button>
+          )}
           {workspaces.length > 1 && (
             <button
               onClick={() => {
@@ -390,6 +449,10 @@ export function WorkspaceSidebar() {
         open={showCreateDialog}
         onClose={() => setShowCreateDialog(false)}
       />
+      <WorkspacePersonaDialog
+        workspace={workspaces.find((w) => w.id === personaWorkspaceId) ?? null}
+        onClose={() => setPersonaWorkspaceId(null)}
+      />
     </>
   );
 }
@@ -1,10 +1,11 @@
 "use client";
 
-import { useState } from "react";
-import type { AgentTrackingInfo, A2aCallInfo } from "@/types/orchestration";
+import { useEffect, useState } from "react";
+import type { AgentTrackingInfo, A2aCallInfo, PinOutputRequest, TaskArtifact } from "@/types/orchestration";
 import { Codicon } from "@/components/ui/Codicon";
 import { MarkdownContent } from "@/components/chat/MarkdownContent";
 import { GeneratedFileBlock } from "@/components/chat/GeneratedFileBlock";
+import { tauriInvoke } from "@/lib/tauri";
 
 interface AgentTrackerProps {
   info: AgentTrackingInfo;
@@ -14,6 +15,8 @@ interface AgentTrackerProps {
   isAwaitingConfirmation?: boolean;
   onRegenerate?: () => void;
   onCancel?: () => void;
+  /** Pin the output or an A2A result to the run's clipboard */
+  onPin?: (pin: Omit<PinOutputRequest, "task_run_id">) => void;
 }
 
 export function AgentTracker({
@@ -24,6 +27,7 @@ export function AgentTracker({
   isAwaitingConfirmation,
   onRegenerate,
   onCancel,
+  onPin,
 }: AgentTrackerProps) {
   const statusIcon =
     info.status === "completed" ? (
@@ -85,6 +89,19 @@ export function AgentTracker({
               Cancel
             </button>
           )}
+          {info.status === "completed" && info.assignmentId && onPin && (
+            <button
+              onClick={(e) => {
+                e.stopPropagation();
+                onPin({ assignment_id: info.assignmentId });
+              }}
+              title="Pin this output to the run's clipboard"
+              className="flex items-center gap-1 px-2 py-0.5 rounded text-[11px] font-medium text-slate-500 hover:bg-slate-500/10 transition-colors"
+            >
+              <Codicon name="pin" className="text-[12px]" />
+              Pin
+            </button>
+          )}
           {isAwaitingConfirmation && info.status === "completed" && onRegenerate && (
             <button
               onClick={(e) => {
@@ -136,7 +153,15 @@ export function AgentTracker({
       {!isExpanded && info.a2aCalls && info.a2aCalls.length > 0 && (
         <div className="mt-2 space-y-1">
           {info.a2aCalls.map((call, idx) => (
-            <A2aCallRow key={`${call.targetAgentId}-${call.iteration}`} call={call} />
+            <A2aCallRow
+              key={`${call.targetAgentId}-${call.iteration}`}
+              call={call}
+              onPin={onPin && (() => onPin({
+                agent_id: call.targetAgentId,
+                label: `${info.agentName} → ${call.targetAgentName || call.targetAgentId}`,
+                content: call.result,
+              }))}
+            />
           ))}
         </div>
       )}
@@ -148,6 +173,15 @@ export function AgentTracker({
         </div>
       )}
 
+      {/* Images and files from the output */}
+      {info.artifacts && info.artifacts.length > 0 && (
+        <div className="mt-2 flex flex-wrap gap-2">
+          {info.artifacts.map((artifact) => (
+            <ArtifactThumb key={artifact.id} artifact={artifact} />
+          ))}
+        </div>
+      )}
+
       {/* Expanded detail view */}
       {isExpanded && (
         <div className="mt-2 pt-2 border-t border-slate-100 dark:border-border-dark/30 space-y-3">
@@ -173,7 +207,15 @@ export function AgentTracker({
               </p>
               <div className="space-y-1">
                 {info.a2aCalls.map((call, idx) => (
-                  <A2aCallRow key={`${call.targetAgentId}-${call.iteration}`} call={call} />
+                  <A2aCallRow
+                    key={`${call.targetAgentId}-${call.iteration}`}
+                    call={call}
+                    onPin={onPin && (() => onPin({
+                      agent_id: call.targetAgentId,
+                      label: `${info.agentName} → ${call.targetAgentName || call.targetAgentId}`,
+                      content: call.result,
+                    }))}
+                  />
                 ))}
               </div>
             </div>
@@ -210,6 +252,95 @@ function extractFileWriteInfo(rawInput: any): { path: string; content: string }
   return null;
 }
 
+/** A tool call payload; truncated ones can load the full content on demand. */
+function ToolPayload({ value, artifactId }: { value: any; artifactId?: string }) {
+  const [full, setFull] = useState<any>(undefined);
+  const [loading, setLoading] = useState(false);
+  const shown = full !== undefined ? full : value;
+
+  const loadFull = async () => {
+    if (!artifactId) return;
+    setLoading(true);
+    try {
+      setFull(await tauriInvoke<any>("get_tool_payload", { artifactId }));
+    } catch (e) {
+      console.error("Failed to load tool payload:", e);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  return (
+    <div>
+      <pre className="text-[10px] text-slate-500 dark:text-gray-500 bg-white dark:bg-slate-900/50 rounded px-2 py-1 overflow-x-auto max-h-32">
+        {typeof shown === "string" ? shown : JSON.stringify(shown, null, 2)}
+      </pre>
+      {artifactId && full === undefined && (
+        <button
+          onClick={loadFull}
+          disabled={loading}
+          className="mt-0.5 text-[10px] text-blue-400 hover:text-blue-300 disabled:opacity-50"
+        >
+          {loading ? "Loading…" : "Load full content"}
+        </button>
+      )}
+    </div>
+  );
+}
+
+/** An image artifact as a thumbnail, any other file as its name and size. */
+function ArtifactThumb({ artifact }: { artifact: TaskArtifact }) {
+  const [src, setSrc] = useState<string | null>(null);
+  const [showFull, setShowFull] = useState(false);
+  const meta = artifact.metadata;
+  const isImage = !!meta?.mime_type.startsWith("image/");
+  const name = artifact.path.split(/[\\/]/).pop() || artifact.path;
+
+  useEffect(() => {
+    if (!isImage) return;
+    let cancelled = false;
+    tauriInvoke<string>("get_artifact_preview", { artifactId: artifact.id })
+      .then((url) => { if (!cancelled) setSrc(url); })
+      .catch((e) => console.error("Failed to load artifact preview:", e));
+    return () => { cancelled = true; };
+  }, [artifact.id, isImage]);
+
+  const size = meta?.width && meta?.height ? `${meta.width}×${meta.height}` : formatBytes(artifact.size_bytes);
+
+  if (isImage && src) {
+    return (
+      <>
+        <button
+          onClick={() => setShowFull(true)}
+          title={`${artifact.path} (${size})`}
+          className="rounded border border-slate-200 dark:border-border-dark overflow-hidden bg-slate-50 dark:bg-black/20"
+        >
+          <img src={src} alt={name} className="h-20 max-w-[10rem] object-contain" />
+        </button>
+        {showFull && (
+          <div
+            onClick={() => setShowFull(false)}
+            className="fixed inset-0 z-50 flex items-center justify-center bg-black/70 p-8"
+          >
+            <img src={src} alt={name} className="max-h-full max-w-full object-contain" />
+          </div>
+        )}
+      </>
+    );
+  }
+
+  return (
+    <div
+      title={artifact.path}
+      className="flex items-center gap-1.5 rounded border border-slate-200 dark:border-border-dark px-2 py-1 text-[11px] text-slate-600 dark:text-gray-400"
+    >
+      <Codicon name={isImage ? "file-media" : "file"} className="text-[12px]" />
+      <span className="truncate max-w-[12rem]">{name}</span>
+      <span className="text-[10px] text-slate-400 dark:text-gray-500">{size}</span>
+    </div>
+  );
+}
+
 function ToolCallRow({ toolCall }: { toolCall: NonNullable<AgentTrackingInfo["toolCalls"]>[number] }) {
   const [showDetail, setShowDetail] = useState(false);
 
@@ -255,18 +386,10 @@ function ToolCallRow({ toolCall }: { toolCall: NonNullable<AgentTrackingInfo["to
       {showDetail && (
         <div className="mt-1.5 space-y-1">
           {toolCall.rawInput && (
-            <pre className="text-[10px] text-slate-500 dark:text-gray-500 bg-white dark:bg-slate-900/50 rounded px-2 py-1 overflow-x-auto max-h-32">
-              {typeof toolCall.rawInput === "string"
-                ? toolCall.rawInput
-                : JSON.stringify(toolCall.rawInput, null, 2)}
-            </pre>
+            <ToolPayload value={toolCall.rawInput} artifactId={toolCall.rawInputArtifactId} />
           )}
           {toolCall.rawOutput && (
-            <pre className="text-[10px] text-slate-500 dark:text-gray-500 bg-white dark:bg-slate-900/50 rounded px-2 py-1 overflow-x-auto max-h-32">
-              {typeof toolCall.rawOutput === "string"
-                ? toolCall.rawOutput
-                : JSON.stringify(toolCall.rawOutput, null, 2)}
-            </pre>
+            <ToolPayload value={toolCall.rawOutput} artifactId={toolCall.rawOutputArtifactId} />
           )}
         </div>
       )}
@@ -274,7 +397,7 @@ function ToolCallRow({ toolCall }: { toolCall: NonNullable<AgentTrackingInfo["to
   );
 }
 
-function A2aCallRow({ call }: { call: A2aCallInfo }) {
+function A2aCallRow({ call, onPin }: { call: A2aCallInfo; onPin?: () => void }) {
   const [showDetail, setShowDetail] = useState(false);
   const hasResult = call.result !== undefined;
   const statusColor = hasResult ? "text-emerald-400" : "text-blue-400";
@@ -303,6 +426,15 @@ function A2aCallRow({ call }: { call: A2aCallInfo }) {
               {call.result}
             </pre>
           )}
+          {call.result && onPin && (
+            <button
+              onClick={onPin}
+              className="flex items-center gap-1 text-[10px] text-blue-400 hover:text-blue-300"
+            >
+              <Codicon name="pin" className="text-[10px]" />
+              Pin result
+            </button>
+          )}
         </div>
       )}
     </div>
@@ -316,3 +448,9 @@ function formatDuration(ms: number): string {
   const secs = Math.floor((ms % 60000) / 1000);
   return `${mins}m ${secs}s`;
 }
+
+function formatBytes(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+}
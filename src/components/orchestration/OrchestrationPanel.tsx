@@ -6,9 +6,17 @@ import { AgentTracker } from "./AgentTracker";
 import { TaskPlanView } from "./TaskPlanView";
 import { TrackingSummary } from "./TrackingSummary";
 import { TaskContextEditor } from "./TaskContextEditor";
+import { PinnedOutputs } from "./PinnedOutputs";
 import { Codicon } from "@/components/ui/Codicon";
 import { MarkdownContent } from "@/components/chat/MarkdownContent";
 import { useState } from "react";
+import type { CancelReason } from "@/types/orchestration";
+
+const CANCEL_REASONS: { value: CancelReason; label: string }[] = [
+  { value: "user_cancelled", label: "No longer needed" },
+  { value: "too_expensive", label: "Too expensive" },
+  { value: "wrong_direction", label: "Wrong direction" },
+];
 
 export function OrchestrationPanel() {
   const activeWorkspaceId = useWorkspaceStore((s) => s.activeWorkspaceId);
@@ -21,6 +29,12 @@ export function OrchestrationPanel() {
   const cancelOrchestration = useOrchestrationStore((s) => s.cancelOrchestration);
   const setExpandedAgentId = useOrchestrationStore((s) => s.setExpandedAgentId);
   const confirmResults = useOrchestrationStore((s) => s.confirmResults);
+  const dismissConfirmation = useOrchestrationStore((s) => s.dismissConfirmation);
+  const approvePlan = useOrchestrationStore((s) => s.approvePlan);
+  const setPlannedOverrides = useOrchestrationStore((s) => s.setPlannedOverrides);
+  const stopSummary = useOrchestrationStore((s) => s.stopSummary);
+  const overrideWorkspaceLock = useOrchestrationStore((s) => s.overrideWorkspaceLock);
+  const setRunPriority = useOrchestrationStore((s) => s.setRunPriority);
   const regenerateAgent = useOrchestrationStore((s) => s.regenerateAgent);
   const regenerateAll = useOrchestrationStore((s) => s.regenerateAll);
   const cancelAgent = useOrchestrationStore((s) => s.cancelAgent);
@@ -28,12 +42,17 @@ export function OrchestrationPanel() {
   const rateTaskRun = useOrchestrationStore((s) => s.rateTaskRun);
   const scheduleTask = useOrchestrationStore((s) => s.scheduleTask);
   const clearSchedule = useOrchestrationStore((s) => s.clearSchedule);
+  const publishRunReport = useOrchestrationStore((s) => s.publishRunReport);
+  const pinOutput = useOrchestrationStore((s) => s.pinOutput);
   const continueOrchestration = useOrchestrationStore((s) => s.continueOrchestration);
   const resumeWithEditedContext = useOrchestrationStore((s) => s.resumeWithEditedContext);
+  const recoverableRunIds = useOrchestrationStore((s) => s.recoverableRunIds);
+  const recoverTaskRuns = useOrchestrationStore((s) => s.recoverTaskRuns);
 
   const [supplementaryText, setSupplementaryText] = useState("");
   const [isSummarizing, setIsSummarizing] = useState(false);
   const [showContextEditor, setShowContextEditor] = useState(false);
+  const [showCancelReasons, setShowCancelReasons] = useState(false);
 
   if (!focused) {
     return (
@@ -43,10 +62,10 @@ export function OrchestrationPanel() {
     );
   }
 
-  const { taskRun, agentTracking, taskPlan, planValidation, streamingAgentId, isAwaitingConfirmation, expandedAgentId } = focused;
+  const { taskRun, agentTracking, taskPlan, planValidation, streamingAgentId, isAwaitingConfirmation, isAwaitingPlanApproval, summaryPreview, isPreempted, expandedAgentId } = focused;
   const status = taskRun.status;
   const isCompleted = status === "completed" || status === "failed" || status === "cancelled";
-  const isTaskRunning = ["pending", "analyzing", "running"].includes(status);
+  const isTaskRunning = ["pending", "waiting_for_workspace", "analyzing", "running"].includes(status);
 
   const statusLabel =
     status === "analyzing"
@@ -55,12 +74,18 @@ export function OrchestrationPanel() {
       ? "Executing Agents"
       : status === "awaiting_confirmation"
       ? "Awaiting Confirmation"
+      : status === "awaiting_plan_approval"
+      ? "Awaiting Plan Approval"
       : status === "completed"
       ? "Completed"
       : status === "failed"
       ? "Failed"
       : status === "cancelled"
-      ? "Cancelled"
+      ? taskRun.cancel_reason && taskRun.cancel_reason !== "user_cancelled"
+        ? `Cancelled: ${CANCEL_REASONS.find((r) => r.value === taskRun.cancel_reason)?.label}`
+        : "Cancelled"
+      : status === "waiting_for_workspace"
+      ? "Waiting for Workspace"
       : "Pending";
 
   return (
@@ -69,7 +94,7 @@ export function OrchestrationPanel() {
       <div className="flex items-center justify-between">
         <div className="flex items-center gap-3">
           <div className="flex items-center gap-2">
-            {!isCompleted && status !== "awaiting_confirmation" && (
+            {!isCompleted && status !== "awaiting_confirmation" && status !== "awaiting_plan_approval" && (
               <Codicon name="loading" className="codicon-modifier-spin text-primary" />
             )}
             {status === "awaiting_confirmation" && (
@@ -87,16 +112,87 @@ export function OrchestrationPanel() {
             </span>
           </div>
         </div>
-        {isTaskRunning && (
+        <div className="flex items-center gap-2">
+          {status === "waiting_for_workspace" && (
+            <button
+              onClick={() => overrideWorkspaceLock(taskRun.id)}
+              title="Start while another run holds the workspace"
+              className="flex items-center gap-1.5 px-3 py-1.5 rounded-lg text-xs font-medium text-amber-500 hover:text-amber-400 hover:bg-amber-500/10 transition-colors"
+            >
+              <Codicon name="unlock" className="text-[14px]" />
+              Run Anyway
+            </button>
+          )}
+          {isTaskRunning && (
+            <div className="relative">
+              <button
+                onClick={() => setShowCancelReasons(!showCancelReasons)}
+                className="flex items-center gap-1.5 px-3 py-1.5 rounded-lg text-xs font-medium text-red-400 hover:text-red-300 hover:bg-red-500/10 transition-colors"
+              >
+                <Codicon name="error" className="text-[14px]" />
+                Cancel
+                <Codicon name="chevron-down" className="text-[12px]" />
+              </button>
+              {showCancelReasons && (
+                <div className="absolute top-[calc(100%+4px)] right-0 w-40 bg-white dark:bg-surface-dark border border-slate-200 dark:border-border-dark rounded-lg shadow-xl z-50 py-1">
+                  {CANCEL_REASONS.map((r) => (
+                    <button
+                      key={r.value}
+                      onClick={() => {
+                        setShowCancelReasons(false);
+                        cancelOrchestration(taskRun.id, r.value);
+                      }}
+                      className="w-full px-3 py-1.5 text-left text-xs text-slate-600 dark:text-gray-300 hover:bg-slate-100 dark:hover:bg-white/5"
+                    >
+                      {r.label}
+                    </button>
+                  ))}
+                </div>
+              )}
+            </div>
+          )}
+        </div>
+      </div>
+
+      {/* Paused for a run of a higher priority */}
+      {isPreempted && !isCompleted && (
+        <div className="flex items-center justify-between gap-3 px-3 py-2 rounded-lg bg-amber-500/10 border border-amber-500/30">
+          <div className="flex items-center gap-2 text-xs text-amber-600 dark:text-amber-400">
+            <Codicon name="debug-pause" className="text-[14px]" />
+            Paused for a higher-priority run; continues when a slot is free
+          </div>
           <button
-            onClick={() => cancelOrchestration(taskRun.id)}
-            className="flex items-center gap-1.5 px-3 py-1.5 rounded-lg text-xs font-medium text-red-400 hover:text-red-300 hover:bg-red-500/10 transition-colors"
+            onClick={() => setRunPriority(taskRun.id, "urgent")}
+            className="px-2.5 py-1 rounded-md text-xs font-medium text-amber-600 dark:text-amber-400 hover:bg-amber-500/15 transition-colors"
           >
-            <Codicon name="error" className="text-[14px]" />
-            Cancel
+            Mark Urgent
           </button>
-        )}
-      </div>
+        </div>
+      )}
+
+      {/* Left unfinished by the last session, held per the startup recovery setting */}
+      {recoverableRunIds.includes(taskRun.id) && (
+        <div className="flex items-center justify-between gap-3 px-3 py-2 rounded-lg bg-amber-500/10 border border-amber-500/30">
+          <div className="flex items-center gap-2 text-xs text-amber-600 dark:text-amber-400">
+            <Codicon name="history" className="text-[14px]" />
+            Interrupted when the app closed; resume it or mark it failed
+          </div>
+          <div className="flex items-center gap-1">
+            <button
+              onClick={() => recoverTaskRuns([taskRun.id], true).catch(() => {})}
+              className="px-2.5 py-1 rounded-md text-xs font-medium text-amber-600 dark:text-amber-400 hover:bg-amber-500/15 transition-colors"
+            >
+              Resume
+            </button>
+            <button
+              onClick={() => recoverTaskRuns([taskRun.id], false).catch(() => {})}
+              className="px-2.5 py-1 rounded-md text-xs font-medium text-red-400 hover:bg-red-500/10 transition-colors"
+            >
+              Mark Failed
+            </button>
+          </div>
+        </div>
+      )}
 
       {/* User prompt */}
       <div className="px-3 py-2 rounded-lg bg-slate-100 dark:bg-white/5 border border-slate-200 dark:border-border-dark/50">
@@ -107,7 +203,18 @@ export function OrchestrationPanel() {
       </div>
 
       {/* Plan */}
-      {taskPlan && <TaskPlanView plan={taskPlan} agentTracking={agentTracking} planValidation={planValidation} />}
+      {taskPlan && (
+        <TaskPlanView
+          plan={taskPlan}
+          agentTracking={agentTracking}
+          planValidation={planValidation}
+          onEditOverrides={
+            isAwaitingPlanApproval
+              ? (index, overrides) => setPlannedOverrides(taskRun.id, index, overrides)
+              : undefined
+          }
+        />
+      )}
 
       {/* Validation warnings */}
       {planValidation && !planValidation.is_valid && (
@@ -148,11 +255,40 @@ export function OrchestrationPanel() {
               isAwaitingConfirmation={isAwaitingConfirmation}
               onRegenerate={() => regenerateAgent(taskRun.id, info.agentId)}
               onCancel={() => cancelAgent(taskRun.id, info.agentId)}
+              onPin={(pin) => pinOutput({ ...pin, task_run_id: taskRun.id })}
             />
           ))}
         </div>
       )}
 
+      <PinnedOutputs taskRunId={taskRun.id} />
+
+      {/* Plan approval bar */}
+      {isAwaitingPlanApproval && (
+        <div className="rounded-lg border-2 border-amber-300 dark:border-amber-700/50 bg-amber-50 dark:bg-amber-950/20 px-4 py-3">
+          <p className="text-sm font-medium text-amber-800 dark:text-amber-300 mb-2">
+            The control hub is not confident in this plan. Check its assumptions before the agents start.
+          </p>
+          <div className="flex items-center gap-2">
+            <button
+              onClick={() => approvePlan(taskRun.id, true)}
+              className="flex items-center gap-1.5 px-4 py-1.5 rounded-lg text-xs font-medium bg-primary text-white hover:bg-primary/90 transition-colors"
+            >
+              <Codicon name="pass-filled" className="text-[14px]" />
+              Approve Plan
+            </button>
+            <button
+              onClick={() => approvePlan(taskRun.id, false)}
+              title="Cancel the task without running the plan"
+              className="flex items-center gap-1.5 px-4 py-1.5 rounded-lg text-xs font-medium text-slate-500 dark:text-gray-400 hover:bg-slate-200 dark:hover:bg-slate-700 transition-colors"
+            >
+              <Codicon name="close" className="text-[14px]" />
+              Reject
+            </button>
+          </div>
+        </div>
+      )}
+
       {/* Confirmation bar */}
       {isAwaitingConfirmation && (
         <div className="rounded-lg border-2 border-amber-300 dark:border-amber-700/50 bg-amber-50 dark:bg-amber-950/20 px-4 py-3">
@@ -202,12 +338,51 @@ export function OrchestrationPanel() {
               <Codicon name="refresh" className="text-[14px]" />
               Re-run All
             </button>
+            <button
+              onClick={() => dismissConfirmation(taskRun.id)}
+              title="Keep the results without a summary and mark the task for review"
+              className="flex items-center gap-1.5 px-4 py-1.5 rounded-lg text-xs font-medium text-slate-500 dark:text-gray-400 hover:bg-slate-200 dark:hover:bg-slate-700 transition-colors"
+            >
+              <Codicon name="eye" className="text-[14px]" />
+              Dismiss for Review
+            </button>
+          </div>
+        </div>
+      )}
+
+      {/* Summary preview, streamed while the hub writes it */}
+      {summaryPreview && !isCompleted && (
+        <div className="rounded-lg border border-primary/30 bg-primary/5 px-4 py-3">
+          <div className="flex items-center gap-2 mb-2">
+            <Codicon name="loading" className="codicon-modifier-spin text-primary text-[14px]" />
+            <p className="text-sm font-medium text-slate-600 dark:text-gray-400">Writing result summary...</p>
+          </div>
+          <div className="max-h-64 overflow-y-auto">
+            <MarkdownContent content={summaryPreview} className="text-sm" />
+          </div>
+          <div className="flex items-center gap-2 mt-3">
+            <button
+              onClick={() => stopSummary(taskRun.id, true)}
+              title="Stop the summary here and keep what it says so far"
+              className="flex items-center gap-1.5 px-4 py-1.5 rounded-lg text-xs font-medium bg-primary text-white hover:bg-primary/90 transition-colors"
+            >
+              <Codicon name="pass-filled" className="text-[14px]" />
+              Accept Now
+            </button>
+            <button
+              onClick={() => stopSummary(taskRun.id, false)}
+              title="Stop the summary and use the built-in summary of the agents' outputs"
+              className="flex items-center gap-1.5 px-4 py-1.5 rounded-lg text-xs font-medium text-slate-500 dark:text-gray-400 hover:bg-slate-200 dark:hover:bg-slate-700 transition-colors"
+            >
+              <Codicon name="close" className="text-[14px]" />
+              Cancel Summary
+            </button>
           </div>
         </div>
       )}
 
-      {/* Summarizing indicator (after confirmation, before completion) */}
-      {isSummarizing && !isAwaitingConfirmation && !isCompleted && (
+      {/* Summarizing indicator (after confirmation, before the summary streams in) */}
+      {isSummarizing && !summaryPreview && !isAwaitingConfirmation && !isCompleted && (
         <div className="rounded-lg border border-primary/30 bg-primary/5 px-4 py-3 flex items-center gap-3">
           <Codicon name="loading" className="codicon-modifier-spin text-primary text-[16px]" />
           <p className="text-sm text-slate-600 dark:text-gray-400">
@@ -226,6 +401,7 @@ export function OrchestrationPanel() {
             onRateComplete={() => dismissTaskRun(taskRun.id)}
             onScheduleTask={scheduleTask}
             onClearSchedule={clearSchedule}
+            onPublishReport={publishRunReport}
           />
           <button
             onClick={() => setShowContextEditor(true)}
@@ -0,0 +1,83 @@
+"use client";
+
+import { useEffect } from "react";
+import { useOrchestrationStore } from "@/stores/orchestrationStore";
+import type { PinnedOutput } from "@/types/orchestration";
+import { Codicon } from "@/components/ui/Codicon";
+
+const EMPTY: PinnedOutput[] = [];
+
+interface PinnedOutputsProps {
+  taskRunId: string;
+}
+
+/** The run's clipboard of pinned outputs, with bulk copy, export and follow-up. */
+export function PinnedOutputs({ taskRunId }: PinnedOutputsProps) {
+  const pinned = useOrchestrationStore((s) => s.pinnedOutputs[taskRunId] ?? EMPTY);
+  const fetchPinnedOutputs = useOrchestrationStore((s) => s.fetchPinnedOutputs);
+  const unpinOutput = useOrchestrationStore((s) => s.unpinOutput);
+  const copyPinnedOutputs = useOrchestrationStore((s) => s.copyPinnedOutputs);
+  const exportPinnedOutputs = useOrchestrationStore((s) => s.exportPinnedOutputs);
+  const attachPinnedOutputs = useOrchestrationStore((s) => s.attachPinnedOutputs);
+
+  useEffect(() => {
+    fetchPinnedOutputs(taskRunId);
+  }, [taskRunId, fetchPinnedOutputs]);
+
+  if (pinned.length === 0) return null;
+
+  const actionClass =
+    "flex items-center gap-1 px-2 py-0.5 rounded text-[11px] font-medium text-slate-500 dark:text-gray-400 hover:bg-slate-200 dark:hover:bg-slate-700 transition-colors";
+
+  return (
+    <div className="flex flex-col gap-2">
+      <div className="flex items-center justify-between">
+        <p className="text-xs font-bold uppercase tracking-wider text-slate-500 dark:text-gray-400">
+          Pinned Outputs ({pinned.length})
+        </p>
+        <div className="flex items-center gap-1">
+          <button onClick={() => copyPinnedOutputs(taskRunId)} className={actionClass}>
+            <Codicon name="copy" className="text-[12px]" />
+            Copy All
+          </button>
+          <button onClick={() => exportPinnedOutputs(taskRunId)} className={actionClass}>
+            <Codicon name="export" className="text-[12px]" />
+            Export
+          </button>
+          <button
+            onClick={() => attachPinnedOutputs(pinned)}
+            title="Append the pinned outputs to the next run's prompt"
+            className={actionClass}
+          >
+            <Codicon name="reply" className="text-[12px]" />
+            Use in Follow-up
+          </button>
+        </div>
+      </div>
+      {pinned.map((p) => (
+        <div
+          key={p.id}
+          className="flex items-start gap-2 rounded-lg border border-slate-200 dark:border-border-dark/50 bg-white dark:bg-surface-dark px-3 py-2"
+        >
+          <Codicon
+            name={p.source === "a2a" ? "call-outgoing" : "pin"}
+            className="text-[12px] text-slate-400 mt-0.5"
+          />
+          <div className="min-w-0 flex-1">
+            <p className="text-xs font-medium text-slate-700 dark:text-gray-300 truncate">{p.label}</p>
+            <p className="text-[11px] text-slate-500 dark:text-gray-500 line-clamp-2 whitespace-pre-wrap">
+              {p.content}
+            </p>
+          </div>
+          <button
+            onClick={() => unpinOutput(taskRunId, p.id)}
+            title="Unpin"
+            className="size-5 flex items-center justify-center rounded hover:bg-slate-200 dark:hover:bg-slate-700 text-slate-400"
+          >
+            <Codicon name="close" className="text-[11px]" />
+          </button>
+        </div>
+      ))}
+    </div>
+  );
+}
@@ -0,0 +1,103 @@
+"use client";
+
+import { useEffect, useState } from "react";
+import type { EnvironmentChange, RunEnvironment } from "@/types/orchestration";
+import { useOrchestrationStore } from "@/stores/orchestrationStore";
+
+interface RunEnvironmentPanelProps {
+  taskRunId: string;
+}
+
+/** What a run ran with, and what changed since another run. */
+export function RunEnvironmentPanel({ taskRunId }: RunEnvironmentPanelProps) {
+  const getRunEnvironment = useOrchestrationStore((s) => s.getRunEnvironment);
+  const compareRunEnvironments = useOrchestrationStore((s) => s.compareRunEnvironments);
+  const taskRuns = useOrchestrationStore((s) => s.taskRuns);
+  const [environment, setEnvironment] = useState<RunEnvironment | null | undefined>(undefined);
+  const [otherRunId, setOtherRunId] = useState("");
+  const [changes, setChanges] = useState<EnvironmentChange[] | null>(null);
+
+  useEffect(() => {
+    setEnvironment(undefined);
+    getRunEnvironment(taskRunId).then(setEnvironment);
+  }, [taskRunId, getRunEnvironment]);
+
+  const compare = async (id: string) => {
+    setOtherRunId(id);
+    setChanges(id ? await compareRunEnvironments(id, taskRunId) : null);
+  };
+
+  if (environment === undefined) {
+    return <p className="text-xs text-slate-400 dark:text-gray-500">Loading environment…</p>;
+  }
+  if (environment === null) {
+    return <p className="text-xs text-slate-400 dark:text-gray-500">No environment was recorded for this run.</p>;
+  }
+
+  return (
+    <div className="space-y-2 text-xs text-slate-600 dark:text-gray-400">
+      <p>
+        Agent Hub {environment.app_version} on {environment.os} · registry {environment.registry_version ?? "unknown"}
+      </p>
+      <table className="w-full">
+        <thead>
+          <tr className="text-left text-[10px] uppercase tracking-wider text-slate-400 dark:text-gray-500">
+            <th className="font-bold py-1">Agent</th>
+            <th className="font-bold py-1">Model</th>
+            <th className="font-bold py-1">Version</th>
+            <th className="font-bold py-1">Runs on</th>
+          </tr>
+        </thead>
+        <tbody>
+          {environment.agents.map((agent) => (
+            <tr key={agent.agent_id} className="border-t border-slate-100 dark:border-border-dark/30">
+              <td className="py-1">{agent.name}{agent.is_control_hub && " (hub)"}</td>
+              <td className="py-1">{agent.model}</td>
+              <td className="py-1">{agent.installed_version ?? agent.registry_version ?? "unknown"}</td>
+              <td className="py-1">{agent.runs_on}</td>
+            </tr>
+          ))}
+        </tbody>
+      </table>
+
+      <div className="flex items-center gap-2 pt-1">
+        <span>Compare with</span>
+        <select
+          value={otherRunId}
+          onChange={(e) => compare(e.target.value)}
+          className="flex-1 rounded border border-slate-200 dark:border-border-dark bg-transparent px-2 py-1"
+        >
+          <option value="">Choose a run…</option>
+          {taskRuns
+            .filter((run) => run.id !== taskRunId)
+            .map((run) => (
+              <option key={run.id} value={run.id}>
+                {run.title} — {new Date(run.created_at).toLocaleString()}
+              </option>
+            ))}
+        </select>
+      </div>
+      {changes && changes.length === 0 && <p>Both runs ran with the same environment.</p>}
+      {changes && changes.length > 0 && (
+        <table className="w-full">
+          <thead>
+            <tr className="text-left text-[10px] uppercase tracking-wider text-slate-400 dark:text-gray-500">
+              <th className="font-bold py-1">Changed</th>
+              <th className="font-bold py-1">Other run</th>
+              <th className="font-bold py-1">This run</th>
+            </tr>
+          </thead>
+          <tbody>
+            {changes.map((change) => (
+              <tr key={change.key} className="border-t border-slate-100 dark:border-border-dark/30">
+                <td className="py-1 font-mono">{change.key}</td>
+                <td className="py-1 text-red-400">{change.before ?? "—"}</td>
+                <td className="py-1 text-emerald-500">{change.after ?? "—"}</td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+    </div>
+  );
+}
@@ -22,6 +22,7 @@ export function TaskHistoryPanel() {
   const scheduleTask = useOrchestrationStore((s) => s.scheduleTask);
   const clearSchedule = useOrchestrationStore((s) => s.clearSchedule);
   const startOrchestration = useOrchestrationStore((s) => s.startOrchestration);
+  const attachRunContext = useOrchestrationStore((s) => s.attachRunContext);
   const resumeWithEditedContext = useOrchestrationStore((s) => s.resumeWithEditedContext);
   const dismissTaskRun = useOrchestrationStore((s) => s.dismissTaskRun);
   const [expandedAgentId, setExpandedAgentId] = useState<string | null>(null);
@@ -182,6 +183,17 @@ export function TaskHistoryPanel() {
               <Codicon name="edit" className="text-[14px]" />
               Edit Context & Resume
             </button>
+            <button
+              onClick={() => {
+                attachRunContext(viewingTaskRun);
+                clearViewingTaskRun();
+              }}
+              title="Plan the next run with this run's summary and outputs"
+              className="flex items-center gap-1.5 px-4 py-1.5 rounded-lg text-xs font-medium bg-slate-200 dark:bg-slate-700 text-slate-700 dark:text-slate-300 hover:bg-slate-300 dark:hover:bg-slate-600 transition-colors"
+            >
+              <Codicon name="reply" className="text-[14px]" />
+              Follow Up
+            </button>
           </div>
           <TaskContextEditor
             open={showContextEditor}
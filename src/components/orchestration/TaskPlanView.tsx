@@ -1,6 +1,13 @@
 "use client";
 
-import type { TaskPlan, AgentTrackingInfo, PlanValidation } from "@/types/orchestration";
+import { useState } from "react";
+import type {
+  TaskPlan,
+  AgentTrackingInfo,
+  PlanValidation,
+  AssignmentOverrides,
+  PermissionProfile,
+} from "@/types/orchestration";
 import { Codicon } from "@/components/ui/Codicon";
 import { useAgentStore } from "@/stores/agentStore";
 import { MarkdownContent } from "@/components/chat/MarkdownContent";
@@ -9,10 +16,113 @@ interface TaskPlanViewProps {
   plan: TaskPlan;
   agentTracking: Record<string, AgentTrackingInfo>;
   planValidation?: PlanValidation | null;
+  /** Set while the plan can be edited; the index is into plan.assignments */
+  onEditOverrides?: (assignmentIndex: number, overrides: AssignmentOverrides | null) => void;
 }
 
-export function TaskPlanView({ plan, agentTracking, planValidation }: TaskPlanViewProps) {
+const PROFILE_LABELS: Record<PermissionProfile, string> = {
+  ask: "Ask",
+  read_only: "Read-only",
+  auto_approve: "Auto-approve",
+};
+
+const chipClass =
+  "px-1.5 py-0.5 rounded text-[9px] font-medium bg-sky-500/10 text-sky-500 border border-sky-500/20";
+
+function OverrideChips({ overrides }: { overrides: AssignmentOverrides }) {
+  return (
+    <>
+      {overrides.model && <span className={chipClass}>{overrides.model}</span>}
+      {overrides.temperature != null && <span className={chipClass}>temp {overrides.temperature}</span>}
+      {overrides.permission_profile && (
+        <span className={chipClass}>{PROFILE_LABELS[overrides.permission_profile]}</span>
+      )}
+      {overrides.system_prompt && (
+        <span className={chipClass} title={overrides.system_prompt}>
+          Custom instructions
+        </span>
+      )}
+    </>
+  );
+}
+
+interface OverridesEditorProps {
+  initial: AssignmentOverrides;
+  onSave: (overrides: AssignmentOverrides | null) => void;
+  onClose: () => void;
+}
+
+function OverridesEditor({ initial, onSave, onClose }: OverridesEditorProps) {
+  const [model, setModel] = useState(initial.model ?? "");
+  const [temperature, setTemperature] = useState(initial.temperature?.toString() ?? "");
+  const [profile, setProfile] = useState<PermissionProfile | "">(initial.permission_profile ?? "");
+  const [systemPrompt, setSystemPrompt] = useState(initial.system_prompt ?? "");
+
+  const save = () => {
+    const overrides: AssignmentOverrides = {};
+    if (model.trim()) overrides.model = model.trim();
+    if (temperature.trim()) overrides.temperature = Number(temperature);
+    if (profile) overrides.permission_profile = profile;
+    if (systemPrompt.trim()) overrides.system_prompt = systemPrompt.trim();
+    onSave(Object.keys(overrides).length > 0 ? overrides : null);
+    onClose();
+  };
+
+  const inputClass =
+    "w-full px-2 py-1 rounded text-[11px] bg-white dark:bg-background-dark border border-slate-200 dark:border-border-dark text-slate-700 dark:text-gray-300";
+
+  return (
+    <div className="mt-1.5 ml-5 flex flex-col gap-1.5">
+      <div className="grid grid-cols-3 gap-1.5">
+        <input value={model} onChange={(e) => setModel(e.target.value)} placeholder="Model" className={inputClass} />
+        <input
+          type="number"
+          min={0}
+          max={2}
+          step={0.1}
+          value={temperature}
+          onChange={(e) => setTemperature(e.target.value)}
+          placeholder="Temperature"
+          className={inputClass}
+        />
+        <select
+          value={profile}
+          onChange={(e) => setProfile(e.target.value as PermissionProfile | "")}
+          className={inputClass}
+        >
+          <option value="">Agent's permissions</option>
+          {(Object.keys(PROFILE_LABELS) as PermissionProfile[]).map((p) => (
+            <option key={p} value={p}>
+              {PROFILE_LABELS[p]}
+            </option>
+          ))}
+        </select>
+      </div>
+      <textarea
+        value={systemPrompt}
+        onChange={(e) => setSystemPrompt(e.target.value)}
+        placeholder="Instructions replacing the agent's system prompt for this step"
+        rows={2}
+        className={inputClass}
+      />
+      <div className="flex justify-end gap-1">
+        <button
+          onClick={onClose}
+          className="px-2 py-0.5 rounded text-[11px] text-slate-500 dark:text-gray-400 hover:bg-slate-200 dark:hover:bg-slate-700"
+        >
+          Cancel
+        </button>
+        <button onClick={save} className="px-2 py-0.5 rounded text-[11px] font-medium bg-primary text-white hover:bg-primary/90">
+          Save
+        </button>
+      </div>
+    </div>
+  );
+}
+
+export function TaskPlanView({ plan, agentTracking, planValidation, onEditOverrides }: TaskPlanViewProps) {
   const agents = useAgentStore((s) => s.agents);
+  const [editingIndex, setEditingIndex] = useState<number | null>(null);
 
   const getAgentName = (agentId: string) => {
     return agents.find((a) => a.id === agentId)?.name ?? "Unknown Agent";
@@ -54,6 +164,7 @@ export function TaskPlanView({ plan, agentTracking, planValidation }: TaskPlanVi
       <div className="flex flex-col gap-1">
         {sorted.map((assignment, idx) => {
           const warnings = getAssignmentWarnings(assignment.agent_id);
+          const planIndex = plan.assignments.indexOf(assignment);
           return (
             <div key={`${assignment.agent_id}-${idx}`}>
               {idx > 0 && (
@@ -70,11 +181,34 @@ export function TaskPlanView({ plan, agentTracking, planValidation }: TaskPlanVi
                   <div className="text-[11px] text-slate-400 dark:text-gray-500 flex-1 min-w-0">
                     <MarkdownContent content={assignment.task_description} className="text-[11px]" />
                   </div>
+                  {onEditOverrides && (
+                    <button
+                      onClick={() => setEditingIndex(editingIndex === planIndex ? null : planIndex)}
+                      title="Agent settings for this step only"
+                      className="size-5 flex items-center justify-center rounded hover:bg-slate-200 dark:hover:bg-slate-700 text-slate-400 shrink-0"
+                    >
+                      <Codicon name="settings-gear" className="text-[12px]" />
+                    </button>
+                  )}
                   <span className="text-[10px] text-slate-300 dark:text-gray-600 font-mono shrink-0 pt-0.5">
                     #{assignment.sequence_order}
                   </span>
                 </div>
 
+                {/* Agent settings overridden for this step */}
+                {assignment.overrides && (
+                  <div className="flex flex-wrap gap-1 mt-1 ml-5">
+                    <OverrideChips overrides={assignment.overrides} />
+                  </div>
+                )}
+                {onEditOverrides && editingIndex === planIndex && (
+                  <OverridesEditor
+                    initial={assignment.overrides ?? {}}
+                    onSave={(overrides) => onEditOverrides(planIndex, overrides)}
+                    onClose={() => setEditingIndex(null)}
+                  />
+                )}
+
                 {/* Matched skills tags */}
                 {assignment.matched_skills && assignment.matched_skills.length > 0 && (
                   <div className="flex flex-wrap gap-1 mt-1 ml-5">
@@ -96,6 +230,20 @@ export function TaskPlanView({ plan, agentTracking, planValidation }: TaskPlanVi
                   </p>
                 )}
 
+                {/* Hub confidence and assumptions */}
+                {assignment.confidence != null && (
+                  <p className="text-[10px] text-slate-400 dark:text-gray-600 mt-1 ml-5">
+                    Confidence {Math.round(assignment.confidence * 100)}%
+                  </p>
+                )}
+                {assignment.assumptions && assignment.assumptions.length > 0 && (
+                  <ul className="list-disc text-[10px] text-amber-500 mt-1 ml-9">
+                    {assignment.assumptions.map((assumption, ai) => (
+                      <li key={ai}>{assumption}</li>
+                    ))}
+                  </ul>
+                )}
+
                 {/* Validation warnings */}
                 {warnings.length > 0 && (
                   <div className="flex flex-wrap gap-1 mt-1 ml-5">
@@ -6,6 +6,7 @@ import { useState } from "react";
 import { Codicon } from "@/components/ui/Codicon";
 import { cn } from "@/lib/cn";
 import { ScheduleDialog } from "./ScheduleDialog";
+import { RunEnvironmentPanel } from "./RunEnvironmentPanel";
 
 function formatTokens(n: number): string {
   if (n === 0) return "--";
@@ -20,9 +21,10 @@ interface TrackingSummaryProps {
   onRateComplete?: () => void;
   onScheduleTask?: (request: ScheduleTaskRequest) => Promise<TaskRun>;
   onClearSchedule?: (taskRunId: string) => Promise<void>;
+  onPublishReport?: (taskRunId: string) => Promise<string | null>;
 }
 
-export function TrackingSummary({ taskRun, agentTracking, onRateTask, onRateComplete, onScheduleTask, onClearSchedule }: TrackingSummaryProps) {
+export function TrackingSummary({ taskRun, agentTracking, onRateTask, onRateComplete, onScheduleTask, onClearSchedule, onPublishReport }: TrackingSummaryProps) {
   const trackingEntries = Object.values(agentTracking);
   const totalDuration = formatDuration(taskRun.total_duration_ms);
   const isCompleted = taskRun.status === "completed";
@@ -31,6 +33,7 @@ export function TrackingSummary({ taskRun, agentTracking, onRateTask, onRateComp
     taskRun.rating ? (taskRun.rating >= 4 ? 'thumbsup' : 'thumbsdown') : null
   );
   const [showScheduleDialog, setShowScheduleDialog] = useState(false);
+  const [showEnvironment, setShowEnvironment] = useState(false);
 
   const handleFeedback = async (feedback: 'thumbsup' | 'thumbsdown') => {
     setUserFeedback(feedback);
@@ -75,6 +78,14 @@ export function TrackingSummary({ taskRun, agentTracking, onRateTask, onRateComp
           >
             {taskRun.status}
           </span>
+          {taskRun.timed_out && (
+            <span
+              className="text-[10px] font-bold uppercase px-2 py-0.5 rounded bg-amber-500/10 text-amber-500"
+              title="The run reached its time limit and was wound down"
+            >
+              timed out
+            </span>
+          )}
           <span className="text-xs text-slate-400 dark:text-gray-500">{totalDuration}</span>
         </div>
       </div>
@@ -176,6 +187,33 @@ export function TrackingSummary({ taskRun, agentTracking, onRateTask, onRateComp
             />
           </button>
 
+          {/* Share report button */}
+          {onPublishReport && (
+            <button
+              onClick={() => onPublishReport(taskRun.id)}
+              className="p-2.5 rounded-lg bg-slate-100 dark:bg-white/5 text-slate-500 dark:text-gray-400 hover:bg-slate-200 dark:hover:bg-white/10 transition-all"
+              aria-label="Share report"
+              title="Save a shareable HTML report of this run"
+            >
+              <Codicon name="export" className="text-[20px]" />
+            </button>
+          )}
+
+          {/* Environment button */}
+          <button
+            onClick={() => setShowEnvironment(!showEnvironment)}
+            className={cn(
+              "p-2.5 rounded-lg transition-all",
+              showEnvironment
+                ? "bg-blue-100 dark:bg-blue-900/30 text-blue-600"
+                : "bg-slate-100 dark:bg-white/5 text-slate-500 dark:text-gray-400 hover:bg-slate-200 dark:hover:bg-white/10"
+            )}
+            aria-label="Environment"
+            title="Agent versions, models and settings this run ran with"
+          >
+            <Codicon name="server-environment" className="text-[20px]" />
+          </button>
+
           {/* Thumbs up button */}
           <button
             onClick={() => handleFeedback('thumbsup')}
@@ -230,6 +268,12 @@ export function TrackingSummary({ taskRun, agentTracking, onRateTask, onRateComp
         </div>
       )}
 
+      {showEnvironment && (
+        <div className="mt-3 pt-3 border-t border-slate-100 dark:border-border-dark/30">
+          <RunEnvironmentPanel taskRunId={taskRun.id} />
+        </div>
+      )}
+
       {/* Schedule Dialog */}
       {showScheduleDialog && onScheduleTask && (
         <ScheduleDialog
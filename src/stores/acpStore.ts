@@ -1,13 +1,18 @@
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
-import { tauriInvoke } from '@/lib/tauri';
-import type { DiscoveredAgent, AgentModel } from '@/types/agent';
+import { tauriInvoke, tauriListen, isTauri } from '@/lib/tauri';
+import type { DiscoveredAgent, AgentModel, AgentLogin, AgentTerminal, AgentTerminalInfo, AgentVersions } from '@/types/agent';
+import { showError, showSuccess } from './toastStore';
 import type { AcpAgentStatus } from '@/types/acp';
 
 interface AcpState {
   discoveredAgents: DiscoveredAgent[];
   agentStatuses: Record<string, string>;
   scanning: boolean;
+  /** Terminals of running agents by terminal id */
+  agentTerminals: Record<string, AgentTerminal>;
+  /** Agents being logged in by agent id */
+  agentLogins: Record<string, AgentLogin>;
 }
 
 interface AcpActions {
@@ -23,6 +28,12 @@ interface AcpActions {
   resumeAcpSession: (sessionId: string) => Promise<{ acpSessionId: string; isLoaded: boolean; models: AgentModel[] }>;
   installAgent: (registryId: string) => Promise<void>;
   uninstallAgent: (registryId: string) => Promise<void>;
+  listAgentVersions: (agentType: string) => Promise<AgentVersions>;
+  /** Install and pin a version, or with null go back to the registry's */
+  setAgentVersion: (agentType: string, version: string | null) => Promise<AgentVersions>;
+  fetchAgentTerminals: () => Promise<void>;
+  /** Send input to an agent's terminal, e.g. the answer to its prompt */
+  writeAgentTerminal: (terminalId: string, data: string) => Promise<void>;
 }
 
 export const useAcpStore = create<AcpState & AcpActions>()(
@@ -31,6 +42,8 @@ export const useAcpStore = create<AcpState & AcpActions>()(
       discoveredAgents: [],
       agentStatuses: {},
       scanning: false,
+      agentTerminals: {},
+      agentLogins: {},
 
       scanForAgents: async () => {
         set({ scanning: true });
@@ -216,6 +229,40 @@ export const useAcpStore = create<AcpState & AcpActions>()(
           throw error;
         }
       },
+
+      listAgentVersions: async (agentType) => {
+        return await tauriInvoke<AgentVersions>('list_agent_versions', { agentType });
+      },
+
+      setAgentVersion: async (agentType, version) => {
+        try {
+          const versions = await tauriInvoke<AgentVersions>('set_agent_version', { agentType, version });
+          showSuccess('Agent 版本已更新', `${versions.name} ${versions.pinned ?? versions.latest}`);
+          return versions;
+        } catch (error) {
+          console.error('Failed to set agent version:', error);
+          showError('设置 Agent 版本失败', error);
+          throw error;
+        }
+      },
+
+      fetchAgentTerminals: async () => {
+        try {
+          const terminals = await tauriInvoke<AgentTerminalInfo[]>('list_agent_terminals');
+          const previous = get().agentTerminals;
+          set({
+            agentTerminals: Object.fromEntries(
+              terminals.map((t) => [t.terminal_id, { ...t, output: previous[t.terminal_id]?.output ?? '' }])
+            ),
+          });
+        } catch (error) {
+          console.error('Failed to list agent terminals:', error);
+        }
+      },
+
+      writeAgentTerminal: async (terminalId, data) => {
+        await tauriInvoke<void>('write_agent_terminal', { terminalId, data });
+      },
     }),
     {
       name: 'acp-store',
@@ -226,3 +273,87 @@ export const useAcpStore = create<AcpState & AcpActions>()(
     }
   )
 );
+
+/** Most characters of terminal output kept per terminal */
+const MAX_TERMINAL_OUTPUT = 4000;
+
+function updateTerminal(terminalId: string, agentId: string, update: (t: AgentTerminal) => Partial<AgentTerminal>) {
+  useAcpStore.setState((state) => {
+    const terminal = state.agentTerminals[terminalId] ?? {
+      terminal_id: terminalId,
+      agent_id: agentId,
+      prompt: null,
+      output: '',
+    };
+    return { agentTerminals: { ...state.agentTerminals, [terminalId]: { ...terminal, ...update(terminal) } } };
+  });
+}
+
+if (isTauri()) {
+  tauriListen<{ terminalId: string; agentId: string; data: string }>('acp:terminal_output', (payload) => {
+    updateTerminal(payload.terminalId, payload.agentId, (t) => ({
+      output: (t.output + payload.data).slice(-MAX_TERMINAL_OUTPUT),
+    }));
+  });
+
+  tauriListen<{ terminalId: string; agentId: string; prompt: string }>('acp:terminal_waiting', (payload) => {
+    updateTerminal(payload.terminalId, payload.agentId, () => ({ prompt: payload.prompt }));
+  });
+
+  tauriListen<{ terminalId: string; agentId: string }>('acp:terminal_resumed', (payload) => {
+    updateTerminal(payload.terminalId, payload.agentId, () => ({ prompt: null }));
+  });
+
+  tauriListen<{ terminalId: string; agentId: string }>('acp:terminal_closed', (payload) => {
+    useAcpStore.setState((state) => {
+      const { [payload.terminalId]: _closed, ...rest } = state.agentTerminals;
+      return { agentTerminals: rest };
+    });
+  });
+
+  tauriListen<{
+    agentId: string;
+    agentName: string;
+    terminalId: string;
+    url: string | null;
+    code: string | null;
+  }>('acp:auth_required', (payload) => {
+    useAcpStore.setState((state) => ({
+      agentLogins: {
+        ...state.agentLogins,
+        [payload.agentId]: {
+          agent_id: payload.agentId,
+          agent_name: payload.agentName,
+          terminal_id: payload.terminalId,
+          url: payload.url,
+          code: payload.code,
+        },
+      },
+    }));
+  });
+
+  tauriListen<{ agentId: string; agentName: string; success: boolean; error: string | null }>(
+    'acp:auth_completed',
+    (payload) => {
+      useAcpStore.setState((state) => {
+        const { [payload.agentId]: _done, ...rest } = state.agentLogins;
+        return { agentLogins: rest };
+      });
+      if (payload.success) {
+        showSuccess(`${payload.agentName} 已登录`);
+      } else {
+        showError(`${payload.agentName} 登录失败`, payload.error);
+      }
+    }
+  );
+
+  // Idle agents are stopped and spawned again on their next prompt
+  tauriListen<{ agent_id: string; reason: string }>('acp:agent_stopped', (payload) => {
+    useAcpStore.setState((state) => ({
+      agentStatuses: { ...state.agentStatuses, [payload.agent_id]: 'stopped' },
+    }));
+  });
+
+  // Catch prompts raised before the window was listening
+  useAcpStore.getState().fetchAgentTerminals();
+}
@@ -0,0 +1,85 @@
+import { create } from 'zustand';
+import { tauriInvoke, tauriListen, isTauri } from '@/lib/tauri';
+import { useWorkspaceStore } from './workspaceStore';
+import type { ActivityEntry, ActivityKind, EventLogQuery, LoggedEvent } from '@/types/activity';
+
+const PAGE_SIZE = 50;
+
+interface ActivityState {
+  entries: ActivityEntry[];
+  kinds: ActivityKind[];
+  hasMore: boolean;
+  loading: boolean;
+}
+
+interface ActivityActions {
+  /** Load the first page for the active workspace */
+  fetchActivity: () => Promise<void>;
+  loadMore: () => Promise<void>;
+  setKinds: (kinds: ActivityKind[]) => Promise<void>;
+  /** Events of kept topics emitted while the window was not listening */
+  fetchLoggedEvents: (query: EventLogQuery) => Promise<LoggedEvent[]>;
+}
+
+async function fetchPage(kinds: ActivityKind[], offset: number): Promise<ActivityEntry[]> {
+  const workspaceId = useWorkspaceStore.getState().activeWorkspaceId;
+  return tauriInvoke<ActivityEntry[]>('list_activity', {
+    query: { workspace_id: workspaceId, kinds, limit: PAGE_SIZE, offset },
+  });
+}
+
+export const useActivityStore = create<ActivityState & ActivityActions>((set, get) => ({
+  entries: [],
+  kinds: [],
+  hasMore: false,
+  loading: false,
+
+  fetchActivity: async () => {
+    if (!isTauri()) return;
+    set({ loading: true });
+    try {
+      const entries = await fetchPage(get().kinds, 0);
+      set({ entries, hasMore: entries.length === PAGE_SIZE, loading: false });
+    } catch (error) {
+      console.error('[ActivityStore] Failed to fetch activity:', error);
+      set({ loading: false });
+    }
+  },
+
+  loadMore: async () => {
+    const { entries, kinds, loading, hasMore } = get();
+    if (!isTauri() || loading || !hasMore) return;
+    set({ loading: true });
+    try {
+      const page = await fetchPage(kinds, entries.length);
+      // Entries recorded since the first page shift the offset; skip repeats
+      const seen = new Set(entries.map((e) => e.id));
+      const fresh = page.filter((e) => !seen.has(e.id));
+      set({ entries: [...entries, ...fresh], hasMore: page.length === PAGE_SIZE, loading: false });
+    } catch (error) {
+      console.error('[ActivityStore] Failed to load more activity:', error);
+      set({ loading: false });
+    }
+  },
+
+  setKinds: async (kinds) => {
+    set({ kinds });
+    await get().fetchActivity();
+  },
+
+  fetchLoggedEvents: async (query) => {
+    if (!isTauri()) return [];
+    return tauriInvoke<LoggedEvent[]>('list_logged_events', { query });
+  },
+}));
+
+// New entries for the active workspace go to the top of the feed
+if (isTauri()) {
+  tauriListen<ActivityEntry>('activity:recorded', (entry) => {
+    const workspaceId = useWorkspaceStore.getState().activeWorkspaceId;
+    const { kinds, entries } = useActivityStore.getState();
+    if (entry.workspace_id !== workspaceId) return;
+    if (kinds.length > 0 && !kinds.includes(entry.kind)) return;
+    useActivityStore.setState({ entries: [entry, ...entries] });
+  });
+}
@@ -3,8 +3,13 @@ import { tauriInvoke, tauriListen, isTauri } from '@/lib/tauri';
 import type {
   AgentConfig,
   CreateAgentRequest,
+  ManifestSyncReport,
+  PermissionPolicy,
+  PermissionPresetInfo,
   UpdateAgentRequest,
 } from '@/types/agent';
+import type { BulkResult } from '@/types/bulk';
+import { errorMessage } from '@/types/error';
 import { useChatStore } from '@/stores/chatStore';
 import { useAcpStore } from '@/stores/acpStore';
 
@@ -32,11 +37,24 @@ interface AgentActions {
   updateAgent: (id: string, req: UpdateAgentRequest) => Promise<AgentConfig>;
   deleteAgent: (id: string) => Promise<void>;
   setControlHub: (id: string) => Promise<AgentConfig>;
+  /** Designate (or clear) the fallback Control Hub */
+  setSecondaryHub: (id: string, secondary: boolean) => Promise<AgentConfig>;
   getControlHub: () => Promise<void>;
   /** Enable a disabled agent (backend performs health check) */
   enableAgent: (id: string) => Promise<AgentConfig>;
   /** Disable an agent (no health check needed) */
   disableAgent: (id: string) => Promise<AgentConfig>;
+  /** Enable or disable several agents in one call (no health check) */
+  setAgentsEnabled: (ids: string[], enabled: boolean) => Promise<BulkResult>;
+  /** Reconcile the workspace's agents with the agents.yaml in its working directory */
+  syncAgentsFromManifest: (workspaceId: string) => Promise<ManifestSyncReport>;
+  generateAgentsMd: (workspaceId: string) => Promise<string>;
+  /** Permission decisions remembered for an agent, or for every agent */
+  listPermissionPolicies: (agentId?: string) => Promise<PermissionPolicy[]>;
+  /** Forget a remembered permission decision */
+  deletePermissionPolicy: (id: string) => Promise<void>;
+  /** Permission presets an agent or a run can be given */
+  listPermissionPresets: () => Promise<PermissionPresetInfo[]>;
   /** Ensure the ACP agent is spawned, initialized, and models are fetched */
   ensureAgentReady: (agentId: string, forceRefresh?: boolean) => Promise<void>;
   /** Force re-fetch models from the agent (ignores cache) */
@@ -187,6 +205,7 @@ export const useAgentStore = create<AgentState & AgentActions>((set, get) => ({
         agents: state.agents.map((a) => ({
           ...a,
           is_control_hub: a.id === id,
+          is_secondary_hub: a.id === id ? false : a.is_secondary_hub,
         })),
       }));
       return updated;
@@ -196,6 +215,25 @@ export const useAgentStore = create<AgentState & AgentActions>((set, get) => ({
     }
   },
 
+  setSecondaryHub: async (id, secondary) => {
+    try {
+      const updated = await tauriInvoke<AgentConfig>(
+        secondary ? 'set_secondary_hub' : 'clear_secondary_hub',
+        { agentId: id },
+      );
+      set((state) => ({
+        agents: state.agents.map((a) => ({
+          ...a,
+          is_secondary_hub: a.id === id ? updated.is_secondary_hub : secondary ? false : a.is_secondary_hub,
+        })),
+      }));
+      return updated;
+    } catch (error) {
+      console.error('Failed to set secondary hub:', error);
+      throw error;
+    }
+  },
+
   getControlHub: async () => {
     try {
       const { useWorkspaceStore } = await import('@/stores/workspaceStore');
@@ -243,6 +281,34 @@ export const useAgentStore = create<AgentState & AgentActions>((set, get) => ({
     }
   },
 
+  setAgentsEnabled: async (ids, enabled) => {
+    const result = await tauriInvoke<BulkResult>('set_agents_enabled', { agentIds: ids, enabled });
+    await get().fetchAgents();
+    return result;
+  },
+
+  syncAgentsFromManifest: async (workspaceId) => {
+    const report = await tauriInvoke<ManifestSyncReport>('sync_agents_from_manifest', { workspaceId });
+    await get().fetchAgents();
+    return report;
+  },
+
+  generateAgentsMd: async (workspaceId) => {
+    return tauriInvoke<string>('generate_agents_md', { workspaceId });
+  },
+
+  listPermissionPolicies: async (agentId) => {
+    return tauriInvoke<PermissionPolicy[]>('list_permission_policies', { agentId: agentId ?? null });
+  },
+
+  deletePermissionPolicy: async (id) => {
+    await tauriInvoke<void>('delete_permission_policy', { id });
+  },
+
+  listPermissionPresets: async () => {
+    return tauriInvoke<PermissionPresetInfo[]>('list_permission_presets');
+  },
+
   ensureAgentReady: async (agentId, forceRefresh) => {
     if (!forceRefresh && get().readyAgentIds.includes(agentId)) {
       console.log('[AgentStore] Agent already ready, skipping:', agentId);
@@ -304,10 +370,10 @@ export const useAgentStore = create<AgentState & AgentActions>((set, get) => ({
           : [...s.readyAgentIds, agentId],
       }));
     } catch (error) {
-      const errorMessage = error instanceof Error ? error.message : String(error);
-      console.error('[AgentStore] Failed to ensure agent ready:', errorMessage);
+      const message = errorMessage(error);
+      console.error('[AgentStore] Failed to ensure agent ready:', message);
       if (get().selectedAgentId === agentId) {
-        set({ agentError: errorMessage });
+        set({ agentError: message });
       }
       set((s) => ({
         readyAgentIds: s.readyAgentIds.filter((id) => id !== agentId),
@@ -365,4 +431,17 @@ if (isTauri()) {
       }
     }
   );
+
+  // A run found every agent disabled and re-enabled the transiently disabled ones
+  tauriListen<{ action: string; agentIds: string[] }>('orchestration:agents_recovery', (payload) => {
+    if (payload.action !== 'reenabled_agents') return;
+    const state = useAgentStore.getState();
+    useAgentStore.setState({
+      agents: state.agents.map((a) =>
+        payload.agentIds.includes(a.id)
+          ? { ...a, is_enabled: true, disabled_reason: null, disabled_transient: false }
+          : a
+      ),
+    });
+  });
 }
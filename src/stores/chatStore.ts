@@ -5,6 +5,9 @@ import type {
   Session,
   CreateSessionRequest,
 } from '@/types/chat';
+import type { PromptRef } from '@/types/prompt';
+import { succeededIds, type BulkResult } from '@/types/bulk';
+import { errorMessage } from '@/types/error';
 import { useEffect } from 'react';
 import { useAcpStore } from './acpStore';
 import { showError } from './toastStore';
@@ -32,6 +35,8 @@ interface ChatState {
   streamedContent: string;
   toolCalls: any[];
   pendingPermission: PermissionRequest | null;
+  /** Pinned messages of the current session, oldest first */
+  pinnedMessages: ChatMessage[];
 }
 
 interface ChatActions {
@@ -39,9 +44,11 @@ interface ChatActions {
   createSession: (req: CreateSessionRequest) => Promise<Session>;
   ensureSession: (agentId: string) => Promise<string>;
   deleteSession: (id: string) => Promise<void>;
+  deleteSessions: (ids: string[]) => Promise<BulkResult>;
+  summarizeSession: (sessionId: string) => Promise<Session>;
   selectSession: (id: string | null) => void;
   fetchMessages: (sessionId: string) => Promise<void>;
-  sendPrompt: (sessionId: string, content: string) => Promise<void>;
+  sendPrompt: (sessionId: string, content: string, prompt?: PromptRef) => Promise<void>;
   cancelPrompt: (sessionId: string) => Promise<void>;
   appendStreamChunk: (chunk: string) => void;
   completeMessage: (msg: ChatMessage) => void;
@@ -52,6 +59,9 @@ interface ChatActions {
   respondToPermission: (agentId: string, optionId: string, userMessage?: string) => Promise<void>;
   clearPendingPermission: () => void;
   endSession: (sessionId: string) => Promise<void>;
+  fetchPinnedMessages: (sessionId: string) => Promise<void>;
+  pinMessage: (messageId: string, pinned: boolean) => Promise<void>;
+  reactToMessage: (messageId: string, reaction: string, reacted: boolean) => Promise<void>;
 }
 
 export const useChatStore = create<ChatState & ChatActions>((set, get) => ({
@@ -62,6 +72,7 @@ export const useChatStore = create<ChatState & ChatActions>((set, get) => ({
   streamedContent: '',
   toolCalls: [],
   pendingPermission: null,
+  pinnedMessages: [],
 
   fetchSessions: async (agentId) => {
     try {
@@ -173,9 +184,32 @@ export const useChatStore = create<ChatState & ChatActions>((set, get) => ({
     }
   },
 
+  deleteSessions: async (ids) => {
+    await Promise.all(ids.map((id) => get().endSession(id).catch(() => {})));
+    const result = await tauriInvoke<BulkResult>('delete_sessions', { ids });
+    const deleted = new Set(succeededIds(result));
+    set((state) => {
+      const currentDeleted = state.currentSessionId !== null && deleted.has(state.currentSessionId);
+      return {
+        sessions: state.sessions.filter((s) => !deleted.has(s.id)),
+        currentSessionId: currentDeleted ? null : state.currentSessionId,
+        messages: currentDeleted ? [] : state.messages,
+      };
+    });
+    return result;
+  },
+
+  summarizeSession: async (sessionId) => {
+    const session = await tauriInvoke<Session>('summarize_session', { sessionId });
+    set((state) => ({
+      sessions: state.sessions.map((s) => (s.id === session.id ? session : s)),
+    }));
+    return session;
+  },
+
   selectSession: (id) => {
     console.log('[ChatStore] selectSession called with:', id);
-    set({ currentSessionId: id, messages: [], streamedContent: '' });
+    set({ currentSessionId: id, messages: [], streamedContent: '', pinnedMessages: [] });
   },
 
   fetchMessages: async (sessionId) => {
@@ -187,7 +221,7 @@ export const useChatStore = create<ChatState & ChatActions>((set, get) => ({
     }
   },
 
-  sendPrompt: async (sessionId, content) => {
+  sendPrompt: async (sessionId, content, prompt) => {
     console.log('[ChatStore] sendPrompt called - sessionId:', sessionId, 'content:', content);
 
     // Validate sessionId
@@ -206,7 +240,7 @@ export const useChatStore = create<ChatState & ChatActions>((set, get) => ({
 
     set({ isStreaming: true, streamedContent: '', toolCalls: [] });
     try {
-      const result = await tauriInvoke<ChatMessage>('send_prompt', { sessionId, content });
+      const result = await tauriInvoke<ChatMessage>('send_prompt', { sessionId, content, prompt });
       console.log('[ChatStore] sendPrompt result:', result);
       // Add user message from backend (it's already saved to DB with proper ID)
       set((state) => ({ messages: [...state.messages, result] }));
@@ -214,7 +248,7 @@ export const useChatStore = create<ChatState & ChatActions>((set, get) => ({
       console.error('[ChatStore] Failed to send prompt:', error);
       showError('发送消息失败', error);
       // Show error to user
-      const errorMsg = error instanceof Error ? error.message : String(error);
+      const errorMsg = errorMessage(error);
       get().addToolCall({
         id: `error-${Date.now()}`,
         name: 'Error',
@@ -275,8 +309,11 @@ export const useChatStore = create<ChatState & ChatActions>((set, get) => ({
       tool_calls_json: toolCallsJson,
     };
 
+    // The reply is saved while it streams, so a reload may already hold it
     set((state) => ({
-      messages: [...state.messages, completedMsg],
+      messages: state.messages.some((m) => m.id === completedMsg.id)
+        ? state.messages.map((m) => (m.id === completedMsg.id ? completedMsg : m))
+        : [...state.messages, completedMsg],
       isStreaming: false,
       streamedContent: '',
       toolCalls: [],
@@ -326,6 +363,50 @@ export const useChatStore = create<ChatState & ChatActions>((set, get) => ({
     set((state) => ({ messages: [...state.messages, userMsg] }));
   },
 
+  fetchPinnedMessages: async (sessionId) => {
+    try {
+      const pinnedMessages = await tauriInvoke<ChatMessage[]>('list_pinned_messages', { sessionId });
+      if (get().currentSessionId === sessionId) set({ pinnedMessages });
+    } catch (error) {
+      console.error('[ChatStore] Failed to fetch pinned messages:', error);
+    }
+  },
+
+  pinMessage: async (messageId, pinned) => {
+    try {
+      const updated = await tauriInvoke<ChatMessage>('pin_message', { messageId, pinned });
+      set((state) => {
+        // Keep the displayed content: a completed message shows the streamed text
+        const messages = state.messages.map((m) =>
+          m.id === updated.id ? { ...m, pinned_at: updated.pinned_at } : m
+        );
+        const pinnedMessages = pinned
+          ? [...state.pinnedMessages.filter((m) => m.id !== updated.id), updated].sort((a, b) =>
+              a.created_at.localeCompare(b.created_at)
+            )
+          : state.pinnedMessages.filter((m) => m.id !== updated.id);
+        return { messages, pinnedMessages };
+      });
+    } catch (error) {
+      console.error('[ChatStore] Failed to pin message:', error);
+      showError('置顶消息失败', error);
+    }
+  },
+
+  reactToMessage: async (messageId, reaction, reacted) => {
+    try {
+      const updated = await tauriInvoke<ChatMessage>('react_to_message', { messageId, reaction, reacted });
+      set((state) => ({
+        messages: state.messages.map((m) =>
+          m.id === updated.id ? { ...m, reactions: updated.reactions } : m
+        ),
+      }));
+    } catch (error) {
+      console.error('[ChatStore] Failed to react to message:', error);
+      showError('添加表情回应失败', error);
+    }
+  },
+
   respondToPermission: async (agentId: string, optionId: string, userMessage?: string) => {
     const state = get();
     const requestId = state.pendingPermission?.id;
@@ -6,7 +6,17 @@ import type {
   UpdateChatToolRequest,
   ChatToolMessage,
   ChatToolContact,
+  ChatToolEscalation,
+  ChatToolMetrics,
+  ContactImport,
+  ContactListFormat,
+  DeliveryStatus,
+  MetricsRange,
+  RunNotificationSubscription,
+  TranscriptFormat,
 } from '@/types/chatTool';
+import { succeededIds, type BulkResult } from '@/types/bulk';
+import { showWarning } from './toastStore';
 
 /** Resolve the currently selected chat tool ID for the active workspace. */
 function getSelectedId(byWorkspace: Record<string, string | null>): string | null {
@@ -36,13 +46,43 @@ interface ChatToolActions {
   getSelectedChatToolId: () => string | null;
   createChatTool: (req: CreateChatToolRequest) => Promise<ChatTool>;
   updateChatTool: (id: string, req: UpdateChatToolRequest) => Promise<ChatTool>;
+  cloneChatTool: (id: string, name?: string) => Promise<ChatTool>;
   deleteChatTool: (id: string) => Promise<void>;
   startChatTool: (id: string) => Promise<void>;
   stopChatTool: (id: string) => Promise<void>;
+  stopAllChatTools: () => Promise<BulkResult>;
   logoutChatTool: (id: string) => Promise<void>;
   fetchMessages: (chatToolId: string) => Promise<void>;
   fetchContacts: (chatToolId: string) => Promise<void>;
   setContactBlocked: (contactId: string, blocked: boolean) => Promise<void>;
+  /** DM a contact when task runs complete or fail; null unsubscribes it */
+  setContactRunNotifications: (
+    contactId: string,
+    subscription: RunNotificationSubscription | null
+  ) => Promise<void>;
+  setContactQuota: (
+    contactId: string,
+    maxRepliesPerDay: number | null,
+    maxTokensPerDay: number | null
+  ) => Promise<void>;
+  resetContactUsage: (contactId: string) => Promise<void>;
+  /** Suspend auto-replies to a contact for a while; 0 minutes resumes them */
+  snoozeContact: (chatToolId: string, contactId: string, durationMinutes: number) => Promise<void>;
+  /** Take over a conversation from the Control Hub, or hand it back */
+  setTakeover: (chatToolId: string, contactId: string, active: boolean) => Promise<void>;
+  /** Write a transcript of the conversation with a contact; returns the file path */
+  exportHistory: (
+    chatToolId: string,
+    contactId: string,
+    format: TranscriptFormat,
+    range?: { since?: string; until?: string }
+  ) => Promise<string>;
+  /** Load a CSV or JSON contact list into a chat tool */
+  importContacts: (chatToolId: string, path: string) => Promise<ContactImport>;
+  /** Write a chat tool's contacts to a CSV or JSON list; returns the file path */
+  exportContacts: (chatToolId: string, format: ContactListFormat) => Promise<string>;
+  /** Response times, Control Hub latency and queue depth over a range */
+  getMetrics: (chatToolId: string, range: MetricsRange) => Promise<ChatToolMetrics>;
   sendMessage: (chatToolId: string, toId: string, content: string) => Promise<void>;
   getQrCode: (id: string) => Promise<void>;
 }
@@ -114,6 +154,12 @@ export const useChatToolStore = create<ChatToolState & ChatToolActions>(
       return updated;
     },
 
+    cloneChatTool: async (id, name) => {
+      const chatTool = await tauriInvoke<ChatTool>('clone_chat_tool', { id, name: name ?? null });
+      set((state) => ({ chatTools: [...state.chatTools, chatTool] }));
+      return chatTool;
+    },
+
     deleteChatTool: async (id) => {
       await tauriInvoke('delete_chat_tool', { id });
       const selectedId = get().getSelectedChatToolId();
@@ -149,6 +195,17 @@ export const useChatToolStore = create<ChatToolState & ChatToolActions>(
       await tauriInvoke('stop_chat_tool', { id });
     },
 
+    stopAllChatTools: async () => {
+      const result = await tauriInvoke<BulkResult>('stop_all_chat_tools');
+      const stopped = new Set(succeededIds(result));
+      set((state) => ({
+        chatTools: state.chatTools.map((t) =>
+          stopped.has(t.id) ? { ...t, status: 'stopped', status_message: null } : t
+        ),
+      }));
+      return result;
+    },
+
     logoutChatTool: async (id) => {
       // Clear QR code state so new one will be shown
       set({ qrCodeUrl: null, qrCodeImage: null });
@@ -192,6 +249,82 @@ export const useChatToolStore = create<ChatToolState & ChatToolActions>(
       }));
     },
 
+    setContactRunNotifications: async (contactId, subscription) => {
+      const updated = await tauriInvoke<ChatToolContact>('set_chat_tool_contact_run_notifications', {
+        contactId,
+        subscription,
+      });
+      set((state) => ({
+        contacts: state.contacts.map((c) => (c.id === contactId ? updated : c)),
+      }));
+    },
+
+    setContactQuota: async (contactId, maxRepliesPerDay, maxTokensPerDay) => {
+      const updated = await tauriInvoke<ChatToolContact>('set_chat_tool_contact_quota', {
+        contactId,
+        maxRepliesPerDay,
+        maxTokensPerDay,
+      });
+      set((state) => ({
+        contacts: state.contacts.map((c) => (c.id === contactId ? updated : c)),
+      }));
+    },
+
+    resetContactUsage: async (contactId) => {
+      const updated = await tauriInvoke<ChatToolContact>('reset_chat_tool_contact_usage', { contactId });
+      set((state) => ({
+        contacts: state.contacts.map((c) => (c.id === contactId ? updated : c)),
+      }));
+    },
+
+    snoozeContact: async (chatToolId, contactId, durationMinutes) => {
+      const updated = await tauriInvoke<ChatToolContact>('snooze_contact', {
+        chatToolId,
+        contactId,
+        durationMinutes,
+      });
+      set((state) => ({
+        contacts: state.contacts.map((c) => (c.id === contactId ? updated : c)),
+      }));
+    },
+
+    setTakeover: async (chatToolId, contactId, active) => {
+      const updated = await tauriInvoke<ChatToolContact>('set_chat_tool_takeover', {
+        chatToolId,
+        contactId,
+        active,
+      });
+      set((state) => ({
+        contacts: state.contacts.map((c) => (c.id === contactId ? updated : c)),
+      }));
+    },
+
+    exportHistory: async (chatToolId, contactId, format, range) => {
+      return tauriInvoke<string>('export_chat_tool_history', {
+        chatToolId,
+        contactId,
+        format,
+        since: range?.since ?? null,
+        until: range?.until ?? null,
+      });
+    },
+
+    importContacts: async (chatToolId, path) => {
+      const result = await tauriInvoke<ContactImport>('import_chat_tool_contacts', { chatToolId, path });
+      if (get().getSelectedChatToolId() === chatToolId) {
+        await get().fetchContacts(chatToolId);
+      }
+      return result;
+    },
+
+    exportContacts: async (chatToolId, format) => {
+      return tauriInvoke<string>('export_chat_tool_contacts', { chatToolId, format });
+    },
+
+    getMetrics: async (chatToolId, range) => {
+      return tauriInvoke<ChatToolMetrics>('get_chat_tool_metrics', { chatToolId, range });
+    },
+
     sendMessage: async (chatToolId, toId, content) => {
       await tauriInvoke('send_chat_tool_message', {
         chatToolId,
@@ -318,6 +451,59 @@ if (isTauri()) {
     }
   );
 
+  tauriListen<{
+    chatToolId: string;
+    messageId: string;
+    status: DeliveryStatus;
+    error: string | null;
+    attempts: number;
+  }>(
+    'chat_tool:message_status',
+    (payload) => {
+      const state = useChatToolStore.getState();
+      if (state.getSelectedChatToolId() === payload.chatToolId) {
+        useChatToolStore.setState({
+          messages: state.messages.map((m) =>
+            m.id === payload.messageId
+              ? {
+                  ...m,
+                  delivery_status: payload.status,
+                  delivery_error: payload.error,
+                  delivery_attempts: payload.attempts,
+                }
+              : m
+          ),
+        });
+      }
+    }
+  );
+
+  tauriListen<{ chatToolId: string; messageId: string; reason: string }>(
+    'chat_tool:message_filtered',
+    (payload) => {
+      const state = useChatToolStore.getState();
+      if (state.getSelectedChatToolId() === payload.chatToolId) {
+        useChatToolStore.setState({
+          messages: state.messages.map((m) =>
+            m.id === payload.messageId ? { ...m, filter_reason: payload.reason } : m
+          ),
+        });
+      }
+    }
+  );
+
+  tauriListen<{ chatToolId: string; contactId: string }>(
+    'chat_tool:contact_snooze_ended',
+    (payload) => {
+      const state = useChatToolStore.getState();
+      useChatToolStore.setState({
+        contacts: state.contacts.map((c) =>
+          c.id === payload.contactId ? { ...c, snoozed_until: null } : c
+        ),
+      });
+    }
+  );
+
   tauriListen<{ chatToolId: string; error: string }>(
     'chat_tool:error',
     (payload) => {
@@ -331,4 +517,15 @@ if (isTauri()) {
       });
     }
   );
+
+  tauriListen<{ chatToolId: string; escalation: ChatToolEscalation }>(
+    'chat_tool:escalated',
+    (payload) => {
+      const tool = useChatToolStore.getState().chatTools.find((t) => t.id === payload.chatToolId);
+      showWarning(
+        `${tool?.name ?? '聊天工具'}：消息已转交人工处理`,
+        `${payload.escalation.external_sender_name ?? 'Unknown'} — ${payload.escalation.reason}`
+      );
+    }
+  );
 }
@@ -1,19 +1,35 @@
 import { create } from 'zustand';
 import { tauriInvoke, tauriListen, isTauri } from '@/lib/tauri';
 import { useWorkspaceStore } from './workspaceStore';
-import { showError } from './toastStore';
+import { showError, showInfo, showSuccess, showWarning } from './toastStore';
+import { succeededIds, type BulkResult } from '@/types/bulk';
 import type {
   TaskRun,
   TaskAssignment,
   TaskPlan,
   AgentTrackingInfo,
   OrchPermissionRequest,
+  FileWriteReview,
   OrchToolCall,
   ScheduleTaskRequest,
+  ScheduledTask,
+  CancelReason,
+  CancellationStats,
   PlanValidation,
   TaskRunState,
+  QueuedRun,
+  RunPriority,
+  TaskRunSearch,
+  StartedTaskRun,
+  PermissionScope,
+  PinnedOutput,
+  PinOutputRequest,
+  AssignmentOverrides,
+  RunEnvironment,
+  EnvironmentChange,
 } from '@/types/orchestration';
-import type { SkillDiscoveryResult } from '@/types/agent';
+import type { PermissionPreset, SkillDiscoveryResult } from '@/types/agent';
+import type { AppNotification } from '@/types/notification';
 
 // ---------------------------------------------------------------------------
 // State shape
@@ -30,6 +46,8 @@ interface OrchestrationState {
   taskRuns: TaskRun[];
   /** Permission requests awaiting user response */
   pendingOrchPermissions: OrchPermissionRequest[];
+  /** Agent file writes awaiting approval */
+  pendingFileWrites: FileWriteReview[];
   /** Task run being viewed from Kanban (read-only historical view) */
   viewingTaskRun: TaskRun | null;
   viewingAssignments: TaskAssignment[];
@@ -39,25 +57,87 @@ interface OrchestrationState {
   discoveredSkills: SkillDiscoveryResult | null;
   /** Task run IDs restored on app restart that need user attention */
   restoredTaskRunIds: string[];
+  /** Runs left unfinished by the last session, held until the user resumes or fails them */
+  recoverableRunIds: string[];
+  /** Task runs waiting for a free slot, in start order */
+  queuedRuns: QueuedRun[];
+  /** Earlier run the next started run is planned with */
+  attachedContextRun: TaskRun | null;
+  /** Clipboard of pinned outputs keyed by taskRunId */
+  pinnedOutputs: Record<string, PinnedOutput[]>;
+  /** Pinned outputs appended to the next started run's prompt */
+  attachedPinnedOutputs: PinnedOutput[];
+  /** Permission preset for the agents of the next started run; null keeps their own */
+  runPermissionPreset: PermissionPreset | null;
 }
 
 interface OrchestrationActions {
   startOrchestration: (prompt: string) => Promise<void>;
-  cancelOrchestration: (taskRunId?: string) => Promise<void>;
-  cancelAgent: (taskRunId: string, agentId: string) => Promise<void>;
+  /** Plan the next started run with an earlier run's summary and outputs, or stop doing so */
+  attachRunContext: (run: TaskRun | null) => void;
+  /** Answer the permission requests of the next started runs with a preset, or with each agent's own */
+  setRunPermissionPreset: (preset: PermissionPreset | null) => void;
+  /** Append pinned outputs to the next started run's prompt, or stop doing so */
+  attachPinnedOutputs: (pinned: PinnedOutput[]) => void;
+  cancelOrchestration: (taskRunId?: string, reason?: CancelReason) => Promise<void>;
+  /** Emergency stop: cancel every in-progress run of the active workspace */
+  cancelAllTaskRuns: (reason?: CancelReason) => Promise<BulkResult>;
+  cancelAgent: (taskRunId: string, agentId: string, reason?: CancelReason) => Promise<void>;
+  /** Cancelled runs and assignments per reason, for the active workspace */
+  fetchCancellationStats: () => Promise<CancellationStats[]>;
+  setAssignmentCaps: (
+    taskRunId: string,
+    assignmentId: string,
+    maxTokensOut: number | null,
+    maxCost: number | null
+  ) => Promise<void>;
+  /** Re-execute an assignment of an ended run; the retry is added to the run */
+  retryAssignment: (assignmentId: string) => Promise<TaskAssignment | null>;
   continueOrchestration: (supplementaryPrompt: string) => Promise<void>;
   dismissTaskRun: (taskRunId?: string) => void;
   fetchTaskRuns: () => Promise<void>;
+  fetchQueue: () => Promise<void>;
+  moveQueuedRun: (taskRunId: string, position: number) => Promise<void>;
+  dropQueuedRun: (taskRunId: string) => Promise<void>;
+  overrideWorkspaceLock: (taskRunId: string) => Promise<void>;
+  setRunPriority: (taskRunId: string, priority: RunPriority) => Promise<void>;
+  searchTaskRuns: (search: TaskRunSearch) => Promise<TaskRun[]>;
+  setTaskRunLabels: (taskRunId: string, labels: string[]) => Promise<TaskRun>;
+  /** Labels in use in the active workspace */
+  fetchTaskRunLabels: () => Promise<string[]>;
+  archiveTaskRuns: (taskRunIds: string[], archived: boolean) => Promise<BulkResult>;
   fetchAssignments: (taskRunId: string) => Promise<void>;
   confirmResults: (taskRunId: string) => Promise<void>;
+  approvePlan: (taskRunId: string, approved: boolean) => Promise<void>;
+  /** Change a planned assignment's agent settings while the plan awaits approval */
+  setPlannedOverrides: (taskRunId: string, assignmentIndex: number, overrides: AssignmentOverrides | null) => Promise<void>;
+  /** Stop the run's summary early, keeping the text so far if accepted */
+  stopSummary: (taskRunId: string, accept: boolean) => Promise<void>;
+  /** Save the run as a shareable HTML report where the user picks; null when cancelled */
+  publishRunReport: (taskRunId: string) => Promise<string | null>;
+  /** What the run ran with; null for runs from before snapshots were kept */
+  getRunEnvironment: (taskRunId: string) => Promise<RunEnvironment | null>;
+  /** What changed in the environment from one run to another */
+  compareRunEnvironments: (taskRunId: string, otherTaskRunId: string) => Promise<EnvironmentChange[]>;
+  fetchPinnedOutputs: (taskRunId: string) => Promise<void>;
+  pinOutput: (request: PinOutputRequest) => Promise<void>;
+  unpinOutput: (taskRunId: string, id: string) => Promise<void>;
+  /** Copy every pinned output of the run to the system clipboard */
+  copyPinnedOutputs: (taskRunId: string) => Promise<void>;
+  /** Save the run's pinned outputs as markdown where the user picks; null when cancelled */
+  exportPinnedOutputs: (taskRunId: string) => Promise<string | null>;
+  dismissConfirmation: (taskRunId: string) => Promise<void>;
   regenerateAgent: (taskRunId: string, agentId: string) => Promise<void>;
   regenerateAll: (taskRunId: string) => Promise<void>;
   respondToOrchPermission: (
     taskRunId: string,
     agentId: string,
     requestId: string,
-    optionId: string
+    optionId: string,
+    remember?: PermissionScope
   ) => Promise<void>;
+  respondFileWrite: (writeId: string, approved: boolean) => Promise<void>;
+  respondAllFileWrites: (taskRunId: string, approved: boolean) => Promise<void>;
   rateTaskRun: (taskRunId: string, rating: number) => Promise<void>;
   setExpandedAgentId: (agentId: string | null) => void;
   setFocusedTaskRunId: (id: string | null) => void;
@@ -67,9 +147,15 @@ interface OrchestrationActions {
   pauseScheduledTask: (taskRunId: string) => Promise<void>;
   resumeScheduledTask: (taskRunId: string) => Promise<void>;
   clearSchedule: (taskRunId: string) => Promise<void>;
+  /** Scheduled tasks of a workspace, or of all workspaces */
+  listScheduledTasks: (workspaceId?: string | null) => Promise<ScheduledTask[]>;
+  /** Fire an occurrence of a schedule now */
+  triggerScheduleNow: (scheduleId: string) => Promise<void>;
   discoverWorkspaceSkills: (forceRefresh?: boolean) => Promise<SkillDiscoveryResult | null>;
   restoreIncompleteTaskRun: () => Promise<void>;
   clearRestoredTaskRunId: (taskRunId: string) => void;
+  /** Resume the held unfinished runs, or mark them failed */
+  recoverTaskRuns: (taskRunIds: string[], resume: boolean) => Promise<BulkResult>;
   resumeWithEditedContext: (taskRunId: string, editedContext: string) => Promise<void>;
   reset: () => void;
 }
@@ -145,6 +231,9 @@ function createPlaceholderTaskRunState(taskRunId: string): TaskRunState {
     taskPlan: null,
     planValidation: null,
     isAwaitingConfirmation: false,
+    isAwaitingPlanApproval: false,
+    summaryPreview: null,
+    isPreempted: false,
     expandedAgentId: null,
   };
 }
@@ -160,6 +249,9 @@ function createTaskRunState(taskRun: TaskRun): TaskRunState {
     taskPlan: null,
     planValidation: null,
     isAwaitingConfirmation: false,
+    isAwaitingPlanApproval: false,
+    summaryPreview: null,
+    isPreempted: false,
     expandedAgentId: null,
   };
 }
@@ -236,25 +328,43 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
     isOrchestrating: false,
     taskRuns: [],
     pendingOrchPermissions: [],
+    pendingFileWrites: [],
     viewingTaskRun: null,
     viewingAssignments: [],
     viewingAgentTracking: {},
     viewingTaskPlan: null,
     discoveredSkills: null,
     restoredTaskRunIds: [],
+    recoverableRunIds: [],
+    queuedRuns: [],
+    attachedContextRun: null,
+    pinnedOutputs: {},
+    attachedPinnedOutputs: [],
+    runPermissionPreset: null,
 
     startOrchestration: async (prompt: string) => {
       set({ discoveredSkills: null });
 
       try {
         const workspaceId = useWorkspaceStore.getState().activeWorkspaceId;
-        const taskRun = await tauriInvoke<TaskRun>('start_orchestration', {
+        const { duplicate, ...taskRun } = await tauriInvoke<StartedTaskRun>('start_orchestration', {
           request: {
             user_prompt: prompt,
             title: '',
             workspace_id: workspaceId,
+            attach_run_context: get().attachedContextRun?.id,
+            pinned_output_ids: get().attachedPinnedOutputs.map((p) => p.id),
+            permission_preset: get().runPermissionPreset ?? undefined,
           },
         });
+        set({ attachedContextRun: null, attachedPinnedOutputs: [] });
+        if (duplicate) {
+          showInfo('该任务已在运行', taskRun.title);
+          if (get().taskRunStates[taskRun.id]) {
+            set({ focusedTaskRunId: taskRun.id });
+            return;
+          }
+        }
         set((state) => {
           // Merge with any existing placeholder state that was created by
           // events arriving before this invoke resolved (race condition fix)
@@ -273,6 +383,9 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
                 planValidation: existing.planValidation ?? fresh.planValidation,
                 expandedAgentId: existing.expandedAgentId ?? fresh.expandedAgentId,
                 isAwaitingConfirmation: existing.isAwaitingConfirmation,
+                isAwaitingPlanApproval: existing.isAwaitingPlanApproval,
+                summaryPreview: existing.summaryPreview,
+                isPreempted: existing.isPreempted,
                 // Always use the real TaskRun from the invoke (has full data)
                 taskRun: {
                   ...taskRun,
@@ -299,7 +412,7 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
       }
     },
 
-    cancelOrchestration: async (taskRunId?: string) => {
+    cancelOrchestration: async (taskRunId?: string, reason: CancelReason = 'user_cancelled') => {
       const { focusedTaskRunId, taskRunStates } = get();
       const targetId = taskRunId ?? focusedTaskRunId;
       if (!targetId) return;
@@ -308,7 +421,7 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
       if (!trs) return;
 
       try {
-        await tauriInvoke('cancel_orchestration', { taskRunId: targetId });
+        await tauriInvoke('cancel_orchestration', { taskRunId: targetId, reason });
       } catch (error) {
         console.error('[Orchestration] Failed to cancel:', error);
         showError('取消编排失败', error);
@@ -316,7 +429,7 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
 
       set((state) => {
         const updated = updateTaskRunState(state, targetId, (cur) => ({
-          taskRun: { ...cur.taskRun, status: 'cancelled' },
+          taskRun: { ...cur.taskRun, status: 'cancelled', cancel_reason: reason },
           isAwaitingConfirmation: false,
         }));
         const newStates = updated.taskRunStates ?? state.taskRunStates;
@@ -327,15 +440,78 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
       });
     },
 
-    cancelAgent: async (taskRunId: string, agentId: string) => {
+    cancelAllTaskRuns: async (reason: CancelReason = 'user_cancelled') => {
+      const workspaceId = useWorkspaceStore.getState().activeWorkspaceId;
+      const result = await tauriInvoke<BulkResult>('cancel_all_running', { workspaceId, reason });
+      const cancelled = new Set(succeededIds(result));
+      set((state) => {
+        const taskRunStates = { ...state.taskRunStates };
+        for (const id of cancelled) {
+          const cur = taskRunStates[id];
+          if (!cur) continue;
+          taskRunStates[id] = {
+            ...cur,
+            taskRun: { ...cur.taskRun, status: 'cancelled', cancel_reason: reason },
+            isAwaitingConfirmation: false,
+          };
+        }
+        return {
+          taskRunStates,
+          taskRuns: state.taskRuns.map((r) => (cancelled.has(r.id) ? { ...r, status: 'cancelled', cancel_reason: reason } : r)),
+          isOrchestrating: computeIsOrchestrating(taskRunStates),
+        };
+      });
+      return result;
+    },
+
+    cancelAgent: async (taskRunId: string, agentId: string, reason: CancelReason = 'user_cancelled') => {
       try {
-        await tauriInvoke('cancel_agent', { taskRunId, agentId });
+        await tauriInvoke('cancel_agent', { taskRunId, agentId, reason });
       } catch (error) {
         console.error('[Orchestration] Failed to cancel agent:', error);
         showError('取消 Agent 失败', error);
       }
     },
 
+    fetchCancellationStats: async () => {
+      const workspaceId = useWorkspaceStore.getState().activeWorkspaceId;
+      return tauriInvoke<CancellationStats[]>('get_cancellation_stats', { workspaceId });
+    },
+
+    setAssignmentCaps: async (taskRunId, assignmentId, maxTokensOut, maxCost) => {
+      try {
+        const updated = await tauriInvoke<TaskAssignment>('set_assignment_caps', {
+          assignmentId,
+          maxTokensOut,
+          maxCost,
+        });
+        set((state) =>
+          updateTaskRunState(state, taskRunId, (trs) => ({
+            assignments: trs.assignments.map((a) => (a.id === updated.id ? updated : a)),
+          }))
+        );
+      } catch (error) {
+        console.error('[Orchestration] Failed to set assignment caps:', error);
+        showError('设置任务上限失败', error);
+      }
+    },
+
+    retryAssignment: async (assignmentId: string) => {
+      try {
+        const retry = await tauriInvoke<TaskAssignment>('retry_assignment', { assignmentId });
+        set((state) =>
+          state.viewingTaskRun?.id === retry.task_run_id
+            ? { viewingAssignments: [...state.viewingAssignments, retry] }
+            : {}
+        );
+        return retry;
+      } catch (error) {
+        console.error('[Orchestration] Failed to retry assignment:', error);
+        showError('重试任务失败', error);
+        return null;
+      }
+    },
+
     continueOrchestration: async (supplementaryPrompt: string) => {
       const { focusedTaskRunId, taskRunStates } = get();
       if (!focusedTaskRunId) return;
@@ -430,6 +606,78 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
       }
     },
 
+    fetchQueue: async () => {
+      try {
+        const queuedRuns = await tauriInvoke<QueuedRun[]>('list_queue');
+        set({ queuedRuns });
+      } catch (error) {
+        console.error('[Orchestration] Failed to fetch the run queue:', error);
+      }
+    },
+
+    moveQueuedRun: async (taskRunId: string, position: number) => {
+      const queuedRuns = await tauriInvoke<QueuedRun[]>('move_queued_run', { taskRunId, position });
+      set({ queuedRuns });
+    },
+
+    dropQueuedRun: async (taskRunId: string) => {
+      await tauriInvoke('drop_queued_run', { taskRunId });
+      set((state) => ({
+        queuedRuns: state.queuedRuns.filter((run) => run.task_run_id !== taskRunId),
+        taskRuns: state.taskRuns.map((tr) =>
+          tr.id === taskRunId ? { ...tr, status: 'cancelled' } : tr
+        ),
+      }));
+    },
+
+    overrideWorkspaceLock: async (taskRunId: string) => {
+      try {
+        await tauriInvoke('override_workspace_lock', { taskRunId });
+      } catch (error) {
+        console.error('[Orchestration] Failed to override workspace lock:', error);
+        showError('强制启动失败', error);
+      }
+    },
+
+    setRunPriority: async (taskRunId: string, priority: RunPriority) => {
+      try {
+        const queuedRuns = await tauriInvoke<QueuedRun[]>('set_run_priority', { taskRunId, priority });
+        set({ queuedRuns });
+      } catch (error) {
+        console.error('[Orchestration] Failed to set run priority:', error);
+        showError('设置优先级失败', error);
+      }
+    },
+
+    searchTaskRuns: async (search: TaskRunSearch) => {
+      return tauriInvoke<TaskRun[]>('search_task_runs', { search });
+    },
+
+    setTaskRunLabels: async (taskRunId: string, labels: string[]) => {
+      const updated = await tauriInvoke<TaskRun>('set_task_run_labels', { taskRunId, labels });
+      set((state) => ({
+        taskRuns: state.taskRuns.map((tr) => (tr.id === taskRunId ? updated : tr)),
+        viewingTaskRun: state.viewingTaskRun?.id === taskRunId ? updated : state.viewingTaskRun,
+      }));
+      return updated;
+    },
+
+    fetchTaskRunLabels: async () => {
+      const workspaceId = useWorkspaceStore.getState().activeWorkspaceId;
+      return tauriInvoke<string[]>('list_task_run_labels', { workspaceId });
+    },
+
+    archiveTaskRuns: async (taskRunIds: string[], archived: boolean) => {
+      const result = await tauriInvoke<BulkResult>('archive_task_runs', { taskRunIds, archived });
+      if (archived) {
+        const done = new Set(succeededIds(result));
+        set((state) => ({ taskRuns: state.taskRuns.filter((tr) => !done.has(tr.id)) }));
+      } else {
+        await get().fetchTaskRuns();
+      }
+      return result;
+    },
+
     fetchAssignments: async (taskRunId: string) => {
       try {
         const assignments = await tauriInvoke<TaskAssignment[]>('get_task_assignments', {
@@ -460,6 +708,145 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
       }
     },
 
+    approvePlan: async (taskRunId: string, approved: boolean) => {
+      try {
+        await tauriInvoke('approve_plan', { taskRunId, approved });
+        set((state) => {
+          const updated = updateTaskRunState(state, taskRunId, (cur) => ({
+            isAwaitingPlanApproval: false,
+            taskRun: { ...cur.taskRun, status: approved ? 'running' : 'cancelled' },
+          }));
+          const newStates = updated.taskRunStates ?? state.taskRunStates;
+          return { ...updated, isOrchestrating: computeIsOrchestrating(newStates) };
+        });
+      } catch (error) {
+        console.error('[Orchestration] Failed to answer plan approval:', error);
+      }
+    },
+
+    setPlannedOverrides: async (taskRunId: string, assignmentIndex: number, overrides: AssignmentOverrides | null) => {
+      try {
+        const plan = await tauriInvoke<TaskPlan>('set_planned_overrides', { taskRunId, assignmentIndex, overrides });
+        set((state) => updateTaskRunState(state, taskRunId, () => ({ taskPlan: plan })));
+      } catch (error) {
+        showError('保存覆盖设置失败', error);
+      }
+    },
+
+    stopSummary: async (taskRunId: string, accept: boolean) => {
+      try {
+        await tauriInvoke('stop_summary', { taskRunId, accept });
+      } catch (error) {
+        console.error('[Orchestration] Failed to stop summary:', error);
+        showError('停止总结失败', error);
+      }
+    },
+
+    publishRunReport: async (taskRunId: string) => {
+      try {
+        const path = await tauriInvoke<string | null>('publish_run_report', { taskRunId });
+        if (path) showSuccess('报告已导出', path);
+        return path;
+      } catch (error) {
+        console.error('[Orchestration] Failed to publish run report:', error);
+        showError('导出报告失败', error);
+        return null;
+      }
+    },
+
+    getRunEnvironment: async (taskRunId: string) => {
+      try {
+        return await tauriInvoke<RunEnvironment | null>('get_run_environment', { taskRunId });
+      } catch (error) {
+        console.error('[Orchestration] Failed to load run environment:', error);
+        return null;
+      }
+    },
+
+    compareRunEnvironments: async (taskRunId: string, otherTaskRunId: string) => {
+      try {
+        return await tauriInvoke<EnvironmentChange[]>('compare_run_environments', { taskRunId, otherTaskRunId });
+      } catch (error) {
+        console.error('[Orchestration] Failed to compare run environments:', error);
+        showError('对比运行环境失败', error);
+        return [];
+      }
+    },
+
+    fetchPinnedOutputs: async (taskRunId: string) => {
+      try {
+        const pinned = await tauriInvoke<PinnedOutput[]>('list_pinned_outputs', { taskRunId });
+        set((state) => ({ pinnedOutputs: { ...state.pinnedOutputs, [taskRunId]: pinned } }));
+      } catch (error) {
+        console.error('[Orchestration] Failed to fetch pinned outputs:', error);
+      }
+    },
+
+    pinOutput: async (request: PinOutputRequest) => {
+      try {
+        const pinned = await tauriInvoke<PinnedOutput>('pin_output', { request });
+        set((state) => ({
+          pinnedOutputs: {
+            ...state.pinnedOutputs,
+            [pinned.task_run_id]: [...(state.pinnedOutputs[pinned.task_run_id] ?? []), pinned],
+          },
+        }));
+        showSuccess('已固定', pinned.label);
+      } catch (error) {
+        console.error('[Orchestration] Failed to pin output:', error);
+        showError('固定输出失败', error);
+      }
+    },
+
+    unpinOutput: async (taskRunId: string, id: string) => {
+      try {
+        await tauriInvoke('unpin_output', { id });
+        set((state) => ({
+          pinnedOutputs: {
+            ...state.pinnedOutputs,
+            [taskRunId]: (state.pinnedOutputs[taskRunId] ?? []).filter((p) => p.id !== id),
+          },
+          attachedPinnedOutputs: state.attachedPinnedOutputs.filter((p) => p.id !== id),
+        }));
+      } catch (error) {
+        console.error('[Orchestration] Failed to unpin output:', error);
+        showError('取消固定失败', error);
+      }
+    },
+
+    copyPinnedOutputs: async (taskRunId: string) => {
+      const pinned = get().pinnedOutputs[taskRunId] ?? [];
+      if (pinned.length === 0) return;
+      const text = pinned.map((p) => `### ${p.label}\n${p.content.trim()}`).join('\n\n');
+      try {
+        await navigator.clipboard.writeText(text);
+        showSuccess('已复制', `${pinned.length} 项输出`);
+      } catch (error) {
+        console.error('[Orchestration] Failed to copy pinned outputs:', error);
+        showError('复制失败', error);
+      }
+    },
+
+    exportPinnedOutputs: async (taskRunId: string) => {
+      try {
+        const path = await tauriInvoke<string | null>('export_pinned_outputs', { taskRunId });
+        if (path) showSuccess('固定输出已导出', path);
+        return path;
+      } catch (error) {
+        console.error('[Orchestration] Failed to export pinned outputs:', error);
+        showError('导出固定输出失败', error);
+        return null;
+      }
+    },
+
+    dismissConfirmation: async (taskRunId: string) => {
+      try {
+        await tauriInvoke('dismiss_confirmation', { taskRunId });
+      } catch (error) {
+        console.error('[Orchestration] Failed to dismiss confirmation:', error);
+      }
+    },
+
     regenerateAgent: async (taskRunId: string, agentId: string) => {
       try {
         set((state) => updateTaskRunState(state, taskRunId, () => ({ isAwaitingConfirmation: false })));
@@ -492,10 +879,17 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
       taskRunId: string,
       agentId: string,
       requestId: string,
-      optionId: string
+      optionId: string,
+      remember?: PermissionScope
     ) => {
       try {
-        await tauriInvoke('respond_orch_permission', { taskRunId, agentId, requestId, optionId });
+        await tauriInvoke('respond_orch_permission', {
+          taskRunId,
+          agentId,
+          requestId,
+          optionId,
+          remember: remember ?? null,
+        });
         set((state) => ({
           pendingOrchPermissions: state.pendingOrchPermissions.filter(
             (p) => !(p.taskRunId === taskRunId && p.requestId === requestId)
@@ -507,6 +901,30 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
       }
     },
 
+    respondFileWrite: async (writeId: string, approved: boolean) => {
+      try {
+        await tauriInvoke('respond_file_write', { writeId, approved });
+        set((state) => ({
+          pendingFileWrites: state.pendingFileWrites.filter((w) => w.write_id !== writeId),
+        }));
+      } catch (error) {
+        console.error('[Orchestration] Failed to respond to file write:', error);
+        showError('文件写入审批失败', error);
+      }
+    },
+
+    respondAllFileWrites: async (taskRunId: string, approved: boolean) => {
+      try {
+        await tauriInvoke<number>('respond_all_file_writes', { taskRunId, approved });
+        set((state) => ({
+          pendingFileWrites: state.pendingFileWrites.filter((w) => w.task_run_id !== taskRunId),
+        }));
+      } catch (error) {
+        console.error('[Orchestration] Failed to respond to file writes:', error);
+        showError('文件写入审批失败', error);
+      }
+    },
+
     setExpandedAgentId: (agentId: string | null) => {
       const { focusedTaskRunId } = get();
       if (!focusedTaskRunId) return;
@@ -581,6 +999,7 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
             streamedContent: '',
             output: assignment.output_text || undefined,
             assignmentId: assignment.id,
+            cached: assignment.cached,
             toolCalls: [],
           };
         }
@@ -597,6 +1016,18 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
       }
     },
 
+    attachRunContext: (run) => {
+      set({ attachedContextRun: run });
+    },
+
+    setRunPermissionPreset: (preset) => {
+      set({ runPermissionPreset: preset });
+    },
+
+    attachPinnedOutputs: (pinned) => {
+      set({ attachedPinnedOutputs: pinned });
+    },
+
     clearViewingTaskRun: () => {
       set({
         viewingTaskRun: null,
@@ -657,9 +1088,29 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
       }));
     },
 
+    listScheduledTasks: async (workspaceId) => {
+      try {
+        return await tauriInvoke<ScheduledTask[]>('list_scheduled_tasks', { workspaceId: workspaceId ?? null });
+      } catch (error) {
+        console.error('[Orchestration] Failed to list scheduled tasks:', error);
+        return [];
+      }
+    },
+
+    triggerScheduleNow: async (scheduleId: string) => {
+      try {
+        await tauriInvoke('trigger_schedule_now', { scheduleId });
+        showSuccess('计划任务已触发');
+      } catch (error) {
+        console.error('[Orchestration] Failed to trigger schedule:', error);
+        showError('触发计划任务失败', error);
+      }
+    },
+
     discoverWorkspaceSkills: async (forceRefresh?: boolean) => {
       try {
         const result = await tauriInvoke<SkillDiscoveryResult>('discover_workspace_skills', {
+          workspaceId: useWorkspaceStore.getState().activeWorkspaceId,
           forceRefresh: forceRefresh ?? false,
         });
         set({ discoveredSkills: result });
@@ -675,10 +1126,12 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
         const workspaceId = useWorkspaceStore.getState().activeWorkspaceId;
         if (!workspaceId) return; // No workspace selected — skip restoring
         const runs = await tauriInvoke<TaskRun[]>('list_task_runs', { workspaceId });
-        set({ taskRuns: runs });
+        const held = await tauriInvoke<TaskRun[]>('list_recoverable_runs');
+        const heldIds = new Set(held.map((r) => r.id));
+        set({ taskRuns: runs, recoverableRunIds: [...heldIds] });
 
         // Find ALL non-completed task runs that the user might want to continue
-        const incompleteStatuses = ['pending', 'running', 'analyzing', 'awaiting_confirmation', 'failed'];
+        const incompleteStatuses = ['pending', 'waiting_for_workspace', 'running', 'analyzing', 'awaiting_confirmation', 'awaiting_plan_approval', 'failed'];
         const incompleteRuns = runs.filter((r) => incompleteStatuses.includes(r.status));
 
         if (incompleteRuns.length === 0) return;
@@ -689,8 +1142,9 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
         for (const incomplete of incompleteRuns) {
           console.log('[Orchestration] Restoring incomplete task run:', incomplete.id, incomplete.status);
 
-          // Tasks with running/analyzing/pending status cannot be resumed after app restart
-          if (['running', 'analyzing', 'pending'].includes(incomplete.status)) {
+          // Tasks with running/analyzing/pending status cannot be resumed after app restart,
+          // unless the backend holds them for the user to decide
+          if (!heldIds.has(incomplete.id) && ['running', 'analyzing', 'pending'].includes(incomplete.status)) {
             incomplete.status = 'failed';
             try {
               await tauriInvoke('update_task_run_status', {
@@ -733,6 +1187,7 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
               streamedContent: '',
               output: assignment.output_text || undefined,
               assignmentId: assignment.id,
+              cached: assignment.cached,
               toolCalls: [],
             };
           }
@@ -746,6 +1201,9 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
             streamingAgentId: null,
             streamedContent: '',
             isAwaitingConfirmation: incomplete.status === 'awaiting_confirmation',
+            isAwaitingPlanApproval: incomplete.status === 'awaiting_plan_approval',
+            summaryPreview: null,
+            isPreempted: false,
             expandedAgentId: null,
           };
 
@@ -769,6 +1227,37 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
       }));
     },
 
+    recoverTaskRuns: async (taskRunIds: string[], resume: boolean) => {
+      try {
+        const result = await tauriInvoke<BulkResult>('recover_task_runs', { taskRunIds, resume });
+        const done = new Set(succeededIds(result));
+        set((state) => {
+          const taskRunStates = { ...state.taskRunStates };
+          if (!resume) {
+            for (const id of done) {
+              const cur = taskRunStates[id];
+              if (cur) taskRunStates[id] = { ...cur, taskRun: { ...cur.taskRun, status: 'failed' } };
+            }
+          }
+          return {
+            taskRunStates,
+            taskRuns: resume ? state.taskRuns : state.taskRuns.map((r) => (done.has(r.id) ? { ...r, status: 'failed' } : r)),
+            recoverableRunIds: state.recoverableRunIds.filter((id) => !done.has(id)),
+          };
+        });
+        if (done.size > 0) {
+          showSuccess(resume ? '任务已恢复' : '任务已标记为失败', `${done.size} 个未完成的任务`);
+        }
+        if (done.size < taskRunIds.length) {
+          showError('部分任务处理失败', `${taskRunIds.length - done.size} 个任务未能处理`);
+        }
+        return result;
+      } catch (error) {
+        showError('恢复任务失败', String(error));
+        throw error;
+      }
+    },
+
     resumeWithEditedContext: async (taskRunId: string, editedContext: string) => {
       const { taskRunStates } = get();
       const trs = taskRunStates[taskRunId];
@@ -829,6 +1318,7 @@ export const useOrchestrationStore = create<OrchestrationState & OrchestrationAc
         focusedTaskRunId: null,
         isOrchestrating: false,
         pendingOrchPermissions: [],
+        pendingFileWrites: [],
         discoveredSkills: null,
         restoredTaskRunIds: [],
       });
@@ -882,6 +1372,17 @@ export function initializeOrchestrationListeners() {
     );
   }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
 
+  // orchestration:plan_revised — the hub replaced the rest of the plan after a failure
+  tauriListen<any>('orchestration:plan_revised', (payload) => {
+    console.log('[Orchestration] Plan revised:', payload);
+    const taskRunId = payload?.taskRunId;
+    const plan = payload?.plan as TaskPlan | undefined;
+    if (!taskRunId || !plan) return;
+    useOrchestrationStore.setState((state) =>
+      upsertTaskRunState(state, taskRunId, () => ({ taskPlan: plan }))
+    );
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
   // orchestration:plan_validated
   tauriListen<any>('orchestration:plan_validated', (payload) => {
     console.log('[Orchestration] Plan validated:', payload);
@@ -955,6 +1456,35 @@ export function initializeOrchestrationListeners() {
     );
   }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
 
+  // orchestration:summary_chunk
+  tauriListen<any>('orchestration:summary_chunk', (payload) => {
+    const taskRunId = payload?.taskRunId;
+    if (!taskRunId || !payload?.text) return;
+    useOrchestrationStore.setState((state) =>
+      upsertTaskRunState(state, taskRunId, (trs) => ({
+        summaryPreview: (trs.summaryPreview ?? '') + payload.text,
+      }))
+    );
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // orchestration:preempted — paused to give its slot to a run of a higher priority
+  tauriListen<any>('orchestration:preempted', (payload) => {
+    const taskRunId = payload?.taskRunId;
+    if (!taskRunId) return;
+    useOrchestrationStore.setState((state) =>
+      updateTaskRunState(state, taskRunId, () => ({ isPreempted: true }))
+    );
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // orchestration:preemption_ended
+  tauriListen<any>('orchestration:preemption_ended', (payload) => {
+    const taskRunId = payload?.taskRunId;
+    if (!taskRunId) return;
+    useOrchestrationStore.setState((state) =>
+      updateTaskRunState(state, taskRunId, () => ({ isPreempted: false }))
+    );
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
   // orchestration:agent_tool_call
   tauriListen<any>('orchestration:agent_tool_call', (payload) => {
     const taskRunId = payload?.taskRunId;
@@ -971,6 +1501,8 @@ export function initializeOrchestrationListeners() {
           status: payload.status || 'running',
           rawInput: payload.rawInput || undefined,
           rawOutput: payload.rawOutput || undefined,
+          rawInputArtifactId: payload.rawInputArtifactId || undefined,
+          rawOutputArtifactId: payload.rawOutputArtifactId || undefined,
         };
 
         const existingCalls = existing.toolCalls || [];
@@ -1087,6 +1619,7 @@ export function initializeOrchestrationListeners() {
             acpSessionId: payload.acpSessionId || existing.acpSessionId,
             output: payload.output || existing.streamedContent || undefined,
             assignmentId: payload.assignmentId || existing.assignmentId,
+            cached: payload.cached ?? existing.cached,
           },
         };
 
@@ -1103,6 +1636,26 @@ export function initializeOrchestrationListeners() {
     );
   }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
 
+  // orchestration:agent_artifact — an image or generated file in an agent's output
+  tauriListen<any>('orchestration:agent_artifact', (payload) => {
+    const taskRunId = payload?.taskRunId;
+    if (!taskRunId || !payload?.agentId || !payload?.artifact) return;
+    useOrchestrationStore.setState((state) =>
+      upsertTaskRunState(state, taskRunId, (trs) => {
+        const existing = trs.agentTracking[payload.agentId];
+        if (!existing) return {};
+        const artifacts = existing.artifacts ?? [];
+        if (artifacts.some((a) => a.id === payload.artifact.id)) return {};
+        return {
+          agentTracking: {
+            ...trs.agentTracking,
+            [payload.agentId]: { ...existing, artifacts: [...artifacts, payload.artifact] },
+          },
+        };
+      })
+    );
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
   // orchestration:awaiting_confirmation
   tauriListen<any>('orchestration:awaiting_confirmation', (payload) => {
     console.log('[Orchestration] Awaiting confirmation:', payload);
@@ -1116,6 +1669,43 @@ export function initializeOrchestrationListeners() {
     );
   }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
 
+  // orchestration:awaiting_plan_approval — the hub is not confident in its plan
+  tauriListen<any>('orchestration:awaiting_plan_approval', (payload) => {
+    console.log('[Orchestration] Awaiting plan approval:', payload);
+    const taskRunId = payload?.taskRunId;
+    if (!taskRunId) return;
+    useOrchestrationStore.setState((state) =>
+      upsertTaskRunState(state, taskRunId, (cur) => ({
+        isAwaitingPlanApproval: true,
+        taskRun: { ...cur.taskRun, status: 'awaiting_plan_approval' as const },
+      }))
+    );
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // orchestration:agents_recovery — every agent was disabled when the run started
+  tauriListen<any>('orchestration:agents_recovery', (payload) => {
+    console.log('[Orchestration] Agents recovery:', payload);
+    if (payload?.action === 'reenabled_agents') {
+      showWarning('智能体已全部禁用', `已重新启用 ${payload.agentIds?.length ?? 0} 个因临时错误被禁用的智能体`);
+    } else if (payload?.action === 'run_on_hub') {
+      showWarning('智能体已全部禁用', '批准计划后将由 Control Hub 独立完成整个任务');
+    }
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // orchestration:needs_review
+  tauriListen<any>('orchestration:needs_review', (payload) => {
+    const taskRunId = payload?.taskRunId;
+    if (!taskRunId) return;
+    useOrchestrationStore.setState((state) => {
+      const updated = updateTaskRunState(state, taskRunId, (cur) => ({
+        isAwaitingConfirmation: false,
+        taskRun: { ...cur.taskRun, status: 'needs_review' as const },
+      }));
+      const newStates = updated.taskRunStates ?? state.taskRunStates;
+      return { ...updated, isOrchestrating: computeIsOrchestrating(newStates) };
+    });
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
   // orchestration:orch_permission
   tauriListen<any>('orchestration:orch_permission', (payload) => {
     console.log('[Orchestration] Permission request:', payload);
@@ -1127,6 +1717,7 @@ export function initializeOrchestrationListeners() {
         sessionId: payload.sessionId || '',
         toolCall: payload.toolCall || undefined,
         options: payload.options || [],
+        requestKey: payload.requestKey || undefined,
       };
       useOrchestrationStore.setState((state) => ({
         pendingOrchPermissions: [...state.pendingOrchPermissions, newPerm],
@@ -1134,11 +1725,60 @@ export function initializeOrchestrationListeners() {
     }
   }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
 
+  // orchestration:orch_permission_batched — a similar request joined a waiting prompt
+  tauriListen<any>('orchestration:orch_permission_batched', (payload) => {
+    if (!payload?.taskRunId) return;
+    useOrchestrationStore.setState((state) => ({
+      pendingOrchPermissions: state.pendingOrchPermissions.map((p) =>
+        p.taskRunId === payload.taskRunId && String(p.requestId) === String(payload.requestId)
+          ? { ...p, batchCount: payload.count }
+          : p
+      ),
+    }));
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // orchestration:file_write_review
+  tauriListen<any>('orchestration:file_write_review', (payload) => {
+    const review: FileWriteReview = {
+      write_id: payload.writeId,
+      task_run_id: payload.taskRunId,
+      agent_id: payload.agentId,
+      path: payload.path,
+      diff: payload.diff,
+      is_new_file: payload.isNewFile,
+      created_at: new Date().toISOString(),
+    };
+    useOrchestrationStore.setState((state) => ({
+      pendingFileWrites: [...state.pendingFileWrites, review],
+    }));
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // orchestration:file_write_resolved (also fires on timeout or cancellation)
+  tauriListen<any>('orchestration:file_write_resolved', (payload) => {
+    useOrchestrationStore.setState((state) => ({
+      pendingFileWrites: state.pendingFileWrites.filter((w) => w.write_id !== payload.writeId),
+    }));
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // orchestration:file_conflict — two in-flight agents wrote the same path
+  tauriListen<any>('orchestration:file_conflict', (payload) => {
+    console.warn('[Orchestration] File conflict:', payload);
+    const action = payload.mode === 'serialize' ? '已暂停后写入的 Agent，等待前一个完成' : '请检查两者的修改';
+    showWarning('文件冲突', `${payload.otherAgentId} 与 ${payload.agentId} 同时修改了 ${payload.path}，${action}`);
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
   // orchestration:feedback
   tauriListen<any>('orchestration:feedback', (payload) => {
     console.log('[Orchestration] Feedback:', payload);
   }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
 
+  // orchestration:feedback_action — the hub asked for a correction
+  tauriListen<any>('orchestration:feedback_action', (payload) => {
+    console.log('[Orchestration] Feedback action:', payload);
+    const label = payload.action === 'rerun_agent' ? '重新运行' : '追加任务';
+    showInfo('Control Hub 修正', `${label}: ${payload.agentName} (${payload.correctionsUsed}/${payload.maxCorrections})`);
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
   // orchestration:completed
   tauriListen<any>('orchestration:completed', (payload) => {
     console.log('[Orchestration] Completed:', payload);
@@ -1147,9 +1787,11 @@ export function initializeOrchestrationListeners() {
     useOrchestrationStore.setState((state) => {
       const updated = upsertTaskRunState(state, taskRunId, (cur) => ({
         isAwaitingConfirmation: false,
+        summaryPreview: null,
         taskRun: {
           ...cur.taskRun,
           status: 'completed' as const,
+          timed_out: !!payload?.timedOut,
           result_summary: payload?.summary || null,
           total_tokens_in: payload?.totalTokensIn ?? 0,
           total_tokens_out: payload?.totalTokensOut ?? 0,
@@ -1163,6 +1805,26 @@ export function initializeOrchestrationListeners() {
     });
   }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
 
+  // orchestration:timed_out — the run reached its time limit and is wound down
+  tauriListen<any>('orchestration:timed_out', (payload) => {
+    const notStarted = payload?.notStarted ?? 0;
+    showWarning('任务已超时', notStarted > 0 ? `${notStarted} 个计划中的任务未开始，正在生成摘要` : '正在收尾并生成摘要');
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // orchestration:recoverable — runs left unfinished by the last session, found on launch
+  tauriListen<any>('orchestration:recoverable', (payload) => {
+    const ids: string[] = (payload?.runs ?? []).map((r: any) => r.taskRunId);
+    if (ids.length === 0) return;
+    if (payload.policy === 'prompt') {
+      useOrchestrationStore.setState((state) => ({
+        recoverableRunIds: [...new Set([...state.recoverableRunIds, ...ids])],
+      }));
+      showWarning('有未完成的任务', `${ids.length} 个未完成的任务等待恢复或标记为失败`);
+    } else if (payload.policy === 'mark_failed') {
+      showInfo('未完成的任务已标记为失败', `${ids.length} 个任务`);
+    }
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
   // orchestration:error
   tauriListen<any>('orchestration:error', (payload) => {
     let errorMsg = 'Unknown orchestration error';
@@ -1180,6 +1842,7 @@ export function initializeOrchestrationListeners() {
     useOrchestrationStore.setState((state) => {
       const updated = upsertTaskRunState(state, taskRunId, (cur) => ({
         isAwaitingConfirmation: false,
+        summaryPreview: null,
         taskRun: { ...cur.taskRun, status: 'failed' as const },
       }));
       const newStates = updated.taskRunStates ?? state.taskRunStates;
@@ -1192,14 +1855,58 @@ export function initializeOrchestrationListeners() {
     useOrchestrationStore.getState().fetchTaskRuns();
   }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
 
-  // orchestration:task_run_updated — emitted by chat tool bridge when a TaskRun status changes
-  tauriListen<any>('orchestration:task_run_updated', () => {
+  // orchestration:task_run_updated — a TaskRun status changed outside its run,
+  // e.g. from a chat tool bridge or while it waits for its workspace
+  tauriListen<any>('orchestration:task_run_updated', (payload) => {
+    const { taskRunId, status } = payload ?? {};
+    if (taskRunId && status) {
+      useOrchestrationStore.setState((state) =>
+        updateTaskRunState(state, taskRunId, (cur) => ({ taskRun: { ...cur.taskRun, status } }))
+      );
+    }
+    useOrchestrationStore.getState().fetchTaskRuns();
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // notification:show — backend notification routed to the desktop
+  tauriListen<AppNotification>('notification:show', (payload) => {
+    if (!payload) return;
+    showWarning(payload.title, payload.body);
+    if (typeof Notification !== 'undefined' && Notification.permission === 'granted') {
+      const notification = new Notification(payload.title, { body: payload.body });
+      const taskRunId = payload.task_run_id;
+      if (taskRunId) {
+        notification.onclick = () => openTaskRunById(taskRunId);
+      }
+    }
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // navigation:open_task_run — an agent-hub://task-runs/<id> deep link was opened
+  tauriListen<{ taskRunId: string }>('navigation:open_task_run', (payload) => {
+    if (payload?.taskRunId) openTaskRunById(payload.taskRunId);
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // orchestration:queue_changed — runs were queued, started, reordered or dropped
+  tauriListen<QueuedRun[]>('orchestration:queue_changed', (payload) => {
+    useOrchestrationStore.setState({ queuedRuns: payload ?? [] });
+  }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
+
+  // schedule:failed — a scheduled run failed its final attempt
+  tauriListen<any>('schedule:failed', () => {
     useOrchestrationStore.getState().fetchTaskRuns();
   }).then((unlisten) => orchestrationUnlistenFns.push(unlisten));
 
   console.log('[Orchestration] Event listeners initialized');
 }
 
+async function openTaskRunById(taskRunId: string) {
+  try {
+    const taskRun = await tauriInvoke<TaskRun>('get_task_run', { taskRunId });
+    await useOrchestrationStore.getState().viewTaskRun(taskRun);
+  } catch (error) {
+    console.error('[Orchestration] Failed to open task run:', error);
+  }
+}
+
 export function cleanupOrchestrationListeners() {
   orchestrationUnlistenFns.forEach((unlisten) => unlisten());
   orchestrationUnlistenFns = [];
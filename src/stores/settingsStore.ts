@@ -1,23 +1,36 @@
 import { create } from 'zustand';
-import { tauriInvoke } from '@/lib/tauri';
+import { tauriInvoke, tauriListen } from '@/lib/tauri';
+import type { AppConfig, AppConfigPatch, ObserverStatus } from '@/types/settings';
+import type { BootstrapReport } from '@/types/onboarding';
+import type { DbStats, DiagnosticReport } from '@/types/diagnostics';
 
 interface SettingsState {
   theme: 'dark' | 'light';
   language: string;
   fontSize: number;
   settings: Record<string, string>;
+  config: AppConfig | null;
   loaded: boolean;
   workingDirectory: string | null;
+  observer: ObserverStatus;
 }
 
 interface SettingsActions {
   loadSettings: () => Promise<void>;
   updateSetting: (key: string, value: string) => Promise<void>;
+  updateConfig: (patch: AppConfigPatch) => Promise<AppConfig>;
+  listenForChanges: () => Promise<() => void>;
   toggleTheme: () => void;
   setLanguage: (lang: string) => void;
   setFontSize: (size: number) => void;
   selectWorkingDirectory: () => Promise<string | null>;
   loadWorkingDirectory: () => Promise<void>;
+  /** First-run setup; returns the checklist for the onboarding UI */
+  bootstrapEnvironment: () => Promise<BootstrapReport>;
+  runDiagnostics: (createBundle?: boolean) => Promise<DiagnosticReport>;
+  getDbStats: () => Promise<DbStats>;
+  /** Turn read-only observer mode on or off */
+  setObserverMode: (enabled: boolean) => Promise<void>;
 }
 
 function applyThemeClass(theme: 'dark' | 'light') {
@@ -38,23 +51,28 @@ export const useSettingsStore = create<SettingsState & SettingsActions>((set, ge
   language: 'en',
   fontSize: 14,
   settings: {},
+  config: null,
   loaded: false,
   workingDirectory: null,
+  observer: { active: false, forced: false },
 
   loadSettings: async () => {
     try {
-      const settings = await tauriInvoke<Record<string, string>>('get_settings');
-      const theme = (settings.theme === 'light' ? 'light' : 'dark') as 'dark' | 'light';
-      const language = settings.language ?? 'en';
-      const fontSize = settings.fontSize ? parseInt(settings.fontSize, 10) : 14;
+      const [settings, config, observer] = await Promise.all([
+        tauriInvoke<Record<string, string>>('get_settings'),
+        tauriInvoke<AppConfig>('get_app_config'),
+        tauriInvoke<ObserverStatus>('get_observer_status'),
+      ]);
 
-      applyThemeClass(theme);
+      applyThemeClass(config.theme);
 
       set({
         settings,
-        theme,
-        language,
-        fontSize: isNaN(fontSize) ? 14 : fontSize,
+        config,
+        theme: config.theme,
+        language: config.language,
+        fontSize: config.font_size,
+        observer,
         loaded: true,
       });
     } catch (error) {
@@ -84,6 +102,25 @@ export const useSettingsStore = create<SettingsState & SettingsActions>((set, ge
     }
   },
 
+  updateConfig: async (patch) => {
+    const config = await tauriInvoke<AppConfig>('update_app_config', { patch });
+    set({ config });
+    return config;
+  },
+
+  listenForChanges: async () => {
+    return tauriListen<AppConfig>('settings:changed', (config) => {
+      applyThemeClass(config.theme);
+      set((state) => ({
+        config,
+        theme: config.theme,
+        language: config.language,
+        fontSize: config.font_size,
+        observer: { ...state.observer, active: state.observer.forced || config.observer_mode },
+      }));
+    });
+  },
+
   toggleTheme: () => {
     const current = get().theme;
     const next = current === 'dark' ? 'light' : 'dark';
@@ -91,7 +128,7 @@ export const useSettingsStore = create<SettingsState & SettingsActions>((set, ge
     set({ theme: next });
 
     // Persist the theme change to the backend
-    tauriInvoke<void>('update_settings', { key: 'theme', value: next }).catch((error) => {
+    get().updateConfig({ theme: next }).catch((error) => {
       console.error('Failed to persist theme setting:', error);
     });
   },
@@ -99,7 +136,7 @@ export const useSettingsStore = create<SettingsState & SettingsActions>((set, ge
   setLanguage: (lang) => {
     set({ language: lang });
 
-    tauriInvoke<void>('update_settings', { key: 'language', value: lang }).catch((error) => {
+    get().updateConfig({ language: lang }).catch((error) => {
       console.error('Failed to persist language setting:', error);
     });
   },
@@ -107,11 +144,9 @@ export const useSettingsStore = create<SettingsState & SettingsActions>((set, ge
   setFontSize: (size) => {
     set({ fontSize: size });
 
-    tauriInvoke<void>('update_settings', { key: 'fontSize', value: String(size) }).catch(
-      (error) => {
-        console.error('Failed to persist font size setting:', error);
-      }
-    );
+    get().updateConfig({ font_size: size }).catch((error) => {
+      console.error('Failed to persist font size setting:', error);
+    });
   },
 
   selectWorkingDirectory: async () => {
@@ -135,4 +170,21 @@ export const useSettingsStore = create<SettingsState & SettingsActions>((set, ge
       console.error('Failed to load working directory:', error);
     }
   },
+
+  bootstrapEnvironment: async () => {
+    return tauriInvoke<BootstrapReport>('bootstrap_environment');
+  },
+
+  runDiagnostics: async (createBundle = false) => {
+    return tauriInvoke<DiagnosticReport>('run_diagnostics', { createBundle });
+  },
+
+  getDbStats: async () => {
+    return tauriInvoke<DbStats>('get_db_stats');
+  },
+
+  setObserverMode: async (enabled) => {
+    const observer = await tauriInvoke<ObserverStatus>('set_observer_mode', { enabled });
+    set({ observer });
+  },
 }));
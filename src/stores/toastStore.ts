@@ -1,4 +1,5 @@
 import { create } from 'zustand';
+import { isAppError } from '@/types/error';
 
 export interface Toast {
   id: string;
@@ -73,6 +74,7 @@ export function showSuccess(title: string, message?: unknown) {
 function formatMessage(value: unknown): string | undefined {
   if (value === undefined || value === null) return undefined;
   if (value instanceof Error) return value.message;
+  if (isAppError(value)) return value.message;
   if (typeof value === 'string') return value;
   try {
     return JSON.stringify(value);
@@ -4,7 +4,9 @@ import type {
   Workspace,
   CreateWorkspaceRequest,
   UpdateWorkspaceRequest,
+  SandboxMode,
 } from '@/types/workspace';
+import type { RepoOnboardingProposal } from '@/types/onboarding';
 
 interface WorkspaceState {
   workspaces: Workspace[];
@@ -17,8 +19,11 @@ interface WorkspaceActions {
   fetchWorkspaces: () => Promise<void>;
   setActiveWorkspace: (id: string) => Promise<void>;
   createWorkspace: (req: CreateWorkspaceRequest) => Promise<Workspace>;
+  createWorkspaceFromRepo: (path: string) => Promise<RepoOnboardingProposal>;
   updateWorkspace: (id: string, req: UpdateWorkspaceRequest) => Promise<Workspace>;
   deleteWorkspace: (id: string) => Promise<void>;
+  /** Mirror a workspace on a copy or git worktree of its directory; no mode picks one */
+  cloneWorkspaceSandbox: (workspaceId: string, mode?: SandboxMode) => Promise<Workspace>;
   selectWorkspaceDirectory: (workspaceId: string) => Promise<string | null>;
   toggleSidebar: () => void;
 }
@@ -61,6 +66,14 @@ export const useWorkspaceStore = create<WorkspaceState & WorkspaceActions>(
       return workspace;
     },
 
+    createWorkspaceFromRepo: async (path: string) => {
+      const proposal = await tauriInvoke<RepoOnboardingProposal>('create_workspace_from_repo', { path });
+      if (proposal.workspace_created) {
+        set((state) => ({ workspaces: [...state.workspaces, proposal.workspace] }));
+      }
+      return proposal;
+    },
+
     updateWorkspace: async (id: string, req: UpdateWorkspaceRequest) => {
       const workspace = await tauriInvoke<Workspace>('update_workspace', { id, request: req });
       set((state) => ({
@@ -89,6 +102,12 @@ export const useWorkspaceStore = create<WorkspaceState & WorkspaceActions>(
       }
     },
 
+    cloneWorkspaceSandbox: async (workspaceId: string, mode?: SandboxMode) => {
+      const workspace = await tauriInvoke<Workspace>('clone_workspace_sandbox', { workspaceId, mode: mode ?? null });
+      set((state) => ({ workspaces: [...state.workspaces, workspace] }));
+      return workspace;
+    },
+
     selectWorkspaceDirectory: async (workspaceId: string) => {
       const path = await tauriInvoke<string | null>('select_workspace_directory', {
         workspaceId,
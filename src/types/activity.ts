@@ -0,0 +1,45 @@
+export type ActivityKind =
+  | 'run_started'
+  | 'run_finished'
+  | 'agent_added'
+  | 'schedule_fired'
+  | 'chat_tool_login';
+
+export interface ActivityEntry {
+  id: string;
+  workspace_id: string | null;
+  kind: ActivityKind;
+  /** Task run, agent or chat tool the event is about */
+  subject_id: string | null;
+  summary: string;
+  created_at: string;
+}
+
+/** Filter and page for `list_activity`; entries come newest first */
+export interface ActivityQuery {
+  workspace_id?: string | null;
+  kinds?: ActivityKind[];
+  subject_id?: string | null;
+  since?: string | null;
+  until?: string | null;
+  limit?: number;
+  offset?: number;
+}
+
+/** An emitted event of a kept topic, e.g. `orchestration:completed` */
+export interface LoggedEvent {
+  id: number;
+  topic: string;
+  subject_id: string | null;
+  payload: unknown;
+  created_at: string;
+}
+
+/** Filter and page for `list_logged_events`; events come oldest first */
+export interface EventLogQuery {
+  /** Only events logged after this one, to catch up from the last seen */
+  after_id?: number | null;
+  topics?: string[];
+  subject_id?: string | null;
+  limit?: number;
+}
@@ -28,6 +28,9 @@ export interface SkillDiscoveryResult {
   last_scanned_at: string;
 }
 
+/** How a containerized agent's workspace is mounted */
+export type ContainerWorkspaceAccess = 'read_only' | 'read_write';
+
 export interface AgentConfig {
   id: string;
   name: string;
@@ -44,11 +47,32 @@ export interface AgentConfig {
   acp_command: string | null;
   acp_args_json: string | null;
   is_control_hub: boolean;
+  /** Takes over planning and summaries when the Control Hub fails */
+  is_secondary_hub: boolean;
   md_file_path: string | null;
   max_concurrency: number;
   available_models_json: string | null;
   is_enabled: boolean;
   disabled_reason: string | null;
+  /** Auto-disabled by an error that is likely to pass, such as a timeout */
+  disabled_transient?: boolean;
+  carry_over_context: boolean;
+  /** Run right after the agent is spawned for a task run; its output is prepended to the first assignment */
+  warmup_prompt: string | null;
+  /** Name of the workspace agents.yaml entry managing this agent */
+  manifest_name: string | null;
+  /** Run the ACP command on this host over SSH instead of locally */
+  ssh_host: string | null;
+  ssh_user: string | null;
+  /** Private key for the SSH connection; the SSH config decides when unset */
+  ssh_key_path: string | null;
+  /** Run the ACP command in a Docker container of this image */
+  container_image: string | null;
+  container_workspace_access: ContainerWorkspaceAccess;
+  /** Minutes without prompts after which the agent's chat process is stopped; null follows the app setting, 0 never stops it */
+  idle_shutdown_minutes?: number | null;
+  /** Preset answering the agent's permission requests in task runs unless the run chose one */
+  permission_preset?: PermissionPreset | null;
   workspace_id: string | null;
   created_at: string;
   updated_at: string;
@@ -69,6 +93,13 @@ export interface CreateAgentRequest {
   acp_args_json?: string;
   is_control_hub?: boolean;
   max_concurrency?: number;
+  carry_over_context?: boolean;
+  warmup_prompt?: string;
+  ssh_host?: string;
+  ssh_user?: string;
+  ssh_key_path?: string;
+  container_image?: string;
+  container_workspace_access?: ContainerWorkspaceAccess;
   workspace_id?: string;
 }
 
@@ -91,6 +122,66 @@ export interface UpdateAgentRequest {
   available_models_json?: string;
   is_enabled?: boolean;
   disabled_reason?: string | null;
+  carry_over_context?: boolean;
+  /** Empty string clears the warm-up prompt */
+  warmup_prompt?: string;
+  /** Empty string clears the host and the agent runs locally again */
+  ssh_host?: string;
+  /** Empty string clears the user */
+  ssh_user?: string;
+  /** Empty string clears the key path */
+  ssh_key_path?: string;
+  /** Empty string clears the image and the agent runs on the host again */
+  container_image?: string;
+  container_workspace_access?: ContainerWorkspaceAccess;
+  /** A negative value clears the override and the app setting applies */
+  idle_shutdown_minutes?: number;
+  /** Empty string clears the preset */
+  permission_preset?: PermissionPreset | '';
+}
+
+/** Permission presets shipped with the app */
+export type PermissionPreset = 'strict' | 'balanced' | 'yolo';
+
+/** What a preset does with one ACP tool kind; prompt leaves it to the user */
+export type PresetRule = 'allow' | 'deny' | 'prompt';
+
+/** A preset with its rule for every tool kind */
+export interface PermissionPresetInfo {
+  preset: PermissionPreset;
+  description: string;
+  /** [tool kind, rule] pairs, e.g. ["execute", "deny"] */
+  rules: [string, PresetRule][];
+}
+
+/** A permission decision remembered for an agent */
+export interface PermissionPolicy {
+  id: string;
+  agent_id: string;
+  /** Kind of request the decision applies to, e.g. "read" or "execute:git" */
+  request_key: string;
+  allow: boolean;
+  created_at: string;
+}
+
+/** Outcome of syncing a workspace's agents with its agents.yaml; agent names */
+export interface ManifestSyncReport {
+  manifest_path: string;
+  created: string[];
+  updated: string[];
+  disabled: string[];
+  unchanged: string[];
+}
+
+export interface AgentContext {
+  agent_id: string;
+  workspace_id: string;
+  acp_session_id: string | null;
+  summary: string;
+  run_count: number;
+  last_task_run_id: string | null;
+  created_at: string;
+  updated_at: string;
 }
 
 export interface DiscoveredAgent {
@@ -110,8 +201,48 @@ export interface DiscoveredAgent {
   cli_version: string | null;
 }
 
+/** Installed, latest and published versions of a registry agent's CLI */
+export interface AgentVersions {
+  /** Registry entry ID, e.g. "gemini" */
+  agent_type: string;
+  name: string;
+  /** npm package of NPX agents, without a version */
+  package: string | null;
+  installed: string | null;
+  latest: string;
+  /** Version the user chose; null follows the registry */
+  pinned: string | null;
+  /** Published versions, newest first; only known for NPX agents */
+  available: string[];
+}
+
 export interface AgentModel {
   model_id: string;
   name: string;
   description: string | null;
 }
+
+/** The terminal of a running agent process */
+export interface AgentTerminalInfo {
+  terminal_id: string;
+  agent_id: string;
+  /** The prompt the agent waits at for input */
+  prompt: string | null;
+}
+
+export interface AgentTerminal extends AgentTerminalInfo {
+  /** Recent terminal output */
+  output: string;
+}
+
+/** An agent being logged in by its login command */
+export interface AgentLogin {
+  agent_id: string;
+  agent_name: string;
+  /** Terminal the login command runs in; its prompts show like any agent terminal's */
+  terminal_id: string;
+  /** Page the user signs in at */
+  url: string | null;
+  /** Device code to enter on that page */
+  code: string | null;
+}
@@ -0,0 +1,15 @@
+/** Outcome of one item in a bulk command */
+export interface BulkItemResult {
+  id: string;
+  ok: boolean;
+  error: string | null;
+}
+
+export interface BulkResult {
+  items: BulkItemResult[];
+  succeeded: number;
+  failed: number;
+}
+
+export const succeededIds = (result: BulkResult): string[] =>
+  result.items.filter((i) => i.ok).map((i) => i.id);
@@ -5,6 +5,10 @@ export interface ChatMessage {
   content_json: string;
   tool_calls_json: string | null;
   created_at: string;
+  /** Set while the message is pinned */
+  pinned_at?: string | null;
+  /** Reactions on the message, sorted */
+  reactions?: string[];
 }
 
 export interface Session {
@@ -16,6 +20,11 @@ export interface Session {
   created_at: string;
   updated_at: string;
   workspace_id: string | null;
+  /** Rolling summary of the conversation, seeded into a new agent session */
+  summary: string | null;
+  /** Messages the summary covers, oldest first */
+  summary_message_count: number;
+  summary_updated_at: string | null;
 }
 
 export interface CreateSessionRequest {
@@ -13,6 +13,45 @@ export interface ChatTool {
   last_active_at: string | null;
   created_at: string;
   updated_at: string;
+  env_json: string;
+  owner_contact_id: string | null;
+  escalation_threshold: number | null;
+  /** Typing indicator while the Control Hub replies, where the bridge supports it */
+  typing_indicator: boolean;
+  /** Replies longer than this are sent as several messages; null sends one */
+  reply_chunk_chars: number | null;
+  /** JSON-encoded ReplyTemplates */
+  reply_templates_json: string;
+}
+
+export interface OfficeHours {
+  /** Local time, "HH:MM"; an end before the start spans midnight */
+  start: string;
+  end: string;
+  /** ISO weekdays, 1 = Monday … 7 = Sunday; defaults to Monday–Friday */
+  weekdays?: number[];
+  notice: string;
+}
+
+/**
+ * Canned replies sent without the Control Hub. Templates may use
+ * {{sender_name}}, {{chat_tool_name}} and {{office_hours}}; empty sends nothing.
+ */
+export interface ReplyTemplates {
+  greeting: string;
+  /** null uses the localized default busy reply */
+  busy: string | null;
+  hub_unavailable: string;
+  /** Sent instead of forwarding when a sender is over its daily quota */
+  over_quota: string;
+  office_hours: OfficeHours | null;
+}
+
+export interface ChatToolEnvProfile {
+  proxy?: string | null;
+  data_dir?: string | null;
+  locale?: string | null;
+  env?: Record<string, string>;
 }
 
 export interface CreateChatToolRequest {
@@ -22,6 +61,7 @@ export interface CreateChatToolRequest {
   linked_agent_id?: string;
   auto_reply_mode?: string;
   workspace_id?: string;
+  env_profile?: ChatToolEnvProfile;
 }
 
 export interface UpdateChatToolRequest {
@@ -29,6 +69,15 @@ export interface UpdateChatToolRequest {
   config_json?: string;
   linked_agent_id?: string;
   auto_reply_mode?: string;
+  env_profile?: ChatToolEnvProfile;
+  /** Empty string clears the owner contact */
+  owner_contact_id?: string;
+  /** Zero or less disables confidence-based escalation */
+  escalation_threshold?: number;
+  typing_indicator?: boolean;
+  /** Zero or less sends each reply as a single message */
+  reply_chunk_chars?: number;
+  reply_templates?: ReplyTemplates;
 }
 
 export interface ChatToolMessage {
@@ -42,9 +91,64 @@ export interface ChatToolMessage {
   agent_response: string | null;
   is_processed: boolean;
   error_message: string | null;
+  /** Set when the spam filter held the message back from auto-reply */
+  filter_reason?: string | null;
+  /** Tracked outgoing messages only; acknowledged by the bridge */
+  delivery_status?: DeliveryStatus | null;
+  delivery_error?: string | null;
+  delivery_attempts?: number;
+  created_at: string;
+}
+
+export type DeliveryStatus = 'pending' | 'sent' | 'delivered' | 'read' | 'failed';
+
+export interface ChatToolEscalation {
+  id: string;
+  chat_tool_id: string;
+  external_sender_id: string | null;
+  external_sender_name: string | null;
+  original_content: string;
+  hub_reply: string;
+  reason: string;
+  confidence: number | null;
+  owner_contact_id: string;
   created_at: string;
 }
 
+export type MetricsRange = 'hour' | 'day' | 'week' | 'month';
+
+/** Responsiveness of a chat tool over a range ending now */
+export interface ChatToolMetrics {
+  chat_tool_id: string;
+  range: MetricsRange;
+  since: string;
+  messages_received: number;
+  messages_replied: number;
+  batches: number;
+  escalated_batches: number;
+  /** Batches the Control Hub failed on or was missing for */
+  failed_batches: number;
+  /** Mean time from receipt to reply sent over replied messages */
+  avg_response_ms: number | null;
+  p95_response_ms: number | null;
+  max_response_ms: number | null;
+  avg_hub_latency_ms: number | null;
+  p95_hub_latency_ms: number | null;
+  max_queue_depth: number;
+  /** Messages waiting for a reply now */
+  backlog: number;
+  buckets: ChatToolMetricsBucket[];
+}
+
+export interface ChatToolMetricsBucket {
+  start: string;
+  received: number;
+  replied: number;
+  avg_response_ms: number | null;
+  avg_hub_latency_ms: number | null;
+  max_queue_depth: number;
+}
+
 export interface ChatToolContact {
   id: string;
   chat_tool_id: string;
@@ -53,10 +157,43 @@ export interface ChatToolContact {
   avatar_url: string | null;
   contact_type: string;
   is_blocked: boolean;
+  /** Daily auto-reply quotas; null is unlimited */
+  max_replies_per_day: number | null;
+  max_tokens_per_day: number | null;
+  replies_today: number;
+  tokens_today: number;
+  /** Auto-replies are suspended until then (UTC); null when they are on */
+  snoozed_until: string | null;
+  /** Set while a human operator has taken over the conversation (UTC) */
+  takeover_started_at: string | null;
+  /** Groups the contact is in, e.g. from an imported customer list */
+  groups: string[];
+  /** Task run outcomes the contact gets a direct message about */
+  run_notifications: RunNotificationSubscription | null;
   created_at: string;
   updated_at: string;
 }
 
+/** Which finished task runs a contact is told about through the chat tool */
+export interface RunNotificationSubscription {
+  on_completed: boolean;
+  on_failed: boolean;
+  /** Only runs of this workspace; null is every workspace */
+  workspace_id: string | null;
+}
+
+export type TranscriptFormat = 'markdown' | 'csv' | 'json';
+
+export type ContactListFormat = 'csv' | 'json';
+
+/** Outcome of importing a contact list */
+export interface ContactImport {
+  created: number;
+  updated: number;
+  /** Rows that were skipped and why */
+  errors: string[];
+}
+
 export interface ChatToolConfigField {
   key: string;
   label: string;
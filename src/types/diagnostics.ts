@@ -0,0 +1,39 @@
+export type CheckStatus = 'ok' | 'warning' | 'error';
+
+/** One finding of `run_diagnostics` */
+export interface DiagnosticCheck {
+  category: 'database' | 'processes' | 'sessions' | 'agents' | 'bridges';
+  /** What was checked, e.g. an agent or chat tool name */
+  subject: string;
+  status: CheckStatus;
+  detail: string;
+}
+
+export interface DiagnosticReport {
+  generated_at: string;
+  app_version: string;
+  os: string;
+  checks: DiagnosticCheck[];
+  errors: number;
+  warnings: number;
+  /** Redacted zip for bug reports, when one was requested */
+  bundle_path: string | null;
+}
+
+/** Database contention counters since start, from `get_db_stats` */
+export interface DbStats {
+  lock_acquisitions: number;
+  /** Acquisitions that found the connection in use */
+  contended_locks: number;
+  lock_wait_ms_total: number;
+  lock_wait_ms_max: number;
+  /** Statements retried because another process held the database */
+  busy_retries: number;
+  /** Statements that failed after `busy_timeout_ms` of retries */
+  busy_timeouts: number;
+  /** Queued writes not yet committed */
+  queued_writes: number;
+  write_batches: number;
+  batched_writes: number;
+  failed_writes: number;
+}
@@ -0,0 +1,46 @@
+/** Stable identifier of the kind of a backend error */
+export type AppErrorCode =
+  | 'database'
+  | 'io'
+  | 'serialization'
+  | 'not_found'
+  | 'invalid_request'
+  | 'permission_denied'
+  | 'observer_mode'
+  | 'acp'
+  | 'agent_not_running'
+  | 'agent_already_running'
+  | 'agent_not_configured'
+  | 'agent_process_lost'
+  | 'agent_cancelled'
+  | 'agent_no_response'
+  | 'agent_failed'
+  | 'version_upgrade_required'
+  | 'transport'
+  | 'timeout'
+  | 'bridge'
+  | 'internal';
+
+/** Error a backend command rejects with */
+export interface AppError {
+  code: AppErrorCode;
+  message: string;
+  agent_id?: string;
+  task_run_id?: string;
+  chat_tool_id?: string;
+  /** JSON-RPC error code of 'agent_failed' */
+  agent_error_code?: number;
+}
+
+export const isAppError = (value: unknown): value is AppError =>
+  typeof value === 'object' &&
+  value !== null &&
+  typeof (value as AppError).code === 'string' &&
+  typeof (value as AppError).message === 'string';
+
+/** Human-readable text of anything a command or promise rejected with */
+export const errorMessage = (value: unknown): string => {
+  if (isAppError(value)) return value.message;
+  if (value instanceof Error) return value.message;
+  return String(value);
+};
@@ -0,0 +1,39 @@
+export interface KnowledgeDocument {
+  id: string;
+  workspace_id: string;
+  /** Absolute path the document was read from */
+  path: string;
+  title: string;
+  file_type: 'md' | 'txt' | 'pdf';
+  size_bytes: number;
+  chunk_count: number;
+  content_hash: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface KnowledgeChunkMatch {
+  document_id: string;
+  document_title: string;
+  path: string;
+  chunk_index: number;
+  content: string;
+  /** Cosine similarity to the query, 0..1 */
+  score: number;
+}
+
+export interface IngestReport {
+  ingested: KnowledgeDocument[];
+  /** Paths whose content had not changed since the last ingest */
+  unchanged: string[];
+  /** "path: reason" for files that could not be read */
+  failed: string[];
+}
+
+export interface KnowledgeContextConfig {
+  /** Add relevant chunks to the planning prompt */
+  planner: boolean;
+  /** Add relevant chunks to each agent's assignment input */
+  agents: boolean;
+  top_k: number;
+}
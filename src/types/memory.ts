@@ -0,0 +1,17 @@
+export interface Memory {
+  id: string;
+  workspace_id: string | null;
+  source_kind: 'task_run' | 'chat';
+  /** Task run id or chat session id */
+  source_id: string;
+  title: string;
+  content: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface MemoryMatch {
+  memory: Memory;
+  /** Cosine similarity to the query, 0..1 */
+  score: number;
+}
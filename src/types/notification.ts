@@ -0,0 +1,20 @@
+export type NotificationTarget =
+  | { type: 'desktop' }
+  | { type: 'webhook'; url: string }
+  | { type: 'chat_tool'; chat_tool_id: string; to_id: string };
+
+export interface AppNotification {
+  kind: string;  // e.g. 'schedule_failed'
+  title: string;
+  body: string;
+  task_run_id?: string;
+  /** Deep link to the related view, e.g. 'agent-hub://task-runs/<id>' */
+  link?: string;
+}
+
+/** Reminders for task runs left waiting on the user */
+export interface StuckRunReminderConfig {
+  enabled: boolean;
+  after_minutes: number;
+  targets: NotificationTarget[];
+}
@@ -0,0 +1,48 @@
+import type { CreateAgentRequest } from './agent';
+import type { Workspace } from './workspace';
+
+export type BootstrapStepId =
+  | 'node'
+  | 'npm'
+  | 'builtin_agent'
+  | 'credentials'
+  | 'workspace'
+  | 'control_hub';
+
+/** One check or setup action of `bootstrap_environment` */
+export interface BootstrapStep {
+  id: BootstrapStepId;
+  status: 'completed' | 'failed';
+  /** What was found or done, or why the step failed */
+  detail: string;
+}
+
+export interface BootstrapReport {
+  steps: BootstrapStep[];
+  workspace_id: string | null;
+  control_hub_id: string | null;
+  /** Every step completed */
+  ready: boolean;
+}
+
+/** What `create_workspace_from_repo` found in a repository */
+export interface RepoInspection {
+  languages: string[];
+  package_managers: string[];
+  test_commands: string[];
+  agents_md_path: string | null;
+  /** Ids of the skills in the repository's skills/ directory */
+  skill_ids: string[];
+  /** agents.yaml at the repository root; syncing it registers its agents */
+  has_agents_manifest: boolean;
+}
+
+export interface RepoOnboardingProposal {
+  workspace: Workspace;
+  /** False when a workspace already used the repository */
+  workspace_created: boolean;
+  control_hub_id: string | null;
+  inspection: RepoInspection;
+  /** Agents to register once the user confirms them */
+  agents: CreateAgentRequest[];
+}
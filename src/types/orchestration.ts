@@ -1,3 +1,6 @@
+import type { PermissionPreset } from './agent';
+import type { NotificationTarget } from './notification';
+
 export interface RecurrencePattern {
   frequency: 'daily' | 'weekly' | 'monthly' | 'yearly';
   time: string;  // HH:MM format
@@ -7,12 +10,15 @@ export interface RecurrencePattern {
   month?: number;  // Month for yearly (1-12)
 }
 
+/** `control_hub_agent_id` of runs planned without a Control Hub */
+export const BUILTIN_PLANNER_ID = 'builtin-planner';
+
 export interface TaskRun {
   id: string;
   title: string;
   user_prompt: string;
   control_hub_agent_id: string;
-  status: 'pending' | 'analyzing' | 'running' | 'awaiting_confirmation' | 'completed' | 'failed' | 'cancelled';
+  status: 'pending' | 'waiting_for_workspace' | 'analyzing' | 'running' | 'awaiting_confirmation' | 'awaiting_plan_approval' | 'needs_review' | 'completed' | 'failed' | 'cancelled';
   task_plan_json: string | null;
   result_summary: string | null;
   total_tokens_in: number;
@@ -30,6 +36,50 @@ export interface TaskRun {
   next_run_at: string | null;
   is_paused: boolean;
   workspace_id: string | null;
+  failure_policy_json?: string | null;
+  /** Hub that planned and summarized the run; differs from control_hub_agent_id after a failover */
+  served_by_hub_agent_id?: string | null;
+  /** Lowercase labels, sorted */
+  labels: string[];
+  /** Set while the run is archived; archived runs are left out of list_task_runs */
+  archived_at?: string | null;
+  /** Earlier run whose summary and outputs were attached to this run's planning prompt */
+  context_run_id?: string | null;
+  /** Permission preset the run was started with */
+  permission_preset?: PermissionPreset | null;
+  /** Wall-clock limit the run was started with, in minutes */
+  max_duration_minutes?: number | null;
+  /** The run reached its wall-clock limit and was wound down; its summary covers what finished in time */
+  timed_out?: boolean;
+  /** Why the run was cancelled */
+  cancel_reason?: CancelReason | null;
+}
+
+/** Why the user cancelled a run or an assignment */
+export type CancelReason = 'user_cancelled' | 'too_expensive' | 'wrong_direction';
+
+/** Result of starting a run: a new one, or the active run the start repeated */
+export interface StartedTaskRun extends TaskRun {
+  duplicate: boolean;
+}
+
+/** Filters for search_task_runs; unset fields do not filter */
+export interface TaskRunSearch {
+  workspace_id?: string | null;
+  statuses?: TaskRun['status'][];
+  /** Runs carrying all of these labels */
+  labels?: string[];
+  /** Created at or after this UTC date or datetime */
+  created_after?: string | null;
+  /** Created before this UTC date or datetime */
+  created_before?: string | null;
+  /** Runs the agent planned, summarized or had an assignment in */
+  agent_id?: string | null;
+  /** Case-insensitive match in the title or the result summary */
+  text?: string | null;
+  /** Only archived runs (true) or only unarchived ones (false); both when unset */
+  archived?: boolean | null;
+  limit?: number | null;
 }
 
 export interface TaskAssignment {
@@ -40,7 +90,8 @@ export interface TaskAssignment {
   sequence_order: number;
   input_text: string;
   output_text: string | null;
-  status: 'pending' | 'running' | 'completed' | 'failed' | 'skipped';
+  /** 'capped': stopped at its token or cost cap, or at the run's time limit */
+  status: 'pending' | 'running' | 'completed' | 'capped' | 'failed' | 'skipped';
   model_used: string | null;
   tokens_in: number;
   tokens_out: number;
@@ -51,11 +102,22 @@ export interface TaskAssignment {
   duration_ms: number;
   error_message: string | null;
   created_at: string;
+  working_directory: string | null;  // relative to the workspace root
+  cached: boolean;  // output served from the response cache
+  max_tokens_out: number | null;
+  max_cost: number | null;  // USD
+  /** Earlier assignment this one re-executed */
+  retry_of_assignment_id?: string | null;
+  overrides?: AssignmentOverrides;
+  /** Why the assignment was cancelled */
+  cancel_reason?: CancelReason | null;
 }
 
 export interface TaskPlan {
   analysis: string;
   assignments: PlannedAssignment[];
+  /** The run's agents get a scratch directory for intermediate files */
+  isolated?: boolean;
 }
 
 export interface PlannedAssignment {
@@ -65,6 +127,58 @@ export interface PlannedAssignment {
   depends_on: string[];
   matched_skills?: string[];
   selection_reason?: string;
+  working_directory?: string;  // relative to the workspace root
+  output_file?: string;  // relative to the run's output directory
+  /** Output tokens at which the agent is told to wrap up and then stopped */
+  max_tokens_out?: number;
+  max_cost?: number;  // USD
+  /** The hub's confidence in the assignment, 0 to 1 */
+  confidence?: number;
+  /** What the hub assumed that the request does not say */
+  assumptions?: string[];
+  /** Agent settings used for this assignment only */
+  overrides?: AssignmentOverrides | null;
+  /** Workspace files sent with the agent's prompt */
+  attachments?: PromptAttachment[];
+  /** Parts of dependencies' outputs the agent needs, by agent_id: JSON keys or Markdown headings */
+  dependency_sections?: Record<string, string[]>;
+}
+
+/** A workspace file sent with an assignment's prompt */
+export interface PromptAttachment {
+  path: string;  // relative to the workspace root
+  description?: string;
+}
+
+/** 'ask': remembered decisions, else the user; 'read_only': edits and commands are rejected */
+export type PermissionProfile = 'ask' | 'read_only' | 'auto_approve';
+
+/** Agent settings an assignment runs with instead of the agent's own */
+export interface AssignmentOverrides {
+  model?: string;
+  temperature?: number;
+  /** Instructions the agent follows instead of its system prompt */
+  system_prompt?: string;
+  permission_profile?: PermissionProfile;
+}
+
+/** What an image or generated-file artifact contains */
+export interface ArtifactMetadata {
+  mime_type: string;
+  width: number | null;
+  height: number | null;
+}
+
+export interface TaskArtifact {
+  id: string;
+  task_run_id: string;
+  assignment_id: string | null;
+  agent_id: string | null;
+  kind: 'output_file' | 'tool_payload' | 'inline_image' | 'generated_file';
+  path: string;
+  size_bytes: number;
+  created_at: string;
+  metadata?: ArtifactMetadata | null;
 }
 
 export interface AssignmentValidation {
@@ -83,7 +197,7 @@ export interface AgentTrackingInfo {
   agentId: string;
   agentName: string;
   model: string;
-  status: 'pending' | 'running' | 'completed' | 'failed' | 'cancelled';
+  status: 'pending' | 'running' | 'completed' | 'capped' | 'failed' | 'cancelled';
   tokensIn: number;
   tokensOut: number;
   cacheCreationTokens: number;
@@ -95,6 +209,10 @@ export interface AgentTrackingInfo {
   toolCalls?: OrchToolCall[];
   assignmentId?: string;
   a2aCalls?: A2aCallInfo[];
+  /** Output served from the response cache */
+  cached?: boolean;
+  /** Images and files found in the agent's output */
+  artifacts?: TaskArtifact[];
 }
 
 export interface A2aCallInfo {
@@ -105,6 +223,26 @@ export interface A2aCallInfo {
   iteration: number;
 }
 
+/** An assignment output or A2A result kept on a run's clipboard */
+export interface PinnedOutput {
+  id: string;
+  task_run_id: string;
+  source: 'assignment' | 'a2a';
+  assignment_id: string | null;
+  agent_id: string | null;
+  label: string;
+  content: string;
+  created_at: string;
+}
+
+export interface PinOutputRequest {
+  task_run_id: string;
+  assignment_id?: string;
+  agent_id?: string;
+  label?: string;
+  content?: string;
+}
+
 export interface OrchToolCall {
   toolCallId: string;
   name: string;
@@ -112,6 +250,9 @@ export interface OrchToolCall {
   status: string;
   rawInput?: any;
   rawOutput?: any;
+  /** Set when the payload above is a truncated preview; load it with `get_tool_payload` */
+  rawInputArtifactId?: string;
+  rawOutputArtifactId?: string;
 }
 
 export interface OrchPermissionRequest {
@@ -119,8 +260,95 @@ export interface OrchPermissionRequest {
   agentId: string;
   requestId: number | string;
   sessionId: string;
-  toolCall?: { toolCallId: string; title: string; rawInput?: any };
+  toolCall?: { toolCallId: string; title: string; kind?: string; rawInput?: any };
   options: Array<{ optionId: string; name: string; kind: string }>;
+  /** Kind of request, e.g. "read" or "execute:git"; similar requests share one prompt */
+  requestKey?: string;
+  /** Requests the prompt answers, when similar ones were batched into it */
+  batchCount?: number;
+}
+
+/** How long a permission decision is remembered */
+export type PermissionScope = 'run' | 'agent';
+
+/** One recorded step of an agent prompt */
+export interface AssignmentEvent {
+  id: number;
+  kind: 'text' | 'thought' | 'tool_call' | 'notice';
+  /** Text for text/thought/notice events; JSON with name, title, input and output preview for tool calls */
+  content: string;
+  tool_call_id: string | null;
+  tool_status: string | null;
+  created_at: string;
+}
+
+export interface AssignmentTimeline {
+  assignment: TaskAssignment;
+  events: AssignmentEvent[];
+}
+
+/** An agent file write awaiting user review (only when review_writes is enabled) */
+export interface FileWriteReview {
+  write_id: string;
+  task_run_id: string;
+  agent_id: string;
+  path: string;
+  /** Unified diff against the current file content */
+  diff: string;
+  is_new_file: boolean;
+  created_at: string;
+}
+
+/** Code changes of one file across a run's reviewed writes */
+export interface FileChange {
+  /** Workspace-relative path */
+  path: string;
+  agent_ids: string[];
+  is_new_file: boolean;
+  additions: number;
+  deletions: number;
+  /** Diffs of the approved writes, in write order */
+  diff: string;
+  rejected_writes: number;
+}
+
+export interface RunChanges {
+  files: FileChange[];
+  additions: number;
+  deletions: number;
+}
+
+/** One agent in a run's environment snapshot */
+export interface AgentEnvironment {
+  agent_id: string;
+  name: string;
+  model: string;
+  command: string | null;
+  registry_id: string | null;
+  registry_version: string | null;
+  /** Adapter version installed locally, for NPX agents */
+  installed_version: string | null;
+  /** "local", "ssh:<host>" or "container:<image>" */
+  runs_on: string;
+  is_control_hub: boolean;
+}
+
+/** What a task run ran with, recorded when it started planning */
+export interface RunEnvironment {
+  captured_at: string;
+  app_version: string;
+  os: string;
+  registry_version: string | null;
+  agents: AgentEnvironment[];
+  settings: Record<string, string>;
+}
+
+/** A value that differs between two run environments */
+export interface EnvironmentChange {
+  /** e.g. "app_version", "agent.Coder.model" or "setting.review_writes" */
+  key: string;
+  before: string | null;
+  after: string | null;
 }
 
 /** Per-task-run state for parallel orchestration */
@@ -133,13 +361,96 @@ export interface TaskRunState {
   taskPlan: TaskPlan | null;
   planValidation: PlanValidation | null;
   isAwaitingConfirmation: boolean;
+  /** The hub's plan is not confident enough to run without approval */
+  isAwaitingPlanApproval: boolean;
+  /** The hub's summary of the run so far, while it is being written */
+  summaryPreview: string | null;
+  /** Paused at a sequence group for a run of a higher priority */
+  isPreempted: boolean;
   expandedAgentId: string | null;
 }
 
+/** Where a run came from; a waiting run of a higher priority starts first */
+export type RunPriority = 'urgent' | 'interactive' | 'scheduled' | 'chat_tool';
+
+/** A task run waiting in the intake queue for a free slot */
+export interface QueuedRun {
+  task_run_id: string;
+  title: string;
+  workspace_id: string | null;
+  priority: RunPriority;
+  queued_at: string;
+  /** Holds the workspace while it runs: its workspace has `exclusive_runs` on */
+  exclusive: boolean;
+  /** Starts whether or not the workspace is held */
+  ignore_workspace_lock: boolean;
+  /** Held back by another run of its workspace rather than by the run limit */
+  waiting_for_workspace: boolean;
+}
+
 // Scheduling request types
 export interface ScheduleTaskRequest {
   task_run_id: string;
   schedule_type: 'none' | 'once' | 'recurring';
   scheduled_time?: string;  // ISO 8601 datetime for one-time execution
   recurrence_pattern?: RecurrencePattern;
+  failure_policy?: ScheduleFailurePolicy;
+}
+
+export interface ScheduleFailurePolicy {
+  max_retries: number;  // retries after the first failed attempt
+  backoff_secs?: number;  // delay before the first retry, doubled per retry (default 60)
+  notify_on_failure: NotificationTarget[];
+}
+
+// Schedule run history
+export interface ScheduleRun {
+  id: string;
+  schedule_task_run_id: string;
+  task_run_id: string | null;  // null when the occurrence was skipped
+  scheduled_for: string | null;
+  fired_at: string;
+  finished_at: string | null;
+  outcome: 'running' | 'completed' | 'failed' | 'cancelled' | 'skipped' | 'needs_review';
+  skipped_reason: 'already_running' | 'shutting_down' | null;
+  error_message: string | null;
+  duration_ms: number;
+  attempt: number;  // 1 for the scheduled firing, 2+ for retries
+}
+
+/** A scheduled task with when it fires next and how it last went */
+export interface ScheduledTask {
+  task_run_id: string;
+  title: string;
+  schedule_type: 'once' | 'recurring';
+  scheduled_time: string | null;
+  recurrence_pattern: RecurrencePattern | null;
+  next_run_at: string | null;  // null once a one-time task has run
+  is_paused: boolean;
+  is_running: boolean;  // an occurrence is running now
+  workspace_id: string | null;
+  workspace_name: string | null;
+  last_run: ScheduleRun | null;
+}
+
+/** Cancellations of one reason; tokens were spent before cancelling */
+export interface CancellationStats {
+  reason: CancelReason;
+  runs: number;
+  assignments: number;
+  tokens_in: number;
+  tokens_out: number;
+}
+
+export interface ScheduleStats {
+  schedule_task_run_id: string;
+  total_runs: number;
+  completed: number;
+  failed: number;
+  cancelled: number;
+  skipped: number;
+  success_rate: number | null;  // 0-1, null until a run has finished
+  avg_duration_ms: number | null;
+  last_fired_at: string | null;
+  last_outcome: ScheduleRun['outcome'] | null;
 }
@@ -0,0 +1,49 @@
+export interface PipelineStage {
+  name: string;
+  prompt_template: string;
+  requires_approval: boolean;
+}
+
+export interface Pipeline {
+  id: string;
+  name: string;
+  description: string;
+  stages_json: string;
+  workspace_id: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface CreatePipelineRequest {
+  name: string;
+  description?: string;
+  stages: PipelineStage[];
+  workspace_id?: string;
+}
+
+export interface UpdatePipelineRequest {
+  name?: string;
+  description?: string;
+  stages?: PipelineStage[];
+}
+
+export type PipelineRunStatus =
+  | 'pending'
+  | 'running'
+  | 'awaiting_approval'
+  | 'completed'
+  | 'failed'
+  | 'cancelled';
+
+export interface PipelineRun {
+  id: string;
+  pipeline_id: string;
+  status: PipelineRunStatus;
+  current_stage: number;
+  initial_input: string;
+  stage_task_run_ids_json: string;
+  error_message: string | null;
+  workspace_id: string | null;
+  created_at: string;
+  updated_at: string;
+}
@@ -0,0 +1,30 @@
+/** USD rates per million tokens for one model */
+export interface ModelPricing {
+  provider: string;
+  /** Model id without provider prefix or date suffix, e.g. "claude-sonnet-4" */
+  model: string;
+  input_per_mtok: number;
+  output_per_mtok: number;
+  /** Falls back to the input rate when null */
+  cache_write_per_mtok: number | null;
+  cache_read_per_mtok: number | null;
+}
+
+export interface PricingTable {
+  entries: ModelPricing[];
+}
+
+export interface AssignmentCost {
+  assignment_id: string;
+  agent_name: string;
+  model: string | null;
+  /** null when the model has no known pricing */
+  cost_usd: number | null;
+}
+
+export interface RunCostEstimate {
+  task_run_id: string;
+  total_usd: number;
+  assignments: AssignmentCost[];
+  unpriced_models: string[];
+}
@@ -0,0 +1,43 @@
+export interface Prompt {
+  id: string;
+  name: string;
+  description: string;
+  tags_json: string;
+  current_version: number;
+  /** Content of the current version; may contain {{variable}} or {{variable|default}} */
+  content: string;
+  workspace_id: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface PromptVersion {
+  prompt_id: string;
+  version: number;
+  content: string;
+  created_at: string;
+}
+
+export interface CreatePromptRequest {
+  name: string;
+  description?: string;
+  content: string;
+  tags?: string[];
+  workspace_id?: string;
+}
+
+/** Changing content adds a new version */
+export interface UpdatePromptRequest {
+  name?: string;
+  description?: string;
+  content?: string;
+  tags?: string[];
+}
+
+/** Reference to a library prompt, used in place of raw prompt text */
+export interface PromptRef {
+  prompt_id: string;
+  /** Defaults to the current version */
+  version?: number;
+  variables?: Record<string, string>;
+}
@@ -0,0 +1,149 @@
+import type { KnowledgeContextConfig } from './knowledge';
+import type { StuckRunReminderConfig } from './notification';
+import type { ModelPricing } from './pricing';
+
+export interface CommandKeywords {
+  help: string;
+  status: string;
+  tasks: string;
+  run: string;
+  /** Contacts in this group may send commands besides the owner contact */
+  allowed_group: string;
+}
+
+/** Checks applied to incoming chat tool messages before auto-reply */
+export interface SpamFilterConfig {
+  enabled: boolean;
+  /** 0 disables the per-sender rate limit */
+  max_messages_per_minute: number;
+  blocked_keywords: string[];
+  max_links: number;
+  filter_first_contact_attachments: boolean;
+  /** Also ask the Control Hub to classify messages that pass the other checks */
+  llm_classifier: boolean;
+}
+
+/** Secrets masked in agent outputs of workspaces that redact them */
+export interface RedactionConfig {
+  /** Built-in pattern groups in use: "api_keys", "tokens" and "emails" */
+  built_in: string[];
+  /** Regular expressions of further text to mask */
+  patterns: string[];
+}
+
+/** Web search MCP tool given to agents without built-in browsing */
+export interface WebSearchConfig {
+  enabled: boolean;
+  backend: 'brave' | 'tavily' | 'searxng';
+  /** API key of Brave or Tavily */
+  api_key: string | null;
+  /** SearXNG instance, e.g. "http://localhost:8888" */
+  base_url: string | null;
+  /** Results a search returns at most */
+  max_results: number;
+  /** Agents given the tool; empty gives it to every local agent */
+  agent_ids: string[];
+}
+
+export interface TelemetryConfig {
+  /** OTLP/HTTP collector, e.g. "http://localhost:4318"; null disables span export */
+  otlp_endpoint: string | null;
+  service_name: string;
+}
+
+export interface DatabaseConfig {
+  /** How long a statement retries while another process holds the database */
+  busy_timeout_ms: number;
+  /** How long queued writes wait for more writes to commit with */
+  write_batch_ms: number;
+  /** How often streamed replies and agent output are written while they stream */
+  stream_flush_ms: number;
+  /** Waits for the connection longer than this are logged */
+  slow_lock_ms: number;
+}
+
+/** Fault injection; only honoured in backend builds with the `chaos` feature */
+export interface ChaosConfig {
+  enabled: boolean;
+  seed: number;
+  delay_probability: number;
+  max_delay_ms: number;
+  drop_probability: number;
+  kill_probability: number;
+  malformed_plan_probability: number;
+}
+
+/** Typed application settings (`get_app_config` / `update_app_config`) */
+export interface AppConfig {
+  schema_version: number;
+  theme: 'dark' | 'light';
+  /** Also selects the catalog for backend text (chat replies, summaries, notifications): "zh…" or English */
+  language: string;
+  font_size: number;
+  working_directory: string | null;
+  active_workspace_id: string | null;
+  review_writes: boolean;
+  file_conflict_mode: 'warn' | 'serialize';
+  /** 0 disables the response cache */
+  response_cache_ttl_minutes: number;
+  confirmation_timeout_minutes: number;
+  /** What happens when a run's confirmation times out */
+  confirmation_timeout_action: 'confirm' | 'pause' | 'fail';
+  /** Keep an isolated run's scratch directory after it ends */
+  keep_run_scratch: boolean;
+  /** Task runs started from the intake queue at once; 0 means no limit */
+  max_concurrent_runs: number;
+  /** When every slot is taken and a run of a higher priority waits, pause
+   *  the lowest-priority active run at its next sequence group until a
+   *  slot is free again */
+  preempt_runs: boolean;
+  /** Let the control hub revise the rest of a run's plan when an assignment fails */
+  replan_on_failure: boolean;
+  /** A start repeating an active run's prompt within this many seconds returns that run; 0 turns it off */
+  duplicate_run_window_secs: number;
+  /** A hub plan with an assignment less confident than this (0-1) waits for approval; 0 never asks */
+  plan_approval_confidence: number;
+  /** Wind a run down after this many minutes: no new assignments, running agents wrap up, the summary is marked timed out; 0 sets no limit */
+  max_run_minutes: number;
+  /** What happens to runs left unfinished when the app last closed: resume them, hold them for the user, or fail them */
+  startup_recovery: 'auto_resume' | 'prompt' | 'mark_failed';
+  stuck_run_reminder: StuckRunReminderConfig;
+  memory_enabled: boolean;
+  /** Summarize a chat session after this many new messages; 0 only summarizes on request */
+  session_summary_every_messages: number;
+  /** Give agent processes a terminal whose prompts, e.g. of a login flow, the user can answer */
+  agent_terminal_attach: boolean;
+  /** Command that logs in an agent type (registry id or command name), over the built-in ones */
+  agent_login_commands: Record<string, string>;
+  /** Stop an agent's chat process after this many minutes without prompts; agents may override it, 0 keeps processes running */
+  agent_idle_shutdown_minutes: number;
+  /** Terms masked in shared run reports, besides credentials and the home directory */
+  report_redactions: string[];
+  redaction: RedactionConfig;
+  web_search: WebSearchConfig;
+  knowledge_context: KnowledgeContextConfig;
+  model_pricing_overrides: ModelPricing[];
+  /** Context window in tokens per model id, over the built-in table */
+  model_context_windows: Record<string, number>;
+  chat_tool_command_keywords: CommandKeywords;
+  chat_tool_spam_filter: SpamFilterConfig;
+  telemetry: TelemetryConfig;
+  database: DatabaseConfig;
+  chaos: ChaosConfig;
+  /** Disable commands that change anything, for demo or shared-dashboard machines */
+  observer_mode: boolean;
+}
+
+/** Whether mutating commands are disabled */
+export interface ObserverStatus {
+  active: boolean;
+  /** Launched with --observer; the setting cannot turn it off */
+  forced: boolean;
+}
+
+/** JSON merge patch: nested objects merge, `null` resets a field to its default */
+export type AppConfigPatch = {
+  [K in keyof AppConfig]?: AppConfig[K] extends object
+    ? Partial<AppConfig[K]> | AppConfig[K] | null
+    : AppConfig[K] | null;
+};
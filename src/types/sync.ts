@@ -0,0 +1,22 @@
+export type SyncTarget =
+  | { kind: 'directory'; path: string }
+  | { kind: 'relay'; url: string };
+
+export interface SyncConfig {
+  target: SyncTarget;
+  /** Empty when read back; leave empty on save to keep the stored passphrase */
+  passphrase: string;
+}
+
+export interface SyncReport {
+  device_id: string;
+  local_changes: number;
+  applied: number;
+  conflicts: number;
+  failed: number;
+  pushed: number;
+  peers: number;
+  /** Envelopes skipped because they could not be decrypted or parsed */
+  rejected: number;
+  synced_at: string;
+}
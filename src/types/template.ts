@@ -0,0 +1,55 @@
+import type { AssignmentOverrides } from './orchestration';
+
+export type AgentPin =
+  | { by: 'id'; agent_id: string }
+  | { by: 'tag'; tag: string };
+
+export interface TemplateAssignment {
+  agent: AgentPin;
+  source_agent_id: string;
+  /** May contain {{variable}} placeholders */
+  task_description: string;
+  sequence_order: number;
+  depends_on: string[];
+  matched_skills: string[];
+  working_directory?: string;
+  output_file?: string;
+  max_tokens_out?: number;
+  max_cost?: number;  // USD
+  overrides?: AssignmentOverrides | null;
+}
+
+export interface TemplateVariable {
+  name: string;
+  default_value: string;
+}
+
+export interface OrchestrationTemplate {
+  id: string;
+  name: string;
+  description: string;
+  source_task_run_id: string | null;
+  prompt_template: string;
+  analysis: string;
+  assignments_json: string;
+  variables_json: string;
+  workspace_id: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface PromoteRunOptions {
+  name?: string;
+  description?: string;
+  /** Pin agents by their first matched skill instead of by id */
+  pin_by_tag?: boolean;
+  /** Extra variables to extract: name -> value as it appears in the run */
+  variables?: Record<string, string>;
+}
+
+export interface RunTemplateRequest {
+  template_id: string;
+  variables?: Record<string, string>;
+  title?: string;
+  workspace_id?: string;
+}
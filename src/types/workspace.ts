@@ -3,10 +3,23 @@ export interface Workspace {
   name: string;
   icon: string;
   working_directory: string;
+  /** Tone, language or domain context the Control Hub is given in this workspace */
+  hub_persona: string | null;
+  /** Mask secrets in the agent outputs of the workspace */
+  redact_outputs: boolean;
+  /** Queue a run while another orchestration of the workspace is active */
+  exclusive_runs: boolean;
+  /** Workspace this sandbox was cloned from */
+  sandbox_of?: string | null;
+  /** How the sandbox's working directory was made from the original's */
+  sandbox_mode?: SandboxMode | null;
   created_at: string;
   updated_at: string;
 }
 
+/** A copy of every file, or a detached git worktree at the original's HEAD */
+export type SandboxMode = 'copy' | 'worktree';
+
 export interface CreateWorkspaceRequest {
   name: string;
   icon?: string;
@@ -18,4 +31,8 @@ export interface UpdateWorkspaceRequest {
   name?: string;
   icon?: string;
   working_directory?: string;
+  /** An empty persona clears it */
+  hub_persona?: string;
+  redact_outputs?: boolean;
+  exclusive_runs?: boolean;
 }